@@ -50,6 +50,7 @@ pub struct Name<'a> {
 pub enum SearchType {
     Artist,
     Album,
+    Track,
 }
 
 impl SearchType {
@@ -57,6 +58,7 @@ impl SearchType {
         match self {
             Self::Artist => "artist",
             Self::Album => "album",
+            Self::Track => "track",
         }
     }
 }
@@ -189,6 +191,13 @@ pub struct Playlist {
     pub images: Vec<Image>,
     pub tracks: Page<PlaylistTrack>,
     pub owner: PlaylistOwner,
+    #[serde(default)]
+    pub snapshot_id: String,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct PlaylistSnapshot {
+    pub snapshot_id: String,
 }
 
 #[derive(Deserialize, Debug, Clone)]
@@ -215,6 +224,43 @@ pub struct SavedTrack {
     pub track: TrackItem,
 }
 
+// Not a Page: the recently played tracks are paginated with cursors
+#[derive(Deserialize, Debug, Clone)]
+pub struct RecentlyPlayed {
+    pub items: Vec<PlayHistoryItem>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct PlayHistoryItem {
+    pub track: TrackItem,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct SavedShow {
+    pub show: Show,
+}
+
+// A podcast
+#[derive(Deserialize, Debug, Clone)]
+pub struct Show {
+    pub id: String,
+    pub name: String,
+    pub publisher: String,
+    pub images: Vec<Image>,
+}
+
+impl WithImages for Show {
+    fn images(&self) -> &[Image] {
+        &self.images[..]
+    }
+}
+
+// Episodes come newest first, and are null when not available in the user's market
+#[derive(Deserialize, Debug, Clone)]
+pub struct Episode {
+    pub id: String,
+}
+
 #[derive(Deserialize, Debug, Clone)]
 pub struct SavedAlbum {
     pub album: Album,
@@ -270,6 +316,13 @@ pub struct Artist {
     pub id: String,
     pub name: String,
     pub images: Option<Vec<Image>>,
+    // Only there for full artist objects
+    pub followers: Option<Followers>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Followers {
+    pub total: usize,
 }
 
 impl WithImages for Artist {
@@ -282,6 +335,23 @@ impl WithImages for Artist {
     }
 }
 
+// A page of the artists a user follows, which is paginated with cursors rather than offsets
+#[derive(Deserialize, Debug, Clone)]
+pub struct FollowedArtists {
+    pub artists: CursorPage<Artist>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct CursorPage<T> {
+    pub items: Vec<T>,
+    pub cursors: Option<Cursors>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Cursors {
+    pub after: Option<String>,
+}
+
 #[derive(Deserialize, Debug, Clone)]
 pub struct User {
     pub id: String,
@@ -368,6 +438,11 @@ pub struct TopTracks {
     pub tracks: Vec<TrackItem>,
 }
 
+#[derive(Deserialize, Debug, Clone)]
+pub struct Recommendations {
+    pub tracks: Vec<TrackItem>,
+}
+
 #[derive(Deserialize, Debug, Clone)]
 pub struct AlbumTrackItem {
     pub id: String,
@@ -376,6 +451,7 @@ pub struct AlbumTrackItem {
     pub name: String,
     pub duration_ms: i64,
     pub artists: Vec<Artist>,
+    pub preview_url: Option<String>,
 }
 
 #[derive(Deserialize, Debug, Clone)]
@@ -388,6 +464,23 @@ pub struct TrackItem {
 #[derive(Deserialize, Debug, Clone)]
 pub struct BadTrackItem {}
 
+#[derive(Deserialize, Debug, Clone)]
+pub struct ExternalIds {
+    pub isrc: Option<String>,
+}
+
+// Only what a track has on top of a TrackItem when it's fetched on its own
+#[derive(Deserialize, Debug, Clone)]
+pub struct FullTrack {
+    pub external_ids: Option<ExternalIds>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct FullTracks {
+    // Unknown ids come back as null
+    pub tracks: Vec<Option<FullTrack>>,
+}
+
 #[derive(Deserialize, Debug, Clone)]
 #[serde(untagged)]
 pub enum FailibleTrackItem {
@@ -408,6 +501,7 @@ impl FailibleTrackItem {
 pub struct RawSearchResults {
     pub albums: Option<Page<Album>>,
     pub artists: Option<Page<Artist>>,
+    pub tracks: Option<Page<TrackItem>>,
 }
 
 impl From<Artist> for ArtistSummary {
@@ -418,6 +512,24 @@ impl From<Artist> for ArtistSummary {
     }
 }
 
+impl From<Show> for ShowSummary {
+    fn from(show: Show) -> Self {
+        let art = show.best_image_for_width(200).map(|i| &i.url).cloned();
+        let Show {
+            id,
+            name,
+            publisher,
+            ..
+        } = show;
+        Self {
+            id,
+            title: name,
+            publisher,
+            art,
+        }
+    }
+}
+
 impl TryFrom<PlaylistTrack> for TrackItem {
     type Error = ();
 
@@ -462,6 +574,12 @@ impl From<TopTracks> for Vec<SongDescription> {
     }
 }
 
+impl From<Recommendations> for Vec<SongDescription> {
+    fn from(recommendations: Recommendations) -> Self {
+        Page::new(recommendations.tracks).into()
+    }
+}
+
 impl<T> From<Page<T>> for Vec<SongDescription>
 where
     T: TryInto<TrackItem>,
@@ -494,51 +612,60 @@ where
         let songs = page
             .into_iter()
             .filter_map(|t| {
-                let TrackItem { track, album } = t.try_into().ok()?;
-                let AlbumTrackItem {
-                    artists,
-                    id,
-                    uri,
-                    name,
-                    duration_ms,
-                    track_number,
-                } = track;
-                let artists = artists
-                    .into_iter()
-                    .map(|a| ArtistRef {
-                        id: a.id,
-                        name: a.name,
-                    })
-                    .collect::<Vec<ArtistRef>>();
-
-                let art = album.best_image_for_width(200).map(|i| &i.url).cloned();
-                let Album {
-                    id: album_id,
-                    name: album_name,
-                    ..
-                } = album;
-
-                let album_ref = AlbumRef {
-                    id: album_id,
-                    name: album_name,
-                };
-
-                Some(SongDescription {
-                    id,
-                    track_number: track_number.map(|u| u as u32),
-                    uri,
-                    title: name,
-                    artists,
-                    album: album_ref,
-                    duration: duration_ms as u32,
-                    art,
-                })
+                let track: TrackItem = t.try_into().ok()?;
+                Some(track.into())
             })
             .collect();
         SongBatch { songs, batch }
     }
 }
 
+impl From<TrackItem> for SongDescription {
+    fn from(track: TrackItem) -> Self {
+        let TrackItem { track, album } = track;
+        let AlbumTrackItem {
+            artists,
+            id,
+            uri,
+            name,
+            duration_ms,
+            track_number,
+            preview_url,
+        } = track;
+        let artists = artists
+            .into_iter()
+            .map(|a| ArtistRef {
+                id: a.id,
+                name: a.name,
+            })
+            .collect::<Vec<ArtistRef>>();
+
+        let art = album.best_image_for_width(200).map(|i| &i.url).cloned();
+        let Album {
+            id: album_id,
+            name: album_name,
+            ..
+        } = album;
+
+        let album_ref = AlbumRef {
+            id: album_id,
+            name: album_name,
+        };
+
+        SongDescription {
+            id,
+            track_number: track_number.map(|u| u as u32),
+            uri,
+            title: name,
+            artists,
+            album: album_ref,
+            duration: duration_ms as u32,
+            art,
+            preview_url,
+        }
+    }
+}
+
 impl TryFrom<Album> for SongBatch {
     type Error = ();
 
@@ -617,18 +744,26 @@ impl From<Playlist> for PlaylistDescription {
             name,
             tracks,
             owner,
+            snapshot_id,
             ..
         } = playlist;
         let PlaylistOwner {
             id: owner_id,
             display_name,
         } = owner;
-        let song_batch = tracks.into();
+        let song_batch: SongBatch = tracks.into();
+        let mosaic = if art.is_none() {
+            mosaic_arts(&song_batch.songs)
+        } else {
+            vec![]
+        };
         PlaylistDescription {
             id,
             title: name,
             art,
             songs: song_batch,
+            snapshot_id,
+            mosaic,
             owner: UserRef {
                 id: owner_id,
                 display_name,
@@ -1,10 +1,11 @@
-use futures::future::BoxFuture;
+use futures::future::{join_all, BoxFuture};
 use futures::{join, FutureExt};
 use regex::Regex;
 use serde::de::DeserializeOwned;
 use serde_json::from_slice;
 use std::convert::Into;
 use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
 
 use super::cache::{CacheExpiry, CacheManager, CachePolicy, FetchResult};
 use super::client::*;
@@ -26,6 +27,11 @@ pub trait SpotifyApiClient {
 
     fn get_playlist(&self, id: &str) -> BoxFuture<SpotifyResult<PlaylistDescription>>;
 
+    fn get_playlist_snapshot_id(&self, id: &str) -> BoxFuture<SpotifyResult<String>>;
+
+    // Same as get_playlist, but bypasses any cached version of the playlist
+    fn refresh_playlist(&self, id: &str) -> BoxFuture<SpotifyResult<PlaylistDescription>>;
+
     fn get_playlist_tracks(
         &self,
         id: &str,
@@ -45,6 +51,9 @@ pub trait SpotifyApiClient {
 
     fn save_tracks(&self, ids: Vec<String>) -> BoxFuture<SpotifyResult<()>>;
 
+    // Whether each track is in the user's Liked Songs, in the same order as the ids
+    fn check_saved_tracks(&self, ids: Vec<String>) -> BoxFuture<SpotifyResult<Vec<bool>>>;
+
     fn remove_saved_album(&self, id: &str) -> BoxFuture<SpotifyResult<()>>;
 
     fn remove_saved_tracks(&self, ids: Vec<String>) -> BoxFuture<SpotifyResult<()>>;
@@ -67,6 +76,10 @@ pub trait SpotifyApiClient {
 
     fn update_playlist_details(&self, id: &str, name: String) -> BoxFuture<SpotifyResult<()>>;
 
+    fn follow_playlist(&self, id: &str) -> BoxFuture<SpotifyResult<PlaylistDescription>>;
+
+    fn unfollow_playlist(&self, id: &str) -> BoxFuture<SpotifyResult<()>>;
+
     fn search(
         &self,
         query: &str,
@@ -74,6 +87,13 @@ pub trait SpotifyApiClient {
         limit: usize,
     ) -> BoxFuture<SpotifyResult<SearchResults>>;
 
+    // Tracks only (search above is for albums and artists), best matches first
+    fn search_tracks(
+        &self,
+        query: &str,
+        limit: usize,
+    ) -> BoxFuture<SpotifyResult<Vec<SongDescription>>>;
+
     fn get_artist_albums(
         &self,
         id: &str,
@@ -94,8 +114,36 @@ pub trait SpotifyApiClient {
 
     fn get_player_queue(&self) -> BoxFuture<SpotifyResult<Vec<SongDescription>>>;
 
+    fn get_track(&self, id: &str) -> BoxFuture<SpotifyResult<SongDescription>>;
+
+    // The ISRC of each track (International Standard Recording Code), if Spotify knows it,
+    // in the same order as the ids
+    fn get_tracks_isrc(&self, ids: Vec<String>) -> BoxFuture<SpotifyResult<Vec<Option<String>>>>;
+
+    // All the artists the user follows
+    fn get_followed_artists(&self) -> BoxFuture<SpotifyResult<Vec<ArtistSummary>>>;
+
+    // Ids of the tracks the user played last, the most recent first (at most 50)
+    fn get_recently_played(&self, limit: usize) -> BoxFuture<SpotifyResult<Vec<String>>>;
+
+    // All the shows (podcasts) the user follows
+    fn get_saved_shows(&self) -> BoxFuture<SpotifyResult<Vec<ShowSummary>>>;
+
+    // Ids of the latest episodes of a show, the most recent first (at most 50)
+    fn get_show_episodes(&self, id: &str, limit: usize) -> BoxFuture<SpotifyResult<Vec<String>>>;
+
+    // Tracks similar to the given ones (at most 5 seeds are accepted)
+    fn get_recommendations(
+        &self,
+        seed_tracks: Vec<String>,
+        limit: usize,
+    ) -> BoxFuture<SpotifyResult<Vec<SongDescription>>>;
+
     fn update_token(&self, token: String);
 
+    // While offline, whatever is cached is served as is, however old
+    fn set_offline(&self, offline: bool);
+
     fn player_pause(&self, device_id: String) -> BoxFuture<SpotifyResult<()>>;
 
     fn player_resume(&self, device_id: String) -> BoxFuture<SpotifyResult<()>>;
@@ -171,20 +219,29 @@ impl<'a> SpotCacheKey<'a> {
     }
 }
 
+// How many songs to look at to find the covers of a playlist mosaic
+const MOSAIC_TRACKS: usize = 20;
+// The most Spotify allows
+const FOLLOWED_ARTISTS_PAGE_SIZE: usize = 50;
+const SAVED_SHOWS_PAGE_SIZE: usize = 50;
+
 lazy_static! {
     pub static ref ME_TRACKS_CACHE: Regex = Regex::new(r"^me_tracks_\w+_\w+\.json$").unwrap();
     pub static ref ME_ALBUMS_CACHE: Regex = Regex::new(r"^me_albums_\w+_\w+\.json$").unwrap();
+    pub static ref ME_PLAYLISTS_CACHE: Regex = Regex::new(r"^me_playlists_\w+_\w+\.json$").unwrap();
     pub static ref USER_CACHE: Regex =
         Regex::new(r"^me_(albums|playlists|tracks)_\w+_\w+\.json$").unwrap();
 }
 
+// The playlist itself and all the pages of its tracks
 fn playlist_cache_key(id: &str) -> Regex {
-    Regex::new(&format!(r"^playlist(_{id}|item_{id}_\w+_\w+)\.json$")).unwrap()
+    Regex::new(&format!(r"^playlist_({id}|item_{id}_\w+_\w+)\.json$")).unwrap()
 }
 
 pub struct CachedSpotifyClient {
     client: SpotifyClient,
     cache: CacheManager,
+    offline: AtomicBool,
 }
 
 impl CachedSpotifyClient {
@@ -192,11 +249,12 @@ impl CachedSpotifyClient {
         CachedSpotifyClient {
             client: SpotifyClient::new(),
             cache: CacheManager::for_dir("spot/net").unwrap(),
+            offline: AtomicBool::new(false),
         }
     }
 
     fn default_cache_policy(&self) -> CachePolicy {
-        if self.client.has_token() {
+        if self.client.has_token() && !self.offline.load(Ordering::Relaxed) {
             CachePolicy::Default
         } else {
             CachePolicy::IgnoreExpiry
@@ -264,6 +322,23 @@ impl CachedSpotifyClient {
             }
         }
     }
+
+    // Playlists without an image get a mosaic of the covers of their first songs instead
+    async fn with_mosaics(&self, playlists: Vec<PlaylistDescription>) -> Vec<PlaylistDescription> {
+        join_all(playlists.into_iter().map(|mut playlist| async move {
+            if playlist.art.is_none() && playlist.mosaic.is_empty() {
+                match self
+                    .get_playlist_tracks(&playlist.id, 0, MOSAIC_TRACKS)
+                    .await
+                {
+                    Ok(songs) => playlist.mosaic = mosaic_arts(&songs.songs),
+                    Err(e) => warn!("Could not get covers for playlist {}: {}", playlist.id, e),
+                }
+            }
+            playlist
+        }))
+        .await
+    }
 }
 
 impl SpotifyApiClient for CachedSpotifyClient {
@@ -271,6 +346,10 @@ impl SpotifyApiClient for CachedSpotifyClient {
         self.client.update_token(new_token)
     }
 
+    fn set_offline(&self, offline: bool) {
+        self.offline.store(offline, Ordering::Relaxed)
+    }
+
     fn get_saved_albums(
         &self,
         offset: usize,
@@ -330,7 +409,7 @@ impl SpotifyApiClient for CachedSpotifyClient {
                 .map(|playlist| playlist.into())
                 .collect::<Vec<PlaylistDescription>>();
 
-            Ok(albums)
+            Ok(self.with_mosaics(albums).await)
         })
     }
 
@@ -444,14 +523,57 @@ impl SpotifyApiClient for CachedSpotifyClient {
         })
     }
 
+    fn follow_playlist(&self, id: &str) -> BoxFuture<SpotifyResult<PlaylistDescription>> {
+        let id = id.to_owned();
+
+        Box::pin(async move {
+            let _ = self.cache.set_expired_pattern(&ME_PLAYLISTS_CACHE).await;
+            self.client.follow_playlist(&id).send_no_response().await?;
+            self.get_playlist(&id).await
+        })
+    }
+
+    fn unfollow_playlist(&self, id: &str) -> BoxFuture<SpotifyResult<()>> {
+        let id = id.to_owned();
+
+        Box::pin(async move {
+            let _ = self.cache.set_expired_pattern(&ME_PLAYLISTS_CACHE).await;
+            self.client.unfollow_playlist(&id).send_no_response().await
+        })
+    }
+
     fn save_tracks(&self, ids: Vec<String>) -> BoxFuture<SpotifyResult<()>> {
         Box::pin(async move {
             let _ = self.cache.set_expired_pattern(&ME_TRACKS_CACHE).await;
-            self.client.save_tracks(ids).send_no_response().await?;
+            // The API won't save more than 50 tracks at once
+            for chunk in ids.chunks(50) {
+                self.client
+                    .save_tracks(chunk.to_vec())
+                    .send_no_response()
+                    .await?;
+            }
             Ok(())
         })
     }
 
+    fn check_saved_tracks(&self, ids: Vec<String>) -> BoxFuture<SpotifyResult<Vec<bool>>> {
+        Box::pin(async move {
+            let mut saved = Vec::with_capacity(ids.len());
+            // The API won't check more than 50 tracks at once
+            for chunk in ids.chunks(50) {
+                let mut chunk_saved = self
+                    .client
+                    .check_saved_tracks(chunk)
+                    .send()
+                    .await?
+                    .deserialize()
+                    .ok_or(SpotifyApiError::NoContent)?;
+                saved.append(&mut chunk_saved);
+            }
+            Ok(saved)
+        })
+    }
+
     fn remove_saved_album(&self, id: &str) -> BoxFuture<SpotifyResult<()>> {
         let id = id.to_owned();
 
@@ -464,10 +586,14 @@ impl SpotifyApiClient for CachedSpotifyClient {
     fn remove_saved_tracks(&self, ids: Vec<String>) -> BoxFuture<SpotifyResult<()>> {
         Box::pin(async move {
             let _ = self.cache.set_expired_pattern(&ME_TRACKS_CACHE).await;
-            self.client
-                .remove_saved_tracks(ids)
-                .send_no_response()
-                .await
+            // Same limit as when saving tracks
+            for chunk in ids.chunks(50) {
+                self.client
+                    .remove_saved_tracks(chunk.to_vec())
+                    .send_no_response()
+                    .await?;
+            }
+            Ok(())
         })
     }
 
@@ -516,6 +642,35 @@ impl SpotifyApiClient for CachedSpotifyClient {
         })
     }
 
+    fn get_playlist_snapshot_id(&self, id: &str) -> BoxFuture<SpotifyResult<String>> {
+        let id = id.to_owned();
+
+        Box::pin(async move {
+            let snapshot = self
+                .client
+                .get_playlist_snapshot_id(&id)
+                .send()
+                .await?
+                .deserialize()
+                .ok_or(SpotifyApiError::NoContent)?;
+
+            Ok(snapshot.snapshot_id)
+        })
+    }
+
+    fn refresh_playlist(&self, id: &str) -> BoxFuture<SpotifyResult<PlaylistDescription>> {
+        let id = id.to_owned();
+
+        Box::pin(async move {
+            self.cache
+                .set_expired_pattern(&playlist_cache_key(&id))
+                .await
+                .unwrap_or(());
+
+            self.get_playlist(&id).await
+        })
+    }
+
     fn get_playlist_tracks(
         &self,
         id: &str,
@@ -594,6 +749,7 @@ impl SpotifyApiClient for CachedSpotifyClient {
             let result = ArtistDescription {
                 id: artist.id,
                 name: artist.name,
+                followers: artist.followers.map(|f| f.total),
                 albums: albums?,
                 top_tracks: top_tracks?.into(),
             };
@@ -636,6 +792,25 @@ impl SpotifyApiClient for CachedSpotifyClient {
         })
     }
 
+    fn search_tracks(
+        &self,
+        query: &str,
+        limit: usize,
+    ) -> BoxFuture<SpotifyResult<Vec<SongDescription>>> {
+        let query = query.to_owned();
+
+        Box::pin(async move {
+            let results = self
+                .client
+                .search_tracks(query, limit)
+                .send()
+                .await?
+                .deserialize()
+                .ok_or(SpotifyApiError::NoContent)?;
+            Ok(results.tracks.map(|page| page.into()).unwrap_or_default())
+        })
+    }
+
     fn get_user_playlists(
         &self,
         id: &str,
@@ -663,7 +838,7 @@ impl SpotifyApiClient for CachedSpotifyClient {
                 .map(|a| a.into())
                 .collect::<Vec<PlaylistDescription>>();
 
-            Ok(playlists)
+            Ok(self.with_mosaics(playlists).await)
         })
     }
 
@@ -723,6 +898,142 @@ impl SpotifyApiClient for CachedSpotifyClient {
         })
     }
 
+    fn get_track(&self, id: &str) -> BoxFuture<SpotifyResult<SongDescription>> {
+        let id = id.to_owned();
+
+        Box::pin(async move {
+            let track = self
+                .client
+                .get_track(&id)
+                .send()
+                .await?
+                .deserialize()
+                .ok_or(SpotifyApiError::NoContent)?;
+            Ok(track.into())
+        })
+    }
+
+    fn get_tracks_isrc(&self, ids: Vec<String>) -> BoxFuture<SpotifyResult<Vec<Option<String>>>> {
+        Box::pin(async move {
+            let mut isrcs = Vec::with_capacity(ids.len());
+            // The API won't return more than 50 tracks at once
+            for chunk in ids.chunks(50) {
+                let tracks = self
+                    .client
+                    .get_tracks(chunk)
+                    .send()
+                    .await?
+                    .deserialize()
+                    .ok_or(SpotifyApiError::NoContent)?;
+                isrcs.extend(
+                    tracks
+                        .tracks
+                        .into_iter()
+                        .map(|track| track.and_then(|t| t.external_ids).and_then(|ids| ids.isrc)),
+                );
+            }
+            Ok(isrcs)
+        })
+    }
+
+    fn get_followed_artists(&self) -> BoxFuture<SpotifyResult<Vec<ArtistSummary>>> {
+        Box::pin(async move {
+            let mut artists = vec![];
+            let mut after: Option<String> = None;
+            loop {
+                let page = self
+                    .client
+                    .get_followed_artists(after.as_deref(), FOLLOWED_ARTISTS_PAGE_SIZE)
+                    .send()
+                    .await?
+                    .deserialize()
+                    .ok_or(SpotifyApiError::NoContent)?
+                    .artists;
+                artists.extend(page.items.into_iter().map(ArtistSummary::from));
+                after = page.cursors.and_then(|c| c.after);
+                if after.is_none() {
+                    break;
+                }
+            }
+            Ok(artists)
+        })
+    }
+
+    fn get_recently_played(&self, limit: usize) -> BoxFuture<SpotifyResult<Vec<String>>> {
+        Box::pin(async move {
+            let recently_played = self
+                .client
+                .get_recently_played(limit)
+                .send()
+                .await?
+                .deserialize()
+                .ok_or(SpotifyApiError::NoContent)?;
+            Ok(recently_played
+                .items
+                .into_iter()
+                .map(|item| item.track.track.id)
+                .collect())
+        })
+    }
+
+    fn get_saved_shows(&self) -> BoxFuture<SpotifyResult<Vec<ShowSummary>>> {
+        Box::pin(async move {
+            let mut shows = vec![];
+            loop {
+                let page = self
+                    .client
+                    .get_saved_shows(shows.len(), SAVED_SHOWS_PAGE_SIZE)
+                    .send()
+                    .await?
+                    .deserialize()
+                    .ok_or(SpotifyApiError::NoContent)?;
+                let total = page.total();
+                let count = shows.len();
+                shows.extend(page.into_iter().map(|saved| ShowSummary::from(saved.show)));
+                if shows.len() >= total || shows.len() == count {
+                    break;
+                }
+            }
+            Ok(shows)
+        })
+    }
+
+    fn get_show_episodes(&self, id: &str, limit: usize) -> BoxFuture<SpotifyResult<Vec<String>>> {
+        let id = id.to_owned();
+
+        Box::pin(async move {
+            let episodes = self
+                .client
+                .get_show_episodes(&id, limit)
+                .send()
+                .await?
+                .deserialize()
+                .ok_or(SpotifyApiError::NoContent)?;
+            Ok(episodes
+                .into_iter()
+                .flatten()
+                .map(|episode| episode.id)
+                .collect())
+        })
+    }
+
+    fn get_recommendations(
+        &self,
+        seed_tracks: Vec<String>,
+        limit: usize,
+    ) -> BoxFuture<SpotifyResult<Vec<SongDescription>>> {
+        Box::pin(async move {
+            let recommendations = self
+                .client
+                .get_recommendations(&seed_tracks, limit)
+                .send()
+                .await?
+                .deserialize()
+                .ok_or(SpotifyApiError::NoContent)?;
+            Ok(recommendations.into())
+        })
+    }
+
     fn player_pause(&self, device_id: String) -> BoxFuture<SpotifyResult<()>> {
         Box::pin(self.client.player_pause(&device_id).send_no_response())
     }
@@ -829,8 +1140,18 @@ impl SpotifyApiClient for CachedSpotifyClient {
 #[cfg(test)]
 pub mod tests {
 
+    use super::{playlist_cache_key, SpotCacheKey};
     use crate::api::api_models::*;
 
+    #[test]
+    fn test_playlist_cache_key() {
+        let key = playlist_cache_key("abc");
+        assert!(key.is_match(&SpotCacheKey::Playlist("abc").into_raw()));
+        assert!(key.is_match(&SpotCacheKey::PlaylistTracks("abc", 100, 50).into_raw()));
+        assert!(!key.is_match(&SpotCacheKey::PlaylistTracks("abcd", 0, 50).into_raw()));
+        assert!(!key.is_match(&SpotCacheKey::Album("abc").into_raw()));
+    }
+
     #[test]
     fn test_search_query() {
         let query = SearchQuery {
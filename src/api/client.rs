@@ -1,7 +1,7 @@
 use form_urlencoded::Serializer;
 use isahc::config::Configurable;
 use isahc::http::{method::Method, request::Builder, StatusCode, Uri};
-use isahc::{AsyncReadResponseExt, HttpClient, Request};
+use isahc::{HttpClient, Request};
 use percent_encoding::{utf8_percent_encode, AsciiSet, CONTROLS};
 use serde::{de::Deserialize, Serialize};
 use serde_json::from_str;
@@ -13,6 +13,7 @@ use thiserror::Error;
 
 pub use super::api_models::*;
 use super::cache::CacheError;
+use super::scheduler::RequestScheduler;
 
 const SPOTIFY_HOST: &str = "api.spotify.com";
 
@@ -41,7 +42,7 @@ pub(crate) struct SpotifyRequest<'a, Body, Response> {
 
 impl<'a, B, R> SpotifyRequest<'a, B, R>
 where
-    B: Into<isahc::AsyncBody>,
+    B: Into<isahc::AsyncBody> + Clone + Send + Sync + 'static,
 {
     fn method(mut self, method: Method) -> Self {
         self.request = self.request.method(method);
@@ -173,6 +174,7 @@ pub enum SpotifyApiError {
 pub(crate) struct SpotifyClient {
     token: Mutex<Option<String>>,
     client: HttpClient,
+    scheduler: RequestScheduler,
 }
 
 impl SpotifyClient {
@@ -185,6 +187,7 @@ impl SpotifyClient {
         Self {
             token: Mutex::new(None),
             client,
+            scheduler: RequestScheduler::new(),
         }
     }
 
@@ -226,28 +229,21 @@ impl SpotifyClient {
         request: Request<B>,
     ) -> Result<SpotifyResponse<T>, SpotifyApiError>
     where
-        B: Into<isahc::AsyncBody>,
+        B: Into<isahc::AsyncBody> + Clone + Send + Sync + 'static,
     {
-        let mut result = self.client.send_async(request).await?;
-
-        let etag = result
-            .headers()
-            .get("etag")
-            .and_then(|header| header.to_str().ok())
-            .map(|s| s.to_owned());
+        let result = self.scheduler.send(&self.client, request).await?;
 
         let cache_control = result
-            .headers()
-            .get("cache-control")
-            .and_then(|header| header.to_str().ok())
+            .cache_control
+            .as_deref()
             .and_then(Self::parse_cache_control);
 
-        match result.status() {
+        match result.status {
             StatusCode::NO_CONTENT => Err(SpotifyApiError::NoContent),
             s if s.is_success() => Ok(SpotifyResponse {
-                kind: SpotifyResponseKind::Ok(result.text().await?, PhantomData),
+                kind: SpotifyResponseKind::Ok(result.body.to_string(), PhantomData),
                 max_age: cache_control.unwrap_or(10),
-                etag,
+                etag: result.etag,
             }),
             StatusCode::UNAUTHORIZED => {
                 self.clear_token();
@@ -257,24 +253,21 @@ impl SpotifyClient {
             StatusCode::NOT_MODIFIED => Ok(SpotifyResponse {
                 kind: SpotifyResponseKind::NotModified,
                 max_age: cache_control.unwrap_or(10),
-                etag,
+                etag: result.etag,
             }),
             s => Err(SpotifyApiError::BadStatus(
                 s.as_u16(),
-                result
-                    .text()
-                    .await
-                    .unwrap_or_else(|_| "(no details available)".to_string()),
+                result.body.to_string(),
             )),
         }
     }
 
     async fn send_req_no_response<B>(&self, request: Request<B>) -> Result<(), SpotifyApiError>
     where
-        B: Into<isahc::AsyncBody>,
+        B: Into<isahc::AsyncBody> + Clone + Send + Sync + 'static,
     {
-        let mut result = self.client.send_async(request).await?;
-        match result.status() {
+        let result = self.scheduler.send(&self.client, request).await?;
+        match result.status {
             StatusCode::UNAUTHORIZED => {
                 self.clear_token();
                 Err(SpotifyApiError::InvalidToken)
@@ -284,10 +277,7 @@ impl SpotifyClient {
             s if s.is_success() => Ok(()),
             s => Err(SpotifyApiError::BadStatus(
                 s.as_u16(),
-                result
-                    .text()
-                    .await
-                    .unwrap_or_else(|_| "(no details available)".to_string()),
+                result.body.to_string(),
             )),
         }
     }
@@ -328,6 +318,22 @@ impl SpotifyClient {
             .uri(format!("/v1/artists/{id}/top-tracks"), Some(&query))
     }
 
+    pub(crate) fn get_recommendations(
+        &self,
+        seed_tracks: &[String],
+        limit: usize,
+    ) -> SpotifyRequest<'_, (), Recommendations> {
+        let query = make_query_params()
+            .append_pair("seed_tracks", &seed_tracks.join(","))
+            .append_pair("limit", &limit.to_string()[..])
+            .append_pair("market", "from_token")
+            .finish();
+
+        self.request()
+            .method(Method::GET)
+            .uri("/v1/recommendations".to_string(), Some(&query))
+    }
+
     pub(crate) fn is_album_saved(&self, id: &str) -> SpotifyRequest<'_, (), Vec<bool>> {
         let query = make_query_params().append_pair("ids", id).finish();
         self.request()
@@ -335,6 +341,15 @@ impl SpotifyClient {
             .uri("/v1/me/albums/contains".to_string(), Some(&query))
     }
 
+    pub(crate) fn check_saved_tracks(&self, ids: &[String]) -> SpotifyRequest<'_, (), Vec<bool>> {
+        let query = make_query_params()
+            .append_pair("ids", &ids.join(","))
+            .finish();
+        self.request()
+            .method(Method::GET)
+            .uri("/v1/me/tracks/contains".to_string(), Some(&query))
+    }
+
     pub(crate) fn save_album(&self, id: &str) -> SpotifyRequest<'_, (), ()> {
         let query = make_query_params().append_pair("ids", id).finish();
         self.request()
@@ -389,7 +404,7 @@ impl SpotifyClient {
         let query = make_query_params()
             .append_pair(
                 "fields",
-                "id,name,images,owner,tracks(total,items(is_local,track(name,id,uri,duration_ms,artists(name,id),album(name,id,images,artists))))",
+                "id,name,images,owner,snapshot_id,tracks(total,items(is_local,track(name,id,uri,duration_ms,artists(name,id),album(name,id,images,artists))))",
             )
             .finish();
         self.request()
@@ -397,6 +412,18 @@ impl SpotifyClient {
             .uri(format!("/v1/playlists/{id}"), Some(&query))
     }
 
+    pub(crate) fn get_playlist_snapshot_id(
+        &self,
+        id: &str,
+    ) -> SpotifyRequest<'_, (), PlaylistSnapshot> {
+        let query = make_query_params()
+            .append_pair("fields", "snapshot_id")
+            .finish();
+        self.request()
+            .method(Method::GET)
+            .uri(format!("/v1/playlists/{id}"), Some(&query))
+    }
+
     pub(crate) fn get_playlist_tracks(
         &self,
         id: &str,
@@ -457,6 +484,18 @@ impl SpotifyClient {
             .json_body(PlaylistDetails { name })
     }
 
+    pub(crate) fn follow_playlist(&self, id: &str) -> SpotifyRequest<'_, (), ()> {
+        self.request()
+            .method(Method::PUT)
+            .uri(format!("/v1/playlists/{id}/followers"), None)
+    }
+
+    pub(crate) fn unfollow_playlist(&self, id: &str) -> SpotifyRequest<'_, (), ()> {
+        self.request()
+            .method(Method::DELETE)
+            .uri(format!("/v1/playlists/{id}/followers"), None)
+    }
+
     pub(crate) fn get_saved_albums(
         &self,
         offset: usize,
@@ -472,6 +511,37 @@ impl SpotifyClient {
             .uri("/v1/me/albums".to_string(), Some(&query))
     }
 
+    pub(crate) fn get_followed_artists(
+        &self,
+        after: Option<&str>,
+        limit: usize,
+    ) -> SpotifyRequest<'_, (), FollowedArtists> {
+        let mut query = make_query_params();
+        query
+            .append_pair("type", "artist")
+            .append_pair("limit", &limit.to_string()[..]);
+        if let Some(after) = after {
+            query.append_pair("after", after);
+        }
+
+        self.request()
+            .method(Method::GET)
+            .uri("/v1/me/following".to_string(), Some(&query.finish()))
+    }
+
+    pub(crate) fn get_recently_played(
+        &self,
+        limit: usize,
+    ) -> SpotifyRequest<'_, (), RecentlyPlayed> {
+        let query = make_query_params()
+            .append_pair("limit", &limit.to_string()[..])
+            .finish();
+
+        self.request()
+            .method(Method::GET)
+            .uri("/v1/me/player/recently-played".to_string(), Some(&query))
+    }
+
     pub(crate) fn get_saved_tracks(
         &self,
         offset: usize,
@@ -487,6 +557,36 @@ impl SpotifyClient {
             .uri("/v1/me/tracks".to_string(), Some(&query))
     }
 
+    pub(crate) fn get_saved_shows(
+        &self,
+        offset: usize,
+        limit: usize,
+    ) -> SpotifyRequest<'_, (), Page<SavedShow>> {
+        let query = make_query_params()
+            .append_pair("offset", &offset.to_string()[..])
+            .append_pair("limit", &limit.to_string()[..])
+            .finish();
+
+        self.request()
+            .method(Method::GET)
+            .uri("/v1/me/shows".to_string(), Some(&query))
+    }
+
+    pub(crate) fn get_show_episodes(
+        &self,
+        id: &str,
+        limit: usize,
+    ) -> SpotifyRequest<'_, (), Page<Option<Episode>>> {
+        let query = make_query_params()
+            .append_pair("market", "from_token")
+            .append_pair("limit", &limit.to_string()[..])
+            .finish();
+
+        self.request()
+            .method(Method::GET)
+            .uri(format!("/v1/shows/{id}/episodes"), Some(&query))
+    }
+
     pub(crate) fn get_saved_playlists(
         &self,
         offset: usize,
@@ -520,6 +620,23 @@ impl SpotifyClient {
             .uri("/v1/search".to_string(), Some(&query.into_query_string()))
     }
 
+    pub(crate) fn search_tracks(
+        &self,
+        query: String,
+        limit: usize,
+    ) -> SpotifyRequest<'_, (), RawSearchResults> {
+        let query = SearchQuery {
+            query,
+            types: vec![SearchType::Track],
+            limit,
+            offset: 0,
+        };
+
+        self.request()
+            .method(Method::GET)
+            .uri("/v1/search".to_string(), Some(&query.into_query_string()))
+    }
+
     pub(crate) fn get_user(&self, id: &str) -> SpotifyRequest<'_, (), User> {
         let id = utf8_percent_encode(id, PATH_ENCODE_SET);
         self.request()
@@ -550,6 +667,22 @@ impl SpotifyClient {
             .uri("/v1/me/player/devices".to_string(), None)
     }
 
+    pub(crate) fn get_track(&self, id: &str) -> SpotifyRequest<'_, (), TrackItem> {
+        self.request()
+            .method(Method::GET)
+            .uri(format!("/v1/tracks/{id}"), None)
+    }
+
+    pub(crate) fn get_tracks(&self, ids: &[String]) -> SpotifyRequest<'_, (), FullTracks> {
+        let query = make_query_params()
+            .append_pair("ids", &ids.join(","))
+            .finish();
+
+        self.request()
+            .method(Method::GET)
+            .uri("/v1/tracks".to_string(), Some(&query))
+    }
+
     pub(crate) fn get_player_queue(&self) -> SpotifyRequest<'_, (), PlayerQueue> {
         self.request()
             .method(Method::GET)
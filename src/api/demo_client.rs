@@ -0,0 +1,710 @@
+use futures::future::{self, BoxFuture};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::{Mutex, MutexGuard};
+
+use super::cached_client::{SpotifyApiClient, SpotifyResult};
+use super::client::SpotifyApiError;
+use crate::app::models::*;
+
+// The user logged in as in demo mode
+pub const DEMO_USER: &str = "demo";
+
+#[derive(Clone, Deserialize)]
+struct DemoUser {
+    id: String,
+    name: String,
+}
+
+#[derive(Clone, Deserialize)]
+struct DemoArtist {
+    id: String,
+    name: String,
+    followers: usize,
+}
+
+#[derive(Clone, Deserialize)]
+struct DemoTrack {
+    title: String,
+    // In seconds
+    duration: u32,
+}
+
+#[derive(Clone, Deserialize)]
+struct DemoAlbum {
+    // Tracks are identified by the id of their album followed by their number on two digits
+    id: String,
+    title: String,
+    artist: String,
+    release_date: String,
+    label: String,
+    tracks: Vec<DemoTrack>,
+}
+
+#[derive(Clone, Deserialize)]
+struct DemoPlaylist {
+    id: String,
+    title: String,
+    owner: String,
+    tracks: Vec<String>,
+    // Bumped on every change, for the snapshot id
+    #[serde(default)]
+    version: u32,
+}
+
+// What the demo user has in their library, all made up. It lives in memory: changes (saving tracks,
+// editing playlists...) work, but are forgotten once Spot quits.
+#[derive(Clone, Deserialize)]
+struct DemoLibrary {
+    users: Vec<DemoUser>,
+    artists: Vec<DemoArtist>,
+    albums: Vec<DemoAlbum>,
+    playlists: Vec<DemoPlaylist>,
+    saved_albums: Vec<String>,
+    saved_tracks: Vec<String>,
+    saved_playlists: Vec<String>,
+}
+
+fn page<T: Clone>(items: &[T], offset: usize, limit: usize) -> Vec<T> {
+    items.iter().skip(offset).take(limit).cloned().collect()
+}
+
+fn matches(text: &str, query: &str) -> bool {
+    text.to_lowercase().contains(&query.to_lowercase())
+}
+
+impl DemoLibrary {
+    fn bundled() -> Self {
+        serde_json::from_str(include_str!("demo_library.json")).expect("Invalid demo library")
+    }
+
+    fn user(&self, id: &str) -> Option<&DemoUser> {
+        self.users.iter().find(|u| u.id == id)
+    }
+
+    fn artist(&self, id: &str) -> Option<&DemoArtist> {
+        self.artists.iter().find(|a| a.id == id)
+    }
+
+    fn album(&self, id: &str) -> Option<&DemoAlbum> {
+        self.albums.iter().find(|a| a.id == id)
+    }
+
+    fn playlist(&self, id: &str) -> Option<&DemoPlaylist> {
+        self.playlists.iter().find(|p| p.id == id)
+    }
+
+    fn playlist_mut(&mut self, id: &str) -> SpotifyResult<&mut DemoPlaylist> {
+        let playlist = self.playlists.iter_mut().find(|p| p.id == id);
+        playlist.ok_or(SpotifyApiError::NoContent)
+    }
+
+    fn artist_ref(&self, id: &str) -> ArtistRef {
+        ArtistRef {
+            id: id.to_string(),
+            name: self.artist(id).map(|a| a.name.clone()).unwrap_or_default(),
+        }
+    }
+
+    fn album_songs(&self, album: &DemoAlbum) -> Vec<SongDescription> {
+        album
+            .tracks
+            .iter()
+            .enumerate()
+            .map(|(i, track)| {
+                let id = format!("{}{:02}", album.id, i + 1);
+                SongDescription {
+                    uri: format!("spotify:track:{}", id),
+                    id,
+                    track_number: Some(i as u32 + 1),
+                    title: track.title.clone(),
+                    artists: vec![self.artist_ref(&album.artist)],
+                    album: AlbumRef {
+                        id: album.id.clone(),
+                        name: album.title.clone(),
+                    },
+                    duration: track.duration * 1000,
+                    art: None,
+                    preview_url: None,
+                }
+            })
+            .collect()
+    }
+
+    fn song(&self, id: &str) -> Option<SongDescription> {
+        let album = self.album(id.get(..id.len().checked_sub(2)?)?)?;
+        self.album_songs(album).into_iter().find(|s| s.id == id)
+    }
+
+    fn songs(&self, ids: &[String]) -> Vec<SongDescription> {
+        ids.iter().filter_map(|id| self.song(id)).collect()
+    }
+
+    fn all_songs(&self) -> Vec<SongDescription> {
+        self.albums
+            .iter()
+            .flat_map(|album| self.album_songs(album))
+            .collect()
+    }
+
+    fn song_batch(songs: Vec<SongDescription>, offset: usize, limit: usize) -> SongBatch {
+        SongBatch {
+            batch: Batch {
+                offset,
+                batch_size: limit,
+                total: songs.len(),
+            },
+            songs: page(&songs, offset, limit),
+        }
+    }
+
+    fn album_description(&self, album: &DemoAlbum) -> AlbumDescription {
+        let songs = self.album_songs(album);
+        let total = songs.len();
+        AlbumDescription {
+            id: album.id.clone(),
+            title: album.title.clone(),
+            artists: vec![self.artist_ref(&album.artist)],
+            release_date: Some(album.release_date.clone()),
+            art: None,
+            songs: Self::song_batch(songs, 0, total.max(1)),
+            is_liked: self.saved_albums.contains(&album.id),
+        }
+    }
+
+    fn playlist_description(&self, playlist: &DemoPlaylist) -> PlaylistDescription {
+        let songs = self.songs(&playlist.tracks);
+        let total = songs.len();
+        let owner = self.user(&playlist.owner);
+        PlaylistDescription {
+            id: playlist.id.clone(),
+            title: playlist.title.clone(),
+            art: None,
+            songs: Self::song_batch(songs, 0, total.max(1)),
+            owner: UserRef {
+                id: playlist.owner.clone(),
+                display_name: owner.map(|u| u.name.clone()).unwrap_or_default(),
+            },
+            snapshot_id: format!("{}-{}", playlist.id, playlist.version),
+            mosaic: vec![],
+        }
+    }
+
+    fn artist_summary(artist: &DemoArtist) -> ArtistSummary {
+        ArtistSummary {
+            id: artist.id.clone(),
+            name: artist.name.clone(),
+            photo: None,
+        }
+    }
+
+    fn artist_albums(&self, id: &str) -> Vec<AlbumDescription> {
+        self.albums
+            .iter()
+            .filter(|album| album.artist == id)
+            .map(|album| self.album_description(album))
+            .collect()
+    }
+}
+
+// In milliseconds, for the demo player to tell when songs end
+pub fn demo_song_durations() -> HashMap<String, u32> {
+    DemoLibrary::bundled()
+        .all_songs()
+        .into_iter()
+        .map(|song| (song.id, song.duration))
+        .collect()
+}
+
+// Serves the bundled demo library instead of Spotify's, so that every screen can be tried without an
+// account (see --demo). There are no Connect devices to control.
+pub struct DemoSpotifyClient {
+    library: Mutex<DemoLibrary>,
+}
+
+impl DemoSpotifyClient {
+    pub fn new() -> Self {
+        Self {
+            library: Mutex::new(DemoLibrary::bundled()),
+        }
+    }
+
+    fn library(&self) -> MutexGuard<DemoLibrary> {
+        self.library.lock().unwrap()
+    }
+
+    fn respond<T, F>(&self, f: F) -> BoxFuture<SpotifyResult<T>>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut DemoLibrary) -> SpotifyResult<T>,
+    {
+        let result = f(&mut self.library());
+        Box::pin(future::ready(result))
+    }
+}
+
+impl SpotifyApiClient for DemoSpotifyClient {
+    fn get_artist(&self, id: &str) -> BoxFuture<SpotifyResult<ArtistDescription>> {
+        self.respond(|library| {
+            let artist = library.artist(id).ok_or(SpotifyApiError::NoContent)?;
+            let albums = library.artist_albums(id);
+            let mut top_tracks: Vec<SongDescription> = albums
+                .iter()
+                .flat_map(|album| album.songs.songs.iter().take(2).cloned())
+                .collect();
+            top_tracks.truncate(5);
+            Ok(ArtistDescription {
+                id: artist.id.clone(),
+                name: artist.name.clone(),
+                followers: Some(artist.followers),
+                albums,
+                top_tracks,
+            })
+        })
+    }
+
+    fn get_album(&self, id: &str) -> BoxFuture<SpotifyResult<AlbumFullDescription>> {
+        self.respond(|library| {
+            let album = library.album(id).ok_or(SpotifyApiError::NoContent)?;
+            Ok(AlbumFullDescription {
+                description: library.album_description(album),
+                release_details: AlbumReleaseDetails {
+                    label: album.label.clone(),
+                    copyright_text: format!("℗ {} {}", &album.release_date[..4], album.label),
+                    total_tracks: album.tracks.len(),
+                },
+            })
+        })
+    }
+
+    fn get_album_tracks(
+        &self,
+        id: &str,
+        offset: usize,
+        limit: usize,
+    ) -> BoxFuture<SpotifyResult<SongBatch>> {
+        self.respond(|library| {
+            let album = library.album(id).ok_or(SpotifyApiError::NoContent)?;
+            let songs = library.album_songs(album);
+            Ok(DemoLibrary::song_batch(songs, offset, limit))
+        })
+    }
+
+    fn get_playlist(&self, id: &str) -> BoxFuture<SpotifyResult<PlaylistDescription>> {
+        self.respond(|library| {
+            let playlist = library.playlist(id).ok_or(SpotifyApiError::NoContent)?;
+            Ok(library.playlist_description(playlist))
+        })
+    }
+
+    fn get_playlist_snapshot_id(&self, id: &str) -> BoxFuture<SpotifyResult<String>> {
+        self.respond(|library| {
+            let playlist = library.playlist(id).ok_or(SpotifyApiError::NoContent)?;
+            Ok(library.playlist_description(playlist).snapshot_id)
+        })
+    }
+
+    fn refresh_playlist(&self, id: &str) -> BoxFuture<SpotifyResult<PlaylistDescription>> {
+        self.get_playlist(id)
+    }
+
+    fn get_playlist_tracks(
+        &self,
+        id: &str,
+        offset: usize,
+        limit: usize,
+    ) -> BoxFuture<SpotifyResult<SongBatch>> {
+        self.respond(|library| {
+            let playlist = library.playlist(id).ok_or(SpotifyApiError::NoContent)?;
+            let songs = library.songs(&playlist.tracks);
+            Ok(DemoLibrary::song_batch(songs, offset, limit))
+        })
+    }
+
+    fn get_saved_albums(
+        &self,
+        offset: usize,
+        limit: usize,
+    ) -> BoxFuture<SpotifyResult<Vec<AlbumDescription>>> {
+        self.respond(|library| {
+            let albums: Vec<AlbumDescription> = library
+                .saved_albums
+                .iter()
+                .filter_map(|id| library.album(id))
+                .map(|album| library.album_description(album))
+                .collect();
+            Ok(page(&albums, offset, limit))
+        })
+    }
+
+    fn get_saved_tracks(&self, offset: usize, limit: usize) -> BoxFuture<SpotifyResult<SongBatch>> {
+        self.respond(|library| {
+            let songs = library.songs(&library.saved_tracks);
+            Ok(DemoLibrary::song_batch(songs, offset, limit))
+        })
+    }
+
+    fn save_album(&self, id: &str) -> BoxFuture<SpotifyResult<AlbumDescription>> {
+        self.respond(|library| {
+            let album = library
+                .album(id)
+                .cloned()
+                .ok_or(SpotifyApiError::NoContent)?;
+            if !library.saved_albums.contains(&album.id) {
+                library.saved_albums.insert(0, album.id.clone());
+            }
+            Ok(library.album_description(&album))
+        })
+    }
+
+    fn save_tracks(&self, ids: Vec<String>) -> BoxFuture<SpotifyResult<()>> {
+        self.respond(|library| {
+            for id in ids.into_iter().rev() {
+                if !library.saved_tracks.contains(&id) {
+                    library.saved_tracks.insert(0, id);
+                }
+            }
+            Ok(())
+        })
+    }
+
+    fn check_saved_tracks(&self, ids: Vec<String>) -> BoxFuture<SpotifyResult<Vec<bool>>> {
+        self.respond(|library| {
+            Ok(ids
+                .iter()
+                .map(|id| library.saved_tracks.contains(id))
+                .collect())
+        })
+    }
+
+    fn remove_saved_album(&self, id: &str) -> BoxFuture<SpotifyResult<()>> {
+        self.respond(|library| {
+            library.saved_albums.retain(|saved| saved != id);
+            Ok(())
+        })
+    }
+
+    fn remove_saved_tracks(&self, ids: Vec<String>) -> BoxFuture<SpotifyResult<()>> {
+        self.respond(|library| {
+            library.saved_tracks.retain(|saved| !ids.contains(saved));
+            Ok(())
+        })
+    }
+
+    fn get_saved_playlists(
+        &self,
+        offset: usize,
+        limit: usize,
+    ) -> BoxFuture<SpotifyResult<Vec<PlaylistDescription>>> {
+        self.respond(|library| {
+            let playlists: Vec<PlaylistDescription> = library
+                .saved_playlists
+                .iter()
+                .filter_map(|id| library.playlist(id))
+                .map(|playlist| library.playlist_description(playlist))
+                .collect();
+            Ok(page(&playlists, offset, limit))
+        })
+    }
+
+    fn add_to_playlist(&self, id: &str, uris: Vec<String>) -> BoxFuture<SpotifyResult<()>> {
+        self.respond(|library| {
+            let playlist = library.playlist_mut(id)?;
+            playlist.tracks.extend(
+                uris.iter()
+                    .filter_map(|uri| uri.strip_prefix("spotify:track:"))
+                    .map(String::from),
+            );
+            playlist.version += 1;
+            Ok(())
+        })
+    }
+
+    fn create_new_playlist(
+        &self,
+        name: &str,
+        user_id: &str,
+    ) -> BoxFuture<SpotifyResult<PlaylistDescription>> {
+        self.respond(|library| {
+            let playlist = DemoPlaylist {
+                id: format!("0demoplaylist{:09}", library.playlists.len() + 1),
+                title: name.to_string(),
+                owner: user_id.to_string(),
+                tracks: vec![],
+                version: 0,
+            };
+            library.saved_playlists.insert(0, playlist.id.clone());
+            library.playlists.push(playlist.clone());
+            Ok(library.playlist_description(&playlist))
+        })
+    }
+
+    fn remove_from_playlist(&self, id: &str, uris: Vec<String>) -> BoxFuture<SpotifyResult<()>> {
+        self.respond(|library| {
+            let playlist = library.playlist_mut(id)?;
+            playlist
+                .tracks
+                .retain(|track| !uris.contains(&format!("spotify:track:{}", track)));
+            playlist.version += 1;
+            Ok(())
+        })
+    }
+
+    fn update_playlist_details(&self, id: &str, name: String) -> BoxFuture<SpotifyResult<()>> {
+        self.respond(|library| {
+            let playlist = library.playlist_mut(id)?;
+            playlist.title = name;
+            playlist.version += 1;
+            Ok(())
+        })
+    }
+
+    fn follow_playlist(&self, id: &str) -> BoxFuture<SpotifyResult<PlaylistDescription>> {
+        self.respond(|library| {
+            let playlist = library
+                .playlist(id)
+                .cloned()
+                .ok_or(SpotifyApiError::NoContent)?;
+            if !library.saved_playlists.contains(&playlist.id) {
+                library.saved_playlists.insert(0, playlist.id.clone());
+            }
+            Ok(library.playlist_description(&playlist))
+        })
+    }
+
+    fn unfollow_playlist(&self, id: &str) -> BoxFuture<SpotifyResult<()>> {
+        self.respond(|library| {
+            library.saved_playlists.retain(|saved| saved != id);
+            Ok(())
+        })
+    }
+
+    fn search(
+        &self,
+        query: &str,
+        offset: usize,
+        limit: usize,
+    ) -> BoxFuture<SpotifyResult<SearchResults>> {
+        self.respond(|library| {
+            let albums: Vec<AlbumDescription> = library
+                .albums
+                .iter()
+                .map(|album| library.album_description(album))
+                .filter(|album| {
+                    matches(&album.title, query) || matches(&album.artists_name(), query)
+                })
+                .collect();
+            let artists: Vec<ArtistSummary> = library
+                .artists
+                .iter()
+                .filter(|artist| matches(&artist.name, query))
+                .map(DemoLibrary::artist_summary)
+                .collect();
+            Ok(SearchResults {
+                albums: page(&albums, offset, limit),
+                artists: page(&artists, offset, limit),
+            })
+        })
+    }
+
+    fn search_tracks(
+        &self,
+        query: &str,
+        limit: usize,
+    ) -> BoxFuture<SpotifyResult<Vec<SongDescription>>> {
+        self.respond(|library| {
+            let songs: Vec<SongDescription> = library
+                .all_songs()
+                .into_iter()
+                .filter(|song| matches(&song.title, query) || matches(&song.artists_name(), query))
+                .collect();
+            Ok(page(&songs, 0, limit))
+        })
+    }
+
+    fn get_artist_albums(
+        &self,
+        id: &str,
+        offset: usize,
+        limit: usize,
+    ) -> BoxFuture<SpotifyResult<Vec<AlbumDescription>>> {
+        self.respond(|library| Ok(page(&library.artist_albums(id), offset, limit)))
+    }
+
+    fn get_user(&self, id: &str) -> BoxFuture<SpotifyResult<UserDescription>> {
+        self.respond(|library| {
+            let user = library.user(id).ok_or(SpotifyApiError::NoContent)?;
+            Ok(UserDescription {
+                id: user.id.clone(),
+                name: user.name.clone(),
+                playlists: library
+                    .playlists
+                    .iter()
+                    .filter(|playlist| playlist.owner == id)
+                    .map(|playlist| library.playlist_description(playlist))
+                    .collect(),
+            })
+        })
+    }
+
+    fn get_user_playlists(
+        &self,
+        id: &str,
+        offset: usize,
+        limit: usize,
+    ) -> BoxFuture<SpotifyResult<Vec<PlaylistDescription>>> {
+        self.respond(|library| {
+            let playlists: Vec<PlaylistDescription> = library
+                .playlists
+                .iter()
+                .filter(|playlist| playlist.owner == id)
+                .map(|playlist| library.playlist_description(playlist))
+                .collect();
+            Ok(page(&playlists, offset, limit))
+        })
+    }
+
+    fn list_available_devices(&self) -> BoxFuture<SpotifyResult<Vec<ConnectDevice>>> {
+        self.respond(|_| Ok(vec![]))
+    }
+
+    fn get_player_queue(&self) -> BoxFuture<SpotifyResult<Vec<SongDescription>>> {
+        self.respond(|_| Ok(vec![]))
+    }
+
+    fn get_track(&self, id: &str) -> BoxFuture<SpotifyResult<SongDescription>> {
+        self.respond(|library| library.song(id).ok_or(SpotifyApiError::NoContent))
+    }
+
+    fn get_tracks_isrc(&self, ids: Vec<String>) -> BoxFuture<SpotifyResult<Vec<Option<String>>>> {
+        self.respond(|_| Ok(vec![None; ids.len()]))
+    }
+
+    fn get_followed_artists(&self) -> BoxFuture<SpotifyResult<Vec<ArtistSummary>>> {
+        self.respond(|library| {
+            Ok(library
+                .artists
+                .iter()
+                .map(DemoLibrary::artist_summary)
+                .collect())
+        })
+    }
+
+    fn get_recently_played(&self, limit: usize) -> BoxFuture<SpotifyResult<Vec<String>>> {
+        self.respond(|library| Ok(page(&library.saved_tracks, 0, limit)))
+    }
+
+    // The demo library has no podcasts
+    fn get_saved_shows(&self) -> BoxFuture<SpotifyResult<Vec<ShowSummary>>> {
+        self.respond(|_| Ok(vec![]))
+    }
+
+    fn get_show_episodes(&self, _id: &str, _limit: usize) -> BoxFuture<SpotifyResult<Vec<String>>> {
+        self.respond(|_| Ok(vec![]))
+    }
+
+    fn get_recommendations(
+        &self,
+        seed_tracks: Vec<String>,
+        limit: usize,
+    ) -> BoxFuture<SpotifyResult<Vec<SongDescription>>> {
+        self.respond(|library| {
+            let songs: Vec<SongDescription> = library
+                .all_songs()
+                .into_iter()
+                .filter(|song| !seed_tracks.contains(&song.id))
+                .collect();
+            Ok(page(&songs, 0, limit))
+        })
+    }
+
+    fn update_token(&self, _token: String) {}
+
+    fn set_offline(&self, _offline: bool) {}
+
+    fn player_pause(&self, _device_id: String) -> BoxFuture<SpotifyResult<()>> {
+        self.respond(|_| Ok(()))
+    }
+
+    fn player_resume(&self, _device_id: String) -> BoxFuture<SpotifyResult<()>> {
+        self.respond(|_| Ok(()))
+    }
+
+    fn player_next(&self, _device_id: String) -> BoxFuture<SpotifyResult<()>> {
+        self.respond(|_| Ok(()))
+    }
+
+    fn player_seek(&self, _device_id: String, _pos: usize) -> BoxFuture<SpotifyResult<()>> {
+        self.respond(|_| Ok(()))
+    }
+
+    fn player_repeat(&self, _device_id: String, _mode: RepeatMode) -> BoxFuture<SpotifyResult<()>> {
+        self.respond(|_| Ok(()))
+    }
+
+    fn player_shuffle(&self, _device_id: String, _shuffle: bool) -> BoxFuture<SpotifyResult<()>> {
+        self.respond(|_| Ok(()))
+    }
+
+    fn player_volume(&self, _device_id: String, _volume: u8) -> BoxFuture<SpotifyResult<()>> {
+        self.respond(|_| Ok(()))
+    }
+
+    fn player_play_in_context(
+        &self,
+        _device_id: String,
+        _context: String,
+        _offset: usize,
+    ) -> BoxFuture<SpotifyResult<()>> {
+        self.respond(|_| Ok(()))
+    }
+
+    fn player_play_no_context(
+        &self,
+        _device_id: String,
+        _uris: Vec<String>,
+        _offset: usize,
+    ) -> BoxFuture<SpotifyResult<()>> {
+        self.respond(|_| Ok(()))
+    }
+
+    fn player_state(&self) -> BoxFuture<SpotifyResult<ConnectPlayerState>> {
+        self.respond(|_| Ok(Default::default()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_bundled_library_is_consistent() {
+        let library = DemoLibrary::bundled();
+        assert!(library.user(DEMO_USER).is_some());
+        for album in library.albums.iter() {
+            assert!(library.artist(&album.artist).is_some(), "{}", album.id);
+            // Ids are turned into Spotify ids for the player, which take 22 characters
+            for song in library.album_songs(album) {
+                assert_eq!(song.id.len(), 22, "{}", song.id);
+            }
+        }
+        for playlist in library.playlists.iter() {
+            assert_eq!(library.songs(&playlist.tracks).len(), playlist.tracks.len());
+        }
+        assert_eq!(
+            library.songs(&library.saved_tracks).len(),
+            library.saved_tracks.len()
+        );
+    }
+
+    #[test]
+    fn test_page_through_songs() {
+        let library = DemoLibrary::bundled();
+        let songs = library.all_songs();
+        let batch = DemoLibrary::song_batch(songs.clone(), 5, 5);
+        assert_eq!(batch.batch.total, songs.len());
+        assert_eq!(batch.songs.len(), 5);
+        assert_eq!(batch.songs[0].id, songs[5].id);
+    }
+}
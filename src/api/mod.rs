@@ -1,11 +1,14 @@
 mod api_models;
 mod cached_client;
 mod client;
+mod demo_client;
+mod scheduler;
 
 pub mod cache;
 
 pub use cached_client::{CachedSpotifyClient, SpotifyApiClient, SpotifyResult};
 pub use client::SpotifyApiError;
+pub use demo_client::{demo_song_durations, DemoSpotifyClient, DEMO_USER};
 
 pub async fn clear_user_cache() -> Option<()> {
     cache::CacheManager::for_dir("spot/net")?
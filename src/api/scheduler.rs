@@ -0,0 +1,267 @@
+// Keeps us from flooding the API (which answers with 429s) when lots of requests are fired at once,
+// say while scrolling quickly through a long list:
+// - requests are paced with a token bucket, and held back for as long as the API tells us to;
+// - identical GETs that are in flight at the same time are only sent once;
+// - only so many requests are sent concurrently to any given endpoint.
+
+use futures::future::{BoxFuture, FutureExt, Shared};
+use isahc::http::{Method, Request, StatusCode};
+use isahc::{AsyncReadResponseExt, HttpClient};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::Semaphore;
+
+// Up to that many requests at once...
+const BUCKET_CAPACITY: f64 = 10.0;
+// ...then that many per second
+const REFILL_PER_SECOND: f64 = 5.0;
+
+const MAX_CONCURRENT_PER_ENDPOINT: usize = 4;
+
+// When the API doesn't say how long to wait
+const DEFAULT_RETRY_AFTER: Duration = Duration::from_secs(1);
+// Past that, we'd rather fail than leave the whole app hanging
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(30);
+const MAX_RETRIES: usize = 3;
+
+// A response, read in full so that it can be shared between identical requests
+#[derive(Clone, Debug)]
+pub(crate) struct RawResponse {
+    pub status: StatusCode,
+    pub etag: Option<String>,
+    pub cache_control: Option<String>,
+    pub retry_after: Option<Duration>,
+    pub body: Arc<String>,
+}
+
+type RawResult = Result<RawResponse, isahc::Error>;
+
+#[derive(Debug)]
+struct TokenBucket {
+    tokens: f64,
+    last_refill: Instant,
+    paused_until: Option<Instant>,
+}
+
+impl TokenBucket {
+    fn new(now: Instant) -> Self {
+        Self {
+            tokens: BUCKET_CAPACITY,
+            last_refill: now,
+            paused_until: None,
+        }
+    }
+
+    // Takes a token if possible, otherwise returns how long to wait before trying again
+    fn take(&mut self, now: Instant) -> Option<Duration> {
+        if let Some(until) = self.paused_until.filter(|until| *until > now) {
+            return Some(until - now);
+        }
+        self.paused_until = None;
+
+        let elapsed = now
+            .saturating_duration_since(self.last_refill)
+            .as_secs_f64();
+        self.tokens = (self.tokens + elapsed * REFILL_PER_SECOND).min(BUCKET_CAPACITY);
+        self.last_refill = now;
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            None
+        } else {
+            Some(Duration::from_secs_f64(
+                (1.0 - self.tokens) / REFILL_PER_SECOND,
+            ))
+        }
+    }
+
+    fn pause(&mut self, until: Instant) {
+        self.paused_until = Some(
+            self.paused_until
+                .map_or(until, |current| current.max(until)),
+        );
+    }
+}
+
+fn copy_request<B: Clone>(request: &Request<B>) -> Request<B> {
+    let mut copy = Request::new(request.body().clone());
+    *copy.method_mut() = request.method().clone();
+    *copy.uri_mut() = request.uri().clone();
+    *copy.headers_mut() = request.headers().clone();
+    copy
+}
+
+// Requests are grouped by the kind of thing they're about (/v1/artists, /v1/me...)
+fn endpoint(path: &str) -> String {
+    path.split('/')
+        .filter(|segment| !segment.is_empty())
+        .take(2)
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+#[derive(Clone)]
+pub(crate) struct RequestScheduler {
+    bucket: Arc<Mutex<TokenBucket>>,
+    endpoints: Arc<Mutex<HashMap<String, Arc<Semaphore>>>>,
+    in_flight: Arc<Mutex<HashMap<String, Shared<BoxFuture<'static, RawResult>>>>>,
+}
+
+impl RequestScheduler {
+    pub(crate) fn new() -> Self {
+        Self {
+            bucket: Arc::new(Mutex::new(TokenBucket::new(Instant::now()))),
+            endpoints: Default::default(),
+            in_flight: Default::default(),
+        }
+    }
+
+    // Holds back every request for that long (when we're told to slow down)
+    fn pause(&self, duration: Duration) {
+        self.bucket.lock().unwrap().pause(Instant::now() + duration);
+    }
+
+    pub(crate) async fn send<B>(&self, client: &HttpClient, request: Request<B>) -> RawResult
+    where
+        B: Into<isahc::AsyncBody> + Clone + Send + Sync + 'static,
+    {
+        if *request.method() != Method::GET {
+            return self.clone().schedule(client.clone(), request).await;
+        }
+
+        let key = format!(
+            "{} {:?}",
+            request.uri(),
+            request.headers().get("If-None-Match")
+        );
+        let existing = self.in_flight.lock().unwrap().get(&key).cloned();
+        if let Some(existing) = existing {
+            debug!("Request already in flight: {}", request.uri());
+            return existing.await;
+        }
+
+        let scheduler = self.clone();
+        let in_flight = Arc::clone(&self.in_flight);
+        let client = client.clone();
+        let shared_key = key.clone();
+        let future = async move {
+            let result = scheduler.schedule(client, request).await;
+            in_flight.lock().unwrap().remove(&shared_key);
+            result
+        }
+        .boxed()
+        .shared();
+        self.in_flight.lock().unwrap().insert(key, future.clone());
+        future.await
+    }
+
+    // Sends the request when it's its turn, again if we're told to retry later
+    async fn schedule<B>(self, client: HttpClient, request: Request<B>) -> RawResult
+    where
+        B: Into<isahc::AsyncBody> + Clone,
+    {
+        let mut retries = 0;
+        loop {
+            let response = self.send_once(&client, copy_request(&request)).await?;
+            if response.status != StatusCode::TOO_MANY_REQUESTS {
+                return Ok(response);
+            }
+
+            let retry_after = response.retry_after.unwrap_or(DEFAULT_RETRY_AFTER);
+            warn!("Rate limited, retrying in {}s", retry_after.as_secs());
+            self.pause(retry_after.min(MAX_RETRY_AFTER));
+            if retry_after > MAX_RETRY_AFTER || retries == MAX_RETRIES {
+                return Ok(response);
+            }
+            retries += 1;
+        }
+    }
+
+    async fn send_once<B>(&self, client: &HttpClient, request: Request<B>) -> RawResult
+    where
+        B: Into<isahc::AsyncBody>,
+    {
+        let semaphore = self.endpoint_semaphore(request.uri().path());
+        let _permit = semaphore.acquire().await;
+        self.wait_for_token().await;
+
+        let mut response = client.send_async(request).await?;
+        let (etag, cache_control, retry_after) = {
+            let headers = response.headers();
+            let header = |name: &str| {
+                headers
+                    .get(name)
+                    .and_then(|header| header.to_str().ok())
+                    .map(|s| s.to_owned())
+            };
+            let retry_after = header("retry-after")
+                .and_then(|s| s.trim().parse::<u64>().ok())
+                .map(Duration::from_secs);
+            (header("etag"), header("cache-control"), retry_after)
+        };
+        let status = response.status();
+        let body = response.text().await?;
+        Ok(RawResponse {
+            status,
+            etag,
+            cache_control,
+            retry_after,
+            body: Arc::new(body),
+        })
+    }
+
+    fn endpoint_semaphore(&self, path: &str) -> Arc<Semaphore> {
+        let mut endpoints = self.endpoints.lock().unwrap();
+        Arc::clone(
+            endpoints
+                .entry(endpoint(path))
+                .or_insert_with(|| Arc::new(Semaphore::new(MAX_CONCURRENT_PER_ENDPOINT))),
+        )
+    }
+
+    async fn wait_for_token(&self) {
+        loop {
+            let wait = self.bucket.lock().unwrap().take(Instant::now());
+            match wait {
+                None => break,
+                Some(wait) => async_std::task::sleep(wait).await,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_token_bucket() {
+        let now = Instant::now();
+        let mut bucket = TokenBucket::new(now);
+        for _ in 0..10 {
+            assert_eq!(bucket.take(now), None);
+        }
+        assert_eq!(bucket.take(now), Some(Duration::from_millis(200)));
+
+        let later = now + Duration::from_millis(200);
+        assert_eq!(bucket.take(later), None);
+        assert!(bucket.take(later).is_some());
+    }
+
+    #[test]
+    fn test_token_bucket_pause() {
+        let now = Instant::now();
+        let mut bucket = TokenBucket::new(now);
+        bucket.pause(now + Duration::from_secs(2));
+        assert_eq!(bucket.take(now), Some(Duration::from_secs(2)));
+        assert_eq!(bucket.take(now + Duration::from_secs(2)), None);
+    }
+
+    #[test]
+    fn test_endpoint() {
+        assert_eq!(endpoint("/v1/artists/some_id/albums"), "v1/artists");
+        assert_eq!(endpoint("/v1/me/tracks"), "v1/me");
+    }
+}
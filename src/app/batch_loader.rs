@@ -1,7 +1,7 @@
 use gettextrs::gettext;
 use std::sync::Arc;
 
-use crate::api::{SpotifyApiClient, SpotifyApiError};
+use crate::api::{SpotifyApiClient, SpotifyApiError, SpotifyResult};
 use crate::app::models::*;
 use crate::app::AppAction;
 
@@ -37,6 +37,15 @@ impl SongsSource {
         matches!(self, Self::Playlist(_) | Self::Album(_))
     }
 
+    // What kind of source this is, as used in the settings (see shuffle-mode-by-source)
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Playlist(_) => "playlist",
+            Self::Album(_) => "album",
+            Self::SavedTracks => "saved-tracks",
+        }
+    }
+
     pub fn spotify_uri(&self) -> Option<String> {
         match self {
             Self::Playlist(id) => Some(format!("spotify:playlist:{}", id)),
@@ -69,6 +78,39 @@ impl BatchLoader {
         Self { api }
     }
 
+    async fn get(
+        &self,
+        source: &SongsSource,
+        offset: usize,
+        batch_size: usize,
+    ) -> SpotifyResult<SongBatch> {
+        let api = Arc::clone(&self.api);
+        match source {
+            SongsSource::Playlist(id) => api.get_playlist_tracks(id, offset, batch_size).await,
+            SongsSource::SavedTracks => api.get_saved_tracks(offset, batch_size).await,
+            SongsSource::Album(id) => api.get_album_tracks(id, offset, batch_size).await,
+        }
+    }
+
+    // Every song of a source, batch after batch, and not just those loaded to be displayed
+    pub async fn query_all(
+        &self,
+        source: SongsSource,
+        batch_size: usize,
+    ) -> SpotifyResult<Vec<SongDescription>> {
+        let mut songs = vec![];
+        let mut next = Some(Batch::first_of_size(batch_size));
+        while let Some(Batch {
+            offset, batch_size, ..
+        }) = next
+        {
+            let mut batch = self.get(&source, offset, batch_size).await?;
+            songs.append(&mut batch.songs);
+            next = batch.batch.next();
+        }
+        Ok(songs)
+    }
+
     // Query a batch and create an action when it's been retrieved succesfully
     pub async fn query<ActionCreator>(
         &self,
@@ -78,16 +120,10 @@ impl BatchLoader {
     where
         ActionCreator: FnOnce(SongsSource, SongBatch) -> AppAction,
     {
-        let api = Arc::clone(&self.api);
-
         let Batch {
             offset, batch_size, ..
         } = query.batch;
-        let result = match &query.source {
-            SongsSource::Playlist(id) => api.get_playlist_tracks(id, offset, batch_size).await,
-            SongsSource::SavedTracks => api.get_saved_tracks(offset, batch_size).await,
-            SongsSource::Album(id) => api.get_album_tracks(id, offset, batch_size).await,
-        };
+        let result = self.get(&query.source, offset, batch_size).await;
 
         match result {
             Ok(batch) => Some(create_action(query.source, batch)),
@@ -0,0 +1,202 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::app::components::EventListener;
+use crate::app::models::{Alarm, Batch};
+use crate::app::state::PlaybackAction;
+use crate::app::{
+    ActionDispatcher, AppAction, AppEvent, AppModel, BatchQuery, SongsSource, SpotifyUri,
+};
+
+// Alarms are checked against the clock rather than timed, so that they still go off on time after
+// the computer was suspended
+const CHECK_INTERVAL_SECS: u32 = 5;
+const RAMP_STEP: Duration = Duration::from_millis(500);
+const FIRST_BATCH_SIZE: usize = 50;
+
+fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or_default()
+}
+
+// Rises linearly from nothing to the target volume
+fn ramp_volume(target: f64, elapsed: Duration, ramp: Duration) -> f64 {
+    if ramp.is_zero() {
+        return target;
+    }
+    target * (elapsed.as_secs_f64() / ramp.as_secs_f64()).min(1.0)
+}
+
+struct Ringer {
+    app_model: Rc<AppModel>,
+    dispatcher: Box<dyn ActionDispatcher>,
+    alarms: RefCell<Vec<Alarm>>,
+    check: RefCell<Option<glib::SourceId>>,
+    ramp: RefCell<Option<glib::SourceId>>,
+}
+
+impl Ringer {
+    // Returns whether there are alarms left
+    fn ring_due(self: &Rc<Self>) -> bool {
+        let now = now();
+        let due: Vec<Alarm> = {
+            let mut alarms = self.alarms.borrow_mut();
+            let (due, later) = alarms.drain(..).partition(|alarm| alarm.at <= now);
+            *alarms = later;
+            due
+        };
+        // Only the last one matters if several went off at once
+        if let Some(alarm) = due.into_iter().last() {
+            self.ring(alarm);
+        }
+        !self.alarms.borrow().is_empty()
+    }
+
+    fn ring(self: &Rc<Self>, alarm: Alarm) {
+        if self.app_model.get_state().logged_user.user.is_none() {
+            warn!("Alarm {} went off while logged out", alarm.id);
+            return;
+        }
+        if alarm.ramp_secs > 0 {
+            self.start_ramp(Duration::from_secs(alarm.ramp_secs as u64));
+        }
+        let source = match alarm.uri {
+            SpotifyUri::Album(id) => SongsSource::Album(id),
+            SpotifyUri::Playlist(id) => SongsSource::Playlist(id),
+            SpotifyUri::Track(id) => {
+                self.dispatcher.dispatch(AppAction::PlayTrack(id));
+                return;
+            }
+            _ => return,
+        };
+        let loader = self.app_model.get_batch_loader();
+        let query = BatchQuery {
+            source,
+            batch: Batch::first_of_size(FIRST_BATCH_SIZE),
+        };
+        self.dispatcher.dispatch_async(Box::pin(async move {
+            loader
+                .query(query, |source, batch| {
+                    let first = batch.songs.first().map(|song| song.id.clone());
+                    let mut actions = vec![PlaybackAction::LoadPagedSongs(source, batch).into()];
+                    actions.extend(first.map(|id| PlaybackAction::Load(id).into()));
+                    AppAction::Batch(actions)
+                })
+                .await
+        }));
+    }
+
+    // Brings the volume back up to where it was, unless the user sets it in the meantime
+    fn start_ramp(self: &Rc<Self>, ramp: Duration) {
+        self.stop_ramp();
+        let target = self.app_model.get_state().playback.volume();
+        self.dispatcher
+            .dispatch(PlaybackAction::SetVolume(0.0).into());
+
+        let ringer = Rc::clone(self);
+        let started = Instant::now();
+        let mut last_volume = 0.0;
+        let source = glib::timeout_add_local(RAMP_STEP, move || {
+            let volume = ringer.app_model.get_state().playback.volume();
+            if (volume - last_volume).abs() > 0.001 {
+                ringer.ramp.take();
+                return glib::Continue(false);
+            }
+            let elapsed = started.elapsed();
+            last_volume = ramp_volume(target, elapsed, ramp);
+            ringer
+                .dispatcher
+                .dispatch(PlaybackAction::SetVolume(last_volume).into());
+            let is_done = elapsed >= ramp;
+            if is_done {
+                ringer.ramp.take();
+            }
+            glib::Continue(!is_done)
+        });
+        self.ramp.replace(Some(source));
+    }
+
+    fn stop_ramp(&self) {
+        if let Some(source) = self.ramp.take() {
+            source.remove();
+        }
+    }
+}
+
+// Plays what was programmed over D-Bus (see SpotAlarms) at the given time
+pub struct AlarmClock {
+    ringer: Rc<Ringer>,
+}
+
+impl AlarmClock {
+    pub fn new(app_model: Rc<AppModel>, dispatcher: Box<dyn ActionDispatcher>) -> Self {
+        Self {
+            ringer: Rc::new(Ringer {
+                app_model,
+                dispatcher,
+                alarms: RefCell::new(vec![]),
+                check: RefCell::new(None),
+                ramp: RefCell::new(None),
+            }),
+        }
+    }
+
+    fn schedule(&self, alarm: Alarm) {
+        info!("Alarm {} scheduled for {}", alarm.id, alarm.at);
+        self.ringer.alarms.borrow_mut().push(alarm);
+        if self.ringer.check.borrow().is_some() {
+            return;
+        }
+        let ringer = Rc::clone(&self.ringer);
+        let check = glib::timeout_add_seconds_local(CHECK_INTERVAL_SECS, move || {
+            // Checks stop once there's nothing left to check
+            let alarms_left = ringer.ring_due();
+            if !alarms_left {
+                ringer.check.take();
+            }
+            glib::Continue(alarms_left)
+        });
+        self.ringer.check.replace(Some(check));
+    }
+
+    fn cancel(&self, id: u32) {
+        let mut alarms = self.ringer.alarms.borrow_mut();
+        alarms.retain(|alarm| alarm.id != id);
+        if alarms.is_empty() {
+            if let Some(check) = self.ringer.check.take() {
+                check.remove();
+            }
+        }
+    }
+}
+
+impl EventListener for AlarmClock {
+    fn on_event(&mut self, event: &AppEvent) {
+        match event {
+            AppEvent::AlarmScheduled(alarm) => self.schedule(alarm.clone()),
+            AppEvent::AlarmCancelled(id) => self.cancel(*id),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_ramp_volume() {
+        let ramp = Duration::from_secs(60);
+        assert_eq!(ramp_volume(0.8, Duration::ZERO, ramp), 0.0);
+        assert_eq!(ramp_volume(0.8, Duration::from_secs(30), ramp), 0.4);
+        assert_eq!(ramp_volume(0.8, Duration::from_secs(90), ramp), 0.8);
+        assert_eq!(
+            ramp_volume(0.8, Duration::from_secs(1), Duration::ZERO),
+            0.8
+        );
+    }
+}
@@ -15,6 +15,9 @@ mod imp {
     #[derive(Debug, Default, CompositeTemplate)]
     #[template(resource = "/dev/alextren/Spot/components/album.ui")]
     pub struct AlbumWidget {
+        #[template_child]
+        pub content_box: TemplateChild<gtk::Box>,
+
         #[template_child]
         pub album_label: TemplateChild<gtk::Label>,
 
@@ -79,12 +82,17 @@ impl AlbumWidget {
         let widget = self.imp();
         widget.cover_image.set_overflow(gtk::Overflow::Hidden);
 
-        if let Some(cover_art) = album_model.cover() {
+        let cover_art = album_model.cover();
+        let mosaic = album_model.mosaic();
+        if cover_art.is_some() || !mosaic.is_empty() {
             let _self = self.downgrade();
             worker.send_local_task(async move {
                 if let Some(_self) = _self.upgrade() {
-                    let loader = ImageLoader::new();
-                    let result = loader.load_remote(&cover_art, "jpg", 200, 200).await;
+                    let loader = ImageLoader::for_widget(&_self);
+                    let result = match cover_art {
+                        Some(cover_art) => loader.load_remote(&cover_art, "jpg", 200, 200).await,
+                        None => loader.load_mosaic(&mosaic, 200).await,
+                    };
                     _self.set_image(result.as_ref());
                     _self.set_loaded();
                 }
@@ -113,6 +121,32 @@ impl AlbumWidget {
         }
     }
 
+    // A single row, with a small cover followed by the album, artist and year side by side
+    pub fn set_compact(&self) {
+        let widget = self.imp();
+        self.add_css_class("album--compact");
+        widget.cover_btn.set_halign(gtk::Align::Fill);
+        widget.cover_btn.set_hexpand(true);
+        widget
+            .content_box
+            .set_orientation(gtk::Orientation::Horizontal);
+        widget.content_box.set_halign(gtk::Align::Fill);
+        widget.content_box.set_valign(gtk::Align::Center);
+        widget.content_box.set_spacing(12);
+        widget.content_box.set_margin_top(0);
+        widget.content_box.set_margin_bottom(0);
+        widget.album_label.remove_css_class("title-4");
+        widget.album_label.add_css_class("heading");
+        for label in [&*widget.album_label, &*widget.artist_label] {
+            label.set_hexpand(true);
+            label.set_xalign(0.0);
+            label.set_justify(gtk::Justification::Left);
+            label.set_wrap(false);
+            label.set_margin_top(0);
+        }
+        widget.year_label.set_width_chars(4);
+    }
+
     pub fn connect_album_pressed<F: Fn(&Self) + 'static>(&self, f: F) {
         self.imp()
             .cover_btn
@@ -73,7 +73,7 @@ impl ArtistWidget {
             let avatar = widget.avatar.downgrade();
             worker.send_local_task(async move {
                 if let Some(avatar) = avatar.upgrade() {
-                    let loader = ImageLoader::new();
+                    let loader = ImageLoader::for_widget(&avatar);
                     let pixbuf = loader.load_remote(&url, "jpg", 200, 200).await;
                     let texture = pixbuf.as_ref().map(gdk::Texture::for_pixbuf);
                     avatar.set_custom_image(texture.as_ref());
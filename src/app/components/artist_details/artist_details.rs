@@ -4,10 +4,10 @@ use gtk::CompositeTemplate;
 use std::rc::Rc;
 
 use crate::app::components::{
-    display_add_css_provider, AlbumWidget, Component, EventListener, Playlist,
+    display, display_add_css_provider, utils, AlbumWidget, Component, EventListener, Playlist,
 };
 use crate::app::{models::*, ListStore};
-use crate::app::{AppEvent, BrowserEvent, Worker};
+use crate::app::{AppEvent, BrowserEvent, Worker, PREFETCH_ROWS};
 
 use super::ArtistDetailsModel;
 
@@ -26,6 +26,18 @@ mod imp {
 
         #[template_child]
         pub artist_releases: TemplateChild<gtk::FlowBox>,
+
+        #[template_child]
+        pub like_top_tracks_button: TemplateChild<gtk::Button>,
+
+        #[template_child]
+        pub release_alert_button: TemplateChild<gtk::ToggleButton>,
+
+        #[template_child]
+        pub new_releases_badge: TemplateChild<gtk::Label>,
+
+        #[template_child]
+        pub followers_label: TemplateChild<gtk::Label>,
     }
 
     #[glib::object_subclass]
@@ -66,17 +78,48 @@ impl ArtistDetailsWidget {
         self.add_css_class("artist__loaded");
     }
 
-    fn connect_bottom_edge<F>(&self, f: F)
+    fn connect_like_top_tracks<F>(&self, f: F)
     where
         F: Fn() + 'static,
     {
         self.imp()
-            .scrolled_window
-            .connect_edge_reached(move |_, pos| {
-                if let gtk::PositionType::Bottom = pos {
-                    f()
-                }
-            });
+            .like_top_tracks_button
+            .connect_clicked(move |_| f());
+    }
+
+    fn connect_release_alert_toggled<F>(&self, f: F)
+    where
+        F: Fn(bool) + 'static,
+    {
+        self.imp()
+            .release_alert_button
+            .connect_toggled(move |button| f(button.is_active()));
+    }
+
+    fn set_release_alert(&self, enabled: bool) {
+        self.imp().release_alert_button.set_active(enabled);
+    }
+
+    fn set_followers(&self, followers: usize) {
+        let label = &self.imp().followers_label;
+        label.set_label(&display::n_followers_label(followers));
+        label.set_visible(true);
+    }
+
+    fn show_new_releases_badge(&self) {
+        self.imp().new_releases_badge.set_visible(true);
+    }
+
+    fn connect_prefetch<F>(&self, f: F)
+    where
+        F: Fn() + 'static,
+    {
+        utils::connect_prefetch(
+            &self.imp().scrolled_window,
+            &self.imp().artist_releases,
+            PREFETCH_ROWS,
+            f,
+        );
     }
 
     fn bind_artist_releases<F>(
@@ -115,10 +158,19 @@ impl ArtistDetails {
 
         let widget = ArtistDetailsWidget::new();
 
-        widget.connect_bottom_edge(clone!(@weak model => move || {
+        widget.connect_prefetch(clone!(@weak model => move || {
             model.load_more();
         }));
 
+        widget.connect_like_top_tracks(clone!(@weak model => move || {
+            model.like_top_tracks();
+        }));
+
+        widget.set_release_alert(model.is_release_alert_set());
+        widget.connect_release_alert_toggled(clone!(@weak model => move |enabled| {
+            model.set_release_alert(enabled);
+        }));
+
         if let Some(store) = model.get_list_store() {
             widget.bind_artist_releases(
                 worker.clone(),
@@ -135,10 +187,20 @@ impl ArtistDetails {
             worker,
         ));
 
-        Self {
+        let artist_details = Self {
             model,
             widget,
             children: vec![playlist],
+        };
+        artist_details.show_new_releases();
+        artist_details
+    }
+
+    // The badge stays for as long as the page is open, but the releases now count as seen
+    fn show_new_releases(&self) {
+        if self.model.has_new_releases() {
+            self.widget.show_new_releases_badge();
+            self.model.mark_releases_seen();
         }
     }
 }
@@ -159,10 +221,27 @@ impl EventListener for ArtistDetails {
             AppEvent::BrowserEvent(BrowserEvent::ArtistDetailsUpdated(id))
                 if id == &self.model.id =>
             {
+                self.model.pages_added();
                 self.widget.set_loaded();
+                if let Some(followers) = self.model.get_followers() {
+                    self.widget.set_followers(followers);
+                }
+            }
+            AppEvent::ReleaseAlertChanged(id) if id == &self.model.id => {
+                self.widget
+                    .set_release_alert(self.model.is_release_alert_set());
             }
+            AppEvent::ReleaseAlertsUpdated => self.show_new_releases(),
             _ => {}
         }
         self.broadcast_event(event);
     }
+
+    fn on_mount(&mut self) {
+        self.broadcast_mount();
+    }
+
+    fn on_unmount(&mut self) {
+        self.broadcast_unmount();
+    }
 }
@@ -5,18 +5,21 @@ use std::rc::Rc;
 
 use crate::api::SpotifyApiError;
 use crate::app::components::SimpleHeaderBarModel;
-use crate::app::components::{labels, PlaylistModel};
+use crate::app::components::{labels, save_tracks_in_bulk, utils, PlaylistModel, Shareable};
 use crate::app::models::*;
 use crate::app::state::SelectionContext;
 use crate::app::state::{
     BrowserAction, BrowserEvent, PlaybackAction, SelectionAction, SelectionState,
 };
-use crate::app::{ActionDispatcher, AppAction, AppEvent, AppModel, ListStore};
+use crate::app::{
+    ActionDispatcher, AppAction, AppEvent, AppModel, ListStore, Prefetcher, SpotifyUri,
+};
 
 pub struct ArtistDetailsModel {
     pub id: String,
     app_model: Rc<AppModel>,
     dispatcher: Box<dyn ActionDispatcher>,
+    prefetcher: Prefetcher,
 }
 
 impl ArtistDetailsModel {
@@ -25,6 +28,7 @@ impl ArtistDetailsModel {
             id,
             app_model,
             dispatcher,
+            prefetcher: Prefetcher::new(),
         }
     }
 
@@ -33,6 +37,14 @@ impl ArtistDetailsModel {
             .map_state_opt(|s| s.browser.artist_state(&self.id)?.artist.as_ref())
     }
 
+    pub fn get_followers(&self) -> Option<usize> {
+        self.app_model
+            .get_state()
+            .browser
+            .artist_state(&self.id)?
+            .followers
+    }
+
     pub fn get_list_store(&self) -> Option<impl Deref<Target = ListStore<AlbumModel>> + '_> {
         self.app_model
             .map_state_opt(|s| Some(&s.browser.artist_state(&self.id)?.albums))
@@ -58,6 +70,40 @@ impl ArtistDetailsModel {
         self.dispatcher.dispatch(AppAction::ViewAlbum(id));
     }
 
+    pub fn is_release_alert_set(&self) -> bool {
+        self.app_model
+            .get_state()
+            .release_alerts
+            .is_subscribed(&self.id)
+    }
+
+    pub fn has_new_releases(&self) -> bool {
+        self.app_model
+            .get_state()
+            .release_alerts
+            .has_unseen(&self.id)
+    }
+
+    pub fn set_release_alert(&self, enabled: bool) {
+        self.dispatcher
+            .dispatch(AppAction::SetReleaseAlert(self.id.clone(), enabled));
+    }
+
+    pub fn mark_releases_seen(&self) {
+        self.dispatcher
+            .dispatch(AppAction::MarkReleasesSeen(self.id.clone()));
+    }
+
+    pub fn like_top_tracks(&self) {
+        let songs = self.song_list_model().collect();
+        save_tracks_in_bulk(songs, &self.app_model, self.dispatcher.as_ref());
+    }
+
+    // The list was updated, with the pages fetched so far
+    pub fn pages_added(&self) {
+        self.prefetcher.release_fetched();
+    }
+
     pub fn load_more(&self) -> Option<()> {
         let api = self.app_model.get_spotify();
         let state = self.app_model.get_state();
@@ -68,7 +114,7 @@ impl ArtistDetailsModel {
         let offset = next_page.next_offset?;
 
         self.dispatcher
-            .call_spotify_and_dispatch(move || async move {
+            .call_spotify_and_prefetch(&self.prefetcher, offset, move || async move {
                 api.get_artist_albums(&id, offset, batch_size)
                     .await
                     .map(|albums| BrowserAction::AppendArtistReleases(id, albums).into())
@@ -79,6 +125,14 @@ impl ArtistDetailsModel {
 }
 
 impl PlaylistModel for ArtistDetailsModel {
+    fn preview_on_hover(&self) -> bool {
+        self.app_model
+            .get_state()
+            .settings
+            .settings
+            .preview_on_hover
+    }
+
     fn song_list_model(&self) -> SongListModel {
         self.app_model
             .get_state()
@@ -116,6 +170,12 @@ impl PlaylistModel for ArtistDetailsModel {
         }
         group.add_action(&song.make_album_action(self.dispatcher.box_clone(), None));
         group.add_action(&song.make_link_action(None));
+        group.add_action(&song.make_web_player_action(None));
+        group.add_action(&song.make_save_action(
+            Rc::clone(&self.app_model),
+            self.dispatcher.box_clone(),
+            None,
+        ));
         group.add_action(&song.make_queue_action(self.dispatcher.box_clone(), None));
 
         Some(group.upcast())
@@ -135,6 +195,10 @@ impl PlaylistModel for ArtistDetailsModel {
         }
 
         menu.append(Some(&*labels::COPY_LINK), Some("song.copy_link"));
+        menu.append(
+            Some(&*labels::OPEN_IN_WEB_PLAYER),
+            Some("song.open_in_web_player"),
+        );
         menu.append(Some(&*labels::ADD_TO_QUEUE), Some("song.queue"));
         Some(menu.upcast())
     }
@@ -158,6 +222,11 @@ impl PlaylistModel for ArtistDetailsModel {
         true
     }
 
+    fn is_saved(&self, id: &str) -> Option<bool> {
+        let is_saved = self.app_model.get_state().browser.is_track_saved(id);
+        Some(is_saved.unwrap_or(false))
+    }
+
     fn selection(&self) -> Option<Box<dyn Deref<Target = SelectionState> + '_>> {
         Some(Box::new(self.app_model.map_state(|s| &s.selection)))
     }
@@ -184,4 +253,16 @@ impl SimpleHeaderBarModel for ArtistDetailsModel {
         self.dispatcher
             .dispatch(SelectionAction::Select(songs).into());
     }
+
+    fn web_url(&self) -> Option<String> {
+        Some(utils::web_player_url("artist", &self.id))
+    }
+
+    fn shareable(&self) -> Option<Shareable> {
+        Some(Shareable::new(
+            SpotifyUri::Artist(self.id.clone()),
+            self.get_artist_name()?.clone(),
+            None,
+        ))
+    }
 }
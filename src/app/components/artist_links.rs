@@ -0,0 +1,60 @@
+// Songs credit each of their artists with a link of their own, wherever the song is shown (rows, the
+// playback bar, Now Playing), so that any of them can be opened and not just the first one.
+
+use gtk::prelude::*;
+
+use crate::app::models::ArtistRef;
+use crate::app::SpotifyUri;
+
+// Pango markup for a label with use-markup, artists separated by commas
+pub fn artist_links_markup(artists: &[ArtistRef]) -> String {
+    artists
+        .iter()
+        .map(|artist| {
+            let name = glib::markup_escape_text(&artist.name);
+            if artist.id.is_empty() {
+                name.to_string()
+            } else {
+                let uri = glib::markup_escape_text(&format!("spotify:artist:{}", artist.id));
+                format!("<a href=\"{uri}\">{name}</a>")
+            }
+        })
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+// Opens artists when their link is clicked, instead of handing the URI over to the system
+pub fn connect_artist_links(label: &gtk::Label) {
+    label.connect_activate_link(|label, uri| match SpotifyUri::parse(uri) {
+        Some(SpotifyUri::Artist(id)) => {
+            let _ = label.activate_action("app.open_artist", Some(&id.to_variant()));
+            glib::signal::Inhibit(true)
+        }
+        _ => glib::signal::Inhibit(false),
+    });
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn artist(id: &str, name: &str) -> ArtistRef {
+        ArtistRef {
+            id: id.to_string(),
+            name: name.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_artist_links_markup() {
+        let artists = vec![
+            artist("0OdUWJ0sBjDrqHygGUXeCF", "Band of Horses"),
+            artist("", "Simon & Garfunkel"),
+        ];
+        assert_eq!(
+            artist_links_markup(&artists),
+            "<a href=\"spotify:artist:0OdUWJ0sBjDrqHygGUXeCF\">Band of Horses</a>, Simon &amp; Garfunkel"
+        );
+    }
+}
@@ -0,0 +1,74 @@
+use std::rc::Rc;
+
+use crate::app::components::EventListener;
+use crate::app::state::{Device, PlaybackAction, PlaybackEvent};
+use crate::app::{ActionDispatcher, AppEvent, AppModel, Prefetcher};
+
+// How many of the last played songs are used to seed the recommendations
+const AUTOPLAY_SEED_SIZE: usize = 5;
+const AUTOPLAY_BATCH_SIZE: usize = 20;
+
+// When enabled in the preferences, keeps the music going once the queue runs out,
+// by queuing songs recommended by Spotify based on what was played last.
+pub struct Autoplay {
+    app_model: Rc<AppModel>,
+    dispatcher: Box<dyn ActionDispatcher>,
+    // Keyed on the length of the queue, so that we only ask once for more songs at a given point
+    prefetcher: Prefetcher,
+}
+
+impl Autoplay {
+    pub fn new(app_model: Rc<AppModel>, dispatcher: Box<dyn ActionDispatcher>) -> Self {
+        Self {
+            app_model,
+            dispatcher,
+            prefetcher: Prefetcher::new(),
+        }
+    }
+
+    fn queue_recommendations_if_needed(&self) {
+        let state = self.app_model.get_state();
+        let is_enabled = state.settings.settings.autoplay;
+        let playback = &state.playback;
+        let is_local = matches!(playback.current_device(), Device::Local);
+        if !is_enabled || !is_local || playback.next_index().is_some() {
+            return;
+        }
+
+        let seed_tracks = playback.recent_song_ids(AUTOPLAY_SEED_SIZE);
+        if seed_tracks.is_empty() {
+            return;
+        }
+
+        let queue_len = playback.songs().len();
+        let api = self.app_model.get_spotify();
+        self.dispatcher.call_spotify_and_prefetch(
+            &self.prefetcher,
+            queue_len,
+            move || async move {
+                api.get_recommendations(seed_tracks, AUTOPLAY_BATCH_SIZE)
+                    .await
+                    .map(|songs| PlaybackAction::QueueAutoplay(songs).into())
+            },
+        );
+    }
+}
+
+impl EventListener for Autoplay {
+    fn on_event(&mut self, event: &AppEvent) {
+        match event {
+            AppEvent::PlaybackEvent(PlaybackEvent::PlaylistChanged) => {
+                self.prefetcher.release_fetched();
+                self.queue_recommendations_if_needed();
+            }
+            AppEvent::PlaybackEvent(PlaybackEvent::TrackChanged(_))
+            | AppEvent::PlaybackEvent(PlaybackEvent::RepeatModeChanged(_)) => {
+                self.queue_recommendations_if_needed();
+            }
+            AppEvent::PlaybackEvent(PlaybackEvent::SourceChanged) => {
+                self.prefetcher.cancel();
+            }
+            _ => {}
+        }
+    }
+}
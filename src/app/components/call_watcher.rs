@@ -0,0 +1,48 @@
+use futures::channel::mpsc::UnboundedSender;
+use std::rc::Rc;
+
+use crate::app::components::EventListener;
+use crate::app::state::SettingsEvent;
+use crate::app::{AppAction, AppEvent, AppModel};
+
+// Watches for calls going on in other apps (only once the user asked to pause during calls),
+// so that the playback state can pause and resume around them
+pub struct CallWatcher {
+    app_model: Rc<AppModel>,
+    sender: UnboundedSender<AppAction>,
+    started: bool,
+}
+
+impl CallWatcher {
+    pub fn new(app_model: Rc<AppModel>, sender: UnboundedSender<AppAction>) -> Self {
+        Self {
+            app_model,
+            sender,
+            started: false,
+        }
+    }
+
+    // The watcher keeps running if the setting is turned off again: calls are then simply ignored
+    fn start_if_enabled(&mut self) {
+        let enabled = self
+            .app_model
+            .get_state()
+            .settings
+            .settings
+            .pause_during_calls;
+        if enabled && !self.started {
+            self.started = true;
+            crate::player::start_call_watcher(self.sender.clone());
+        }
+    }
+}
+
+impl EventListener for CallWatcher {
+    fn on_event(&mut self, event: &AppEvent) {
+        if let AppEvent::Started
+        | AppEvent::SettingsEvent(SettingsEvent::PauseDuringCallsChanged(true)) = event
+        {
+            self.start_if_enabled();
+        }
+    }
+}
@@ -0,0 +1,54 @@
+use gettextrs::*;
+use gtk::prelude::*;
+use libadwaita::prelude::*;
+
+use crate::app::components::{utils, EventListener};
+use crate::app::AppEvent;
+use crate::crash_report;
+
+// If Spot crashed last time, offers to report it: the saved report goes into a prefilled GitHub issue,
+// which the user gets to review in their browser before anything is submitted
+pub struct CrashReporter {
+    window: gtk::Window,
+}
+
+impl CrashReporter {
+    pub fn new(window: gtk::Window) -> Self {
+        Self { window }
+    }
+
+    fn offer_report(&self, report: String) {
+        let dialog = libadwaita::MessageDialog::builder()
+            .transient_for(&self.window)
+            .modal(true)
+            // translators: Title of the dialog shown on startup after Spot crashed
+            .heading(gettext("Spot quit unexpectedly"))
+            .body(gettext(
+                "Reporting the problem helps fix it. A bug report will open in your browser, with what went wrong filled in, so that you can review it before submitting it.",
+            ))
+            .build();
+        dialog.add_responses(&[
+            // translators: Dialog button, the crash isn't reported
+            ("dismiss", &gettext("Dismiss")),
+            // translators: Dialog button, opens a prefilled bug report in the browser
+            ("report", &gettext("Report Problem")),
+        ]);
+        dialog.set_response_appearance("report", libadwaita::ResponseAppearance::Suggested);
+        dialog.set_default_response(Some("report"));
+
+        dialog.connect_response(Some("report"), move |_, _| {
+            utils::open_in_browser(&crash_report::issue_url(&report));
+        });
+        dialog.present();
+    }
+}
+
+impl EventListener for CrashReporter {
+    fn on_event(&mut self, event: &AppEvent) {
+        if let AppEvent::Started = event {
+            if let Some(report) = crash_report::take_report() {
+                self.offer_report(report);
+            }
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use crate::app::components::display_add_css_provider;
+use crate::app::components::{display_add_css_provider, indicators};
 use gettextrs::gettext;
 use gtk::prelude::*;
 use gtk::subclass::prelude::*;
@@ -29,6 +29,15 @@ mod imp {
         #[template_child]
         pub play_button: TemplateChild<gtk::Button>,
 
+        #[template_child]
+        pub like_all_button: TemplateChild<gtk::Button>,
+
+        #[template_child]
+        pub copy_button: TemplateChild<gtk::Button>,
+
+        #[template_child]
+        pub keep_silence_button: TemplateChild<gtk::ToggleButton>,
+
         #[template_child]
         pub info_button: TemplateChild<gtk::Button>,
 
@@ -89,6 +98,28 @@ impl AlbumHeaderWidget {
         self.imp().like_button.connect_clicked(move |_| f());
     }
 
+    pub fn connect_like_all<F>(&self, f: F)
+    where
+        F: Fn() + 'static,
+    {
+        self.imp().like_all_button.connect_clicked(move |_| f());
+    }
+
+    pub fn connect_copy_track_list<F>(&self, f: F)
+    where
+        F: Fn() + 'static,
+    {
+        self.imp().copy_button.connect_clicked(move |_| f());
+    }
+
+    pub fn connect_keep_silence<F>(&self, f: F)
+    where
+        F: Fn() + 'static,
+    {
+        // Only when clicked, not when updated with set_keep_silence
+        self.imp().keep_silence_button.connect_clicked(move |_| f());
+    }
+
     pub fn connect_info<F>(&self, f: F)
     where
         F: Fn() + 'static,
@@ -107,11 +138,29 @@ impl AlbumHeaderWidget {
     }
 
     pub fn set_liked(&self, is_liked: bool) {
-        self.imp().like_button.set_icon_name(if is_liked {
-            "starred-symbolic"
+        let tooltip = if is_liked {
+            // translators: Tooltip of the button to remove an album from the library, on the album page
+            gettext("Remove from Library")
         } else {
-            "non-starred-symbolic"
-        });
+            // translators: Tooltip of the button to add an album to the library, on the album page
+            gettext("Add to Library")
+        };
+        indicators::set_saved(&self.imp().like_button, is_liked, &tooltip);
+    }
+
+    // Only shown if long silences are skipped at all
+    pub fn set_keep_silence(&self, trimming_enabled: bool, is_kept: bool) {
+        let button = &self.imp().keep_silence_button;
+        let tooltip = if is_kept {
+            // translators: Tooltip of a button on album pages, when the long silences at the end of its tracks are played
+            gettext("Keeping Silences at the End of Tracks")
+        } else {
+            // translators: Tooltip of a button on album pages, when the long silences at the end of its tracks are skipped
+            gettext("Skipping Silences at the End of Tracks")
+        };
+        button.set_visible(trimming_enabled);
+        button.set_active(is_kept);
+        button.set_tooltip_text(Some(&tooltip));
     }
 
     pub fn set_playing(&self, is_playing: bool) {
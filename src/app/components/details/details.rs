@@ -8,11 +8,12 @@ use super::release_details::ReleaseDetailsWindow;
 use super::DetailsModel;
 
 use crate::app::components::{
-    Component, EventListener, HeaderBarComponent, HeaderBarWidget, Playlist, ScrollingHeaderWidget,
+    display_add_artwork_color, Component, EventListener, HeaderBarComponent, HeaderBarWidget,
+    Playlist, ScrollingHeaderWidget,
 };
 use crate::app::dispatch::Worker;
-use crate::app::loader::ImageLoader;
-use crate::app::state::PlaybackEvent;
+use crate::app::loader::{ArtworkColor, ImageLoader};
+use crate::app::state::{PlaybackEvent, SettingsEvent};
 use crate::app::{AppEvent, BrowserEvent};
 
 mod imp {
@@ -30,6 +31,9 @@ mod imp {
         #[template_child]
         pub headerbar: TemplateChild<HeaderBarWidget>,
 
+        #[template_child]
+        pub header_clamp: TemplateChild<libadwaita::Clamp>,
+
         #[template_child]
         pub header_widget: TemplateChild<AlbumHeaderWidget>,
 
@@ -132,6 +136,30 @@ impl AlbumDetailsWidget {
         self.imp().header_mobile.connect_play(f);
     }
 
+    fn connect_like_all<F>(&self, f: F)
+    where
+        F: Fn() + Clone + 'static,
+    {
+        self.imp().header_widget.connect_like_all(f.clone());
+        self.imp().header_mobile.connect_like_all(f);
+    }
+
+    fn connect_copy_track_list<F>(&self, f: F)
+    where
+        F: Fn() + Clone + 'static,
+    {
+        self.imp().header_widget.connect_copy_track_list(f.clone());
+        self.imp().header_mobile.connect_copy_track_list(f);
+    }
+
+    fn connect_keep_silence<F>(&self, f: F)
+    where
+        F: Fn() + Clone + 'static,
+    {
+        self.imp().header_widget.connect_keep_silence(f.clone());
+        self.imp().header_mobile.connect_keep_silence(f);
+    }
+
     fn connect_info<F>(&self, f: F)
     where
         F: Fn() + Clone + 'static,
@@ -145,6 +173,15 @@ impl AlbumDetailsWidget {
         self.imp().header_mobile.set_liked(is_liked);
     }
 
+    fn set_keep_silence(&self, trimming_enabled: bool, is_kept: bool) {
+        self.imp()
+            .header_widget
+            .set_keep_silence(trimming_enabled, is_kept);
+        self.imp()
+            .header_mobile
+            .set_keep_silence(trimming_enabled, is_kept);
+    }
+
     fn set_playing(&self, is_playing: bool) {
         self.imp().header_widget.set_playing(is_playing);
         self.imp().header_mobile.set_playing(is_playing);
@@ -165,6 +202,12 @@ impl AlbumDetailsWidget {
         self.imp().header_mobile.set_artwork(art);
     }
 
+    fn set_artwork_color(&self, color: ArtworkColor) {
+        let class = display_add_artwork_color(color);
+        self.imp().header_clamp.add_css_class(&class);
+        self.imp().headerbar.add_classes(&[&class]);
+    }
+
     fn connect_artist_clicked<F>(&self, f: F)
     where
         F: Fn() + Clone + 'static,
@@ -209,6 +252,12 @@ impl Details {
 
         widget.connect_play(clone!(@weak model => move || model.toggle_play_album()));
 
+        widget.connect_like_all(clone!(@weak model => move || model.like_all_tracks()));
+
+        widget.connect_copy_track_list(clone!(@weak model => move || model.copy_track_list()));
+
+        widget.connect_keep_silence(clone!(@weak model => move || model.toggle_keep_silence()));
+
         widget.connect_header();
 
         widget.connect_bottom_edge(clone!(@weak model => move || {
@@ -227,13 +276,20 @@ impl Details {
             modal.set_visible(true);
         }));
 
-        Self {
+        let details = Self {
             model,
             worker,
             widget,
             modal,
             children: vec![playlist, headerbar],
-        }
+        };
+        details.update_keep_silence();
+        details
+    }
+
+    fn update_keep_silence(&self) {
+        let (trimming_enabled, is_kept) = self.model.keeps_silence();
+        self.widget.set_keep_silence(trimming_enabled, is_kept);
     }
 
     fn update_liked(&self) {
@@ -279,15 +335,17 @@ impl Details {
             );
 
             if let Some(art) = album.art.clone() {
+                let loader = ImageLoader::for_widget(&self.widget);
                 let widget = self.widget.downgrade();
 
                 self.worker.send_local_task(async move {
-                    let pixbuf = ImageLoader::new()
-                        .load_remote(&art[..], "jpg", 320, 320)
-                        .await;
+                    let pixbuf = loader.load_remote(&art[..], "jpg", 320, 320).await;
                     if let (Some(widget), Some(ref pixbuf)) = (widget.upgrade(), pixbuf) {
                         widget.set_artwork(pixbuf);
                         widget.set_loaded();
+                        if let Some(color) = loader.load_artwork_color(&art[..], pixbuf).await {
+                            widget.set_artwork_color(color);
+                        }
                     }
                 });
             } else {
@@ -322,6 +380,9 @@ impl EventListener for Details {
             {
                 self.update_liked();
             }
+            AppEvent::SettingsEvent(SettingsEvent::SilenceTrimmingChanged) => {
+                self.update_keep_silence();
+            }
             AppEvent::PlaybackEvent(PlaybackEvent::PlaybackPaused) => {
                 self.update_playing(false);
             }
@@ -332,4 +393,12 @@ impl EventListener for Details {
         }
         self.broadcast_event(event);
     }
+
+    fn on_mount(&mut self) {
+        self.broadcast_mount();
+    }
+
+    fn on_unmount(&mut self) {
+        self.broadcast_unmount();
+    }
 }
@@ -5,16 +5,22 @@ use std::ops::Deref;
 use std::rc::Rc;
 
 use crate::api::SpotifyApiError;
+use crate::app::components::copy_track_list;
 use crate::app::components::labels;
+use crate::app::components::save_tracks_in_bulk;
+use crate::app::components::utils;
 use crate::app::components::HeaderBarModel;
 use crate::app::components::PlaylistModel;
+use crate::app::components::Shareable;
 use crate::app::components::SimpleHeaderBarModel;
 use crate::app::components::SimpleHeaderBarModelWrapper;
 use crate::app::dispatch::ActionDispatcher;
 use crate::app::models::*;
 use crate::app::state::SelectionContext;
-use crate::app::state::{BrowserAction, PlaybackAction, SelectionAction, SelectionState};
-use crate::app::{AppAction, AppEvent, AppModel, AppState, BatchQuery, SongsSource};
+use crate::app::state::{
+    BrowserAction, PendingChange, PlaybackAction, SelectionAction, SelectionState, SettingsAction,
+};
+use crate::app::{AppAction, AppEvent, AppModel, AppState, BatchQuery, SongsSource, SpotifyUri};
 
 pub struct DetailsModel {
     pub id: String,
@@ -79,22 +85,20 @@ impl DetailsModel {
 
     pub fn toggle_save_album(&self) {
         if let Some(album) = self.get_album_description() {
-            let id = album.id.clone();
-            let is_liked = album.is_liked;
+            // Can be undone for a little while
+            if album.is_liked {
+                let change = PendingChange::UnsaveAlbum(Box::new((*album).clone()));
+                self.dispatcher.dispatch(AppAction::DeferChange(change));
+                return;
+            }
 
+            let id = album.id.clone();
             let api = self.app_model.get_spotify();
-
             self.dispatcher
                 .call_spotify_and_dispatch(move || async move {
-                    if !is_liked {
-                        api.save_album(&id)
-                            .await
-                            .map(|album| BrowserAction::SaveAlbum(Box::new(album)).into())
-                    } else {
-                        api.remove_saved_album(&id)
-                            .await
-                            .map(|_| BrowserAction::UnsaveAlbum(id).into())
-                    }
+                    api.save_album(&id)
+                        .await
+                        .map(|album| BrowserAction::SaveAlbum(Box::new(album)).into())
                 });
         }
     }
@@ -130,6 +134,40 @@ impl DetailsModel {
         }
     }
 
+    pub fn like_all_tracks(&self) {
+        let songs = self.song_list_model().collect();
+        save_tracks_in_bulk(songs, &self.app_model, self.dispatcher.as_ref());
+    }
+
+    pub fn copy_track_list(&self) {
+        copy_track_list(
+            SongsSource::Album(self.id.clone()),
+            &self.song_list_model(),
+            &self.app_model,
+            self.dispatcher.as_ref(),
+        );
+    }
+
+    // Whether long silences are skipped at all, and whether this album keeps them anyway
+    pub fn keeps_silence(&self) -> (bool, bool) {
+        let state = self.state();
+        let silence_trimming = &state.settings.settings.silence_trimming;
+        (
+            silence_trimming.enabled,
+            silence_trimming.kept_albums.contains(&self.id),
+        )
+    }
+
+    pub fn toggle_keep_silence(&self) {
+        let mut silence_trimming = self.state().settings.settings.silence_trimming.clone();
+        if !silence_trimming.kept_albums.remove(&self.id) {
+            silence_trimming.kept_albums.insert(self.id.clone());
+        }
+        silence_trimming.save_kept_albums();
+        self.dispatcher
+            .dispatch(SettingsAction::ChangeSettings.into());
+    }
+
     pub fn load_more(&self) -> Option<()> {
         let last_batch = self.song_list_model().last_batch()?;
         let query = BatchQuery {
@@ -162,6 +200,14 @@ impl DetailsModel {
 }
 
 impl PlaylistModel for DetailsModel {
+    fn preview_on_hover(&self) -> bool {
+        self.app_model
+            .get_state()
+            .settings
+            .settings
+            .preview_on_hover
+    }
+
     fn song_list_model(&self) -> SongListModel {
         self.app_model
             .get_state()
@@ -199,6 +245,11 @@ impl PlaylistModel for DetailsModel {
         true
     }
 
+    fn is_saved(&self, id: &str) -> Option<bool> {
+        let is_saved = self.app_model.get_state().browser.is_track_saved(id);
+        Some(is_saved.unwrap_or(false))
+    }
+
     fn selection(&self) -> Option<Box<dyn Deref<Target = SelectionState> + '_>> {
         Some(Box::new(self.app_model.map_state(|s| &s.selection)))
     }
@@ -228,6 +279,12 @@ impl PlaylistModel for DetailsModel {
             group.add_action(&view_artist);
         }
         group.add_action(&song.make_link_action(None));
+        group.add_action(&song.make_web_player_action(None));
+        group.add_action(&song.make_save_action(
+            Rc::clone(&self.app_model),
+            self.dispatcher.box_clone(),
+            None,
+        ));
         group.add_action(&song.make_queue_action(self.dispatcher.box_clone(), None));
 
         Some(group.upcast())
@@ -246,6 +303,10 @@ impl PlaylistModel for DetailsModel {
         }
 
         menu.append(Some(&*labels::COPY_LINK), Some("song.copy_link"));
+        menu.append(
+            Some(&*labels::OPEN_IN_WEB_PLAYER),
+            Some("song.open_in_web_player"),
+        );
         menu.append(Some(&*labels::ADD_TO_QUEUE), Some("song.queue"));
         Some(menu.upcast())
     }
@@ -269,4 +330,17 @@ impl SimpleHeaderBarModel for DetailsModel {
         self.dispatcher
             .dispatch(SelectionAction::Select(songs).into());
     }
+
+    fn web_url(&self) -> Option<String> {
+        Some(utils::web_player_url("album", &self.id))
+    }
+
+    fn shareable(&self) -> Option<Shareable> {
+        let album = self.get_album_description()?;
+        Some(Shareable::new(
+            SpotifyUri::Album(self.id.clone()),
+            album.title.clone(),
+            Some(album.artists_name()),
+        ))
+    }
 }
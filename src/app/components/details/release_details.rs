@@ -2,7 +2,7 @@ use gtk::subclass::prelude::*;
 use gtk::CompositeTemplate;
 use libadwaita::subclass::prelude::*;
 
-use crate::app::components::labels;
+use crate::app::components::{display, labels};
 
 mod imp {
 
@@ -80,7 +80,7 @@ impl ReleaseDetailsWindow {
 
         widget.label.set_text(label);
         widget.release.set_text(release_date);
-        widget.tracks.set_text(&track_count.to_string());
+        widget.tracks.set_text(&display::number(track_count));
         widget.copyright.set_text(copyright);
     }
 }
@@ -0,0 +1,128 @@
+use gtk::prelude::*;
+use gtk::subclass::prelude::*;
+use gtk::CompositeTemplate;
+use std::rc::Rc;
+
+use crate::app::components::display;
+use crate::app::AppModel;
+use crate::player::PlaybackMetrics;
+
+mod imp {
+
+    use super::*;
+    use libadwaita::subclass::prelude::*;
+
+    #[derive(Debug, Default, CompositeTemplate)]
+    #[template(resource = "/dev/alextren/Spot/components/diagnostics.ui")]
+    pub struct DiagnosticsWindow {
+        #[template_child]
+        pub stack: TemplateChild<gtk::Stack>,
+
+        #[template_child]
+        pub cache_label: TemplateChild<gtk::Label>,
+
+        #[template_child]
+        pub stalls_label: TemplateChild<gtk::Label>,
+
+        #[template_child]
+        pub latency_label: TemplateChild<gtk::Label>,
+
+        #[template_child]
+        pub played_label: TemplateChild<gtk::Label>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for DiagnosticsWindow {
+        const NAME: &'static str = "DiagnosticsWindow";
+        type Type = super::DiagnosticsWindow;
+        type ParentType = libadwaita::Window;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for DiagnosticsWindow {}
+    impl WidgetImpl for DiagnosticsWindow {}
+    impl WindowImpl for DiagnosticsWindow {}
+    impl AdwWindowImpl for DiagnosticsWindow {}
+}
+
+glib::wrapper! {
+    pub struct DiagnosticsWindow(ObjectSubclass<imp::DiagnosticsWindow>) @extends gtk::Widget, gtk::Window, libadwaita::Window;
+}
+
+fn set_metric<T>(label: &gtk::Label, value: Option<T>, format: impl Fn(T) -> String) {
+    label.set_label(&value.map(format).unwrap_or_else(|| "–".to_string()));
+}
+
+impl DiagnosticsWindow {
+    fn new() -> Self {
+        glib::Object::new()
+    }
+
+    fn show_metrics(&self, enabled: bool, metrics: &PlaybackMetrics) {
+        let widget = self.imp();
+        if metrics.is_empty() {
+            let page = if enabled { "empty" } else { "disabled" };
+            widget.stack.set_visible_child_name(page);
+            return;
+        }
+        widget.stack.set_visible_child_name("metrics");
+        set_metric(
+            &widget.cache_label,
+            metrics.cache_hit_rate(),
+            display::percent_label,
+        );
+        set_metric(
+            &widget.stalls_label,
+            metrics.stalls_per_hour(),
+            display::per_hour_label,
+        );
+        set_metric(
+            &widget.latency_label,
+            metrics.average_start_latency(),
+            |latency| display::milliseconds_label(latency.as_millis()),
+        );
+        widget
+            .played_label
+            .set_label(&display::listening_time_label(metrics.played_ms / 60_000));
+    }
+}
+
+// Shows the playback metrics collected by the player, if the user opted in: how often songs come from
+// the cache, how often playback has to wait for the network, and how long songs take to start.
+// Metrics collected before they were turned off are still shown.
+pub struct Diagnostics {
+    parent: gtk::Window,
+    window: DiagnosticsWindow,
+    app_model: Rc<AppModel>,
+}
+
+impl Diagnostics {
+    pub fn new(parent: gtk::Window, app_model: Rc<AppModel>) -> Self {
+        Self {
+            parent,
+            window: DiagnosticsWindow::new(),
+            app_model,
+        }
+    }
+
+    // The metrics are read again every time, as the player keeps saving them while Spot is open
+    pub fn show_self(&self) {
+        let enabled = self
+            .app_model
+            .get_state()
+            .settings
+            .settings
+            .playback_metrics;
+        self.window.show_metrics(enabled, &PlaybackMetrics::load());
+        self.window.set_transient_for(Some(&self.parent));
+        self.window.set_modal(true);
+        self.window.set_visible(true);
+    }
+}
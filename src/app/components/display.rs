@@ -0,0 +1,263 @@
+use gettextrs::*;
+
+// Everything that shows a number to the user goes through here: counts get proper plural forms
+// (some languages have more than two), and large numbers are grouped the way the language does it.
+
+fn group_digits(n: usize, separator: &str) -> String {
+    let digits = n.to_string();
+    let mut grouped = String::new();
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i) % 3 == 0 {
+            grouped.push_str(separator);
+        }
+        grouped.push(digit);
+    }
+    grouped
+}
+
+pub fn number(n: usize) -> String {
+    // translators: Separates groups of three digits in large numbers, as in "1,000,000". Use whatever your language uses (a dot, a space...).
+    group_digits(n, &pgettext("thousands separator", ","))
+}
+
+pub fn n_tracks_label(n: usize) -> String {
+    // this is just to fool xgettext, it doesn't like macros (or rust for that matter) :(
+    if cfg!(debug_assertions) {
+        // translators: How many tracks a playlist has, shown under its title.
+        ngettext("{} track", "{} tracks", n as u32);
+    }
+    ngettext!("{} track", "{} tracks", n as u32, number(n))
+}
+
+pub fn n_followers_label(n: usize) -> String {
+    // this is just to fool xgettext, it doesn't like macros (or rust for that matter) :(
+    if cfg!(debug_assertions) {
+        // translators: How many people follow an artist on Spotify, shown on the artist's page.
+        ngettext("{} follower", "{} followers", n as u32);
+    }
+    ngettext!("{} follower", "{} followers", n as u32, number(n))
+}
+
+pub fn n_songs_selected_label(n: usize) -> String {
+    // this is just to fool xgettext, it doesn't like macros (or rust for that matter) :(
+    if cfg!(debug_assertions) {
+        // translators: This shows up when in selection mode. This text should be as short as possible.
+        ngettext("{} song selected", "{} songs selected", n as u32);
+    }
+    ngettext!("{} song selected", "{} songs selected", n as u32, number(n))
+}
+
+pub fn add_n_to_queue_label(n: usize) -> String {
+    // this is just to fool xgettext, it doesn't like macros (or rust for that matter) :(
+    if cfg!(debug_assertions) {
+        // translators: This is a button of the selection toolbar that adds the selected tracks at the end of the play queue. This text should be as short as possible.
+        ngettext("Add {} to queue", "Add {} to queue", n as u32);
+    }
+    ngettext!("Add {} to queue", "Add {} to queue", n as u32, number(n))
+}
+
+pub fn add_n_to_playlist_label(n: usize) -> String {
+    // this is just to fool xgettext, it doesn't like macros (or rust for that matter) :(
+    if cfg!(debug_assertions) {
+        // translators: This is a menu button of the selection toolbar that adds the selected tracks to a playlist chosen by the user. This text should be as short as possible.
+        ngettext("Add {} to playlist...", "Add {} to playlist...", n as u32);
+    }
+    ngettext!(
+        "Add {} to playlist...",
+        "Add {} to playlist...",
+        n as u32,
+        number(n)
+    )
+}
+
+pub fn remove_n_label(n: usize) -> String {
+    // this is just to fool xgettext, it doesn't like macros (or rust for that matter) :(
+    if cfg!(debug_assertions) {
+        // translators: This is a button of the selection toolbar that removes the selected tracks from the queue or playlist being edited. This text should be as short as possible.
+        ngettext("Remove {}", "Remove {}", n as u32);
+    }
+    ngettext!("Remove {}", "Remove {}", n as u32, number(n))
+}
+
+pub fn remove_n_from_library_label(n: usize) -> String {
+    // this is just to fool xgettext, it doesn't like macros (or rust for that matter) :(
+    if cfg!(debug_assertions) {
+        // translators: This is a button of the selection toolbar that removes the selected tracks from the user's saved tracks. This text should be as short as possible.
+        ngettext("Remove {} from library", "Remove {} from library", n as u32);
+    }
+    ngettext!(
+        "Remove {} from library",
+        "Remove {} from library",
+        n as u32,
+        number(n)
+    )
+}
+
+pub fn saving_tracks_progress_label(saved: usize, total: usize) -> String {
+    // this is just to fool xgettext, it doesn't like macros (or rust for that matter) :(
+    if cfg!(debug_assertions) {
+        // translators: This is a notification shown while many tracks are being added to Liked Songs at once; it reads "Adding to Liked Songs… <done>/<total>".
+        gettext("Adding to Liked Songs… {}/{}");
+    }
+    gettext!("Adding to Liked Songs… {}/{}", number(saved), number(total))
+}
+
+pub fn n_tracks_saved_label(n: usize) -> String {
+    // this is just to fool xgettext, it doesn't like macros (or rust for that matter) :(
+    if cfg!(debug_assertions) {
+        // translators: This is a notification shown after many tracks were added to Liked Songs at once.
+        ngettext(
+            "{} track added to Liked Songs",
+            "{} tracks added to Liked Songs",
+            n as u32,
+        );
+    }
+    ngettext!(
+        "{} track added to Liked Songs",
+        "{} tracks added to Liked Songs",
+        n as u32,
+        number(n)
+    )
+}
+
+pub fn n_tracks_removed_from_playlist_label(n: usize) -> String {
+    // this is just to fool xgettext, it doesn't like macros (or rust for that matter) :(
+    if cfg!(debug_assertions) {
+        // translators: This is a notification shown after removing tracks from a playlist, with a button to undo it.
+        ngettext(
+            "{} track removed from the playlist",
+            "{} tracks removed from the playlist",
+            n as u32,
+        );
+    }
+    ngettext!(
+        "{} track removed from the playlist",
+        "{} tracks removed from the playlist",
+        n as u32,
+        number(n)
+    )
+}
+
+pub fn pending_scrobbles_label(n: usize) -> String {
+    // this is just to fool xgettext, it doesn't like macros (or rust for that matter) :(
+    if cfg!(debug_assertions) {
+        // translators: Shown in preferences when some listens couldn't be submitted yet (while offline, for instance).
+        ngettext(
+            "{} listen waiting to be submitted",
+            "{} listens waiting to be submitted",
+            n as u32,
+        );
+    }
+    ngettext!(
+        "{} listen waiting to be submitted",
+        "{} listens waiting to be submitted",
+        n as u32,
+        number(n)
+    )
+}
+
+pub fn matching_history_progress_label(done: usize, total: usize) -> String {
+    // this is just to fool xgettext, it doesn't like macros (or rust for that matter) :(
+    if cfg!(debug_assertions) {
+        // translators: Shown while the songs of an imported listening history are looked up on Spotify; it reads "Looking up songs… <done>/<total>".
+        gettext("Looking up songs… {}/{}");
+    }
+    gettext!("Looking up songs… {}/{}", number(done), number(total))
+}
+
+pub fn history_matches_label(found: usize, total: usize) -> String {
+    // this is just to fool xgettext, it doesn't like macros (or rust for that matter) :(
+    if cfg!(debug_assertions) {
+        // translators: Shown once the songs of an imported listening history were looked up on Spotify; it reads "<found> of <total> songs found on Spotify".
+        ngettext(
+            "{} of {} song found on Spotify",
+            "{} of {} songs found on Spotify",
+            total as u32,
+        );
+    }
+    ngettext!(
+        "{} of {} song found on Spotify",
+        "{} of {} songs found on Spotify",
+        total as u32,
+        number(found),
+        number(total)
+    )
+}
+
+pub fn n_plays_label(n: usize) -> String {
+    // this is just to fool xgettext, it doesn't like macros (or rust for that matter) :(
+    if cfg!(debug_assertions) {
+        // translators: How many times songs were played, shown in the listening stats.
+        ngettext("{} play", "{} plays", n as u32);
+    }
+    ngettext!("{} play", "{} plays", n as u32, number(n))
+}
+
+pub fn listening_time_label(minutes: u64) -> String {
+    let hours = minutes / 60;
+    let minutes = minutes % 60;
+    // this is just to fool xgettext, it doesn't like macros (or rust for that matter) :(
+    if cfg!(debug_assertions) {
+        // translators: Total time spent listening, shown in the listening stats; it reads "<hours> h <minutes> min".
+        gettext("{} h {} min");
+        // translators: Total time spent listening (under an hour), shown in the listening stats.
+        gettext("{} min");
+    }
+    if hours > 0 {
+        gettext!("{} h {} min", number(hours as usize), minutes)
+    } else {
+        gettext!("{} min", minutes)
+    }
+}
+
+pub fn streaming_bitrate_label(kbps: u32) -> String {
+    // this is just to fool xgettext, it doesn't like macros (or rust for that matter) :(
+    if cfg!(debug_assertions) {
+        // translators: This shows the quality of the audio currently playing, in kilobits per second.
+        gettext("Streaming at {} kbps");
+    }
+    gettext!("Streaming at {} kbps", kbps)
+}
+
+pub fn percent_label(ratio: f64) -> String {
+    let percent = (ratio * 100.0).round() as usize;
+    // this is just to fool xgettext, it doesn't like macros (or rust for that matter) :(
+    if cfg!(debug_assertions) {
+        // translators: A percentage, as in "42 %". Use whatever your language uses (no space, a narrow space...).
+        gettext("{} %");
+    }
+    gettext!("{} %", number(percent))
+}
+
+pub fn per_hour_label(rate: f64) -> String {
+    // this is just to fool xgettext, it doesn't like macros (or rust for that matter) :(
+    if cfg!(debug_assertions) {
+        // translators: How often something happened, in the playback diagnostics (for instance "1.5 per hour").
+        gettext("{} per hour");
+    }
+    gettext!("{} per hour", format!("{:.1}", rate))
+}
+
+pub fn milliseconds_label(ms: u128) -> String {
+    // this is just to fool xgettext, it doesn't like macros (or rust for that matter) :(
+    if cfg!(debug_assertions) {
+        // translators: A duration in milliseconds, in the playback diagnostics.
+        gettext("{} ms");
+    }
+    gettext!("{} ms", number(ms as usize))
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_group_digits() {
+        assert_eq!(group_digits(0, ","), "0");
+        assert_eq!(group_digits(999, ","), "999");
+        assert_eq!(group_digits(1000, ","), "1,000");
+        assert_eq!(group_digits(123456, "."), "123.456");
+        assert_eq!(group_digits(1234567, "\u{202f}"), "1\u{202f}234\u{202f}567");
+    }
+}
@@ -1,11 +1,14 @@
 use std::rc::Rc;
 
+use gettextrs::gettext;
 use glib::Cast;
 use gtk::prelude::*;
 
 use crate::app::{
-    components::{Component, EventListener, ListenerComponent},
-    state::{SelectionContext, SelectionEvent},
+    components::{
+        sidebar::SidebarDestination, utils, Component, EventListener, ListenerComponent, Shareable,
+    },
+    state::{ScreenName, SelectionContext, SelectionEvent},
     ActionDispatcher, AppAction, AppEvent, AppModel, BrowserAction, BrowserEvent,
 };
 
@@ -16,12 +19,16 @@ pub trait HeaderBarModel {
     fn title_updated(&self, event: &AppEvent) -> bool;
     fn go_back(&self);
     fn can_go_back(&self) -> bool;
+    fn breadcrumbs(&self) -> Vec<(ScreenName, String)>;
+    fn go_back_to(&self, screen: ScreenName);
     fn selection_context(&self) -> Option<SelectionContext>;
     fn can_select_all(&self) -> bool;
     fn start_selection(&self);
     fn select_all(&self);
     fn cancel_selection(&self);
     fn selected_count(&self) -> usize;
+    fn web_url(&self) -> Option<String>;
+    fn shareable(&self) -> Option<Shareable>;
 }
 
 pub struct DefaultHeaderBarModel {
@@ -65,6 +72,15 @@ impl HeaderBarModel for DefaultHeaderBarModel {
         self.app_model.get_state().browser.can_pop()
     }
 
+    fn breadcrumbs(&self) -> Vec<(ScreenName, String)> {
+        common::breadcrumbs(&self.app_model)
+    }
+
+    fn go_back_to(&self, screen: ScreenName) {
+        self.dispatcher
+            .dispatch(BrowserAction::NavigationPopTo(screen).into())
+    }
+
     fn selection_context(&self) -> Option<SelectionContext> {
         self.selection_context.clone()
     }
@@ -89,6 +105,14 @@ impl HeaderBarModel for DefaultHeaderBarModel {
     fn selected_count(&self) -> usize {
         self.app_model.get_state().selection.count()
     }
+
+    fn web_url(&self) -> Option<String> {
+        None
+    }
+
+    fn shareable(&self) -> Option<Shareable> {
+        None
+    }
 }
 
 pub trait SimpleHeaderBarModel {
@@ -96,6 +120,8 @@ pub trait SimpleHeaderBarModel {
     fn title_updated(&self, event: &AppEvent) -> bool;
     fn selection_context(&self) -> Option<SelectionContext>;
     fn select_all(&self);
+    fn web_url(&self) -> Option<String>;
+    fn shareable(&self) -> Option<Shareable>;
 }
 
 pub struct SimpleHeaderBarModelWrapper<M> {
@@ -139,6 +165,15 @@ where
         self.app_model.get_state().browser.can_pop()
     }
 
+    fn breadcrumbs(&self) -> Vec<(ScreenName, String)> {
+        common::breadcrumbs(&self.app_model)
+    }
+
+    fn go_back_to(&self, screen: ScreenName) {
+        self.dispatcher
+            .dispatch(BrowserAction::NavigationPopTo(screen).into())
+    }
+
     fn selection_context(&self) -> Option<SelectionContext> {
         self.wrapped_model.selection_context()
     }
@@ -165,12 +200,62 @@ where
     fn selected_count(&self) -> usize {
         self.app_model.get_state().selection.count()
     }
+
+    fn web_url(&self) -> Option<String> {
+        self.wrapped_model.web_url()
+    }
+
+    fn shareable(&self) -> Option<Shareable> {
+        self.wrapped_model.shareable()
+    }
 }
 
 mod common {
 
     use super::*;
 
+    // The screens we can go back to, with a title to show for each
+    pub fn breadcrumbs(app_model: &AppModel) -> Vec<(ScreenName, String)> {
+        let state = app_model.get_state();
+        let mut trail = state.browser.navigation_trail();
+        trail.pop();
+        trail
+            .into_iter()
+            .map(|(screen, title)| {
+                let title = match (&screen, title) {
+                    (_, Some(title)) => title,
+                    (ScreenName::Home, None) => state
+                        .browser
+                        .home_state()
+                        .and_then(|home| SidebarDestination::from_home_page(home.visible_page))
+                        .map(|dest| dest.title())
+                        .unwrap_or_else(|| gettext("Library")),
+                    // translators: Shown in the headerbar when going back to the search results is possible
+                    (ScreenName::Search, None) => gettext("Search"),
+                    // Not loaded yet
+                    (_, None) => "…".to_string(),
+                };
+                (screen, title)
+            })
+            .collect()
+    }
+
+    fn update_breadcrumbs<Model>(widget: &HeaderBarWidget, model: &Rc<Model>)
+    where
+        Model: HeaderBarModel + 'static,
+    {
+        let breadcrumbs = model.breadcrumbs();
+        let titles: Vec<String> = breadcrumbs.iter().map(|(_, title)| title.clone()).collect();
+        widget.set_breadcrumbs(
+            &titles,
+            clone!(@weak model => move |i| {
+                if let Some((screen, _)) = breadcrumbs.get(i) {
+                    model.go_back_to(screen.clone());
+                }
+            }),
+        );
+    }
+
     pub fn update_for_event<Model>(event: &AppEvent, widget: &HeaderBarWidget, model: &Rc<Model>)
     where
         Model: HeaderBarModel + 'static,
@@ -188,6 +273,7 @@ mod common {
             | AppEvent::BrowserEvent(BrowserEvent::NavigationHidden(_)) => {
                 model.cancel_selection();
                 widget.set_can_go_back(model.can_go_back());
+                update_breadcrumbs(widget, model);
             }
             event if model.title_updated(event) => {
                 widget.set_title(model.title().as_ref().map(|s| &s[..]));
@@ -204,11 +290,24 @@ mod common {
         widget.connect_select_all(clone!(@weak model => move || model.select_all()));
         widget.connect_selection_cancel(clone!(@weak model => move || model.cancel_selection()));
         widget.connect_go_back(clone!(@weak model => move || model.go_back()));
+        widget.connect_open_in_web_player(clone!(@weak model => move || {
+            if let Some(url) = model.web_url() {
+                utils::open_in_browser(&url);
+            }
+        }));
 
         widget.set_title(model.title().as_ref().map(|s| &s[..]));
         widget.set_selection_possible(model.selection_context().is_some());
         widget.set_select_all_possible(model.can_select_all());
         widget.set_can_go_back(model.can_go_back());
+        update_breadcrumbs(widget, model);
+        widget.set_open_in_web_player_possible(model.web_url().is_some());
+
+        widget.bind_share_menu(
+            clone!(@weak model => @default-return None, move || model.shareable()),
+        );
+        // Anything with a public page can be shared, once loaded
+        widget.set_share_possible(model.web_url().is_some());
     }
 }
 
@@ -293,4 +392,12 @@ where
         common::update_for_event(event, &self.widget, &self.model);
         self.broadcast_event(event);
     }
+
+    fn on_mount(&mut self) {
+        self.broadcast_mount();
+    }
+
+    fn on_unmount(&mut self) {
+        self.broadcast_unmount();
+    }
 }
@@ -3,7 +3,7 @@ use gtk::subclass::prelude::*;
 use gtk::CompositeTemplate;
 use libadwaita::subclass::prelude::BinImpl;
 
-use crate::app::components::labels;
+use crate::app::components::{bind_share_menu, display, Shareable};
 
 mod imp {
 
@@ -21,6 +21,12 @@ mod imp {
         #[template_child]
         pub go_back: TemplateChild<gtk::Button>,
 
+        #[template_child]
+        pub breadcrumbs_container: TemplateChild<gtk::Box>,
+
+        #[template_child]
+        pub breadcrumbs: TemplateChild<gtk::Box>,
+
         #[template_child]
         pub title: TemplateChild<libadwaita::WindowTitle>,
 
@@ -30,6 +36,12 @@ mod imp {
         #[template_child]
         pub start_selection: TemplateChild<gtk::Button>,
 
+        #[template_child]
+        pub open_in_web_player: TemplateChild<gtk::Button>,
+
+        #[template_child]
+        pub share: TemplateChild<gtk::MenuButton>,
+
         #[template_child]
         pub select_all: TemplateChild<gtk::Button>,
 
@@ -104,6 +116,20 @@ impl HeaderBarWidget {
         self.imp().cancel.connect_clicked(move |_| f());
     }
 
+    pub fn connect_open_in_web_player<F>(&self, f: F)
+    where
+        F: Fn() + 'static,
+    {
+        self.imp().open_in_web_player.connect_clicked(move |_| f());
+    }
+
+    pub fn bind_share_menu<F>(&self, shareable: F)
+    where
+        F: Fn() -> Option<Shareable> + Clone + 'static,
+    {
+        bind_share_menu(&self.imp().share, shareable);
+    }
+
     pub fn connect_go_back<F>(&self, f: F)
     where
         F: Fn() + 'static,
@@ -119,6 +145,11 @@ impl HeaderBarWidget {
                 "show-start-title-buttons",
             )
             .build();
+        // Not enough room for the breadcrumbs when folded, the back button will do
+        leaflet
+            .bind_property("folded", &*self.imp().breadcrumbs_container, "visible")
+            .invert_boolean()
+            .build();
         leaflet.notify("folded");
     }
 
@@ -126,10 +157,59 @@ impl HeaderBarWidget {
         self.imp().go_back.set_visible(can_go_back);
     }
 
+    // The screens below the current one, from the bottom of the stack: clicking one goes back to it
+    pub fn set_breadcrumbs<F>(&self, titles: &[String], on_selected: F)
+    where
+        F: Fn(usize) + Clone + 'static,
+    {
+        let breadcrumbs = &*self.imp().breadcrumbs;
+        while let Some(child) = breadcrumbs.first_child() {
+            breadcrumbs.remove(&child);
+        }
+
+        for (i, title) in titles.iter().enumerate() {
+            if i > 0 {
+                let separator = gtk::Label::new(Some("›"));
+                separator.add_css_class("dim-label");
+                breadcrumbs.append(&separator);
+            }
+
+            let label = gtk::Label::builder()
+                .label(title)
+                .ellipsize(gtk::pango::EllipsizeMode::End)
+                .max_width_chars(16)
+                .build();
+            let button = gtk::Button::builder()
+                .child(&label)
+                .tooltip_text(title)
+                .has_frame(false)
+                .build();
+            let on_selected = on_selected.clone();
+            button.connect_clicked(move |_| on_selected(i));
+            breadcrumbs.append(&button);
+        }
+
+        // With a single screen to go back to, that's what the back button is for
+        breadcrumbs.set_visible(titles.len() > 1);
+    }
+
+    // For pages with an action of their own
+    pub fn pack_end(&self, widget: &impl IsA<gtk::Widget>) {
+        self.imp().main_header.pack_end(widget);
+    }
+
     pub fn set_selection_possible(&self, possible: bool) {
         self.imp().start_selection.set_visible(possible);
     }
 
+    pub fn set_open_in_web_player_possible(&self, possible: bool) {
+        self.imp().open_in_web_player.set_visible(possible);
+    }
+
+    pub fn set_share_possible(&self, possible: bool) {
+        self.imp().share.set_visible(possible);
+    }
+
     pub fn set_select_all_possible(&self, possible: bool) {
         self.imp().select_all.set_visible(possible);
     }
@@ -138,7 +218,7 @@ impl HeaderBarWidget {
         if active {
             self.imp()
                 .selection_title
-                .set_title(&labels::n_songs_selected_label(0));
+                .set_title(&display::n_songs_selected_label(0));
             self.imp().selection_title.set_visible(true);
             self.imp().selection_header.set_visible(true);
         } else {
@@ -150,7 +230,7 @@ impl HeaderBarWidget {
     pub fn set_selection_count(&self, count: usize) {
         self.imp()
             .selection_title
-            .set_title(&labels::n_songs_selected_label(count));
+            .set_title(&display::n_songs_selected_label(count));
     }
 
     pub fn add_classes(&self, classes: &[&str]) {
@@ -0,0 +1,108 @@
+use gettextrs::*;
+use gio::prelude::*;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::player::{audio_backend_available, cache_dir, SpotifyPlayerSettings};
+
+// What the API client talks to, whatever the network says
+const SPOTIFY_HOST: &str = "api.spotify.com";
+const SPOTIFY_PORT: u16 = 443;
+
+// What can keep Spot from working, as found when it starts
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum HealthProblem {
+    NoAudioOutput,
+    SpotifyUnreachable,
+    InvalidCredentials,
+    CacheNotWritable,
+}
+
+impl HealthProblem {
+    pub fn title(&self) -> String {
+        match self {
+            // translators: A problem found as Spot starts
+            Self::NoAudioOutput => gettext("No Sound Output"),
+            // translators: A problem found as Spot starts
+            Self::SpotifyUnreachable => gettext("Spotify Can't Be Reached"),
+            // translators: A problem found as Spot starts
+            Self::InvalidCredentials => gettext("Couldn't Log In"),
+            // translators: A problem found as Spot starts
+            Self::CacheNotWritable => gettext("Cache Can't Be Written"),
+        }
+    }
+
+    // What the user can do about it
+    pub fn suggestion(&self) -> String {
+        match self {
+            // translators: How to fix a problem found as Spot starts (No Sound Output)
+            Self::NoAudioOutput => gettext("The sound server or sound card used for playback isn't there. Make sure it's running, or pick another audio backend in the preferences, then restart Spot."),
+            // translators: How to fix a problem found as Spot starts (Spotify Can't Be Reached)
+            Self::SpotifyUnreachable => gettext("Check your connection, and that no firewall or proxy blocks Spotify. Music in the cache can still be browsed."),
+            // translators: How to fix a problem found as Spot starts (Couldn't Log In)
+            Self::InvalidCredentials => gettext("The saved credentials were refused, for instance after a password change. Log in again."),
+            // translators: How to fix a problem found as Spot starts (Cache Can't Be Written)
+            Self::CacheNotWritable => gettext("Songs and images can't be cached, and will be downloaded every time. Make sure the disk isn't full, and that the cache folder belongs to you."),
+        }
+    }
+
+    // The label of the button that fixes it, if there's one
+    pub fn fix_label(&self) -> Option<String> {
+        match self {
+            // translators: Button next to a problem found as Spot starts (Couldn't Log In)
+            Self::InvalidCredentials => Some(gettext("Log In")),
+            _ => None,
+        }
+    }
+}
+
+pub fn has_audio_output(settings: &SpotifyPlayerSettings) -> bool {
+    audio_backend_available(&settings.backend)
+}
+
+pub async fn can_reach_spotify() -> bool {
+    let address = gio::NetworkAddress::new(SPOTIFY_HOST, SPOTIFY_PORT);
+    let result = gio::NetworkMonitor::default()
+        .can_reach_future(&address)
+        .await;
+    if let Err(e) = &result {
+        warn!("Can't reach {}: {}", SPOTIFY_HOST, e);
+    }
+    result.is_ok()
+}
+
+// Both the API client's cache and the player's
+pub fn cache_dirs(settings: &SpotifyPlayerSettings) -> Vec<PathBuf> {
+    vec![glib::user_cache_dir().join("spot"), cache_dir(settings)]
+}
+
+// Writes a file (then removes it) to make sure it can be done
+pub fn is_writable(dir: &Path) -> bool {
+    let probe = dir.join(".spot-health-check");
+    let result = fs::create_dir_all(dir)
+        .and_then(|_| fs::write(&probe, b""))
+        .and_then(|_| fs::remove_file(&probe));
+    if let Err(e) = &result {
+        warn!("Can't write to {:?}: {}", dir, e);
+    }
+    result.is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_is_writable() {
+        let dir = std::env::temp_dir().join(format!("spot-health-check-{}", std::process::id()));
+        assert!(is_writable(&dir.join("cache")));
+
+        // Not a folder
+        let file = dir.join("file");
+        fs::write(&file, b"").unwrap();
+        assert!(!is_writable(&file.join("cache")));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
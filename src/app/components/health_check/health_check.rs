@@ -0,0 +1,247 @@
+use gtk::prelude::*;
+use gtk::subclass::prelude::*;
+use gtk::CompositeTemplate;
+use libadwaita::prelude::*;
+use std::cell::RefCell;
+use std::collections::BTreeSet;
+use std::rc::Rc;
+
+use super::checks::{self, HealthProblem};
+use crate::app::components::EventListener;
+use crate::app::state::{LoginAction, LoginEvent};
+use crate::app::{ActionDispatcher, AppEvent, AppModel, Worker};
+
+mod imp {
+
+    use super::*;
+    use libadwaita::subclass::prelude::*;
+
+    #[derive(Debug, Default, CompositeTemplate)]
+    #[template(resource = "/dev/alextren/Spot/components/health_check.ui")]
+    pub struct HealthCheckWindow {
+        #[template_child]
+        pub problems: TemplateChild<gtk::ListBox>,
+
+        #[template_child]
+        pub check_again_button: TemplateChild<gtk::Button>,
+
+        #[template_child]
+        pub dismiss_button: TemplateChild<gtk::Button>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for HealthCheckWindow {
+        const NAME: &'static str = "HealthCheckWindow";
+        type Type = super::HealthCheckWindow;
+        type ParentType = libadwaita::Window;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for HealthCheckWindow {
+        fn constructed(&self) {
+            self.parent_constructed();
+            self.dismiss_button
+                .connect_clicked(clone!(@weak self as _self => move |_| {
+                    _self.obj().set_visible(false);
+                }));
+        }
+    }
+
+    impl WidgetImpl for HealthCheckWindow {}
+    impl WindowImpl for HealthCheckWindow {}
+    impl AdwWindowImpl for HealthCheckWindow {}
+}
+
+glib::wrapper! {
+    pub struct HealthCheckWindow(ObjectSubclass<imp::HealthCheckWindow>) @extends gtk::Widget, gtk::Window, libadwaita::Window;
+}
+
+impl HealthCheckWindow {
+    fn new() -> Self {
+        glib::Object::new()
+    }
+
+    fn connect_check_again<F>(&self, f: F)
+    where
+        F: Fn() + 'static,
+    {
+        self.imp().check_again_button.connect_clicked(move |_| f());
+    }
+
+    fn show_problems<F>(&self, problems: &BTreeSet<HealthProblem>, on_fix: F)
+    where
+        F: Fn(HealthProblem) + Clone + 'static,
+    {
+        let list = &self.imp().problems;
+        while let Some(row) = list.first_child() {
+            list.remove(&row);
+        }
+        for problem in problems {
+            let row = libadwaita::ActionRow::builder()
+                .title(problem.title())
+                .subtitle(problem.suggestion())
+                .build();
+            row.add_prefix(&gtk::Image::from_icon_name("dialog-warning-symbolic"));
+            if let Some(label) = problem.fix_label() {
+                let button = gtk::Button::builder()
+                    .label(label)
+                    .valign(gtk::Align::Center)
+                    .build();
+                let on_fix = on_fix.clone();
+                let problem = *problem;
+                button.connect_clicked(move |_| on_fix(problem));
+                row.add_suffix(&button);
+            }
+            list.append(&row);
+        }
+    }
+}
+
+struct Checks {
+    app_model: Rc<AppModel>,
+    dispatcher: Box<dyn ActionDispatcher>,
+    worker: Worker,
+    parent: gtk::Window,
+    window: HealthCheckWindow,
+    problems: RefCell<BTreeSet<HealthProblem>>,
+}
+
+impl Checks {
+    fn run(self: &Rc<Self>) {
+        let settings = self
+            .app_model
+            .get_state()
+            .settings
+            .settings
+            .player_settings
+            .clone();
+        self.set(
+            HealthProblem::NoAudioOutput,
+            !checks::has_audio_output(&settings),
+        );
+        let cache_writable = checks::cache_dirs(&settings)
+            .iter()
+            .all(|dir| checks::is_writable(dir));
+        self.set(HealthProblem::CacheNotWritable, !cache_writable);
+
+        let checks = Rc::clone(self);
+        self.worker.send_local_task(async move {
+            let reachable = checks::can_reach_spotify().await;
+            checks.set(HealthProblem::SpotifyUnreachable, !reachable);
+        });
+    }
+
+    fn has(&self, problem: HealthProblem) -> bool {
+        self.problems.borrow().contains(&problem)
+    }
+
+    fn set(self: &Rc<Self>, problem: HealthProblem, found: bool) {
+        let changed = if found {
+            self.problems.borrow_mut().insert(problem)
+        } else {
+            self.problems.borrow_mut().remove(&problem)
+        };
+        if changed {
+            self.update();
+        }
+    }
+
+    // Shows what's wrong, or closes the window once everything's fine
+    fn update(self: &Rc<Self>) {
+        let problems = self.problems.borrow();
+        if problems.is_empty() {
+            self.window.set_visible(false);
+            return;
+        }
+        let checks = Rc::downgrade(self);
+        self.window.show_problems(&problems, move |problem| {
+            if let Some(checks) = checks.upgrade() {
+                checks.fix(problem);
+            }
+        });
+        self.window.set_transient_for(Some(&self.parent));
+        self.window.set_modal(true);
+        self.window.present();
+    }
+
+    fn fix(&self, problem: HealthProblem) {
+        if problem == HealthProblem::InvalidCredentials {
+            self.problems.borrow_mut().remove(&problem);
+            self.window.set_visible(false);
+            self.dispatcher.dispatch(LoginAction::ShowLogin.into());
+        }
+    }
+}
+
+// Makes sure Spot has what it needs as it starts: somewhere to play sound, a way to Spotify, valid saved
+// credentials and a cache it can write to. Problems are listed with what to do about them, rather than
+// leaving the user with a window that stays empty or songs that never play.
+pub struct HealthCheck {
+    checks: Rc<Checks>,
+    // Until the login with the saved credentials is over
+    checking_credentials: bool,
+}
+
+impl HealthCheck {
+    pub fn new(
+        parent: gtk::Window,
+        app_model: Rc<AppModel>,
+        dispatcher: Box<dyn ActionDispatcher>,
+        worker: Worker,
+    ) -> Self {
+        let window = HealthCheckWindow::new();
+        let checks = Rc::new(Checks {
+            app_model,
+            dispatcher,
+            worker,
+            parent,
+            window,
+            problems: RefCell::new(BTreeSet::new()),
+        });
+        checks
+            .window
+            .connect_check_again(clone!(@weak checks => move || checks.run()));
+        Self {
+            checks,
+            checking_credentials: false,
+        }
+    }
+
+    fn login_failed(&mut self) {
+        if !self.checking_credentials {
+            return;
+        }
+        self.checking_credentials = false;
+        // Credentials can't be checked without reaching Spotify
+        let online = self.checks.app_model.get_state().online;
+        if online && !self.checks.has(HealthProblem::SpotifyUnreachable) {
+            self.checks.set(HealthProblem::InvalidCredentials, true);
+        }
+    }
+}
+
+impl EventListener for HealthCheck {
+    fn on_event(&mut self, event: &AppEvent) {
+        match event {
+            AppEvent::Started => {
+                self.checking_credentials = true;
+                self.checks.run();
+            }
+            AppEvent::LoginEvent(LoginEvent::LoginCompleted(_)) => {
+                self.checking_credentials = false;
+                self.checks.set(HealthProblem::InvalidCredentials, false);
+            }
+            // There were no saved credentials
+            AppEvent::LoginEvent(LoginEvent::LoginShown) => self.checking_credentials = false,
+            AppEvent::LoginEvent(LoginEvent::LoginFailed) => self.login_failed(),
+            _ => {}
+        }
+    }
+}
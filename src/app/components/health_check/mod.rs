@@ -0,0 +1,4 @@
+mod checks;
+mod health_check;
+
+pub use health_check::*;
@@ -0,0 +1,224 @@
+// Reading a listening history (a Last.fm export, or any CSV with artists and titles),
+// and telling whether a Spotify track is the one an entry refers to.
+
+use std::collections::HashSet;
+
+use crate::app::models::SongDescription;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HistoryEntry {
+    pub artist: String,
+    pub title: String,
+}
+
+impl HistoryEntry {
+    pub fn search_query(&self) -> String {
+        format!("{} {}", self.artist, self.title)
+    }
+}
+
+// Fields of a CSV line, quotes and all (see RFC 4180)
+fn parse_csv_line(line: &str) -> Vec<String> {
+    let mut fields = vec![];
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(std::mem::take(&mut field)),
+            c => field.push(c),
+        }
+    }
+    fields.push(field);
+    fields.into_iter().map(|f| f.trim().to_string()).collect()
+}
+
+// Where to find the artist and title
+fn find_columns(first_row: &[String]) -> (Option<(usize, usize)>, bool) {
+    let position = |names: &[&str]| {
+        first_row
+            .iter()
+            .position(|field| names.contains(&field.to_lowercase().as_str()))
+    };
+    let artist = position(&["artist", "artist name", "artist_name"]);
+    let title = position(&["title", "track", "track name", "track_name", "name", "song"]);
+    match (artist, title) {
+        // A header
+        (Some(artist), Some(title)) => (Some((artist, title)), true),
+        // Last.fm exports have no header: artist, album, title, date
+        _ if first_row.len() == 4 => (Some((0, 2)), false),
+        // Otherwise, artist then title
+        _ if first_row.len() >= 2 => (Some((0, 1)), false),
+        _ => (None, false),
+    }
+}
+
+// Entries of a history, in order, without the duplicates (the same song listened to many times)
+pub fn parse_history(content: &str) -> Vec<HistoryEntry> {
+    let mut rows = content
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(parse_csv_line)
+        .peekable();
+
+    let (columns, has_header) = match rows.peek() {
+        Some(first_row) => find_columns(first_row),
+        None => return vec![],
+    };
+    let (artist_col, title_col) = match columns {
+        Some(columns) => columns,
+        None => return vec![],
+    };
+    if has_header {
+        rows.next();
+    }
+
+    let mut seen = HashSet::new();
+    rows.filter_map(|row| {
+        let artist = row.get(artist_col)?.to_string();
+        let title = row.get(title_col)?.to_string();
+        Some(HistoryEntry { artist, title }).filter(|e| !e.artist.is_empty() && !e.title.is_empty())
+    })
+    .filter(|entry| seen.insert((normalize(&entry.artist), normalize(&entry.title))))
+    .collect()
+}
+
+// Lowercase, without punctuation nor what usually differs between versions of a song
+// ("Song (Remastered 2011)", "Song - Live")
+fn normalize(s: &str) -> String {
+    let mut depth = 0;
+    let without_details: String = s
+        .split(" - ")
+        .next()
+        .unwrap_or_default()
+        .chars()
+        .filter(|c| match c {
+            '(' | '[' => {
+                depth += 1;
+                false
+            }
+            ')' | ']' => {
+                depth -= 1;
+                false
+            }
+            _ => depth == 0,
+        })
+        .collect();
+    without_details
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+pub fn is_match(entry: &HistoryEntry, song: &SongDescription) -> bool {
+    let title_matches = normalize(&song.title) == normalize(&entry.title);
+    let entry_artist = normalize(&entry.artist);
+    // The entry might have "A & B" where Spotify lists A and B separately
+    let artist_matches = song.artists.iter().any(|artist| {
+        let artist = normalize(&artist.name);
+        !artist.is_empty() && entry_artist.contains(&artist)
+    });
+    title_matches && artist_matches
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::app::models::{AlbumRef, ArtistRef};
+
+    fn entry(artist: &str, title: &str) -> HistoryEntry {
+        HistoryEntry {
+            artist: artist.to_string(),
+            title: title.to_string(),
+        }
+    }
+
+    fn song(artists: &[&str], title: &str) -> SongDescription {
+        SongDescription {
+            id: "id".to_string(),
+            track_number: None,
+            uri: "".to_string(),
+            title: title.to_string(),
+            artists: artists
+                .iter()
+                .map(|name| ArtistRef {
+                    id: "".to_string(),
+                    name: name.to_string(),
+                })
+                .collect(),
+            album: AlbumRef {
+                id: "".to_string(),
+                name: "".to_string(),
+            },
+            duration: 0,
+            art: None,
+            preview_url: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_csv_line() {
+        assert_eq!(
+            parse_csv_line(r#"Artist,"Title, with a comma","Say ""hi"""#),
+            vec!["Artist", "Title, with a comma", r#"Say "hi""#]
+        );
+    }
+
+    #[test]
+    fn test_parse_lastfm_export() {
+        let content = "Radiohead,OK Computer,Airbag,01 Jan 2020 10:00\n\
+            Radiohead,OK Computer,Paranoid Android,01 Jan 2020 10:05\n\
+            Radiohead,OK Computer,Airbag,02 Jan 2020 10:00\n";
+        assert_eq!(
+            parse_history(content),
+            vec![
+                entry("Radiohead", "Airbag"),
+                entry("Radiohead", "Paranoid Android")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_csv_with_header() {
+        let content = "Title,Album,Artist\nAirbag,OK Computer,Radiohead\n\n";
+        assert_eq!(parse_history(content), vec![entry("Radiohead", "Airbag")]);
+    }
+
+    #[test]
+    fn test_parse_two_columns() {
+        let content = "Radiohead,Airbag\nPortishead,\n";
+        assert_eq!(parse_history(content), vec![entry("Radiohead", "Airbag")]);
+    }
+
+    #[test]
+    fn test_is_match() {
+        assert!(is_match(
+            &entry("Radiohead", "Airbag"),
+            &song(&["Radiohead"], "Airbag - Remastered")
+        ));
+        assert!(is_match(
+            &entry("Simon & Garfunkel", "The Boxer"),
+            &song(&["Simon", "Garfunkel"], "The Boxer")
+        ));
+        assert!(is_match(
+            &entry("The Beatles", "Help!"),
+            &song(&["The Beatles"], "Help! (Remastered 2009)")
+        ));
+        assert!(!is_match(
+            &entry("Radiohead", "Airbag"),
+            &song(&["Someone Else"], "Airbag")
+        ));
+        assert!(!is_match(
+            &entry("Radiohead", "Airbag"),
+            &song(&["Radiohead"], "Lucky")
+        ));
+    }
+}
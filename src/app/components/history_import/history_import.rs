@@ -0,0 +1,256 @@
+use gettextrs::*;
+use gtk::prelude::*;
+use gtk::subclass::prelude::*;
+use gtk::CompositeTemplate;
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use super::history::{parse_history, HistoryEntry};
+use super::HistoryImportModel;
+use crate::app::components::display;
+use crate::app::models::SongDescription;
+use crate::app::Worker;
+
+mod imp {
+
+    use super::*;
+    use libadwaita::subclass::prelude::*;
+
+    #[derive(Debug, Default, CompositeTemplate)]
+    #[template(resource = "/dev/alextren/Spot/components/history_import.ui")]
+    pub struct HistoryImportWindow {
+        #[template_child]
+        pub toast_overlay: TemplateChild<libadwaita::ToastOverlay>,
+
+        #[template_child]
+        pub stack: TemplateChild<gtk::Stack>,
+
+        #[template_child]
+        pub choose_button: TemplateChild<gtk::Button>,
+
+        #[template_child]
+        pub matching_label: TemplateChild<gtk::Label>,
+
+        #[template_child]
+        pub progress: TemplateChild<gtk::ProgressBar>,
+
+        #[template_child]
+        pub summary_label: TemplateChild<gtk::Label>,
+
+        #[template_child]
+        pub unmatched_list: TemplateChild<gtk::ListBox>,
+
+        #[template_child]
+        pub playlist_button: TemplateChild<gtk::Button>,
+
+        #[template_child]
+        pub like_button: TemplateChild<gtk::Button>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for HistoryImportWindow {
+        const NAME: &'static str = "HistoryImportWindow";
+        type Type = super::HistoryImportWindow;
+        type ParentType = libadwaita::Window;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for HistoryImportWindow {}
+    impl WidgetImpl for HistoryImportWindow {}
+    impl WindowImpl for HistoryImportWindow {}
+    impl AdwWindowImpl for HistoryImportWindow {}
+}
+
+glib::wrapper! {
+    pub struct HistoryImportWindow(ObjectSubclass<imp::HistoryImportWindow>) @extends gtk::Widget, gtk::Window, libadwaita::Window;
+}
+
+impl HistoryImportWindow {
+    fn new() -> Self {
+        glib::Object::new()
+    }
+
+    fn show_start(&self) {
+        self.imp().stack.set_visible_child_name("start");
+    }
+
+    fn show_progress(&self, done: usize, total: usize) {
+        let widget = self.imp();
+        widget.stack.set_visible_child_name("matching");
+        widget
+            .matching_label
+            .set_label(&display::matching_history_progress_label(done, total));
+        widget
+            .progress
+            .set_fraction(done as f64 / total.max(1) as f64);
+    }
+
+    // What was found, and the entries left behind for the user to review
+    fn show_review(&self, found: usize, unmatched: &[HistoryEntry]) {
+        let widget = self.imp();
+        widget.stack.set_visible_child_name("review");
+        widget
+            .summary_label
+            .set_label(&display::history_matches_label(
+                found,
+                found + unmatched.len(),
+            ));
+
+        while let Some(row) = widget.unmatched_list.first_child() {
+            widget.unmatched_list.remove(&row);
+        }
+        for entry in unmatched {
+            let row = libadwaita::ActionRow::builder()
+                .title(glib::markup_escape_text(&entry.title))
+                .subtitle(glib::markup_escape_text(&entry.artist))
+                .build();
+            widget.unmatched_list.append(&row);
+        }
+        widget.unmatched_list.set_visible(!unmatched.is_empty());
+
+        widget.like_button.set_sensitive(found > 0);
+        widget.playlist_button.set_sensitive(found > 0);
+    }
+
+    fn show_toast(&self, message: &str) {
+        self.imp()
+            .toast_overlay
+            .add_toast(libadwaita::Toast::new(message));
+    }
+
+    // Asks for a file, and hands over its content
+    fn connect_file_chosen<F>(&self, f: F)
+    where
+        F: Fn(String) + Clone + 'static,
+    {
+        self.imp()
+            .choose_button
+            .connect_clicked(clone!(@weak self as _self => move |_| {
+                let filter = gtk::FileFilter::new();
+                // translators: Name of the kind of files that can be imported as a listening history
+                filter.set_name(Some(&gettext("CSV files")));
+                filter.add_suffix("csv");
+                let dialog = gtk::FileDialog::builder()
+                    .modal(true)
+                    .default_filter(&filter)
+                    .build();
+
+                let f = f.clone();
+                glib::MainContext::default().spawn_local(clone!(@weak _self => async move {
+                    let file = match dialog.open_future(Some(&_self)).await {
+                        Ok(file) => file,
+                        // Cancelled
+                        Err(_) => return,
+                    };
+                    match file.load_contents_future().await {
+                        Ok((bytes, _)) => f(String::from_utf8_lossy(&bytes).into_owned()),
+                        Err(e) => {
+                            warn!("Could not read {:?}: {}", file.path(), e);
+                            // translators: Shown when the file chosen as a listening history can't be read
+                            _self.show_toast(&gettext("Could not read this file"));
+                        }
+                    }
+                }));
+            }));
+    }
+
+    fn connect_like<F>(&self, f: F)
+    where
+        F: Fn() + 'static,
+    {
+        self.imp().like_button.connect_clicked(move |_| f());
+    }
+
+    fn connect_create_playlist<F>(&self, f: F)
+    where
+        F: Fn() + 'static,
+    {
+        self.imp().playlist_button.connect_clicked(move |_| f());
+    }
+}
+
+// Imports a listening history: every entry is looked up on Spotify, then the songs found can be liked
+// or put in a new playlist; those that weren't found are listed so the user knows what's missing.
+pub struct HistoryImport {
+    parent: gtk::Window,
+    window: HistoryImportWindow,
+}
+
+impl HistoryImport {
+    pub fn new(parent: gtk::Window, model: HistoryImportModel, worker: Worker) -> Self {
+        let window = HistoryImportWindow::new();
+        let model = Rc::new(model);
+        // Bumped when starting over, so that a lookup still going on knows to stop
+        let generation = Rc::new(Cell::new(0));
+        let found: Rc<RefCell<Vec<SongDescription>>> = Default::default();
+
+        window.connect_file_chosen(
+            clone!(@weak window, @weak model, @strong generation, @strong found => move |content| {
+                let entries = parse_history(&content);
+                if entries.is_empty() {
+                    // translators: Shown when the file chosen as a listening history has no songs we could make sense of
+                    window.show_toast(&gettext("No songs found in this file"));
+                    return;
+                }
+
+                let current = generation.get() + 1;
+                generation.set(current);
+                found.borrow_mut().clear();
+                window.show_progress(0, entries.len());
+
+                let generation = Rc::clone(&generation);
+                let found = Rc::clone(&found);
+                worker.send_local_task(async move {
+                    let mut unmatched = vec![];
+                    for (i, entry) in entries.iter().enumerate() {
+                        if generation.get() != current {
+                            return;
+                        }
+                        match model.find_match(entry).await {
+                            Some(song) => found.borrow_mut().push(song),
+                            None => unmatched.push(entry.clone()),
+                        }
+                        window.show_progress(i + 1, entries.len());
+                    }
+                    window.show_review(found.borrow().len(), &unmatched);
+                });
+            }),
+        );
+
+        window.connect_like(clone!(@weak window, @weak model, @strong found => move || {
+            model.like(found.take());
+            window.close();
+        }));
+
+        window.connect_create_playlist(
+            clone!(@weak window, @weak model, @strong found => move || {
+                model.create_playlist(found.take());
+                window.close();
+            }),
+        );
+
+        // Closing the window stops any lookup going on
+        window.connect_close_request(
+            clone!(@strong generation => @default-return gtk::Inhibit(false), move |_| {
+                generation.set(generation.get() + 1);
+                gtk::Inhibit(false)
+            }),
+        );
+
+        Self { parent, window }
+    }
+
+    pub fn show_self(&self) {
+        self.window.show_start();
+        self.window.set_transient_for(Some(&self.parent));
+        self.window.set_modal(true);
+        self.window.set_visible(true);
+    }
+}
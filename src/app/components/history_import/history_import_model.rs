@@ -0,0 +1,45 @@
+use gettextrs::*;
+use std::rc::Rc;
+
+use super::history::{is_match, HistoryEntry};
+use crate::app::components::{create_playlist_with_songs, save_tracks_in_bulk};
+use crate::app::models::SongDescription;
+use crate::app::{ActionDispatcher, AppModel};
+
+// How many results we look at to find an entry
+const SEARCH_LIMIT: usize = 5;
+
+pub struct HistoryImportModel {
+    app_model: Rc<AppModel>,
+    dispatcher: Box<dyn ActionDispatcher>,
+}
+
+impl HistoryImportModel {
+    pub fn new(app_model: Rc<AppModel>, dispatcher: Box<dyn ActionDispatcher>) -> Self {
+        Self {
+            app_model,
+            dispatcher,
+        }
+    }
+
+    // The track on Spotify for that entry, if we can find it
+    pub async fn find_match(&self, entry: &HistoryEntry) -> Option<SongDescription> {
+        let api = self.app_model.get_spotify();
+        let results = api
+            .search_tracks(&entry.search_query(), SEARCH_LIMIT)
+            .await
+            .ok()?;
+        results.into_iter().find(|song| is_match(entry, song))
+    }
+
+    pub fn like(&self, songs: Vec<SongDescription>) {
+        save_tracks_in_bulk(songs, &self.app_model, self.dispatcher.as_ref());
+    }
+
+    pub fn create_playlist(&self, songs: Vec<SongDescription>) {
+        // translators: Name of the playlist created when importing a listening history
+        let name = gettext("Imported Songs");
+        let uris = songs.into_iter().map(|s| s.uri).collect();
+        create_playlist_with_songs(name, uris, &self.app_model, self.dispatcher.as_ref());
+    }
+}
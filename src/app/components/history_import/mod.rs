@@ -0,0 +1,6 @@
+mod history;
+mod history_import;
+mod history_import_model;
+
+pub use history_import::*;
+pub use history_import_model::*;
@@ -0,0 +1,145 @@
+use gettextrs::*;
+use gtk::prelude::*;
+use gtk::ApplicationInhibitFlags;
+use std::cell::Cell;
+use std::rc::Rc;
+
+use crate::app::components::sidebar::SidebarDestination;
+use crate::app::components::{EventListener, PlaybackWidget};
+use crate::app::state::{PlaybackEvent, ScreenName};
+use crate::app::{AppEvent, AppModel, AppState, BrowserEvent};
+
+// What we keep the session from doing: while playing, the computer shouldn't suspend; while playing
+// in fullscreen Now Playing (say, on a TV, with nobody touching the keyboard), the screen shouldn't
+// lock either. Nothing while paused.
+fn inhibit_flags(is_playing: bool, is_watched: bool) -> ApplicationInhibitFlags {
+    match (is_playing, is_watched) {
+        (true, true) => ApplicationInhibitFlags::IDLE | ApplicationInhibitFlags::SUSPEND,
+        (true, false) => ApplicationInhibitFlags::SUSPEND,
+        (false, _) => ApplicationInhibitFlags::empty(),
+    }
+}
+
+struct Inhibition {
+    window: gtk::Window,
+    indicator: PlaybackWidget,
+    // Whether Now Playing is the page shown in the home screen (the state doesn't follow it when it's
+    // brought up from the playback bar)
+    now_playing_page: Cell<bool>,
+    cookie: Cell<Option<u32>>,
+    flags: Cell<ApplicationInhibitFlags>,
+}
+
+impl Inhibition {
+    fn is_now_playing_shown(&self, state: &AppState) -> bool {
+        self.now_playing_page.get() && *state.browser.current_screen() == ScreenName::Home
+    }
+
+    // With the window given, GTK keeps the screen on through the Wayland idle-inhibit protocol, which
+    // only holds while the window is visible; elsewhere, it goes through the session (or the portal)
+    fn update(&self, state: &AppState) {
+        let is_watched = self.window.is_fullscreen() && self.is_now_playing_shown(state);
+        let flags = inhibit_flags(state.playback.is_playing(), is_watched);
+        if flags == self.flags.get() {
+            return;
+        }
+        self.flags.set(flags);
+
+        let app = self.window.application();
+        if let (Some(cookie), Some(app)) = (self.cookie.take(), app.as_ref()) {
+            app.uninhibit(cookie);
+        }
+
+        let reason = if flags.contains(ApplicationInhibitFlags::IDLE) {
+            // translators: Shown by the system (and as a tooltip) while Spot keeps the screen from locking
+            Some(gettext("Showing what's playing in fullscreen"))
+        } else if flags.contains(ApplicationInhibitFlags::SUSPEND) {
+            // translators: Shown by the system (and as a tooltip) while Spot keeps the computer from suspending
+            Some(gettext("Playing music"))
+        } else {
+            None
+        };
+
+        let cookie = reason.as_ref().zip(app).and_then(|(reason, app)| {
+            // 0 means the request failed
+            Some(app.inhibit(Some(&self.window), flags, Some(reason.as_str()))).filter(|c| *c != 0)
+        });
+        self.cookie.set(cookie);
+        self.indicator
+            .set_inhibit_indicator(cookie.and(reason.as_deref()));
+    }
+}
+
+// Keeps the screen on or the computer awake depending on playback and what's shown (see inhibit_flags),
+// with an indicator in the playback bar while it does.
+pub struct IdleInhibitor {
+    app_model: Rc<AppModel>,
+    inhibition: Rc<Inhibition>,
+}
+
+impl IdleInhibitor {
+    pub fn new(app_model: Rc<AppModel>, window: gtk::Window, indicator: PlaybackWidget) -> Self {
+        let now_playing_page = app_model
+            .get_state()
+            .browser
+            .home_state()
+            .map(|home| home.visible_page == SidebarDestination::NowPlaying.id())
+            .unwrap_or(false);
+        let inhibition = Rc::new(Inhibition {
+            window,
+            indicator,
+            now_playing_page: Cell::new(now_playing_page),
+            cookie: Cell::new(None),
+            flags: Cell::new(ApplicationInhibitFlags::empty()),
+        });
+        inhibition.window.connect_fullscreened_notify(
+            clone!(@weak app_model, @weak inhibition => move |_| {
+                inhibition.update(&app_model.get_state());
+            }),
+        );
+        Self {
+            app_model,
+            inhibition,
+        }
+    }
+}
+
+impl EventListener for IdleInhibitor {
+    fn on_event(&mut self, event: &AppEvent) {
+        match event {
+            AppEvent::NowPlayingShown => self.inhibition.now_playing_page.set(true),
+            AppEvent::BrowserEvent(BrowserEvent::HomeVisiblePageChanged(page)) => self
+                .inhibition
+                .now_playing_page
+                .set(*page == SidebarDestination::NowPlaying.id()),
+            AppEvent::PlaybackEvent(
+                PlaybackEvent::PlaybackPaused
+                | PlaybackEvent::PlaybackResumed
+                | PlaybackEvent::PlaybackStopped,
+            )
+            | AppEvent::BrowserEvent(
+                BrowserEvent::NavigationPushed(_)
+                | BrowserEvent::NavigationPopped
+                | BrowserEvent::NavigationPoppedTo(_),
+            ) => {}
+            _ => return,
+        }
+        self.inhibition.update(&self.app_model.get_state());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_inhibit_flags() {
+        assert_eq!(
+            inhibit_flags(true, true),
+            ApplicationInhibitFlags::IDLE | ApplicationInhibitFlags::SUSPEND
+        );
+        assert_eq!(inhibit_flags(true, false), ApplicationInhibitFlags::SUSPEND);
+        assert!(inhibit_flags(false, true).is_empty());
+    }
+}
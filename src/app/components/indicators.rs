@@ -0,0 +1,81 @@
+// State is never told by color alone, for those who can't tell colors apart: each indicator here has
+// a shape (a filled icon, a glyph) and a label (a tooltip, also read by screen readers) along with the
+// color. With high contrast indicators (see MainWindow), colors also give way to stronger shapes.
+
+use gtk::accessible::{Property, State};
+use gtk::prelude::*;
+use gtk::AccessibleTristate;
+
+use crate::app::components::{display_add_css_provider, labels};
+
+// Set on the window when the user wants high contrast indicators
+pub const HIGH_CONTRAST_CLASS: &str = "high-contrast-indicators";
+
+const ON_CLASS: &str = "indicator--on";
+
+// Also covers what the high contrast mode changes elsewhere (the sidebar, the song playing)
+pub fn add_css() {
+    display_add_css_provider(resource!("/components/indicators.css"));
+}
+
+// A toggle to save something to the library (Liked Songs, saved albums): a filled star when it's saved
+pub fn set_saved(button: &gtk::Button, is_saved: bool, tooltip: &str) {
+    add_css();
+    button.set_icon_name(if is_saved {
+        "starred-symbolic"
+    } else {
+        "non-starred-symbolic"
+    });
+    button.set_tooltip_text(Some(tooltip));
+    button.update_state(&[State::Pressed(if is_saved {
+        AccessibleTristate::True
+    } else {
+        AccessibleTristate::False
+    })]);
+    if is_saved {
+        button.add_css_class(ON_CLASS);
+    } else {
+        button.remove_css_class(ON_CLASS);
+    }
+}
+
+// Each playlist label color has a shape of its own
+pub fn color_label_shape(color: &str) -> &'static str {
+    match color {
+        "red" => "●",
+        "orange" => "◆",
+        "yellow" => "▲",
+        "green" => "■",
+        "blue" => "★",
+        "purple" => "✚",
+        _ => "●",
+    }
+}
+
+// A playlist label color, with its shape and its name
+pub fn set_color_label(label: &gtk::Label, color: &str) {
+    add_css();
+    let name = labels::playlist_label_color(color);
+    label.set_text(color_label_shape(color));
+    label.set_tooltip_text(Some(&name));
+    label.update_property(&[Property::Label(&name)]);
+    let class = format!("indicator__label--{color}");
+    label.set_css_classes(&["indicator__label", class.as_str()]);
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::app::models::PLAYLIST_LABEL_COLORS;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_color_label_shapes_are_distinct() {
+        let shapes: HashSet<&str> = PLAYLIST_LABEL_COLORS
+            .iter()
+            .map(|color| color_label_shape(color))
+            .collect();
+        assert_eq!(shapes.len(), PLAYLIST_LABEL_COLORS.len());
+    }
+}
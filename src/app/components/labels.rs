@@ -10,8 +10,17 @@ lazy_static! {
     // translators: This is part of a contextual menu attached to a single track; this entry adds a track at the end of the play queue.
     pub static ref ADD_TO_QUEUE: String = gettext("Add to queue");
 
+    // translators: This is part of a contextual menu attached to a single track; the intent is to open the track's page on open.spotify.com in a web browser.
+    pub static ref OPEN_IN_WEB_PLAYER: String = gettext("Open in web player");
+
     // translators: This is part of a contextual menu attached to a single track; this entry removes a track from the play queue.
     pub static ref REMOVE_FROM_QUEUE: String = gettext("Remove from queue");
+
+    // translators: This is part of a contextual menu attached to a track of the play queue that was added by autoplay; this entry removes all such tracks from the queue.
+    pub static ref REMOVE_AUTOPLAY_SONGS: String = gettext("Remove all autoplay songs");
+
+    // translators: This is a notification shown after the list of tracks of an album or playlist was copied to the clipboard.
+    pub static ref TRACK_LIST_COPIED: String = gettext("Track list copied to clipboard");
 }
 
 pub fn add_to_playlist_label(playlist: &str) -> String {
@@ -23,13 +32,22 @@ pub fn add_to_playlist_label(playlist: &str) -> String {
     gettext!("Add to {}", playlist)
 }
 
-pub fn n_songs_selected_label(n: usize) -> String {
+pub fn only_in_playlist_label(playlist: &str) -> String {
     // this is just to fool xgettext, it doesn't like macros (or rust for that matter) :(
     if cfg!(debug_assertions) {
-        // translators: This shows up when in selection mode. This text should be as short as possible.
-        ngettext("{} song selected", "{} songs selected", n as u32);
+        // translators: Title of the list of tracks that are in a playlist but not in the one it's compared with; the full text is "Only in <playlist>".
+        gettext("Only in {}");
     }
-    ngettext!("{} song selected", "{} songs selected", n as u32, n)
+    gettext!("Only in {}", playlist)
+}
+
+pub fn playlist_difference_name(playlist: &str, other: &str) -> String {
+    // this is just to fool xgettext, it doesn't like macros (or rust for that matter) :(
+    if cfg!(debug_assertions) {
+        // translators: Name of the playlist created with the tracks of a playlist that another one doesn't have; it reads "<playlist> (not in <other playlist>)".
+        gettext("{} (not in {})");
+    }
+    gettext!("{} (not in {})", playlist, other)
 }
 
 pub fn more_from_label(artist: &str) -> String {
@@ -53,3 +71,48 @@ pub fn album_by_artist_label(album: &str, artist: &str) -> String {
         glib::markup_escape_text(artist)
     )
 }
+
+pub fn new_release_title(artist: &str) -> String {
+    // this is just to fool xgettext, it doesn't like macros (or rust for that matter) :(
+    if cfg!(debug_assertions) {
+        // translators: This is the title of the notification shown when an artist the user subscribed to releases something; {} is the artist name.
+        gettext("New release by {}");
+    }
+    gettext!("New release by {}", artist)
+}
+
+pub fn queue_playlist_name(date: &str) -> String {
+    // this is just to fool xgettext, it doesn't like macros (or rust for that matter) :(
+    if cfg!(debug_assertions) {
+        // translators: This is the name suggested for a playlist made from the play queue; {} is the date and time it's saved at.
+        gettext("Queue of {}");
+    }
+    gettext!("Queue of {}", date)
+}
+
+pub fn playlist_incomplete_label(playlist: &str) -> String {
+    // this is just to fool xgettext, it doesn't like macros (or rust for that matter) :(
+    if cfg!(debug_assertions) {
+        // translators: This is a notification shown when a new playlist was created, but not all of its tracks could be added to it; {} is the name of the playlist.
+        gettext("Some tracks could not be added to {}");
+    }
+    gettext!("Some tracks could not be added to {}", playlist)
+}
+
+pub fn playlist_label_color(color: &str) -> String {
+    match color {
+        // translators: This is a color that can be used to label a playlist in the sidebar.
+        "red" => gettext("Red"),
+        // translators: This is a color that can be used to label a playlist in the sidebar.
+        "orange" => gettext("Orange"),
+        // translators: This is a color that can be used to label a playlist in the sidebar.
+        "yellow" => gettext("Yellow"),
+        // translators: This is a color that can be used to label a playlist in the sidebar.
+        "green" => gettext("Green"),
+        // translators: This is a color that can be used to label a playlist in the sidebar.
+        "blue" => gettext("Blue"),
+        // translators: This is a color that can be used to label a playlist in the sidebar.
+        "purple" => gettext("Purple"),
+        _ => color.to_string(),
+    }
+}
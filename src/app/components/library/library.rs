@@ -1,15 +1,27 @@
 use gtk::prelude::*;
 use gtk::subclass::prelude::*;
 use gtk::CompositeTemplate;
+use std::cell::RefCell;
 use std::rc::Rc;
 
 use super::LibraryModel;
-use crate::app::components::utils::wrap_flowbox_item;
+use crate::app::components::utils::{self, wrap_flowbox_item};
 use crate::app::components::{AlbumWidget, Component, EventListener};
 use crate::app::dispatch::Worker;
 use crate::app::models::AlbumModel;
-use crate::app::state::LoginEvent;
-use crate::app::{AppEvent, BrowserEvent, ListStore};
+use crate::app::state::{LibraryOptions, LibrarySort, LoginEvent};
+use crate::app::{AppEvent, BrowserEvent, PREFETCH_ROWS};
+
+// In the order of the sort menu
+const SORTS: [LibrarySort; 4] = [
+    LibrarySort::RecentlyAdded,
+    LibrarySort::Artist,
+    LibrarySort::Title,
+    LibrarySort::ReleaseYear,
+];
+
+// GTK's default, for the grid
+const MAX_ALBUMS_PER_LINE: u32 = 7;
 
 mod imp {
 
@@ -18,6 +30,15 @@ mod imp {
     #[derive(Debug, Default, CompositeTemplate)]
     #[template(resource = "/dev/alextren/Spot/components/library.ui")]
     pub struct LibraryWidget {
+        #[template_child]
+        pub filter_entry: TemplateChild<gtk::SearchEntry>,
+
+        #[template_child]
+        pub sort_dropdown: TemplateChild<gtk::DropDown>,
+
+        #[template_child]
+        pub compact_button: TemplateChild<gtk::ToggleButton>,
+
         #[template_child]
         pub scrolled_window: TemplateChild<gtk::ScrolledWindow>,
 
@@ -57,35 +78,76 @@ impl LibraryWidget {
         glib::Object::new()
     }
 
-    fn connect_bottom_edge<F>(&self, f: F)
+    fn connect_prefetch<F>(&self, f: F)
     where
         F: Fn() + 'static,
     {
-        self.imp()
-            .scrolled_window
-            .connect_edge_reached(move |_, pos| {
-                if let gtk::PositionType::Bottom = pos {
-                    f()
-                }
-            });
+        utils::connect_prefetch(
+            &self.imp().scrolled_window,
+            &self.imp().flowbox,
+            PREFETCH_ROWS,
+            f,
+        );
     }
 
-    fn bind_albums<F>(&self, worker: Worker, store: &ListStore<AlbumModel>, on_album_pressed: F)
-    where
+    fn bind_albums<F>(
+        &self,
+        worker: Worker,
+        albums: &gtk::SortListModel,
+        compact: bool,
+        on_album_pressed: F,
+    ) where
         F: Fn(String) + Clone + 'static,
     {
-        self.imp()
-            .flowbox
-            .bind_model(Some(store.unsafe_store()), move |item| {
-                wrap_flowbox_item(item, |album_model| {
-                    let f = on_album_pressed.clone();
-                    let album = AlbumWidget::for_model(album_model, worker.clone());
-                    album.connect_album_pressed(clone!(@weak album_model => move |_| {
-                        f(album_model.uri());
-                    }));
-                    album
-                })
+        let flowbox = &self.imp().flowbox;
+        flowbox.set_max_children_per_line(if compact { 1 } else { MAX_ALBUMS_PER_LINE });
+        flowbox.bind_model(Some(albums), move |item| {
+            wrap_flowbox_item(item, |album_model| {
+                let f = on_album_pressed.clone();
+                let album = AlbumWidget::for_model(album_model, worker.clone());
+                if compact {
+                    album.set_compact();
+                }
+                album.connect_album_pressed(clone!(@weak album_model => move |_| {
+                    f(album_model.uri());
+                }));
+                album
+            })
+        });
+    }
+
+    fn set_options(&self, options: &LibraryOptions) {
+        let widget = self.imp();
+        if let Some(position) = SORTS.iter().position(|s| *s == options.sort) {
+            widget.sort_dropdown.set_selected(position as u32);
+        }
+        widget.compact_button.set_active(options.compact);
+    }
+
+    fn connect_options_changed<Filter, Sort, Compact>(
+        &self,
+        on_filter: Filter,
+        on_sort: Sort,
+        on_compact: Compact,
+    ) where
+        Filter: Fn(String) + 'static,
+        Sort: Fn(LibrarySort) + 'static,
+        Compact: Fn(bool) + 'static,
+    {
+        let widget = self.imp();
+        widget
+            .filter_entry
+            .connect_search_changed(move |entry| on_filter(entry.text().to_string()));
+        widget
+            .sort_dropdown
+            .connect_selected_notify(move |dropdown| {
+                if let Some(sort) = SORTS.get(dropdown.selected() as usize) {
+                    on_sort(*sort);
+                }
             });
+        widget
+            .compact_button
+            .connect_toggled(move |button| on_compact(button.is_active()));
     }
 
     pub fn status_page(&self) -> &libadwaita::StatusPage {
@@ -93,36 +155,91 @@ impl LibraryWidget {
     }
 }
 
+// Saved albums are sorted and filtered on our side, through the options kept in the state
+// (see LibraryOptions), so changing them doesn't fetch anything.
 pub struct Library {
     widget: LibraryWidget,
     worker: Worker,
     model: Rc<LibraryModel>,
+    // What the filter and sorter below go by, as last seen in the state
+    options: Rc<RefCell<LibraryOptions>>,
+    filter: gtk::CustomFilter,
+    sorter: gtk::CustomSorter,
+    albums: gtk::SortListModel,
 }
 
 impl Library {
     pub fn new(worker: Worker, model: LibraryModel) -> Self {
         let model = Rc::new(model);
         let widget = LibraryWidget::new();
-        widget.connect_bottom_edge(clone!(@weak model => move || {
+        widget.connect_prefetch(clone!(@weak model => move || {
             model.load_more_albums();
         }));
 
+        let options = Rc::new(RefCell::new(model.options()));
+        widget.set_options(&options.borrow());
+        widget.connect_options_changed(
+            clone!(@weak model => move |filter| model.set_filter(filter)),
+            clone!(@weak model => move |sort| model.set_sort(sort)),
+            clone!(@weak model => move |compact| model.set_compact(compact)),
+        );
+
+        let filter = gtk::CustomFilter::new(clone!(@strong options => move |item| {
+            let album = item.downcast_ref::<AlbumModel>().unwrap();
+            options.borrow().matches(album)
+        }));
+        let sorter = gtk::CustomSorter::new(clone!(@strong options => move |a, b| {
+            let a = a.downcast_ref::<AlbumModel>().unwrap();
+            let b = b.downcast_ref::<AlbumModel>().unwrap();
+            options.borrow().sort.compare(a, b).into()
+        }));
+
+        let albums = {
+            let store = model.get_list_store().unwrap();
+            let filtered =
+                gtk::FilterListModel::new(Some(store.unsafe_store().clone()), Some(filter.clone()));
+            gtk::SortListModel::new(Some(filtered), Some(sorter.clone()))
+        };
+
         Self {
             widget,
             worker,
             model,
+            options,
+            filter,
+            sorter,
+            albums,
         }
     }
 
     fn bind_flowbox(&self) {
         self.widget.bind_albums(
             self.worker.clone(),
-            &self.model.get_list_store().unwrap(),
+            &self.albums,
+            self.options.borrow().compact,
             clone!(@weak self.model as model => move |id| {
                 model.open_album(id);
             }),
         );
     }
+
+    fn update_options(&self) {
+        let options = self.model.options();
+        let previous = self.options.replace(options.clone());
+        if previous.sort != options.sort || previous.compact != options.compact {
+            self.model.save_options();
+        }
+        if previous.filter != options.filter {
+            self.filter.changed(gtk::FilterChange::Different);
+        }
+        if previous.sort != options.sort {
+            self.sorter.changed(gtk::SorterChange::Different);
+        }
+        // Each album gets a new widget
+        if previous.compact != options.compact {
+            self.bind_flowbox();
+        }
+    }
 }
 
 impl EventListener for Library {
@@ -135,7 +252,9 @@ impl EventListener for Library {
             AppEvent::LoginEvent(LoginEvent::LoginCompleted(_)) => {
                 let _ = self.model.refresh_saved_albums();
             }
+            AppEvent::BrowserEvent(BrowserEvent::LibraryOptionsChanged) => self.update_options(),
             AppEvent::BrowserEvent(BrowserEvent::LibraryUpdated) => {
+                self.model.pages_added();
                 self.widget
                     .status_page()
                     .set_visible(!self.model.has_albums());
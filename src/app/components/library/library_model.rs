@@ -3,12 +3,13 @@ use std::ops::Deref;
 use std::rc::Rc;
 
 use crate::app::models::*;
-use crate::app::state::HomeState;
-use crate::app::{ActionDispatcher, AppAction, AppModel, BrowserAction, ListStore};
+use crate::app::state::{HomeState, LibraryOptions, LibrarySort};
+use crate::app::{ActionDispatcher, AppAction, AppModel, BrowserAction, ListStore, Prefetcher};
 
 pub struct LibraryModel {
     app_model: Rc<AppModel>,
     dispatcher: Box<dyn ActionDispatcher>,
+    prefetcher: Prefetcher,
 }
 
 impl LibraryModel {
@@ -16,6 +17,7 @@ impl LibraryModel {
         Self {
             app_model,
             dispatcher,
+            prefetcher: Prefetcher::new(),
         }
     }
 
@@ -47,6 +49,11 @@ impl LibraryModel {
             .unwrap_or(false)
     }
 
+    // The list was updated, with the pages fetched so far
+    pub fn pages_added(&self) {
+        self.prefetcher.release_fetched();
+    }
+
     pub fn load_more_albums(&self) -> Option<()> {
         let api = self.app_model.get_spotify();
 
@@ -55,7 +62,7 @@ impl LibraryModel {
         let offset = next_page.next_offset?;
 
         self.dispatcher
-            .call_spotify_and_dispatch(move || async move {
+            .call_spotify_and_prefetch(&self.prefetcher, offset, move || async move {
                 api.get_saved_albums(offset, batch_size)
                     .await
                     .map(|albums| BrowserAction::AppendLibraryContent(albums).into())
@@ -64,6 +71,31 @@ impl LibraryModel {
         Some(())
     }
 
+    pub fn options(&self) -> LibraryOptions {
+        self.state()
+            .map(|s| s.library_options.clone())
+            .unwrap_or_default()
+    }
+
+    pub fn set_sort(&self, sort: LibrarySort) {
+        self.dispatcher
+            .dispatch(BrowserAction::SetLibrarySort(sort).into());
+    }
+
+    pub fn set_filter(&self, filter: String) {
+        self.dispatcher
+            .dispatch(BrowserAction::SetLibraryFilter(filter).into());
+    }
+
+    pub fn set_compact(&self, compact: bool) {
+        self.dispatcher
+            .dispatch(BrowserAction::SetLibraryCompact(compact).into());
+    }
+
+    pub fn save_options(&self) {
+        self.options().save();
+    }
+
     pub fn open_album(&self, album_id: String) {
         self.dispatcher.dispatch(AppAction::ViewAlbum(album_id));
     }
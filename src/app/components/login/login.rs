@@ -27,6 +27,9 @@ mod imp {
         #[template_child]
         pub login_button: TemplateChild<gtk::Button>,
 
+        #[template_child]
+        pub browser_login_button: TemplateChild<gtk::Button>,
+
         #[template_child]
         pub auth_error_container: TemplateChild<gtk::Revealer>,
     }
@@ -98,6 +101,18 @@ impl LoginWindow {
             }));
     }
 
+    fn connect_browser_login<F>(&self, on_click: F)
+    where
+        F: Fn() + 'static,
+    {
+        self.imp()
+            .browser_login_button
+            .connect_clicked(clone!(@weak self as _self => move |_| {
+                _self.show_auth_error(false);
+                on_click();
+            }));
+    }
+
     fn show_auth_error(&self, shown: bool) {
         let error_class = "error";
         let widget = self.imp();
@@ -154,6 +169,10 @@ impl Login {
             model.login(username.to_string(), password.to_string());
         }));
 
+        login_window.connect_browser_login(clone!(@weak model => move || {
+            model.login_with_browser();
+        }));
+
         Self {
             parent,
             login_window,
@@ -189,6 +208,9 @@ impl EventListener for Login {
             ))) => {
                 self.hide_and_save_creds(creds.clone());
             }
+            AppEvent::LoginEvent(LoginEvent::AuthorizationPageOpened(url)) => {
+                self.model.open_authorization_page(url);
+            }
             AppEvent::LoginEvent(LoginEvent::LoginFailed) => {
                 self.model.clear_saved_credentials();
                 self.reveal_error();
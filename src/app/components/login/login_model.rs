@@ -2,6 +2,8 @@ use std::time::SystemTime;
 
 use gettextrs::*;
 
+use crate::api::DEMO_USER;
+use crate::app::components::utils;
 use crate::app::credentials::Credentials;
 use crate::app::state::{LoginAction, TryLoginAction};
 use crate::app::{ActionDispatcher, AppAction, Worker};
@@ -9,25 +11,43 @@ use crate::app::{ActionDispatcher, AppAction, Worker};
 pub struct LoginModel {
     dispatcher: Box<dyn ActionDispatcher>,
     worker: Worker,
+    // In demo mode, the saved credentials are neither used nor cleared
+    demo: bool,
 }
 
 impl LoginModel {
-    pub fn new(dispatcher: Box<dyn ActionDispatcher>, worker: Worker) -> Self {
-        Self { dispatcher, worker }
+    pub fn new(dispatcher: Box<dyn ActionDispatcher>, worker: Worker, demo: bool) -> Self {
+        Self {
+            dispatcher,
+            worker,
+            demo,
+        }
     }
 
     pub fn try_autologin(&self) {
+        if self.demo {
+            let action = LoginAction::TryLogin(TryLoginAction::Token {
+                username: DEMO_USER.to_string(),
+                token: String::new(),
+            });
+            self.dispatcher.dispatch(action.into());
+            return;
+        }
         self.dispatcher.dispatch_async(Box::pin(async {
             let action = match Credentials::retrieve().await {
-                Ok(creds) => LoginAction::TryLogin(if creds.token_expired() {
-                    TryLoginAction::Password {
+                Ok(creds) => LoginAction::TryLogin(if !creds.token_expired() {
+                    TryLoginAction::Token {
                         username: creds.username,
-                        password: creds.password,
+                        token: creds.token,
+                    }
+                } else if creds.refresh_token.is_some() {
+                    TryLoginAction::OAuth {
+                        refresh_token: creds.refresh_token,
                     }
                 } else {
-                    TryLoginAction::Token {
+                    TryLoginAction::Password {
                         username: creds.username,
-                        token: creds.token,
+                        password: creds.password,
                     }
                 }),
                 Err(err) => {
@@ -40,12 +60,18 @@ impl LoginModel {
     }
 
     pub fn clear_saved_credentials(&self) {
+        if self.demo {
+            return;
+        }
         self.worker.send_task(async {
             let _ = Credentials::logout().await;
         });
     }
 
     pub fn save_token(&self, token: String, token_expiry_time: SystemTime) {
+        if self.demo {
+            return;
+        }
         self.worker.send_task(async move {
             if let Ok(mut credentials) = Credentials::retrieve().await {
                 credentials.token = token;
@@ -58,6 +84,9 @@ impl LoginModel {
     }
 
     pub fn save_for_autologin(&self, credentials: Credentials) {
+        if self.demo {
+            return;
+        }
         self.dispatcher.dispatch_async(Box::pin(async move {
             let Err(err) = credentials.save().await else {
                 return None;
@@ -74,4 +103,17 @@ impl LoginModel {
         self.dispatcher
             .dispatch(LoginAction::TryLogin(TryLoginAction::Password { username, password }).into())
     }
+
+    pub fn login_with_browser(&self) {
+        self.dispatcher.dispatch(
+            LoginAction::TryLogin(TryLoginAction::OAuth {
+                refresh_token: None,
+            })
+            .into(),
+        )
+    }
+
+    pub fn open_authorization_page(&self, url: &str) {
+        utils::open_in_browser(url);
+    }
 }
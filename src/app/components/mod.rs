@@ -11,7 +11,8 @@ use std::collections::HashSet;
 use std::future::Future;
 
 use crate::api::SpotifyApiError;
-use crate::app::{state::LoginAction, ActionDispatcher, AppAction, AppEvent};
+use crate::app::loader::ArtworkColor;
+use crate::app::{state::LoginAction, ActionDispatcher, AppAction, AppEvent, Prefetcher};
 
 mod navigation;
 pub use navigation::*;
@@ -31,9 +32,66 @@ pub use settings::*;
 mod player_notifier;
 pub use player_notifier::PlayerNotifier;
 
+mod playlist_refresher;
+pub use playlist_refresher::{fetch_playlist, PlaylistRefresher};
+
+mod autoplay;
+pub use autoplay::Autoplay;
+
+mod pending_changes;
+pub use pending_changes::PendingChangesCommitter;
+
+mod saved_content_loader;
+pub use saved_content_loader::SavedContentLoader;
+
+mod saved_tracks_sync;
+pub use saved_tracks_sync::SavedTracksSync;
+
+mod saved_tracks_checker;
+pub use saved_tracks_checker::SavedTracksChecker;
+
+mod recently_played_sync;
+pub use recently_played_sync::RecentlyPlayedSync;
+
+mod track_notifier;
+pub use track_notifier::TrackNotifier;
+
+mod track_opener;
+pub use track_opener::TrackOpener;
+
+mod alarm_clock;
+pub use alarm_clock::AlarmClock;
+
+mod release_watcher;
+pub use release_watcher::ReleaseWatcher;
+
+mod show_watcher;
+pub use show_watcher::ShowWatcher;
+
+mod idle_inhibitor;
+pub use idle_inhibitor::IdleInhibitor;
+
+mod call_watcher;
+pub use call_watcher::CallWatcher;
+
+mod network_watcher;
+pub use network_watcher::NetworkWatcher;
+
+mod crash_reporter;
+pub use crash_reporter::CrashReporter;
+
+mod session_keeper;
+pub use session_keeper::SessionKeeper;
+
+mod scrobbler;
+pub use scrobbler::{ScrobbleSpool, Scrobbler};
+
 mod library;
 pub use library::*;
 
+mod new_releases;
+pub use new_releases::*;
+
 mod details;
 pub use details::*;
 
@@ -64,6 +122,24 @@ pub use saved_tracks::*;
 mod user_menu;
 pub use user_menu::*;
 
+mod history_import;
+pub use history_import::*;
+
+mod stats;
+pub use stats::*;
+
+mod diagnostics;
+pub use diagnostics::*;
+
+mod health_check;
+pub use health_check::*;
+
+mod playlist_compare;
+pub use playlist_compare::*;
+
+mod share;
+pub use share::{bind_share_menu, Shareable};
+
 mod notification;
 pub use notification::*;
 
@@ -89,6 +165,12 @@ pub mod utils;
 
 pub mod labels;
 
+pub mod indicators;
+
+pub mod artist_links;
+
+pub mod display;
+
 pub mod sidebar;
 
 // without this the builder doesn't seen to know about the custom widgets
@@ -105,7 +187,7 @@ impl dyn ActionDispatcher {
     fn call_spotify_and_dispatch<F, C>(&self, call: C)
     where
         C: 'static + Send + Clone + FnOnce() -> F,
-        F: Send + Future<Output = Result<AppAction, SpotifyApiError>>,
+        F: 'static + Send + Future<Output = Result<AppAction, SpotifyApiError>>,
     {
         self.call_spotify_and_dispatch_many(move || async { call().await.map(|a| vec![a]) })
     }
@@ -113,28 +195,68 @@ impl dyn ActionDispatcher {
     fn call_spotify_and_dispatch_many<F, C>(&self, call: C)
     where
         C: 'static + Send + Clone + FnOnce() -> F,
-        F: Send + Future<Output = Result<Vec<AppAction>, SpotifyApiError>>,
+        F: 'static + Send + Future<Output = Result<Vec<AppAction>, SpotifyApiError>>,
     {
-        self.dispatch_many_async(Box::pin(async move {
-            let first_call = call.clone();
-            let result = first_call().await;
-            match result {
-                Ok(actions) => actions,
-                Err(SpotifyApiError::NoToken) => vec![],
-                Err(SpotifyApiError::InvalidToken) => {
-                    let mut retried = call().await.unwrap_or_else(|_| Vec::new());
-                    retried.insert(0, LoginAction::RefreshToken.into());
-                    retried
+        self.dispatch_many_async(Box::pin(actions_for_spotify_call(call)))
+    }
+
+    // Same as call_spotify_and_dispatch, but to fetch a page of some list: nothing happens if the prefetcher
+    // is already fetching the page at that offset
+    fn call_spotify_and_prefetch<F, C>(&self, prefetcher: &Prefetcher, offset: usize, call: C)
+    where
+        C: 'static + Send + Clone + FnOnce() -> F,
+        F: 'static + Send + Future<Output = Result<AppAction, SpotifyApiError>>,
+    {
+        let actions = try_actions_for_spotify_call(move || async { call().await.map(|a| vec![a]) });
+        if let Some(fetch) = prefetcher.fetch(offset, actions) {
+            self.dispatch_many_async(Box::pin(async move {
+                match fetch.await {
+                    Some(Ok(actions) | Err(actions)) => actions,
+                    None => vec![],
                 }
-                Err(err) => {
-                    error!("Spotify API error: {}", err);
-                    vec![AppAction::ShowNotification(gettext(
-                        // translators: This notification is the default message for unhandled errors. Logs refer to console output.
-                        "An error occured. Check logs for details!",
-                    ))]
+            }));
+        }
+    }
+}
+
+async fn actions_for_spotify_call<F, C>(call: C) -> Vec<AppAction>
+where
+    C: 'static + Send + Clone + FnOnce() -> F,
+    F: 'static + Send + Future<Output = Result<Vec<AppAction>, SpotifyApiError>>,
+{
+    try_actions_for_spotify_call(call)
+        .await
+        .unwrap_or_else(|actions| actions)
+}
+
+// Same as above, but tells whether the call succeeded: the error holds what to dispatch instead
+async fn try_actions_for_spotify_call<F, C>(call: C) -> Result<Vec<AppAction>, Vec<AppAction>>
+where
+    C: 'static + Send + Clone + FnOnce() -> F,
+    F: 'static + Send + Future<Output = Result<Vec<AppAction>, SpotifyApiError>>,
+{
+    let first_call = call.clone();
+    let result = first_call().await;
+    match result {
+        Ok(actions) => Ok(actions),
+        Err(SpotifyApiError::NoToken) => Err(vec![]),
+        Err(SpotifyApiError::InvalidToken) => {
+            let refresh = LoginAction::RefreshToken.into();
+            match call().await {
+                Ok(mut retried) => {
+                    retried.insert(0, refresh);
+                    Ok(retried)
                 }
+                Err(_) => Err(vec![refresh]),
             }
-        }))
+        }
+        Err(err) => {
+            error!("Spotify API error: {}", err);
+            Err(vec![AppAction::ShowNotification(gettext(
+                // translators: This notification is the default message for unhandled errors. Logs refer to console output.
+                "An error occured. Check logs for details!",
+            ))])
+        }
     }
 }
 
@@ -159,8 +281,49 @@ pub fn display_add_css_provider(resource: &'static str) {
     });
 }
 
+thread_local!(static ARTWORK_COLORS_ADDED: RefCell<HashSet<String>> = RefCell::new(HashSet::new()));
+
+// Registers a style class that tints the header of a details page with the given color,
+// and returns its name.
+pub fn display_add_artwork_color(color: ArtworkColor) -> String {
+    let hex = color.to_hex();
+    let class = format!("artwork-color-{}", &hex[1..]);
+    ARTWORK_COLORS_ADDED.with(|set| {
+        if !set.borrow_mut().insert(class.clone()) {
+            return;
+        }
+
+        let foreground = if color.is_light() {
+            "rgba(0, 0, 0, 0.8)"
+        } else {
+            "#ffffff"
+        };
+        // Only while artwork colors are enabled (see MainWindow)
+        let css = format!(
+            "window.artwork-colors windowhandle > clamp.{class}, window.artwork-colors headerbar.flat.{class} {{ background-color: {hex}; color: {foreground}; }}
+            window.artwork-colors clamp.{class} .title-4 label {{ color: {foreground}; }}"
+        );
+
+        let provider = gtk::CssProvider::new();
+        provider.load_from_data(&css);
+
+        gtk::style_context_add_provider_for_display(
+            &gdk::Display::default().unwrap(),
+            &provider,
+            gtk::STYLE_PROVIDER_PRIORITY_APPLICATION,
+        );
+    });
+    class
+}
+
 pub trait EventListener {
     fn on_event(&mut self, _: &AppEvent) {}
+
+    // Screens are mounted when they come into view (pushed, or uncovered), and unmounted when they're
+    // popped or covered: a good time to stop whatever only makes sense while the user is looking
+    fn on_mount(&mut self) {}
+
+    fn on_unmount(&mut self) {}
 }
 
 pub trait Component {
@@ -177,6 +340,22 @@ pub trait Component {
             }
         }
     }
+
+    fn broadcast_mount(&mut self) {
+        if let Some(children) = self.get_children() {
+            for child in children.iter_mut() {
+                child.on_mount();
+            }
+        }
+    }
+
+    fn broadcast_unmount(&mut self) {
+        if let Some(children) = self.get_children() {
+            for child in children.iter_mut() {
+                child.on_unmount();
+            }
+        }
+    }
 }
 
 pub trait ListenerComponent: Component + EventListener {}
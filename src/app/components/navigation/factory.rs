@@ -12,6 +12,18 @@ pub struct ScreenFactory {
     leaflet: libadwaita::Leaflet,
 }
 
+// Lazy screens keep one around to be made later
+impl Clone for ScreenFactory {
+    fn clone(&self) -> Self {
+        Self {
+            app_model: Rc::clone(&self.app_model),
+            dispatcher: self.dispatcher.box_clone(),
+            worker: self.worker.clone(),
+            leaflet: self.leaflet.clone(),
+        }
+    }
+}
+
 impl ScreenFactory {
     pub fn new(
         app_model: Rc<AppModel>,
@@ -42,6 +54,25 @@ impl ScreenFactory {
         )
     }
 
+    pub fn make_new_releases(&self) -> impl ListenerComponent {
+        let model = NewReleasesModel::new(
+            Rc::clone(&self.app_model),
+            self.dispatcher.box_clone(),
+            self.worker.clone(),
+        );
+        let screen_model = DefaultHeaderBarModel::new(
+            Some(gettext("New Releases")),
+            None,
+            Rc::clone(&self.app_model),
+            self.dispatcher.box_clone(),
+        );
+        StandardScreen::new(
+            NewReleases::new(self.worker.clone(), model),
+            &self.leaflet,
+            Rc::new(screen_model),
+        )
+    }
+
     pub fn make_sidebar(&self, listbox: gtk::ListBox) -> impl ListenerComponent {
         let model = SidebarModel::new(Rc::clone(&self.app_model), self.dispatcher.box_clone());
         Sidebar::new(listbox, Rc::new(model))
@@ -124,22 +155,26 @@ impl ScreenFactory {
 
     pub fn make_playlist_details(&self, id: String) -> impl ListenerComponent {
         let model = Rc::new(PlaylistDetailsModel::new(
-            id,
+            id.clone(),
             Rc::clone(&self.app_model),
             self.dispatcher.box_clone(),
         ));
-        PlaylistDetails::new(model, self.worker.clone())
+        let compare_model =
+            PlaylistCompareModel::new(id, Rc::clone(&self.app_model), self.dispatcher.box_clone());
+        PlaylistDetails::new(model, compare_model, self.worker.clone())
     }
 
     pub fn make_user_details(&self, id: String) -> impl ListenerComponent {
-        let screen_model = DefaultHeaderBarModel::new(
-            None,
-            None,
+        let model = Rc::new(UserDetailsModel::new(
+            id,
+            Rc::clone(&self.app_model),
+            self.dispatcher.box_clone(),
+        ));
+        let screen_model = SimpleHeaderBarModelWrapper::new(
+            Rc::clone(&model),
             Rc::clone(&self.app_model),
             self.dispatcher.box_clone(),
         );
-        let model =
-            UserDetailsModel::new(id, Rc::clone(&self.app_model), self.dispatcher.box_clone());
         StandardScreen::new(
             UserDetails::new(model, self.worker.clone()),
             &self.leaflet,
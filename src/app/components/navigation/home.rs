@@ -4,85 +4,125 @@ use crate::app::components::sidebar::SidebarDestination;
 use crate::app::components::{Component, EventListener, ScreenFactory};
 use crate::app::{AppEvent, BrowserEvent};
 
+use super::{EventKind, ScreenRegistry, ALL_EVENTS};
+
+// Pages listing albums or playlists don't follow playback
+const LISTING_EVENTS: &[EventKind] = &[
+    EventKind::App,
+    EventKind::Login,
+    EventKind::Browser,
+    EventKind::Selection,
+];
+
 pub struct HomePane {
     stack: gtk::Stack,
-    components: Vec<Box<dyn EventListener>>,
+    sidebar: Box<dyn EventListener>,
+    pages: ScreenRegistry,
+    visible_page: &'static str,
 }
 
 impl HomePane {
     pub fn new(listbox: gtk::ListBox, screen_factory: &ScreenFactory) -> Self {
-        let library = screen_factory.make_library();
-        let saved_playlists = screen_factory.make_saved_playlists();
-        let saved_tracks = screen_factory.make_saved_tracks();
-        let now_playing = screen_factory.make_now_playing();
-        let sidebar = screen_factory.make_sidebar(listbox);
-
-        let stack = gtk::Stack::new();
-        stack.set_transition_type(gtk::StackTransitionType::Crossfade);
-
-        let dest = SidebarDestination::Library;
-        stack.add_titled(
-            library.get_root_widget(),
-            Option::from(dest.id()),
-            &dest.title(),
+        let mut pages = ScreenRegistry::new();
+        pages.register(
+            SidebarDestination::Library.id(),
+            LISTING_EVENTS,
+            screen_factory.make_library(),
         );
-
-        let dest = SidebarDestination::SavedTracks;
-        stack.add_titled(
-            saved_tracks.get_root_widget(),
-            Option::from(dest.id()),
-            &dest.title(),
+        pages.register(
+            SidebarDestination::NewReleases.id(),
+            LISTING_EVENTS,
+            screen_factory.make_new_releases(),
         );
-
-        let dest = SidebarDestination::SavedPlaylists;
-        stack.add_titled(
-            saved_playlists.get_root_widget(),
-            Option::from(dest.id()),
-            &dest.title(),
+        let factory = screen_factory.clone();
+        pages.register_lazy(
+            SidebarDestination::SavedTracks.id(),
+            ALL_EVENTS,
+            move || factory.make_saved_tracks(),
         );
-
-        let dest = SidebarDestination::NowPlaying;
-        stack.add_titled(
-            now_playing.get_root_widget(),
-            Option::from(dest.id()),
-            &dest.title(),
+        let factory = screen_factory.clone();
+        pages.register_lazy(
+            SidebarDestination::SavedPlaylists.id(),
+            LISTING_EVENTS,
+            move || factory.make_saved_playlists(),
+        );
+        pages.register(
+            SidebarDestination::NowPlaying.id(),
+            ALL_EVENTS,
+            screen_factory.make_now_playing(),
         );
 
+        let stack = gtk::Stack::new();
+        stack.set_transition_type(gtk::StackTransitionType::Crossfade);
+
+        let destinations = [
+            SidebarDestination::Library,
+            SidebarDestination::NewReleases,
+            SidebarDestination::SavedTracks,
+            SidebarDestination::SavedPlaylists,
+            SidebarDestination::NowPlaying,
+        ];
+        for dest in destinations.iter() {
+            // Lazy pages are put in place once made (see show_page)
+            let placeholder = gtk::Box::new(gtk::Orientation::Vertical, 0);
+            let widget = pages
+                .widget(dest.id())
+                .cloned()
+                .unwrap_or_else(|| placeholder.upcast());
+            stack.add_titled(&widget, Some(dest.id()), &dest.title());
+        }
+
         Self {
             stack,
-            components: vec![
-                Box::new(sidebar),
-                Box::new(library),
-                Box::new(saved_playlists),
-                Box::new(saved_tracks),
-                Box::new(now_playing),
-            ],
+            sidebar: Box::new(screen_factory.make_sidebar(listbox)),
+            pages,
+            visible_page: SidebarDestination::Library.id(),
         }
     }
+
+    fn show_page(&mut self, page: &'static str) {
+        if page != self.visible_page {
+            self.pages.unmount(self.visible_page);
+            self.visible_page = page;
+        }
+        if let Some(widget) = self.pages.mount(page) {
+            let placeholder = self
+                .stack
+                .child_by_name(page)
+                .and_then(|child| child.downcast::<gtk::Box>().ok());
+            if let Some(placeholder) = placeholder {
+                widget.set_vexpand(true);
+                placeholder.append(&widget);
+            }
+        }
+        self.stack.set_visible_child_name(page);
+    }
 }
 
 impl Component for HomePane {
     fn get_root_widget(&self) -> &gtk::Widget {
         self.stack.upcast_ref()
     }
-
-    fn get_children(&mut self) -> Option<&mut Vec<Box<dyn EventListener>>> {
-        Some(&mut self.components)
-    }
 }
 
 impl EventListener for HomePane {
     fn on_event(&mut self, event: &AppEvent) {
         match event {
-            AppEvent::NowPlayingShown => {
-                self.stack
-                    .set_visible_child_name(SidebarDestination::NowPlaying.id());
-            }
+            AppEvent::NowPlayingShown => self.show_page(SidebarDestination::NowPlaying.id()),
             AppEvent::BrowserEvent(BrowserEvent::HomeVisiblePageChanged(page)) => {
-                self.stack.set_visible_child_name(page);
+                self.show_page(page)
             }
             _ => {}
         }
-        self.broadcast_event(event);
+        self.sidebar.on_event(event);
+        self.pages.on_event(event);
+    }
+
+    fn on_mount(&mut self) {
+        self.show_page(self.visible_page);
+    }
+
+    fn on_unmount(&mut self) {
+        self.pages.unmount(self.visible_page);
     }
 }
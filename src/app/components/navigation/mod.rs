@@ -6,5 +6,8 @@ pub use navigation_model::*;
 
 mod home;
 
+mod registry;
+pub use registry::*;
+
 mod factory;
 pub use factory::*;
@@ -1,5 +1,6 @@
 use gtk::traits::WidgetExt;
 use libadwaita::NavigationDirection;
+use std::cell::RefCell;
 use std::rc::Rc;
 
 use crate::app::components::{EventListener, ListenerComponent};
@@ -11,21 +12,25 @@ use super::{factory::ScreenFactory, home::HomePane, NavigationModel};
 pub struct Navigation {
     model: Rc<NavigationModel>,
     leaflet: libadwaita::Leaflet,
-    navigation_stack: gtk::Stack,
+    // Screens slide in and out of this one, and it can be swiped back (on touchpads and touchscreens)
+    navigation_stack: libadwaita::Leaflet,
     home_listbox: gtk::ListBox,
     screen_factory: ScreenFactory,
     children: Vec<Box<dyn ListenerComponent>>,
+    // Popped screens are only removed once they've slid out of view
+    popped_widgets: Rc<RefCell<Vec<gtk::Widget>>>,
 }
 
 impl Navigation {
     pub fn new(
         model: NavigationModel,
         leaflet: libadwaita::Leaflet,
-        navigation_stack: gtk::Stack,
+        navigation_stack: libadwaita::Leaflet,
         home_listbox: gtk::ListBox,
         screen_factory: ScreenFactory,
     ) -> Self {
         let model = Rc::new(model);
+        let popped_widgets: Rc<RefCell<Vec<gtk::Widget>>> = Default::default();
 
         leaflet.connect_folded_notify(
             clone!(@weak model => move |leaflet| {
@@ -43,6 +48,23 @@ impl Navigation {
             })
         );
 
+        // The visible screen only changes on its own when the user swipes back: the state needs to follow
+        navigation_stack.connect_visible_child_name_notify(clone!(@weak model => move |stack| {
+            let visible = stack.visible_child_name();
+            let expected = model.visible_child_name().identifier().to_string();
+            if visible.map(|s| s.as_str() != expected).unwrap_or(false) {
+                model.go_back();
+            }
+        }));
+
+        navigation_stack.connect_child_transition_running_notify(
+            clone!(@weak popped_widgets => move |stack| {
+                if !stack.is_child_transition_running() {
+                    Self::remove_popped(stack, &popped_widgets);
+                }
+            }),
+        );
+
         Self {
             model,
             leaflet,
@@ -50,6 +72,14 @@ impl Navigation {
             home_listbox,
             screen_factory,
             children: vec![],
+            popped_widgets,
+        }
+    }
+
+    fn remove_popped(stack: &libadwaita::Leaflet, popped_widgets: &RefCell<Vec<gtk::Widget>>) {
+        let widgets: Vec<gtk::Widget> = popped_widgets.borrow_mut().drain(..).collect();
+        for widget in widgets {
+            stack.remove(&widget);
         }
     }
 
@@ -81,11 +111,21 @@ impl Navigation {
         };
 
         let widget = component.get_root_widget().clone();
+        if let Some(covered) = self.children.last_mut() {
+            covered.on_unmount();
+        }
         self.children.push(component);
+        if let Some(pushed) = self.children.last_mut() {
+            pushed.on_mount();
+        }
+
+        // A screen that was just popped might still be sliding out, and could have the same name
+        Self::remove_popped(&self.navigation_stack, &self.popped_widgets);
 
         self.leaflet.navigate(NavigationDirection::Forward);
         self.navigation_stack
-            .add_named(&widget, Some(name.identifier().as_ref()));
+            .append(&widget)
+            .set_name(Some(name.identifier().as_ref()));
         self.navigation_stack
             .set_visible_child_name(name.identifier().as_ref());
 
@@ -94,26 +134,37 @@ impl Navigation {
         });
     }
 
-    fn pop(&mut self) {
-        let children = &mut self.children;
-        let popped = children.pop();
+    fn show_current_screen(&mut self, mut popped: Vec<Box<dyn ListenerComponent>>) {
+        if let Some(top) = popped.last_mut() {
+            top.on_unmount();
+        }
+        if let Some(uncovered) = self.children.last_mut() {
+            uncovered.on_mount();
+        }
 
         let name = self.model.visible_child_name();
         self.navigation_stack
             .set_visible_child_name(name.identifier().as_ref());
 
-        if let Some(child) = popped {
-            self.navigation_stack.remove(child.get_root_widget());
+        self.popped_widgets.borrow_mut().extend(
+            popped
+                .iter()
+                .map(|component| component.get_root_widget().clone()),
+        );
+        // No transition at all if animations are disabled
+        if !self.navigation_stack.is_child_transition_running() {
+            Self::remove_popped(&self.navigation_stack, &self.popped_widgets);
         }
     }
 
-    fn pop_to(&mut self, screen: &ScreenName) {
-        self.navigation_stack
-            .set_visible_child_name(screen.identifier().as_ref());
-        let remainder = self.children.split_off(self.model.children_count());
-        for widget in remainder {
-            self.navigation_stack.remove(widget.get_root_widget());
-        }
+    fn pop(&mut self) {
+        let popped = self.children.pop().into_iter().collect();
+        self.show_current_screen(popped);
+    }
+
+    fn pop_to(&mut self) {
+        let popped = self.children.split_off(self.model.children_count());
+        self.show_current_screen(popped);
     }
 }
 
@@ -132,8 +183,8 @@ impl EventListener for Navigation {
             AppEvent::BrowserEvent(BrowserEvent::NavigationPopped) => {
                 self.pop();
             }
-            AppEvent::BrowserEvent(BrowserEvent::NavigationPoppedTo(name)) => {
-                self.pop_to(name);
+            AppEvent::BrowserEvent(BrowserEvent::NavigationPoppedTo(_)) => {
+                self.pop_to();
             }
             AppEvent::BrowserEvent(BrowserEvent::HomeVisiblePageChanged(_)) => {
                 self.leaflet.navigate(NavigationDirection::Forward);
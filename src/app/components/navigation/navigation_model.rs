@@ -25,6 +25,11 @@ impl NavigationModel {
             .dispatch(BrowserAction::SetNavigationHidden(hidden).into());
     }
 
+    pub fn go_back(&self) {
+        self.dispatcher
+            .dispatch(BrowserAction::NavigationPop.into());
+    }
+
     pub fn children_count(&self) -> usize {
         self.app_model.get_state().browser.count()
     }
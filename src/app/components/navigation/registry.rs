@@ -0,0 +1,177 @@
+use crate::app::components::{EventListener, ListenerComponent};
+use crate::app::AppEvent;
+
+// Broad categories of events, for screens to say what they need to hear about
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EventKind {
+    Playback,
+    Browser,
+    Selection,
+    Login,
+    Settings,
+    // Anything else (Started, notifications...)
+    App,
+}
+
+impl EventKind {
+    pub fn of(event: &AppEvent) -> Self {
+        match event {
+            AppEvent::PlaybackEvent(_) => Self::Playback,
+            AppEvent::BrowserEvent(_) => Self::Browser,
+            AppEvent::SelectionEvent(_) => Self::Selection,
+            AppEvent::LoginEvent(_) => Self::Login,
+            AppEvent::SettingsEvent(_) => Self::Settings,
+            _ => Self::App,
+        }
+    }
+}
+
+// Everything: for screens with song lists or anything else following playback
+pub const ALL_EVENTS: &[EventKind] = &[];
+
+type Factory = Box<dyn FnOnce() -> Box<dyn ListenerComponent>>;
+
+struct Registration {
+    name: &'static str,
+    // Empty to hear about everything
+    interests: &'static [EventKind],
+    // Until the screen is made
+    factory: Option<Factory>,
+    component: Option<Box<dyn ListenerComponent>>,
+    mounted: bool,
+}
+
+impl Registration {
+    fn wants(&self, event: &AppEvent) -> bool {
+        self.interests.is_empty() || self.interests.contains(&EventKind::of(event))
+    }
+}
+
+// Screens declared by name, along with the events they care about (the others don't go through them).
+// Lazy ones are only made when first mounted, so that nothing is loaded for pages never visited.
+#[derive(Default)]
+pub struct ScreenRegistry {
+    registrations: Vec<Registration>,
+}
+
+impl ScreenRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register(
+        &mut self,
+        name: &'static str,
+        interests: &'static [EventKind],
+        component: impl ListenerComponent + 'static,
+    ) {
+        self.registrations.push(Registration {
+            name,
+            interests,
+            factory: None,
+            component: Some(Box::new(component)),
+            mounted: false,
+        });
+    }
+
+    pub fn register_lazy<C, F>(&mut self, name: &'static str, interests: &'static [EventKind], f: F)
+    where
+        C: ListenerComponent + 'static,
+        F: FnOnce() -> C + 'static,
+    {
+        self.registrations.push(Registration {
+            name,
+            interests,
+            factory: Some(Box::new(move || {
+                Box::new(f()) as Box<dyn ListenerComponent>
+            })),
+            component: None,
+            mounted: false,
+        });
+    }
+
+    fn registration_mut(&mut self, name: &str) -> Option<&mut Registration> {
+        self.registrations.iter_mut().find(|r| r.name == name)
+    }
+
+    // The root widget of a screen, None if it's lazy and wasn't made yet
+    pub fn widget(&self, name: &str) -> Option<&gtk::Widget> {
+        self.registrations
+            .iter()
+            .find(|r| r.name == name)?
+            .component
+            .as_ref()
+            .map(|c| c.get_root_widget())
+    }
+
+    // Makes the screen if needed, and lets it know it's in view: its root widget is returned if it was
+    // just made, for the caller to put it somewhere
+    pub fn mount(&mut self, name: &str) -> Option<gtk::Widget> {
+        let registration = self.registration_mut(name)?;
+        let made = registration.factory.take().map(|factory| factory());
+        let widget = made.as_ref().map(|c| c.get_root_widget().clone());
+        if made.is_some() {
+            registration.component = made;
+        }
+        if !registration.mounted {
+            registration.mounted = true;
+            if let Some(component) = registration.component.as_mut() {
+                component.on_mount();
+            }
+        }
+        widget
+    }
+
+    pub fn unmount(&mut self, name: &str) {
+        if let Some(registration) = self.registration_mut(name) {
+            if registration.mounted {
+                registration.mounted = false;
+                if let Some(component) = registration.component.as_mut() {
+                    component.on_unmount();
+                }
+            }
+        }
+    }
+}
+
+impl EventListener for ScreenRegistry {
+    fn on_event(&mut self, event: &AppEvent) {
+        for registration in self.registrations.iter_mut() {
+            if !registration.wants(event) {
+                continue;
+            }
+            if let Some(component) = registration.component.as_mut() {
+                component.on_event(event);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::app::state::PlaybackEvent;
+    use crate::app::BrowserEvent;
+
+    fn registration(interests: &'static [EventKind]) -> Registration {
+        Registration {
+            name: "screen",
+            interests,
+            factory: None,
+            component: None,
+            mounted: false,
+        }
+    }
+
+    #[test]
+    fn test_screen_interests() {
+        let library = registration(&[EventKind::App, EventKind::Browser]);
+        assert!(library.wants(&AppEvent::Started));
+        assert!(library.wants(&BrowserEvent::LibraryUpdated.into()));
+        assert!(!library.wants(&PlaybackEvent::SeekSynced(0).into()));
+
+        let now_playing = registration(ALL_EVENTS);
+        assert!(now_playing.wants(&PlaybackEvent::SeekSynced(0).into()));
+    }
+}
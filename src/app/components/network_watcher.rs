@@ -0,0 +1,60 @@
+use gio::prelude::*;
+use std::rc::Rc;
+
+use crate::app::components::EventListener;
+use crate::app::{ActionDispatcher, AppAction, AppEvent, AppModel};
+
+// Captive portals and the like are as good as offline to us
+fn is_online(monitor: &gio::NetworkMonitor) -> bool {
+    monitor.is_network_available() && monitor.connectivity() == gio::NetworkConnectivity::Full
+}
+
+// Follows what the system says of the network: while offline, a banner says so, API calls are served
+// from the cache, and background checks hold off until the connection is back.
+pub struct NetworkWatcher {
+    app_model: Rc<AppModel>,
+    dispatcher: Box<dyn ActionDispatcher>,
+    monitor: gio::NetworkMonitor,
+    banner: libadwaita::Banner,
+}
+
+impl NetworkWatcher {
+    pub fn new(
+        app_model: Rc<AppModel>,
+        dispatcher: Box<dyn ActionDispatcher>,
+        banner: libadwaita::Banner,
+    ) -> Self {
+        Self {
+            app_model,
+            dispatcher,
+            monitor: gio::NetworkMonitor::default(),
+            banner,
+        }
+    }
+
+    fn start(&self) {
+        let dispatcher = self.dispatcher.box_clone();
+        // Fired a few times in a row as interfaces come and go: only actual changes make it past the state
+        self.monitor.connect_network_changed(move |monitor, _| {
+            dispatcher.dispatch(AppAction::SetOnline(is_online(monitor)));
+        });
+        self.dispatcher
+            .dispatch(AppAction::SetOnline(is_online(&self.monitor)));
+    }
+
+    fn update(&self, online: bool) {
+        debug!("Online: {}", online);
+        self.app_model.get_spotify().set_offline(!online);
+        self.banner.set_revealed(!online);
+    }
+}
+
+impl EventListener for NetworkWatcher {
+    fn on_event(&mut self, event: &AppEvent) {
+        match event {
+            AppEvent::Started => self.start(),
+            AppEvent::ConnectivityChanged(online) => self.update(*online),
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,229 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::app::models::{AlbumDescription, ArtistRef, SongBatch};
+
+// How often the feed is refreshed
+pub const FEED_REFRESH_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+// How far back releases are considered new
+pub const FEED_MAX_AGE_DAYS: i32 = 90;
+// More would take a while to scroll through anyway
+const FEED_MAX_LEN: usize = 100;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct CachedRelease {
+    id: String,
+    title: String,
+    // (id, name)
+    artists: Vec<(String, String)>,
+    release_date: Option<String>,
+    art: Option<String>,
+}
+
+impl From<&AlbumDescription> for CachedRelease {
+    fn from(album: &AlbumDescription) -> Self {
+        Self {
+            id: album.id.clone(),
+            title: album.title.clone(),
+            artists: album
+                .artists
+                .iter()
+                .map(|a| (a.id.clone(), a.name.clone()))
+                .collect(),
+            release_date: album.release_date.clone(),
+            art: album.art.clone(),
+        }
+    }
+}
+
+impl From<CachedRelease> for AlbumDescription {
+    fn from(release: CachedRelease) -> Self {
+        Self {
+            id: release.id,
+            title: release.title,
+            artists: release
+                .artists
+                .into_iter()
+                .map(|(id, name)| ArtistRef { id, name })
+                .collect(),
+            release_date: release.release_date,
+            art: release.art,
+            songs: SongBatch::empty(),
+            is_liked: false,
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CachedFeed {
+    // In seconds since epoch
+    fetched_at: u64,
+    releases: Vec<CachedRelease>,
+}
+
+// The latest releases of the artists the user follows, as last fetched. It takes a request per artist
+// to build, so it's kept on disk and only refreshed once a day.
+#[derive(Debug, Default)]
+pub struct ReleaseFeed {
+    path: Option<PathBuf>,
+    feed: CachedFeed,
+}
+
+impl ReleaseFeed {
+    fn default_path() -> PathBuf {
+        glib::user_cache_dir()
+            .join("spot")
+            .join("new_releases.json")
+    }
+
+    pub fn load() -> Self {
+        let path = Self::default_path();
+        let feed = fs::read(&path)
+            .ok()
+            .and_then(|content| serde_json::from_slice(&content).ok())
+            .unwrap_or_default();
+        Self {
+            path: Some(path),
+            feed,
+        }
+    }
+
+    pub fn releases(&self) -> Vec<AlbumDescription> {
+        self.feed
+            .releases
+            .iter()
+            .cloned()
+            .map(AlbumDescription::from)
+            .collect()
+    }
+
+    pub fn is_refresh_due(&self, now: u64) -> bool {
+        now.saturating_sub(self.feed.fetched_at) >= FEED_REFRESH_INTERVAL.as_secs()
+    }
+
+    pub fn update(&mut self, releases: &[AlbumDescription], now: u64) {
+        self.feed = CachedFeed {
+            fetched_at: now,
+            releases: releases.iter().map(CachedRelease::from).collect(),
+        };
+        self.save();
+    }
+
+    fn save(&self) {
+        let path = match self.path.as_ref() {
+            Some(path) => path,
+            None => return,
+        };
+        let result = path
+            .parent()
+            .map(fs::create_dir_all)
+            .unwrap_or(Ok(()))
+            .and_then(|_| {
+                let content = serde_json::to_vec(&self.feed).unwrap_or_default();
+                fs::write(path, content)
+            });
+        if let Err(e) = result {
+            warn!("Could not save new releases: {}", e);
+        }
+    }
+}
+
+// The earliest release date that counts as recent, as of today
+pub fn recent_since() -> Option<String> {
+    let date = glib::DateTime::now_utc()
+        .and_then(|now| now.add_days(-FEED_MAX_AGE_DAYS))
+        .and_then(|date| date.format("%Y-%m-%d"))
+        .ok()?;
+    Some(date.to_string())
+}
+
+// Releases from the given date on, newest first. Releases shared by several followed artists show up once.
+// Dates that are just a year ("2023") can't be placed, they are left out.
+pub fn recent_releases(albums: Vec<AlbumDescription>, since: &str) -> Vec<AlbumDescription> {
+    let mut ids = HashSet::new();
+    let mut releases: Vec<AlbumDescription> = albums
+        .into_iter()
+        .filter(|a| {
+            a.release_date
+                .as_deref()
+                .map_or(false, |date| date >= since)
+        })
+        .filter(|a| ids.insert(a.id.clone()))
+        .collect();
+    releases.sort_by(|a, b| b.release_date.cmp(&a.release_date));
+    releases.truncate(FEED_MAX_LEN);
+    releases
+}
+
+// The releases that came out after the latest one the user saw; none if they never looked
+pub fn unread_releases(releases: &[AlbumDescription], last_seen: &str) -> HashSet<String> {
+    if last_seen.is_empty() {
+        return HashSet::new();
+    }
+    releases
+        .iter()
+        .filter(|a| {
+            a.release_date
+                .as_deref()
+                .map_or(false, |date| date > last_seen)
+        })
+        .map(|a| a.id.clone())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn album(id: &str, release_date: &str) -> AlbumDescription {
+        AlbumDescription {
+            id: id.to_string(),
+            title: id.to_string(),
+            artists: vec![],
+            release_date: Some(release_date.to_string()),
+            art: None,
+            songs: SongBatch::empty(),
+            is_liked: false,
+        }
+    }
+
+    #[test]
+    fn test_recent_releases() {
+        let albums = vec![
+            album("old", "2023-01-01"),
+            album("a", "2023-05-01"),
+            album("b", "2023-06-15"),
+            // Also by another followed artist
+            album("a", "2023-05-01"),
+            album("year_only", "2023"),
+        ];
+        let ids: Vec<String> = recent_releases(albums, "2023-04-01")
+            .into_iter()
+            .map(|a| a.id)
+            .collect();
+        assert_eq!(ids, vec!["b", "a"]);
+    }
+
+    #[test]
+    fn test_unread_releases() {
+        let releases = vec![album("a", "2023-06-15"), album("b", "2023-05-01")];
+        let unread = unread_releases(&releases, "2023-05-01");
+        assert_eq!(unread, HashSet::from(["a".to_string()]));
+        assert!(unread_releases(&releases, "").is_empty());
+    }
+
+    #[test]
+    fn test_feed_cache_round_trip() {
+        let mut feed = ReleaseFeed::default();
+        assert!(feed.is_refresh_due(1000));
+
+        feed.update(&[album("a", "2023-06-15")], 1000);
+        assert!(!feed.is_refresh_due(1000 + 60));
+        assert!(feed.is_refresh_due(1000 + FEED_REFRESH_INTERVAL.as_secs()));
+        assert_eq!(feed.releases()[0].id, "a");
+    }
+}
@@ -0,0 +1,6 @@
+mod feed;
+mod new_releases;
+mod new_releases_model;
+
+pub use new_releases::*;
+pub use new_releases_model::*;
@@ -0,0 +1,183 @@
+use gettextrs::gettext;
+use gtk::prelude::*;
+use gtk::subclass::prelude::*;
+use gtk::CompositeTemplate;
+use std::rc::Rc;
+
+use super::NewReleasesModel;
+use crate::app::components::sidebar::SidebarDestination;
+use crate::app::components::utils::wrap_flowbox_item;
+use crate::app::components::{display_add_css_provider, AlbumWidget, Component, EventListener};
+use crate::app::dispatch::Worker;
+use crate::app::models::AlbumModel;
+use crate::app::state::LoginEvent;
+use crate::app::{AppEvent, BrowserEvent, ListStore};
+
+// The feed is refreshed daily, but Spot can stay open for days: every so often, we see if that's due
+const DUE_CHECK_INTERVAL_SECS: u32 = 60 * 60;
+
+mod imp {
+
+    use super::*;
+
+    #[derive(Debug, Default, CompositeTemplate)]
+    #[template(resource = "/dev/alextren/Spot/components/new_releases.ui")]
+    pub struct NewReleasesWidget {
+        #[template_child]
+        pub scrolled_window: TemplateChild<gtk::ScrolledWindow>,
+
+        #[template_child]
+        pub flowbox: TemplateChild<gtk::FlowBox>,
+
+        #[template_child]
+        pub status_page: TemplateChild<libadwaita::StatusPage>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for NewReleasesWidget {
+        const NAME: &'static str = "NewReleasesWidget";
+        type Type = super::NewReleasesWidget;
+        type ParentType = gtk::Box;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for NewReleasesWidget {}
+    impl WidgetImpl for NewReleasesWidget {}
+    impl BoxImpl for NewReleasesWidget {}
+}
+
+glib::wrapper! {
+    pub struct NewReleasesWidget(ObjectSubclass<imp::NewReleasesWidget>) @extends gtk::Widget, gtk::Box;
+}
+
+impl NewReleasesWidget {
+    pub fn new() -> Self {
+        display_add_css_provider(resource!("/components/new_releases.css"));
+        glib::Object::new()
+    }
+
+    // Releases the user hasn't seen yet get a "New" badge over their cover
+    fn bind_albums<F, U>(
+        &self,
+        worker: Worker,
+        store: &ListStore<AlbumModel>,
+        on_album_pressed: F,
+        is_unread: U,
+    ) where
+        F: Fn(String) + Clone + 'static,
+        U: Fn(&str) -> bool + 'static,
+    {
+        self.imp()
+            .flowbox
+            .bind_model(Some(store.unsafe_store()), move |item| {
+                wrap_flowbox_item(item, |album_model: &AlbumModel| {
+                    let f = on_album_pressed.clone();
+                    let album = AlbumWidget::for_model(album_model, worker.clone());
+                    album.connect_album_pressed(clone!(@weak album_model => move |_| {
+                        f(album_model.uri());
+                    }));
+
+                    let overlay = gtk::Overlay::builder().child(&album).build();
+                    if is_unread(&album_model.uri()) {
+                        // translators: This is a badge shown on releases that came out since the user last looked at the New Releases page.
+                        let badge = gtk::Label::builder()
+                            .label(&gettext("New"))
+                            .halign(gtk::Align::End)
+                            .valign(gtk::Align::Start)
+                            .css_classes(vec!["new-releases__badge".to_string()])
+                            .build();
+                        overlay.add_overlay(&badge);
+                    }
+                    overlay
+                })
+            });
+    }
+
+    pub fn status_page(&self) -> &libadwaita::StatusPage {
+        &self.imp().status_page
+    }
+}
+
+// Recent releases of the artists the user follows, on a page of its own
+pub struct NewReleases {
+    widget: NewReleasesWidget,
+    worker: Worker,
+    model: Rc<NewReleasesModel>,
+    timeout: Option<glib::SourceId>,
+}
+
+impl NewReleases {
+    pub fn new(worker: Worker, model: NewReleasesModel) -> Self {
+        Self {
+            widget: NewReleasesWidget::new(),
+            worker,
+            model: Rc::new(model),
+            timeout: None,
+        }
+    }
+
+    fn bind_flowbox(&self) {
+        self.widget.bind_albums(
+            self.worker.clone(),
+            &self.model.get_list_store().unwrap(),
+            clone!(@weak self.model as model => move |id| {
+                model.open_album(id);
+            }),
+            clone!(@weak self.model as model => @default-return false, move |id: &str| {
+                model.is_unread(id)
+            }),
+        );
+    }
+
+    fn start(&mut self) {
+        if self.timeout.is_none() {
+            self.model.load_cached();
+            let model = Rc::clone(&self.model);
+            self.timeout = Some(glib::timeout_add_seconds_local(
+                DUE_CHECK_INTERVAL_SECS,
+                move || {
+                    model.refresh_if_due();
+                    glib::Continue(true)
+                },
+            ));
+        }
+        self.model.refresh_if_due();
+    }
+}
+
+impl EventListener for NewReleases {
+    fn on_event(&mut self, event: &AppEvent) {
+        match event {
+            AppEvent::Started => self.bind_flowbox(),
+            AppEvent::LoginEvent(LoginEvent::LoginCompleted(_)) => self.start(),
+            AppEvent::ConnectivityChanged(true) => self.model.refresh_if_due(),
+            AppEvent::BrowserEvent(BrowserEvent::NewReleasesUpdated) => {
+                self.widget
+                    .status_page()
+                    .set_visible(!self.model.has_releases());
+                if self.model.is_visible() {
+                    self.model.mark_read();
+                }
+            }
+            AppEvent::BrowserEvent(BrowserEvent::HomeVisiblePageChanged(page))
+                if *page == SidebarDestination::NewReleases.id() =>
+            {
+                self.model.mark_read();
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Component for NewReleases {
+    fn get_root_widget(&self) -> &gtk::Widget {
+        self.widget.as_ref()
+    }
+}
@@ -0,0 +1,164 @@
+use std::cell::{Cell, Ref, RefCell};
+use std::collections::HashSet;
+use std::ops::Deref;
+use std::rc::Rc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::feed::{recent_releases, recent_since, unread_releases, ReleaseFeed};
+use crate::app::components::sidebar::SidebarDestination;
+use crate::app::models::*;
+use crate::app::state::HomeState;
+use crate::app::{ActionDispatcher, AppAction, AppModel, BrowserAction, ListStore, Worker};
+use crate::settings::NewReleasesLastSeen;
+
+// Releases come sorted by type (albums, then singles...), we look at enough of them to find the latest
+const RELEASES_LIMIT: usize = 50;
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+pub struct NewReleasesModel {
+    app_model: Rc<AppModel>,
+    dispatcher: Box<dyn ActionDispatcher>,
+    worker: Worker,
+    feed: RefCell<ReleaseFeed>,
+    last_seen: RefCell<NewReleasesLastSeen>,
+    // Kept here rather than read from the state, as it's needed while the state is being updated.
+    // Unlike the state, this isn't cleared when the user looks at the feed, so that badges stay put.
+    unread: RefCell<HashSet<String>>,
+    refreshing: Cell<bool>,
+}
+
+impl NewReleasesModel {
+    pub fn new(
+        app_model: Rc<AppModel>,
+        dispatcher: Box<dyn ActionDispatcher>,
+        worker: Worker,
+    ) -> Self {
+        Self {
+            app_model,
+            dispatcher,
+            worker,
+            feed: RefCell::new(ReleaseFeed::load()),
+            last_seen: RefCell::new(NewReleasesLastSeen::new_from_gsettings()),
+            unread: Default::default(),
+            refreshing: Cell::new(false),
+        }
+    }
+
+    fn state(&self) -> Option<Ref<'_, HomeState>> {
+        self.app_model.map_state_opt(|s| s.browser.home_state())
+    }
+
+    pub fn get_list_store(&self) -> Option<impl Deref<Target = ListStore<AlbumModel>> + '_> {
+        Some(Ref::map(self.state()?, |s| &s.new_releases))
+    }
+
+    pub fn has_releases(&self) -> bool {
+        self.get_list_store()
+            .map(|list| list.len() > 0)
+            .unwrap_or(false)
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.state()
+            .map(|s| s.visible_page == SidebarDestination::NewReleases.id())
+            .unwrap_or(false)
+    }
+
+    pub fn is_unread(&self, album_id: &str) -> bool {
+        self.unread.borrow().contains(album_id)
+    }
+
+    // Shows what we fetched last, which might be all we need for today
+    pub fn load_cached(&self) {
+        let releases = self.feed.borrow().releases();
+        if !releases.is_empty() {
+            self.set_releases(releases);
+        }
+    }
+
+    fn set_releases(&self, releases: Vec<AlbumDescription>) {
+        let mut last_seen = self.last_seen.borrow_mut();
+        // The first time around, there's no telling what the user already knows of
+        if last_seen.date.is_empty() {
+            last_seen.date = releases
+                .first()
+                .and_then(|a| a.release_date.clone())
+                .unwrap_or_default();
+            last_seen.save();
+        }
+        let unread = unread_releases(&releases, &last_seen.date);
+        self.unread.replace(unread.clone());
+        self.dispatcher
+            .dispatch(BrowserAction::SetNewReleases(releases, unread).into());
+    }
+
+    pub fn refresh_if_due(self: &Rc<Self>) {
+        let state = self.app_model.get_state();
+        let can_refresh = state.logged_user.user.is_some() && state.online;
+        drop(state);
+        if can_refresh && self.feed.borrow().is_refresh_due(now()) {
+            self.refresh();
+        }
+    }
+
+    // Goes through the releases of every followed artist, one after the other
+    fn refresh(self: &Rc<Self>) {
+        let since = match recent_since() {
+            Some(since) => since,
+            None => return,
+        };
+        if self.refreshing.replace(true) {
+            return;
+        }
+
+        let model = Rc::clone(self);
+        let api = self.app_model.get_spotify();
+        self.worker.send_local_task(async move {
+            match api.get_followed_artists().await {
+                Ok(artists) => {
+                    let mut albums = vec![];
+                    for artist in artists {
+                        match api.get_artist_albums(&artist.id, 0, RELEASES_LIMIT).await {
+                            Ok(artist_albums) => albums.extend(artist_albums),
+                            Err(e) => warn!("Couldn't get releases of {}: {:?}", artist.id, e),
+                        }
+                    }
+                    let releases = recent_releases(albums, &since);
+                    model.feed.borrow_mut().update(&releases, now());
+                    model.set_releases(releases);
+                }
+                Err(e) => warn!("Couldn't get followed artists: {:?}", e),
+            }
+            model.refreshing.set(false);
+        });
+    }
+
+    // The user is looking at the feed, everything in it has been seen
+    pub fn mark_read(&self) {
+        let latest = self
+            .feed
+            .borrow()
+            .releases()
+            .first()
+            .and_then(|a| a.release_date.clone());
+        if let Some(latest) = latest {
+            let mut last_seen = self.last_seen.borrow_mut();
+            if latest > last_seen.date {
+                last_seen.date = latest;
+                last_seen.save();
+            }
+        }
+        self.dispatcher
+            .dispatch(BrowserAction::MarkNewReleasesRead.into());
+    }
+
+    pub fn open_album(&self, album_id: String) {
+        self.dispatcher.dispatch(AppAction::ViewAlbum(album_id));
+    }
+}
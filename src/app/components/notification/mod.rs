@@ -1,15 +1,33 @@
-use crate::app::components::EventListener;
-use crate::app::AppEvent;
+use crate::app::components::{display, remove_saved_tracks_in_bulk, EventListener};
+use crate::app::state::PendingChange;
+use crate::app::{ActionDispatcher, AppAction, AppEvent, AppModel};
 use gettextrs::*;
 use glib::ToVariant;
+use std::rc::Rc;
+
+// How long destructive changes can be undone for
+const UNDO_TIMEOUT_SECS: u32 = 10;
 
 pub struct Notification {
     toast_overlay: libadwaita::ToastOverlay,
+    app_model: Rc<AppModel>,
+    dispatcher: Box<dyn ActionDispatcher>,
+    // Stays up while tracks are being added to Liked Songs
+    progress_toast: Option<libadwaita::Toast>,
 }
 
 impl Notification {
-    pub fn new(toast_overlay: libadwaita::ToastOverlay) -> Self {
-        Self { toast_overlay }
+    pub fn new(
+        toast_overlay: libadwaita::ToastOverlay,
+        app_model: Rc<AppModel>,
+        dispatcher: Box<dyn ActionDispatcher>,
+    ) -> Self {
+        Self {
+            toast_overlay,
+            app_model,
+            dispatcher,
+            progress_toast: None,
+        }
     }
 
     fn show(&self, content: &str) {
@@ -34,14 +52,87 @@ impl Notification {
             .build();
         self.toast_overlay.add_toast(toast);
     }
+
+    fn show_saving_progress(&mut self, saved: usize, total: usize) {
+        let title = display::saving_tracks_progress_label(saved, total);
+        match self.progress_toast.as_ref() {
+            Some(toast) if saved > 0 => toast.set_title(&title),
+            _ => {
+                self.dismiss_saving_progress();
+                let toast = libadwaita::Toast::builder().title(title).timeout(0).build();
+                self.toast_overlay.add_toast(toast.clone());
+                self.progress_toast = Some(toast);
+            }
+        }
+    }
+
+    fn dismiss_saving_progress(&mut self) {
+        if let Some(toast) = self.progress_toast.take() {
+            toast.dismiss();
+        }
+    }
+
+    fn show_tracks_saved(&mut self, ids: &[String]) {
+        self.dismiss_saving_progress();
+        // translators: This is a label in the notification shown after adding many tracks to Liked Songs at once. If it is clicked, these tracks are removed from Liked Songs.
+        let label = gettext("Undo");
+        let toast = libadwaita::Toast::builder()
+            .title(display::n_tracks_saved_label(ids.len()))
+            .timeout(6)
+            .button_label(label)
+            .build();
+        let ids = ids.to_vec();
+        let app_model = Rc::clone(&self.app_model);
+        let dispatcher = self.dispatcher.box_clone();
+        toast.connect_button_clicked(move |_| {
+            remove_saved_tracks_in_bulk(ids.clone(), &app_model, dispatcher.as_ref());
+        });
+        self.toast_overlay.add_toast(toast);
+    }
+
+    // The change is only committed once the toast goes away, unless it was undone first
+    fn show_undoable(&self, id: usize, change: &PendingChange) {
+        let title = match change {
+            PendingChange::RemoveTracksFromPlaylist { uris, .. } => {
+                display::n_tracks_removed_from_playlist_label(uris.len())
+            }
+            // translators: This is a notification that pops up when an album is removed from the library, with a button to undo it.
+            PendingChange::UnsaveAlbum(_) => gettext("Album removed from your library"),
+            // translators: This is a notification that pops up when a playlist is deleted from the sidebar.
+            PendingChange::DeletePlaylist(_) => {
+                gettext("Playlist deleted. It can be recovered from the main menu for 90 days.")
+            }
+        };
+        let toast = libadwaita::Toast::builder()
+            .title(title)
+            .timeout(UNDO_TIMEOUT_SECS)
+            // translators: This is a label in the notification shown after removing something (tracks from a playlist, an album from the library...). If it is clicked, it is put back.
+            .button_label(gettext("Undo"))
+            .build();
+        let dispatcher = self.dispatcher.box_clone();
+        toast.connect_button_clicked(move |_| dispatcher.dispatch(AppAction::Undo(id)));
+        // Does nothing if it was undone
+        let dispatcher = self.dispatcher.box_clone();
+        toast.connect_dismissed(move |_| dispatcher.dispatch(AppAction::CommitPending(id)));
+        self.toast_overlay.add_toast(toast);
+    }
 }
 
 impl EventListener for Notification {
     fn on_event(&mut self, event: &AppEvent) {
-        if let AppEvent::NotificationShown(content) = event {
-            self.show(content)
-        } else if let AppEvent::PlaylistCreatedNotificationShown(id) = event {
-            self.show_playlist_created(id)
+        match event {
+            AppEvent::NotificationShown(content) => {
+                // Likely an error: whatever was in progress won't complete
+                self.dismiss_saving_progress();
+                self.show(content)
+            }
+            AppEvent::PlaylistCreatedNotificationShown(id) => self.show_playlist_created(id),
+            AppEvent::TracksSavingProgressShown(saved, total) => {
+                self.show_saving_progress(*saved, *total)
+            }
+            AppEvent::TracksSavedNotificationShown(ids) => self.show_tracks_saved(ids),
+            AppEvent::ChangeDeferred(id, change) => self.show_undoable(*id, change),
+            _ => {}
         }
     }
 }
@@ -1,16 +1,27 @@
+use gettextrs::gettext;
 use gtk::prelude::*;
 use gtk::subclass::prelude::*;
 use gtk::CompositeTemplate;
 use std::rc::Rc;
 
 use super::NowPlayingModel;
+use crate::app::components::artist_links::{artist_links_markup, connect_artist_links};
+use crate::app::components::sidebar::CreatePlaylistPopover;
+use crate::app::components::utils::{format_duration, Clock, Debouncer};
 use crate::app::components::{
-    Component, DeviceSelector, DeviceSelectorWidget, EventListener, HeaderBarComponent,
+    display, Component, DeviceSelector, DeviceSelectorWidget, EventListener, HeaderBarComponent,
     HeaderBarWidget, Playlist,
 };
+use crate::app::loader::ImageLoader;
+use crate::app::models::{ArtistRef, SongDescription};
 use crate::app::state::PlaybackEvent;
 use crate::app::{AppEvent, Worker};
 
+// Big enough to be seen from across the room
+const ARTWORK_SIZE: i32 = 320;
+// How far the arrow keys seek
+const SEEK_STEP_MS: i64 = 10_000;
+
 mod imp {
 
     use super::*;
@@ -29,6 +40,50 @@ mod imp {
 
         #[template_child]
         pub scrolled_window: TemplateChild<gtk::ScrolledWindow>,
+
+        #[template_child]
+        pub bitrate_label: TemplateChild<gtk::Label>,
+
+        #[template_child]
+        pub stage: TemplateChild<gtk::Box>,
+
+        #[template_child]
+        pub artwork: TemplateChild<gtk::Image>,
+
+        #[template_child]
+        pub track_title: TemplateChild<gtk::Label>,
+
+        #[template_child]
+        pub track_artist: TemplateChild<gtk::Label>,
+
+        #[template_child]
+        pub track_album: TemplateChild<gtk::Label>,
+
+        #[template_child]
+        pub seek_bar: TemplateChild<gtk::Scale>,
+
+        #[template_child]
+        pub position_label: TemplateChild<gtk::Label>,
+
+        #[template_child]
+        pub duration_label: TemplateChild<gtk::Label>,
+
+        #[template_child]
+        pub volume: TemplateChild<gtk::Scale>,
+
+        #[template_child]
+        pub volume_icon: TemplateChild<gtk::Image>,
+
+        #[template_child]
+        pub upcoming: TemplateChild<libadwaita::Clamp>,
+
+        #[template_child]
+        pub upcoming_list: TemplateChild<gtk::ListBox>,
+
+        #[template_child]
+        pub reshuffle_button: TemplateChild<gtk::Button>,
+
+        pub save_queue: gtk::MenuButton,
     }
 
     #[glib::object_subclass]
@@ -49,6 +104,12 @@ mod imp {
     impl ObjectImpl for NowPlayingWidget {
         fn constructed(&self) {
             self.parent_constructed();
+            connect_artist_links(&self.track_artist);
+            self.save_queue.set_icon_name("document-save-symbolic");
+            // translators: Tooltip of the button in Now Playing that creates a playlist with the tracks of the queue
+            self.save_queue
+                .set_tooltip_text(Some(&gettext("Save Queue as Playlist")));
+            self.headerbar.pack_end(&self.save_queue);
         }
     }
 
@@ -78,6 +139,168 @@ impl NowPlayingWidget {
             });
     }
 
+    fn set_bitrate(&self, bitrate: Option<u32>) {
+        let label = &self.imp().bitrate_label;
+        if let Some(kbps) = bitrate {
+            label.set_label(&display::streaming_bitrate_label(kbps));
+        }
+        label.set_visible(bitrate.is_some());
+    }
+
+    fn set_track(&self, title: &str, artists: &[ArtistRef], album: &str) {
+        let widget = self.imp();
+        widget.track_title.set_label(title);
+        widget.track_artist.set_label(&artist_links_markup(artists));
+        widget.track_album.set_label(album);
+    }
+
+    fn reset_track(&self) {
+        let widget = self.imp();
+        self.set_track("", &[], "");
+        widget.artwork.set_icon_name(Some("emblem-music-symbolic"));
+        self.set_duration(0.0);
+    }
+
+    fn set_artwork_from_url(&self, url: String, worker: &Worker) {
+        let weak_self = self.downgrade();
+        let loader = ImageLoader::for_widget(self);
+        worker.send_local_task(async move {
+            let result = loader
+                .load_remote(&url, "jpg", ARTWORK_SIZE, ARTWORK_SIZE)
+                .await;
+            if let (Some(ref _self), Some(ref result)) = (weak_self.upgrade(), result) {
+                _self.imp().artwork.set_from_pixbuf(Some(result));
+            }
+        });
+    }
+
+    fn set_duration(&self, duration: f64) {
+        let widget = self.imp();
+        widget.seek_bar.set_range(0.0, duration);
+        widget.duration_label.set_text(&format_duration(duration));
+        self.set_position(0.0);
+    }
+
+    fn set_position(&self, position: f64) {
+        let widget = self.imp();
+        widget.seek_bar.set_value(position);
+        widget.position_label.set_text(&format_duration(position));
+    }
+
+    fn increment_position(&self) {
+        self.set_position(self.imp().seek_bar.value() + 1_000.0);
+    }
+
+    fn set_volume(&self, volume: f64) {
+        let widget = self.imp();
+        widget.volume.set_value(volume);
+        let icon = match volume {
+            v if v <= 0.0 => "audio-volume-muted-symbolic",
+            v if v < 0.33 => "audio-volume-low-symbolic",
+            v if v < 0.66 => "audio-volume-medium-symbolic",
+            _ => "audio-volume-high-symbolic",
+        };
+        widget.volume_icon.set_icon_name(Some(icon));
+    }
+
+    // Only shown when shuffling, since the queue itself stays in its original order
+    fn set_upcoming(&self, songs: &[SongDescription]) {
+        let widget = self.imp();
+        let listbox = &widget.upcoming_list;
+        while let Some(row) = listbox.row_at_index(0) {
+            listbox.remove(&row);
+        }
+
+        for song in songs {
+            let row = libadwaita::ActionRow::builder()
+                .title(glib::markup_escape_text(&song.title).as_str())
+                .subtitle(glib::markup_escape_text(&song.artists_name()).as_str())
+                .build();
+            listbox.append(&row);
+        }
+        widget.upcoming.set_visible(!songs.is_empty());
+    }
+
+    fn connect_reshuffle<F>(&self, f: F)
+    where
+        F: Fn() + 'static,
+    {
+        self.imp().reshuffle_button.connect_clicked(move |_| f());
+    }
+
+    // The popover suggests a name, the one the playlist gets if it's left as is
+    fn connect_save_queue<N, F>(&self, default_name: N, save: F)
+    where
+        N: Fn() -> String + 'static,
+        F: Fn(String) + Clone + 'static,
+    {
+        let popover = CreatePlaylistPopover::new();
+        popover.set_position(gtk::PositionType::Bottom);
+        popover.connect_create(save);
+        popover.connect_show(move |popover| popover.set_name(&default_name()));
+        self.imp().save_queue.set_popover(Some(&popover));
+    }
+
+    fn set_save_queue_possible(&self, possible: bool) {
+        self.imp().save_queue.set_sensitive(possible);
+    }
+
+    fn connect_seek<F>(&self, f: F)
+    where
+        F: Fn(u32) + Clone + 'static,
+    {
+        let debouncer = Debouncer::new();
+        self.imp().seek_bar.connect_change_value(
+            clone!(@weak self as _self => @default-return glib::signal::Inhibit(false), move |_, _, requested| {
+                _self.imp().position_label.set_text(&format_duration(requested));
+                // Scrubbing results in a single seek once the user stops moving
+                let f = f.clone();
+                debouncer.debounce(200, move || f(requested as u32));
+                glib::signal::Inhibit(false)
+            }),
+        );
+    }
+
+    fn connect_volume_changed<F>(&self, f: F)
+    where
+        F: Fn(f64) + 'static,
+    {
+        self.imp()
+            .volume
+            .connect_change_value(move |_, _, requested| {
+                f(requested.clamp(0.0, 1.0));
+                glib::signal::Inhibit(false)
+            });
+    }
+
+    // Left and right to seek, plus and minus for the volume; the rest (play/pause, next...)
+    // already works everywhere in the app
+    fn connect_keys(&self, model: &Rc<NowPlayingModel>) {
+        let controller = gtk::EventControllerKey::new();
+        controller.connect_key_pressed(
+            clone!(@weak model => @default-return gtk::Inhibit(false), move |_, key, _, _| {
+                match key {
+                    gdk::Key::Left => model.seek_by(-SEEK_STEP_MS),
+                    gdk::Key::Right => model.seek_by(SEEK_STEP_MS),
+                    gdk::Key::plus | gdk::Key::KP_Add | gdk::Key::equal => model.increase_volume(),
+                    gdk::Key::minus | gdk::Key::KP_Subtract => model.decrease_volume(),
+                    _ => return gtk::Inhibit(false),
+                }
+                gtk::Inhibit(true)
+            }),
+        );
+        self.add_controller(controller);
+    }
+
+    // Artwork and controls only fit next to the queue when there's enough room
+    fn bind_stage_to_leaflet(&self, leaflet: &libadwaita::Leaflet) {
+        leaflet
+            .bind_property("folded", &*self.imp().stage, "visible")
+            .invert_boolean()
+            .build();
+        leaflet.notify("folded");
+    }
+
     fn song_list_widget(&self) -> &gtk::ListView {
         self.imp().song_list.as_ref()
     }
@@ -94,6 +317,8 @@ impl NowPlayingWidget {
 pub struct NowPlaying {
     widget: NowPlayingWidget,
     model: Rc<NowPlayingModel>,
+    worker: Worker,
+    clock: Clock,
     children: Vec<Box<dyn EventListener>>,
 }
 
@@ -108,7 +333,7 @@ impl NowPlaying {
         let playlist = Box::new(Playlist::new(
             widget.song_list_widget().clone(),
             model.clone(),
-            worker,
+            worker.clone(),
         ));
 
         let headerbar_widget = widget.headerbar_widget();
@@ -123,10 +348,68 @@ impl NowPlaying {
             model.device_selector_model(),
         ));
 
-        Self {
+        widget.set_bitrate(model.bitrate());
+
+        widget.bind_stage_to_leaflet(leaflet);
+        widget.connect_seek(clone!(@weak model => move |position| model.seek_to(position)));
+        widget
+            .connect_volume_changed(clone!(@weak model => move |volume| model.set_volume(volume)));
+        widget.connect_keys(&model);
+        widget.connect_reshuffle(clone!(@weak model => move || model.reshuffle()));
+        widget.connect_save_queue(
+            clone!(@weak model => @default-return String::new(), move || model.default_queue_playlist_name()),
+            clone!(@weak model => move |name| model.save_queue_as_playlist(name)),
+        );
+
+        let now_playing = Self {
             widget,
             model,
+            worker,
+            clock: Clock::default(),
             children: vec![playlist, headerbar, device_selector],
+        };
+        now_playing.update_track();
+        now_playing.update_upcoming();
+        now_playing.update_playing();
+        now_playing.update_save_queue();
+        now_playing.widget.set_volume(now_playing.model.volume());
+        now_playing
+    }
+
+    fn update_track(&self) {
+        if let Some(song) = self.model.current_song() {
+            self.widget
+                .set_track(&song.title, &song.artists, &song.album.name);
+            self.widget.set_duration(song.duration as f64);
+            self.widget.set_position(self.model.position() as f64);
+            if let Some(url) = song.art {
+                self.widget.set_artwork_from_url(url, &self.worker);
+            }
+        } else {
+            self.widget.reset_track();
+        }
+    }
+
+    fn update_save_queue(&self) {
+        self.widget
+            .set_save_queue_possible(self.model.can_save_queue());
+    }
+
+    fn update_upcoming(&self) {
+        self.widget.set_upcoming(&self.model.upcoming_songs());
+    }
+
+    // The position moves along on its own while playing, until we're told otherwise
+    fn update_playing(&self) {
+        if self.model.is_playing() {
+            let widget = self.widget.downgrade();
+            self.clock.start(move || {
+                if let Some(widget) = widget.upgrade() {
+                    widget.increment_position();
+                }
+            });
+        } else {
+            self.clock.stop();
         }
     }
 }
@@ -143,9 +426,59 @@ impl Component for NowPlaying {
 
 impl EventListener for NowPlaying {
     fn on_event(&mut self, event: &AppEvent) {
-        if let AppEvent::PlaybackEvent(PlaybackEvent::TrackChanged(_)) = event {
-            self.model.load_more();
+        match event {
+            AppEvent::PlaybackEvent(PlaybackEvent::TrackChanged(_)) => {
+                self.model.load_more();
+                self.update_track();
+                self.update_upcoming();
+            }
+            AppEvent::PlaybackEvent(
+                PlaybackEvent::ShuffleChanged(_)
+                | PlaybackEvent::ShuffleOrderChanged
+                | PlaybackEvent::PlaylistChanged,
+            ) => {
+                self.update_upcoming();
+                self.update_save_queue();
+            }
+            AppEvent::PlaybackEvent(PlaybackEvent::PlaybackStopped) => {
+                self.update_playing();
+                self.update_track();
+                self.update_save_queue();
+            }
+            AppEvent::PlaybackEvent(
+                PlaybackEvent::PlaybackPaused | PlaybackEvent::PlaybackResumed,
+            ) => {
+                self.update_playing();
+            }
+            AppEvent::PlaybackEvent(
+                PlaybackEvent::TrackSeeked(position) | PlaybackEvent::SeekSynced(position),
+            ) => {
+                self.widget.set_position(*position as f64);
+            }
+            AppEvent::PlaybackEvent(PlaybackEvent::DurationSynced(duration)) => {
+                let position = self.model.position();
+                self.widget.set_duration(*duration as f64);
+                self.widget.set_position(position as f64);
+            }
+            AppEvent::PlaybackEvent(PlaybackEvent::VolumeSet(volume)) => {
+                self.widget.set_volume(*volume);
+            }
+            AppEvent::PlaybackEvent(
+                PlaybackEvent::BitrateChanged(_) | PlaybackEvent::SwitchedDevice(_),
+            ) => {
+                self.widget.set_bitrate(self.model.bitrate());
+                self.update_upcoming();
+            }
+            _ => {}
         }
         self.broadcast_event(event);
     }
+
+    fn on_mount(&mut self) {
+        self.broadcast_mount();
+    }
+
+    fn on_unmount(&mut self) {
+        self.broadcast_unmount();
+    }
 }
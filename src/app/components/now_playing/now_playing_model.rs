@@ -4,8 +4,8 @@ use std::ops::Deref;
 use std::rc::Rc;
 
 use crate::app::components::{
-    labels, DeviceSelectorModel, HeaderBarModel, PlaylistModel, SimpleHeaderBarModel,
-    SimpleHeaderBarModelWrapper,
+    create_playlist_from_source, create_playlist_with_songs, labels, DeviceSelectorModel,
+    HeaderBarModel, PlaylistModel, Shareable, SimpleHeaderBarModel, SimpleHeaderBarModelWrapper,
 };
 use crate::app::models::{SongDescription, SongListModel};
 use crate::app::state::Device;
@@ -60,6 +60,107 @@ impl NowPlayingModel {
         DeviceSelectorModel::new(self.app_model.clone(), self.dispatcher.box_clone())
     }
 
+    // Only known for Spot's own player
+    pub fn bitrate(&self) -> Option<u32> {
+        let playback = self.queue();
+        match playback.current_device() {
+            Device::Local => playback.bitrate(),
+            Device::Connect(_) => None,
+        }
+    }
+
+    pub fn current_song(&self) -> Option<SongDescription> {
+        self.queue().current_song()
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.queue().is_playing()
+    }
+
+    // In milliseconds
+    pub fn position(&self) -> u32 {
+        self.queue().position()
+    }
+
+    pub fn volume(&self) -> f64 {
+        self.queue().volume()
+    }
+
+    pub fn seek_to(&self, position: u32) {
+        self.dispatcher
+            .dispatch(PlaybackAction::Seek(position).into());
+    }
+
+    pub fn seek_by(&self, delta_ms: i64) {
+        if let Some(song) = self.current_song() {
+            let position = (self.position() as i64 + delta_ms).clamp(0, song.duration as i64);
+            self.seek_to(position as u32);
+        }
+    }
+
+    pub fn set_volume(&self, volume: f64) {
+        self.dispatcher
+            .dispatch(PlaybackAction::SetVolume(volume).into());
+    }
+
+    pub fn increase_volume(&self) {
+        self.dispatcher
+            .dispatch(PlaybackAction::IncreaseVolume.into());
+    }
+
+    pub fn decrease_volume(&self) {
+        self.dispatcher
+            .dispatch(PlaybackAction::DecreaseVolume.into());
+    }
+
+    // The shuffled order is up to the device when playing elsewhere, we don't know it
+    pub fn upcoming_songs(&self) -> Vec<SongDescription> {
+        let playback = self.queue();
+        match playback.current_device() {
+            Device::Local => playback.upcoming_shuffled_songs(),
+            Device::Connect(_) => vec![],
+        }
+    }
+
+    pub fn reshuffle(&self) {
+        self.dispatcher.dispatch(PlaybackAction::Reshuffle.into());
+    }
+
+    // Songs that aren't loaded yet can only be found again from where the queue comes from
+    pub fn can_save_queue(&self) -> bool {
+        let queue = self.queue();
+        let songs = queue.songs();
+        songs.partial_len() > 0
+            && (songs.partial_len() >= songs.len() || queue.current_source().is_some())
+    }
+
+    // Named after when it's saved, unless the user picks another name
+    pub fn default_queue_playlist_name(&self) -> String {
+        let date = glib::DateTime::now_local()
+            .and_then(|now| now.format("%x %H:%M"))
+            .map(|date| date.to_string())
+            .unwrap_or_default();
+        labels::queue_playlist_name(&date)
+    }
+
+    // All the songs of the queue, as listed here; those not loaded yet are loaded first
+    pub fn save_queue_as_playlist(&self, name: String) {
+        if !self.can_save_queue() {
+            return;
+        }
+        let name = Some(name)
+            .filter(|name| !name.trim().is_empty())
+            .unwrap_or_else(|| self.default_queue_playlist_name());
+        let queue = self.queue();
+        let songs = queue.songs();
+        if songs.partial_len() >= songs.len() {
+            let uris = songs.map_collect(|song| song.uri);
+            create_playlist_with_songs(name, uris, &self.app_model, self.dispatcher.as_ref());
+        } else if let Some(source) = queue.current_source().cloned() {
+            create_playlist_from_source(name, source, &self.app_model, self.dispatcher.as_ref());
+        }
+    }
+
     fn current_selection_context(&self) -> SelectionContext {
         let state = self.app_model.get_state();
         match state.playback.current_device() {
@@ -102,8 +203,23 @@ impl PlaylistModel for NowPlayingModel {
         }
         group.add_action(&song.make_album_action(self.dispatcher.box_clone(), None));
         group.add_action(&song.make_link_action(None));
+        group.add_action(&song.make_web_player_action(None));
+        group.add_action(&song.make_save_action(
+            Rc::clone(&self.app_model),
+            self.dispatcher.box_clone(),
+            None,
+        ));
         group.add_action(&song.make_dequeue_action(self.dispatcher.box_clone(), None));
 
+        if queue.is_autoplay(id) {
+            let clear_autoplay = gio::SimpleAction::new("clear_autoplay", None);
+            let dispatcher = self.dispatcher.box_clone();
+            clear_autoplay.connect_activate(move |_, _| {
+                dispatcher.dispatch(PlaybackAction::ClearAutoplay.into());
+            });
+            group.add_action(&clear_autoplay);
+        }
+
         Some(group.upcast())
     }
 
@@ -122,11 +238,25 @@ impl PlaylistModel for NowPlayingModel {
         }
 
         menu.append(Some(&*labels::COPY_LINK), Some("song.copy_link"));
+        menu.append(
+            Some(&*labels::OPEN_IN_WEB_PLAYER),
+            Some("song.open_in_web_player"),
+        );
         menu.append(Some(&*labels::REMOVE_FROM_QUEUE), Some("song.dequeue"));
+        if queue.is_autoplay(id) {
+            menu.append(
+                Some(&*labels::REMOVE_AUTOPLAY_SONGS),
+                Some("song.clear_autoplay"),
+            );
+        }
 
         Some(menu.upcast())
     }
 
+    fn is_autoplay(&self, id: &str) -> bool {
+        self.queue().is_autoplay(id)
+    }
+
     fn select_song(&self, id: &str) {
         let queue = self.queue();
         if let Some(song) = queue.songs().get(id) {
@@ -147,6 +277,11 @@ impl PlaylistModel for NowPlayingModel {
         true
     }
 
+    fn is_saved(&self, id: &str) -> Option<bool> {
+        let is_saved = self.app_model.get_state().browser.is_track_saved(id);
+        Some(is_saved.unwrap_or(false))
+    }
+
     fn selection(&self) -> Option<Box<dyn Deref<Target = SelectionState> + '_>> {
         let selection = self.app_model.map_state(|s| &s.selection);
         Some(Box::new(selection))
@@ -171,4 +306,12 @@ impl SimpleHeaderBarModel for NowPlayingModel {
         self.dispatcher
             .dispatch(SelectionAction::Select(songs).into());
     }
+
+    fn web_url(&self) -> Option<String> {
+        None
+    }
+
+    fn shareable(&self) -> Option<Shareable> {
+        None
+    }
 }
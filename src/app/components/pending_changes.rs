@@ -0,0 +1,311 @@
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::fs;
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::sync::Arc;
+
+use super::try_actions_for_spotify_call;
+use crate::api::{SpotifyApiClient, SpotifyApiError};
+use crate::app::components::{fetch_playlist, EventListener};
+use crate::app::models::PlaylistSummary;
+use crate::app::state::{LoginEvent, PendingChange};
+use crate::app::{ActionDispatcher, AppAction, AppEvent, AppModel, BrowserAction, Worker};
+
+// What's needed to send a change to Spotify, as kept on disk
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+enum UnsentChange {
+    RemoveTracksFromPlaylist {
+        playlist_id: String,
+        uris: Vec<String>,
+    },
+    UnsaveAlbum {
+        id: String,
+    },
+    DeletePlaylist {
+        id: String,
+        title: String,
+    },
+}
+
+impl From<&PendingChange> for UnsentChange {
+    fn from(change: &PendingChange) -> Self {
+        match change {
+            PendingChange::RemoveTracksFromPlaylist { playlist_id, uris } => {
+                Self::RemoveTracksFromPlaylist {
+                    playlist_id: playlist_id.clone(),
+                    uris: uris.clone(),
+                }
+            }
+            PendingChange::UnsaveAlbum(album) => Self::UnsaveAlbum {
+                id: album.id.clone(),
+            },
+            PendingChange::DeletePlaylist(playlist) => Self::DeletePlaylist {
+                id: playlist.id.clone(),
+                title: playlist.title.clone(),
+            },
+        }
+    }
+}
+
+// The changes not sent yet, kept on disk so that quitting Spot before they're committed (or while offline)
+// doesn't bring back what the user removed: they're sent on next start instead.
+struct UnsentChanges {
+    path: Option<PathBuf>,
+}
+
+impl UnsentChanges {
+    fn default_path() -> PathBuf {
+        glib::user_data_dir()
+            .join("spot")
+            .join("pending-changes.json")
+    }
+
+    fn load(&self) -> Vec<UnsentChange> {
+        self.path
+            .as_ref()
+            .and_then(|path| fs::read(path).ok())
+            .and_then(|content| serde_json::from_slice(&content).ok())
+            .unwrap_or_default()
+    }
+
+    fn save(&self, changes: &[UnsentChange]) {
+        let path = match self.path.as_ref() {
+            Some(path) => path,
+            None => return,
+        };
+        let result = if changes.is_empty() {
+            match fs::remove_file(path) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+                _ => Ok(()),
+            }
+        } else {
+            path.parent()
+                .map(fs::create_dir_all)
+                .unwrap_or(Ok(()))
+                .and_then(|_| fs::write(path, serde_json::to_vec(changes).unwrap_or_default()))
+        };
+        if let Err(e) = result {
+            warn!("Could not save pending changes: {}", e);
+        }
+    }
+}
+
+// Changes that were committed, but that Spotify didn't take yet
+#[derive(Default)]
+struct Outbox {
+    // Held until we're online and logged in
+    waiting: Vec<UnsentChange>,
+    // Being sent: they go back to waiting if that fails
+    sending: Vec<UnsentChange>,
+}
+
+// Sends destructive changes to Spotify once they can no longer be undone (see PendingChanges),
+// and brings back what the state can't restore on its own when they are undone.
+// Changes committed while offline are held until the connection is back.
+pub struct PendingChangesCommitter {
+    app_model: Rc<AppModel>,
+    dispatcher: Box<dyn ActionDispatcher>,
+    worker: Worker,
+    outbox: Rc<RefCell<Outbox>>,
+    unsent: Rc<UnsentChanges>,
+}
+
+impl PendingChangesCommitter {
+    pub fn new(
+        app_model: Rc<AppModel>,
+        dispatcher: Box<dyn ActionDispatcher>,
+        worker: Worker,
+        demo: bool,
+    ) -> Self {
+        // The demo's changes only apply to its own library, and the user's are left for next time
+        let unsent = UnsentChanges {
+            path: Some(UnsentChanges::default_path()).filter(|_| !demo),
+        };
+        let outbox = Outbox {
+            // Left over from last time, sent once logged in
+            waiting: unsent.load(),
+            sending: vec![],
+        };
+        Self {
+            app_model,
+            dispatcher,
+            worker,
+            outbox: Rc::new(RefCell::new(outbox)),
+            unsent: Rc::new(unsent),
+        }
+    }
+
+    fn commit_when_online(&self, change: UnsentChange) {
+        let state = self.app_model.get_state();
+        let can_send = state.online && state.logged_user.user.is_some();
+        drop(state);
+        if can_send {
+            self.commit(change);
+        } else {
+            self.outbox.borrow_mut().waiting.push(change);
+        }
+    }
+
+    fn commit_waiting(&self) {
+        let waiting = std::mem::take(&mut self.outbox.borrow_mut().waiting);
+        for change in waiting {
+            self.commit_when_online(change);
+        }
+    }
+
+    // Whatever can still be undone, and whatever Spotify didn't take yet
+    fn save_unsent(app_model: &AppModel, outbox: &RefCell<Outbox>, unsent: &UnsentChanges) {
+        let state = app_model.get_state();
+        let outbox = outbox.borrow();
+        let changes: Vec<UnsentChange> = state
+            .pending_changes
+            .iter()
+            .map(UnsentChange::from)
+            .chain(outbox.waiting.iter().cloned())
+            .chain(outbox.sending.iter().cloned())
+            .collect();
+        unsent.save(&changes);
+    }
+
+    fn save(&self) {
+        Self::save_unsent(&self.app_model, &self.outbox, &self.unsent);
+    }
+
+    async fn send(
+        api: Arc<dyn SpotifyApiClient + Send + Sync>,
+        change: UnsentChange,
+    ) -> Result<Vec<AppAction>, SpotifyApiError> {
+        match change {
+            UnsentChange::RemoveTracksFromPlaylist { playlist_id, uris } => {
+                api.remove_from_playlist(&playlist_id, uris).await?;
+                Ok(vec![])
+            }
+            UnsentChange::UnsaveAlbum { id } => {
+                api.remove_saved_album(&id).await?;
+                Ok(vec![])
+            }
+            // Spotify doesn't actually delete playlists, users just stop following them
+            UnsentChange::DeletePlaylist { id, title } => {
+                api.unfollow_playlist(&id).await?;
+                Ok(vec![AppAction::DeletePlaylist(PlaylistSummary {
+                    id,
+                    title,
+                })])
+            }
+        }
+    }
+
+    // The change is kept (on disk too) until the call succeeds
+    fn commit(&self, change: UnsentChange) {
+        self.outbox.borrow_mut().sending.push(change.clone());
+
+        let api = self.app_model.get_spotify();
+        let call = {
+            let change = change.clone();
+            move || Self::send(api, change)
+        };
+        let app_model = Rc::clone(&self.app_model);
+        let dispatcher = self.dispatcher.box_clone();
+        let outbox = Rc::clone(&self.outbox);
+        let unsent = Rc::clone(&self.unsent);
+        self.worker.send_local_task(async move {
+            let result = try_actions_for_spotify_call(call).await;
+            {
+                let mut outbox = outbox.borrow_mut();
+                if let Some(i) = outbox.sending.iter().position(|c| c == &change) {
+                    let change = outbox.sending.remove(i);
+                    if result.is_err() {
+                        outbox.waiting.push(change);
+                    }
+                }
+            }
+            Self::save_unsent(&app_model, &outbox, &unsent);
+            dispatcher.dispatch_many(result.unwrap_or_else(|actions| actions));
+        });
+    }
+
+    fn restore(&self, change: PendingChange) {
+        let api = self.app_model.get_spotify();
+        match change {
+            // The tracks never left the playlist on Spotify's side
+            PendingChange::RemoveTracksFromPlaylist { playlist_id, uris } => {
+                let loaded = self
+                    .app_model
+                    .get_state()
+                    .browser
+                    .playlist_details_state(&playlist_id)
+                    .map(|s| s.songs.partial_len())
+                    .unwrap_or(0)
+                    + uris.len();
+                self.dispatcher
+                    .call_spotify_and_dispatch(move || async move {
+                        let playlist = fetch_playlist(api, &playlist_id, loaded).await?;
+                        Ok(BrowserAction::RefreshPlaylist(Box::new(playlist)).into())
+                    })
+            }
+            // Nor was it unfollowed
+            PendingChange::DeletePlaylist(playlist) => {
+                self.dispatcher
+                    .call_spotify_and_dispatch(move || async move {
+                        api.get_playlist(&playlist.id)
+                            .await
+                            .map(AppAction::RecoverPlaylist)
+                    })
+            }
+            PendingChange::UnsaveAlbum(_) => {}
+        }
+    }
+}
+
+impl EventListener for PendingChangesCommitter {
+    fn on_event(&mut self, event: &AppEvent) {
+        match event {
+            AppEvent::ChangeDeferred(..) => self.save(),
+            AppEvent::ChangeCommitted(change) => {
+                self.commit_when_online(change.into());
+                self.save();
+            }
+            AppEvent::ChangeUndone(change) => {
+                self.restore(change.clone());
+                self.save();
+            }
+            AppEvent::ConnectivityChanged(true)
+            | AppEvent::LoginEvent(LoginEvent::LoginCompleted(_)) => {
+                self.commit_waiting();
+                self.save();
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_unsent_changes_persist() {
+        let path =
+            std::env::temp_dir().join(format!("spot-pending-changes-{}.json", std::process::id()));
+        let unsent = UnsentChanges {
+            path: Some(path.clone()),
+        };
+        let changes = vec![
+            UnsentChange::UnsaveAlbum {
+                id: "album".to_string(),
+            },
+            UnsentChange::DeletePlaylist {
+                id: "playlist".to_string(),
+                title: "Playlist".to_string(),
+            },
+        ];
+        unsent.save(&changes);
+        assert_eq!(unsent.load(), changes);
+
+        unsent.save(&[]);
+        assert!(!path.exists());
+        assert!(unsent.load().is_empty());
+    }
+}
@@ -1,12 +1,15 @@
 use std::ops::Deref;
 use std::rc::Rc;
 
-use crate::app::components::EventListener;
+use crate::app::components::utils::Debouncer;
+use crate::app::components::{EventListener, Shareable};
 use crate::app::models::*;
-use crate::app::state::{PlaybackAction, PlaybackEvent, ScreenName, SelectionEvent};
+use crate::app::state::{Device, PlaybackAction, PlaybackEvent, ScreenName, SelectionEvent};
 use crate::app::{
-    ActionDispatcher, AppAction, AppEvent, AppModel, AppState, BrowserAction, Worker,
+    ActionDispatcher, AppAction, AppEvent, AppModel, AppState, BrowserAction, BrowserEvent,
+    SpotifyUri, Worker,
 };
+use crate::settings::PlaybackVolume;
 
 use super::playback_widget::PlaybackWidget;
 
@@ -45,6 +48,27 @@ impl PlaybackModel {
         self.app_model.get_state().playback.current_song()
     }
 
+    fn is_current_song_saved(&self) -> Option<bool> {
+        let state = self.state();
+        let id = state.playback.current_song_id()?;
+        Some(state.browser.is_track_saved(&id).unwrap_or(false))
+    }
+
+    fn current_song_shareable(&self) -> Option<Shareable> {
+        let song = self.current_song()?;
+        Some(Shareable::new(
+            SpotifyUri::Track(song.id.clone()),
+            song.title.clone(),
+            Some(song.artists_name()),
+        ))
+    }
+
+    fn toggle_current_song_saved(&self) {
+        if let Some(song) = self.current_song() {
+            song.toggle_saved(&self.app_model, self.dispatcher.as_ref());
+        }
+    }
+
     fn play_next_song(&self) {
         self.dispatcher.dispatch(PlaybackAction::Next.into());
     }
@@ -71,12 +95,27 @@ impl PlaybackModel {
         self.dispatcher
             .dispatch(PlaybackAction::Seek(position).into());
     }
+
+    fn volume(&self) -> f64 {
+        self.state().playback.volume()
+    }
+
+    fn is_local(&self) -> bool {
+        matches!(self.state().playback.current_device(), Device::Local)
+    }
+
+    fn set_volume(&self, volume: f64) {
+        self.dispatcher
+            .dispatch(PlaybackAction::SetVolume(volume).into());
+    }
 }
 
 pub struct PlaybackControl {
     model: Rc<PlaybackModel>,
     widget: PlaybackWidget,
     worker: Worker,
+    // To avoid writing the volume to the settings for every step of the slider
+    volume_debouncer: Debouncer,
 }
 
 impl PlaybackControl {
@@ -90,11 +129,19 @@ impl PlaybackControl {
         widget.connect_repeat(clone!(@weak model => move || model.toggle_repeat()));
         widget.connect_seek(clone!(@weak model => move |position| model.seek_to(position)));
         widget.connect_now_playing_clicked(clone!(@weak model => move || model.go_home()));
+        widget.connect_save(clone!(@weak model => move || model.toggle_current_song_saved()));
+        widget.bind_share_menu(
+            clone!(@weak model => @default-return None, move || model.current_song_shareable()),
+        );
+        widget
+            .connect_volume_changed(clone!(@weak model => move |volume| model.set_volume(volume)));
+        widget.set_volume(model.volume());
 
         Self {
             model,
             widget,
             worker,
+            volume_debouncer: Debouncer::new(),
         }
     }
 
@@ -114,7 +161,7 @@ impl PlaybackControl {
     fn update_current_info(&self) {
         if let Some(song) = self.model.current_song() {
             self.widget
-                .set_title_and_artist(&song.title, &song.artists_name());
+                .set_title_and_artists(&song.title, &song.artists);
             self.widget.set_song_duration(Some(song.duration as f64));
             if let Some(url) = song.art {
                 self.widget.set_artwork_from_url(url, &self.worker);
@@ -122,11 +169,28 @@ impl PlaybackControl {
         } else {
             self.widget.reset_info();
         }
+        self.widget
+            .set_share_visible(self.model.current_song().is_some());
+        self.update_saved();
+    }
+
+    fn update_saved(&self) {
+        self.widget.set_saved(self.model.is_current_song_saved());
     }
 
     fn sync_seek(&self, pos: u32) {
         self.widget.set_seek_position(pos as f64);
     }
+
+    fn update_volume(&self, volume: f64) {
+        self.widget.set_volume(volume);
+        // Only the volume of Spot's own player is restored on startup
+        if self.model.is_local() {
+            self.volume_debouncer.debounce(500, move || {
+                PlaybackVolume { level: volume }.save();
+            });
+        }
+    }
 }
 
 impl EventListener for PlaybackControl {
@@ -153,9 +217,21 @@ impl EventListener for PlaybackControl {
             | AppEvent::PlaybackEvent(PlaybackEvent::TrackSeeked(pos)) => {
                 self.sync_seek(*pos);
             }
+            AppEvent::PlaybackEvent(PlaybackEvent::DurationSynced(duration)) => {
+                self.widget.sync_song_duration(*duration as f64);
+            }
+            AppEvent::PlaybackEvent(PlaybackEvent::BufferingChanged(buffering)) => {
+                self.widget.set_buffering(*buffering);
+            }
+            AppEvent::PlaybackEvent(PlaybackEvent::VolumeSet(volume)) => {
+                self.update_volume(*volume);
+            }
             AppEvent::SelectionEvent(SelectionEvent::SelectionModeChanged(active)) => {
                 self.widget.set_seekbar_visible(!active);
             }
+            AppEvent::BrowserEvent(BrowserEvent::TrackSavedStateChanged(_)) => {
+                self.update_saved();
+            }
             _ => {}
         }
     }
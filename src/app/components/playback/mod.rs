@@ -3,6 +3,7 @@ mod playback_controls;
 mod playback_info;
 mod playback_widget;
 pub use component::*;
+pub use playback_widget::PlaybackWidget;
 
 use glib::prelude::*;
 
@@ -3,6 +3,9 @@ use gtk::prelude::*;
 use gtk::subclass::prelude::*;
 use gtk::{glib, CompositeTemplate};
 
+use crate::app::components::artist_links::{artist_links_markup, connect_artist_links};
+use crate::app::models::ArtistRef;
+
 mod imp {
 
     use super::*;
@@ -32,7 +35,12 @@ mod imp {
         }
     }
 
-    impl ObjectImpl for PlaybackInfoWidget {}
+    impl ObjectImpl for PlaybackInfoWidget {
+        fn constructed(&self) {
+            self.parent_constructed();
+            connect_artist_links(&self.current_song_info);
+        }
+    }
     impl WidgetImpl for PlaybackInfoWidget {}
     impl ButtonImpl for PlaybackInfoWidget {}
 }
@@ -42,11 +50,11 @@ glib::wrapper! {
 }
 
 impl PlaybackInfoWidget {
-    pub fn set_title_and_artist(&self, title: &str, artist: &str) {
+    pub fn set_title_and_artists(&self, title: &str, artists: &[ArtistRef]) {
         let widget = self.imp();
         let title = glib::markup_escape_text(title);
-        let artist = glib::markup_escape_text(artist);
-        let label = format!("<b>{}</b>\n{}", title.as_str(), artist.as_str());
+        let artists = artist_links_markup(artists);
+        let label = format!("<b>{}</b>\n{}", title.as_str(), artists);
         widget.current_song_info.set_label(&label[..]);
     }
 
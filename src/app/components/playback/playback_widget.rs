@@ -1,11 +1,14 @@
+use gettextrs::gettext;
 use gtk::prelude::*;
 use gtk::subclass::prelude::*;
 use gtk::{glib, CompositeTemplate};
+use std::cell::Cell;
 
-use crate::app::components::display_add_css_provider;
 use crate::app::components::utils::{format_duration, Clock, Debouncer};
+use crate::app::components::{bind_share_menu, Shareable};
+use crate::app::components::{display_add_css_provider, indicators};
 use crate::app::loader::ImageLoader;
-use crate::app::models::RepeatMode;
+use crate::app::models::{ArtistRef, RepeatMode};
 use crate::app::Worker;
 
 use super::playback_controls::PlaybackControlsWidget;
@@ -33,13 +36,31 @@ mod imp {
         #[template_child]
         pub seek_bar: TemplateChild<gtk::Scale>,
 
+        #[template_child]
+        pub save_button: TemplateChild<gtk::Button>,
+
+        #[template_child]
+        pub share_button: TemplateChild<gtk::MenuButton>,
+
         #[template_child]
         pub track_position: TemplateChild<gtk::Label>,
 
         #[template_child]
         pub track_duration: TemplateChild<gtk::Label>,
 
+        #[template_child]
+        pub volume: TemplateChild<gtk::Scale>,
+
+        #[template_child]
+        pub volume_icon: TemplateChild<gtk::Image>,
+
+        #[template_child]
+        pub inhibit_indicator: TemplateChild<gtk::Image>,
+
         pub clock: Clock,
+
+        // While the track is loading, the position doesn't move on its own
+        pub is_buffering: Cell<bool>,
     }
 
     #[glib::object_subclass]
@@ -75,9 +96,9 @@ glib::wrapper! {
 }
 
 impl PlaybackWidget {
-    pub fn set_title_and_artist(&self, title: &str, artist: &str) {
+    pub fn set_title_and_artists(&self, title: &str, artists: &[ArtistRef]) {
         let widget = self.imp();
-        widget.now_playing.set_title_and_artist(title, artist);
+        widget.now_playing.set_title_and_artists(title, artists);
     }
 
     pub fn reset_info(&self) {
@@ -95,8 +116,8 @@ impl PlaybackWidget {
 
     pub fn set_artwork_from_url(&self, url: String, worker: &Worker) {
         let weak_self = self.downgrade();
+        let loader = ImageLoader::for_widget(self);
         worker.send_local_task(async move {
-            let loader = ImageLoader::new();
             let result = loader.load_remote(&url, "jpg", 48, 48).await;
             if let (Some(ref _self), Some(ref result)) = (weak_self.upgrade(), result) {
                 _self.set_artwork(result);
@@ -111,6 +132,7 @@ impl PlaybackWidget {
             self.add_css_class(class);
             widget.seek_bar.set_range(0.0, duration);
             widget.seek_bar.set_value(0.0);
+            widget.seek_bar.set_fill_level(0.0);
             widget.track_position.set_text("0∶00");
             widget
                 .track_duration
@@ -125,6 +147,35 @@ impl PlaybackWidget {
         }
     }
 
+    // The player knows the exact duration of the track, which might slightly differ from the one in the metadata
+    pub fn sync_song_duration(&self, duration: f64) {
+        let widget = self.imp();
+        widget.seek_bar.set_range(0.0, duration);
+        if !widget.is_buffering.get() {
+            widget.seek_bar.set_fill_level(duration);
+        }
+        widget
+            .track_duration
+            .set_text(&format!(" / {}", format_duration(duration)));
+    }
+
+    // The fill level of the seek bar shows how much of the track is available: librespot doesn't tell
+    // us about its progress when fetching a track, but once it starts playing seeking anywhere is quick.
+    pub fn set_buffering(&self, buffering: bool) {
+        let widget = self.imp();
+        widget.is_buffering.set(buffering);
+        let class = "seek-bar--buffering";
+        if buffering {
+            self.add_css_class(class);
+            widget.seek_bar.set_fill_level(widget.seek_bar.value());
+        } else {
+            self.remove_css_class(class);
+            widget
+                .seek_bar
+                .set_fill_level(widget.seek_bar.adjustment().upper());
+        }
+    }
+
     pub fn set_seek_position(&self, pos: f64) {
         let widget = self.imp();
         widget.seek_bar.set_value(pos);
@@ -132,6 +183,9 @@ impl PlaybackWidget {
     }
 
     pub fn increment_seek_position(&self) {
+        if self.imp().is_buffering.get() {
+            return;
+        }
         let value = self.imp().seek_bar.value() + 1_000.0;
         self.set_seek_position(value);
     }
@@ -153,18 +207,94 @@ impl PlaybackWidget {
         let debouncer = Debouncer::new();
         let widget = self.imp();
         widget.seek_bar.set_increments(5_000.0, 10_000.0);
-        widget.seek_bar.connect_change_value(
-            clone!(@weak self as _self => @default-return glib::signal::Inhibit(false), move |_, _, requested| {
-                _self.imp()
-                    .track_position
-                    .set_text(&format_duration(requested));
+
+        // A live timestamp above the seek bar while scrubbing, or hovering it
+        let timestamp = gtk::Label::new(None);
+        let popover = gtk::Popover::builder()
+            .child(&timestamp)
+            .position(gtk::PositionType::Top)
+            .autohide(false)
+            .can_focus(false)
+            .build();
+        popover.set_parent(&*widget.seek_bar);
+        let show_timestamp = clone!(@weak popover, @weak timestamp => move |x: f64, position: f64| {
+            timestamp.set_text(&format_duration(position));
+            popover.set_pointing_to(Some(&gdk::Rectangle::new(x as i32, 0, 1, 1)));
+            popover.popup();
+        });
+
+        let seek_bar = &*widget.seek_bar;
+        let hover = gtk::EventControllerMotion::new();
+        hover.connect_motion(
+            clone!(@weak seek_bar, @strong show_timestamp => move |_, x, _| {
+                let adjustment = seek_bar.adjustment();
+                let range = adjustment.upper() - adjustment.lower();
+                let width = seek_bar.width();
+                if range > 0.0 && width > 0 {
+                    let fraction = (x / width as f64).clamp(0.0, 1.0);
+                    show_timestamp(x, adjustment.lower() + fraction * range);
+                }
+            }),
+        );
+        hover.connect_leave(clone!(@weak popover => move |_| popover.popdown()));
+        seek_bar.add_controller(hover);
+
+        seek_bar.connect_change_value(
+            clone!(@weak self as _self => @default-return glib::signal::Inhibit(false), move |seek_bar, _, requested| {
+                _self.imp().track_position.set_text(&format_duration(requested));
+
+                let adjustment = seek_bar.adjustment();
+                let range = adjustment.upper() - adjustment.lower();
+                if range > 0.0 {
+                    let fraction = ((requested - adjustment.lower()) / range).clamp(0.0, 1.0);
+                    show_timestamp(fraction * seek_bar.width() as f64, requested);
+                }
+
+                // Scrubbing results in a single seek once the user stops moving
                 let seek = seek.clone();
-                debouncer.debounce(200, move || seek(requested as u32));
+                let popover = popover.downgrade();
+                debouncer.debounce(200, move || {
+                    if let Some(popover) = popover.upgrade() {
+                        popover.popdown();
+                    }
+                    seek(requested as u32);
+                });
                 glib::signal::Inhibit(false)
             }),
         );
     }
 
+    pub fn set_volume(&self, volume: f64) {
+        let widget = self.imp();
+        widget.volume.set_value(volume);
+        let icon = match volume {
+            v if v <= 0.0 => "audio-volume-muted-symbolic",
+            v if v < 0.33 => "audio-volume-low-symbolic",
+            v if v < 0.66 => "audio-volume-medium-symbolic",
+            _ => "audio-volume-high-symbolic",
+        };
+        widget.volume_icon.set_icon_name(Some(icon));
+    }
+
+    // Shown while we keep the screen on or the computer awake, the reason being the tooltip
+    pub fn set_inhibit_indicator(&self, reason: Option<&str>) {
+        let indicator = &self.imp().inhibit_indicator;
+        indicator.set_visible(reason.is_some());
+        indicator.set_tooltip_text(reason);
+    }
+
+    pub fn connect_volume_changed<F>(&self, f: F)
+    where
+        F: Fn(f64) + 'static,
+    {
+        self.imp()
+            .volume
+            .connect_change_value(move |_, _, requested| {
+                f(requested.clamp(0.0, 1.0));
+                glib::signal::Inhibit(false)
+            });
+    }
+
     pub fn set_playing(&self, is_playing: bool) {
         let widget = self.imp();
         widget.controls.set_playing(is_playing);
@@ -190,6 +320,37 @@ impl PlaybackWidget {
         widget.controls_mobile.set_shuffled(shuffled);
     }
 
+    // Whether the current song is in Liked Songs, None hides the button (nothing playing)
+    pub fn set_saved(&self, is_saved: Option<bool>) {
+        let button = &self.imp().save_button;
+        button.set_visible(is_saved.is_some());
+        let is_saved = is_saved.unwrap_or(false);
+        let tooltip = if is_saved {
+            gettext("Remove from Liked Songs")
+        } else {
+            gettext("Add to Liked Songs")
+        };
+        indicators::set_saved(button, is_saved, &tooltip);
+    }
+
+    pub fn connect_save<F>(&self, f: F)
+    where
+        F: Fn() + 'static,
+    {
+        self.imp().save_button.connect_clicked(move |_| f());
+    }
+
+    pub fn set_share_visible(&self, visible: bool) {
+        self.imp().share_button.set_visible(visible);
+    }
+
+    pub fn bind_share_menu<F>(&self, shareable: F)
+    where
+        F: Fn() -> Option<Shareable> + Clone + 'static,
+    {
+        bind_share_menu(&self.imp().share_button, shareable);
+    }
+
     pub fn set_seekbar_visible(&self, visible: bool) {
         let widget = self.imp();
         widget.seek_bar.set_visible(visible);
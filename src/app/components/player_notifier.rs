@@ -96,8 +96,14 @@ impl PlayerNotifier {
                     token: token.to_owned(),
                 })
             }
+            LoginEvent::LoginStarted(LoginStartedEvent::OAuth { refresh_token }) => {
+                Some(Command::OAuthLogin {
+                    refresh_token: refresh_token.to_owned(),
+                })
+            }
             LoginEvent::FreshTokenRequested => Some(Command::RefreshToken),
             LoginEvent::LogoutCompleted => Some(Command::Logout),
+            LoginEvent::LoginCompleted(_) => Some(Command::FetchRootlist),
             _ => None,
         };
 
@@ -147,7 +153,32 @@ impl PlayerNotifier {
         }
     }
 
+    // Whether long silences can be skipped in the current track
+    fn trims_silence(&self) -> bool {
+        let state = self.app_model.get_state();
+        state
+            .playback
+            .current_song()
+            .map(|song| {
+                state
+                    .settings
+                    .settings
+                    .silence_trimming
+                    .applies_to(&song.album.id)
+            })
+            .unwrap_or(false)
+    }
+
     fn notify_local_player(&self, event: &PlaybackEvent) {
+        // Before the track is loaded, so that it applies from its start
+        if matches!(
+            event,
+            PlaybackEvent::TrackChanged(_) | PlaybackEvent::SourceChanged
+        ) {
+            self.send_command_to_local_player(Command::PlayerSetSilenceTrimming(
+                self.trims_silence(),
+            ));
+        }
         let command = match event {
             PlaybackEvent::PlaybackPaused => Some(Command::PlayerPause),
             PlaybackEvent::PlaybackResumed => Some(Command::PlayerResume),
@@ -217,6 +248,17 @@ impl EventListener for PlayerNotifier {
             (Device::Local, AppEvent::SettingsEvent(SettingsEvent::PlayerSettingsChanged)) => {
                 self.send_command_to_local_player(Command::ReloadSettings)
             }
+            // Whatever the device, so that the local player is ready if we switch back to it
+            (_, AppEvent::SettingsEvent(SettingsEvent::NightModeChanged(strength))) => {
+                self.send_command_to_local_player(Command::PlayerSetNightMode(*strength))
+            }
+            (_, AppEvent::SettingsEvent(SettingsEvent::SilenceTrimmingChanged)) => self
+                .send_command_to_local_player(Command::PlayerSetSilenceTrimming(
+                    self.trims_silence(),
+                )),
+            (_, AppEvent::SettingsEvent(SettingsEvent::PlaybackMetricsChanged(enabled))) => {
+                self.send_command_to_local_player(Command::PlayerSetMetricsEnabled(*enabled))
+            }
             (Device::Connect(_), AppEvent::PlaybackEvent(event)) => {
                 self.notify_connect_player(event)
             }
@@ -6,3 +6,6 @@ pub use song::*;
 
 mod song_actions;
 pub use song_actions::*;
+
+mod song_preview;
+use song_preview::SongPreview;
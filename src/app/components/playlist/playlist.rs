@@ -1,12 +1,14 @@
 use gio::prelude::*;
 use gtk::prelude::*;
+use std::cell::RefCell;
 use std::ops::Deref;
 use std::rc::Rc;
 
+use super::SongPreview;
 use crate::app::components::utils::{ancestor, AnimatorDefault};
 use crate::app::components::{Component, EventListener, SongWidget};
-use crate::app::models::{SongListModel, SongModel, SongState};
-use crate::app::state::{PlaybackEvent, SelectionEvent, SelectionState};
+use crate::app::models::{SongDescription, SongListModel, SongModel, SongState};
+use crate::app::state::{BrowserEvent, PlaybackEvent, SelectionEvent, SelectionState};
 use crate::app::{AppEvent, Worker};
 
 pub trait PlaylistModel {
@@ -18,6 +20,14 @@ pub trait PlaylistModel {
 
     fn play_song_at(&self, pos: usize, id: &str);
 
+    // Plays only the songs left once the list is filtered, starting with that one
+    // By default, we simply play the whole list from there
+    fn play_filtered_songs(&self, _songs: Vec<SongDescription>, id: &str) {
+        if let Some(pos) = self.song_list_model().find_index(id) {
+            self.play_song_at(pos, id);
+        }
+    }
+
     fn autoscroll_to_playing(&self) -> bool {
         true
     }
@@ -26,6 +36,11 @@ pub trait PlaylistModel {
         true
     }
 
+    // Whether songs are previewed while the pointer rests on them (see SongPreview)
+    fn preview_on_hover(&self) -> bool {
+        false
+    }
+
     fn actions_for(&self, _id: &str) -> Option<gio::ActionGroup> {
         None
     }
@@ -33,6 +48,16 @@ pub trait PlaylistModel {
         None
     }
 
+    // Whether the song was queued by autoplay rather than by the user
+    fn is_autoplay(&self, _id: &str) -> bool {
+        false
+    }
+
+    // Whether the song is in the user's Liked Songs, or None if that isn't shown in this list
+    fn is_saved(&self, _id: &str) -> Option<bool> {
+        None
+    }
+
     fn select_song(&self, _id: &str) {}
     fn deselect_song(&self, _id: &str) {}
     fn enable_selection(&self) -> bool {
@@ -58,6 +83,7 @@ pub trait PlaylistModel {
         SongState {
             is_selected,
             is_playing,
+            is_saved: self.is_saved(id).unwrap_or(false),
         }
     }
 
@@ -76,6 +102,10 @@ pub struct Playlist<Model> {
     animator: AnimatorDefault,
     listview: gtk::ListView,
     model: Rc<Model>,
+    // What the list is filtered by, as typed (empty when it isn't)
+    filter_text: Rc<RefCell<String>>,
+    filter: gtk::CustomFilter,
+    preview: Rc<SongPreview>,
 }
 
 impl<Model> Playlist<Model>
@@ -84,7 +114,14 @@ where
 {
     pub fn new(listview: gtk::ListView, model: Rc<Model>, worker: Worker) -> Self {
         let list_model = model.song_list_model();
-        let selection_model = gtk::NoSelection::new(Some(list_model.clone()));
+        let filter_text = Rc::new(RefCell::new(String::new()));
+        let filter = gtk::CustomFilter::new(clone!(@strong filter_text => move |item| {
+            let text = filter_text.borrow();
+            let song = item.downcast_ref::<SongModel>().unwrap();
+            text.trim().is_empty() || song.description().matches_filter(&text)
+        }));
+        let filtered_model = gtk::FilterListModel::new(Some(list_model), Some(filter.clone()));
+        let selection_model = gtk::NoSelection::new(Some(filtered_model.clone()));
         let factory = gtk::SignalListItemFactory::new();
 
         listview.add_css_class("playlist");
@@ -97,10 +134,29 @@ where
         Self::set_paused(&listview, model.is_paused());
         Self::set_selection_active(&listview, model.is_selection_enabled());
 
-        factory.connect_setup(|_, item| {
+        let preview = SongPreview::new();
+        factory.connect_setup(clone!(@weak model, @strong preview => move |_, item| {
             let item = item.downcast_ref::<gtk::ListItem>().unwrap();
-            item.set_child(Some(&SongWidget::new()));
-        });
+            let widget = SongWidget::new();
+
+            let hover = gtk::EventControllerMotion::new();
+            hover.connect_enter(clone!(@weak item, @weak model, @strong preview => move |_, _, _| {
+                if !model.preview_on_hover() {
+                    return;
+                }
+                let url = item
+                    .item()
+                    .and_then(|song| song.downcast::<SongModel>().ok())
+                    .and_then(|song| song.description().preview_url.clone());
+                if let Some(url) = url {
+                    preview.start(url);
+                }
+            }));
+            hover.connect_leave(clone!(@strong preview => move |_| preview.stop()));
+            widget.add_controller(hover);
+
+            item.set_child(Some(&widget));
+        }));
 
         factory.connect_bind(clone!(@weak model => move |_, item| {
             let item = item.downcast_ref::<gtk::ListItem>().unwrap();
@@ -113,6 +169,8 @@ where
             let id = &song_model.get_id();
             widget.set_actions(model.actions_for(id).as_ref());
             widget.set_menu(model.menu_for(id).as_ref());
+            widget.set_autoplay(model.is_autoplay(id));
+            widget.set_show_saved(model.is_saved(id).is_some());
         }));
 
         factory.connect_unbind(|_, item| {
@@ -121,14 +179,21 @@ where
             song_model.unbind_all();
         });
 
-        listview.connect_activate(clone!(@weak list_model, @weak model => move |_, position| {
-            let song = list_model.index_continuous(position as usize).expect("attempt to access invalid index");
-            let song = song.description();
+        listview.connect_activate(clone!(@weak filtered_model, @weak model, @strong filter_text, @strong preview => move |_, position| {
+            preview.stop();
+            let song = filtered_model.item(position).and_then(|s| s.downcast::<SongModel>().ok()).expect("attempt to access invalid index");
+            let id = song.get_id();
             let selection_enabled = model.is_selection_enabled();
             if selection_enabled {
-                model.toggle_select(&song.id);
+                model.toggle_select(&id);
+            } else if filter_text.borrow().trim().is_empty() {
+                model.play_song_at(position as usize, &id);
             } else {
-                model.play_song_at(position as usize, &song.id);
+                let songs = (0..filtered_model.n_items())
+                    .filter_map(|i| filtered_model.item(i)?.downcast::<SongModel>().ok())
+                    .map(|s| s.into_description())
+                    .collect();
+                model.play_filtered_songs(songs, &id);
             }
         }));
 
@@ -144,9 +209,48 @@ where
             animator: AnimatorDefault::ease_in_out_animator(),
             listview,
             model,
+            filter_text,
+            filter,
+            preview,
         }
     }
 
+    // Ctrl+F on that page reveals the bar, then the list only shows the songs whose title, artists
+    // or album match what's typed in it
+    pub fn bind_filter_bar(
+        &self,
+        page: &impl IsA<gtk::Widget>,
+        bar: &gtk::SearchBar,
+        entry: &gtk::SearchEntry,
+    ) {
+        bar.connect_entry(entry);
+
+        let filter_text = Rc::clone(&self.filter_text);
+        let filter = &self.filter;
+        entry.connect_search_changed(clone!(@weak filter => move |entry| {
+            filter_text.replace(entry.text().to_string());
+            filter.changed(gtk::FilterChange::Different);
+        }));
+
+        let show_bar = gtk::CallbackAction::new(
+            clone!(@weak bar, @weak entry => @default-return false, move |_, _| {
+                bar.set_search_mode(true);
+                entry.grab_focus();
+                true
+            }),
+        );
+        let controller = gtk::ShortcutController::new();
+        controller.add_shortcut(gtk::Shortcut::new(
+            gtk::ShortcutTrigger::parse_string("<Ctrl>F"),
+            Some(show_bar),
+        ));
+        page.add_controller(controller);
+    }
+
+    fn is_filtered(&self) -> bool {
+        !self.filter_text.borrow().trim().is_empty()
+    }
+
     fn autoscroll_to_playing(&self, index: usize) {
         let len = self.model.song_list_model().partial_len() as f64;
         let scrolled_window: Option<gtk::ScrolledWindow> = ancestor(&self.listview);
@@ -171,7 +275,8 @@ where
     }
 
     fn update_list(&self) {
-        let autoscroll_to_playing = self.model.autoscroll_to_playing();
+        // Positions don't match the full list while filtered
+        let autoscroll_to_playing = self.model.autoscroll_to_playing() && !self.is_filtered();
         let is_selection_enabled = self.model.is_selection_enabled();
 
         self.model.song_list_model().for_each(|i, model_song| {
@@ -183,6 +288,15 @@ where
         });
     }
 
+    fn update_saved_state(&self, ids: &[String]) {
+        let songs = self.model.song_list_model();
+        for id in ids {
+            if let (Some(song), Some(is_saved)) = (songs.get(id), self.model.is_saved(id)) {
+                song.set_saved(is_saved);
+            }
+        }
+    }
+
     fn set_selection_active(listview: &gtk::ListView, active: bool) {
         let class_name = "playlist--selectable";
         if active {
@@ -208,10 +322,12 @@ impl SongModel {
         SongState {
             is_playing,
             is_selected,
+            is_saved,
         }: SongState,
     ) {
         self.set_playing(is_playing);
         self.set_selected(is_selected);
+        self.set_saved(is_saved);
     }
 }
 
@@ -236,9 +352,17 @@ where
                 Self::set_selection_active(&self.listview, self.model.is_selection_enabled());
                 self.update_list();
             }
+            AppEvent::BrowserEvent(BrowserEvent::TrackSavedStateChanged(ids)) => {
+                self.update_saved_state(ids);
+            }
             _ => {}
         }
     }
+
+    // The pointer doesn't leave the song when the screen goes away
+    fn on_unmount(&mut self) {
+        self.preview.stop();
+    }
 }
 
 impl<Model> Component for Playlist<Model> {
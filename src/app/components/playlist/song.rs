@@ -1,7 +1,9 @@
-use crate::app::components::display_add_css_provider;
+use crate::app::components::artist_links::connect_artist_links;
+use crate::app::components::{display_add_css_provider, indicators};
 use crate::app::loader::ImageLoader;
 use crate::app::models::SongModel;
 use crate::app::Worker;
+use gettextrs::*;
 use gio::MenuModel;
 use glib::subclass::InitializingObject;
 
@@ -14,6 +16,7 @@ mod imp {
     use super::*;
 
     const SONG_CLASS: &str = "song--playing";
+    const SAVED_CLASS: &str = "song__save--saved";
 
     #[derive(Debug, Default, CompositeTemplate)]
     #[template(resource = "/dev/alextren/Spot/components/song.ui")]
@@ -33,9 +36,15 @@ mod imp {
         #[template_child]
         pub song_artist: TemplateChild<gtk::Label>,
 
+        #[template_child]
+        pub song_autoplay: TemplateChild<gtk::Label>,
+
         #[template_child]
         pub song_length: TemplateChild<gtk::Label>,
 
+        #[template_child]
+        pub save_btn: TemplateChild<gtk::Button>,
+
         #[template_child]
         pub menu_btn: TemplateChild<gtk::MenuButton>,
 
@@ -59,9 +68,10 @@ mod imp {
     }
 
     lazy_static! {
-        static ref PROPERTIES: [glib::ParamSpec; 2] = [
+        static ref PROPERTIES: [glib::ParamSpec; 3] = [
             glib::ParamSpecBoolean::builder("playing").build(),
-            glib::ParamSpecBoolean::builder("selected").build()
+            glib::ParamSpecBoolean::builder("selected").build(),
+            glib::ParamSpecBoolean::builder("saved").build()
         ];
     }
 
@@ -88,6 +98,12 @@ mod imp {
                         .expect("type conformity checked by `Object::set_property`");
                     self.song_checkbox.set_active(is_selected);
                 }
+                "saved" => {
+                    let is_saved = value
+                        .get()
+                        .expect("type conformity checked by `Object::set_property`");
+                    self.set_saved(is_saved);
+                }
                 _ => unimplemented!(),
             }
         }
@@ -96,6 +112,7 @@ mod imp {
             match pspec.name() {
                 "playing" => self.obj().has_css_class(SONG_CLASS).to_value(),
                 "selected" => self.song_checkbox.is_active().to_value(),
+                "saved" => self.save_btn.has_css_class(SAVED_CLASS).to_value(),
                 _ => unimplemented!(),
             }
         }
@@ -103,6 +120,7 @@ mod imp {
         fn constructed(&self) {
             self.parent_constructed();
             self.song_checkbox.set_sensitive(false);
+            connect_artist_links(&self.song_artist);
         }
 
         fn dispose(&self) {
@@ -112,6 +130,22 @@ mod imp {
         }
     }
 
+    impl SongWidget {
+        fn set_saved(&self, is_saved: bool) {
+            let tooltip = if is_saved {
+                gettext("Remove from Liked Songs")
+            } else {
+                gettext("Add to Liked Songs")
+            };
+            indicators::set_saved(&self.save_btn, is_saved, &tooltip);
+            if is_saved {
+                self.save_btn.add_css_class(SAVED_CLASS);
+            } else {
+                self.save_btn.remove_css_class(SAVED_CLASS);
+            }
+        }
+    }
+
     impl WidgetImpl for SongWidget {}
     impl GridImpl for SongWidget {}
 }
@@ -138,6 +172,14 @@ impl SongWidget {
         }
     }
 
+    pub fn set_autoplay(&self, is_autoplay: bool) {
+        self.imp().song_autoplay.set_visible(is_autoplay);
+    }
+
+    pub fn set_show_saved(&self, show_saved: bool) {
+        self.imp().save_btn.set_visible(show_saved);
+    }
+
     fn set_show_cover(&self, show_cover: bool) {
         let song_class = "song--cover";
         if show_cover {
@@ -156,7 +198,7 @@ impl SongWidget {
             let _self = self.downgrade();
             worker.send_local_task(async move {
                 if let Some(_self) = _self.upgrade() {
-                    let loader = ImageLoader::new();
+                    let loader = ImageLoader::for_widget(&_self);
                     let result = loader.load_remote(&url, "jpg", 100, 100).await;
                     _self.set_image(result.as_ref());
                 }
@@ -168,10 +210,11 @@ impl SongWidget {
         let widget = self.imp();
 
         model.bind_title(&*widget.song_title, "label");
-        model.bind_artist(&*widget.song_artist, "label");
+        model.bind_artist_links(&*widget.song_artist, "label");
         model.bind_duration(&*widget.song_length, "label");
         model.bind_playing(self, "playing");
         model.bind_selected(self, "selected");
+        model.bind_saved(self, "saved");
 
         self.set_show_cover(show_cover);
         if show_cover {
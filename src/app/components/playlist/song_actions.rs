@@ -1,9 +1,15 @@
 use gdk::prelude::*;
+use gettextrs::*;
 use gio::SimpleAction;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
 
-use crate::app::models::SongDescription;
-use crate::app::state::{AppAction, PlaybackAction};
-use crate::app::ActionDispatcher;
+use crate::api::{SpotifyApiClient, SpotifyApiError};
+use crate::app::components::{labels, utils};
+use crate::app::models::{SongDescription, SongListModel};
+use crate::app::state::{AppAction, BrowserAction, PlaybackAction};
+use crate::app::{ActionDispatcher, AppModel, SongsSource};
 
 impl SongDescription {
     pub fn make_queue_action(
@@ -32,6 +38,65 @@ impl SongDescription {
         dequeue
     }
 
+    // Adds the song to Liked Songs, or removes it if it's already there.
+    // The state is updated right away so that every view reflects it, and reverted if the API call fails.
+    pub fn toggle_saved(
+        &self,
+        app_model: &AppModel,
+        dispatcher: &(dyn ActionDispatcher + 'static),
+    ) {
+        let is_saved = app_model
+            .get_state()
+            .browser
+            .is_track_saved(&self.id)
+            .unwrap_or(false);
+
+        let save = BrowserAction::SaveTracks(vec![self.clone()]);
+        let remove = BrowserAction::RemoveSavedTracks(vec![self.id.clone()]);
+        let (toggle, revert) = if is_saved {
+            (remove, save)
+        } else {
+            (save, remove)
+        };
+        dispatcher.dispatch(toggle.into());
+
+        let api = app_model.get_spotify();
+        let ids = vec![self.id.clone()];
+        dispatcher.call_spotify_and_dispatch_many(move || async move {
+            let result = if is_saved {
+                api.remove_saved_tracks(ids).await
+            } else {
+                api.save_tracks(ids).await
+            };
+            match result {
+                Ok(_) => Ok(vec![]),
+                // Let these go through the usual token refresh
+                Err(err @ (SpotifyApiError::NoToken | SpotifyApiError::InvalidToken)) => Err(err),
+                Err(err) => {
+                    error!("Failed to update Liked Songs: {}", err);
+                    Ok(vec![
+                        revert.into(),
+                        AppAction::ShowNotification(gettext("Couldn't update Liked Songs")),
+                    ])
+                }
+            }
+        });
+    }
+
+    pub fn make_save_action(
+        &self,
+        app_model: Rc<AppModel>,
+        dispatcher: Box<dyn ActionDispatcher>,
+        name: Option<&str>,
+    ) -> SimpleAction {
+        let toggle_saved = SimpleAction::new(name.unwrap_or("toggle_saved"), None);
+        let song = self.clone();
+        toggle_saved.connect_activate(move |_, _| {
+            song.toggle_saved(&app_model, dispatcher.as_ref());
+        });
+        toggle_saved
+    }
+
     pub fn make_link_action(&self, name: Option<&str>) -> SimpleAction {
         let track_id = self.id.clone();
         let copy_link = SimpleAction::new(name.unwrap_or("copy_link"), None);
@@ -45,6 +110,15 @@ impl SongDescription {
         copy_link
     }
 
+    pub fn make_web_player_action(&self, name: Option<&str>) -> SimpleAction {
+        let track_id = self.id.clone();
+        let open_in_web_player = SimpleAction::new(name.unwrap_or("open_in_web_player"), None);
+        open_in_web_player.connect_activate(move |_, _| {
+            utils::open_in_browser(&utils::web_player_url("track", &track_id));
+        });
+        open_in_web_player
+    }
+
     pub fn make_album_action(
         &self,
         dispatcher: Box<dyn ActionDispatcher>,
@@ -80,3 +154,180 @@ impl SongDescription {
             .collect()
     }
 }
+
+// How many tracks we save (or remove) per request, that's the most the API accepts
+const SAVE_BATCH_SIZE: usize = 50;
+
+// Adds many songs to Liked Songs (all tracks of an album, say), one batch at a time so that
+// the progress can be shown as batches complete; the notification shown at the end allows undoing it.
+// Songs already known to be saved are left alone, so undoing doesn't remove them.
+pub fn save_tracks_in_bulk(
+    songs: Vec<SongDescription>,
+    app_model: &AppModel,
+    dispatcher: &(dyn ActionDispatcher + 'static),
+) {
+    let songs: Vec<SongDescription> = {
+        let state = app_model.get_state();
+        songs
+            .into_iter()
+            .filter(|s| state.browser.is_track_saved(&s.id) != Some(true))
+            .collect()
+    };
+
+    let total = songs.len();
+    if total == 0 {
+        dispatcher.dispatch(AppAction::ShowNotification(gettext(
+            // translators: This notification shows up when trying to like all tracks of an album (or the top tracks of an artist) that are all liked already.
+            "All tracks are already in Liked Songs",
+        )));
+        return;
+    }
+
+    dispatcher.dispatch(AppAction::ShowTracksSavingProgress(0, total));
+
+    let all_ids: Arc<Vec<String>> = Arc::new(songs.iter().map(|s| s.id.clone()).collect());
+    let saved_count = Arc::new(AtomicUsize::new(0));
+    for batch in songs.chunks(SAVE_BATCH_SIZE) {
+        let batch = batch.to_vec();
+        let api = app_model.get_spotify();
+        let all_ids = Arc::clone(&all_ids);
+        let saved_count = Arc::clone(&saved_count);
+        dispatcher.call_spotify_and_dispatch_many(move || async move {
+            let ids = batch.iter().map(|s| s.id.clone()).collect();
+            api.save_tracks(ids).await?;
+
+            let saved = saved_count.fetch_add(batch.len(), Ordering::SeqCst) + batch.len();
+            let progress = if saved < total {
+                AppAction::ShowTracksSavingProgress(saved, total)
+            } else {
+                AppAction::ShowTracksSavedNotification(all_ids.to_vec())
+            };
+            Ok(vec![BrowserAction::SaveTracks(batch).into(), progress])
+        });
+    }
+}
+
+// The opposite of save_tracks_in_bulk, used to undo it
+pub fn remove_saved_tracks_in_bulk(
+    ids: Vec<String>,
+    app_model: &AppModel,
+    dispatcher: &(dyn ActionDispatcher + 'static),
+) {
+    for batch in ids.chunks(SAVE_BATCH_SIZE) {
+        let batch = batch.to_vec();
+        let api = app_model.get_spotify();
+        dispatcher.call_spotify_and_dispatch(move || async move {
+            api.remove_saved_tracks(batch.clone()).await?;
+            Ok(BrowserAction::RemoveSavedTracks(batch).into())
+        });
+    }
+}
+
+// The most tracks the API adds to a playlist at once
+const PLAYLIST_BATCH_SIZE: usize = 100;
+
+// Once created, the playlist is kept (and listed) even if some tracks couldn't be added to it
+async fn new_playlist_with_songs(
+    api: Arc<dyn SpotifyApiClient + Send + Sync>,
+    name: String,
+    user_id: String,
+    uris: Vec<String>,
+) -> Result<Vec<AppAction>, SpotifyApiError> {
+    let playlist = api.create_new_playlist(&name, &user_id).await?;
+    for batch in uris.chunks(PLAYLIST_BATCH_SIZE) {
+        if let Err(err) = api.add_to_playlist(&playlist.id, batch.to_vec()).await {
+            error!("Could not add all tracks to {}: {}", playlist.id, err);
+            return Ok(vec![
+                AppAction::CreatePlaylist(playlist),
+                AppAction::ShowNotification(labels::playlist_incomplete_label(&name)),
+            ]);
+        }
+    }
+    Ok(vec![AppAction::CreatePlaylist(playlist)])
+}
+
+// Creates a playlist for the logged in user with these tracks, in order
+pub fn create_playlist_with_songs(
+    name: String,
+    uris: Vec<String>,
+    app_model: &AppModel,
+    dispatcher: &(dyn ActionDispatcher + 'static),
+) {
+    let user_id = match app_model.get_state().logged_user.user.clone() {
+        Some(user_id) => user_id,
+        None => return,
+    };
+    let api = app_model.get_spotify();
+    dispatcher
+        .call_spotify_and_dispatch_many(move || new_playlist_with_songs(api, name, user_id, uris));
+}
+
+// Same, with every track of an album or playlist, including those that aren't loaded yet
+pub fn create_playlist_from_source(
+    name: String,
+    source: SongsSource,
+    app_model: &AppModel,
+    dispatcher: &(dyn ActionDispatcher + 'static),
+) {
+    let user_id = match app_model.get_state().logged_user.user.clone() {
+        Some(user_id) => user_id,
+        None => return,
+    };
+    let api = app_model.get_spotify();
+    let loader = app_model.get_batch_loader();
+    dispatcher.call_spotify_and_dispatch_many(move || async move {
+        let songs = loader.query_all(source, TRACK_LIST_BATCH_SIZE).await?;
+        let uris = songs.into_iter().map(|song| song.uri).collect();
+        new_playlist_with_songs(api, name, user_id, uris).await
+    });
+}
+
+// How many tracks are loaded at once when a whole list is needed
+const TRACK_LIST_BATCH_SIZE: usize = 50;
+
+fn set_track_list(songs: &[SongDescription], template: &str, dispatcher: &dyn ActionDispatcher) {
+    let track_list = utils::format_track_list(songs, template);
+    let clipboard = gdk::Display::default().unwrap().clipboard();
+    clipboard
+        .set_content(Some(&gdk::ContentProvider::for_value(
+            &track_list.to_value(),
+        )))
+        .expect("Failed to set clipboard content");
+    dispatcher.dispatch(AppAction::ShowNotification(
+        labels::TRACK_LIST_COPIED.clone(),
+    ));
+}
+
+// Copies the tracks of an album or playlist to the clipboard, formatted with the user's template.
+// Tracks that aren't loaded yet are fetched first, so that the list is always complete.
+pub fn copy_track_list(
+    source: SongsSource,
+    songs: &SongListModel,
+    app_model: &AppModel,
+    dispatcher: &dyn ActionDispatcher,
+) {
+    let template = app_model
+        .get_state()
+        .settings
+        .settings
+        .track_list_template
+        .clone();
+    if songs.partial_len() >= songs.len() {
+        set_track_list(&songs.collect(), &template, dispatcher);
+        return;
+    }
+    let loader = app_model.get_batch_loader();
+    let dispatcher = dispatcher.box_clone();
+    glib::MainContext::default().spawn_local(async move {
+        match loader.query_all(source, TRACK_LIST_BATCH_SIZE).await {
+            Ok(songs) => set_track_list(&songs, &template, dispatcher.as_ref()),
+            Err(err) => {
+                error!("Could not load the track list: {}", err);
+                dispatcher.dispatch(AppAction::ShowNotification(gettext(
+                    // translators: This notification is the default message for unhandled errors. Logs refer to console output.
+                    "An error occured. Check logs for details!",
+                )));
+            }
+        }
+    });
+}
@@ -0,0 +1,65 @@
+use gtk::prelude::*;
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use std::time::Duration;
+
+// How long the pointer has to rest on a song before it's previewed, so that moving across the list
+// doesn't start anything
+const HOVER_DELAY: Duration = Duration::from_millis(600);
+const PREVIEW_LENGTH: Duration = Duration::from_secs(5);
+const PREVIEW_VOLUME: f64 = 0.3;
+
+// Plays the start of Spotify's clip of a song, quietly, while the pointer rests on it. It goes through
+// GTK's own media playback rather than the player, so whatever is playing carries on meanwhile.
+#[derive(Default)]
+pub struct SongPreview {
+    stream: RefCell<Option<gtk::MediaFile>>,
+    // Either waiting to start the preview, or to end it
+    timeout: Cell<Option<glib::SourceId>>,
+}
+
+impl SongPreview {
+    pub fn new() -> Rc<Self> {
+        Rc::new(Self::default())
+    }
+
+    pub fn start(self: &Rc<Self>, url: String) {
+        self.stop();
+        let preview = Rc::downgrade(self);
+        let timeout = glib::timeout_add_local(HOVER_DELAY, move || {
+            if let Some(preview) = preview.upgrade() {
+                preview.timeout.set(None);
+                preview.play(&url);
+            }
+            glib::Continue(false)
+        });
+        self.timeout.set(Some(timeout));
+    }
+
+    fn play(self: &Rc<Self>, url: &str) {
+        let stream = gtk::MediaFile::for_file(&gio::File::for_uri(url));
+        stream.set_volume(PREVIEW_VOLUME);
+        stream.play();
+        self.stream.replace(Some(stream));
+
+        let preview = Rc::downgrade(self);
+        let timeout = glib::timeout_add_local(PREVIEW_LENGTH, move || {
+            if let Some(preview) = preview.upgrade() {
+                preview.timeout.set(None);
+                preview.stop();
+            }
+            glib::Continue(false)
+        });
+        self.timeout.set(Some(timeout));
+    }
+
+    pub fn stop(&self) {
+        if let Some(timeout) = self.timeout.take() {
+            timeout.remove();
+        }
+        if let Some(stream) = self.stream.take() {
+            stream.pause();
+            stream.clear();
+        }
+    }
+}
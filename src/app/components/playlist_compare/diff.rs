@@ -0,0 +1,81 @@
+use std::collections::HashSet;
+
+use crate::app::models::SongDescription;
+
+// A track of one of the playlists being compared. The same recording can be on Spotify several times
+// (on an album and on a compilation, say) under different ids, but they share an ISRC.
+#[derive(Clone, Debug)]
+pub struct ComparedTrack {
+    pub song: SongDescription,
+    pub isrc: Option<String>,
+}
+
+// The tracks of a playlist that the other one doesn't have, in order and without duplicates
+pub fn only_in(playlist: &[ComparedTrack], other: &[ComparedTrack]) -> Vec<SongDescription> {
+    let ids: HashSet<&str> = other.iter().map(|t| t.song.id.as_str()).collect();
+    let isrcs: HashSet<&str> = other.iter().filter_map(|t| t.isrc.as_deref()).collect();
+    let mut seen = HashSet::new();
+    playlist
+        .iter()
+        .filter(|t| !ids.contains(t.song.id.as_str()))
+        .filter(|t| !t.isrc.as_deref().map_or(false, |isrc| isrcs.contains(isrc)))
+        .filter(|t| seen.insert(t.song.id.as_str()))
+        .map(|t| t.song.clone())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::app::models::AlbumRef;
+
+    fn track(id: &str, isrc: Option<&str>) -> ComparedTrack {
+        ComparedTrack {
+            song: SongDescription {
+                id: id.to_string(),
+                track_number: None,
+                uri: format!("spotify:track:{id}"),
+                title: id.to_string(),
+                artists: vec![],
+                album: AlbumRef {
+                    id: "".to_string(),
+                    name: "".to_string(),
+                },
+                duration: 0,
+                art: None,
+                preview_url: None,
+            },
+            isrc: isrc.map(|s| s.to_string()),
+        }
+    }
+
+    fn ids(songs: Vec<SongDescription>) -> Vec<String> {
+        songs.into_iter().map(|s| s.id).collect()
+    }
+
+    #[test]
+    fn test_only_in_by_id() {
+        let a = vec![track("1", None), track("2", None), track("3", None)];
+        let b = vec![track("2", None), track("4", None)];
+        assert_eq!(ids(only_in(&a, &b)), vec!["1", "3"]);
+        assert_eq!(ids(only_in(&b, &a)), vec!["4"]);
+    }
+
+    #[test]
+    fn test_only_in_by_isrc() {
+        // Same recording, from another release
+        let a = vec![
+            track("1", Some("USABC0000001")),
+            track("2", Some("USABC0000002")),
+        ];
+        let b = vec![track("3", Some("USABC0000001"))];
+        assert_eq!(ids(only_in(&a, &b)), vec!["2"]);
+    }
+
+    #[test]
+    fn test_only_in_without_duplicates() {
+        let a = vec![track("1", None), track("1", None)];
+        assert_eq!(ids(only_in(&a, &[])), vec!["1"]);
+    }
+}
@@ -0,0 +1,6 @@
+mod diff;
+mod playlist_compare;
+mod playlist_compare_model;
+
+pub use playlist_compare::*;
+pub use playlist_compare_model::*;
@@ -0,0 +1,282 @@
+use gettextrs::*;
+use gio::prelude::*;
+use gtk::prelude::*;
+use gtk::subclass::prelude::*;
+use gtk::CompositeTemplate;
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use super::diff::only_in;
+use super::PlaylistCompareModel;
+use crate::app::components::labels;
+use crate::app::models::SongDescription;
+use crate::app::Worker;
+
+mod imp {
+
+    use super::*;
+    use libadwaita::subclass::prelude::*;
+
+    #[derive(Debug, Default, CompositeTemplate)]
+    #[template(resource = "/dev/alextren/Spot/components/playlist_compare.ui")]
+    pub struct PlaylistCompareWindow {
+        #[template_child]
+        pub stack: TemplateChild<gtk::Stack>,
+
+        #[template_child]
+        pub view_stack: TemplateChild<libadwaita::ViewStack>,
+
+        #[template_child]
+        pub first_page: TemplateChild<libadwaita::ViewStackPage>,
+
+        #[template_child]
+        pub first_list: TemplateChild<gtk::ListBox>,
+
+        #[template_child]
+        pub second_page: TemplateChild<libadwaita::ViewStackPage>,
+
+        #[template_child]
+        pub second_list: TemplateChild<gtk::ListBox>,
+
+        #[template_child]
+        pub copy_button: TemplateChild<gtk::Button>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for PlaylistCompareWindow {
+        const NAME: &'static str = "PlaylistCompareWindow";
+        type Type = super::PlaylistCompareWindow;
+        type ParentType = libadwaita::Window;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for PlaylistCompareWindow {
+        fn constructed(&self) {
+            self.parent_constructed();
+            for list in [&*self.first_list, &*self.second_list] {
+                // translators: Shown when a playlist has no tracks that the one it's compared with doesn't have
+                let placeholder = gtk::Label::new(Some(&gettext("Nothing missing")));
+                placeholder.set_margin_top(12);
+                placeholder.set_margin_bottom(12);
+                placeholder.add_css_class("dim-label");
+                list.set_placeholder(Some(&placeholder));
+            }
+        }
+    }
+
+    impl WidgetImpl for PlaylistCompareWindow {}
+    impl WindowImpl for PlaylistCompareWindow {}
+    impl AdwWindowImpl for PlaylistCompareWindow {}
+}
+
+glib::wrapper! {
+    pub struct PlaylistCompareWindow(ObjectSubclass<imp::PlaylistCompareWindow>) @extends gtk::Widget, gtk::Window, libadwaita::Window;
+}
+
+impl PlaylistCompareWindow {
+    fn new() -> Self {
+        glib::Object::new()
+    }
+
+    fn show_loading(&self, first_title: &str, second_title: &str) {
+        let widget = self.imp();
+        widget.stack.set_visible_child_name("loading");
+        widget.view_stack.set_visible_child_name("first");
+        widget
+            .first_page
+            .set_title(Some(&labels::only_in_playlist_label(first_title)));
+        widget
+            .second_page
+            .set_title(Some(&labels::only_in_playlist_label(second_title)));
+    }
+
+    fn show_error(&self) {
+        self.imp().stack.set_visible_child_name("error");
+    }
+
+    fn fill_list(list: &gtk::ListBox, songs: &[SongDescription]) {
+        while let Some(row) = list.first_child() {
+            list.remove(&row);
+        }
+        for song in songs {
+            let row = libadwaita::ActionRow::builder()
+                .title(glib::markup_escape_text(&song.title))
+                .subtitle(glib::markup_escape_text(&song.artists_name()))
+                .build();
+            list.append(&row);
+        }
+    }
+
+    fn show_result(&self, only_in_first: &[SongDescription], only_in_second: &[SongDescription]) {
+        let widget = self.imp();
+        widget.stack.set_visible_child_name("result");
+        Self::fill_list(&widget.first_list, only_in_first);
+        Self::fill_list(&widget.second_list, only_in_second);
+        widget
+            .first_page
+            .set_badge_number(only_in_first.len() as u32);
+        widget
+            .second_page
+            .set_badge_number(only_in_second.len() as u32);
+        self.update_copy_button(only_in_first, only_in_second);
+    }
+
+    // Whether the list shown is the first one
+    fn is_first_shown(&self) -> bool {
+        self.imp().view_stack.visible_child_name().as_deref() != Some("second")
+    }
+
+    fn update_copy_button(
+        &self,
+        only_in_first: &[SongDescription],
+        only_in_second: &[SongDescription],
+    ) {
+        let shown = if self.is_first_shown() {
+            only_in_first
+        } else {
+            only_in_second
+        };
+        self.imp().copy_button.set_sensitive(!shown.is_empty());
+    }
+
+    fn connect_shown_list_changed<F>(&self, f: F)
+    where
+        F: Fn() + 'static,
+    {
+        self.imp()
+            .view_stack
+            .connect_visible_child_notify(move |_| f());
+    }
+
+    fn connect_copy<F>(&self, f: F)
+    where
+        F: Fn() + 'static,
+    {
+        self.imp().copy_button.connect_clicked(move |_| f());
+    }
+}
+
+// What's in one playlist but not in the other, both ways
+#[derive(Default)]
+struct Comparison {
+    other_id: String,
+    only_in_first: Vec<SongDescription>,
+    only_in_second: Vec<SongDescription>,
+}
+
+// Compares a playlist with another one, by track (or recording, see ComparedTrack): each list shows
+// the tracks one has that the other doesn't, and can be copied into a new playlist.
+pub struct PlaylistCompare {
+    model: Rc<PlaylistCompareModel>,
+    worker: Worker,
+    window: PlaylistCompareWindow,
+    // Bumped for each comparison, so that one still loading knows it's no longer wanted
+    generation: Rc<Cell<usize>>,
+    comparison: Rc<RefCell<Comparison>>,
+}
+
+impl PlaylistCompare {
+    pub fn new(model: PlaylistCompareModel, worker: Worker) -> Self {
+        let window = PlaylistCompareWindow::new();
+        let model = Rc::new(model);
+        let generation = Rc::new(Cell::new(0));
+        let comparison: Rc<RefCell<Comparison>> = Default::default();
+
+        window.connect_shown_list_changed(clone!(@weak window, @weak comparison => move || {
+            let comparison = comparison.borrow();
+            window.update_copy_button(&comparison.only_in_first, &comparison.only_in_second);
+        }));
+
+        window.connect_copy(
+            clone!(@weak window, @weak model, @weak comparison => move || {
+                let mut comparison = comparison.borrow_mut();
+                if window.is_first_shown() {
+                    let songs = std::mem::take(&mut comparison.only_in_first);
+                    model.copy_to_new_playlist(&model.id, &comparison.other_id, songs);
+                } else {
+                    let songs = std::mem::take(&mut comparison.only_in_second);
+                    model.copy_to_new_playlist(&comparison.other_id, &model.id, songs);
+                }
+                window.close();
+            }),
+        );
+
+        window.connect_close_request(
+            clone!(@strong generation => @default-return gtk::Inhibit(false), move |_| {
+                generation.set(generation.get() + 1);
+                gtk::Inhibit(false)
+            }),
+        );
+
+        Self {
+            model,
+            worker,
+            window,
+            generation,
+            comparison,
+        }
+    }
+
+    // Lists the playlists this one can be compared with, each activating compare.with(id)
+    pub fn fill_menu(&self, menu: &gio::Menu) {
+        let playlists = self.model.other_playlists();
+        if playlists.is_empty() {
+            // translators: Shown (disabled) in the menu of playlists to compare with, when the user has no other playlist
+            menu.append(Some(&gettext("No other playlists")), None);
+        }
+        for playlist in playlists {
+            let item = gio::MenuItem::new(Some(&playlist.title), None);
+            item.set_action_and_target_value(Some("compare.with"), Some(&playlist.id.to_variant()));
+            menu.append_item(&item);
+        }
+    }
+
+    pub fn compare_with(&self, parent: Option<&gtk::Window>, other_id: String) {
+        let current = self.generation.get() + 1;
+        self.generation.set(current);
+        *self.comparison.borrow_mut() = Comparison {
+            other_id: other_id.clone(),
+            ..Default::default()
+        };
+
+        self.window.show_loading(
+            &self.model.playlist_title(&self.model.id),
+            &self.model.playlist_title(&other_id),
+        );
+        self.window.set_transient_for(parent);
+        self.window.set_modal(true);
+        self.window.set_visible(true);
+
+        let model = Rc::clone(&self.model);
+        let window = self.window.downgrade();
+        let generation = Rc::clone(&self.generation);
+        let comparison = Rc::clone(&self.comparison);
+        self.worker.send_local_task(async move {
+            let first = model.load_tracks(&model.id).await;
+            let second = model.load_tracks(&other_id).await;
+            let window = match window.upgrade() {
+                Some(window) if generation.get() == current => window,
+                _ => return,
+            };
+            match (first, second) {
+                (Ok(first), Ok(second)) => {
+                    let mut comparison = comparison.borrow_mut();
+                    comparison.only_in_first = only_in(&first, &second);
+                    comparison.only_in_second = only_in(&second, &first);
+                    window.show_result(&comparison.only_in_first, &comparison.only_in_second);
+                }
+                (Err(e), _) | (_, Err(e)) => {
+                    warn!("Could not load the playlists to compare: {:?}", e);
+                    window.show_error();
+                }
+            }
+        });
+    }
+}
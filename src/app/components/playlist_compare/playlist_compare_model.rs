@@ -0,0 +1,86 @@
+use std::iter;
+use std::rc::Rc;
+
+use super::diff::ComparedTrack;
+use crate::api::SpotifyResult;
+use crate::app::components::{create_playlist_with_songs, fetch_playlist, labels};
+use crate::app::models::{PlaylistSummary, SongDescription};
+use crate::app::{ActionDispatcher, AppModel};
+
+pub struct PlaylistCompareModel {
+    // The playlist that others are compared with
+    pub id: String,
+    app_model: Rc<AppModel>,
+    dispatcher: Box<dyn ActionDispatcher>,
+}
+
+impl PlaylistCompareModel {
+    pub fn new(id: String, app_model: Rc<AppModel>, dispatcher: Box<dyn ActionDispatcher>) -> Self {
+        Self {
+            id,
+            app_model,
+            dispatcher,
+        }
+    }
+
+    pub fn playlist_title(&self, id: &str) -> String {
+        let state = self.app_model.get_state();
+        state
+            .browser
+            .playlist_details_state(id)
+            .and_then(|s| s.playlist.as_ref())
+            .map(|p| p.title.clone())
+            .or_else(|| {
+                state
+                    .logged_user
+                    .playlists
+                    .iter()
+                    .find(|p| p.id == id)
+                    .map(|p| p.title.clone())
+            })
+            .unwrap_or_default()
+    }
+
+    // What the playlist can be compared with: the user's own playlists
+    pub fn other_playlists(&self) -> Vec<PlaylistSummary> {
+        self.app_model
+            .get_state()
+            .logged_user
+            .playlists
+            .iter()
+            .filter(|p| p.id != self.id)
+            .cloned()
+            .collect()
+    }
+
+    // Every track of a playlist (not just those loaded so far), along with their ISRCs
+    pub async fn load_tracks(&self, id: &str) -> SpotifyResult<Vec<ComparedTrack>> {
+        let api = self.app_model.get_spotify();
+        let songs = fetch_playlist(api.clone(), id, usize::MAX)
+            .await?
+            .songs
+            .songs;
+        let ids = songs.iter().map(|s| s.id.clone()).collect();
+        let isrcs = api.get_tracks_isrc(ids).await?;
+        Ok(songs
+            .into_iter()
+            .zip(isrcs.into_iter().chain(iter::repeat(None)))
+            .map(|(song, isrc)| ComparedTrack { song, isrc })
+            .collect())
+    }
+
+    // Copies the tracks of one playlist that another doesn't have into a new playlist
+    pub fn copy_to_new_playlist(
+        &self,
+        playlist_id: &str,
+        other_id: &str,
+        songs: Vec<SongDescription>,
+    ) {
+        let name = labels::playlist_difference_name(
+            &self.playlist_title(playlist_id),
+            &self.playlist_title(other_id),
+        );
+        let uris = songs.into_iter().map(|s| s.uri).collect();
+        create_playlist_with_songs(name, uris, &self.app_model, self.dispatcher.as_ref());
+    }
+}
@@ -8,10 +8,11 @@ use super::playlist_headerbar::PlaylistHeaderBarWidget;
 use super::PlaylistDetailsModel;
 
 use crate::app::components::{
-    Component, EventListener, Playlist, PlaylistModel, ScrollingHeaderWidget,
+    display_add_artwork_color, Component, EventListener, Playlist, PlaylistCompare,
+    PlaylistCompareModel, PlaylistModel, ScrollingHeaderWidget, Shareable,
 };
 use crate::app::dispatch::Worker;
-use crate::app::loader::ImageLoader;
+use crate::app::loader::{ArtworkColor, ImageLoader};
 use crate::app::state::{PlaybackEvent, SelectionEvent};
 use crate::app::{AppEvent, BrowserEvent};
 use libadwaita::subclass::prelude::BinImpl;
@@ -29,6 +30,9 @@ mod imp {
         #[template_child]
         pub scrolling_header: TemplateChild<ScrollingHeaderWidget>,
 
+        #[template_child]
+        pub header_clamp: TemplateChild<libadwaita::Clamp>,
+
         #[template_child]
         pub header_widget: TemplateChild<PlaylistHeaderWidget>,
 
@@ -37,6 +41,12 @@ mod imp {
 
         #[template_child]
         pub tracks: TemplateChild<gtk::ListView>,
+
+        #[template_child]
+        pub filter_bar: TemplateChild<gtk::SearchBar>,
+
+        #[template_child]
+        pub filter_entry: TemplateChild<gtk::SearchEntry>,
     }
 
     #[glib::object_subclass]
@@ -84,6 +94,14 @@ impl PlaylistDetailsWidget {
         self.imp().tracks.as_ref()
     }
 
+    fn filter_bar(&self) -> &gtk::SearchBar {
+        self.imp().filter_bar.as_ref()
+    }
+
+    fn filter_entry(&self) -> &gtk::SearchEntry {
+        self.imp().filter_entry.as_ref()
+    }
+
     fn connect_bottom_edge<F>(&self, f: F)
     where
         F: Fn() + 'static,
@@ -126,9 +144,13 @@ impl PlaylistDetailsWidget {
         self.imp().headerbar.set_editable(editing);
     }
 
-    fn set_info(&self, playlist: &str, owner: &str) {
-        self.imp().header_widget.set_info(playlist, owner);
-        self.imp().header_mobile.set_info(playlist, owner);
+    fn set_info(&self, playlist: &str, owner: &str, track_count: usize) {
+        self.imp()
+            .header_widget
+            .set_info(playlist, owner, track_count);
+        self.imp()
+            .header_mobile
+            .set_info(playlist, owner, track_count);
         self.imp().headerbar.set_title(Some(playlist));
     }
 
@@ -142,6 +164,12 @@ impl PlaylistDetailsWidget {
         self.imp().header_mobile.set_artwork(art);
     }
 
+    fn set_artwork_color(&self, color: ArtworkColor) {
+        let class = display_add_artwork_color(color);
+        self.imp().header_clamp.add_css_class(&class);
+        self.imp().headerbar.add_classes(&[&class]);
+    }
+
     fn connect_owner_clicked<F>(&self, f: F)
     where
         F: Fn() + Clone + 'static,
@@ -157,6 +185,40 @@ impl PlaylistDetailsWidget {
         self.imp().headerbar.connect_edit(f);
     }
 
+    pub fn connect_open_in_web_player<F>(&self, f: F)
+    where
+        F: Fn() + 'static,
+    {
+        self.imp().headerbar.connect_open_in_web_player(f);
+    }
+
+    pub fn bind_share_menu<F>(&self, shareable: F)
+    where
+        F: Fn() -> Option<Shareable> + Clone + 'static,
+    {
+        self.imp().headerbar.bind_share_menu(shareable);
+    }
+
+    pub fn connect_compare_menu<F>(&self, f: F)
+    where
+        F: Fn(&gio::Menu) + 'static,
+    {
+        self.imp().headerbar.connect_compare_menu(f);
+    }
+
+    // Called with the window to show the comparison over, and the id of the other playlist
+    pub fn connect_compare<F>(&self, f: F)
+    where
+        F: Fn(Option<gtk::Window>, String) + 'static,
+    {
+        self.imp()
+            .headerbar
+            .connect_compare(clone!(@weak self as _self => move |id| {
+                let parent = _self.root().and_then(|root| root.downcast::<gtk::Window>().ok());
+                f(parent, id);
+            }));
+    }
+
     pub fn connect_cancel<F>(&self, f: F)
     where
         F: Fn() + 'static,
@@ -178,6 +240,14 @@ impl PlaylistDetailsWidget {
         self.imp().header_mobile.connect_play(f);
     }
 
+    pub fn connect_copy_track_list<F>(&self, f: F)
+    where
+        F: Fn() + Clone + 'static,
+    {
+        self.imp().header_widget.connect_copy_track_list(f.clone());
+        self.imp().header_mobile.connect_copy_track_list(f);
+    }
+
     pub fn connect_done<F>(&self, f: F)
     where
         F: Fn(String) + 'static,
@@ -206,17 +276,22 @@ pub struct PlaylistDetails {
 }
 
 impl PlaylistDetails {
-    pub fn new(model: Rc<PlaylistDetailsModel>, worker: Worker) -> Self {
+    pub fn new(
+        model: Rc<PlaylistDetailsModel>,
+        compare_model: PlaylistCompareModel,
+        worker: Worker,
+    ) -> Self {
         if model.get_playlist_info().is_none() {
             model.load_playlist_info();
         }
 
         let widget = PlaylistDetailsWidget::new();
-        let playlist = Box::new(Playlist::new(
+        let playlist = Playlist::new(
             widget.playlist_tracks_widget().clone(),
             model.clone(),
             worker.clone(),
-        ));
+        );
+        playlist.bind_filter_bar(&widget, widget.filter_bar(), widget.filter_entry());
 
         widget.set_editable(model.is_playlist_editable());
 
@@ -240,13 +315,28 @@ impl PlaylistDetails {
 
         widget.connect_play(clone!(@weak model => move || model.toggle_play_playlist()));
 
+        widget.connect_copy_track_list(clone!(@weak model => move || model.copy_track_list()));
+
+        widget
+            .connect_open_in_web_player(clone!(@weak model => move || model.open_in_web_player()));
+
         widget.connect_go_back(clone!(@weak model => move || model.go_back()));
 
+        widget.bind_share_menu(
+            clone!(@weak model => @default-return None, move || model.shareable()),
+        );
+
+        let compare = Rc::new(PlaylistCompare::new(compare_model, worker.clone()));
+        widget.connect_compare_menu(clone!(@strong compare => move |menu| compare.fill_menu(menu)));
+        widget.connect_compare(clone!(@strong compare => move |parent, id| {
+            compare.compare_with(parent.as_ref(), id);
+        }));
+
         Self {
             model,
             worker,
             widget,
-            children: vec![playlist],
+            children: vec![Box::new(playlist)],
         }
     }
 
@@ -254,19 +344,28 @@ impl PlaylistDetails {
         if let Some(info) = self.model.get_playlist_info() {
             let title = &info.title[..];
             let owner = &info.owner.display_name[..];
-            let art_url = info.art.as_ref();
+            let art_url = info.art.clone();
+            let mosaic = info.mosaic.clone();
 
-            self.widget.set_info(title, owner);
+            self.widget.set_info(title, owner, info.songs.batch.total);
 
-            if let Some(art_url) = art_url.cloned() {
+            if art_url.is_some() || !mosaic.is_empty() {
+                let loader = ImageLoader::for_widget(&self.widget);
                 let widget = self.widget.downgrade();
                 self.worker.send_local_task(async move {
-                    let pixbuf = ImageLoader::new()
-                        .load_remote(&art_url[..], "jpg", 320, 320)
-                        .await;
+                    let (pixbuf, key) = match art_url {
+                        Some(art_url) => (
+                            loader.load_remote(&art_url[..], "jpg", 320, 320).await,
+                            art_url,
+                        ),
+                        None => (loader.load_mosaic(&mosaic, 320).await, mosaic.join(" ")),
+                    };
                     if let (Some(widget), Some(ref pixbuf)) = (widget.upgrade(), pixbuf) {
                         widget.set_artwork(pixbuf);
                         widget.set_loaded();
+                        if let Some(color) = loader.load_artwork_color(&key, pixbuf).await {
+                            widget.set_artwork_color(color);
+                        }
                     }
                 });
             } else {
@@ -323,4 +422,12 @@ impl EventListener for PlaylistDetails {
         }
         self.broadcast_event(event);
     }
+
+    fn on_mount(&mut self) {
+        self.broadcast_mount();
+    }
+
+    fn on_unmount(&mut self) {
+        self.broadcast_unmount();
+    }
 }
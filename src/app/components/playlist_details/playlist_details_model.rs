@@ -5,12 +5,12 @@ use std::ops::Deref;
 use std::rc::Rc;
 
 use crate::api::SpotifyApiError;
-use crate::app::components::{labels, PlaylistModel};
+use crate::app::components::{copy_track_list, labels, utils, PlaylistModel, Shareable};
 use crate::app::models::*;
 use crate::app::state::SelectionContext;
 use crate::app::state::{BrowserAction, PlaybackAction, SelectionAction, SelectionState};
 use crate::app::AppState;
-use crate::app::{ActionDispatcher, AppAction, AppModel, BatchQuery, SongsSource};
+use crate::app::{ActionDispatcher, AppAction, AppModel, BatchQuery, SongsSource, SpotifyUri};
 
 pub struct PlaylistDetailsModel {
     pub id: String,
@@ -118,6 +118,28 @@ impl PlaylistDetailsModel {
         Some(())
     }
 
+    pub fn copy_track_list(&self) {
+        copy_track_list(
+            SongsSource::Playlist(self.id.clone()),
+            &self.song_list_model(),
+            &self.app_model,
+            self.dispatcher.as_ref(),
+        );
+    }
+
+    pub fn open_in_web_player(&self) {
+        utils::open_in_browser(&utils::web_player_url("playlist", &self.id));
+    }
+
+    pub fn shareable(&self) -> Option<Shareable> {
+        let playlist = self.get_playlist_info()?;
+        Some(Shareable::new(
+            SpotifyUri::Playlist(self.id.clone()),
+            playlist.title.clone(),
+            Some(playlist.owner.display_name.clone()),
+        ))
+    }
+
     pub fn update_playlist_details(&self, title: String) {
         let api = self.app_model.get_spotify();
         let id = self.id.clone();
@@ -150,6 +172,14 @@ impl PlaylistDetailsModel {
 }
 
 impl PlaylistModel for PlaylistDetailsModel {
+    fn preview_on_hover(&self) -> bool {
+        self.app_model
+            .get_state()
+            .settings
+            .settings
+            .preview_on_hover
+    }
+
     fn song_list_model(&self) -> SongListModel {
         self.state()
             .browser
@@ -178,6 +208,13 @@ impl PlaylistModel for PlaylistDetailsModel {
         }
     }
 
+    fn play_filtered_songs(&self, songs: Vec<SongDescription>, id: &str) {
+        self.dispatcher
+            .dispatch(PlaybackAction::LoadSongs(songs).into());
+        self.dispatcher
+            .dispatch(PlaybackAction::Load(id.to_string()).into());
+    }
+
     fn actions_for(&self, id: &str) -> Option<gio::ActionGroup> {
         let song = self.song_list_model().get(id)?;
         let song = song.description();
@@ -189,6 +226,12 @@ impl PlaylistModel for PlaylistDetailsModel {
         }
         group.add_action(&song.make_album_action(self.dispatcher.box_clone(), None));
         group.add_action(&song.make_link_action(None));
+        group.add_action(&song.make_web_player_action(None));
+        group.add_action(&song.make_save_action(
+            Rc::clone(&self.app_model),
+            self.dispatcher.box_clone(),
+            None,
+        ));
         group.add_action(&song.make_queue_action(self.dispatcher.box_clone(), None));
 
         Some(group.upcast())
@@ -208,6 +251,10 @@ impl PlaylistModel for PlaylistDetailsModel {
         }
 
         menu.append(Some(&*labels::COPY_LINK), Some("song.copy_link"));
+        menu.append(
+            Some(&*labels::OPEN_IN_WEB_PLAYER),
+            Some("song.open_in_web_player"),
+        );
         menu.append(Some(&*labels::ADD_TO_QUEUE), Some("song.queue"));
 
         Some(menu.upcast())
@@ -236,6 +283,11 @@ impl PlaylistModel for PlaylistDetailsModel {
         true
     }
 
+    fn is_saved(&self, id: &str) -> Option<bool> {
+        let is_saved = self.app_model.get_state().browser.is_track_saved(id);
+        Some(is_saved.unwrap_or(false))
+    }
+
     fn selection(&self) -> Option<Box<dyn Deref<Target = SelectionState> + '_>> {
         Some(Box::new(self.app_model.map_state(|s| &s.selection)))
     }
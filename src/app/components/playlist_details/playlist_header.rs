@@ -1,4 +1,4 @@
-use crate::app::components::display_add_css_provider;
+use crate::app::components::{display, display_add_css_provider};
 use gettextrs::gettext;
 use gtk::prelude::*;
 use gtk::subclass::prelude::*;
@@ -35,9 +35,15 @@ mod imp {
         #[template_child]
         pub author_button_label: TemplateChild<gtk::Label>,
 
+        #[template_child]
+        pub track_count_label: TemplateChild<gtk::Label>,
+
         #[template_child]
         pub play_button: TemplateChild<gtk::Button>,
 
+        #[template_child]
+        pub copy_button: TemplateChild<gtk::Button>,
+
         #[property(get, set, name = "original-entry-text")]
         pub original_entry_text: RefCell<String>,
     }
@@ -106,6 +112,13 @@ impl PlaylistHeaderWidget {
         self.imp().play_button.connect_clicked(move |_| f());
     }
 
+    pub fn connect_copy_track_list<F>(&self, f: F)
+    where
+        F: Fn() + 'static,
+    {
+        self.imp().copy_button.connect_clicked(move |_| f());
+    }
+
     pub fn reset_playlist_name(&self) {
         self.imp()
             .playlist_label_entry
@@ -120,7 +133,7 @@ impl PlaylistHeaderWidget {
         self.imp().playlist_art.set_from_pixbuf(Some(art));
     }
 
-    pub fn set_info(&self, playlist: &str, owner: &str) {
+    pub fn set_info(&self, playlist: &str, owner: &str, track_count: usize) {
         let widget = self.imp();
         self.set_original_entry_text(playlist);
         widget.playlist_label_entry.set_text(playlist);
@@ -128,6 +141,9 @@ impl PlaylistHeaderWidget {
             .playlist_label_entry
             .set_placeholder_text(Some(playlist));
         widget.author_button_label.set_label(owner);
+        widget
+            .track_count_label
+            .set_label(&display::n_tracks_label(track_count));
     }
 
     pub fn set_playing(&self, is_playing: bool) {
@@ -156,6 +172,8 @@ impl PlaylistHeaderWidget {
         widget.playlist_image_box.set_margin_start(0);
         widget.playlist_label_entry.set_xalign(0.5);
         widget.author_button.set_halign(gtk::Align::Center);
+        widget.track_count_label.set_halign(gtk::Align::Center);
+        widget.track_count_label.set_margin_start(0);
     }
 
     pub fn set_editing(&self, editing: bool) {
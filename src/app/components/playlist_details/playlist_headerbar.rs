@@ -1,8 +1,11 @@
+use gio::prelude::*;
 use gtk::prelude::*;
 use gtk::subclass::prelude::*;
 use gtk::CompositeTemplate;
 use libadwaita::subclass::prelude::BinImpl;
 
+use crate::app::components::{bind_share_menu, Shareable};
+
 mod imp {
 
     use super::*;
@@ -25,6 +28,18 @@ mod imp {
         #[template_child]
         pub edit: TemplateChild<gtk::Button>,
 
+        #[template_child]
+        pub compare: TemplateChild<gtk::MenuButton>,
+
+        #[template_child]
+        pub compare_menu: TemplateChild<gio::Menu>,
+
+        #[template_child]
+        pub open_in_web_player: TemplateChild<gtk::Button>,
+
+        #[template_child]
+        pub share: TemplateChild<gtk::MenuButton>,
+
         #[template_child]
         pub ok: TemplateChild<gtk::Button>,
 
@@ -85,6 +100,50 @@ impl PlaylistHeaderBarWidget {
         self.imp().edit.connect_clicked(move |_| f());
     }
 
+    pub fn connect_open_in_web_player<F>(&self, f: F)
+    where
+        F: Fn() + 'static,
+    {
+        self.imp().open_in_web_player.connect_clicked(move |_| f());
+    }
+
+    pub fn bind_share_menu<F>(&self, shareable: F)
+    where
+        F: Fn() -> Option<Shareable> + Clone + 'static,
+    {
+        bind_share_menu(&self.imp().share, shareable);
+    }
+
+    // The menu is filled again every time it's opened, as playlists come and go
+    pub fn connect_compare_menu<F>(&self, f: F)
+    where
+        F: Fn(&gio::Menu) + 'static,
+    {
+        let menu = self.imp().compare_menu.clone();
+        self.imp().compare.connect_active_notify(move |button| {
+            if button.is_active() {
+                menu.remove_all();
+                f(&menu);
+            }
+        });
+    }
+
+    // Called with the id of the playlist picked in the menu
+    pub fn connect_compare<F>(&self, f: F)
+    where
+        F: Fn(String) + 'static,
+    {
+        let compare_with = gio::SimpleAction::new("with", Some(glib::VariantTy::STRING));
+        compare_with.connect_activate(move |_, id| {
+            if let Some(id) = id.and_then(|id| id.get::<String>()) {
+                f(id);
+            }
+        });
+        let actions = gio::SimpleActionGroup::new();
+        actions.add_action(&compare_with);
+        self.insert_action_group("compare", Some(&actions));
+    }
+
     pub fn connect_ok<F>(&self, f: F)
     where
         F: Fn() + 'static,
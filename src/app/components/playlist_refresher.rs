@@ -0,0 +1,141 @@
+use std::rc::Rc;
+use std::sync::Arc;
+
+use crate::api::{SpotifyApiClient, SpotifyResult};
+use crate::app::components::utils::Clock;
+use crate::app::components::EventListener;
+use crate::app::models::PlaylistDescription;
+use crate::app::state::{LoginEvent, SettingsEvent};
+use crate::app::{ActionDispatcher, AppEvent, AppModel, BrowserAction};
+
+// Periodically checks whether the playlists opened in the app were modified elsewhere
+// (by a collaborator, or from another client), and reloads those that were.
+// A playlist's snapshot id changes every time it is modified, so we only need to fetch that to know.
+pub struct PlaylistRefresher {
+    app_model: Rc<AppModel>,
+    dispatcher: Box<dyn ActionDispatcher>,
+    clock: Option<Clock>,
+    logged_in: bool,
+}
+
+impl PlaylistRefresher {
+    pub fn new(app_model: Rc<AppModel>, dispatcher: Box<dyn ActionDispatcher>) -> Self {
+        Self {
+            app_model,
+            dispatcher,
+            clock: None,
+            logged_in: false,
+        }
+    }
+
+    fn start(&mut self, interval_secs: u32) {
+        self.stop();
+        if interval_secs == 0 {
+            return;
+        }
+
+        let clock = Clock::new(interval_secs.saturating_mul(1000));
+        let app_model = Rc::clone(&self.app_model);
+        let dispatcher = self.dispatcher.box_clone();
+        clock.start(move || Self::check_playlists(&app_model, dispatcher.as_ref()));
+        self.clock = Some(clock);
+    }
+
+    fn stop(&mut self) {
+        if let Some(clock) = self.clock.take() {
+            clock.stop();
+        }
+    }
+
+    fn check_playlists(app_model: &AppModel, dispatcher: &dyn ActionDispatcher) {
+        let state = app_model.get_state();
+        if !state.online {
+            return;
+        }
+        let watched = state
+            .browser
+            .opened_playlists()
+            .into_iter()
+            .filter_map(|id| {
+                let snapshot_id = state.browser.playlist_snapshot(&id)?.to_owned();
+                let loaded = state
+                    .browser
+                    .playlist_details_state(&id)
+                    .map(|s| s.songs.partial_len())
+                    .unwrap_or(0);
+                Some((id, snapshot_id, loaded))
+            });
+
+        for (id, snapshot_id, loaded) in watched {
+            let api = app_model.get_spotify();
+            dispatcher.dispatch_async(Box::pin(async move {
+                let latest_snapshot_id = api
+                    .get_playlist_snapshot_id(&id)
+                    .await
+                    .map_err(|e| warn!("Could not check playlist {}: {}", id, e))
+                    .ok()?;
+                if latest_snapshot_id == snapshot_id {
+                    return None;
+                }
+
+                debug!("Playlist {} changed, refreshing", id);
+                let playlist = fetch_playlist(api, &id, loaded)
+                    .await
+                    .map_err(|e| warn!("Could not refresh playlist {}: {}", id, e))
+                    .ok()?;
+
+                Some(BrowserAction::RefreshPlaylist(Box::new(playlist)).into())
+            }));
+        }
+    }
+}
+
+// Fetches a playlist again, with as many tracks as were loaded so that only what actually changed is updated
+pub async fn fetch_playlist(
+    api: Arc<dyn SpotifyApiClient + Send + Sync>,
+    id: &str,
+    loaded: usize,
+) -> SpotifyResult<PlaylistDescription> {
+    let mut playlist = api.refresh_playlist(id).await?;
+    let mut batch = playlist.songs.batch;
+    while batch.offset + batch.batch_size < usize::min(loaded, batch.total) {
+        let offset = batch.offset + batch.batch_size;
+        let mut more = api
+            .get_playlist_tracks(id, offset, batch.batch_size)
+            .await?;
+        playlist.songs.songs.append(&mut more.songs);
+        batch = more.batch;
+    }
+    Ok(playlist)
+}
+
+impl EventListener for PlaylistRefresher {
+    fn on_event(&mut self, event: &AppEvent) {
+        match event {
+            AppEvent::LoginEvent(LoginEvent::LoginCompleted(_)) => {
+                let interval = self
+                    .app_model
+                    .get_state()
+                    .settings
+                    .settings
+                    .playlist_refresh_interval;
+                self.logged_in = true;
+                self.start(interval);
+            }
+            AppEvent::LoginEvent(LoginEvent::LogoutCompleted) => {
+                self.logged_in = false;
+                self.stop();
+            }
+            AppEvent::SettingsEvent(SettingsEvent::PlaylistRefreshIntervalChanged(interval))
+                if self.logged_in =>
+            {
+                self.start(*interval);
+            }
+            // Catching up on what changed in the meantime
+            AppEvent::ConnectivityChanged(true) if self.clock.is_some() => {
+                Self::check_playlists(&self.app_model, self.dispatcher.as_ref())
+            }
+            _ => {}
+        }
+    }
+}
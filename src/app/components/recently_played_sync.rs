@@ -0,0 +1,42 @@
+use std::rc::Rc;
+
+use crate::app::components::EventListener;
+use crate::app::state::{LoginEvent, PlaybackAction};
+use crate::app::{ActionDispatcher, AppEvent, AppModel};
+
+// The most the API will return
+const RECENTLY_PLAYED_LIMIT: usize = 50;
+
+// Lets the state know what the user played before this session (on any device), so that
+// shuffling can play those tracks last (see ShuffleMode::LeastRecent)
+pub struct RecentlyPlayedSync {
+    app_model: Rc<AppModel>,
+    dispatcher: Box<dyn ActionDispatcher>,
+}
+
+impl RecentlyPlayedSync {
+    pub fn new(app_model: Rc<AppModel>, dispatcher: Box<dyn ActionDispatcher>) -> Self {
+        Self {
+            app_model,
+            dispatcher,
+        }
+    }
+
+    fn sync(&self) {
+        let api = self.app_model.get_spotify();
+        self.dispatcher
+            .call_spotify_and_dispatch(move || async move {
+                api.get_recently_played(RECENTLY_PLAYED_LIMIT)
+                    .await
+                    .map(|ids| PlaybackAction::SetRecentlyPlayed(ids).into())
+            });
+    }
+}
+
+impl EventListener for RecentlyPlayedSync {
+    fn on_event(&mut self, event: &AppEvent) {
+        if let AppEvent::LoginEvent(LoginEvent::LoginCompleted(_)) = event {
+            self.sync();
+        }
+    }
+}
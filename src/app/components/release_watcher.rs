@@ -0,0 +1,185 @@
+use gio::prelude::*;
+use std::cell::Cell;
+use std::rc::Rc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::app::components::{labels, EventListener};
+use crate::app::models::AlbumDescription;
+use crate::app::state::LoginEvent;
+use crate::app::{ActionDispatcher, AppAction, AppEvent, AppModel, Worker};
+
+// Spot can stay open for days: every so often, we see if a check is due
+const DUE_CHECK_INTERVAL_SECS: u32 = 60 * 60;
+// Releases come sorted by type (albums, then singles...), we look at enough of them to find the latest
+const RELEASES_LIMIT: usize = 50;
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+struct Checker {
+    app_model: Rc<AppModel>,
+    dispatcher: Box<dyn ActionDispatcher>,
+    worker: Worker,
+    checking: Cell<bool>,
+}
+
+impl Checker {
+    // Fetches the releases of these artists one after the other, unless we already are
+    fn check(self: &Rc<Self>, artist_ids: Vec<String>, is_full_check: bool) {
+        if artist_ids.is_empty() || self.checking.get() {
+            return;
+        }
+        self.checking.set(true);
+
+        let checker = Rc::clone(self);
+        let api = self.app_model.get_spotify();
+        self.worker.send_local_task(async move {
+            for id in artist_ids {
+                match api.get_artist_albums(&id, 0, RELEASES_LIMIT).await {
+                    Ok(albums) => checker
+                        .dispatcher
+                        .dispatch(AppAction::RecordReleases(id, albums)),
+                    Err(e) => warn!("Couldn't check releases of {}: {:?}", id, e),
+                }
+            }
+            if is_full_check {
+                checker
+                    .dispatcher
+                    .dispatch(AppAction::SetReleasesChecked(now()));
+            }
+            checker.checking.set(false);
+        });
+    }
+
+    fn check_if_due(self: &Rc<Self>) {
+        let state = self.app_model.get_state();
+        if state.logged_user.user.is_none()
+            || !state.online
+            || !state.release_alerts.is_check_due(now())
+        {
+            return;
+        }
+        let artists = state.release_alerts.artists();
+        drop(state);
+        self.check(artists, true);
+    }
+}
+
+// Looks for new releases of the artists the user subscribed to (from their page), once a week.
+// New releases trigger a desktop notification, and a badge on the artist page until it's visited.
+pub struct ReleaseWatcher {
+    app_model: Rc<AppModel>,
+    checker: Rc<Checker>,
+    timeout: Option<glib::SourceId>,
+}
+
+impl ReleaseWatcher {
+    pub fn new(
+        app_model: Rc<AppModel>,
+        dispatcher: Box<dyn ActionDispatcher>,
+        worker: Worker,
+    ) -> Self {
+        let checker = Rc::new(Checker {
+            app_model: Rc::clone(&app_model),
+            dispatcher,
+            worker,
+            checking: Cell::new(false),
+        });
+        Self {
+            app_model,
+            checker,
+            timeout: None,
+        }
+    }
+
+    fn start(&mut self) {
+        self.checker.check_if_due();
+        if self.timeout.is_some() {
+            return;
+        }
+        let checker = Rc::clone(&self.checker);
+        self.timeout = Some(glib::timeout_add_seconds_local(
+            DUE_CHECK_INTERVAL_SECS,
+            move || {
+                checker.check_if_due();
+                glib::Continue(true)
+            },
+        ));
+    }
+
+    // Right after subscribing, so that we know where the artist is at
+    fn check_new_subscription(&self, artist_id: &str) {
+        let state = self.app_model.get_state();
+        if state.release_alerts.is_subscribed(artist_id)
+            && state.release_alerts.known_latest(artist_id).is_none()
+        {
+            drop(state);
+            self.checker.check(vec![artist_id.to_string()], false);
+        }
+    }
+
+    fn save(&self) {
+        self.app_model.get_state().release_alerts.save();
+    }
+
+    fn notify(artist_id: &str, releases: &[AlbumDescription]) {
+        let newest = match releases
+            .iter()
+            .max_by(|a, b| a.release_date.cmp(&b.release_date))
+        {
+            Some(newest) => newest,
+            None => return,
+        };
+        let artist_name = newest
+            .artists
+            .iter()
+            .find(|a| a.id == artist_id)
+            .or_else(|| newest.artists.first())
+            .map(|a| a.name.as_str())
+            .unwrap_or_default();
+
+        let notification = gio::Notification::new(&labels::new_release_title(artist_name));
+        let body = releases
+            .iter()
+            .map(|a| a.title.as_str())
+            .collect::<Vec<_>>()
+            .join(", ");
+        notification.set_body(Some(&body));
+        if releases.len() == 1 {
+            notification.set_default_action_and_target_value(
+                "app.open_album",
+                Some(&newest.id.to_variant()),
+            );
+        } else {
+            notification.set_default_action_and_target_value(
+                "app.open_artist",
+                Some(&artist_id.to_variant()),
+            );
+        }
+
+        if let Some(app) = gio::Application::default() {
+            // One notification per artist, a newer one replaces it
+            app.send_notification(Some(&format!("new-releases-{artist_id}")), &notification);
+        }
+    }
+}
+
+impl EventListener for ReleaseWatcher {
+    fn on_event(&mut self, event: &AppEvent) {
+        match event {
+            AppEvent::LoginEvent(LoginEvent::LoginCompleted(_)) => self.start(),
+            AppEvent::ReleaseAlertChanged(id) => {
+                self.save();
+                self.check_new_subscription(id);
+            }
+            AppEvent::NewReleasesFound(id, releases) => Self::notify(id, releases),
+            AppEvent::ConnectivityChanged(true) => self.checker.check_if_due(),
+            AppEvent::ReleaseAlertsUpdated => self.save(),
+            _ => {}
+        }
+    }
+}
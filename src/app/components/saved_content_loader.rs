@@ -0,0 +1,31 @@
+use std::rc::Rc;
+
+use crate::app::components::{EventListener, SavedPlaylistsModel, SavedTracksModel};
+use crate::app::state::LoginEvent;
+use crate::app::{ActionDispatcher, AppEvent, AppModel};
+
+// Loads the first page of the user's saved tracks and playlists, as soon as we can.
+// Their screens are only made once shown, but the sidebar, the library index and the
+// sync of saved tracks need them from the start.
+pub struct SavedContentLoader {
+    saved_tracks: SavedTracksModel,
+    saved_playlists: SavedPlaylistsModel,
+}
+
+impl SavedContentLoader {
+    pub fn new(app_model: Rc<AppModel>, dispatcher: Box<dyn ActionDispatcher>) -> Self {
+        Self {
+            saved_tracks: SavedTracksModel::new(Rc::clone(&app_model), dispatcher.box_clone()),
+            saved_playlists: SavedPlaylistsModel::new(app_model, dispatcher),
+        }
+    }
+}
+
+impl EventListener for SavedContentLoader {
+    fn on_event(&mut self, event: &AppEvent) {
+        if let AppEvent::Started | AppEvent::LoginEvent(LoginEvent::LoginCompleted(_)) = event {
+            self.saved_tracks.load_initial();
+            let _ = self.saved_playlists.refresh_saved_playlists();
+        }
+    }
+}
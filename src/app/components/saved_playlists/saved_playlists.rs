@@ -4,11 +4,10 @@ use gtk::CompositeTemplate;
 use std::rc::Rc;
 
 use super::SavedPlaylistsModel;
-use crate::app::components::{AlbumWidget, Component, EventListener};
+use crate::app::components::{utils, AlbumWidget, Component, EventListener};
 use crate::app::dispatch::Worker;
 use crate::app::models::AlbumModel;
-use crate::app::state::LoginEvent;
-use crate::app::{AppEvent, BrowserEvent, ListStore};
+use crate::app::{AppEvent, BrowserEvent, ListStore, PREFETCH_ROWS};
 
 mod imp {
 
@@ -55,17 +54,16 @@ impl SavedPlaylistsWidget {
         glib::Object::new()
     }
 
-    fn connect_bottom_edge<F>(&self, f: F)
+    fn connect_prefetch<F>(&self, f: F)
     where
         F: Fn() + 'static,
     {
-        self.imp()
-            .scrolled_window
-            .connect_edge_reached(move |_, pos| {
-                if let gtk::PositionType::Bottom = pos {
-                    f()
-                }
-            });
+        utils::connect_prefetch(
+            &self.imp().scrolled_window,
+            &self.imp().flowbox,
+            PREFETCH_ROWS,
+            f,
+        );
     }
 
     fn bind_albums<F>(&self, worker: Worker, store: &ListStore<AlbumModel>, on_album_pressed: F)
@@ -97,6 +95,7 @@ pub struct SavedPlaylists {
     widget: SavedPlaylistsWidget,
     worker: Worker,
     model: Rc<SavedPlaylistsModel>,
+    bound: bool,
 }
 
 impl SavedPlaylists {
@@ -105,7 +104,7 @@ impl SavedPlaylists {
 
         let widget = SavedPlaylistsWidget::new();
 
-        widget.connect_bottom_edge(clone!(@weak model => move || {
+        widget.connect_prefetch(clone!(@weak model => move || {
             model.load_more_playlists();
         }));
 
@@ -113,6 +112,7 @@ impl SavedPlaylists {
             widget,
             worker,
             model,
+            bound: false,
         }
     }
 
@@ -130,14 +130,8 @@ impl SavedPlaylists {
 impl EventListener for SavedPlaylists {
     fn on_event(&mut self, event: &AppEvent) {
         match event {
-            AppEvent::Started => {
-                let _ = self.model.refresh_saved_playlists();
-                self.bind_flowbox();
-            }
-            AppEvent::LoginEvent(LoginEvent::LoginCompleted(_)) => {
-                let _ = self.model.refresh_saved_playlists();
-            }
             AppEvent::BrowserEvent(BrowserEvent::SavedPlaylistsUpdated) => {
+                self.model.pages_added();
                 self.widget
                     .get_status_page()
                     .set_visible(!self.model.has_playlists());
@@ -145,6 +139,14 @@ impl EventListener for SavedPlaylists {
             _ => {}
         }
     }
+
+    // Playlists are loaded from the start (see SavedContentLoader), but only shown once the page is
+    fn on_mount(&mut self) {
+        if !self.bound {
+            self.bound = true;
+            self.bind_flowbox();
+        }
+    }
 }
 
 impl Component for SavedPlaylists {
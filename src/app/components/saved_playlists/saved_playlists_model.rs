@@ -4,11 +4,12 @@ use std::rc::Rc;
 
 use crate::app::models::*;
 use crate::app::state::HomeState;
-use crate::app::{ActionDispatcher, AppAction, AppModel, BrowserAction, ListStore};
+use crate::app::{ActionDispatcher, AppAction, AppModel, BrowserAction, ListStore, Prefetcher};
 
 pub struct SavedPlaylistsModel {
     app_model: Rc<AppModel>,
     dispatcher: Box<dyn ActionDispatcher>,
+    prefetcher: Prefetcher,
 }
 
 impl SavedPlaylistsModel {
@@ -16,6 +17,7 @@ impl SavedPlaylistsModel {
         Self {
             app_model,
             dispatcher,
+            prefetcher: Prefetcher::new(),
         }
     }
 
@@ -47,6 +49,11 @@ impl SavedPlaylistsModel {
             .unwrap_or(false)
     }
 
+    // The list was updated, with the pages fetched so far
+    pub fn pages_added(&self) {
+        self.prefetcher.release_fetched();
+    }
+
     pub fn load_more_playlists(&self) -> Option<()> {
         let api = self.app_model.get_spotify();
 
@@ -55,7 +62,7 @@ impl SavedPlaylistsModel {
         let offset = next_page.next_offset?;
 
         self.dispatcher
-            .call_spotify_and_dispatch(move || async move {
+            .call_spotify_and_prefetch(&self.prefetcher, offset, move || async move {
                 api.get_saved_playlists(offset, batch_size)
                     .await
                     .map(|playlists| BrowserAction::AppendPlaylistsContent(playlists).into())
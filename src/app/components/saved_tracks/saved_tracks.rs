@@ -5,7 +5,6 @@ use std::rc::Rc;
 
 use super::SavedTracksModel;
 use crate::app::components::{Component, EventListener, Playlist};
-use crate::app::state::LoginEvent;
 use crate::app::{AppEvent, Worker};
 use libadwaita::subclass::prelude::BinImpl;
 
@@ -21,6 +20,12 @@ mod imp {
 
         #[template_child]
         pub scrolled_window: TemplateChild<gtk::ScrolledWindow>,
+
+        #[template_child]
+        pub filter_bar: TemplateChild<gtk::SearchBar>,
+
+        #[template_child]
+        pub filter_entry: TemplateChild<gtk::SearchEntry>,
     }
 
     #[glib::object_subclass]
@@ -68,11 +73,18 @@ impl SavedTracksWidget {
     fn song_list_widget(&self) -> &gtk::ListView {
         self.imp().song_list.as_ref()
     }
+
+    fn filter_bar(&self) -> &gtk::SearchBar {
+        self.imp().filter_bar.as_ref()
+    }
+
+    fn filter_entry(&self) -> &gtk::SearchEntry {
+        self.imp().filter_entry.as_ref()
+    }
 }
 
 pub struct SavedTracks {
     widget: SavedTracksWidget,
-    model: Rc<SavedTracksModel>,
     children: Vec<Box<dyn EventListener>>,
 }
 
@@ -85,10 +97,10 @@ impl SavedTracks {
         }));
 
         let playlist = Playlist::new(widget.song_list_widget().clone(), model.clone(), worker);
+        playlist.bind_filter_bar(&widget, widget.filter_bar(), widget.filter_entry());
 
         Self {
             widget,
-            model,
             children: vec![Box::new(playlist)],
         }
     }
@@ -106,12 +118,14 @@ impl Component for SavedTracks {
 
 impl EventListener for SavedTracks {
     fn on_event(&mut self, event: &AppEvent) {
-        match event {
-            AppEvent::Started | AppEvent::LoginEvent(LoginEvent::LoginCompleted(_)) => {
-                self.model.load_initial();
-            }
-            _ => {}
-        }
         self.broadcast_event(event);
     }
+
+    fn on_mount(&mut self) {
+        self.broadcast_mount();
+    }
+
+    fn on_unmount(&mut self) {
+        self.broadcast_unmount();
+    }
 }
@@ -56,6 +56,14 @@ impl SavedTracksModel {
 }
 
 impl PlaylistModel for SavedTracksModel {
+    fn preview_on_hover(&self) -> bool {
+        self.app_model
+            .get_state()
+            .settings
+            .settings
+            .preview_on_hover
+    }
+
     fn song_list_model(&self) -> SongListModel {
         self.app_model
             .get_state()
@@ -84,6 +92,14 @@ impl PlaylistModel for SavedTracksModel {
                 .dispatch(PlaybackAction::Load(id.to_string()).into());
         }
     }
+
+    fn play_filtered_songs(&self, songs: Vec<SongDescription>, id: &str) {
+        self.dispatcher
+            .dispatch(PlaybackAction::LoadSongs(songs).into());
+        self.dispatcher
+            .dispatch(PlaybackAction::Load(id.to_string()).into());
+    }
+
     fn autoscroll_to_playing(&self) -> bool {
         true
     }
@@ -99,6 +115,12 @@ impl PlaylistModel for SavedTracksModel {
         }
         group.add_action(&song.make_album_action(self.dispatcher.box_clone(), None));
         group.add_action(&song.make_link_action(None));
+        group.add_action(&song.make_web_player_action(None));
+        group.add_action(&song.make_save_action(
+            Rc::clone(&self.app_model),
+            self.dispatcher.box_clone(),
+            None,
+        ));
 
         Some(group.upcast())
     }
@@ -117,6 +139,10 @@ impl PlaylistModel for SavedTracksModel {
         }
 
         menu.append(Some(&*labels::COPY_LINK), Some("song.copy_link"));
+        menu.append(
+            Some(&*labels::OPEN_IN_WEB_PLAYER),
+            Some("song.open_in_web_player"),
+        );
 
         Some(menu.upcast())
     }
@@ -140,6 +166,11 @@ impl PlaylistModel for SavedTracksModel {
         true
     }
 
+    fn is_saved(&self, id: &str) -> Option<bool> {
+        let is_saved = self.app_model.get_state().browser.is_track_saved(id);
+        Some(is_saved.unwrap_or(false))
+    }
+
     fn selection(&self) -> Option<Box<dyn Deref<Target = SelectionState> + '_>> {
         let selection = self.app_model.map_state(|s| &s.selection);
         Some(Box::new(selection))
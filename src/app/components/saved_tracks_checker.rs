@@ -0,0 +1,92 @@
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::rc::Rc;
+
+use crate::app::components::utils::Debouncer;
+use crate::app::components::EventListener;
+use crate::app::models::SongListModel;
+use crate::app::state::PlaybackEvent;
+use crate::app::{ActionDispatcher, AppEvent, AppModel, AppState, BrowserAction, BrowserEvent};
+
+// Songs often arrive in several steps (a page, then the next...): wait a bit to check them all at once
+const CHECK_DELAY_MS: u32 = 300;
+
+// Finds out which of the songs we show are in the user's Liked Songs, so that every list
+// (and the playback bar) can tell (see BrowserState::is_track_saved).
+// Songs are checked once, in batches, as they are loaded; after that, saving or removing tracks
+// from within Spot keeps the state up to date.
+pub struct SavedTracksChecker {
+    app_model: Rc<AppModel>,
+    dispatcher: Box<dyn ActionDispatcher>,
+    pending: Rc<RefCell<HashSet<String>>>,
+    debouncer: Debouncer,
+}
+
+impl SavedTracksChecker {
+    pub fn new(app_model: Rc<AppModel>, dispatcher: Box<dyn ActionDispatcher>) -> Self {
+        Self {
+            app_model,
+            dispatcher,
+            pending: Default::default(),
+            debouncer: Debouncer::new(),
+        }
+    }
+
+    // The songs that were just loaded as a result of that event
+    fn loaded_songs<'a>(state: &'a AppState, event: &AppEvent) -> Option<&'a SongListModel> {
+        let browser = &state.browser;
+        match event {
+            AppEvent::BrowserEvent(
+                BrowserEvent::AlbumDetailsLoaded(id) | BrowserEvent::AlbumTracksAppended(id),
+            ) => browser.details_state(id).map(|s| &s.songs),
+            AppEvent::BrowserEvent(
+                BrowserEvent::PlaylistDetailsLoaded(id) | BrowserEvent::PlaylistTracksAppended(id),
+            ) => browser.playlist_details_state(id).map(|s| &s.songs),
+            AppEvent::BrowserEvent(BrowserEvent::ArtistDetailsUpdated(id)) => {
+                browser.artist_state(id).map(|s| &s.top_tracks)
+            }
+            AppEvent::PlaybackEvent(
+                PlaybackEvent::PlaylistChanged | PlaybackEvent::TrackChanged(_),
+            ) => Some(state.playback.songs()),
+            _ => None,
+        }
+    }
+
+    fn unknown_songs(&self, event: &AppEvent) -> Vec<String> {
+        let state = self.app_model.get_state();
+        Self::loaded_songs(&state, event)
+            .map(|songs| songs.map_collect(|song| song.id))
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|id| state.browser.is_track_saved(id).is_none())
+            .collect()
+    }
+
+    fn check_later(&self, ids: Vec<String>) {
+        if ids.is_empty() {
+            return;
+        }
+        self.pending.borrow_mut().extend(ids);
+
+        let pending = Rc::clone(&self.pending);
+        let api = self.app_model.get_spotify();
+        let dispatcher = self.dispatcher.box_clone();
+        self.debouncer.debounce(CHECK_DELAY_MS, move || {
+            let ids: Vec<String> = pending.borrow_mut().drain().collect();
+            let api = api.clone();
+            debug!("Checking if {} tracks are saved", ids.len());
+            dispatcher.call_spotify_and_dispatch(move || async move {
+                let saved = api.check_saved_tracks(ids.clone()).await?;
+                let states = ids.into_iter().zip(saved).collect();
+                Ok(BrowserAction::SetTracksSavedState(states).into())
+            });
+        });
+    }
+}
+
+impl EventListener for SavedTracksChecker {
+    fn on_event(&mut self, event: &AppEvent) {
+        let ids = self.unknown_songs(event);
+        self.check_later(ids);
+    }
+}
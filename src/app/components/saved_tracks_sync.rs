@@ -0,0 +1,113 @@
+use std::rc::Rc;
+
+use crate::app::components::utils::Clock;
+use crate::app::components::EventListener;
+use crate::app::models::Batch;
+use crate::app::state::LoginEvent;
+use crate::app::{ActionDispatcher, AppEvent, AppModel, BrowserAction, BrowserEvent, Prefetcher};
+
+// Delay between two pages, so that syncing a large library doesn't hammer the API
+const SYNC_INTERVAL_MS: u32 = 1500;
+
+// Loads the user's saved tracks page after page in the background, until we have all of them:
+// the Liked Songs screen (and the search of the library) then has the full list rather than its first page.
+// Pages go through the API cache like any other, so they're persisted on disk and reading them again
+// (on the next launch, or offline) is cheap.
+// Progress is whatever was already loaded, so the sync simply resumes where it left off if interrupted.
+pub struct SavedTracksSync {
+    app_model: Rc<AppModel>,
+    dispatcher: Box<dyn ActionDispatcher>,
+    prefetcher: Rc<Prefetcher>,
+    clock: Option<Clock>,
+}
+
+impl SavedTracksSync {
+    pub fn new(app_model: Rc<AppModel>, dispatcher: Box<dyn ActionDispatcher>) -> Self {
+        Self {
+            app_model,
+            dispatcher,
+            prefetcher: Rc::new(Prefetcher::new()),
+            clock: None,
+        }
+    }
+
+    fn start(&mut self) {
+        self.stop();
+        let clock = Clock::new(SYNC_INTERVAL_MS);
+        let app_model = Rc::clone(&self.app_model);
+        let dispatcher = self.dispatcher.box_clone();
+        let prefetcher = Rc::clone(&self.prefetcher);
+        clock.start(move || {
+            // Picks up where it left off once back online
+            if !app_model.get_state().online {
+                return;
+            }
+            if let Some(batch) = Self::next_batch(&app_model) {
+                Self::sync_batch(&app_model, dispatcher.as_ref(), &prefetcher, batch);
+            }
+        });
+        self.clock = Some(clock);
+    }
+
+    fn stop(&mut self) {
+        if let Some(clock) = self.clock.take() {
+            clock.stop();
+        }
+        self.prefetcher.cancel();
+    }
+
+    fn is_complete(&self) -> bool {
+        let state = self.app_model.get_state();
+        let last_batch = state
+            .browser
+            .home_state()
+            .and_then(|home| home.saved_tracks.last_batch());
+        matches!(last_batch, Some(batch) if batch.next().is_none())
+    }
+
+    // None if the first page isn't loaded yet, or if we already have everything
+    fn next_batch(app_model: &AppModel) -> Option<Batch> {
+        let state = app_model.get_state();
+        let saved_tracks = &state.browser.home_state()?.saved_tracks;
+        saved_tracks.last_batch()?.next()
+    }
+
+    fn sync_batch(
+        app_model: &AppModel,
+        dispatcher: &dyn ActionDispatcher,
+        prefetcher: &Prefetcher,
+        batch: Batch,
+    ) {
+        let api = app_model.get_spotify();
+        let Batch {
+            offset, batch_size, ..
+        } = batch;
+        let fetch = prefetcher.fetch(offset, async move {
+            debug!("Syncing saved tracks from {}", offset);
+            api.get_saved_tracks(offset, batch_size)
+                .await
+                .map(|song_batch| BrowserAction::AppendSavedTracks(Box::new(song_batch)).into())
+                .map_err(|e| warn!("Could not sync saved tracks: {}", e))
+        });
+        if let Some(fetch) = fetch {
+            dispatcher.dispatch_async(Box::pin(async move { fetch.await?.ok() }));
+        }
+    }
+}
+
+impl EventListener for SavedTracksSync {
+    fn on_event(&mut self, event: &AppEvent) {
+        match event {
+            AppEvent::LoginEvent(LoginEvent::LoginCompleted(_)) => self.start(),
+            AppEvent::LoginEvent(LoginEvent::LogoutCompleted) => self.stop(),
+            AppEvent::BrowserEvent(BrowserEvent::SavedTracksUpdated) => {
+                self.prefetcher.release_fetched();
+                if self.clock.is_some() && self.is_complete() {
+                    debug!("Saved tracks synced");
+                    self.stop();
+                }
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,102 @@
+use isahc::{AsyncReadResponseExt, Request};
+use serde_json::json;
+
+use super::spool::Listen;
+
+const SUBMIT_URL: &str = "https://api.listenbrainz.org/1/submit-listens";
+
+pub enum SubmitError {
+    // Worth trying again later (offline, server down...)
+    Unreachable,
+    // These listens will never be accepted
+    Rejected,
+    // Nothing will go through until the user fixes their token
+    InvalidToken,
+}
+
+// See https://listenbrainz.readthedocs.io/en/latest/users/api/core.html#post--1-submit-listens
+fn make_payload(listens: &[Listen]) -> serde_json::Value {
+    let payload: Vec<serde_json::Value> = listens
+        .iter()
+        .map(|listen| {
+            let mut additional_info = json!({
+                "duration_ms": listen.duration_ms,
+                "submission_client": "Spot",
+            });
+            if let Some(isrc) = listen.isrc.as_ref() {
+                additional_info["isrc"] = json!(isrc);
+            }
+            if let Some(mbid) = listen.recording_mbid.as_ref() {
+                additional_info["recording_mbid"] = json!(mbid);
+            }
+            json!({
+                "listened_at": listen.listened_at,
+                "track_metadata": {
+                    "artist_name": listen.artist,
+                    "track_name": listen.title,
+                    "release_name": listen.album,
+                    "additional_info": additional_info,
+                }
+            })
+        })
+        .collect();
+    json!({
+        "listen_type": if listens.len() == 1 { "single" } else { "import" },
+        "payload": payload,
+    })
+}
+
+pub async fn submit(token: &str, listens: &[Listen]) -> Result<(), SubmitError> {
+    let body = serde_json::to_vec(&make_payload(listens)).map_err(|_| SubmitError::Rejected)?;
+    let request = Request::post(SUBMIT_URL)
+        .header("Authorization", format!("Token {token}"))
+        .header("Content-Type", "application/json")
+        .body(body)
+        .map_err(|_| SubmitError::Rejected)?;
+    let mut response = isahc::send_async(request)
+        .await
+        .map_err(|_| SubmitError::Unreachable)?;
+
+    let status = response.status();
+    match status.as_u16() {
+        _ if status.is_success() => Ok(()),
+        401 => Err(SubmitError::InvalidToken),
+        400 => {
+            warn!(
+                "Listens rejected: {}",
+                response.text().await.unwrap_or_default()
+            );
+            Err(SubmitError::Rejected)
+        }
+        _ => Err(SubmitError::Unreachable),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_payload() {
+        let listen = Listen {
+            artist: "Artist".to_string(),
+            title: "Title".to_string(),
+            album: "Album".to_string(),
+            listened_at: 1000,
+            duration_ms: 180_000,
+            isrc: None,
+            recording_mbid: Some("mbid".to_string()),
+        };
+        let payload = make_payload(&[listen]);
+        assert_eq!(payload["listen_type"], "single");
+        assert_eq!(payload["payload"][0]["listened_at"], 1000);
+        assert_eq!(
+            payload["payload"][0]["track_metadata"]["track_name"],
+            "Title"
+        );
+        let additional_info = &payload["payload"][0]["track_metadata"]["additional_info"];
+        assert_eq!(additional_info["recording_mbid"], "mbid");
+        assert!(additional_info.get("isrc").is_none());
+    }
+}
@@ -0,0 +1,380 @@
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::app::components::EventListener;
+use crate::app::models::SongDescription;
+use crate::app::state::{LoginEvent, PlaybackEvent};
+use crate::app::{AppEvent, AppModel, SongsSource, Worker};
+use crate::settings::ScrobbleSettings;
+
+mod listenbrainz;
+mod musicbrainz;
+mod spool;
+
+use listenbrainz::SubmitError;
+use spool::Listen;
+pub use spool::ScrobbleSpool;
+
+// How many listens we submit at once when catching up
+const SUBMIT_BATCH_SIZE: usize = 50;
+// How often we try again when we couldn't submit
+const RETRY_INTERVAL_SECS: u32 = 300;
+
+// Tracks longer than 30 seconds, played for the share of their length set in the preferences, or for
+// long enough. By default, same rules as Last.fm: half their length or 4 minutes.
+fn should_scrobble(rules: &ScrobbleSettings, duration_ms: u32, played: Duration) -> bool {
+    let duration = Duration::from_millis(duration_ms.into());
+    let mut needed = duration * rules.threshold / 100;
+    if rules.max_minutes > 0 {
+        needed = needed.min(Duration::from_secs(u64::from(rules.max_minutes) * 60));
+    }
+    duration > Duration::from_secs(30) && played >= needed
+}
+
+// Listens never submitted, however long they were
+fn is_ignored(
+    rules: &ScrobbleSettings,
+    song: &SongDescription,
+    source: Option<&SongsSource>,
+) -> bool {
+    (rules.ignore_podcasts && song.uri.starts_with("spotify:episode:"))
+        || matches!(source, Some(SongsSource::Playlist(id)) if rules.ignored_playlists.contains(id))
+}
+
+// The track being played, and for how long it has actually been played so far
+pub(crate) struct CurrentListen {
+    pub song: SongDescription,
+    pub started_at: SystemTime,
+    played: Duration,
+    playing_since: Option<Instant>,
+}
+
+impl CurrentListen {
+    pub fn new(song: SongDescription, is_playing: bool) -> Self {
+        Self {
+            song,
+            started_at: SystemTime::now(),
+            played: Duration::ZERO,
+            playing_since: Some(Instant::now()).filter(|_| is_playing),
+        }
+    }
+
+    pub fn played(&self) -> Duration {
+        self.played + self.playing_since.map(|t| t.elapsed()).unwrap_or_default()
+    }
+
+    pub fn pause(&mut self) {
+        self.played = self.played();
+        self.playing_since = None;
+    }
+
+    pub fn resume(&mut self) {
+        if self.playing_since.is_none() {
+            self.playing_since = Some(Instant::now());
+        }
+    }
+}
+
+// What we found out about a track to make it easier to match
+#[derive(Clone, Debug, Default)]
+struct TrackIds {
+    isrc: Option<String>,
+    recording_mbid: Option<String>,
+}
+
+// Finds the ISRC of tracks (from Spotify) then their MusicBrainz recording, in the background
+struct IdResolver {
+    app_model: Rc<AppModel>,
+    worker: Worker,
+    // None while still resolving
+    resolved: RefCell<HashMap<String, Option<TrackIds>>>,
+}
+
+impl IdResolver {
+    fn resolve(self: &Rc<Self>, track_id: &str) {
+        if self.resolved.borrow().contains_key(track_id) {
+            return;
+        }
+        self.resolved
+            .borrow_mut()
+            .insert(track_id.to_string(), None);
+
+        let resolver = Rc::clone(self);
+        let api = self.app_model.get_spotify();
+        let track_id = track_id.to_string();
+        self.worker.send_local_task(async move {
+            let isrc = api
+                .get_tracks_isrc(vec![track_id.clone()])
+                .await
+                .ok()
+                .and_then(|isrcs| isrcs.into_iter().next().flatten());
+            let recording_mbid = match isrc.as_ref() {
+                Some(isrc) => musicbrainz::recording_mbid(isrc).await,
+                None => None,
+            };
+            debug!("Resolved {} to {:?}", track_id, recording_mbid);
+            resolver.resolved.borrow_mut().insert(
+                track_id,
+                Some(TrackIds {
+                    isrc,
+                    recording_mbid,
+                }),
+            );
+        });
+    }
+
+    // Whatever we know so far: a listen isn't held back because a lookup is slow
+    fn take(&self, track_id: &str) -> TrackIds {
+        self.resolved
+            .borrow_mut()
+            .remove(track_id)
+            .flatten()
+            .unwrap_or_default()
+    }
+}
+
+struct Submitter {
+    app_model: Rc<AppModel>,
+    worker: Worker,
+    spool: RefCell<ScrobbleSpool>,
+    submitting: Cell<bool>,
+}
+
+impl Submitter {
+    // Submits every pending listen, in order, unless we already are
+    fn token(&self) -> String {
+        self.app_model
+            .get_state()
+            .settings
+            .settings
+            .listenbrainz_token
+            .clone()
+    }
+
+    fn flush(self: &Rc<Self>) {
+        let token = self.token();
+        if token.is_empty() || self.submitting.get() || self.spool.borrow().is_empty() {
+            return;
+        }
+        self.submitting.set(true);
+
+        let submitter = Rc::clone(self);
+        self.worker.send_local_task(async move {
+            loop {
+                let listens = submitter.spool.borrow().oldest(SUBMIT_BATCH_SIZE);
+                if listens.is_empty() {
+                    break;
+                }
+                match listenbrainz::submit(&token, &listens).await {
+                    Ok(_) => {
+                        debug!("Submitted {} listens", listens.len());
+                        submitter.spool.borrow_mut().remove_oldest(listens.len());
+                    }
+                    Err(SubmitError::Rejected) => {
+                        submitter.spool.borrow_mut().remove_oldest(listens.len());
+                    }
+                    Err(SubmitError::InvalidToken) => {
+                        warn!("Invalid ListenBrainz token, keeping listens for later");
+                        break;
+                    }
+                    Err(SubmitError::Unreachable) => {
+                        debug!("ListenBrainz unreachable, will try again later");
+                        break;
+                    }
+                }
+            }
+            submitter.submitting.set(false);
+        });
+    }
+}
+
+// Submits what the user listens to (to ListenBrainz, if they've set a token in the preferences).
+// Listens are spooled to disk first, and submitted in order whenever we can.
+pub struct Scrobbler {
+    app_model: Rc<AppModel>,
+    submitter: Rc<Submitter>,
+    resolver: Rc<IdResolver>,
+    current: Option<CurrentListen>,
+    // Where the current track is played from
+    source: Option<SongsSource>,
+}
+
+impl Scrobbler {
+    pub fn new(app_model: Rc<AppModel>, worker: Worker) -> Self {
+        let submitter = Rc::new(Submitter {
+            app_model: Rc::clone(&app_model),
+            worker: worker.clone(),
+            spool: RefCell::new(ScrobbleSpool::load()),
+            submitting: Cell::new(false),
+        });
+        glib::timeout_add_seconds_local(
+            RETRY_INTERVAL_SECS,
+            clone!(@weak submitter => @default-return glib::Continue(false), move || {
+                submitter.flush();
+                glib::Continue(true)
+            }),
+        );
+        let resolver = Rc::new(IdResolver {
+            app_model: Rc::clone(&app_model),
+            worker,
+            resolved: Default::default(),
+        });
+        Self {
+            app_model,
+            submitter,
+            resolver,
+            current: None,
+            source: None,
+        }
+    }
+
+    fn finish_current(&mut self) {
+        let current = match self.current.take() {
+            Some(current) => current,
+            None => return,
+        };
+        let ids = self.resolver.take(&current.song.id);
+        let rules = self
+            .app_model
+            .get_state()
+            .settings
+            .settings
+            .scrobble
+            .clone();
+        if is_ignored(&rules, &current.song, self.source.as_ref())
+            || !should_scrobble(&rules, current.song.duration, current.played())
+        {
+            return;
+        }
+        let listened_at = current
+            .started_at
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        self.submitter.spool.borrow_mut().push(Listen {
+            artist: current.song.artists_name(),
+            title: current.song.title,
+            album: current.song.album.name,
+            listened_at,
+            duration_ms: current.song.duration,
+            isrc: ids.isrc,
+            recording_mbid: ids.recording_mbid,
+        });
+        self.submitter.flush();
+    }
+
+    fn start_new(&mut self) {
+        let state = self.app_model.get_state();
+        let playback = &state.playback;
+        self.current = playback
+            .current_song()
+            .map(|song| CurrentListen::new(song, playback.is_playing()));
+        self.source = playback.current_source().cloned();
+        // No need to look anything up if we're not submitting listens
+        if let Some(current) = self.current.as_ref() {
+            if !self.submitter.token().is_empty() {
+                self.resolver.resolve(&current.song.id);
+            }
+        }
+    }
+}
+
+impl EventListener for Scrobbler {
+    fn on_event(&mut self, event: &AppEvent) {
+        match event {
+            AppEvent::PlaybackEvent(PlaybackEvent::TrackChanged(_)) => {
+                self.finish_current();
+                self.start_new();
+            }
+            AppEvent::PlaybackEvent(PlaybackEvent::PlaybackStopped) => self.finish_current(),
+            AppEvent::PlaybackEvent(PlaybackEvent::PlaybackPaused) => {
+                if let Some(current) = self.current.as_mut() {
+                    current.pause();
+                }
+            }
+            AppEvent::PlaybackEvent(PlaybackEvent::PlaybackResumed) => {
+                if let Some(current) = self.current.as_mut() {
+                    current.resume();
+                }
+            }
+            AppEvent::LoginEvent(LoginEvent::LoginCompleted(_))
+            | AppEvent::ConnectivityChanged(true) => self.submitter.flush(),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::app::models::AlbumRef;
+
+    #[test]
+    fn test_should_scrobble() {
+        let rules = ScrobbleSettings::default();
+        // Too short
+        assert!(!should_scrobble(&rules, 20_000, Duration::from_secs(20)));
+        // Half of it
+        assert!(!should_scrobble(&rules, 180_000, Duration::from_secs(89)));
+        assert!(should_scrobble(&rules, 180_000, Duration::from_secs(90)));
+        // Long tracks only need 4 minutes
+        assert!(should_scrobble(
+            &rules,
+            20 * 60_000,
+            Duration::from_secs(4 * 60)
+        ));
+    }
+
+    #[test]
+    fn test_should_scrobble_custom_threshold() {
+        let rules = ScrobbleSettings {
+            threshold: 90,
+            max_minutes: 0,
+            ..Default::default()
+        };
+        assert!(!should_scrobble(&rules, 180_000, Duration::from_secs(90)));
+        assert!(should_scrobble(&rules, 180_000, Duration::from_secs(162)));
+        // No limit for long tracks
+        assert!(!should_scrobble(
+            &rules,
+            20 * 60_000,
+            Duration::from_secs(4 * 60)
+        ));
+    }
+
+    #[test]
+    fn test_ignored_listens() {
+        let mut rules = ScrobbleSettings::default();
+        rules.ignored_playlists.insert("sleep".to_string());
+        let song = SongDescription {
+            id: "id".to_string(),
+            track_number: None,
+            uri: "spotify:track:id".to_string(),
+            title: "Title".to_string(),
+            artists: vec![],
+            album: AlbumRef {
+                id: "".to_string(),
+                name: "".to_string(),
+            },
+            duration: 180_000,
+            art: None,
+            preview_url: None,
+        };
+        let sleep = SongsSource::Playlist("sleep".to_string());
+        let focus = SongsSource::Playlist("focus".to_string());
+        assert!(is_ignored(&rules, &song, Some(&sleep)));
+        assert!(!is_ignored(&rules, &song, Some(&focus)));
+        assert!(!is_ignored(&rules, &song, None));
+
+        let episode = SongDescription {
+            uri: "spotify:episode:id".to_string(),
+            ..song
+        };
+        assert!(is_ignored(&rules, &episode, None));
+        rules.ignore_podcasts = false;
+        assert!(!is_ignored(&rules, &episode, None));
+    }
+}
@@ -0,0 +1,63 @@
+use isahc::{AsyncReadResponseExt, Request};
+use serde::Deserialize;
+
+use crate::config;
+
+const ISRC_URL: &str = "https://musicbrainz.org/ws/2/isrc";
+
+#[derive(Deserialize)]
+struct Recording {
+    id: String,
+}
+
+#[derive(Deserialize)]
+struct IsrcLookup {
+    #[serde(default)]
+    recordings: Vec<Recording>,
+}
+
+// Several recordings can share an ISRC (remasters...), the first one will do
+fn first_recording(body: &str) -> Option<String> {
+    let lookup: IsrcLookup = serde_json::from_str(body).ok()?;
+    lookup.recordings.into_iter().next().map(|r| r.id)
+}
+
+// The MusicBrainz id of the recording with that ISRC, if there's one.
+// See https://musicbrainz.org/doc/MusicBrainz_API#Non-MBID_Lookups
+pub async fn recording_mbid(isrc: &str) -> Option<String> {
+    let request = Request::get(format!("{ISRC_URL}/{isrc}?fmt=json"))
+        // MusicBrainz wants to know who's asking
+        .header(
+            "User-Agent",
+            format!(
+                "Spot/{} ( https://github.com/xou816/spot )",
+                config::VERSION
+            ),
+        )
+        .header("Accept", "application/json")
+        .body(())
+        .ok()?;
+    let mut response = isahc::send_async(request).await.ok()?;
+    if !response.status().is_success() {
+        debug!("No recording found for ISRC {}", isrc);
+        return None;
+    }
+    first_recording(&response.text().await.ok()?)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_first_recording() {
+        let body = r#"{"isrc":"GBAYE0601498","recordings":[{"id":"e7c5dcf8-07c1-4e5a-9a84-64f4ac16e1e5","title":"Song"},{"id":"other"}]}"#;
+        assert_eq!(
+            first_recording(body),
+            Some("e7c5dcf8-07c1-4e5a-9a84-64f4ac16e1e5".to_string())
+        );
+        assert_eq!(first_recording(r#"{"isrc":"X","recordings":[]}"#), None);
+        assert_eq!(first_recording("not json"), None);
+    }
+}
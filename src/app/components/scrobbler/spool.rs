@@ -0,0 +1,141 @@
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fs;
+use std::path::PathBuf;
+
+// A track that was listened to, to be submitted
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Listen {
+    pub artist: String,
+    pub title: String,
+    pub album: String,
+    // In seconds since epoch
+    pub listened_at: u64,
+    pub duration_ms: u32,
+    // Help ListenBrainz match the listen, when we could find them
+    #[serde(default)]
+    pub isrc: Option<String>,
+    #[serde(default)]
+    pub recording_mbid: Option<String>,
+}
+
+// Listens waiting to be submitted, oldest first, kept on disk so that none are lost
+// while offline (or if Spot is closed in the meantime).
+pub struct ScrobbleSpool {
+    path: Option<PathBuf>,
+    listens: VecDeque<Listen>,
+}
+
+impl ScrobbleSpool {
+    fn default_path() -> PathBuf {
+        glib::user_data_dir().join("spot").join("scrobbles.json")
+    }
+
+    pub fn load() -> Self {
+        Self::load_from(Self::default_path())
+    }
+
+    fn load_from(path: PathBuf) -> Self {
+        let listens = fs::read(&path)
+            .ok()
+            .and_then(|content| serde_json::from_slice(&content).ok())
+            .unwrap_or_default();
+        Self {
+            path: Some(path),
+            listens,
+        }
+    }
+
+    // How many listens are waiting, without keeping the spool around
+    pub fn pending_count() -> usize {
+        Self::load().len()
+    }
+
+    pub fn len(&self) -> usize {
+        self.listens.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.listens.is_empty()
+    }
+
+    pub fn push(&mut self, listen: Listen) {
+        self.listens.push_back(listen);
+        self.save();
+    }
+
+    // The oldest listens, to be submitted first
+    pub fn oldest(&self, count: usize) -> Vec<Listen> {
+        self.listens.iter().take(count).cloned().collect()
+    }
+
+    pub fn remove_oldest(&mut self, count: usize) {
+        let count = count.min(self.listens.len());
+        self.listens.drain(..count);
+        self.save();
+    }
+
+    fn save(&self) {
+        let path = match self.path.as_ref() {
+            Some(path) => path,
+            None => return,
+        };
+        let result = path
+            .parent()
+            .map(fs::create_dir_all)
+            .unwrap_or(Ok(()))
+            .and_then(|_| {
+                let content = serde_json::to_vec(&self.listens).unwrap_or_default();
+                fs::write(path, content)
+            });
+        if let Err(e) = result {
+            warn!("Could not save pending scrobbles: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn make_listen(title: &str) -> Listen {
+        Listen {
+            artist: "Artist".to_string(),
+            title: title.to_string(),
+            album: "Album".to_string(),
+            listened_at: 0,
+            duration_ms: 180_000,
+            isrc: None,
+            recording_mbid: None,
+        }
+    }
+
+    #[test]
+    fn test_spool_keeps_order() {
+        let mut spool = ScrobbleSpool {
+            path: None,
+            listens: VecDeque::new(),
+        };
+        spool.push(make_listen("1"));
+        spool.push(make_listen("2"));
+        spool.push(make_listen("3"));
+
+        assert_eq!(spool.oldest(2), vec![make_listen("1"), make_listen("2")]);
+        spool.remove_oldest(2);
+        assert_eq!(spool.oldest(2), vec![make_listen("3")]);
+        spool.remove_oldest(2);
+        assert!(spool.is_empty());
+    }
+
+    #[test]
+    fn test_spool_persists() {
+        let path = std::env::temp_dir().join(format!("spot-scrobbles-{}.json", std::process::id()));
+        let mut spool = ScrobbleSpool::load_from(path.clone());
+        spool.push(make_listen("1"));
+
+        let spool = ScrobbleSpool::load_from(path.clone());
+        assert_eq!(spool.oldest(1), vec![make_listen("1")]);
+        let _ = fs::remove_file(path);
+    }
+}
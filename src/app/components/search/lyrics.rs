@@ -0,0 +1,126 @@
+// Finding songs from a line of their lyrics, with LRCLIB (https://lrclib.net/docs)
+
+use isahc::{AsyncReadResponseExt, Request};
+use serde::Deserialize;
+
+use crate::config;
+
+const SEARCH_URL: &str = "https://lrclib.net/api/search";
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LyricsResult {
+    pub track_name: String,
+    pub artist_name: String,
+    #[serde(default)]
+    pub plain_lyrics: Option<String>,
+}
+
+impl LyricsResult {
+    // What to look for on Spotify
+    pub fn search_query(&self) -> String {
+        format!("{} {}", self.artist_name, self.track_name)
+    }
+}
+
+// Lowercase words only, so that "Don't stop!" matches "dont stop"
+fn normalize(text: &str) -> String {
+    text.to_lowercase()
+        .chars()
+        .filter(|c| c.is_alphanumeric() || c.is_whitespace())
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// The line of the lyrics the phrase is in (or starts in), to show along with the result
+pub fn matching_line(lyrics: &str, phrase: &str) -> Option<String> {
+    let phrase = normalize(phrase);
+    if phrase.is_empty() {
+        return None;
+    }
+    let lines: Vec<&str> = lyrics
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty())
+        .collect();
+    // The phrase might span two lines
+    let found = lines.iter().enumerate().find(|(i, line)| {
+        let with_next = match lines.get(i + 1) {
+            Some(next) => format!("{line} {next}"),
+            None => line.to_string(),
+        };
+        normalize(&with_next).contains(&phrase)
+    })?;
+    Some(found.1.to_string())
+}
+
+fn parse_results(body: &str) -> Vec<LyricsResult> {
+    serde_json::from_str(body).unwrap_or_default()
+}
+
+// Songs whose lyrics might contain that phrase, best matches first
+pub async fn search(phrase: &str) -> Vec<LyricsResult> {
+    let query = form_urlencoded::Serializer::new(String::new())
+        .append_pair("q", phrase)
+        .finish();
+    let request = Request::get(format!("{SEARCH_URL}?{query}"))
+        // LRCLIB asks clients to identify themselves
+        .header(
+            "User-Agent",
+            format!(
+                "Spot/{} ( https://github.com/xou816/spot )",
+                config::VERSION
+            ),
+        )
+        .body(());
+    let response = match request {
+        Ok(request) => isahc::send_async(request).await,
+        Err(_) => return vec![],
+    };
+    let body = match response {
+        Ok(mut response) if response.status().is_success() => response.text().await.ok(),
+        _ => None,
+    };
+    match body {
+        Some(body) => parse_results(&body),
+        None => {
+            debug!("Lyrics search failed");
+            vec![]
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    const LYRICS: &str = "Is this the real life?\nIs this just fantasy?\n\nCaught in a landslide\nNo escape from reality";
+
+    #[test]
+    fn test_matching_line() {
+        assert_eq!(
+            matching_line(LYRICS, "caught in a LANDSLIDE"),
+            Some("Caught in a landslide".to_string())
+        );
+        // Across two lines, punctuation aside
+        assert_eq!(
+            matching_line(LYRICS, "just fantasy caught in"),
+            Some("Is this just fantasy?".to_string())
+        );
+        assert_eq!(matching_line(LYRICS, "thunderbolt and lightning"), None);
+        assert_eq!(matching_line(LYRICS, "?!"), None);
+    }
+
+    #[test]
+    fn test_parse_results() {
+        let body = r#"[{"id":1,"trackName":"Bohemian Rhapsody","artistName":"Queen","albumName":"A Night at the Opera","duration":354,"plainLyrics":"Is this the real life?"},{"id":2,"trackName":"Other","artistName":"Someone","plainLyrics":null}]"#;
+        let results = parse_results(body);
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].search_query(), "Queen Bohemian Rhapsody");
+        assert_eq!(results[1].plain_lyrics, None);
+        assert!(parse_results("not json").is_empty());
+    }
+}
@@ -1,6 +1,8 @@
 mod search;
 pub use search::*;
 
+mod lyrics;
+
 mod search_model;
 pub use search_model::*;
 
@@ -1,13 +1,15 @@
+use gettextrs::*;
 use gtk::prelude::*;
 use gtk::subclass::prelude::*;
 use gtk::CompositeTemplate;
+use libadwaita::prelude::*;
 use std::rc::Rc;
 
 use crate::app::components::utils::{wrap_flowbox_item, Debouncer};
 use crate::app::components::{AlbumWidget, ArtistWidget, Component, EventListener};
 use crate::app::dispatch::Worker;
-use crate::app::models::{AlbumModel, ArtistModel};
-use crate::app::state::{AppEvent, BrowserEvent};
+use crate::app::models::{AlbumModel, ArtistModel, LyricsMatch};
+use crate::app::state::{AppEvent, BrowserEvent, LibraryItem, LibraryItemKind};
 
 use super::SearchResultsModel;
 mod imp {
@@ -32,11 +34,29 @@ mod imp {
         #[template_child]
         pub search_results: TemplateChild<gtk::Widget>,
 
+        #[template_child]
+        pub library_results_section: TemplateChild<gtk::Expander>,
+
+        #[template_child]
+        pub library_results: TemplateChild<gtk::ListBox>,
+
         #[template_child]
         pub albums_results: TemplateChild<gtk::FlowBox>,
 
         #[template_child]
         pub artist_results: TemplateChild<gtk::FlowBox>,
+
+        #[template_child]
+        pub regular_results: TemplateChild<gtk::Box>,
+
+        #[template_child]
+        pub lyrics_results_section: TemplateChild<gtk::Expander>,
+
+        #[template_child]
+        pub lyrics_results: TemplateChild<gtk::ListBox>,
+
+        #[template_child]
+        pub lyrics_mode: TemplateChild<gtk::ToggleButton>,
     }
 
     #[glib::object_subclass]
@@ -108,6 +128,93 @@ impl SearchResultsWidget {
             }));
     }
 
+    fn connect_lyrics_mode_toggled<F>(&self, f: F)
+    where
+        F: Fn(bool) + 'static,
+    {
+        self.imp()
+            .lyrics_mode
+            .connect_toggled(clone!(@weak self as _self => move |button| {
+                let enabled = button.is_active();
+                let widget = _self.imp();
+                widget.regular_results.set_visible(!enabled);
+                widget.lyrics_results_section.set_visible(enabled);
+                let placeholder = if enabled {
+                    // translators: Placeholder of the search field when searching lyrics
+                    gettext("A line from a song")
+                } else {
+                    String::new()
+                };
+                widget.search_entry.set_placeholder_text(Some(&placeholder));
+                f(enabled);
+            }));
+    }
+
+    fn connect_lyrics_result_activated<F>(&self, f: F)
+    where
+        F: Fn(usize) + 'static,
+    {
+        self.imp()
+            .lyrics_results
+            .connect_row_activated(move |_, row| f(row.index() as usize));
+    }
+
+    fn set_lyrics_results(&self, results: &[LyricsMatch]) {
+        let listbox = &self.imp().lyrics_results;
+        while let Some(row) = listbox.row_at_index(0) {
+            listbox.remove(&row);
+        }
+
+        for result in results {
+            let song = &result.song;
+            let title = format!("{} – {}", song.artists_name(), song.title);
+            let row = libadwaita::ActionRow::builder()
+                .title(glib::markup_escape_text(&title).as_str())
+                .activatable(true)
+                .build();
+            if let Some(line) = result.line.as_ref() {
+                row.set_subtitle(glib::markup_escape_text(&format!("“{line}”")).as_str());
+            }
+            row.add_prefix(&gtk::Image::from_icon_name("audio-x-generic-symbolic"));
+            listbox.append(&row);
+        }
+    }
+
+    fn connect_library_result_activated<F>(&self, f: F)
+    where
+        F: Fn(usize) + 'static,
+    {
+        self.imp()
+            .library_results
+            .connect_row_activated(move |_, row| f(row.index() as usize));
+    }
+
+    fn set_library_results(&self, results: &[LibraryItem]) {
+        let listbox = &self.imp().library_results;
+        while let Some(row) = listbox.row_at_index(0) {
+            listbox.remove(&row);
+        }
+
+        for item in results {
+            let icon = match item.kind {
+                LibraryItemKind::Album => "media-optical-cd-audio-symbolic",
+                LibraryItemKind::Playlist => "view-list-symbolic",
+                LibraryItemKind::Track => "audio-x-generic-symbolic",
+            };
+            let row = libadwaita::ActionRow::builder()
+                .title(glib::markup_escape_text(&item.title).as_str())
+                .subtitle(glib::markup_escape_text(&item.subtitle).as_str())
+                .activatable(true)
+                .build();
+            row.add_prefix(&gtk::Image::from_icon_name(icon));
+            listbox.append(&row);
+        }
+
+        self.imp()
+            .library_results_section
+            .set_visible(!results.is_empty());
+    }
+
     fn bind_albums_results<F>(&self, worker: Worker, store: &gio::ListStore, on_album_pressed: F)
     where
         F: Fn(String) + Clone + 'static,
@@ -171,6 +278,18 @@ impl SearchResults {
             model.search(q);
         }));
 
+        widget.connect_library_result_activated(clone!(@weak model => move |index| {
+            model.open_library_result(index);
+        }));
+
+        widget.connect_lyrics_mode_toggled(clone!(@weak model => move |enabled| {
+            model.set_lyrics_mode(enabled);
+        }));
+
+        widget.connect_lyrics_result_activated(clone!(@weak model => move |index| {
+            model.play_lyrics_result(index);
+        }));
+
         widget.bind_albums_results(
             worker.clone(),
             &album_results_model,
@@ -221,6 +340,18 @@ impl SearchResults {
         }
     }
 
+    fn update_library_results(&self) {
+        if let Some(results) = self.model.get_library_results() {
+            self.widget.set_library_results(&results[..]);
+        }
+    }
+
+    fn update_lyrics_results(&self) {
+        if let Some(results) = self.model.get_lyrics_results() {
+            self.widget.set_lyrics_results(&results[..]);
+        }
+    }
+
     fn update_search_query(&self) {
         self.debouncer.debounce(
             600,
@@ -245,6 +376,12 @@ impl EventListener for SearchResults {
             AppEvent::BrowserEvent(BrowserEvent::SearchResultsUpdated) => {
                 self.update_results();
             }
+            AppEvent::BrowserEvent(BrowserEvent::LibrarySearchResultsUpdated) => {
+                self.update_library_results();
+            }
+            AppEvent::BrowserEvent(BrowserEvent::LyricsResultsUpdated) => {
+                self.update_lyrics_results();
+            }
             _ => {}
         }
     }
@@ -3,7 +3,15 @@ use std::rc::Rc;
 
 use crate::app::dispatch::ActionDispatcher;
 use crate::app::models::*;
-use crate::app::state::{AppAction, AppModel, BrowserAction};
+use crate::app::state::{
+    AppAction, AppModel, BrowserAction, LibraryItem, LibraryItemKind, PlaybackAction,
+};
+use crate::app::SpotifyUri;
+
+use super::lyrics;
+
+// How many songs we look up on Spotify
+const LYRICS_RESULTS_LIMIT: usize = 10;
 
 pub struct SearchResultsModel {
     app_model: Rc<AppModel>,
@@ -24,6 +32,13 @@ impl SearchResultsModel {
     }
 
     pub fn search(&self, query: String) {
+        // A pasted link is opened rather than searched for. Not usernames though, they can't be told
+        // apart from a link still being typed.
+        let uri = SpotifyUri::parse(&query).filter(|uri| !matches!(uri, SpotifyUri::User(_)));
+        if let Some(uri) = uri {
+            self.dispatcher.dispatch(uri.into());
+            return;
+        }
         self.dispatcher
             .dispatch(BrowserAction::Search(query).into());
     }
@@ -33,7 +48,25 @@ impl SearchResultsModel {
             .map_state_opt(|s| Some(&s.browser.search_state()?.query).filter(|s| !s.is_empty()))
     }
 
+    pub fn is_lyrics_mode(&self) -> bool {
+        self.app_model
+            .get_state()
+            .browser
+            .search_state()
+            .map(|s| s.lyrics_mode)
+            .unwrap_or(false)
+    }
+
+    pub fn set_lyrics_mode(&self, enabled: bool) {
+        self.dispatcher
+            .dispatch(BrowserAction::SetLyricsSearch(enabled).into());
+    }
+
     pub fn fetch_results(&self) {
+        if self.is_lyrics_mode() {
+            self.fetch_lyrics_results();
+            return;
+        }
         let api = self.app_model.get_spotify();
         if let Some(query) = self.get_query() {
             let query = query.to_owned();
@@ -46,6 +79,57 @@ impl SearchResultsModel {
         }
     }
 
+    // Songs whose lyrics have the query in them, looked up on Spotify one by one
+    fn fetch_lyrics_results(&self) {
+        let api = self.app_model.get_spotify();
+        if let Some(query) = self.get_query() {
+            let query = query.to_owned();
+            self.dispatcher
+                .call_spotify_and_dispatch(move || async move {
+                    let mut results = lyrics::search(&query).await;
+                    results.truncate(LYRICS_RESULTS_LIMIT);
+
+                    let mut matches: Vec<LyricsMatch> = vec![];
+                    for result in results {
+                        let song = api
+                            .search_tracks(&result.search_query(), 1)
+                            .await?
+                            .into_iter()
+                            .next();
+                        let song = match song {
+                            Some(song) if !matches.iter().any(|m| m.song.id == song.id) => song,
+                            _ => continue,
+                        };
+                        let line = result
+                            .plain_lyrics
+                            .as_deref()
+                            .and_then(|lyrics| lyrics::matching_line(lyrics, &query));
+                        matches.push(LyricsMatch { song, line });
+                    }
+                    // Songs we know say it first
+                    matches.sort_by_key(|m| m.line.is_none());
+                    Ok(BrowserAction::SetLyricsResults(matches).into())
+                });
+        }
+    }
+
+    pub fn get_lyrics_results(&self) -> Option<impl Deref<Target = Vec<LyricsMatch>> + '_> {
+        self.app_model
+            .map_state_opt(|s| Some(&s.browser.search_state()?.lyrics_results))
+    }
+
+    pub fn play_lyrics_result(&self, index: usize) {
+        let song = self
+            .get_lyrics_results()
+            .and_then(|results| Some(results.get(index)?.song.clone()));
+        if let Some(song) = song {
+            let id = song.id.clone();
+            self.dispatcher
+                .dispatch(PlaybackAction::LoadSongs(vec![song]).into());
+            self.dispatcher.dispatch(PlaybackAction::Load(id).into());
+        }
+    }
+
     pub fn get_album_results(&self) -> Option<impl Deref<Target = Vec<AlbumDescription>> + '_> {
         self.app_model
             .map_state_opt(|s| Some(&s.browser.search_state()?.album_results))
@@ -56,6 +140,25 @@ impl SearchResultsModel {
             .map_state_opt(|s| Some(&s.browser.search_state()?.artist_results))
     }
 
+    pub fn get_library_results(&self) -> Option<impl Deref<Target = Vec<LibraryItem>> + '_> {
+        self.app_model
+            .map_state_opt(|s| Some(&s.browser.search_state()?.library_results))
+    }
+
+    pub fn open_library_result(&self, index: usize) {
+        let action = self.get_library_results().and_then(|results| {
+            let item = results.get(index)?;
+            Some(match item.kind {
+                LibraryItemKind::Album => AppAction::ViewAlbum(item.id.clone()),
+                LibraryItemKind::Playlist => AppAction::ViewPlaylist(item.id.clone()),
+                LibraryItemKind::Track => AppAction::ViewAlbum(item.album.as_ref()?.id.clone()),
+            })
+        });
+        if let Some(action) = action {
+            self.dispatcher.dispatch(action);
+        }
+    }
+
     pub fn open_album(&self, id: String) {
         self.dispatcher.dispatch(AppAction::ViewAlbum(id));
     }
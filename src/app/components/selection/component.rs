@@ -6,9 +6,9 @@ use std::rc::Rc;
 use crate::app::components::{Component, EventListener};
 use crate::app::models::PlaylistSummary;
 use crate::app::state::{
-    LoginEvent, SelectionAction, SelectionContext, SelectionEvent, SelectionState,
+    LoginEvent, PendingChange, SelectionAction, SelectionContext, SelectionEvent, SelectionState,
 };
-use crate::app::{ActionDispatcher, AppAction, AppEvent, AppModel, BrowserAction};
+use crate::app::{ActionDispatcher, AppAction, AppEvent, AppModel};
 
 use super::widget::{SelectionToolState, SelectionToolbarWidget};
 
@@ -112,22 +112,19 @@ impl SelectionToolbarModel {
     }
 
     fn remove_from_playlist(&self, id: &str) {
-        let api = self.app_model.get_spotify();
-        let id = id.to_string();
         let uris: Vec<String> = self
             .selection()
             .peek_selection()
             .map(|s| &s.uri)
             .cloned()
             .collect();
-        self.dispatcher
-            .call_spotify_and_dispatch_many(move || async move {
-                api.remove_from_playlist(&id, uris.clone()).await?;
-                Ok(vec![
-                    BrowserAction::RemoveTracksFromPlaylist(id, uris).into(),
-                    SelectionAction::Clear.into(),
-                ])
-            })
+        self.dispatcher.dispatch_many(vec![
+            AppAction::DeferChange(PendingChange::RemoveTracksFromPlaylist {
+                playlist_id: id.to_string(),
+                uris,
+            }),
+            SelectionAction::Clear.into(),
+        ]);
     }
 }
 
@@ -149,7 +146,10 @@ impl SelectionToolbar {
 
     fn update_active_tools(&self) {
         let count = self.model.selected_count();
-        match self.model.selection().context {
+        let context = self.model.selection().context.clone();
+        self.widget
+            .set_selected_count(count, matches!(context, SelectionContext::SavedTracks));
+        match context {
             SelectionContext::Default => {
                 self.widget.set_move(SelectionToolState::Hidden);
                 self.widget
@@ -4,7 +4,7 @@ use gtk::prelude::*;
 use gtk::subclass::prelude::*;
 use gtk::CompositeTemplate;
 
-use crate::app::components::{display_add_css_provider, labels};
+use crate::app::components::{display, display_add_css_provider, labels};
 use crate::app::models::PlaylistSummary;
 
 mod imp {
@@ -29,9 +29,15 @@ mod imp {
         #[template_child]
         pub remove: TemplateChild<gtk::Button>,
 
+        #[template_child]
+        pub remove_label: TemplateChild<libadwaita::ButtonContent>,
+
         #[template_child]
         pub queue: TemplateChild<gtk::Button>,
 
+        #[template_child]
+        pub queue_label: TemplateChild<libadwaita::ButtonContent>,
+
         #[template_child]
         pub save: TemplateChild<gtk::Button>,
     }
@@ -140,6 +146,21 @@ impl SelectionToolbarWidget {
         self.imp().remove.set_visible(state.visible());
     }
 
+    pub fn set_selected_count(&self, count: usize, from_library: bool) {
+        self.imp()
+            .queue_label
+            .set_label(&display::add_n_to_queue_label(count));
+        self.imp()
+            .add
+            .set_label(&display::add_n_to_playlist_label(count));
+        let remove_label = if from_library {
+            display::remove_n_from_library_label(count)
+        } else {
+            display::remove_n_label(count)
+        };
+        self.imp().remove_label.set_label(&remove_label);
+    }
+
     pub fn set_save(&self, state: SelectionToolState) {
         self.imp().save.set_sensitive(state.sensitive());
         self.imp().save.set_visible(state.visible());
@@ -0,0 +1,117 @@
+use std::rc::Rc;
+use std::time::Instant;
+
+use crate::app::components::EventListener;
+use crate::app::state::{LoginEvent, PlaybackAction, PlaybackEvent, ScreenName};
+use crate::app::{ActionDispatcher, AppEvent, AppModel, BrowserAction, BrowserEvent};
+use crate::crash_report::{self, SessionSnapshot};
+
+// Keeps a snapshot of the queue, position and current page for the crash handler to save (see
+// crash_report), and after a crash, puts the user back where they were. Playback is restored paused.
+pub struct SessionKeeper {
+    app_model: Rc<AppModel>,
+    dispatcher: Box<dyn ActionDispatcher>,
+    // What to restore once logged in (the player can't load anything before that)
+    crashed_session: Option<SessionSnapshot>,
+}
+
+impl SessionKeeper {
+    pub fn new(app_model: Rc<AppModel>, dispatcher: Box<dyn ActionDispatcher>) -> Self {
+        Self {
+            app_model,
+            dispatcher,
+            crashed_session: None,
+        }
+    }
+
+    fn start(&mut self) {
+        self.crashed_session = crash_report::take_session();
+        let screen = self
+            .crashed_session
+            .as_mut()
+            .and_then(|session| session.screen.take());
+        match screen {
+            Some(ScreenName::Home) | None => {}
+            Some(screen) => self
+                .dispatcher
+                .dispatch(BrowserAction::NavigationPush(screen).into()),
+        }
+    }
+
+    fn restore_playback(&mut self) {
+        let session = match self.crashed_session.take() {
+            Some(session) => session,
+            None => return,
+        };
+        // Whatever the user started playing meanwhile comes first
+        let has_song = self
+            .app_model
+            .get_state()
+            .playback
+            .current_song_id()
+            .is_some();
+        let current_song_id = match session.current_song_id {
+            Some(id) if !has_song => id,
+            _ => return,
+        };
+        self.dispatcher.dispatch_many(vec![
+            PlaybackAction::LoadSongs(session.songs).into(),
+            PlaybackAction::Load(current_song_id).into(),
+            PlaybackAction::Seek(session.position).into(),
+            PlaybackAction::Pause.into(),
+        ]);
+    }
+
+    // The queue can be long, it's only copied when it changes
+    fn update_songs(&self) {
+        let songs: Vec<_> = self.app_model.get_state().playback.songs().collect();
+        crash_report::update_session(|session| session.songs = songs);
+        self.update_playback();
+    }
+
+    fn update_playback(&self) {
+        let state = self.app_model.get_state();
+        let current_song_id = state.playback.current_song_id();
+        let position = state.playback.position();
+        let playing_since = if state.playback.is_playing() {
+            Some(Instant::now())
+        } else {
+            None
+        };
+        drop(state);
+        crash_report::update_session(|session| {
+            session.current_song_id = current_song_id;
+            session.position = position;
+            session.playing_since = playing_since;
+        });
+    }
+
+    fn update_screen(&self) {
+        let screen = self.app_model.get_state().browser.current_screen().clone();
+        crash_report::update_session(|session| session.screen = Some(screen));
+    }
+}
+
+impl EventListener for SessionKeeper {
+    fn on_event(&mut self, event: &AppEvent) {
+        match event {
+            AppEvent::Started => self.start(),
+            AppEvent::LoginEvent(LoginEvent::LoginCompleted(_)) => self.restore_playback(),
+            AppEvent::PlaybackEvent(PlaybackEvent::PlaylistChanged) => self.update_songs(),
+            AppEvent::PlaybackEvent(
+                PlaybackEvent::TrackChanged(_)
+                | PlaybackEvent::TrackSeeked(_)
+                | PlaybackEvent::SeekSynced(_)
+                | PlaybackEvent::PlaybackPaused
+                | PlaybackEvent::PlaybackResumed
+                | PlaybackEvent::PlaybackStopped,
+            ) => self.update_playback(),
+            AppEvent::BrowserEvent(
+                BrowserEvent::NavigationPushed(_)
+                | BrowserEvent::NavigationPopped
+                | BrowserEvent::NavigationPoppedTo(_),
+            ) => self.update_screen(),
+            _ => {}
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use crate::app::components::EventListener;
+use crate::app::components::{display, EventListener, ScrobbleSpool};
 use crate::app::AppEvent;
 use crate::settings::SpotSettings;
 
@@ -6,6 +6,7 @@ use gtk::prelude::*;
 use gtk::subclass::prelude::*;
 use gtk::CompositeTemplate;
 use libadwaita::prelude::*;
+use std::rc::Rc;
 
 use super::SettingsModel;
 
@@ -31,14 +32,83 @@ mod imp {
         #[template_child]
         pub audio_backend: TemplateChild<libadwaita::ComboRow>,
 
+        #[template_child]
+        pub audio_mixer: TemplateChild<libadwaita::ComboRow>,
+
         #[template_child]
         pub gapless_playback: TemplateChild<libadwaita::ActionRow>,
 
+        #[template_child]
+        pub night_mode_scale: TemplateChild<gtk::Scale>,
+
+        #[template_child]
+        pub trim_silence: TemplateChild<libadwaita::ActionRow>,
+
+        #[template_child]
+        pub playback_metrics: TemplateChild<libadwaita::ActionRow>,
+
+        #[template_child]
+        pub shuffle_mode: TemplateChild<libadwaita::ComboRow>,
+
+        #[template_child]
+        pub autoplay: TemplateChild<libadwaita::ActionRow>,
+
+        #[template_child]
+        pub pause_during_calls: TemplateChild<libadwaita::ActionRow>,
+
+        #[template_child]
+        pub player_profile: TemplateChild<gtk::Entry>,
+
+        #[template_child]
+        pub listenbrainz_token: TemplateChild<gtk::PasswordEntry>,
+
+        #[template_child]
+        pub scrobble_threshold: TemplateChild<gtk::SpinButton>,
+
+        #[template_child]
+        pub scrobble_max_minutes: TemplateChild<gtk::SpinButton>,
+
+        #[template_child]
+        pub scrobble_ignore_podcasts: TemplateChild<libadwaita::ActionRow>,
+
+        #[template_child]
+        pub pending_scrobbles: TemplateChild<libadwaita::ActionRow>,
+
+        #[template_child]
+        pub preview_on_hover: TemplateChild<libadwaita::ActionRow>,
+
+        #[template_child]
+        pub notify_track_changes: TemplateChild<libadwaita::ActionRow>,
+
+        #[template_child]
+        pub close_behavior: TemplateChild<libadwaita::ComboRow>,
+
+        #[template_child]
+        pub start_minimized: TemplateChild<libadwaita::ActionRow>,
+
         #[template_child]
         pub ap_port: TemplateChild<gtk::Entry>,
 
+        #[template_child]
+        pub playlist_refresh_interval: TemplateChild<gtk::Entry>,
+
+        #[template_child]
+        pub track_change_delay: TemplateChild<gtk::Entry>,
+
+        #[template_child]
+        pub previous_restart_threshold: TemplateChild<gtk::Entry>,
+
         #[template_child]
         pub theme: TemplateChild<libadwaita::ComboRow>,
+
+        #[template_child]
+        pub use_artwork_colors: TemplateChild<libadwaita::ActionRow>,
+
+        #[template_child]
+        pub high_contrast_indicators: TemplateChild<libadwaita::ActionRow>,
+
+        #[template_child]
+        pub track_list_template: TemplateChild<gtk::Entry>,
     }
 
     #[glib::object_subclass]
@@ -73,7 +143,6 @@ impl SettingsWindow {
 
         window.bind_backend_and_device();
         window.bind_settings();
-        window.connect_theme_select();
         window
     }
 
@@ -148,6 +217,7 @@ impl SettingsWindow {
                         "pulseaudio" => 0,
                         "alsa" => 1,
                         "gstreamer" => 2,
+                        "pipewire" => 3,
                         _ => unreachable!(),
                     }
                     .to_value()
@@ -159,6 +229,35 @@ impl SettingsWindow {
                         0 => "pulseaudio",
                         1 => "alsa",
                         2 => "gstreamer",
+                        3 => "pipewire",
+                        _ => unreachable!(),
+                    }
+                    .to_variant()
+                })
+            })
+            .build();
+
+        let audio_mixer = widget
+            .audio_mixer
+            .downcast_ref::<libadwaita::ComboRow>()
+            .unwrap();
+        settings
+            .bind("audio-mixer", audio_mixer, "selected")
+            .mapping(|variant, _| {
+                variant.str().map(|s| {
+                    match s {
+                        "software" => 0,
+                        "hardware" => 1,
+                        _ => unreachable!(),
+                    }
+                    .to_value()
+                })
+            })
+            .set_mapping(|value, _| {
+                value.get::<u32>().ok().map(|u| {
+                    match u {
+                        0 => "software",
+                        1 => "hardware",
                         _ => unreachable!(),
                     }
                     .to_variant()
@@ -178,6 +277,202 @@ impl SettingsWindow {
             )
             .build();
 
+        settings
+            .bind(
+                "night-mode-strength",
+                &widget.night_mode_scale.adjustment(),
+                "value",
+            )
+            .mapping(|variant, _| variant.get::<u32>().map(|u| (u as f64).to_value()))
+            .set_mapping(|value, _| {
+                value
+                    .get::<f64>()
+                    .ok()
+                    .map(|f| (f.round() as u32).to_variant())
+            })
+            .build();
+
+        let trim_silence = widget
+            .trim_silence
+            .downcast_ref::<libadwaita::ActionRow>()
+            .unwrap();
+        settings
+            .bind(
+                "trim-silence",
+                &trim_silence.activatable_widget().unwrap(),
+                "active",
+            )
+            .build();
+
+        let shuffle_mode = widget
+            .shuffle_mode
+            .downcast_ref::<libadwaita::ComboRow>()
+            .unwrap();
+        settings
+            .bind("shuffle-mode", shuffle_mode, "selected")
+            .mapping(|variant, _| {
+                variant.str().map(|s| {
+                    match s {
+                        "random" => 0,
+                        "artist-spread" => 1,
+                        "least-recent" => 2,
+                        _ => unreachable!(),
+                    }
+                    .to_value()
+                })
+            })
+            .set_mapping(|value, _| {
+                value.get::<u32>().ok().map(|u| {
+                    match u {
+                        0 => "random",
+                        1 => "artist-spread",
+                        2 => "least-recent",
+                        _ => unreachable!(),
+                    }
+                    .to_variant()
+                })
+            })
+            .build();
+
+        let autoplay = widget
+            .autoplay
+            .downcast_ref::<libadwaita::ActionRow>()
+            .unwrap();
+        settings
+            .bind(
+                "autoplay",
+                &autoplay.activatable_widget().unwrap(),
+                "active",
+            )
+            .build();
+
+        let pause_during_calls = widget
+            .pause_during_calls
+            .downcast_ref::<libadwaita::ActionRow>()
+            .unwrap();
+        settings
+            .bind(
+                "pause-during-calls",
+                &pause_during_calls.activatable_widget().unwrap(),
+                "active",
+            )
+            .build();
+
+        let playback_metrics = widget
+            .playback_metrics
+            .downcast_ref::<libadwaita::ActionRow>()
+            .unwrap();
+        settings
+            .bind(
+                "playback-metrics",
+                &playback_metrics.activatable_widget().unwrap(),
+                "active",
+            )
+            .build();
+
+        let player_profile = widget.player_profile.downcast_ref::<gtk::Entry>().unwrap();
+        settings
+            .bind("player-profile", player_profile, "text")
+            .build();
+
+        settings
+            .bind("listenbrainz-token", &*widget.listenbrainz_token, "text")
+            .build();
+
+        for (key, spin_button) in [
+            ("scrobble-threshold", &*widget.scrobble_threshold),
+            ("scrobble-max-minutes", &*widget.scrobble_max_minutes),
+        ] {
+            settings
+                .bind(key, &spin_button.adjustment(), "value")
+                .mapping(|variant, _| variant.get::<u32>().map(|u| (u as f64).to_value()))
+                .set_mapping(|value, _| {
+                    value
+                        .get::<f64>()
+                        .ok()
+                        .map(|f| (f.round() as u32).to_variant())
+                })
+                .build();
+        }
+
+        let scrobble_ignore_podcasts = widget
+            .scrobble_ignore_podcasts
+            .downcast_ref::<libadwaita::ActionRow>()
+            .unwrap();
+        settings
+            .bind(
+                "scrobble-ignore-podcasts",
+                &scrobble_ignore_podcasts.activatable_widget().unwrap(),
+                "active",
+            )
+            .build();
+
+        let preview_on_hover = widget
+            .preview_on_hover
+            .downcast_ref::<libadwaita::ActionRow>()
+            .unwrap();
+        settings
+            .bind(
+                "preview-on-hover",
+                &preview_on_hover.activatable_widget().unwrap(),
+                "active",
+            )
+            .build();
+
+        let notify_track_changes = widget
+            .notify_track_changes
+            .downcast_ref::<libadwaita::ActionRow>()
+            .unwrap();
+        settings
+            .bind(
+                "notify-track-changes",
+                &notify_track_changes.activatable_widget().unwrap(),
+                "active",
+            )
+            .build();
+
+        let start_minimized = widget
+            .start_minimized
+            .downcast_ref::<libadwaita::ActionRow>()
+            .unwrap();
+        settings
+            .bind(
+                "start-minimized",
+                &start_minimized.activatable_widget().unwrap(),
+                "active",
+            )
+            .build();
+
+        let close_behavior = widget
+            .close_behavior
+            .downcast_ref::<libadwaita::ComboRow>()
+            .unwrap();
+        settings
+            .bind("close-behavior", close_behavior, "selected")
+            .mapping(|variant, _| {
+                variant.str().map(|s| {
+                    match s {
+                        "ask" => 0,
+                        "stop" => 1,
+                        "background" => 2,
+                        _ => unreachable!(),
+                    }
+                    .to_value()
+                })
+            })
+            .set_mapping(|value, _| {
+                value.get::<u32>().ok().map(|u| {
+                    match u {
+                        0 => "ask",
+                        1 => "stop",
+                        2 => "background",
+                        _ => unreachable!(),
+                    }
+                    .to_variant()
+                })
+            })
+            .build();
+
         let ap_port = widget.ap_port.downcast_ref::<gtk::Entry>().unwrap();
         settings
             .bind("ap-port", ap_port, "text")
@@ -185,6 +480,52 @@ impl SettingsWindow {
             .set_mapping(|value, _| value.get::<u32>().ok().map(|u| u.to_variant()))
             .build();
 
+        let playlist_refresh_interval = widget
+            .playlist_refresh_interval
+            .downcast_ref::<gtk::Entry>()
+            .unwrap();
+        settings
+            .bind(
+                "playlist-refresh-interval",
+                playlist_refresh_interval,
+                "text",
+            )
+            .mapping(|variant, _| variant.get::<u32>().map(|s| s.to_value()))
+            .set_mapping(|value, _| value.get::<u32>().ok().map(|u| u.to_variant()))
+            .build();
+
+        let track_change_delay = widget
+            .track_change_delay
+            .downcast_ref::<gtk::Entry>()
+            .unwrap();
+        settings
+            .bind("track-change-delay", track_change_delay, "text")
+            .mapping(|variant, _| variant.get::<u32>().map(|s| s.to_value()))
+            .set_mapping(|value, _| value.get::<u32>().ok().map(|u| u.to_variant()))
+            .build();
+
+        let previous_restart_threshold = widget
+            .previous_restart_threshold
+            .downcast_ref::<gtk::Entry>()
+            .unwrap();
+        settings
+            .bind(
+                "previous-restart-threshold",
+                previous_restart_threshold,
+                "text",
+            )
+            .mapping(|variant, _| variant.get::<u32>().map(|s| s.to_value()))
+            .set_mapping(|value, _| value.get::<u32>().ok().map(|u| u.to_variant()))
+            .build();
+
+        let track_list_template = widget
+            .track_list_template
+            .downcast_ref::<gtk::Entry>()
+            .unwrap();
+        settings
+            .bind("track-list-template", track_list_template, "text")
+            .build();
+
         let theme = widget.theme.downcast_ref::<libadwaita::ComboRow>().unwrap();
         settings
             .bind("theme-preference", theme, "selected")
@@ -211,23 +552,69 @@ impl SettingsWindow {
                 })
             })
             .build();
+
+        let use_artwork_colors = widget
+            .use_artwork_colors
+            .downcast_ref::<libadwaita::ActionRow>()
+            .unwrap();
+        settings
+            .bind(
+                "use-artwork-colors",
+                &use_artwork_colors.activatable_widget().unwrap(),
+                "active",
+            )
+            .build();
+
+        settings
+            .bind(
+                "high-contrast-indicators",
+                &widget
+                    .high_contrast_indicators
+                    .activatable_widget()
+                    .unwrap(),
+                "active",
+            )
+            .build();
+    }
+
+    fn set_pending_scrobbles(&self, count: usize) {
+        let row = &self.imp().pending_scrobbles;
+        row.set_visible(count > 0);
+        row.set_subtitle(&display::pending_scrobbles_label(count));
     }
 
-    fn connect_theme_select(&self) {
+    // Appearance settings apply right away, the others when the window is closed
+    fn connect_appearance_changed<F>(&self, f: F)
+    where
+        F: Fn() + Clone + 'static,
+    {
         let widget = self.imp();
         let theme = widget.theme.downcast_ref::<libadwaita::ComboRow>().unwrap();
-        theme.connect_selected_notify(|theme| {
-            debug!("Theme switched! --> value: {}", theme.selected());
-            let manager = libadwaita::StyleManager::default();
+        theme.connect_selected_notify(clone!(@strong f => move |_| f()));
 
-            let pref = match theme.selected() {
-                0 => libadwaita::ColorScheme::ForceLight,
-                1 => libadwaita::ColorScheme::ForceDark,
-                _ => libadwaita::ColorScheme::Default,
-            };
+        let use_artwork_colors = widget
+            .use_artwork_colors
+            .downcast_ref::<libadwaita::ActionRow>()
+            .unwrap()
+            .activatable_widget()
+            .unwrap();
+        use_artwork_colors
+            .connect_notify_local(Some("active"), clone!(@strong f => move |_, _| f()));
 
-            manager.set_color_scheme(pref);
-        });
+        let high_contrast_indicators = widget
+            .high_contrast_indicators
+            .activatable_widget()
+            .unwrap();
+        high_contrast_indicators.connect_notify_local(Some("active"), move |_, _| f());
+    }
+
+    fn connect_night_mode_changed<F>(&self, f: F)
+    where
+        F: Fn() + 'static,
+    {
+        self.imp()
+            .night_mode_scale
+            .connect_value_changed(move |_| f());
     }
 
     fn connect_close<F>(&self, on_close: F)
@@ -253,7 +640,12 @@ pub struct Settings {
 impl Settings {
     pub fn new(parent: gtk::Window, model: SettingsModel) -> Self {
         let settings_window = SettingsWindow::new();
+        let model = Rc::new(model);
 
+        settings_window
+            .connect_appearance_changed(clone!(@weak model => move || model.set_appearance()));
+        settings_window
+            .connect_night_mode_changed(clone!(@weak model => move || model.set_night_mode()));
         settings_window.connect_close(move || {
             let new_settings = SpotSettings::new_from_gsettings().unwrap_or_default();
             if model.settings().player_settings != new_settings.player_settings {
@@ -273,6 +665,9 @@ impl Settings {
     }
 
     pub fn show_self(&self) {
+        // Listens that couldn't be submitted yet (offline...)
+        self.settings_window
+            .set_pending_scrobbles(ScrobbleSpool::pending_count());
         self.window().set_transient_for(Some(&self.parent));
         self.window().set_modal(true);
         self.window().set_visible(true);
@@ -25,6 +25,16 @@ impl SettingsModel {
             .dispatch(SettingsAction::ChangeSettings.into());
     }
 
+    pub fn set_appearance(&self) {
+        self.dispatcher
+            .dispatch(SettingsAction::ChangeAppearance.into());
+    }
+
+    pub fn set_night_mode(&self) {
+        self.dispatcher
+            .dispatch(SettingsAction::ChangeNightMode.into());
+    }
+
     pub fn settings(&self) -> SpotSettings {
         let state = self.app_model.get_state();
         state.settings.settings.clone()
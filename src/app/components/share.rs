@@ -0,0 +1,172 @@
+use gdk::prelude::*;
+use gettextrs::*;
+use gio::SimpleAction;
+use gtk::prelude::*;
+use qrcode::{Color, QrCode};
+
+use crate::app::components::labels;
+use crate::app::SpotifyUri;
+
+// Size of the QR code, in pixels
+const QR_CODE_SIZE: i32 = 240;
+// The blank margin scanners need around the code, in modules
+const QR_CODE_QUIET_ZONE: usize = 2;
+
+// Something that can be shared: an album, an artist, a playlist or a track
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Shareable {
+    pub uri: SpotifyUri,
+    pub title: String,
+    // Who made it: the artists of an album or track, the owner of a playlist
+    pub by: Option<String>,
+}
+
+impl Shareable {
+    pub fn new(uri: SpotifyUri, title: String, by: Option<String>) -> Self {
+        Self { uri, title, by }
+    }
+
+    // "Artist – Title"
+    pub fn text(&self) -> String {
+        match self.by.as_ref() {
+            Some(by) => format!("{} – {}", by, self.title),
+            None => self.title.clone(),
+        }
+    }
+}
+
+fn copy_to_clipboard(text: &str) {
+    let clipboard = gdk::Display::default().unwrap().clipboard();
+    clipboard
+        .set_content(Some(&gdk::ContentProvider::for_value(&text.to_value())))
+        .expect("Failed to set clipboard content");
+}
+
+fn qr_code_area(data: &str) -> Option<gtk::DrawingArea> {
+    let code = QrCode::new(data.as_bytes()).ok()?;
+    let width = code.width();
+    let colors = code.to_colors();
+
+    let area = gtk::DrawingArea::builder()
+        .content_width(QR_CODE_SIZE)
+        .content_height(QR_CODE_SIZE)
+        .build();
+    area.set_draw_func(move |_, cr, w, h| {
+        let modules = (width + 2 * QR_CODE_QUIET_ZONE) as f64;
+        let size = (f64::from(w.min(h)) / modules).floor();
+        let x0 = (f64::from(w) - size * modules) / 2.0;
+        let y0 = (f64::from(h) - size * modules) / 2.0;
+
+        // Always dark on light, whatever the theme: not all scanners deal with inverted codes
+        cr.set_source_rgb(1.0, 1.0, 1.0);
+        cr.rectangle(x0, y0, size * modules, size * modules);
+        let _ = cr.fill();
+
+        cr.set_source_rgb(0.0, 0.0, 0.0);
+        for (i, color) in colors.iter().enumerate() {
+            if *color == Color::Dark {
+                let x = (i % width + QR_CODE_QUIET_ZONE) as f64;
+                let y = (i / width + QR_CODE_QUIET_ZONE) as f64;
+                cr.rectangle(x0 + x * size, y0 + y * size, size, size);
+            }
+        }
+        let _ = cr.fill();
+    });
+    Some(area)
+}
+
+// Shows a QR code of the link in a popover, to open it on a phone
+fn show_qr_code(parent: &gtk::Widget, shareable: &Shareable) {
+    let area = match qr_code_area(&shareable.uri.web_url()) {
+        Some(area) => area,
+        None => return,
+    };
+
+    let title = gtk::Label::builder()
+        .label(&shareable.text())
+        .ellipsize(gtk::pango::EllipsizeMode::End)
+        .max_width_chars(30)
+        .build();
+    title.add_css_class("heading");
+
+    let content = gtk::Box::builder()
+        .orientation(gtk::Orientation::Vertical)
+        .spacing(12)
+        .margin_top(6)
+        .margin_bottom(6)
+        .margin_start(6)
+        .margin_end(6)
+        .build();
+    content.append(&title);
+    content.append(&area);
+
+    let popover = gtk::Popover::builder().child(&content).build();
+    popover.set_parent(parent);
+    popover.connect_closed(|popover| popover.unparent());
+    popover.popup();
+}
+
+fn share_menu() -> gio::Menu {
+    let menu = gio::Menu::new();
+    menu.append(Some(&*labels::COPY_LINK), Some("share.copy_link"));
+    // translators: This is part of the Share menu, to copy the URI (spotify:album:...) of an album, artist, playlist or track, which opens it in Spotify apps.
+    menu.append(Some(&gettext("Copy Spotify URI")), Some("share.copy_uri"));
+    // translators: This is part of the Share menu, to copy a text like "Artist – Title" describing an album, artist, playlist or track.
+    menu.append(Some(&gettext("Copy as text")), Some("share.copy_text"));
+    // translators: This is part of the Share menu, to show a QR code of the link to an album, artist, playlist or track, to scan it with a phone.
+    menu.append(Some(&gettext("Show QR code")), Some("share.show_qr_code"));
+    menu
+}
+
+// Gives that button a Share menu, for whatever shareable returns when the menu is used
+pub fn bind_share_menu<F>(button: &gtk::MenuButton, shareable: F)
+where
+    F: Fn() -> Option<Shareable> + Clone + 'static,
+{
+    let group = gio::SimpleActionGroup::new();
+
+    let copy_action = |name: &str, text: fn(&Shareable) -> String| {
+        let action = SimpleAction::new(name, None);
+        let shareable = shareable.clone();
+        action.connect_activate(move |_, _| {
+            if let Some(shareable) = shareable() {
+                copy_to_clipboard(&text(&shareable));
+            }
+        });
+        action
+    };
+    group.add_action(&copy_action("copy_link", |s| s.uri.web_url()));
+    group.add_action(&copy_action("copy_uri", |s| s.uri.to_uri()));
+    group.add_action(&copy_action("copy_text", Shareable::text));
+
+    let show_qr = SimpleAction::new("show_qr_code", None);
+    show_qr.connect_activate(clone!(@weak button => move |_, _| {
+        if let Some(shareable) = shareable() {
+            show_qr_code(button.upcast_ref(), &shareable);
+        }
+    }));
+    group.add_action(&show_qr);
+
+    button.insert_action_group("share", Some(&group));
+    button.set_menu_model(Some(&share_menu()));
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_shareable_text() {
+        let id = "6rqhFgbbKwnb9MLmUQDhG6".to_string();
+        let album = Shareable::new(
+            SpotifyUri::Album(id.clone()),
+            "Title".to_string(),
+            Some("Artist".to_string()),
+        );
+        assert_eq!(album.text(), "Artist – Title");
+
+        let artist = Shareable::new(SpotifyUri::Artist(id), "Artist".to_string(), None);
+        assert_eq!(artist.text(), "Artist");
+    }
+}
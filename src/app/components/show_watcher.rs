@@ -0,0 +1,146 @@
+use std::cell::Cell;
+use std::rc::Rc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::app::components::EventListener;
+use crate::app::state::{LoginEvent, PlaybackEvent};
+use crate::app::{ActionDispatcher, AppAction, AppEvent, AppModel, Worker};
+
+// Spot can stay open for days: every so often, we see if a check is due
+const DUE_CHECK_INTERVAL_SECS: u32 = 60 * 60;
+// Episodes newer than these are all we keep track of
+const EPISODES_LIMIT: usize = 50;
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+struct Checker {
+    app_model: Rc<AppModel>,
+    dispatcher: Box<dyn ActionDispatcher>,
+    worker: Worker,
+    checking: Cell<bool>,
+}
+
+impl Checker {
+    // Fetches the followed shows, then the latest episodes of each of them if a check is due
+    fn check(self: &Rc<Self>) {
+        let state = self.app_model.get_state();
+        if state.logged_user.user.is_none() || !state.online || self.checking.get() {
+            return;
+        }
+        let is_due = state.saved_shows.progress.is_check_due(now());
+        drop(state);
+        self.checking.set(true);
+
+        let checker = Rc::clone(self);
+        let api = self.app_model.get_spotify();
+        self.worker.send_local_task(async move {
+            match api.get_saved_shows().await {
+                Ok(shows) => {
+                    let ids: Vec<String> = shows.iter().map(|show| show.id.clone()).collect();
+                    checker.dispatcher.dispatch(AppAction::SetSavedShows(shows));
+                    if is_due {
+                        for id in ids {
+                            match api.get_show_episodes(&id, EPISODES_LIMIT).await {
+                                Ok(episodes) => checker
+                                    .dispatcher
+                                    .dispatch(AppAction::RecordEpisodes(id, episodes)),
+                                Err(e) => warn!("Couldn't check episodes of {}: {:?}", id, e),
+                            }
+                        }
+                        checker
+                            .dispatcher
+                            .dispatch(AppAction::SetEpisodesChecked(now()));
+                    }
+                }
+                Err(e) => warn!("Couldn't fetch saved shows: {:?}", e),
+            }
+            checker.checking.set(false);
+        });
+    }
+}
+
+// Keeps the shows (podcasts) the user follows in the sidebar, and looks for new episodes every few
+// hours. Episodes count as unplayed until they're played in Spot, or the whole show is marked as played.
+pub struct ShowWatcher {
+    app_model: Rc<AppModel>,
+    checker: Rc<Checker>,
+    timeout: Option<glib::SourceId>,
+}
+
+impl ShowWatcher {
+    pub fn new(
+        app_model: Rc<AppModel>,
+        dispatcher: Box<dyn ActionDispatcher>,
+        worker: Worker,
+    ) -> Self {
+        let checker = Rc::new(Checker {
+            app_model: Rc::clone(&app_model),
+            dispatcher,
+            worker,
+            checking: Cell::new(false),
+        });
+        Self {
+            app_model,
+            checker,
+            timeout: None,
+        }
+    }
+
+    fn start(&mut self) {
+        self.checker.check();
+        if self.timeout.is_some() {
+            return;
+        }
+        let checker = Rc::clone(&self.checker);
+        self.timeout = Some(glib::timeout_add_seconds_local(
+            DUE_CHECK_INTERVAL_SECS,
+            move || {
+                if checker
+                    .app_model
+                    .get_state()
+                    .saved_shows
+                    .progress
+                    .is_check_due(now())
+                {
+                    checker.check();
+                }
+                glib::Continue(true)
+            },
+        ));
+    }
+
+    fn mark_played(&self, id: &str) {
+        let is_unplayed = self
+            .app_model
+            .get_state()
+            .saved_shows
+            .progress
+            .is_unplayed(id);
+        if is_unplayed {
+            self.checker
+                .dispatcher
+                .dispatch(AppAction::MarkEpisodePlayed(id.to_string()));
+        }
+    }
+
+    fn save(&self) {
+        self.app_model.get_state().saved_shows.progress.save();
+    }
+}
+
+impl EventListener for ShowWatcher {
+    fn on_event(&mut self, event: &AppEvent) {
+        match event {
+            AppEvent::LoginEvent(LoginEvent::LoginCompleted(_)) => self.start(),
+            AppEvent::ConnectivityChanged(true) => self.checker.check(),
+            AppEvent::PlaybackEvent(PlaybackEvent::TrackChanged(id)) => self.mark_played(id),
+            AppEvent::SavedShowsUpdated => self.save(),
+            _ => {}
+        }
+    }
+}
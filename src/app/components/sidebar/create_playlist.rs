@@ -47,6 +47,13 @@ impl CreatePlaylistPopover {
         glib::Object::new()
     }
 
+    // A name to start from, selected so that typing replaces it
+    pub fn set_name(&self, name: &str) {
+        let entry = &self.imp().entry;
+        entry.set_text(name);
+        entry.select_region(0, -1);
+    }
+
     pub fn connect_create<F: Clone + Fn(String) + 'static>(&self, create_fun: F) {
         let entry = self.imp().entry.get();
         let closure = clone!(@weak self as popover, @weak entry, @strong create_fun => move || {
@@ -5,4 +5,6 @@ mod sidebar_item;
 pub use sidebar_item::*;
 
 mod create_playlist;
+pub use create_playlist::CreatePlaylistPopover;
+
 mod sidebar_row;
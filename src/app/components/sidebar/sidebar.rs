@@ -1,25 +1,40 @@
 use gettextrs::gettext;
+use gio::{SimpleAction, SimpleActionGroup};
 use gtk::prelude::*;
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, HashSet};
 use std::rc::Rc;
 
 use super::create_playlist::CreatePlaylistPopover;
 use super::{
     sidebar_row::SidebarRow, SidebarDestination, SidebarItem, CREATE_PLAYLIST_ITEM,
-    SAVED_PLAYLISTS_SECTION,
+    PLAYLIST_FOLDER, SAVED_PLAYLISTS_SECTION, SHOWS_SECTION,
 };
-use crate::app::models::{AlbumModel, PlaylistSummary};
-use crate::app::state::ScreenName;
+use crate::app::components::{labels, utils};
+use crate::app::models::{
+    AlbumModel, PlaylistLabel, PlaylistSummary, RootlistItem, PLAYLIST_LABEL_COLORS,
+};
+use crate::app::state::{PendingChange, ScreenName, SettingsAction};
 use crate::app::{
     ActionDispatcher, AppAction, AppEvent, AppModel, BrowserAction, BrowserEvent, Component,
-    EventListener,
+    EventListener, Prefetcher,
 };
+use crate::settings::PlaylistLabels;
 
-const NUM_FIXED_ENTRIES: u32 = 6;
-const NUM_PLAYLISTS: usize = 20;
+const NUM_FIXED_ENTRIES: u32 = 7;
+// Where the New Releases entry is, among the fixed entries
+const NEW_RELEASES_POSITION: u32 = 1;
+// Where the shows go (if the user follows any), between the fixed entries and above the playlists
+const SHOWS_POSITION: u32 = 4;
 
 pub struct SidebarModel {
     app_model: Rc<AppModel>,
     dispatcher: Box<dyn ActionDispatcher>,
+    prefetcher: Prefetcher,
+    expanded_folders: RefCell<HashSet<String>>,
+    labels: RefCell<PlaylistLabels>,
+    // How many rows the shows take, their section included
+    show_rows: Cell<u32>,
 }
 
 impl SidebarModel {
@@ -27,20 +42,205 @@ impl SidebarModel {
         Self {
             app_model,
             dispatcher,
+            prefetcher: Prefetcher::new(),
+            expanded_folders: Default::default(),
+            labels: RefCell::new(PlaylistLabels::new_from_gsettings()),
+            show_rows: Cell::new(0),
         }
     }
 
-    fn get_playlists(&self) -> Vec<SidebarDestination> {
+    fn unread_releases_count(&self) -> usize {
         self.app_model
-            .get_state()
+            .map_state_opt(|s| s.browser.home_state())
+            .map(|s| s.unread_releases.len())
+            .unwrap_or(0)
+    }
+
+    // The followed shows under their own section, with how many episodes weren't played yet
+    fn get_shows(&self) -> Vec<SidebarItem> {
+        let state = self.app_model.get_state();
+        let saved_shows = &state.saved_shows;
+        if saved_shows.shows.is_empty() {
+            return vec![];
+        }
+        let mut items = vec![SidebarItem::shows_section()];
+        items.extend(saved_shows.shows.iter().map(|show| {
+            let item = SidebarItem::from_destination(SidebarDestination::Show {
+                id: show.id.clone(),
+                title: show.title.clone(),
+            });
+            item.set_badge(saved_shows.progress.unplayed_count(&show.id) as u32);
+            item
+        }));
+        items
+    }
+
+    fn mark_show_played(&self, show_id: String) {
+        self.dispatcher.dispatch(AppAction::MarkShowPlayed(show_id));
+    }
+
+    fn get_playlists(&self) -> Vec<SidebarItem> {
+        let state = self.app_model.get_state();
+        let home_state = state
             .browser
             .home_state()
-            .expect("expected HomeState to be available")
+            .expect("expected HomeState to be available");
+
+        // Folders are ignored when grouping by label
+        if self.sort_by_label() {
+            let labels = self.labels.borrow();
+            let mut playlists: Vec<(Option<&PlaylistLabel>, AlbumModel)> = home_state
+                .playlists
+                .iter()
+                .map(|playlist| (labels.labels.get(&playlist.uri()), playlist))
+                .collect();
+            // Unlabeled playlists last
+            playlists.sort_by(|(a, _), (b, _)| {
+                (a.is_none(), a.map(|l| l.sort_key())).cmp(&(b.is_none(), b.map(|l| l.sort_key())))
+            });
+            return playlists
+                .into_iter()
+                .map(|(_, playlist)| self.make_playlist_item(playlist, 0))
+                .collect();
+        }
+
+        let mut remaining: HashMap<String, AlbumModel> = home_state
             .playlists
             .iter()
-            .take(NUM_PLAYLISTS)
-            .map(Self::map_to_destination)
-            .collect()
+            .map(|playlist| (playlist.uri(), playlist))
+            .collect();
+
+        let mut items = vec![];
+        self.add_rootlist_items(&home_state.rootlist, 0, true, &mut remaining, &mut items);
+
+        // Playlists we couldn't find in the rootlist (or all of them, if we don't know about folders)
+        items.extend(
+            home_state
+                .playlists
+                .iter()
+                .filter(|playlist| remaining.contains_key(&playlist.uri()))
+                .map(|playlist| self.make_playlist_item(playlist, 0)),
+        );
+        items
+    }
+
+    // Playlists are only listed once they've been loaded, as that's how we know their title
+    fn add_rootlist_items(
+        &self,
+        rootlist: &[RootlistItem],
+        depth: u32,
+        visible: bool,
+        remaining: &mut HashMap<String, AlbumModel>,
+        items: &mut Vec<SidebarItem>,
+    ) {
+        for rootlist_item in rootlist {
+            match rootlist_item {
+                RootlistItem::Playlist(id) => {
+                    if let Some(playlist) = remaining.remove(id).filter(|_| visible) {
+                        items.push(self.make_playlist_item(playlist, depth));
+                    }
+                }
+                RootlistItem::Folder(folder) => {
+                    let expanded = self.expanded_folders.borrow().contains(&folder.id);
+                    if visible {
+                        let item = SidebarItem::playlist_folder(&folder.id, &folder.name, expanded);
+                        item.set_depth(depth);
+                        items.push(item);
+                    }
+                    self.add_rootlist_items(
+                        &folder.items,
+                        depth + 1,
+                        visible && expanded,
+                        remaining,
+                        items,
+                    );
+                }
+            }
+        }
+    }
+
+    fn make_playlist_item(&self, playlist: AlbumModel, depth: u32) -> SidebarItem {
+        let label = self.labels.borrow().labels.get(&playlist.uri()).cloned();
+        let item = SidebarItem::from_destination(Self::map_to_destination(playlist));
+        item.set_depth(depth);
+        item.set_label(label.map(|l| l.serialize()).unwrap_or_default());
+        item
+    }
+
+    fn set_label(&self, playlist_id: String, label: Option<PlaylistLabel>) {
+        let mut labels = self.labels.borrow_mut();
+        match label {
+            Some(label) => labels.labels.insert(playlist_id, label),
+            None => labels.labels.remove(&playlist_id),
+        };
+        labels.save();
+    }
+
+    fn sort_by_label(&self) -> bool {
+        self.labels.borrow().sort_by_label
+    }
+
+    fn toggle_sort_by_label(&self) {
+        let mut labels = self.labels.borrow_mut();
+        labels.sort_by_label = !labels.sort_by_label;
+        labels.save();
+    }
+
+    fn is_scrobbled(&self, playlist_id: &str) -> bool {
+        !self
+            .app_model
+            .get_state()
+            .settings
+            .settings
+            .scrobble
+            .ignored_playlists
+            .contains(playlist_id)
+    }
+
+    fn toggle_scrobbled(&self, playlist_id: String) {
+        let mut scrobble = self
+            .app_model
+            .get_state()
+            .settings
+            .settings
+            .scrobble
+            .clone();
+        if !scrobble.ignored_playlists.remove(&playlist_id) {
+            scrobble.ignored_playlists.insert(playlist_id);
+        }
+        scrobble.save_ignored_playlists();
+        self.dispatcher
+            .dispatch(SettingsAction::ChangeSettings.into());
+    }
+
+    fn toggle_folder(&self, folder_id: String) {
+        let mut expanded_folders = self.expanded_folders.borrow_mut();
+        if !expanded_folders.remove(&folder_id) {
+            expanded_folders.insert(folder_id);
+        }
+    }
+
+    // The list was updated, with the pages fetched so far
+    pub fn pages_added(&self) {
+        self.prefetcher.release_fetched();
+    }
+
+    fn load_more_playlists(&self) -> Option<()> {
+        let api = self.app_model.get_spotify();
+
+        let state = self.app_model.get_state();
+        let next_page = &state.browser.home_state()?.next_playlists_page;
+        let batch_size = next_page.batch_size;
+        let offset = next_page.next_offset?;
+
+        self.dispatcher
+            .call_spotify_and_prefetch(&self.prefetcher, offset, move || async move {
+                api.get_saved_playlists(offset, batch_size)
+                    .await
+                    .map(|playlists| BrowserAction::AppendPlaylistsContent(playlists).into())
+            });
+
+        Some(())
     }
 
     fn map_to_destination(a: AlbumModel) -> SidebarDestination {
@@ -62,9 +262,27 @@ impl SidebarModel {
             })
     }
 
+    fn is_owned(&self, playlist_id: &str) -> bool {
+        let state = self.app_model.get_state();
+        state
+            .logged_user
+            .playlists
+            .iter()
+            .any(|p| p.id == playlist_id)
+    }
+
+    // Can be undone for a little while (see PendingChangesCommitter)
+    fn delete_playlist(&self, playlist: PlaylistSummary) {
+        self.dispatcher
+            .dispatch(AppAction::DeferChange(PendingChange::DeletePlaylist(
+                playlist,
+            )));
+    }
+
     fn navigate(&self, dest: SidebarDestination) {
         let actions = match dest {
             SidebarDestination::Library
+            | SidebarDestination::NewReleases
             | SidebarDestination::SavedTracks
             | SidebarDestination::NowPlaying
             | SidebarDestination::SavedPlaylists => {
@@ -76,6 +294,10 @@ impl SidebarModel {
             SidebarDestination::Playlist(PlaylistSummary { id, .. }) => {
                 vec![AppAction::ViewPlaylist(id)]
             }
+            SidebarDestination::Show { id, .. } => {
+                utils::open_in_browser(&utils::web_player_url("show", &id));
+                vec![]
+            }
         };
         self.dispatcher.dispatch_many(actions);
     }
@@ -95,6 +317,9 @@ impl Sidebar {
         let list_store = gio::ListStore::new(SidebarItem::static_type());
 
         list_store.append(&SidebarItem::from_destination(SidebarDestination::Library));
+        list_store.append(&SidebarItem::from_destination(
+            SidebarDestination::NewReleases,
+        ));
         list_store.append(&SidebarItem::from_destination(
             SidebarDestination::SavedTracks,
         ));
@@ -109,32 +334,47 @@ impl Sidebar {
 
         listbox.bind_model(
             Some(&list_store),
-            clone!(@weak popover => @default-panic, move |obj| {
+            clone!(@weak popover, @weak model, @weak list_store => @default-panic, move |obj| {
                 let item = obj.downcast_ref::<SidebarItem>().unwrap();
                 if item.navigatable() {
-                    Self::make_navigatable(item)
+                    match item.destination() {
+                        Some(SidebarDestination::Playlist(playlist)) => {
+                            Self::make_playlist(item, playlist, &model, &list_store)
+                        }
+                        Some(SidebarDestination::Show { id, .. }) => {
+                            Self::make_show(item, id, &model)
+                        }
+                        _ => Self::make_navigatable(item),
+                    }
                 } else {
                     match item.id().as_str() {
-                        SAVED_PLAYLISTS_SECTION => Self::make_section_label(item),
+                        SAVED_PLAYLISTS_SECTION | SHOWS_SECTION => Self::make_section_label(item),
                         CREATE_PLAYLIST_ITEM => Self::make_create_playlist(item, popover),
+                        PLAYLIST_FOLDER => Self::make_folder(item),
                         _ => unimplemented!(),
                     }
                 }
             }),
         );
 
-        listbox.connect_row_activated(clone!(@weak popover, @weak model => move |_, row| {
-            if let Some(row) = row.downcast_ref::<SidebarRow>() {
-                if let Some(dest) = row.item().destination() {
-                    model.navigate(dest);
-                } else {
-                    match row.item().id().as_str() {
-                        CREATE_PLAYLIST_ITEM => popover.popup(),
-                        _ => unimplemented!()
+        listbox.connect_row_activated(
+            clone!(@weak popover, @weak model, @weak list_store => move |_, row| {
+                if let Some(row) = row.downcast_ref::<SidebarRow>() {
+                    if let Some(dest) = row.item().destination() {
+                        model.navigate(dest);
+                    } else {
+                        match row.item().id().as_str() {
+                            CREATE_PLAYLIST_ITEM => popover.popup(),
+                            PLAYLIST_FOLDER => {
+                                model.toggle_folder(row.item().data());
+                                Self::update_playlists(&list_store, &model);
+                            }
+                            _ => unimplemented!()
+                        }
                     }
                 }
-            }
-        }));
+            }),
+        );
 
         Self {
             listbox,
@@ -149,6 +389,166 @@ impl Sidebar {
         row.upcast()
     }
 
+    // Playlist rows get a context menu to assign them a label, keep them from being scrobbled, or delete them
+    fn make_playlist(
+        item: &SidebarItem,
+        playlist: PlaylistSummary,
+        model: &Rc<SidebarModel>,
+        list_store: &gio::ListStore,
+    ) -> gtk::Widget {
+        let playlist_id = playlist.id.clone();
+        let row = SidebarRow::new(item.clone());
+        row.set_selectable(false);
+
+        let emoji_chooser = gtk::EmojiChooser::new();
+        row.add_popover(&emoji_chooser);
+        emoji_chooser.connect_emoji_picked(
+            clone!(@weak model, @weak list_store, @strong playlist_id => move |_, emoji| {
+                model.set_label(playlist_id.clone(), Some(PlaylistLabel::Emoji(emoji.to_string())));
+                Self::update_playlists(&list_store, &model);
+            }),
+        );
+
+        let set_color = SimpleAction::new("set_color", Some(glib::VariantTy::STRING));
+        set_color.connect_activate(
+            clone!(@weak model, @weak list_store, @strong playlist_id => move |_, color| {
+                let color = color.and_then(|c| c.get::<String>()).unwrap_or_default();
+                model.set_label(playlist_id.clone(), Some(PlaylistLabel::Color(color)));
+                Self::update_playlists(&list_store, &model);
+            }),
+        );
+
+        let pick_emoji = SimpleAction::new("pick_emoji", None);
+        pick_emoji.connect_activate(clone!(@weak emoji_chooser => move |_, _| {
+            emoji_chooser.popup();
+        }));
+
+        let clear = SimpleAction::new("clear", None);
+        clear.connect_activate(
+            clone!(@weak model, @weak list_store, @strong playlist_id => move |_, _| {
+                model.set_label(playlist_id.clone(), None);
+                Self::update_playlists(&list_store, &model);
+            }),
+        );
+
+        let sort = SimpleAction::new_stateful("sort", None, model.sort_by_label().to_variant());
+        sort.connect_activate(clone!(@weak model, @weak list_store => move |_, _| {
+            model.toggle_sort_by_label();
+            Self::update_playlists(&list_store, &model);
+        }));
+
+        let group = SimpleActionGroup::new();
+        group.add_action(&set_color);
+        group.add_action(&pick_emoji);
+        group.add_action(&clear);
+        group.add_action(&sort);
+        row.insert_action_group("label", Some(&group));
+
+        let is_owned = model.is_owned(&playlist_id);
+        let delete = SimpleAction::new("delete", None);
+        delete.connect_activate(clone!(@weak model => move |_, _| {
+            model.delete_playlist(playlist.clone());
+        }));
+        let scrobble = SimpleAction::new_stateful(
+            "scrobble",
+            None,
+            model.is_scrobbled(&playlist_id).to_variant(),
+        );
+        scrobble.connect_activate(
+            clone!(@weak model, @strong playlist_id => move |action, _| {
+                model.toggle_scrobbled(playlist_id.clone());
+                action.set_state(model.is_scrobbled(&playlist_id).to_variant());
+            }),
+        );
+        let playlist_group = SimpleActionGroup::new();
+        playlist_group.add_action(&delete);
+        playlist_group.add_action(&scrobble);
+        row.insert_action_group("playlist", Some(&playlist_group));
+
+        let colors = gio::Menu::new();
+        for color in PLAYLIST_LABEL_COLORS {
+            colors.append(
+                Some(&labels::playlist_label_color(color)),
+                Some(&format!("label.set_color::{color}")),
+            );
+        }
+        let others = gio::Menu::new();
+        // translators: This is part of the contextual menu of a playlist in the sidebar, it opens an emoji picker to label the playlist.
+        others.append(Some(&gettext("Emoji…")), Some("label.pick_emoji"));
+        // translators: This is part of the contextual menu of a playlist in the sidebar, it removes the label (color or emoji) of the playlist.
+        others.append(Some(&gettext("Remove label")), Some("label.clear"));
+        let sorting = gio::Menu::new();
+        // translators: This is part of the contextual menu of a playlist in the sidebar, it toggles grouping playlists by label.
+        sorting.append(Some(&gettext("Group by label")), Some("label.sort"));
+        let scrobbling = gio::Menu::new();
+        // translators: This is part of the contextual menu of a playlist in the sidebar, it toggles submitting what's played from it to ListenBrainz.
+        scrobbling.append(Some(&gettext("Scrobble")), Some("playlist.scrobble"));
+
+        let deletion = gio::Menu::new();
+        let delete_label = if is_owned {
+            // translators: This is part of the contextual menu of a playlist (owned by the user) in the sidebar. The playlist can be recovered later.
+            gettext("Delete")
+        } else {
+            // translators: This is part of the contextual menu of a playlist (owned by someone else) in the sidebar. It unfollows the playlist.
+            gettext("Remove from library")
+        };
+        deletion.append(Some(&delete_label), Some("playlist.delete"));
+
+        let menu = gio::Menu::new();
+        menu.append_section(None, &colors);
+        menu.append_section(None, &others);
+        menu.append_section(None, &sorting);
+        menu.append_section(None, &scrobbling);
+        menu.append_section(None, &deletion);
+
+        let popover = gtk::PopoverMenu::from_model(Some(&menu));
+        popover.set_has_arrow(false);
+        row.add_popover(&popover);
+
+        let gesture = gtk::GestureClick::new();
+        gesture.set_button(gdk::BUTTON_SECONDARY);
+        gesture.connect_pressed(clone!(@weak popover => move |_, _, x, y| {
+            popover.set_pointing_to(Some(&gdk::Rectangle::new(x as i32, y as i32, 1, 1)));
+            popover.popup();
+        }));
+        row.add_controller(gesture);
+
+        row.upcast()
+    }
+
+    // Show rows get a context menu to mark all their episodes as played
+    fn make_show(item: &SidebarItem, show_id: String, model: &Rc<SidebarModel>) -> gtk::Widget {
+        let row = SidebarRow::new(item.clone());
+        row.set_selectable(false);
+
+        let mark_played = SimpleAction::new("mark_played", None);
+        mark_played.connect_activate(clone!(@weak model => move |_, _| {
+            model.mark_show_played(show_id.clone());
+        }));
+        mark_played.set_enabled(item.badge() > 0);
+        let group = SimpleActionGroup::new();
+        group.add_action(&mark_played);
+        row.insert_action_group("show", Some(&group));
+
+        let menu = gio::Menu::new();
+        // translators: This is part of the contextual menu of a show (podcast) in the sidebar, it clears the count of new episodes.
+        menu.append(Some(&gettext("Mark as Played")), Some("show.mark_played"));
+
+        let popover = gtk::PopoverMenu::from_model(Some(&menu));
+        popover.set_has_arrow(false);
+        row.add_popover(&popover);
+
+        let gesture = gtk::GestureClick::new();
+        gesture.set_button(gdk::BUTTON_SECONDARY);
+        gesture.connect_pressed(clone!(@weak popover => move |_, _, x, y| {
+            popover.set_pointing_to(Some(&gdk::Rectangle::new(x as i32, y as i32, 1, 1)));
+            popover.popup();
+        }));
+        row.add_controller(gesture);
+
+        row.upcast()
+    }
+
     fn make_section_label(item: &SidebarItem) -> gtk::Widget {
         let label = gtk::Label::new(Some(item.title().as_str()));
         label.add_css_class("caption-heading");
@@ -170,19 +570,40 @@ impl Sidebar {
         row.upcast()
     }
 
-    fn update_playlists_in_sidebar(&self) {
-        let playlists: Vec<SidebarItem> = self
-            .model
-            .get_playlists()
-            .into_iter()
-            .map(SidebarItem::from_destination)
-            .collect();
-        self.list_store.splice(
-            NUM_FIXED_ENTRIES,
-            self.list_store.n_items() - NUM_FIXED_ENTRIES,
+    fn make_folder(item: &SidebarItem) -> gtk::Widget {
+        let row = SidebarRow::new(item.clone());
+        row.set_activatable(true);
+        row.set_selectable(false);
+        row.upcast()
+    }
+
+    fn update_playlists(list_store: &gio::ListStore, model: &SidebarModel) {
+        let playlists = model.get_playlists();
+        let position = NUM_FIXED_ENTRIES + model.show_rows.get();
+        list_store.splice(
+            position,
+            list_store.n_items() - position,
             playlists.as_slice(),
         );
     }
+
+    fn update_shows(&self) {
+        let shows = self.model.get_shows();
+        self.list_store
+            .splice(SHOWS_POSITION, self.model.show_rows.get(), shows.as_slice());
+        self.model.show_rows.set(shows.len() as u32);
+    }
+
+    fn update_playlists_in_sidebar(&self) {
+        Self::update_playlists(&self.list_store, &self.model);
+    }
+
+    // Rows are built from their item, so the item is replaced for the row to show the new count
+    fn update_new_releases_badge(&self) {
+        let item = SidebarItem::from_destination(SidebarDestination::NewReleases);
+        item.set_badge(self.model.unread_releases_count() as u32);
+        self.list_store.splice(NEW_RELEASES_POSITION, 1, &[item]);
+    }
 }
 
 impl Component for Sidebar {
@@ -193,8 +614,21 @@ impl Component for Sidebar {
 
 impl EventListener for Sidebar {
     fn on_event(&mut self, event: &AppEvent) {
-        if let AppEvent::BrowserEvent(BrowserEvent::SavedPlaylistsUpdated) = event {
-            self.update_playlists_in_sidebar();
+        match event {
+            AppEvent::BrowserEvent(BrowserEvent::SavedPlaylistsUpdated) => {
+                self.model.pages_added();
+                self.update_playlists_in_sidebar();
+                // One page after the other, until the sidebar has all of them: playlists
+                // can be filed away in any folder, scrolling wouldn't necessarily reveal them
+                self.model.load_more_playlists();
+            }
+            AppEvent::BrowserEvent(BrowserEvent::NewReleasesUpdated) => {
+                self.update_new_releases_badge();
+            }
+            AppEvent::SavedShowsUpdated => {
+                self.update_shows();
+            }
+            _ => {}
         }
     }
 }
@@ -6,30 +6,51 @@ use gtk::subclass::prelude::*;
 use crate::app::models::PlaylistSummary;
 
 const LIBRARY: &str = "library";
+const NEW_RELEASES: &str = "new_releases";
 const SAVED_TRACKS: &str = "saved_tracks";
 const NOW_PLAYING: &str = "now_playing";
 const SAVED_PLAYLISTS: &str = "saved_playlists";
 const PLAYLIST: &str = "playlist";
+const SHOW: &str = "show";
+pub const SHOWS_SECTION: &str = "shows_section";
 pub const SAVED_PLAYLISTS_SECTION: &str = "saved_playlists_section";
 pub const CREATE_PLAYLIST_ITEM: &str = "create_playlist";
+pub const PLAYLIST_FOLDER: &str = "playlist_folder";
 
 #[derive(Debug)]
 pub enum SidebarDestination {
     Library,
+    NewReleases,
     SavedTracks,
     NowPlaying,
     SavedPlaylists,
     Playlist(PlaylistSummary),
+    // A podcast, only opened in the web player as Spot can't play them
+    Show { id: String, title: String },
 }
 
 impl SidebarDestination {
+    // The destinations that are pages of the home screen (see HomeState::visible_page)
+    pub fn from_home_page(page: &str) -> Option<Self> {
+        match page {
+            LIBRARY => Some(Self::Library),
+            NEW_RELEASES => Some(Self::NewReleases),
+            SAVED_TRACKS => Some(Self::SavedTracks),
+            NOW_PLAYING => Some(Self::NowPlaying),
+            SAVED_PLAYLISTS => Some(Self::SavedPlaylists),
+            _ => None,
+        }
+    }
+
     pub fn id(&self) -> &'static str {
         match self {
             Self::Library => LIBRARY,
+            Self::NewReleases => NEW_RELEASES,
             Self::SavedTracks => SAVED_TRACKS,
             Self::NowPlaying => NOW_PLAYING,
             Self::SavedPlaylists => SAVED_PLAYLISTS,
             Self::Playlist(_) => PLAYLIST,
+            Self::Show { .. } => SHOW,
         }
     }
 
@@ -37,6 +58,8 @@ impl SidebarDestination {
         match self {
             // translators: This is a sidebar entry to browse to saved albums.
             Self::Library => gettext("Library"),
+            // translators: This is a sidebar entry to browse to the recent releases of the artists the user follows.
+            Self::NewReleases => gettext("New Releases"),
             // translators: This is a sidebar entry to browse to saved tracks.
             Self::SavedTracks => gettext("Saved tracks"),
             // translators: This is a sidebar entry to browse to saved playlists.
@@ -44,16 +67,19 @@ impl SidebarDestination {
             // translators: This is a sidebar entry that marks that the entries below are playlists.
             Self::SavedPlaylists => gettext("Playlists"),
             Self::Playlist(PlaylistSummary { title, .. }) => title.clone(),
+            Self::Show { title, .. } => title.clone(),
         }
     }
 
     pub fn icon(&self) -> &'static str {
         match self {
             Self::Library => "library-music-symbolic",
+            Self::NewReleases => "audio-x-generic-symbolic",
             Self::SavedTracks => "starred-symbolic",
             Self::NowPlaying => "music-queue-symbolic",
             Self::SavedPlaylists => "view-app-grid-symbolic",
             Self::Playlist(_) => "playlist2-symbolic",
+            Self::Show { .. } => "audio-input-microphone-symbolic",
         }
     }
 }
@@ -64,6 +90,7 @@ impl SidebarItem {
             SidebarDestination::Playlist(PlaylistSummary { id, title }) => {
                 (PLAYLIST, Some(id), title)
             }
+            SidebarDestination::Show { id, title } => (SHOW, Some(id), title),
             _ => (dest.id(), None, dest.title()),
         };
         glib::Object::builder()
@@ -83,6 +110,26 @@ impl SidebarItem {
             .build()
     }
 
+    pub fn shows_section() -> Self {
+        glib::Object::builder()
+            .property("id", SHOWS_SECTION)
+            .property("data", &String::new())
+            // translators: This is a sidebar section that lists the shows (podcasts) the user follows.
+            .property("title", &gettext("Shows"))
+            .property("navigatable", false)
+            .build()
+    }
+
+    pub fn playlist_folder(folder_id: &str, name: &str, expanded: bool) -> Self {
+        glib::Object::builder()
+            .property("id", PLAYLIST_FOLDER)
+            .property("data", folder_id)
+            .property("title", name)
+            .property("navigatable", false)
+            .property("expanded", expanded)
+            .build()
+    }
+
     pub fn create_playlist_item() -> Self {
         glib::Object::builder()
             .property("id", CREATE_PLAYLIST_ITEM)
@@ -100,6 +147,7 @@ impl SidebarItem {
             let title = self.title();
             match id.as_str() {
                 LIBRARY => Some(SidebarDestination::Library),
+                NEW_RELEASES => Some(SidebarDestination::NewReleases),
                 SAVED_TRACKS => Some(SidebarDestination::SavedTracks),
                 NOW_PLAYING => Some(SidebarDestination::NowPlaying),
                 SAVED_PLAYLISTS => Some(SidebarDestination::SavedPlaylists),
@@ -107,6 +155,7 @@ impl SidebarItem {
                     id: data,
                     title,
                 })),
+                SHOW => Some(SidebarDestination::Show { id: data, title }),
                 _ => None,
             }
         } else {
@@ -117,6 +166,8 @@ impl SidebarItem {
     pub fn icon(&self) -> Option<&str> {
         match self.id().as_str() {
             CREATE_PLAYLIST_ITEM => Some("list-add-symbolic"),
+            PLAYLIST_FOLDER if self.expanded() => Some("folder-open-symbolic"),
+            PLAYLIST_FOLDER => Some("folder-symbolic"),
             _ => self.destination().map(|d| d.icon()),
         }
     }
@@ -138,6 +189,18 @@ mod imp {
         pub title: RefCell<String>,
         #[property(get, set)]
         pub navigatable: Cell<bool>,
+        // How deep in the playlist folders this item is
+        #[property(get, set)]
+        pub depth: Cell<u32>,
+        // Only relevant for folders
+        #[property(get, set)]
+        pub expanded: Cell<bool>,
+        // Only relevant for playlists, see PlaylistLabel
+        #[property(get, set)]
+        pub label: RefCell<String>,
+        // A count of unread things, shown if not zero
+        #[property(get, set)]
+        pub badge: Cell<u32>,
     }
 
     #[glib::object_subclass]
@@ -3,11 +3,21 @@ use gtk::subclass::prelude::*;
 use gtk::CompositeTemplate;
 
 use super::SidebarItem;
+use crate::app::components::{display_add_css_provider, indicators};
+use crate::app::models::PlaylistLabel;
 
 impl SidebarRow {
     pub fn new(item: SidebarItem) -> Self {
+        display_add_css_provider(resource!("/sidebar/sidebar_row.css"));
         glib::Object::builder().property("item", item).build()
     }
+
+    // The row owns it from then on, and lets go of it when it's disposed
+    pub fn add_popover(&self, popover: &impl IsA<gtk::Popover>) {
+        let popover: &gtk::Popover = popover.upcast_ref();
+        popover.set_parent(self);
+        self.imp().popovers.borrow_mut().push(popover.clone());
+    }
 }
 
 mod imp {
@@ -25,14 +35,39 @@ mod imp {
         #[template_child]
         pub title: TemplateChild<gtk::Label>,
 
+        #[template_child]
+        pub label: TemplateChild<gtk::Label>,
+
         #[property(get, set = Self::set_item)]
         pub item: RefCell<SidebarItem>,
+
+        pub popovers: RefCell<Vec<gtk::Popover>>,
     }
 
     impl SidebarRow {
         fn set_item(&self, item: SidebarItem) {
             self.title.set_text(item.title().as_str());
             self.icon.set_icon_name(item.icon());
+            self.icon.set_margin_start(item.depth() as i32 * 12);
+            match PlaylistLabel::parse(&item.label()) {
+                Some(PlaylistLabel::Color(color)) => {
+                    indicators::set_color_label(&self.label, &color);
+                    self.label.set_visible(true);
+                }
+                Some(PlaylistLabel::Emoji(emoji)) => {
+                    self.label.set_text(&emoji);
+                    self.label.set_tooltip_text(None);
+                    self.label.set_css_classes(&[]);
+                    self.label.set_visible(true);
+                }
+                None if item.badge() > 0 => {
+                    self.label.set_text(&item.badge().to_string());
+                    self.label.set_tooltip_text(None);
+                    self.label.set_css_classes(&["sidebar__badge"]);
+                    self.label.set_visible(true);
+                }
+                None => self.label.set_visible(false),
+            }
             self.obj().set_tooltip_text(Some(item.title().as_str()));
             self.item.replace(item);
         }
@@ -56,7 +91,9 @@ mod imp {
             Self {
                 icon: Default::default(),
                 title: Default::default(),
+                label: Default::default(),
                 item: RefCell::new(glib::Object::new()),
+                popovers: Default::default(),
             }
         }
     }
@@ -73,6 +110,12 @@ mod imp {
         fn property(&self, id: usize, pspec: &glib::ParamSpec) -> glib::Value {
             self.derived_property(id, pspec)
         }
+
+        fn dispose(&self) {
+            for popover in self.popovers.take() {
+                popover.unparent();
+            }
+        }
     }
 
     impl WidgetImpl for SidebarRow {}
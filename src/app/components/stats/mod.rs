@@ -0,0 +1,6 @@
+mod play_log;
+mod play_recorder;
+mod stats;
+
+pub use play_recorder::*;
+pub use stats::*;
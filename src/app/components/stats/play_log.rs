@@ -0,0 +1,257 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+
+// How many artists and tracks make the top
+const TOP_LEN: usize = 10;
+
+// A track that was played
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Play {
+    pub track_id: String,
+    pub title: String,
+    pub artists: Vec<String>,
+    pub album: String,
+    // In seconds since epoch
+    pub played_at: u64,
+    // How long it was actually played for
+    pub played_ms: u64,
+}
+
+// Everything played in Spot, oldest first. Kept on disk, and never leaves the computer.
+#[derive(Default)]
+pub struct PlayLog {
+    path: Option<PathBuf>,
+    plays: Vec<Play>,
+}
+
+impl PlayLog {
+    fn default_path() -> PathBuf {
+        glib::user_data_dir().join("spot").join("plays.json")
+    }
+
+    pub fn load() -> Self {
+        let path = Self::default_path();
+        let plays = fs::read(&path)
+            .ok()
+            .and_then(|content| serde_json::from_slice(&content).ok())
+            .unwrap_or_default();
+        Self {
+            path: Some(path),
+            plays,
+        }
+    }
+
+    pub fn plays(&self) -> &[Play] {
+        &self.plays
+    }
+
+    pub fn push(&mut self, play: Play) {
+        self.plays.push(play);
+        self.save();
+    }
+
+    fn save(&self) {
+        let path = match self.path.as_ref() {
+            Some(path) => path,
+            None => return,
+        };
+        let result = path
+            .parent()
+            .map(fs::create_dir_all)
+            .unwrap_or(Ok(()))
+            .and_then(|_| {
+                let content = serde_json::to_vec(&self.plays).unwrap_or_default();
+                fs::write(path, content)
+            });
+        if let Err(e) = result {
+            warn!("Could not save play log: {}", e);
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StatsPeriod {
+    Week,
+    Month,
+    Year,
+}
+
+impl StatsPeriod {
+    pub fn duration(self) -> Duration {
+        let days = match self {
+            Self::Week => 7,
+            Self::Month => 30,
+            Self::Year => 365,
+        };
+        Duration::from_secs(days * 24 * 60 * 60)
+    }
+}
+
+// An artist or a track, and how much it was played
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TopEntry {
+    pub name: String,
+    // The artists, for tracks
+    pub detail: Option<String>,
+    pub plays: usize,
+    pub listened: Duration,
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct ListeningReport {
+    pub plays: usize,
+    pub listened: Duration,
+    pub top_artists: Vec<TopEntry>,
+    pub top_tracks: Vec<TopEntry>,
+}
+
+// Most played first; ties go to what was listened to the longest
+fn top(entries: HashMap<String, TopEntry>) -> Vec<TopEntry> {
+    let mut entries: Vec<TopEntry> = entries.into_values().collect();
+    entries.sort_by(|a, b| {
+        b.plays
+            .cmp(&a.plays)
+            .then(b.listened.cmp(&a.listened))
+            .then(a.name.cmp(&b.name))
+    });
+    entries.truncate(TOP_LEN);
+    entries
+}
+
+// What was played from the given time on (in seconds since epoch)
+pub fn listening_report(plays: &[Play], since: u64) -> ListeningReport {
+    let mut report = ListeningReport::default();
+    let mut artists: HashMap<String, TopEntry> = HashMap::new();
+    let mut tracks: HashMap<String, TopEntry> = HashMap::new();
+
+    for play in plays.iter().filter(|p| p.played_at >= since) {
+        let listened = Duration::from_millis(play.played_ms);
+        report.plays += 1;
+        report.listened += listened;
+
+        for artist in play.artists.iter() {
+            let entry = artists.entry(artist.clone()).or_insert_with(|| TopEntry {
+                name: artist.clone(),
+                detail: None,
+                plays: 0,
+                listened: Duration::ZERO,
+            });
+            entry.plays += 1;
+            entry.listened += listened;
+        }
+
+        let entry = tracks
+            .entry(play.track_id.clone())
+            .or_insert_with(|| TopEntry {
+                name: play.title.clone(),
+                detail: Some(play.artists.join(", ")),
+                plays: 0,
+                listened: Duration::ZERO,
+            });
+        entry.plays += 1;
+        entry.listened += listened;
+    }
+
+    report.top_artists = top(artists);
+    report.top_tracks = top(tracks);
+    report
+}
+
+// Quoted only when needed (see RFC 4180)
+fn csv_field(field: &str) -> String {
+    if field.contains(|c| matches!(c, ',' | '"' | '\n' | '\r')) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn iso_date(secs: u64) -> String {
+    glib::DateTime::from_unix_utc(secs as i64)
+        .and_then(|date| date.format_iso8601())
+        .map(|date| date.to_string())
+        .unwrap_or_default()
+}
+
+// The whole log, one play per line, for use elsewhere
+pub fn plays_to_csv(plays: &[Play]) -> String {
+    let mut csv = String::from("played_at,artist,album,title,track_id,played_ms\n");
+    for play in plays {
+        let line = [
+            iso_date(play.played_at),
+            csv_field(&play.artists.join(", ")),
+            csv_field(&play.album),
+            csv_field(&play.title),
+            play.track_id.clone(),
+            play.played_ms.to_string(),
+        ]
+        .join(",");
+        csv.push_str(&line);
+        csv.push('\n');
+    }
+    csv
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn play(track_id: &str, artists: &[&str], played_at: u64, played_ms: u64) -> Play {
+        Play {
+            track_id: track_id.to_string(),
+            title: format!("Title of {track_id}"),
+            artists: artists.iter().map(|a| a.to_string()).collect(),
+            album: "Album".to_string(),
+            played_at,
+            played_ms,
+        }
+    }
+
+    #[test]
+    fn test_listening_report() {
+        let plays = vec![
+            play("old", &["Someone"], 10, 60_000),
+            play("a", &["Artist A"], 100, 60_000),
+            play("b", &["Artist A", "Artist B"], 200, 120_000),
+            play("a", &["Artist A"], 300, 30_000),
+        ];
+        let report = listening_report(&plays, 100);
+        assert_eq!(report.plays, 3);
+        assert_eq!(report.listened, Duration::from_secs(210));
+
+        let artists: Vec<(&str, usize)> = report
+            .top_artists
+            .iter()
+            .map(|a| (a.name.as_str(), a.plays))
+            .collect();
+        assert_eq!(artists, vec![("Artist A", 3), ("Artist B", 1)]);
+
+        let tracks: Vec<(&str, usize)> = report
+            .top_tracks
+            .iter()
+            .map(|t| (t.name.as_str(), t.plays))
+            .collect();
+        assert_eq!(tracks, vec![("Title of a", 2), ("Title of b", 1)]);
+        assert_eq!(
+            report.top_tracks[1].detail.as_deref(),
+            Some("Artist A, Artist B")
+        );
+    }
+
+    #[test]
+    fn test_plays_to_csv() {
+        let mut quoted = play("b", &["Artist A", "Artist B"], 0, 1000);
+        quoted.title = "Say \"Hi\"".to_string();
+        let csv = plays_to_csv(&[quoted]);
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], "played_at,artist,album,title,track_id,played_ms");
+        assert_eq!(
+            lines[1],
+            "1970-01-01T00:00:00Z,\"Artist A, Artist B\",Album,\"Say \"\"Hi\"\"\",b,1000"
+        );
+    }
+}
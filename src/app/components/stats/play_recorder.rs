@@ -0,0 +1,83 @@
+use std::rc::Rc;
+use std::time::{Duration, UNIX_EPOCH};
+
+use super::play_log::{Play, PlayLog};
+use crate::app::components::scrobbler::CurrentListen;
+use crate::app::components::EventListener;
+use crate::app::state::PlaybackEvent;
+use crate::app::{AppEvent, AppModel};
+
+// Same as Spotify: a track counts as played after 30 seconds
+const MIN_PLAYED: Duration = Duration::from_secs(30);
+
+// Keeps a log of what the user plays, for the listening stats
+pub struct PlayRecorder {
+    app_model: Rc<AppModel>,
+    log: PlayLog,
+    current: Option<CurrentListen>,
+}
+
+impl PlayRecorder {
+    pub fn new(app_model: Rc<AppModel>) -> Self {
+        Self {
+            app_model,
+            log: PlayLog::load(),
+            current: None,
+        }
+    }
+
+    fn finish_current(&mut self) {
+        let current = match self.current.take() {
+            Some(current) => current,
+            None => return,
+        };
+        let played = current.played();
+        if played < MIN_PLAYED {
+            return;
+        }
+        let played_at = current
+            .started_at
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        self.log.push(Play {
+            track_id: current.song.id,
+            title: current.song.title,
+            artists: current.song.artists.into_iter().map(|a| a.name).collect(),
+            album: current.song.album.name,
+            played_at,
+            played_ms: played.as_millis() as u64,
+        });
+    }
+
+    fn start_new(&mut self) {
+        let state = self.app_model.get_state();
+        let playback = &state.playback;
+        self.current = playback
+            .current_song()
+            .map(|song| CurrentListen::new(song, playback.is_playing()));
+    }
+}
+
+impl EventListener for PlayRecorder {
+    fn on_event(&mut self, event: &AppEvent) {
+        match event {
+            AppEvent::PlaybackEvent(PlaybackEvent::TrackChanged(_)) => {
+                self.finish_current();
+                self.start_new();
+            }
+            AppEvent::PlaybackEvent(PlaybackEvent::PlaybackStopped) => self.finish_current(),
+            AppEvent::PlaybackEvent(PlaybackEvent::PlaybackPaused) => {
+                if let Some(current) = self.current.as_mut() {
+                    current.pause();
+                }
+            }
+            AppEvent::PlaybackEvent(PlaybackEvent::PlaybackResumed) => {
+                if let Some(current) = self.current.as_mut() {
+                    current.resume();
+                }
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,246 @@
+use gettextrs::*;
+use gtk::prelude::*;
+use gtk::subclass::prelude::*;
+use gtk::CompositeTemplate;
+use libadwaita::prelude::*;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::play_log::{
+    listening_report, plays_to_csv, ListeningReport, PlayLog, StatsPeriod, TopEntry,
+};
+use crate::app::components::display;
+
+mod imp {
+
+    use super::*;
+    use libadwaita::subclass::prelude::*;
+
+    #[derive(Debug, Default, CompositeTemplate)]
+    #[template(resource = "/dev/alextren/Spot/components/stats.ui")]
+    pub struct StatsWindow {
+        #[template_child]
+        pub toast_overlay: TemplateChild<libadwaita::ToastOverlay>,
+
+        #[template_child]
+        pub export_button: TemplateChild<gtk::Button>,
+
+        #[template_child]
+        pub week_button: TemplateChild<gtk::ToggleButton>,
+
+        #[template_child]
+        pub month_button: TemplateChild<gtk::ToggleButton>,
+
+        #[template_child]
+        pub year_button: TemplateChild<gtk::ToggleButton>,
+
+        #[template_child]
+        pub stack: TemplateChild<gtk::Stack>,
+
+        #[template_child]
+        pub listened_label: TemplateChild<gtk::Label>,
+
+        #[template_child]
+        pub plays_label: TemplateChild<gtk::Label>,
+
+        #[template_child]
+        pub top_artists: TemplateChild<gtk::ListBox>,
+
+        #[template_child]
+        pub top_tracks: TemplateChild<gtk::ListBox>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for StatsWindow {
+        const NAME: &'static str = "StatsWindow";
+        type Type = super::StatsWindow;
+        type ParentType = libadwaita::Window;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for StatsWindow {}
+    impl WidgetImpl for StatsWindow {}
+    impl WindowImpl for StatsWindow {}
+    impl AdwWindowImpl for StatsWindow {}
+}
+
+glib::wrapper! {
+    pub struct StatsWindow(ObjectSubclass<imp::StatsWindow>) @extends gtk::Widget, gtk::Window, libadwaita::Window;
+}
+
+fn fill_top_list(list: &gtk::ListBox, entries: &[TopEntry]) {
+    while let Some(row) = list.first_child() {
+        list.remove(&row);
+    }
+    for (i, entry) in entries.iter().enumerate() {
+        let row = libadwaita::ActionRow::builder()
+            .title(glib::markup_escape_text(&entry.name))
+            .build();
+        if let Some(detail) = entry.detail.as_ref() {
+            row.set_subtitle(&glib::markup_escape_text(detail));
+        }
+        let rank = gtk::Label::builder()
+            .label(&(i + 1).to_string())
+            .width_chars(2)
+            .css_classes(vec!["dim-label".to_string(), "numeric".to_string()])
+            .build();
+        row.add_prefix(&rank);
+        let plays = gtk::Label::builder()
+            .label(&display::n_plays_label(entry.plays))
+            .css_classes(vec!["dim-label".to_string()])
+            .build();
+        row.add_suffix(&plays);
+        list.append(&row);
+    }
+    list.set_visible(!entries.is_empty());
+}
+
+impl StatsWindow {
+    fn new() -> Self {
+        glib::Object::new()
+    }
+
+    fn period(&self) -> StatsPeriod {
+        let widget = self.imp();
+        if widget.year_button.is_active() {
+            StatsPeriod::Year
+        } else if widget.month_button.is_active() {
+            StatsPeriod::Month
+        } else {
+            StatsPeriod::Week
+        }
+    }
+
+    fn show_report(&self, report: &ListeningReport) {
+        let widget = self.imp();
+        if report.plays == 0 {
+            widget.stack.set_visible_child_name("empty");
+            return;
+        }
+        widget.stack.set_visible_child_name("report");
+        widget
+            .listened_label
+            .set_label(&display::listening_time_label(
+                report.listened.as_secs() / 60,
+            ));
+        widget
+            .plays_label
+            .set_label(&display::n_plays_label(report.plays));
+        fill_top_list(&widget.top_artists, &report.top_artists);
+        fill_top_list(&widget.top_tracks, &report.top_tracks);
+    }
+
+    fn show_toast(&self, message: &str) {
+        self.imp()
+            .toast_overlay
+            .add_toast(libadwaita::Toast::new(message));
+    }
+
+    fn connect_period_changed<F>(&self, f: F)
+    where
+        F: Fn() + Clone + 'static,
+    {
+        let widget = self.imp();
+        for button in [
+            &widget.week_button,
+            &widget.month_button,
+            &widget.year_button,
+        ] {
+            let f = f.clone();
+            button.connect_toggled(move |button| {
+                if button.is_active() {
+                    f();
+                }
+            });
+        }
+    }
+
+    // Asks where to save the log, and writes it there
+    fn connect_export<F>(&self, f: F)
+    where
+        F: Fn() -> String + Clone + 'static,
+    {
+        self.imp()
+            .export_button
+            .connect_clicked(clone!(@weak self as _self => move |_| {
+                let dialog = gtk::FileDialog::builder()
+                    .modal(true)
+                    .initial_name("spot-plays.csv")
+                    .build();
+
+                let f = f.clone();
+                glib::MainContext::default().spawn_local(clone!(@weak _self => async move {
+                    let file = match dialog.save_future(Some(&_self)).await {
+                        Ok(file) => file,
+                        // Cancelled
+                        Err(_) => return,
+                    };
+                    let result = file
+                        .replace_contents_future(
+                            f().into_bytes(),
+                            None,
+                            false,
+                            gio::FileCreateFlags::REPLACE_DESTINATION,
+                        )
+                        .await;
+                    match result {
+                        // translators: Shown once the listening history was exported to a CSV file
+                        Ok(_) => _self.show_toast(&gettext("Listening history exported")),
+                        Err((_, e)) => {
+                            warn!("Could not write {:?}: {}", file.path(), e);
+                            // translators: Shown when the listening history couldn't be exported
+                            _self.show_toast(&gettext("Could not export the listening history"));
+                        }
+                    }
+                }));
+            }));
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+// A self-hosted look back at what the user listened to: top artists and tracks, and time spent listening,
+// over the last week, month or year. It's all based on the plays logged locally.
+pub struct Stats {
+    parent: gtk::Window,
+    window: StatsWindow,
+}
+
+impl Stats {
+    pub fn new(parent: gtk::Window) -> Self {
+        let window = StatsWindow::new();
+
+        window.connect_period_changed(clone!(@weak window => move || {
+            Self::update(&window);
+        }));
+
+        window.connect_export(|| plays_to_csv(PlayLog::load().plays()));
+
+        Self { parent, window }
+    }
+
+    // The log is read again every time, as it keeps growing while Spot is open
+    fn update(window: &StatsWindow) {
+        let since = now().saturating_sub(window.period().duration().as_secs());
+        let report = listening_report(PlayLog::load().plays(), since);
+        window.show_report(&report);
+    }
+
+    pub fn show_self(&self) {
+        Self::update(&self.window);
+        self.window.set_transient_for(Some(&self.parent));
+        self.window.set_modal(true);
+        self.window.set_visible(true);
+    }
+}
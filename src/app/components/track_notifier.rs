@@ -0,0 +1,103 @@
+use gettextrs::*;
+use gio::prelude::*;
+use gtk::prelude::*;
+use std::rc::Rc;
+
+use crate::app::components::EventListener;
+use crate::app::loader::ImageLoader;
+use crate::app::models::SongDescription;
+use crate::app::state::PlaybackEvent;
+use crate::app::{AppEvent, AppModel, Worker};
+
+// Sending a notification with the same id replaces the previous one, so we don't pile them up
+const NOTIFICATION_ID: &str = "track-changed";
+
+// Shows a desktop notification when the track changes while Spot is in the background.
+// Buttons trigger the same app actions as the keyboard shortcuts, which dispatch the matching AppAction.
+pub struct TrackNotifier {
+    app_model: Rc<AppModel>,
+    window: gtk::Window,
+    worker: Worker,
+}
+
+impl TrackNotifier {
+    pub fn new(app_model: Rc<AppModel>, window: gtk::Window, worker: Worker) -> Self {
+        // No need to keep the notification around once the user is back
+        window.connect_is_active_notify(|window| {
+            if window.is_active() {
+                if let Some(app) = gio::Application::default() {
+                    app.withdraw_notification(NOTIFICATION_ID);
+                }
+            }
+        });
+        Self {
+            app_model,
+            window,
+            worker,
+        }
+    }
+
+    fn make_notification(song: &SongDescription, is_playing: bool) -> gio::Notification {
+        let notification = gio::Notification::new(&song.title);
+        notification.set_body(Some(&song.artists_name()));
+        notification.set_priority(gio::NotificationPriority::Low);
+
+        // translators: This is a button of the notification shown when the track changes, to play the previous track.
+        notification.add_button(&gettext("Previous"), "app.player_prev");
+        let toggle_label = if is_playing {
+            // translators: This is a button of the notification shown when the track changes, to pause playback.
+            gettext("Pause")
+        } else {
+            // translators: This is a button of the notification shown when the track changes, to resume playback.
+            gettext("Play")
+        };
+        notification.add_button(&toggle_label, "app.toggle_playback");
+        // translators: This is a button of the notification shown when the track changes, to play the next track.
+        notification.add_button(&gettext("Next"), "app.player_next");
+        notification
+    }
+
+    fn send(notification: &gio::Notification) {
+        if let Some(app) = gio::Application::default() {
+            app.send_notification(Some(NOTIFICATION_ID), notification);
+        }
+    }
+
+    fn notify_track_changed(&self) {
+        if self.window.is_active() {
+            return;
+        }
+
+        let state = self.app_model.get_state();
+        if !state.settings.settings.notify_track_changes {
+            return;
+        }
+        let song = match state.playback.current_song() {
+            Some(song) => song,
+            None => return,
+        };
+
+        let notification = Self::make_notification(&song, state.playback.is_playing());
+        match song.art {
+            // The artwork is most likely already cached, since it's also displayed in the playback bar
+            Some(url) => {
+                self.worker.send_local_task(async move {
+                    let loader = ImageLoader::new();
+                    if let Some(artwork) = loader.load_remote(&url, "jpg", 128, 128).await {
+                        notification.set_icon(&artwork);
+                    }
+                    Self::send(&notification);
+                });
+            }
+            None => Self::send(&notification),
+        }
+    }
+}
+
+impl EventListener for TrackNotifier {
+    fn on_event(&mut self, event: &AppEvent) {
+        if let AppEvent::PlaybackEvent(PlaybackEvent::TrackChanged(_)) = event {
+            self.notify_track_changed();
+        }
+    }
+}
@@ -0,0 +1,55 @@
+use std::rc::Rc;
+
+use crate::app::components::EventListener;
+use crate::app::state::{LoginEvent, PlaybackAction};
+use crate::app::{ActionDispatcher, AppEvent, AppModel};
+
+// Plays the tracks that are opened from a link (see SpotifyUri). Links can be opened as Spot starts,
+// in which case the track is only fetched once the user is logged in.
+pub struct TrackOpener {
+    app_model: Rc<AppModel>,
+    dispatcher: Box<dyn ActionDispatcher>,
+    pending: Option<String>,
+}
+
+impl TrackOpener {
+    pub fn new(app_model: Rc<AppModel>, dispatcher: Box<dyn ActionDispatcher>) -> Self {
+        Self {
+            app_model,
+            dispatcher,
+            pending: None,
+        }
+    }
+
+    fn play(&self, id: String) {
+        let api = self.app_model.get_spotify();
+        self.dispatcher
+            .call_spotify_and_dispatch_many(move || async move {
+                let song = api.get_track(&id).await?;
+                Ok(vec![
+                    PlaybackAction::LoadSongs(vec![song]).into(),
+                    PlaybackAction::Load(id).into(),
+                ])
+            });
+    }
+}
+
+impl EventListener for TrackOpener {
+    fn on_event(&mut self, event: &AppEvent) {
+        match event {
+            AppEvent::TrackPlayRequested(id) => {
+                if self.app_model.get_state().logged_user.user.is_some() {
+                    self.play(id.clone());
+                } else {
+                    self.pending = Some(id.clone());
+                }
+            }
+            AppEvent::LoginEvent(LoginEvent::LoginCompleted(_)) => {
+                if let Some(id) = self.pending.take() {
+                    self.play(id);
+                }
+            }
+            _ => {}
+        }
+    }
+}
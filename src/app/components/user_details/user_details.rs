@@ -3,10 +3,10 @@ use gtk::subclass::prelude::*;
 use gtk::CompositeTemplate;
 use std::rc::Rc;
 
-use crate::app::components::utils::wrap_flowbox_item;
+use crate::app::components::utils::{self, wrap_flowbox_item};
 use crate::app::components::{display_add_css_provider, AlbumWidget, Component, EventListener};
 use crate::app::{models::*, ListStore};
-use crate::app::{AppEvent, BrowserEvent, Worker};
+use crate::app::{AppEvent, BrowserEvent, Worker, PREFETCH_ROWS};
 
 use super::UserDetailsModel;
 
@@ -62,17 +62,16 @@ impl UserDetailsWidget {
         self.imp().user_name.set_text(name);
     }
 
-    fn connect_bottom_edge<F>(&self, f: F)
+    fn connect_prefetch<F>(&self, f: F)
     where
         F: Fn() + 'static,
     {
-        self.imp()
-            .scrolled_window
-            .connect_edge_reached(move |_, pos| {
-                if let gtk::PositionType::Bottom = pos {
-                    f()
-                }
-            });
+        utils::connect_prefetch(
+            &self.imp().scrolled_window,
+            &self.imp().user_playlists,
+            PREFETCH_ROWS,
+            f,
+        );
     }
 
     fn bind_user_playlists<F>(&self, worker: Worker, store: &ListStore<AlbumModel>, on_pressed: F)
@@ -100,13 +99,12 @@ pub struct UserDetails {
 }
 
 impl UserDetails {
-    pub fn new(model: UserDetailsModel, worker: Worker) -> Self {
+    pub fn new(model: Rc<UserDetailsModel>, worker: Worker) -> Self {
         model.load_user_details(model.id.clone());
 
         let widget = UserDetailsWidget::new();
-        let model = Rc::new(model);
 
-        widget.connect_bottom_edge(clone!(@weak model => move || {
+        widget.connect_prefetch(clone!(@weak model => move || {
             model.load_more();
         }));
 
@@ -142,6 +140,7 @@ impl EventListener for UserDetails {
             AppEvent::BrowserEvent(BrowserEvent::UserDetailsUpdated(id))
                 if id == &self.model.id =>
             {
+                self.model.pages_added();
                 self.update_details();
             }
             _ => {}
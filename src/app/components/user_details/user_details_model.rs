@@ -1,14 +1,18 @@
 use std::ops::Deref;
 use std::rc::Rc;
 
+use crate::app::components::{utils, Shareable, SimpleHeaderBarModel};
 use crate::app::models::*;
-use crate::app::state::BrowserAction;
-use crate::app::{ActionDispatcher, AppAction, AppModel, ListStore};
+use crate::app::state::{BrowserAction, SelectionContext};
+use crate::app::{
+    ActionDispatcher, AppAction, AppEvent, AppModel, ListStore, Prefetcher, SpotifyUri,
+};
 
 pub struct UserDetailsModel {
     pub id: String,
     app_model: Rc<AppModel>,
     dispatcher: Box<dyn ActionDispatcher>,
+    prefetcher: Prefetcher,
 }
 
 impl UserDetailsModel {
@@ -17,6 +21,7 @@ impl UserDetailsModel {
             id,
             app_model,
             dispatcher,
+            prefetcher: Prefetcher::new(),
         }
     }
     pub fn get_user_name(&self) -> Option<impl Deref<Target = String> + '_> {
@@ -43,6 +48,11 @@ impl UserDetailsModel {
         self.dispatcher.dispatch(AppAction::ViewPlaylist(id));
     }
 
+    // The list was updated, with the pages fetched so far
+    pub fn pages_added(&self) {
+        self.prefetcher.release_fetched();
+    }
+
     pub fn load_more(&self) -> Option<()> {
         let api = self.app_model.get_spotify();
         let state = self.app_model.get_state();
@@ -52,7 +62,7 @@ impl UserDetailsModel {
         let batch_size = next_page.batch_size;
         let offset = next_page.next_offset?;
         self.dispatcher
-            .call_spotify_and_dispatch(move || async move {
+            .call_spotify_and_prefetch(&self.prefetcher, offset, move || async move {
                 api.get_user_playlists(&id, offset, batch_size)
                     .await
                     .map(|playlists| BrowserAction::AppendUserPlaylists(id, playlists).into())
@@ -61,3 +71,31 @@ impl UserDetailsModel {
         Some(())
     }
 }
+
+impl SimpleHeaderBarModel for UserDetailsModel {
+    fn title(&self) -> Option<String> {
+        None
+    }
+
+    fn title_updated(&self, _: &AppEvent) -> bool {
+        false
+    }
+
+    fn selection_context(&self) -> Option<SelectionContext> {
+        None
+    }
+
+    fn select_all(&self) {}
+
+    fn web_url(&self) -> Option<String> {
+        Some(utils::web_player_url("user", &self.id))
+    }
+
+    fn shareable(&self) -> Option<Shareable> {
+        Some(Shareable::new(
+            SpotifyUri::User(self.id.clone()),
+            self.get_user_name()?.clone(),
+            None,
+        ))
+    }
+}
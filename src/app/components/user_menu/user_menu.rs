@@ -4,7 +4,7 @@ use gtk::prelude::*;
 use std::rc::Rc;
 
 use super::UserMenuModel;
-use crate::app::components::{EventListener, Settings};
+use crate::app::components::{Diagnostics, EventListener, HistoryImport, Settings, Stats};
 use crate::app::{state::LoginEvent, AppEvent};
 
 pub struct UserMenu {
@@ -16,6 +16,9 @@ impl UserMenu {
     pub fn new(
         user_button: gtk::MenuButton,
         settings: Settings,
+        history_import: HistoryImport,
+        stats: Stats,
+        diagnostics: Diagnostics,
         about: libadwaita::AboutWindow,
         model: UserMenuModel,
     ) -> Self {
@@ -46,6 +49,30 @@ impl UserMenu {
             settings_action
         });
 
+        action_group.add_action(&{
+            let import_action = SimpleAction::new("import_history", None);
+            import_action.connect_activate(move |_, _| {
+                history_import.show_self();
+            });
+            import_action
+        });
+
+        action_group.add_action(&{
+            let stats_action = SimpleAction::new("stats", None);
+            stats_action.connect_activate(move |_, _| {
+                stats.show_self();
+            });
+            stats_action
+        });
+
+        action_group.add_action(&{
+            let diagnostics_action = SimpleAction::new("diagnostics", None);
+            diagnostics_action.connect_activate(move |_, _| {
+                diagnostics.show_self();
+            });
+            diagnostics_action
+        });
+
         action_group.add_action(&{
             let about_action = SimpleAction::new("about", None);
             about_action.connect_activate(clone!(@weak about => move |_, _| {
@@ -54,6 +81,17 @@ impl UserMenu {
             about_action
         });
 
+        action_group.add_action(&{
+            let recover_action =
+                SimpleAction::new("recover_playlist", Some(glib::VariantTy::STRING));
+            recover_action.connect_activate(clone!(@weak model => move |_, id| {
+                if let Some(id) = id.and_then(|id| id.get::<String>()) {
+                    model.recover_playlist(id);
+                }
+            }));
+            recover_action
+        });
+
         user_button.insert_action_group("menu", Some(&action_group));
 
         Self { user_button, model }
@@ -63,6 +101,11 @@ impl UserMenu {
         let menu = gio::Menu::new();
         // translators: This is a menu entry.
         menu.append(Some(&gettext("Preferences")), Some("menu.settings"));
+        // translators: This is a menu entry, it opens a window showing playback metrics (cache hits, buffering...)
+        menu.append(
+            Some(&gettext("Playback Diagnostics…")),
+            Some("menu.diagnostics"),
+        );
         // translators: This is a menu entry.
         menu.append(Some(&gettext("About")), Some("menu.about"));
         // translators: This is a menu entry.
@@ -70,6 +113,27 @@ impl UserMenu {
 
         if let Some(username) = self.model.username() {
             let user_menu = gio::Menu::new();
+
+            let deleted_playlists = self.model.deleted_playlists();
+            if !deleted_playlists.is_empty() {
+                let recover_menu = gio::Menu::new();
+                for playlist in deleted_playlists.iter() {
+                    recover_menu.append(
+                        Some(&playlist.title),
+                        Some(&format!("menu.recover_playlist::{}", playlist.id)),
+                    );
+                }
+                // translators: This is a menu entry, it lists the playlists deleted in the last 90 days. Clicking one of them recovers it.
+                user_menu.append_submenu(Some(&gettext("Recover Deleted Playlist")), &recover_menu);
+            }
+
+            // translators: This is a menu entry, it opens a window showing what the user listened to the most
+            user_menu.append(Some(&gettext("Listening Stats…")), Some("menu.stats"));
+            // translators: This is a menu entry, it opens a window to import songs from a listening history (Last.fm...)
+            user_menu.append(
+                Some(&gettext("Import Listening History…")),
+                Some("menu.import_history"),
+            );
             // translators: This is a menu entry.
             user_menu.append(Some(&gettext("Log out")), Some("menu.logout"));
             menu.insert_section(0, Some(&username), &user_menu);
@@ -86,6 +150,14 @@ impl EventListener for UserMenu {
                 self.update_menu();
                 self.model.fetch_user_playlists();
             }
+            AppEvent::PlaylistDeleted(playlist) => {
+                self.model.remember_deleted_playlist(playlist.clone());
+                self.update_menu();
+            }
+            AppEvent::PlaylistRecovered(id) => {
+                self.model.forget_deleted_playlist(id);
+                self.update_menu();
+            }
             _ => {}
         }
     }
@@ -1,20 +1,28 @@
 use crate::api::clear_user_cache;
 use crate::app::credentials::Credentials;
+use crate::app::models::{DeletedPlaylist, PlaylistSummary};
 use crate::app::state::{LoginAction, PlaybackAction};
-use crate::app::{ActionDispatcher, AppModel};
+use crate::app::{ActionDispatcher, AppAction, AppModel};
+use crate::settings::DeletedPlaylists;
+use std::cell::{Ref, RefCell};
 use std::ops::Deref;
 use std::rc::Rc;
 
 pub struct UserMenuModel {
     app_model: Rc<AppModel>,
     dispatcher: Box<dyn ActionDispatcher>,
+    deleted_playlists: RefCell<DeletedPlaylists>,
+    // Logging out of the demo leaves the user's credentials and cache alone
+    demo: bool,
 }
 
 impl UserMenuModel {
-    pub fn new(app_model: Rc<AppModel>, dispatcher: Box<dyn ActionDispatcher>) -> Self {
+    pub fn new(app_model: Rc<AppModel>, dispatcher: Box<dyn ActionDispatcher>, demo: bool) -> Self {
         Self {
             app_model,
             dispatcher,
+            deleted_playlists: RefCell::new(DeletedPlaylists::new_from_gsettings()),
+            demo,
         }
     }
 
@@ -25,6 +33,10 @@ impl UserMenuModel {
 
     pub fn logout(&self) {
         self.dispatcher.dispatch(PlaybackAction::Stop.into());
+        if self.demo {
+            self.dispatcher.dispatch(LoginAction::Logout.into());
+            return;
+        }
         self.dispatcher.dispatch_async(Box::pin(async {
             let _ = Credentials::logout().await;
             let _ = clear_user_cache().await;
@@ -49,4 +61,30 @@ impl UserMenuModel {
                 });
         }
     }
+
+    pub fn deleted_playlists(&self) -> Ref<'_, Vec<DeletedPlaylist>> {
+        Ref::map(self.deleted_playlists.borrow(), |d| &d.playlists)
+    }
+
+    pub fn remember_deleted_playlist(&self, playlist: PlaylistSummary) {
+        let mut deleted_playlists = self.deleted_playlists.borrow_mut();
+        deleted_playlists.add(DeletedPlaylist::new(playlist));
+        deleted_playlists.save();
+    }
+
+    pub fn forget_deleted_playlist(&self, id: &str) {
+        let mut deleted_playlists = self.deleted_playlists.borrow_mut();
+        deleted_playlists.remove(id);
+        deleted_playlists.save();
+    }
+
+    pub fn recover_playlist(&self, id: String) {
+        let api = self.app_model.get_spotify();
+        self.dispatcher
+            .call_spotify_and_dispatch(move || async move {
+                api.follow_playlist(&id)
+                    .await
+                    .map(AppAction::RecoverPlaylist)
+            });
+    }
 }
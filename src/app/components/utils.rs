@@ -1,8 +1,13 @@
 use gtk::prelude::*;
-use std::cell::Cell;
+use std::cell::{Cell, RefCell};
 use std::rc::Rc;
 use std::time::Duration;
 
+use crate::app::components::EventListener;
+use crate::app::models::SongDescription;
+use crate::app::state::PlaybackEvent;
+use crate::app::{AppEvent, AppModel};
+
 #[derive(Clone)]
 pub struct Clock {
     interval_ms: u32,
@@ -74,6 +79,99 @@ impl Debouncer {
             previous_source.remove();
         }
     }
+
+    pub fn cancel(&self) {
+        if let Some(previous_source) = self.0.take() {
+            previous_source.remove();
+        }
+    }
+}
+
+// The track change held back by a TrackChangeDebouncer, kept apart from the timer for testing
+#[derive(Default)]
+struct HeldTrackChange(Option<AppEvent>);
+
+impl HeldTrackChange {
+    // The events to pass on right away, in order. The track change is held back instead, and dropped if
+    // playback stops or moves to another source in the meantime: it isn't the current track anymore.
+    fn on_event(&mut self, event: &AppEvent, delay: u32) -> Vec<AppEvent> {
+        match event {
+            AppEvent::PlaybackEvent(PlaybackEvent::TrackChanged(_)) if delay > 0 => {
+                self.0 = Some(event.clone());
+                vec![]
+            }
+            AppEvent::PlaybackEvent(PlaybackEvent::PlaybackStopped)
+            | AppEvent::PlaybackEvent(PlaybackEvent::SourceChanged) => {
+                self.0 = None;
+                vec![event.clone()]
+            }
+            // The user settled on that track after all
+            AppEvent::PlaybackEvent(PlaybackEvent::PlaybackPaused)
+            | AppEvent::PlaybackEvent(PlaybackEvent::PlaybackResumed) => {
+                self.0.take().into_iter().chain([event.clone()]).collect()
+            }
+            _ => vec![event.clone()],
+        }
+    }
+
+    fn is_held(&self) -> bool {
+        self.0.is_some()
+    }
+
+    fn take(&mut self) -> Option<AppEvent> {
+        self.0.take()
+    }
+}
+
+// Wraps a listener so that it only hears about the track the user settles on when skipping quickly
+// through tracks: TrackChanged events are held back until no other track change happened for the
+// configured delay. Other events go through as they come (see HeldTrackChange).
+pub struct TrackChangeDebouncer<L> {
+    app_model: Rc<AppModel>,
+    listener: Rc<RefCell<L>>,
+    held: Rc<RefCell<HeldTrackChange>>,
+    debouncer: Debouncer,
+}
+
+impl<L: EventListener + 'static> TrackChangeDebouncer<L> {
+    pub fn new(app_model: Rc<AppModel>, listener: L) -> Self {
+        Self {
+            app_model,
+            listener: Rc::new(RefCell::new(listener)),
+            held: Default::default(),
+            debouncer: Debouncer::new(),
+        }
+    }
+}
+
+impl<L: EventListener + 'static> EventListener for TrackChangeDebouncer<L> {
+    fn on_event(&mut self, event: &AppEvent) {
+        let delay = self
+            .app_model
+            .get_state()
+            .settings
+            .settings
+            .track_change_delay;
+        let events = self.held.borrow_mut().on_event(event, delay);
+        // Nothing to pass on: the event is a track change that was just held back
+        if events.is_empty() {
+            let listener = Rc::downgrade(&self.listener);
+            let held = Rc::downgrade(&self.held);
+            self.debouncer.debounce(delay, move || {
+                if let (Some(listener), Some(held)) = (listener.upgrade(), held.upgrade()) {
+                    let event = held.borrow_mut().take();
+                    if let Some(event) = event {
+                        listener.borrow_mut().on_event(&event);
+                    }
+                }
+            });
+        } else if !self.held.borrow().is_held() {
+            self.debouncer.cancel();
+        }
+        for event in events {
+            self.listener.borrow_mut().on_event(&event);
+        }
+    }
 }
 
 pub struct Animator<EasingFn> {
@@ -152,6 +250,36 @@ pub fn wrap_flowbox_item<
     child.upcast::<gtk::Widget>()
 }
 
+// Calls f whenever the visible part of a scrolled window gets within a few rows of the end of a flowbox,
+// so that the next page of whatever the flowbox shows can be fetched before the user hits the bottom.
+// The height of a row is estimated from the first child of the flowbox.
+pub fn connect_prefetch<F>(
+    scrolled_window: &gtk::ScrolledWindow,
+    flowbox: &gtk::FlowBox,
+    rows: u32,
+    f: F,
+) where
+    F: Fn() + 'static,
+{
+    let f = Rc::new(f);
+    let check = clone!(@weak flowbox => move |adjustment: &gtk::Adjustment| {
+        let row_height = match flowbox.child_at_index(0) {
+            Some(child) if child.height() > 0 => child.height(),
+            // Nothing loaded yet
+            _ => return,
+        };
+        let remaining = adjustment.upper() - adjustment.value() - adjustment.page_size();
+        if remaining <= f64::from(row_height) * f64::from(rows) {
+            f();
+        }
+    });
+
+    let adjustment = scrolled_window.vadjustment();
+    // Checking when the adjustment changes too, to keep prefetching if a freshly loaded page didn't fill the viewport
+    adjustment.connect_changed(check.clone());
+    adjustment.connect_value_changed(check);
+}
+
 pub fn format_duration(duration: f64) -> String {
     let seconds = (duration / 1000.0) as i32;
     let hours = seconds.div_euclid(3600);
@@ -163,3 +291,152 @@ pub fn format_duration(duration: f64) -> String {
         format!("{minutes}∶{seconds:02}")
     }
 }
+
+pub fn web_player_url(kind: &str, id: &str) -> String {
+    format!("https://open.spotify.com/{kind}/{id}")
+}
+
+pub fn open_in_browser(uri: &str) {
+    if let Err(err) = gio::AppInfo::launch_default_for_uri(uri, None::<&gio::AppLaunchContext>) {
+        warn!("Could not open {}: {}", uri, err);
+    }
+}
+
+// Formats a single track according to a user provided template, e.g. "{index}. {artist} – {title}"
+// Unknown placeholders are left untouched
+pub fn format_track(song: &SongDescription, index: usize, template: &str) -> String {
+    let mut result = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        result.push_str(&rest[..start]);
+        let tail = &rest[start..];
+        let end = match tail.find('}') {
+            Some(end) => end,
+            None => {
+                rest = tail;
+                break;
+            }
+        };
+        match &tail[1..end] {
+            "index" => result.push_str(&index.to_string()),
+            "title" => result.push_str(&song.title),
+            "artist" => result.push_str(&song.artists_name()),
+            "album" => result.push_str(&song.album.name),
+            "duration" => result.push_str(&format_duration(song.duration.into())),
+            "link" => result.push_str(&web_player_url("track", &song.id)),
+            _ => result.push_str(&tail[..=end]),
+        }
+        rest = &tail[end + 1..];
+    }
+    result.push_str(rest);
+    result
+}
+
+pub fn format_track_list(songs: &[SongDescription], template: &str) -> String {
+    songs
+        .iter()
+        .enumerate()
+        .map(|(i, song)| format_track(song, i + 1, template))
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::app::models::{AlbumRef, ArtistRef};
+
+    fn song(title: &str) -> SongDescription {
+        SongDescription {
+            id: "some_id".to_string(),
+            uri: "".to_string(),
+            title: title.to_string(),
+            artists: vec![ArtistRef {
+                id: "".to_string(),
+                name: "Artist".to_string(),
+            }],
+            album: AlbumRef {
+                id: "".to_string(),
+                name: "Album".to_string(),
+            },
+            duration: 185000,
+            art: None,
+            preview_url: None,
+            track_number: None,
+        }
+    }
+
+    #[test]
+    fn test_format_track() {
+        let formatted = format_track(
+            &song("Title"),
+            3,
+            "{index}. {artist} – {title} ({duration})",
+        );
+        assert_eq!(formatted, "3. Artist – Title (3∶05)");
+    }
+
+    #[test]
+    fn test_format_track_unknown_placeholder() {
+        let formatted = format_track(&song("Title"), 1, "{title} {foo} {album");
+        assert_eq!(formatted, "Title {foo} {album");
+    }
+
+    #[test]
+    fn test_format_track_no_recursive_substitution() {
+        let formatted = format_track(&song("{artist}"), 1, "{title}");
+        assert_eq!(formatted, "{artist}");
+    }
+
+    #[test]
+    fn test_format_track_list() {
+        let formatted = format_track_list(&[song("A"), song("B")], "{index}. {title}");
+        assert_eq!(formatted, "1. A\n2. B");
+    }
+
+    fn track_changed(id: &str) -> AppEvent {
+        PlaybackEvent::TrackChanged(id.to_string()).into()
+    }
+
+    #[test]
+    fn test_held_track_change_dropped_when_stopped() {
+        let mut held = HeldTrackChange::default();
+        assert!(held.on_event(&track_changed("1"), 500).is_empty());
+
+        let events = held.on_event(&PlaybackEvent::PlaybackStopped.into(), 500);
+        assert!(matches!(
+            events[..],
+            [AppEvent::PlaybackEvent(PlaybackEvent::PlaybackStopped)]
+        ));
+        assert!(held.take().is_none());
+
+        held.on_event(&track_changed("2"), 500);
+        held.on_event(&PlaybackEvent::SourceChanged.into(), 500);
+        assert!(held.take().is_none());
+    }
+
+    #[test]
+    fn test_held_track_change_comes_before_pause() {
+        let mut held = HeldTrackChange::default();
+        held.on_event(&track_changed("1"), 500);
+        held.on_event(&track_changed("2"), 500);
+
+        let events = held.on_event(&PlaybackEvent::PlaybackPaused.into(), 500);
+        assert!(matches!(
+            &events[..],
+            [
+                AppEvent::PlaybackEvent(PlaybackEvent::TrackChanged(id)),
+                AppEvent::PlaybackEvent(PlaybackEvent::PlaybackPaused)
+            ] if id == "2"
+        ));
+        assert!(!held.is_held());
+    }
+
+    #[test]
+    fn test_track_change_not_held_without_delay() {
+        let mut held = HeldTrackChange::default();
+        assert_eq!(held.on_event(&track_changed("1"), 0).len(), 1);
+        assert!(!held.is_held());
+    }
+}
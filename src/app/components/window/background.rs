@@ -0,0 +1,55 @@
+use gettextrs::gettext;
+use gio::prelude::*;
+use std::cell::Cell;
+use std::collections::HashMap;
+
+thread_local! {
+    static REQUESTED: Cell<bool> = Cell::new(false);
+}
+
+// Tells the system (through the Background portal) that Spot keeps running without a window on purpose:
+// sandboxed apps could otherwise be stopped, or reported as running with no window.
+// See https://flatpak.github.io/xdg-desktop-portal/docs/doc-org.freedesktop.portal.Background.html
+pub fn request_background() {
+    if REQUESTED.with(|r| r.replace(true)) {
+        return;
+    }
+
+    let mut options: HashMap<String, glib::Variant> = HashMap::new();
+    options.insert(
+        "reason".to_string(),
+        // translators: Shown by the system when Spot asks to keep running once its window is closed
+        gettext("Keep playing music once the window is closed").to_variant(),
+    );
+    options.insert("autostart".to_string(), false.to_variant());
+    // No parent window: by the time we ask, it's hidden
+    let parameters = (String::new(), options).to_variant();
+
+    glib::MainContext::default().spawn_local(async move {
+        let result = match gio::bus_get_future(gio::BusType::Session).await {
+            Ok(connection) => {
+                connection
+                    .call_future(
+                        Some("org.freedesktop.portal.Desktop"),
+                        "/org/freedesktop/portal/desktop",
+                        "org.freedesktop.portal.Background",
+                        "RequestBackground",
+                        Some(&parameters),
+                        None,
+                        gio::DBusCallFlags::NONE,
+                        -1,
+                    )
+                    .await
+            }
+            Err(e) => Err(e),
+        };
+        match result {
+            Ok(_) => debug!("Requested to run in the background"),
+            Err(e) => {
+                // Not much we can do, and it doesn't matter outside of a sandbox anyway
+                warn!("Couldn't request to run in the background: {}", e);
+                REQUESTED.with(|r| r.set(false));
+            }
+        }
+    });
+}
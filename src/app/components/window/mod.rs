@@ -1,10 +1,16 @@
+use gettextrs::gettext;
 use gtk::prelude::*;
+use libadwaita::prelude::*;
 use std::cell::RefCell;
 use std::rc::Rc;
 
-use crate::app::components::EventListener;
-use crate::app::{AppEvent, AppModel};
-use crate::settings::WindowGeometry;
+use crate::app::components::{indicators, EventListener};
+use crate::app::state::{SettingsAction, SettingsEvent};
+use crate::app::{ActionDispatcher, AppEvent, AppModel};
+use crate::settings::{CloseBehavior, WindowGeometry};
+
+mod background;
+use background::request_background;
 
 thread_local! {
     static WINDOW_GEOMETRY: RefCell<WindowGeometry> = RefCell::new(WindowGeometry {
@@ -14,6 +20,7 @@ thread_local! {
 
 pub struct MainWindow {
     initial_window_geometry: WindowGeometry,
+    app_model: Rc<AppModel>,
     window: libadwaita::ApplicationWindow,
 }
 
@@ -21,16 +28,24 @@ impl MainWindow {
     pub fn new(
         initial_window_geometry: WindowGeometry,
         app_model: Rc<AppModel>,
+        dispatcher: Box<dyn ActionDispatcher>,
         window: libadwaita::ApplicationWindow,
     ) -> Self {
         window.connect_close_request(
             clone!(@weak app_model => @default-return gtk::Inhibit(false), move |window| {
                 let state = app_model.get_state();
-                if state.playback.is_playing() {
-                    window.set_visible(false);
-                    gtk::Inhibit(true)
-                } else {
-                    gtk::Inhibit(false)
+                match state.settings.settings.close_behavior {
+                    // Playing or not, only quitting stops Spot then
+                    CloseBehavior::Background => {
+                        Self::hide_to_background(window);
+                        gtk::Inhibit(true)
+                    }
+                    _ if !state.playback.is_playing() => gtk::Inhibit(false),
+                    CloseBehavior::Stop => gtk::Inhibit(false),
+                    CloseBehavior::Ask => {
+                        Self::ask_close_behavior(window, dispatcher.box_clone());
+                        gtk::Inhibit(true)
+                    }
                 }
             }),
         );
@@ -46,10 +61,66 @@ impl MainWindow {
 
         Self {
             initial_window_geometry,
+            app_model,
             window,
         }
     }
 
+    fn ask_close_behavior(
+        window: &libadwaita::ApplicationWindow,
+        dispatcher: Box<dyn ActionDispatcher>,
+    ) {
+        // translators: Checkbox in the dialog shown when closing the window while music is playing
+        let remember = gtk::CheckButton::with_label(&gettext("Remember my choice"));
+        remember.set_halign(gtk::Align::Center);
+
+        let dialog = libadwaita::MessageDialog::builder()
+            .transient_for(window)
+            .modal(true)
+            // translators: Title of the dialog shown when closing the window while music is playing
+            .heading(gettext("Keep playing?"))
+            .body(gettext(
+                "Music can keep playing in the background once the window is closed. This can be changed in the preferences.",
+            ))
+            .extra_child(&remember)
+            .build();
+        dialog.add_responses(&[
+            // translators: Dialog button, closing the window quits Spot
+            ("stop", &gettext("Stop playback")),
+            // translators: Dialog button, closing the window only hides it
+            ("background", &gettext("Keep playing")),
+        ]);
+        dialog.set_response_appearance("background", libadwaita::ResponseAppearance::Suggested);
+        dialog.set_default_response(Some("background"));
+
+        dialog.connect_response(
+            None,
+            clone!(@weak window => move |_, response| {
+                let behavior = match response {
+                    "stop" => CloseBehavior::Stop,
+                    "background" => CloseBehavior::Background,
+                    _ => return,
+                };
+                if remember.is_active() {
+                    behavior.save();
+                    dispatcher.dispatch(SettingsAction::ChangeSettings.into());
+                }
+                if behavior == CloseBehavior::Stop {
+                    window.destroy();
+                } else {
+                    Self::hide_to_background(&window);
+                }
+            }),
+        );
+        dialog.present();
+    }
+
+    // The player keeps running, and can still be controlled through MPRIS or notifications
+    fn hide_to_background(window: &libadwaita::ApplicationWindow) {
+        window.set_visible(false);
+        request_background();
+    }
+
     fn start(&self) {
         self.window.set_default_size(
             self.initial_window_geometry.width,
@@ -58,7 +129,31 @@ impl MainWindow {
         if self.initial_window_geometry.is_maximized {
             self.window.maximize();
         }
-        self.window.present();
+        self.apply_appearance();
+        if self.app_model.get_state().settings.settings.start_minimized {
+            // Opening Spot again (or raising it from the media controls) shows the window
+            request_background();
+        } else {
+            self.window.present();
+        }
+    }
+
+    fn apply_appearance(&self) {
+        let state = self.app_model.get_state();
+        let settings = &state.settings.settings;
+        libadwaita::StyleManager::default().set_color_scheme(settings.theme_preference);
+        if settings.use_artwork_colors {
+            self.window.add_css_class("artwork-colors");
+        } else {
+            self.window.remove_css_class("artwork-colors");
+        }
+        indicators::add_css();
+        if settings.high_contrast_indicators {
+            self.window.add_css_class(indicators::HIGH_CONTRAST_CLASS);
+        } else {
+            self.window
+                .remove_css_class(indicators::HIGH_CONTRAST_CLASS);
+        }
     }
 
     fn raise(&self) {
@@ -84,6 +179,7 @@ impl EventListener for MainWindow {
         match event {
             AppEvent::Started => self.start(),
             AppEvent::Raised => self.raise(),
+            AppEvent::SettingsEvent(SettingsEvent::AppearanceChanged) => self.apply_appearance(),
             _ => {}
         }
     }
@@ -19,6 +19,9 @@ pub struct Credentials {
     pub token: String,
     pub token_expiry_time: Option<SystemTime>,
     pub country: String,
+    // Only when logged in from the browser, to get new tokens without a password
+    #[serde(default)]
+    pub refresh_token: Option<String>,
 }
 
 impl Credentials {
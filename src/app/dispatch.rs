@@ -10,6 +10,7 @@ use super::AppAction;
 // It is a trait because I guess I wanted to be able to stub it, but see how that went...
 pub trait ActionDispatcher {
     fn dispatch(&self, action: AppAction);
+    // The actions are processed together, and the UI only updated once they're all done
     fn dispatch_many(&self, actions: Vec<AppAction>);
     fn dispatch_async(&self, action: BoxFuture<'static, Option<AppAction>>);
     fn dispatch_many_async(&self, actions: BoxFuture<'static, Vec<AppAction>>);
@@ -29,13 +30,21 @@ impl ActionDispatcherImpl {
     }
 }
 
+fn batch(mut actions: Vec<AppAction>) -> Option<AppAction> {
+    match actions.len() {
+        0 => None,
+        1 => actions.pop(),
+        _ => Some(AppAction::Batch(actions)),
+    }
+}
+
 impl ActionDispatcher for ActionDispatcherImpl {
     fn dispatch(&self, action: AppAction) {
         self.sender.unbounded_send(action).unwrap();
     }
 
     fn dispatch_many(&self, actions: Vec<AppAction>) {
-        for action in actions.into_iter() {
+        if let Some(action) = batch(actions) {
             self.sender.unbounded_send(action).unwrap();
         }
     }
@@ -52,7 +61,7 @@ impl ActionDispatcher for ActionDispatcherImpl {
     fn dispatch_many_async(&self, actions: BoxFuture<'static, Vec<AppAction>>) {
         let clone = self.sender.clone();
         self.worker.send_task(async move {
-            for action in actions.await.into_iter() {
+            if let Some(action) = batch(actions.await) {
                 clone.unbounded_send(action).unwrap();
             }
         });
@@ -63,6 +72,51 @@ impl ActionDispatcher for ActionDispatcherImpl {
     }
 }
 
+// Keeps actions instead of sending them, and runs async ones to completion right away,
+// so that models can be tested without a main loop (or GTK at all)
+#[cfg(test)]
+#[derive(Clone, Default)]
+pub struct TestDispatcher {
+    actions: std::rc::Rc<std::cell::RefCell<Vec<AppAction>>>,
+}
+
+#[cfg(test)]
+impl TestDispatcher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Everything dispatched so far, in order
+    pub fn take_actions(&self) -> Vec<AppAction> {
+        self.actions.borrow_mut().drain(..).collect()
+    }
+}
+
+#[cfg(test)]
+impl ActionDispatcher for TestDispatcher {
+    fn dispatch(&self, action: AppAction) {
+        self.actions.borrow_mut().push(action);
+    }
+
+    fn dispatch_many(&self, actions: Vec<AppAction>) {
+        self.actions.borrow_mut().extend(actions);
+    }
+
+    fn dispatch_async(&self, action: BoxFuture<'static, Option<AppAction>>) {
+        if let Some(action) = futures::executor::block_on(action) {
+            self.dispatch(action);
+        }
+    }
+
+    fn dispatch_many_async(&self, actions: BoxFuture<'static, Vec<AppAction>>) {
+        self.dispatch_many(futures::executor::block_on(actions));
+    }
+
+    fn box_clone(&self) -> Box<dyn ActionDispatcher> {
+        Box::new(self.clone())
+    }
+}
+
 // Funky name for a mere wrapper around an MPSC send/recv pair
 pub struct DispatchLoop {
     receiver: UnboundedReceiver<AppAction>,
@@ -126,3 +180,25 @@ impl Worker {
         self.1.unbounded_send(Box::pin(task)).ok()
     }
 }
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_test_dispatcher() {
+        let dispatcher = TestDispatcher::new();
+        let boxed: Box<dyn ActionDispatcher> = dispatcher.box_clone();
+        boxed.dispatch(AppAction::Raise);
+        boxed.dispatch_async(Box::pin(async { Some(AppAction::ViewNowPlaying) }));
+        boxed.dispatch_many_async(Box::pin(async { vec![] }));
+
+        let actions = dispatcher.take_actions();
+        assert!(matches!(
+            actions[..],
+            [AppAction::Raise, AppAction::ViewNowPlaying]
+        ));
+        assert!(dispatcher.take_actions().is_empty());
+    }
+}
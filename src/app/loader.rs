@@ -1,6 +1,7 @@
 use crate::api::cache::*;
 use gdk_pixbuf::traits::PixbufLoaderExt;
-use gdk_pixbuf::{Pixbuf, PixbufLoader};
+use gdk_pixbuf::{Colorspace, InterpType, Pixbuf, PixbufLoader};
+use gtk::prelude::*;
 use isahc::config::Configurable;
 use isahc::{AsyncBody, AsyncReadResponseExt, HttpClient, Response};
 use std::collections::hash_map::DefaultHasher;
@@ -26,18 +27,131 @@ impl<'a> Write for LocalPixbufLoader<'a> {
     }
 }
 
-// A helper to load remote images, with simple cache management
+// The prevailing color of an image, used to tint the header of details pages
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ArtworkColor {
+    pub red: u8,
+    pub green: u8,
+    pub blue: u8,
+}
+
+impl ArtworkColor {
+    // Colors are quantized to 4 bits per channel, and the most frequent bucket wins;
+    // the result is the average of the pixels that fell into that bucket.
+    fn dominant(
+        pixels: &[u8],
+        n_channels: usize,
+        rowstride: usize,
+        width: usize,
+        height: usize,
+    ) -> Option<Self> {
+        let mut buckets = vec![(0u32, [0u32; 3]); 1 << 12];
+        for y in 0..height {
+            for x in 0..width {
+                let offset = y * rowstride + x * n_channels;
+                let pixel = pixels.get(offset..offset + n_channels)?;
+                if n_channels == 4 && pixel[3] < 128 {
+                    continue;
+                }
+                let key = ((pixel[0] as usize >> 4) << 8)
+                    | ((pixel[1] as usize >> 4) << 4)
+                    | (pixel[2] as usize >> 4);
+                let (count, sums) = &mut buckets[key];
+                *count += 1;
+                for (sum, value) in sums.iter_mut().zip(pixel) {
+                    *sum += *value as u32;
+                }
+            }
+        }
+
+        let (count, sums) = buckets.into_iter().max_by_key(|(count, _)| *count)?;
+        if count == 0 {
+            return None;
+        }
+        Some(Self {
+            red: (sums[0] / count) as u8,
+            green: (sums[1] / count) as u8,
+            blue: (sums[2] / count) as u8,
+        })
+    }
+
+    pub fn from_pixbuf(pixbuf: &Pixbuf) -> Option<Self> {
+        if pixbuf.colorspace() != Colorspace::Rgb || pixbuf.bits_per_sample() != 8 {
+            return None;
+        }
+        let pixels = pixbuf.read_pixel_bytes();
+        Self::dominant(
+            &pixels,
+            pixbuf.n_channels() as usize,
+            pixbuf.rowstride() as usize,
+            pixbuf.width() as usize,
+            pixbuf.height() as usize,
+        )
+    }
+
+    fn from_hex(hex: &str) -> Option<Self> {
+        let hex = hex.trim().strip_prefix('#')?;
+        let channel = |i: usize| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok();
+        Some(Self {
+            red: channel(0)?,
+            green: channel(2)?,
+            blue: channel(4)?,
+        })
+    }
+
+    pub fn to_hex(self) -> String {
+        format!("#{:02x}{:02x}{:02x}", self.red, self.green, self.blue)
+    }
+
+    // Relative luminance, as defined by the WCAG
+    fn luminance(self) -> f64 {
+        let linear = |c: u8| {
+            let c = c as f64 / 255.0;
+            if c <= 0.03928 {
+                c / 12.92
+            } else {
+                ((c + 0.055) / 1.055).powf(2.4)
+            }
+        };
+        0.2126 * linear(self.red) + 0.7152 * linear(self.green) + 0.0722 * linear(self.blue)
+    }
+
+    // Above that luminance, black text contrasts better than white text
+    pub fn is_light(self) -> bool {
+        self.luminance() > 0.179
+    }
+}
+
+// A helper to load remote images, with simple cache management.
+// Sizes are given in logical pixels, the size images are shown at.
 pub struct ImageLoader {
     cache: CacheManager,
+    // Device pixels per logical pixel
+    scale: i32,
 }
 
 impl ImageLoader {
     pub fn new() -> Self {
         Self {
             cache: CacheManager::for_dir("spot/img").unwrap(),
+            scale: 1,
+        }
+    }
+
+    // For images shown by the given widget, loaded with as many pixels as its screen has. With fractional
+    // scaling (say 1.5), GTK renders at the next integer scale and the compositor scales the result down,
+    // so images are loaded at that integer scale too: anything less gets upscaled, and looks blurry.
+    pub fn for_widget(widget: &impl IsA<gtk::Widget>) -> Self {
+        Self {
+            scale: widget.scale_factor().max(1),
+            ..Self::new()
         }
     }
 
+    fn device_size(&self, size: i32) -> i32 {
+        size * self.scale
+    }
+
     // Downloaded images are simply named [hash of url].[file extension]
     fn resource_for(url: &str, ext: &str) -> String {
         let mut hasher = DefaultHasher::new();
@@ -61,6 +175,17 @@ impl ImageLoader {
         ext: &str,
         width: i32,
         height: i32,
+    ) -> Option<Pixbuf> {
+        self.load_device_pixels(url, ext, self.device_size(width), self.device_size(height))
+            .await
+    }
+
+    async fn load_device_pixels(
+        &self,
+        url: &str,
+        ext: &str,
+        width: i32,
+        height: i32,
     ) -> Option<Pixbuf> {
         let resource = Self::resource_for(url, ext);
         let pixbuf_loader = PixbufLoader::new();
@@ -97,4 +222,112 @@ impl ImageLoader {
         pixbuf_loader.close().ok()?;
         pixbuf_loader.pixbuf()
     }
+
+    // A 2x2 mosaic of the given images, or just the first one if there aren't 4 of them.
+    // The mosaic is cached as well, so that we don't have to assemble it every time.
+    pub async fn load_mosaic(&self, urls: &[String], size: i32) -> Option<Pixbuf> {
+        if urls.len() < 4 {
+            return self.load_remote(urls.first()?, "jpg", size, size).await;
+        }
+
+        let size = self.device_size(size);
+        let resource = Self::resource_for(&format!("{} {}", size, urls.join(" ")), "png");
+        if let Ok(CacheFile::Fresh(buffer, _)) = self
+            .cache
+            .read_cache_file(&resource[..], CachePolicy::IgnoreExpiry)
+            .await
+        {
+            let pixbuf_loader = PixbufLoader::new();
+            pixbuf_loader.write(&buffer[..]).ok()?;
+            pixbuf_loader.close().ok()?;
+            return pixbuf_loader.pixbuf();
+        }
+
+        let half = size / 2;
+        let mosaic = Pixbuf::new(Colorspace::Rgb, false, 8, half * 2, half * 2)?;
+        mosaic.fill(0);
+        for (i, url) in urls.iter().take(4).enumerate() {
+            let tile = self
+                .load_device_pixels(url, "jpg", half, half)
+                .await?
+                .scale_simple(half, half, InterpType::Bilinear)?;
+            let (x, y) = ((i as i32 % 2) * half, (i as i32 / 2) * half);
+            tile.copy_area(0, 0, half, half, &mosaic, x, y);
+        }
+
+        if let Ok(buffer) = mosaic.save_to_bufferv("png", &[]) {
+            self.cache
+                .write_cache_file(&resource[..], &buffer[..], CacheExpiry::Never)
+                .await
+                .ok();
+        }
+        Some(mosaic)
+    }
+
+    // The prevailing color of an image that was loaded from the given key (url or mosaic urls).
+    // It's cached next to the images, as it's a bit costly to compute.
+    pub async fn load_artwork_color(&self, key: &str, pixbuf: &Pixbuf) -> Option<ArtworkColor> {
+        let resource = Self::resource_for(key, "color");
+        if let Ok(CacheFile::Fresh(buffer, _)) = self
+            .cache
+            .read_cache_file(&resource[..], CachePolicy::IgnoreExpiry)
+            .await
+        {
+            if let Some(color) = std::str::from_utf8(&buffer[..])
+                .ok()
+                .and_then(ArtworkColor::from_hex)
+            {
+                return Some(color);
+            }
+        }
+
+        let color = ArtworkColor::from_pixbuf(pixbuf)?;
+        self.cache
+            .write_cache_file(&resource[..], color.to_hex().as_bytes(), CacheExpiry::Never)
+            .await
+            .ok();
+        Some(color)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dominant_color() {
+        #[rustfmt::skip]
+        let pixels = [
+            250, 10, 10, 255,   0, 0, 255, 255,
+            252, 12, 8, 255,    0, 0, 0, 0,
+        ];
+        let color = ArtworkColor::dominant(&pixels, 4, 8, 2, 2);
+        assert_eq!(
+            color,
+            Some(ArtworkColor {
+                red: 251,
+                green: 11,
+                blue: 9
+            })
+        );
+    }
+
+    #[test]
+    fn test_color_hex() {
+        let color = ArtworkColor {
+            red: 255,
+            green: 250,
+            blue: 0,
+        };
+        assert_eq!(color.to_hex(), "#fffa00");
+        assert_eq!(ArtworkColor::from_hex("#fffa00"), Some(color));
+        assert!(color.is_light());
+
+        let color = ArtworkColor {
+            red: 20,
+            green: 30,
+            blue: 120,
+        };
+        assert!(!color.is_light());
+    }
 }
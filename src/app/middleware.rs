@@ -0,0 +1,270 @@
+use super::state::LoginAction;
+use super::{AppAction, AppEvent};
+use crate::crash_report;
+
+// Something that gets to see every action on its way to the state (and what came out of it),
+// to log, persist or record things without every component having to care.
+pub trait Middleware {
+    // Called before the action reaches the state: it can be changed, or dropped by returning None
+    fn before_update(&mut self, action: AppAction) -> Option<AppAction> {
+        Some(action)
+    }
+
+    // Called with the events that the action resulted in
+    fn after_update(&mut self, _events: &[AppEvent]) {}
+}
+
+// Runs actions through the middlewares, in order, then through the reducer (the state update)
+#[derive(Default)]
+pub struct ActionPipeline {
+    middlewares: Vec<Box<dyn Middleware>>,
+}
+
+impl ActionPipeline {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with(mut self, middleware: impl Middleware + 'static) -> Self {
+        self.middlewares.push(Box::new(middleware));
+        self
+    }
+
+    pub fn process<R>(&mut self, action: AppAction, reducer: R) -> Vec<AppEvent>
+    where
+        R: FnOnce(AppAction) -> Vec<AppEvent>,
+    {
+        let action = self
+            .middlewares
+            .iter_mut()
+            .try_fold(action, |action, middleware| {
+                middleware.before_update(action)
+            });
+        let events = match action {
+            Some(action) => reducer(action),
+            None => return vec![],
+        };
+        for middleware in self.middlewares.iter_mut() {
+            middleware.after_update(&events);
+        }
+        events
+    }
+
+    // Processes actions one after the other, as one transaction: what they changed comes out as a single
+    // set of events (see AppEvent::consolidate)
+    pub fn process_batch<R>(&mut self, actions: Vec<AppAction>, mut reducer: R) -> Vec<AppEvent>
+    where
+        R: FnMut(AppAction) -> Vec<AppEvent>,
+    {
+        let mut events = vec![];
+        for action in actions {
+            events.extend(self.process(action, &mut reducer));
+        }
+        AppEvent::consolidate(events)
+    }
+}
+
+// Logs every action (when debug logs are enabled)
+pub struct LoggingMiddleware;
+
+impl LoggingMiddleware {
+    fn describe(action: &AppAction) -> String {
+        match action {
+            // Those carry passwords or tokens
+            AppAction::LoginAction(LoginAction::TryLogin(_)) => {
+                "LoginAction(TryLogin(...))".to_string()
+            }
+            AppAction::LoginAction(LoginAction::SetLoginSuccess(_)) => {
+                "LoginAction(SetLoginSuccess(...))".to_string()
+            }
+            AppAction::LoginAction(LoginAction::SetRefreshedToken { .. }) => {
+                "LoginAction(SetRefreshedToken { .. })".to_string()
+            }
+            action => format!("{:?}", action),
+        }
+    }
+}
+
+impl Middleware for LoggingMiddleware {
+    fn before_update(&mut self, action: AppAction) -> Option<AppAction> {
+        debug!("Action: {}", Self::describe(&action));
+        Some(action)
+    }
+}
+
+// Keeps track of the last actions, to be included in crash reports (see crash_report)
+pub struct CrashLogMiddleware;
+
+impl Middleware for CrashLogMiddleware {
+    fn before_update(&mut self, action: AppAction) -> Option<AppAction> {
+        crash_report::record_action(&action);
+        Some(action)
+    }
+}
+
+// While offline, most everything fails: rather than a notification for each error, the offline banner
+// says it all (see NetworkWatcher)
+#[derive(Default)]
+pub struct OfflineMiddleware {
+    offline: bool,
+}
+
+impl Middleware for OfflineMiddleware {
+    fn before_update(&mut self, action: AppAction) -> Option<AppAction> {
+        match action {
+            AppAction::ShowNotification(content) if self.offline => {
+                debug!("Offline, not showing: {}", content);
+                None
+            }
+            action => Some(action),
+        }
+    }
+
+    fn after_update(&mut self, events: &[AppEvent]) {
+        for event in events {
+            if let AppEvent::ConnectivityChanged(online) = event {
+                self.offline = !online;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::app::state::{PlaybackEvent, SelectionEvent, SetLoginSuccessAction, TryLoginAction};
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    // Drops notifications, and remembers how many events went by
+    #[derive(Clone, Default)]
+    struct Muffler {
+        seen_events: Rc<RefCell<usize>>,
+    }
+
+    impl Middleware for Muffler {
+        fn before_update(&mut self, action: AppAction) -> Option<AppAction> {
+            match action {
+                AppAction::ShowNotification(_) => None,
+                action => Some(action),
+            }
+        }
+
+        fn after_update(&mut self, events: &[AppEvent]) {
+            *self.seen_events.borrow_mut() += events.len();
+        }
+    }
+
+    fn reducer(action: AppAction) -> Vec<AppEvent> {
+        match action {
+            AppAction::ShowNotification(c) => vec![AppEvent::NotificationShown(c)],
+            AppAction::Raise => vec![AppEvent::Raised],
+            AppAction::SetOnline(online) => vec![AppEvent::ConnectivityChanged(online)],
+            _ => vec![],
+        }
+    }
+
+    #[test]
+    fn test_middleware_drops_action() {
+        let muffler = Muffler::default();
+        let mut pipeline = ActionPipeline::new()
+            .with(LoggingMiddleware)
+            .with(muffler.clone());
+
+        let events = pipeline.process(AppAction::ShowNotification("hi".to_string()), reducer);
+        assert!(events.is_empty());
+        assert_eq!(*muffler.seen_events.borrow(), 0);
+
+        let events = pipeline.process(AppAction::Raise, reducer);
+        assert!(matches!(events[..], [AppEvent::Raised]));
+        assert_eq!(*muffler.seen_events.borrow(), 1);
+    }
+
+    #[test]
+    fn test_batch_consolidates_events() {
+        let muffler = Muffler::default();
+        let mut pipeline = ActionPipeline::new().with(muffler.clone());
+        let reducer = |action: AppAction| match action {
+            AppAction::QueueSelection => vec![
+                SelectionEvent::SelectionModeChanged(false).into(),
+                PlaybackEvent::PlaylistChanged.into(),
+            ],
+            action => reducer(action),
+        };
+
+        let events = pipeline.process_batch(
+            vec![
+                AppAction::QueueSelection,
+                AppAction::ShowNotification("hi".to_string()),
+                AppAction::Raise,
+                AppAction::QueueSelection,
+            ],
+            reducer,
+        );
+        assert!(matches!(
+            events[..],
+            [
+                AppEvent::SelectionEvent(SelectionEvent::SelectionModeChanged(false)),
+                AppEvent::Raised,
+                AppEvent::SelectionEvent(SelectionEvent::SelectionModeChanged(false)),
+                AppEvent::PlaybackEvent(PlaybackEvent::PlaylistChanged),
+            ]
+        ));
+        // Middlewares still see every action on its own
+        assert_eq!(*muffler.seen_events.borrow(), 5);
+    }
+
+    #[test]
+    fn test_offline_middleware_drops_notifications() {
+        let mut pipeline = ActionPipeline::new().with(OfflineMiddleware::default());
+        let notify = || AppAction::ShowNotification("error".to_string());
+
+        assert_eq!(pipeline.process(notify(), reducer).len(), 1);
+
+        pipeline.process(AppAction::SetOnline(false), reducer);
+        assert!(pipeline.process(notify(), reducer).is_empty());
+
+        pipeline.process(AppAction::SetOnline(true), reducer);
+        assert_eq!(pipeline.process(notify(), reducer).len(), 1);
+    }
+
+    #[test]
+    fn test_logging_leaves_credentials_out() {
+        let actions: Vec<AppAction> = vec![
+            LoginAction::TryLogin(TryLoginAction::Password {
+                username: "someone".to_string(),
+                password: "hunter2".to_string(),
+            })
+            .into(),
+            LoginAction::TryLogin(TryLoginAction::Token {
+                username: "someone".to_string(),
+                token: "hunter2".to_string(),
+            })
+            .into(),
+            LoginAction::TryLogin(TryLoginAction::OAuth {
+                refresh_token: Some("hunter2".to_string()),
+            })
+            .into(),
+            LoginAction::SetLoginSuccess(SetLoginSuccessAction::Token {
+                username: "someone".to_string(),
+                token: "hunter2".to_string(),
+            })
+            .into(),
+            LoginAction::SetRefreshedToken {
+                token: "hunter2".to_string(),
+                token_expiry_time: std::time::SystemTime::now(),
+            }
+            .into(),
+        ];
+        for action in actions {
+            let logged = LoggingMiddleware::describe(&action);
+            assert!(logged.starts_with("LoginAction("));
+            assert!(!logged.contains("hunter2"), "{}", logged);
+        }
+        assert_eq!(
+            LoggingMiddleware::describe(&AppAction::Raise),
+            format!("{:?}", AppAction::Raise)
+        );
+    }
+}
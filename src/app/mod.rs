@@ -1,4 +1,5 @@
-use crate::api::CachedSpotifyClient;
+use crate::api::{CachedSpotifyClient, DemoSpotifyClient, SpotifyApiClient};
+use crate::app::state::{EpisodeProgress, LibraryOptions, ReleaseAlerts};
 use crate::settings::SpotSettings;
 use futures::channel::mpsc::UnboundedSender;
 use std::rc::Rc;
@@ -7,6 +8,9 @@ use std::sync::Arc;
 pub mod dispatch;
 pub use dispatch::{ActionDispatcher, ActionDispatcherImpl, DispatchLoop, Worker};
 
+mod middleware;
+pub use middleware::*;
+
 pub mod components;
 use components::*;
 
@@ -21,11 +25,17 @@ pub use state::{AppAction, AppEvent, AppModel, AppState, BrowserAction, BrowserE
 mod batch_loader;
 pub use batch_loader::*;
 
+mod prefetch;
+pub use prefetch::*;
+
+mod uri;
+pub use uri::SpotifyUri;
+
 pub mod credentials;
 pub mod loader;
 
 pub mod rng;
-pub use rng::LazyRandomIndex;
+pub use rng::{LazyRandomIndex, ShuffleMode};
 
 // Where all the app logic happens
 pub struct App {
@@ -36,9 +46,13 @@ pub struct App {
     components: Vec<Box<dyn EventListener>>,
     // Holds the app state
     model: Rc<AppModel>,
+    // What actions go through before updating the state
+    pipeline: ActionPipeline,
     // Allows sending actions that are handled by the model above
     sender: UnboundedSender<AppAction>,
     worker: Worker,
+    // Started with --demo: the bundled demo library is shown instead of the user's, and nothing is played
+    demo: bool,
 }
 
 impl App {
@@ -47,29 +61,106 @@ impl App {
         builder: gtk::Builder,
         sender: UnboundedSender<AppAction>,
         worker: Worker,
+        demo: bool,
     ) -> Self {
-        let state = AppState::new();
-        let spotify_client = Arc::new(CachedSpotifyClient::new());
+        let mut state = AppState::new(settings.clone());
+        state.release_alerts = ReleaseAlerts::new_from_gsettings();
+        // The demo doesn't follow any show, which would wipe out what the user has
+        if !demo {
+            state.saved_shows.progress = EpisodeProgress::load();
+        }
+        if let Some(home) = state.browser.home_state_mut() {
+            home.library_options = LibraryOptions::new_from_gsettings();
+        }
+        let spotify_client: Arc<dyn SpotifyApiClient + Send + Sync> = if demo {
+            Arc::new(DemoSpotifyClient::new())
+        } else {
+            Arc::new(CachedSpotifyClient::new())
+        };
         let model = Rc::new(AppModel::new(state, spotify_client));
 
         // Non widget components
-        let components: Vec<Box<dyn EventListener>> = vec![
+        let mut components: Vec<Box<dyn EventListener>> = vec![
             App::make_player_notifier(
                 Rc::clone(&model),
                 &settings,
                 Box::new(ActionDispatcherImpl::new(sender.clone(), worker.clone())),
                 sender.clone(),
+                demo,
             ),
             App::make_dbus(Rc::clone(&model), sender.clone()),
+            App::make_playlist_refresher(
+                Rc::clone(&model),
+                Box::new(ActionDispatcherImpl::new(sender.clone(), worker.clone())),
+            ),
+            App::make_autoplay(
+                Rc::clone(&model),
+                Box::new(ActionDispatcherImpl::new(sender.clone(), worker.clone())),
+            ),
+            App::make_saved_content_loader(
+                Rc::clone(&model),
+                Box::new(ActionDispatcherImpl::new(sender.clone(), worker.clone())),
+            ),
+            App::make_saved_tracks_sync(
+                Rc::clone(&model),
+                Box::new(ActionDispatcherImpl::new(sender.clone(), worker.clone())),
+            ),
+            App::make_saved_tracks_checker(
+                Rc::clone(&model),
+                Box::new(ActionDispatcherImpl::new(sender.clone(), worker.clone())),
+            ),
+            App::make_recently_played_sync(
+                Rc::clone(&model),
+                Box::new(ActionDispatcherImpl::new(sender.clone(), worker.clone())),
+            ),
+            App::make_pending_changes_committer(
+                Rc::clone(&model),
+                Box::new(ActionDispatcherImpl::new(sender.clone(), worker.clone())),
+                worker.clone(),
+                demo,
+            ),
+            App::make_track_opener(
+                Rc::clone(&model),
+                Box::new(ActionDispatcherImpl::new(sender.clone(), worker.clone())),
+            ),
+            App::make_alarm_clock(
+                Rc::clone(&model),
+                Box::new(ActionDispatcherImpl::new(sender.clone(), worker.clone())),
+            ),
+            App::make_release_watcher(
+                Rc::clone(&model),
+                Box::new(ActionDispatcherImpl::new(sender.clone(), worker.clone())),
+                worker.clone(),
+            ),
+            App::make_show_watcher(
+                Rc::clone(&model),
+                Box::new(ActionDispatcherImpl::new(sender.clone(), worker.clone())),
+                worker.clone(),
+            ),
+            App::make_call_watcher(Rc::clone(&model), sender.clone()),
         ];
+        // Nothing is really listened to in demo mode, and the user's own session is left alone
+        if !demo {
+            components.push(App::make_scrobbler(Rc::clone(&model), worker.clone()));
+            components.push(App::make_play_recorder(Rc::clone(&model)));
+            components.push(App::make_session_keeper(
+                Rc::clone(&model),
+                Box::new(ActionDispatcherImpl::new(sender.clone(), worker.clone())),
+            ));
+        }
 
         Self {
             settings,
             builder,
             components,
             model,
+            pipeline: ActionPipeline::new()
+                .with(LoggingMiddleware)
+                .with(CrashLogMiddleware)
+                .with(OfflineMiddleware::default()),
             sender,
             worker,
+            demo,
         }
     }
 
@@ -88,7 +179,12 @@ impl App {
 
         // All components that will be available initially
         let mut components: Vec<Box<dyn EventListener>> = vec![
-            App::make_window(&self.settings, builder, Rc::clone(model)),
+            App::make_window(
+                &self.settings,
+                builder,
+                Rc::clone(model),
+                dispatcher.box_clone(),
+            ),
             App::make_selection_toolbar(builder, Rc::clone(model), dispatcher.box_clone()),
             App::make_playback(
                 builder,
@@ -96,7 +192,7 @@ impl App {
                 dispatcher.box_clone(),
                 worker.clone(),
             ),
-            App::make_login(builder, dispatcher.box_clone(), worker.clone()),
+            App::make_login(builder, dispatcher.box_clone(), worker.clone(), self.demo),
             App::make_navigation(
                 builder,
                 Rc::clone(model),
@@ -104,9 +200,28 @@ impl App {
                 worker.clone(),
             ),
             App::make_search_button(builder, dispatcher.box_clone()),
-            App::make_user_menu(builder, Rc::clone(model), dispatcher),
-            App::make_notification(builder),
+            App::make_user_menu(
+                builder,
+                Rc::clone(model),
+                dispatcher.box_clone(),
+                worker.clone(),
+                self.demo,
+            ),
+            App::make_network_watcher(builder, Rc::clone(model), dispatcher.box_clone()),
+            App::make_notification(builder, Rc::clone(model), dispatcher.box_clone()),
+            App::make_track_notifier(builder, Rc::clone(model), worker.clone()),
+            App::make_idle_inhibitor(builder, Rc::clone(model)),
+            App::make_crash_reporter(builder),
         ];
+        // The demo needs neither sound nor Spotify
+        if !self.demo {
+            components.push(App::make_health_check(
+                builder,
+                Rc::clone(model),
+                dispatcher.box_clone(),
+                worker.clone(),
+            ));
+        }
 
         self.components.append(&mut components);
     }
@@ -117,13 +232,19 @@ impl App {
         settings: &SpotSettings,
         dispatcher: Box<dyn ActionDispatcher>,
         sender: UnboundedSender<AppAction>,
+        demo: bool,
     ) -> Box<impl EventListener> {
         let api = app_model.get_spotify();
+        let player = if demo {
+            crate::player::start_demo_player(sender.clone())
+        } else {
+            crate::player::start_player_service(settings.player_settings.clone(), sender.clone())
+        };
         Box::new(PlayerNotifier::new(
             app_model,
             dispatcher,
             // Either communications with the librespot player
-            crate::player::start_player_service(settings.player_settings.clone(), sender.clone()),
+            player,
             // or with a Spotify Connect device
             crate::connect::start_connect_server(api, sender),
         ))
@@ -134,16 +255,159 @@ impl App {
         app_model: Rc<AppModel>,
         sender: UnboundedSender<AppAction>,
     ) -> Box<impl EventListener> {
-        Box::new(crate::dbus::start_dbus_server(app_model, sender))
+        let listener = crate::dbus::start_dbus_server(Rc::clone(&app_model), sender);
+        Box::new(utils::TrackChangeDebouncer::new(app_model, listener))
+    }
+
+    // A component that keeps opened playlists up to date with changes made elsewhere
+    fn make_playlist_refresher(
+        app_model: Rc<AppModel>,
+        dispatcher: Box<dyn ActionDispatcher>,
+    ) -> Box<impl EventListener> {
+        Box::new(PlaylistRefresher::new(app_model, dispatcher))
+    }
+
+    // A component that queues recommended songs when the queue runs out
+    fn make_autoplay(
+        app_model: Rc<AppModel>,
+        dispatcher: Box<dyn ActionDispatcher>,
+    ) -> Box<impl EventListener> {
+        Box::new(Autoplay::new(app_model, dispatcher))
+    }
+
+    // A component that loads the user's saved tracks and playlists, before their screens are made
+    fn make_saved_content_loader(
+        app_model: Rc<AppModel>,
+        dispatcher: Box<dyn ActionDispatcher>,
+    ) -> Box<impl EventListener> {
+        Box::new(SavedContentLoader::new(app_model, dispatcher))
+    }
+
+    // A component that loads all of the user's saved tracks in the background
+    fn make_saved_tracks_sync(
+        app_model: Rc<AppModel>,
+        dispatcher: Box<dyn ActionDispatcher>,
+    ) -> Box<impl EventListener> {
+        Box::new(SavedTracksSync::new(app_model, dispatcher))
+    }
+
+    fn make_saved_tracks_checker(
+        app_model: Rc<AppModel>,
+        dispatcher: Box<dyn ActionDispatcher>,
+    ) -> Box<impl EventListener> {
+        Box::new(SavedTracksChecker::new(app_model, dispatcher))
+    }
+
+    // A component that sends destructive changes to Spotify once they can't be undone anymore
+    fn make_pending_changes_committer(
+        app_model: Rc<AppModel>,
+        dispatcher: Box<dyn ActionDispatcher>,
+        worker: Worker,
+        demo: bool,
+    ) -> Box<impl EventListener> {
+        Box::new(PendingChangesCommitter::new(
+            app_model, dispatcher, worker, demo,
+        ))
+    }
+
+    // A component that puts the user back where they were after a crash
+    fn make_session_keeper(
+        app_model: Rc<AppModel>,
+        dispatcher: Box<dyn ActionDispatcher>,
+    ) -> Box<impl EventListener> {
+        Box::new(SessionKeeper::new(app_model, dispatcher))
+    }
+
+    // A component that plays tracks opened from a link
+    fn make_track_opener(
+        app_model: Rc<AppModel>,
+        dispatcher: Box<dyn ActionDispatcher>,
+    ) -> Box<impl EventListener> {
+        Box::new(TrackOpener::new(app_model, dispatcher))
+    }
+
+    // A component that plays what other programs scheduled over D-Bus
+    fn make_alarm_clock(
+        app_model: Rc<AppModel>,
+        dispatcher: Box<dyn ActionDispatcher>,
+    ) -> Box<impl EventListener> {
+        Box::new(AlarmClock::new(app_model, dispatcher))
+    }
+
+    // A component that looks for new releases of the artists the user subscribed to
+    fn make_release_watcher(
+        app_model: Rc<AppModel>,
+        dispatcher: Box<dyn ActionDispatcher>,
+        worker: Worker,
+    ) -> Box<impl EventListener> {
+        Box::new(ReleaseWatcher::new(app_model, dispatcher, worker))
+    }
+
+    // A component that keeps track of the shows the user follows, and of their new episodes
+    fn make_show_watcher(
+        app_model: Rc<AppModel>,
+        dispatcher: Box<dyn ActionDispatcher>,
+        worker: Worker,
+    ) -> Box<impl EventListener> {
+        Box::new(ShowWatcher::new(app_model, dispatcher, worker))
+    }
+
+    // A component that pauses playback during calls, if the user wants to
+    fn make_call_watcher(
+        app_model: Rc<AppModel>,
+        sender: UnboundedSender<AppAction>,
+    ) -> Box<impl EventListener> {
+        Box::new(CallWatcher::new(app_model, sender))
+    }
+
+    fn make_scrobbler(app_model: Rc<AppModel>, worker: Worker) -> Box<impl EventListener> {
+        Box::new(Scrobbler::new(app_model, worker))
+    }
+
+    // A component that logs what's played, for the listening stats
+    fn make_play_recorder(app_model: Rc<AppModel>) -> Box<impl EventListener> {
+        Box::new(PlayRecorder::new(app_model))
+    }
+
+    fn make_recently_played_sync(
+        app_model: Rc<AppModel>,
+        dispatcher: Box<dyn ActionDispatcher>,
+    ) -> Box<impl EventListener> {
+        Box::new(RecentlyPlayedSync::new(app_model, dispatcher))
     }
 
     fn make_window(
         settings: &SpotSettings,
         builder: &gtk::Builder,
         app_model: Rc<AppModel>,
+        dispatcher: Box<dyn ActionDispatcher>,
     ) -> Box<impl EventListener> {
         let window: libadwaita::ApplicationWindow = builder.object("window").unwrap();
-        Box::new(MainWindow::new(settings.window.clone(), app_model, window))
+        Box::new(MainWindow::new(
+            settings.window.clone(),
+            app_model,
+            dispatcher,
+            window,
+        ))
+    }
+
+    fn make_track_notifier(
+        builder: &gtk::Builder,
+        app_model: Rc<AppModel>,
+        worker: Worker,
+    ) -> Box<impl EventListener> {
+        let window: gtk::Window = builder.object("window").unwrap();
+        let notifier = TrackNotifier::new(Rc::clone(&app_model), window, worker);
+        Box::new(utils::TrackChangeDebouncer::new(app_model, notifier))
+    }
+
+    fn make_idle_inhibitor(
+        builder: &gtk::Builder,
+        app_model: Rc<AppModel>,
+    ) -> Box<impl EventListener> {
+        let window: gtk::Window = builder.object("window").unwrap();
+        let playback: PlaybackWidget = builder.object("playback").unwrap();
+        Box::new(IdleInhibitor::new(app_model, window, playback))
     }
 
     fn make_navigation(
@@ -153,7 +417,7 @@ impl App {
         worker: Worker,
     ) -> Box<Navigation> {
         let leaflet: libadwaita::Leaflet = builder.object("leaflet").unwrap();
-        let navigation_stack: gtk::Stack = builder.object("navigation_stack").unwrap();
+        let navigation_stack: libadwaita::Leaflet = builder.object("navigation_stack").unwrap();
         let home_listbox: gtk::ListBox = builder.object("home_listbox").unwrap();
         let model = NavigationModel::new(Rc::clone(&app_model), dispatcher.box_clone());
         // This is where components that are not created initially will be assembled
@@ -176,9 +440,10 @@ impl App {
         builder: &gtk::Builder,
         dispatcher: Box<dyn ActionDispatcher>,
         worker: Worker,
+        demo: bool,
     ) -> Box<Login> {
         let parent: gtk::Window = builder.object("window").unwrap();
-        let model = LoginModel::new(dispatcher, worker);
+        let model = LoginModel::new(dispatcher, worker, demo);
         Box::new(Login::new(parent, model))
     }
 
@@ -220,30 +485,86 @@ impl App {
         builder: &gtk::Builder,
         app_model: Rc<AppModel>,
         dispatcher: Box<dyn ActionDispatcher>,
+        worker: Worker,
+        demo: bool,
     ) -> Box<UserMenu> {
         let parent: gtk::Window = builder.object("window").unwrap();
         let settings_model = SettingsModel::new(app_model.clone(), dispatcher.box_clone());
-        let settings = Settings::new(parent, settings_model);
+        let settings = Settings::new(parent.clone(), settings_model);
+
+        let history_import_model =
+            HistoryImportModel::new(app_model.clone(), dispatcher.box_clone());
+        let history_import = HistoryImport::new(parent.clone(), history_import_model, worker);
+        let stats = Stats::new(parent.clone());
+        let diagnostics = Diagnostics::new(parent, app_model.clone());
 
         let button: gtk::MenuButton = builder.object("user").unwrap();
         let about: libadwaita::AboutWindow = builder.object("about").unwrap();
-        let model = UserMenuModel::new(app_model, dispatcher);
-        let user_menu = UserMenu::new(button, settings, about, model);
+        let model = UserMenuModel::new(app_model, dispatcher, demo);
+        let user_menu = UserMenu::new(
+            button,
+            settings,
+            history_import,
+            stats,
+            diagnostics,
+            about,
+            model,
+        );
         Box::new(user_menu)
     }
 
-    fn make_notification(builder: &gtk::Builder) -> Box<Notification> {
+    // A component that offers to report the last crash, if any
+    fn make_crash_reporter(builder: &gtk::Builder) -> Box<CrashReporter> {
+        let window: gtk::Window = builder.object("window").unwrap();
+        Box::new(CrashReporter::new(window))
+    }
+
+    // A component that lists what keeps Spot from working as it starts, with what to do about it
+    fn make_health_check(
+        builder: &gtk::Builder,
+        app_model: Rc<AppModel>,
+        dispatcher: Box<dyn ActionDispatcher>,
+        worker: Worker,
+    ) -> Box<HealthCheck> {
+        let window: gtk::Window = builder.object("window").unwrap();
+        Box::new(HealthCheck::new(window, app_model, dispatcher, worker))
+    }
+
+    // A component that shows a banner while offline, and lets the rest of the app know
+    fn make_network_watcher(
+        builder: &gtk::Builder,
+        app_model: Rc<AppModel>,
+        dispatcher: Box<dyn ActionDispatcher>,
+    ) -> Box<NetworkWatcher> {
+        let banner: libadwaita::Banner = builder.object("offline_banner").unwrap();
+        Box::new(NetworkWatcher::new(app_model, dispatcher, banner))
+    }
+
+    fn make_notification(
+        builder: &gtk::Builder,
+        app_model: Rc<AppModel>,
+        dispatcher: Box<dyn ActionDispatcher>,
+    ) -> Box<Notification> {
         let toast_overlay: libadwaita::ToastOverlay = builder.object("main").unwrap();
-        Box::new(Notification::new(toast_overlay))
+        Box::new(Notification::new(toast_overlay, app_model, dispatcher))
     }
 
     // Main handler called in a loop
     fn handle(&mut self, action: AppAction) {
         let starting = matches!(&action, &AppAction::Start);
 
-        // Update the state based on an incoming action
+        // Update the state based on an incoming action (once the middlewares are done with it)
         // and obtain events representing what that mutation entailed...
-        let events = self.model.update_state(action);
+        let model = &self.model;
+        let events = match action {
+            // ...a batch being processed as a whole, so that components only see the outcome
+            AppAction::Batch(actions) => self
+                .pipeline
+                .process_batch(actions, |action| model.update_state(action)),
+            action => self
+                .pipeline
+                .process(action, |action| model.update_state(action)),
+        };
 
         // (AppAction::Start is special and is used to setup the initial components)
         if !events.is_empty() && starting {
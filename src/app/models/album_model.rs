@@ -27,6 +27,15 @@ impl AlbumModel {
             .property("uri", uri)
             .build()
     }
+
+    // Covers to use as a mosaic when there's no cover (only for playlists)
+    pub fn mosaic(&self) -> Vec<String> {
+        self.imp().mosaic.borrow().clone()
+    }
+
+    pub fn set_mosaic(&self, mosaic: Vec<String>) {
+        self.imp().mosaic.replace(mosaic);
+    }
 }
 
 mod imp {
@@ -48,6 +57,7 @@ mod imp {
         cover: RefCell<Option<String>>,
         #[property(get, set)]
         uri: RefCell<String>,
+        pub mosaic: RefCell<Vec<String>>,
     }
 
     #[glib::object_subclass]
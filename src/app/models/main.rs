@@ -1,9 +1,13 @@
 use std::{
     hash::{Hash, Hasher},
     str::FromStr,
+    time::{Duration, SystemTime},
 };
 
-use crate::app::SongsSource;
+use percent_encoding::percent_decode_str;
+use serde::{Deserialize, Serialize};
+
+use crate::app::{SongsSource, SpotifyUri};
 
 // A batch of whatever
 #[derive(Clone, Copy, Debug)]
@@ -49,13 +53,13 @@ pub struct UserRef {
     pub display_name: String,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ArtistRef {
     pub id: String,
     pub name: String,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct AlbumRef {
     pub id: String,
     pub name: String,
@@ -67,6 +71,13 @@ pub struct SearchResults {
     pub artists: Vec<ArtistSummary>,
 }
 
+// A song found from a line of its lyrics, with that line if we know it
+#[derive(Clone, Debug)]
+pub struct LyricsMatch {
+    pub song: SongDescription,
+    pub line: Option<String>,
+}
+
 #[derive(Clone, Debug)]
 pub struct AlbumDescription {
     pub id: String,
@@ -115,6 +126,29 @@ pub struct PlaylistDescription {
     pub art: Option<String>,
     pub songs: SongBatch,
     pub owner: UserRef,
+    // Changes every time the playlist is modified (possibly by someone else)
+    pub snapshot_id: String,
+    // Album covers to make up an image when the playlist has none (see mosaic_arts)
+    pub mosaic: Vec<String>,
+}
+
+// How many covers make up a (2x2) mosaic
+pub const MOSAIC_SIZE: usize = 4;
+
+// The first distinct album covers of a list of songs, to display as a mosaic.
+// If there aren't enough, only the first one is kept and used as is.
+pub fn mosaic_arts(songs: &[SongDescription]) -> Vec<String> {
+    let mut arts: Vec<String> = vec![];
+    for art in songs.iter().filter_map(|song| song.art.as_ref()) {
+        if !arts.contains(art) {
+            arts.push(art.clone());
+        }
+        if arts.len() == MOSAIC_SIZE {
+            return arts;
+        }
+    }
+    arts.truncate(1);
+    arts
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -138,7 +172,138 @@ pub struct PlaylistSummary {
     pub title: String,
 }
 
-#[derive(Clone, Debug)]
+// The user's "rootlist" is how playlists are organized (in folders) in the official client
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RootlistItem {
+    Playlist(String),
+    Folder(PlaylistFolder),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PlaylistFolder {
+    pub id: String,
+    pub name: String,
+    pub items: Vec<RootlistItem>,
+}
+
+impl RootlistItem {
+    // Folders are delimited by special uris in the (otherwise flat) rootlist:
+    // spotify:start-group:[id]:[url encoded name] ... spotify:end-group:[id]
+    pub fn parse_uris<'a>(uris: impl IntoIterator<Item = &'a str>) -> Vec<Self> {
+        let mut root = vec![];
+        let mut open_folders: Vec<PlaylistFolder> = vec![];
+
+        fn push(root: &mut Vec<RootlistItem>, open: &mut [PlaylistFolder], item: RootlistItem) {
+            match open.last_mut() {
+                Some(folder) => folder.items.push(item),
+                None => root.push(item),
+            }
+        }
+
+        for uri in uris {
+            let parts: Vec<&str> = uri.split(':').collect();
+            match parts.as_slice() {
+                ["spotify", "start-group", id, name @ ..] => {
+                    let name = name.join(":").replace('+', " ");
+                    open_folders.push(PlaylistFolder {
+                        id: id.to_string(),
+                        name: percent_decode_str(&name).decode_utf8_lossy().into_owned(),
+                        items: vec![],
+                    });
+                }
+                ["spotify", "end-group", ..] => {
+                    if let Some(folder) = open_folders.pop() {
+                        push(&mut root, &mut open_folders, Self::Folder(folder));
+                    }
+                }
+                ["spotify", "playlist", id] | ["spotify", "user", _, "playlist", id] => {
+                    push(&mut root, &mut open_folders, Self::Playlist(id.to_string()));
+                }
+                _ => {}
+            }
+        }
+
+        // Shouldn't happen, but let's not lose anything if a folder isn't closed
+        while let Some(folder) = open_folders.pop() {
+            push(&mut root, &mut open_folders, Self::Folder(folder));
+        }
+
+        root
+    }
+}
+
+pub const PLAYLIST_LABEL_COLORS: [&str; 6] = ["red", "orange", "yellow", "green", "blue", "purple"];
+
+// A label that can be assigned to a playlist locally, to help find it in the sidebar
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PlaylistLabel {
+    Color(String),
+    Emoji(String),
+}
+
+impl PlaylistLabel {
+    // Labels are stored as "color:[name]" or "emoji:[emoji]"
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.split_once(':')? {
+            ("color", color) if PLAYLIST_LABEL_COLORS.contains(&color) => {
+                Some(Self::Color(color.to_string()))
+            }
+            ("emoji", emoji) if !emoji.is_empty() => Some(Self::Emoji(emoji.to_string())),
+            _ => None,
+        }
+    }
+
+    // Colors first, in the order of the palette, then emojis
+    pub fn sort_key(&self) -> (usize, &str) {
+        match self {
+            Self::Color(color) => (
+                PLAYLIST_LABEL_COLORS
+                    .iter()
+                    .position(|c| c == color)
+                    .unwrap_or_default(),
+                "",
+            ),
+            Self::Emoji(emoji) => (PLAYLIST_LABEL_COLORS.len(), emoji),
+        }
+    }
+
+    pub fn serialize(&self) -> String {
+        match self {
+            Self::Color(color) => format!("color:{color}"),
+            Self::Emoji(emoji) => format!("emoji:{emoji}"),
+        }
+    }
+}
+
+// Same as Spotify, which lets users recover deleted playlists for 90 days
+pub const PLAYLIST_RECOVERY_PERIOD: Duration = Duration::from_secs(90 * 24 * 60 * 60);
+
+// A playlist deleted (unfollowed) from Spot, remembered locally so that it can be recovered
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DeletedPlaylist {
+    pub id: String,
+    pub title: String,
+    pub deleted_at: SystemTime,
+}
+
+impl DeletedPlaylist {
+    pub fn new(PlaylistSummary { id, title }: PlaylistSummary) -> Self {
+        Self {
+            id,
+            title,
+            deleted_at: SystemTime::now(),
+        }
+    }
+
+    pub fn is_recoverable(&self, now: SystemTime) -> bool {
+        now.duration_since(self.deleted_at)
+            .map(|elapsed| elapsed < PLAYLIST_RECOVERY_PERIOD)
+            // Deleted "in the future", the clock must have changed
+            .unwrap_or(true)
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct SongDescription {
     pub id: String,
     pub track_number: Option<u32>,
@@ -148,6 +313,8 @@ pub struct SongDescription {
     pub album: AlbumRef,
     pub duration: u32,
     pub art: Option<String>,
+    // A 30 seconds clip of the song, when Spotify has one
+    pub preview_url: Option<String>,
 }
 
 impl SongDescription {
@@ -158,6 +325,16 @@ impl SongDescription {
             .collect::<Vec<String>>()
             .join(", ")
     }
+
+    // Whether every word of the filter is in the title, the artists or the album, whatever the case
+    pub fn matches_filter(&self, filter: &str) -> bool {
+        let haystack =
+            format!("{} {} {}", self.title, self.artists_name(), self.album.name).to_lowercase();
+        filter
+            .to_lowercase()
+            .split_whitespace()
+            .all(|word| haystack.contains(word))
+    }
 }
 
 impl Hash for SongDescription {
@@ -170,6 +347,8 @@ impl Hash for SongDescription {
 pub struct SongState {
     pub is_playing: bool,
     pub is_selected: bool,
+    // In the user's Liked Songs
+    pub is_saved: bool,
 }
 
 // A batch of SONGS
@@ -228,6 +407,7 @@ impl SongBatch {
 pub struct ArtistDescription {
     pub id: String,
     pub name: String,
+    pub followers: Option<usize>,
     pub albums: Vec<AlbumDescription>,
     pub top_tracks: Vec<SongDescription>,
 }
@@ -239,6 +419,27 @@ pub struct ArtistSummary {
     pub photo: Option<String>,
 }
 
+// A show (podcast) the user follows
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ShowSummary {
+    pub id: String,
+    pub title: String,
+    pub publisher: String,
+    pub art: Option<String>,
+}
+
+// Something to play at a given time, as programmed over D-Bus (see SpotAlarms)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Alarm {
+    pub id: u32,
+    // An album, a playlist or a track
+    pub uri: SpotifyUri,
+    // In seconds since epoch
+    pub at: i64,
+    // How long the volume takes to go from nothing to where it was, 0 to start at that volume
+    pub ramp_secs: u32,
+}
+
 #[derive(Clone, Debug)]
 pub struct UserDescription {
     pub id: String,
@@ -293,10 +494,98 @@ mod tests {
             },
             duration: 1000,
             art: None,
+            preview_url: None,
             track_number: None,
         }
     }
 
+    #[test]
+    fn song_matches_filter() {
+        let song = SongDescription {
+            title: "Bohemian Rhapsody".to_string(),
+            artists: vec![ArtistRef {
+                id: "".to_string(),
+                name: "Queen".to_string(),
+            }],
+            album: AlbumRef {
+                id: "".to_string(),
+                name: "A Night at the Opera".to_string(),
+            },
+            ..song("1")
+        };
+        assert!(song.matches_filter(""));
+        assert!(song.matches_filter("rhapsody"));
+        assert!(song.matches_filter("queen  OPERA"));
+        assert!(!song.matches_filter("queen under pressure"));
+    }
+
+    #[test]
+    fn parse_rootlist() {
+        let rootlist = RootlistItem::parse_uris(vec![
+            "spotify:playlist:a",
+            "spotify:start-group:f1:My+Folder%21",
+            "spotify:playlist:b",
+            "spotify:start-group:f2:Nested",
+            "spotify:user:someone:playlist:c",
+            "spotify:end-group:f2",
+            "spotify:end-group:f1",
+            "spotify:playlist:d",
+        ]);
+
+        assert_eq!(
+            rootlist,
+            vec![
+                RootlistItem::Playlist("a".to_string()),
+                RootlistItem::Folder(PlaylistFolder {
+                    id: "f1".to_string(),
+                    name: "My Folder!".to_string(),
+                    items: vec![
+                        RootlistItem::Playlist("b".to_string()),
+                        RootlistItem::Folder(PlaylistFolder {
+                            id: "f2".to_string(),
+                            name: "Nested".to_string(),
+                            items: vec![RootlistItem::Playlist("c".to_string())],
+                        }),
+                    ],
+                }),
+                RootlistItem::Playlist("d".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_rootlist_unclosed_folder() {
+        let rootlist =
+            RootlistItem::parse_uris(vec!["spotify:start-group:f1:Folder", "spotify:playlist:a"]);
+
+        assert_eq!(
+            rootlist,
+            vec![RootlistItem::Folder(PlaylistFolder {
+                id: "f1".to_string(),
+                name: "Folder".to_string(),
+                items: vec![RootlistItem::Playlist("a".to_string())],
+            })]
+        );
+    }
+
+    #[test]
+    fn parse_playlist_label() {
+        assert_eq!(
+            PlaylistLabel::parse("color:red"),
+            Some(PlaylistLabel::Color("red".to_string()))
+        );
+        assert_eq!(
+            PlaylistLabel::parse("emoji:🎸"),
+            Some(PlaylistLabel::Emoji("🎸".to_string()))
+        );
+        assert_eq!(PlaylistLabel::parse("color:chartreuse"), None);
+        assert_eq!(PlaylistLabel::parse("emoji:"), None);
+        assert_eq!(PlaylistLabel::parse("garbage"), None);
+
+        let label = PlaylistLabel::Emoji("🎸".to_string());
+        assert_eq!(PlaylistLabel::parse(&label.serialize()), Some(label));
+    }
+
     #[test]
     fn resize_batch() {
         let batch = SongBatch {
@@ -309,4 +598,43 @@ mod tests {
         assert_eq!(&batches.get(0).unwrap().songs.get(0).unwrap().id, "1");
         assert_eq!(&batches.get(1).unwrap().songs.get(0).unwrap().id, "3");
     }
+
+    #[test]
+    fn test_deleted_playlist_recovery_period() {
+        let playlist = DeletedPlaylist::new(PlaylistSummary {
+            id: "id".to_string(),
+            title: "title".to_string(),
+        });
+        let deleted_at = playlist.deleted_at;
+        let day = Duration::from_secs(24 * 60 * 60);
+
+        assert!(playlist.is_recoverable(deleted_at));
+        assert!(playlist.is_recoverable(deleted_at + 89 * day));
+        assert!(!playlist.is_recoverable(deleted_at + 90 * day));
+        assert!(playlist.is_recoverable(deleted_at - day));
+    }
+
+    #[test]
+    fn test_mosaic_arts() {
+        let with_art = |id: &str, art: &str| SongDescription {
+            art: Some(art.to_string()),
+            ..song(id)
+        };
+
+        let songs = vec![
+            with_art("1", "a"),
+            with_art("2", "a"),
+            song("3"),
+            with_art("4", "b"),
+            with_art("5", "c"),
+            with_art("6", "d"),
+            with_art("7", "e"),
+        ];
+        assert_eq!(mosaic_arts(&songs), vec!["a", "b", "c", "d"]);
+
+        let songs = vec![with_art("1", "a"), with_art("2", "b")];
+        assert_eq!(mosaic_arts(&songs), vec!["a"]);
+
+        assert!(mosaic_arts(&[song("1")]).is_empty());
+    }
 }
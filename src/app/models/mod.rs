@@ -32,14 +32,16 @@ impl From<AlbumDescription> for AlbumModel {
 
 impl From<&PlaylistDescription> for AlbumModel {
     fn from(playlist: &PlaylistDescription) -> Self {
-        AlbumModel::new(
+        let model = AlbumModel::new(
             &playlist.owner.display_name,
             &playlist.title,
             // Playlists do not have their released date since they are expected to be updated anytime.
             None,
             playlist.art.as_ref(),
             &playlist.id,
-        )
+        );
+        model.set_mosaic(playlist.mosaic.clone());
+        model
     }
 }
 
@@ -12,14 +12,17 @@ use crate::app::models::*;
 // Eventually commit() must be called to send an update signal with merged affected ranges
 #[must_use]
 pub struct SongListModelPending<'a> {
-    change: Option<ListRangeUpdate>,
+    changes: Vec<ListRangeUpdate>,
     song_list_model: &'a mut SongListModel,
 }
 
 impl<'a> SongListModelPending<'a> {
-    fn new(change: Option<ListRangeUpdate>, song_list_model: &'a mut SongListModel) -> Self {
+    fn new(
+        changes: impl IntoIterator<Item = ListRangeUpdate>,
+        song_list_model: &'a mut SongListModel,
+    ) -> Self {
         Self {
-            change,
+            changes: changes.into_iter().collect(),
             song_list_model,
         }
     }
@@ -29,31 +32,34 @@ impl<'a> SongListModelPending<'a> {
         Op: FnOnce(&mut SongListModel) -> SongListModelPending<'_> + 'static,
     {
         let Self {
-            change,
+            mut changes,
             song_list_model,
         } = self;
 
-        let new_change = op(song_list_model).change;
+        let mut new_changes = op(song_list_model).changes.into_iter();
 
-        let merged_change = if let (Some(change), Some(new_change)) = (change, new_change) {
-            Some(change.merge(new_change))
-        } else {
-            change.or(new_change)
-        };
+        // Changes are sequential: the last one we had and the first new one can always be merged
+        match (changes.last_mut(), new_changes.next()) {
+            (Some(change), Some(new_change)) => *change = change.merge(new_change),
+            (None, Some(new_change)) => changes.push(new_change),
+            _ => {}
+        }
+        changes.extend(new_changes);
 
         Self {
-            change: merged_change,
+            changes,
             song_list_model,
         }
     }
 
     pub fn commit(self) -> bool {
         let Self {
-            change,
+            changes,
             song_list_model,
         } = self;
-        song_list_model.notify_changes(change);
-        change.is_some()
+        let changed = !changes.is_empty();
+        song_list_model.notify_changes(changes);
+        changed
     }
 }
 
@@ -161,8 +167,14 @@ impl SongListModel {
     }
 
     pub fn remove(&mut self, ids: &[String]) -> SongListModelPending {
-        let change = self.inner_mut().remove(ids);
-        SongListModelPending::new(Some(change), self)
+        let changes = self.inner_mut().remove(ids);
+        SongListModelPending::new(changes, self)
+    }
+
+    // Replace all songs with those of the batch, keeping the songs (and their position in the UI) that didn't change
+    pub fn replace(&mut self, song_batch: SongBatch) -> SongListModelPending {
+        let changes = self.inner_mut().replace(song_batch);
+        SongListModelPending::new(changes, self)
     }
 
     pub fn move_down(&mut self, a: usize) -> SongListModelPending {
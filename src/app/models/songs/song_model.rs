@@ -4,6 +4,7 @@ use gio::prelude::*;
 use glib::{subclass::prelude::*, SignalHandlerId};
 use std::{cell::Ref, ops::Deref};
 
+use crate::app::components::artist_links::artist_links_markup;
 use crate::app::components::utils::format_duration;
 use crate::app::models::*;
 
@@ -27,6 +28,10 @@ impl SongModel {
         self.set_property("selected", is_selected);
     }
 
+    pub fn set_saved(&self, is_saved: bool) {
+        self.set_property("saved", is_saved);
+    }
+
     pub fn get_playing(&self) -> bool {
         self.property("playing")
     }
@@ -47,9 +52,10 @@ impl SongModel {
         );
     }
 
-    pub fn bind_artist(&self, o: &impl ObjectType, property: &str) {
+    // Markup with a link to each artist, for a label with use-markup
+    pub fn bind_artist_links(&self, o: &impl ObjectType, property: &str) {
         self.imp().push_binding(
-            self.bind_property("artist", o, property)
+            self.bind_property("artist-links", o, property)
                 .flags(glib::BindingFlags::DEFAULT | glib::BindingFlags::SYNC_CREATE)
                 .build(),
         );
@@ -87,6 +93,14 @@ impl SongModel {
         );
     }
 
+    pub fn bind_saved(&self, o: &impl ObjectType, property: &str) {
+        self.imp().push_binding(
+            self.bind_property("saved", o, property)
+                .flags(glib::BindingFlags::DEFAULT | glib::BindingFlags::SYNC_CREATE)
+                .build(),
+        );
+    }
+
     pub fn unbind_all(&self) {
         self.imp().unbind_all(self);
     }
@@ -150,11 +164,13 @@ mod imp {
     }
 
     lazy_static! {
-        static ref PROPERTIES: [glib::ParamSpec; 8] = [
+        static ref PROPERTIES: [glib::ParamSpec; 9] = [
             glib::ParamSpecString::builder("id").read_only().build(),
             glib::ParamSpecUInt::builder("index").read_only().build(),
             glib::ParamSpecString::builder("title").read_only().build(),
-            glib::ParamSpecString::builder("artist").read_only().build(),
+            glib::ParamSpecString::builder("artist-links")
+                .read_only()
+                .build(),
             glib::ParamSpecString::builder("duration")
                 .read_only()
                 .build(),
@@ -167,6 +183,7 @@ mod imp {
             glib::ParamSpecBoolean::builder("selected")
                 .readwrite()
                 .build(),
+            glib::ParamSpecBoolean::builder("saved").readwrite().build(),
         ];
     }
 
@@ -181,20 +198,27 @@ mod imp {
                     let is_playing = value
                         .get()
                         .expect("type conformity checked by `Object::set_property`");
-                    let SongState { is_selected, .. } = self.state.get();
                     self.state.set(SongState {
                         is_playing,
-                        is_selected,
+                        ..self.state.get()
                     });
                 }
                 "selected" => {
                     let is_selected = value
                         .get()
                         .expect("type conformity checked by `Object::set_property`");
-                    let SongState { is_playing, .. } = self.state.get();
                     self.state.set(SongState {
-                        is_playing,
                         is_selected,
+                        ..self.state.get()
+                    });
+                }
+                "saved" => {
+                    let is_saved = value
+                        .get()
+                        .expect("type conformity checked by `Object::set_property`");
+                    self.state.set(SongState {
+                        is_saved,
+                        ..self.state.get()
                     });
                 }
                 _ => unimplemented!(),
@@ -218,12 +242,12 @@ mod imp {
                     .expect("song set at constructor")
                     .title
                     .to_value(),
-                "artist" => self
+                "artist-links" => self
                     .song
                     .borrow()
                     .as_ref()
+                    .map(|s| artist_links_markup(&s.artists))
                     .expect("song set at constructor")
-                    .artists_name()
                     .to_value(),
                 "id" => self
                     .song
@@ -248,6 +272,7 @@ mod imp {
                     .to_value(),
                 "playing" => self.state.get().is_playing.to_value(),
                 "selected" => self.state.get().is_selected.to_value(),
+                "saved" => self.state.get().is_saved.to_value(),
                 _ => unimplemented!(),
             }
         }
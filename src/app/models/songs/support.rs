@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::convert::{TryFrom, TryInto};
 
 use crate::app::models::*;
@@ -117,6 +117,71 @@ impl ListRangeUpdate {
     }
 }
 
+// Past that many comparisons, diffing two lists is not worth it
+const MAX_DIFF_SIZE: usize = 1_000_000;
+
+// Computes the updates turning a list of songs into another, songs being identified by their id.
+// Songs found in both lists (in the same order) are left untouched, the rest is removed or inserted:
+// a song that moved is removed, then inserted back at its new position.
+// Updates are meant to be applied in order, each one to the list as left by the previous one.
+pub fn diff_ids(old: &[String], new: &[String]) -> Vec<ListRangeUpdate> {
+    // Most edits only affect a few songs, so let's not compare the common start and end of both lists
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let (old, new) = (&old[prefix..], &new[prefix..]);
+    let suffix = old
+        .iter()
+        .rev()
+        .zip(new.iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let (old, new) = (&old[..old.len() - suffix], &new[..new.len() - suffix]);
+
+    let (n, m) = (old.len(), new.len());
+    if n == 0 && m == 0 {
+        return vec![];
+    }
+    if n.saturating_mul(m) > MAX_DIFF_SIZE {
+        return vec![ListRangeUpdate(prefix as i32, n as i32, m as i32)];
+    }
+
+    // common[i][j] is the length of the longest common subsequence of old[i..] and new[j..]
+    let mut common = vec![vec![0u32; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            common[i][j] = if old[i] == new[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                u32::max(common[i + 1][j], common[i][j + 1])
+            };
+        }
+    }
+
+    let mut updates = vec![];
+    let (mut i, mut j, mut position) = (0, 0, prefix);
+    while i < n || j < m {
+        if i < n && j < m && old[i] == new[j] {
+            i += 1;
+            j += 1;
+            position += 1;
+            continue;
+        }
+        // Everything up to the next common song is one update
+        let (mut removed, mut added) = (0, 0);
+        while (i < n || j < m) && !(i < n && j < m && old[i] == new[j]) {
+            if j == m || (i < n && common[i + 1][j] >= common[i][j + 1]) {
+                i += 1;
+                removed += 1;
+            } else {
+                j += 1;
+                added += 1;
+            }
+        }
+        updates.push(ListRangeUpdate(position as i32, removed, added));
+        position += added as usize;
+    }
+    updates
+}
+
 // A list of songs that supports
 // - batch loading (with non contiguous batches if songs are accessed in random order)
 // - O(1) time access to a song by its id
@@ -231,22 +296,53 @@ impl SongList {
         ListRangeUpdate::removed(0, len)
     }
 
-    pub fn remove(&mut self, ids: &[String]) -> ListRangeUpdate {
-        let len = self.total_loaded;
+    fn loaded_ids(&self) -> Vec<String> {
+        self.iter_ids_from(0).map(|(_, id)| id.clone()).collect()
+    }
+
+    pub fn remove(&mut self, ids: &[String]) -> Vec<ListRangeUpdate> {
+        let old_ids = self.loaded_ids();
         let mut batches = HashMap::<usize, Vec<String>>::default();
-        self.iter_ids_from(0)
-            .filter(|(_, s)| !ids.contains(s))
+        old_ids
+            .iter()
+            .filter(|s| !ids.contains(s))
             // Removing is expensive, we have to recreate all batches
-            .for_each(|(_, next)| {
+            .for_each(|next| {
                 Self::batches_add(&mut batches, self.batch_size, next);
             });
         self.last_batch_key = batches.len().saturating_sub(1);
         self.batches = batches;
-        let removed = ids.len();
+        let new_ids = self.loaded_ids();
+        let removed = old_ids.len() - new_ids.len();
         self.total = self.total.saturating_sub(removed);
         self.total_loaded = self.total_loaded.saturating_sub(removed);
-        // Lazy computation of the affected range, basically assume everything has changed
-        ListRangeUpdate(0, len as i32, self.total_loaded as i32)
+        diff_ids(&old_ids, &new_ids)
+    }
+
+    // Replaces the content of the list with the songs of that batch (which must start the list),
+    // returning only the changes needed to get there: songs that are still around are left untouched.
+    pub fn replace(&mut self, SongBatch { songs, batch }: SongBatch) -> Vec<ListRangeUpdate> {
+        assert_eq!(batch.offset, 0);
+
+        let old_ids = self.loaded_ids();
+        let mut batches = HashMap::<usize, Vec<String>>::default();
+        let mut new_ids = Vec::with_capacity(songs.len());
+        for song in songs {
+            Self::batches_add(&mut batches, self.batch_size, &song.id);
+            new_ids.push(song.id.clone());
+            // Keep the existing model of songs we already had, that's what the UI is bound to
+            self.indexed_songs
+                .entry(song.id.clone())
+                .or_insert_with(|| SongModel::new(song));
+        }
+        let kept: HashSet<&String> = new_ids.iter().collect();
+        self.indexed_songs.retain(|id, _| kept.contains(id));
+
+        self.total = usize::max(batch.total, new_ids.len());
+        self.total_loaded = new_ids.len();
+        self.last_batch_key = batches.len().saturating_sub(1);
+        self.batches = batches;
+        diff_ids(&old_ids, &new_ids)
     }
 
     pub fn append(&mut self, songs: Vec<SongDescription>) -> ListRangeUpdate {
@@ -468,6 +564,7 @@ mod tests {
             },
             duration: 1000,
             art: None,
+            preview_url: None,
             track_number: None,
         }
     }
@@ -625,8 +722,9 @@ mod tests {
         let mut list = SongList::new_from_initial_batch(batch(0));
         list.add(batch(1));
 
-        list.remove(&["song0".to_string()]);
+        let changes = list.remove(&["song0".to_string()]);
 
+        assert_eq!(changes, vec![ListRangeUpdate::removed(0, 1)]);
         assert_eq!(list.partial_len(), 3);
 
         let mut list_iter = list.iter();
@@ -636,6 +734,69 @@ mod tests {
         assert!(list_iter.next().is_none());
     }
 
+    fn ids(ids: &[&str]) -> Vec<String> {
+        ids.iter().map(|id| id.to_string()).collect()
+    }
+
+    #[test]
+    fn test_diff() {
+        let old = ids(&["a", "b", "c", "d"]);
+
+        assert_eq!(diff_ids(&old, &old), vec![]);
+        assert_eq!(
+            diff_ids(&old, &ids(&["a", "c", "d"])),
+            vec![ListRangeUpdate::removed(1, 1)]
+        );
+        assert_eq!(
+            diff_ids(&old, &ids(&["a", "b", "x", "c", "d"])),
+            vec![ListRangeUpdate::inserted(2, 1)]
+        );
+        assert_eq!(
+            diff_ids(&old, &ids(&["b", "c", "a", "d"])),
+            vec![
+                ListRangeUpdate::removed(0, 1),
+                ListRangeUpdate::inserted(2, 1)
+            ]
+        );
+        assert_eq!(
+            diff_ids(&old, &ids(&["x", "b", "c", "y"])),
+            vec![ListRangeUpdate::updated(0), ListRangeUpdate::updated(3)]
+        );
+    }
+
+    #[test]
+    fn test_replace() {
+        let mut list = SongList::new_from_initial_batch(batch(0));
+        list.add(batch(1));
+
+        let changes = list.replace(SongBatch {
+            batch: Batch {
+                offset: 0,
+                batch_size: 4,
+                total: 9,
+            },
+            songs: vec![song("song0"), song("song2"), song("new"), song("song3")],
+        });
+
+        assert_eq!(
+            changes,
+            vec![
+                ListRangeUpdate::removed(1, 1),
+                ListRangeUpdate::inserted(2, 1)
+            ]
+        );
+        assert_eq!(list.partial_len(), 4);
+        assert_eq!(list.len(), 9);
+        assert!(list.get("song1").is_none());
+
+        let mut list_iter = list.iter();
+        assert_eq!(list_iter.next().unwrap().description().id, "song0");
+        assert_eq!(list_iter.next().unwrap().description().id, "song2");
+        assert_eq!(list_iter.next().unwrap().description().id, "new");
+        assert_eq!(list_iter.next().unwrap().description().id, "song3");
+        assert!(list_iter.next().is_none());
+    }
+
     #[test]
     fn test_batch_for() {
         let mut list = SongList::new_from_initial_batch(batch(0));
@@ -0,0 +1,156 @@
+use futures::future::{abortable, AbortHandle, Future};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+// How close (in rows) to the end of a list we should be before fetching the next page
+pub const PREFETCH_ROWS: u32 = 3;
+
+// Keeps track of the pages of a list currently being fetched, so that scrolling near the end
+// of that list doesn't trigger the same request over and over.
+// A page stays reserved once fetched, until its owner releases it when the list is updated: until then,
+// the state still points at that page as the next one to load.
+// Pending requests are aborted when the prefetcher is cancelled or dropped (typically when the user
+// navigates away from the screen that owns it), so that we don't bother loading stale pages.
+#[derive(Default)]
+pub struct Prefetcher {
+    // Without a handle once fetched
+    in_flight: Arc<Mutex<HashMap<usize, Option<AbortHandle>>>>,
+}
+
+impl Prefetcher {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    #[cfg(test)]
+    pub fn is_fetching(&self, offset: usize) -> bool {
+        self.in_flight.lock().unwrap().contains_key(&offset)
+    }
+
+    // Wraps the future fetching the page at the given offset, returns None if that page is already being fetched.
+    // The wrapped future resolves to None if it was aborted. A page that failed to load can be fetched again right away.
+    pub fn fetch<F, T, E>(
+        &self,
+        offset: usize,
+        future: F,
+    ) -> Option<impl Future<Output = Option<Result<T, E>>>>
+    where
+        F: Future<Output = Result<T, E>>,
+    {
+        let mut in_flight = self.in_flight.lock().unwrap();
+        if in_flight.contains_key(&offset) {
+            return None;
+        }
+
+        let (future, handle) = abortable(future);
+        in_flight.insert(offset, Some(handle));
+
+        let in_flight = Arc::clone(&self.in_flight);
+        Some(async move {
+            let result = future.await.ok();
+            match &result {
+                Some(Ok(_)) => {
+                    in_flight.lock().unwrap().insert(offset, None);
+                }
+                Some(Err(_)) => {
+                    in_flight.lock().unwrap().remove(&offset);
+                }
+                // Cancelled, nothing left to clean up
+                None => {}
+            }
+            result
+        })
+    }
+
+    // To be called once the list was updated: the pages fetched so far are in it now
+    pub fn release_fetched(&self) {
+        self.in_flight
+            .lock()
+            .unwrap()
+            .retain(|_, handle| handle.is_some());
+    }
+
+    pub fn cancel(&self) {
+        for handle in self
+            .in_flight
+            .lock()
+            .unwrap()
+            .drain()
+            .filter_map(|(_, h)| h)
+        {
+            handle.abort();
+        }
+    }
+}
+
+impl Drop for Prefetcher {
+    fn drop(&mut self) {
+        self.cancel();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use futures::executor::block_on;
+    use futures::future::pending;
+
+    #[test]
+    fn test_dedup_in_flight() {
+        let prefetcher = Prefetcher::new();
+
+        let first = prefetcher.fetch(20, async { Ok::<_, ()>(1) });
+        assert!(first.is_some());
+        assert!(prefetcher.is_fetching(20));
+        assert!(prefetcher.fetch(20, async { Ok::<_, ()>(2) }).is_none());
+        assert!(prefetcher.fetch(40, async { Ok::<_, ()>(3) }).is_some());
+
+        assert_eq!(block_on(first.unwrap()), Some(Ok(1)));
+        // Not in the list yet
+        assert!(prefetcher.fetch(20, async { Ok::<_, ()>(4) }).is_none());
+
+        prefetcher.release_fetched();
+        assert!(!prefetcher.is_fetching(20));
+        assert!(prefetcher.fetch(20, async { Ok::<_, ()>(5) }).is_some());
+    }
+
+    #[test]
+    fn test_retry_failed() {
+        let prefetcher = Prefetcher::new();
+
+        let fetch = prefetcher.fetch(20, async { Err::<(), _>("offline") });
+        assert_eq!(block_on(fetch.unwrap()), Some(Err("offline")));
+        assert!(!prefetcher.is_fetching(20));
+    }
+
+    #[test]
+    fn test_release_keeps_in_flight() {
+        let prefetcher = Prefetcher::new();
+
+        let _fetch = prefetcher.fetch(0, pending::<Result<(), ()>>()).unwrap();
+        prefetcher.release_fetched();
+
+        assert!(prefetcher.is_fetching(0));
+    }
+
+    #[test]
+    fn test_cancel() {
+        let prefetcher = Prefetcher::new();
+
+        let fetch = prefetcher.fetch(0, pending::<Result<(), ()>>()).unwrap();
+        prefetcher.cancel();
+
+        assert!(!prefetcher.is_fetching(0));
+        assert_eq!(block_on(fetch), None);
+    }
+
+    #[test]
+    fn test_cancel_on_drop() {
+        let prefetcher = Prefetcher::new();
+        let fetch = prefetcher.fetch(0, pending::<Result<(), ()>>()).unwrap();
+        drop(prefetcher);
+
+        assert_eq!(block_on(fetch), None);
+    }
+}
@@ -1,5 +1,37 @@
 use rand::{rngs::SmallRng, RngCore, SeedableRng};
 
+// How the order of tracks is picked when shuffling
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShuffleMode {
+    // Any track can come up next
+    Random,
+    // Avoid playing tracks from the same artist back to back when possible
+    ArtistSpread,
+    // Play tracks that were played recently last, when possible
+    LeastRecent,
+}
+
+impl ShuffleMode {
+    // Same names as in the settings schema
+    pub fn from_nick(nick: &str) -> Option<Self> {
+        match nick {
+            "random" => Some(Self::Random),
+            "artist-spread" => Some(Self::ArtistSpread),
+            "least-recent" => Some(Self::LeastRecent),
+            _ => None,
+        }
+    }
+}
+
+impl Default for ShuffleMode {
+    fn default() -> Self {
+        Self::Random
+    }
+}
+
+// Setting this variable makes the shuffled order reproducible, which is handy when debugging
+const SEED_VAR: &str = "SPOT_SHUFFLE_SEED";
+
 // A random, resizable mapping (i-th element to play => j-th track) used to handle shuffled playlists
 // It's lazy: initially we don't compute what index i maps to
 // It's resizable: if our playlist grows or shrinks, we have to keep the generated mappings stable
@@ -14,7 +46,12 @@ pub struct LazyRandomIndex {
 
 impl Default for LazyRandomIndex {
     fn default() -> Self {
-        Self::from(SmallRng::from_entropy())
+        let seed = std::env::var(SEED_VAR)
+            .ok()
+            .and_then(|seed| seed.parse::<u64>().ok())
+            .unwrap_or_else(|| SmallRng::from_entropy().next_u64());
+        debug!("shuffle seed: {} (set {} to reuse it)", seed, SEED_VAR);
+        Self::from(SmallRng::seed_from_u64(seed))
     }
 }
 
@@ -37,6 +74,12 @@ impl LazyRandomIndex {
         }
     }
 
+    // Forgets the mappings after the i-th one: whatever comes next will be picked again
+    // This is used to reshuffle what's left to play without touching what's playing
+    pub fn forget_after(&mut self, i: usize) {
+        self.generated = usize::min(i + 1, self.generated);
+    }
+
     // Grow or shrink
     pub fn resize(&mut self, size: usize) {
         if size >= self.indices.len() {
@@ -69,6 +112,14 @@ impl LazyRandomIndex {
 
     // Generate all mappings until the mapping for i has been generated
     pub fn next_until(&mut self, i: usize) -> Option<usize> {
+        self.next_until_avoiding(i, |_, _| false)
+    }
+
+    // Same as next_until, but using next_avoiding
+    pub fn next_until_avoiding<F>(&mut self, i: usize, avoid: F) -> Option<usize>
+    where
+        F: Fn(Option<usize>, usize) -> bool,
+    {
         if i >= self.indices.len() {
             return None;
         }
@@ -77,13 +128,23 @@ impl LazyRandomIndex {
             if self.generated > i {
                 break Some(self.indices[i]);
             }
-            self.next();
+            self.next_avoiding(&avoid);
         }
     }
 
     // Generate the next mapping
+    #[cfg(test)]
     pub fn next(&mut self) -> Option<usize> {
-        if self.indices.len() < self.generated {
+        self.next_avoiding(|_, _| false)
+    }
+
+    // Generate the next mapping, avoiding (if possible) any index we'd rather not have next,
+    // according to `avoid(previous, candidate)` where previous is the previously mapped index, if any
+    pub fn next_avoiding<F>(&mut self, avoid: F) -> Option<usize>
+    where
+        F: Fn(Option<usize>, usize) -> bool,
+    {
+        if self.indices.len() <= self.generated {
             return None;
         }
 
@@ -104,7 +165,16 @@ impl LazyRandomIndex {
         // [1, 0, 2], generated = 1, we swapped 0 and 1
         // [1, 0, 2], generated = 2, we swapped 1 and 1 (no-op)
         // [1, 0, 2], generated = 3, no-op again (no choice, only one element left to place)
-        let next = (self.rng.next_u64() as usize) % (self.indices.len() - last) + last;
+        let remaining = self.indices.len() - last;
+        let picked = (self.rng.next_u64() as usize) % remaining;
+
+        // If what we picked is to be avoided, we look for the next acceptable candidate,
+        // and settle for our first pick if there's none
+        let previous = last.checked_sub(1).map(|i| self.indices[i]);
+        let next = (0..remaining)
+            .map(|offset| (picked + offset) % remaining + last)
+            .find(|&candidate| !avoid(previous, self.indices[candidate]))
+            .unwrap_or(picked + last);
         Some(self.pick_next(next))
     }
 
@@ -195,4 +265,54 @@ mod tests {
         index.reset_picking_first(2);
         assert_eq!(index.get(0), Some(2));
     }
+
+    #[test]
+    fn test_forget_after() {
+        let mut index = LazyRandomIndex::from(rng_for_test());
+
+        index.grow(10);
+        index.next_until(9);
+        let played = (0..3).filter_map(|i| index.get(i)).collect::<Vec<usize>>();
+
+        // Reshuffling while the third element plays keeps it (and the ones before) in place
+        index.forget_after(2);
+        assert_eq!(index.get(3), None);
+        index.next_until(9);
+        assert_eq!(
+            (0..3).filter_map(|i| index.get(i)).collect::<Vec<usize>>(),
+            played
+        );
+
+        let mut values = (0..10).filter_map(|i| index.get(i)).collect::<Vec<usize>>();
+        values.sort();
+        assert_eq!(values, (0..10).collect::<Vec<usize>>());
+    }
+
+    #[test]
+    fn test_spread() {
+        let mut index = LazyRandomIndex::from(rng_for_test());
+
+        // Think of even and odd indices as tracks from two different artists
+        let same_artist = |a: usize, b: usize| a % 2 == b % 2;
+        index.grow(4);
+        index.next_until_avoiding(3, |a, b| a.map(|a| same_artist(a, b)).unwrap_or(false));
+
+        let values = (0..4).filter_map(|i| index.get(i)).collect::<Vec<usize>>();
+        assert_eq!(values.len(), 4);
+        assert!(values.windows(2).all(|w| !same_artist(w[0], w[1])));
+    }
+
+    #[test]
+    fn test_avoid_without_previous() {
+        let mut index = LazyRandomIndex::from(rng_for_test());
+
+        // Think of indices below 3 as recently played tracks
+        let recent = |i: usize| i < 3;
+        index.grow(5);
+        index.next_until_avoiding(4, |_, i| recent(i));
+
+        let values = (0..5).filter_map(|i| index.get(i)).collect::<Vec<usize>>();
+        assert_eq!(values.len(), 5);
+        assert!(values[..2].iter().all(|i| !recent(*i)));
+    }
 }
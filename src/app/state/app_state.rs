@@ -1,14 +1,21 @@
 use std::borrow::Cow;
 
-use crate::app::models::{PlaylistDescription, PlaylistSummary};
+use crate::app::models::{
+    Alarm, AlbumDescription, PlaylistDescription, PlaylistSummary, ShowSummary,
+};
 use crate::app::state::{
     browser_state::{BrowserAction, BrowserEvent, BrowserState},
     login_state::{LoginAction, LoginEvent, LoginState},
+    pending_changes::{PendingChange, PendingChanges},
     playback_state::{PlaybackAction, PlaybackEvent, PlaybackState},
+    release_alerts::{latest_release_date, releases_after, ReleaseAlerts},
+    saved_shows::SavedShows,
     selection_state::{SelectionAction, SelectionContext, SelectionEvent, SelectionState},
     settings_state::{SettingsAction, SettingsEvent, SettingsState},
     ScreenName, UpdatableState,
 };
+use crate::app::SpotifyUri;
+use crate::settings::SpotSettings;
 
 // It's a big one...
 // All possible actions!
@@ -25,7 +32,13 @@ pub enum AppAction {
     Start,
     Raise,
     ShowNotification(String),
+    // How many tracks were added to Liked Songs so far, out of how many
+    ShowTracksSavingProgress(usize, usize),
+    // Tracks were added to Liked Songs, with a way to undo it
+    ShowTracksSavedNotification(Vec<String>),
     ViewNowPlaying,
+    // Plays a track we only know the id of (from a link): it has to be fetched first
+    PlayTrack(String),
     // Cross-state actions
     QueueSelection,
     DequeueSelection,
@@ -37,33 +50,42 @@ pub enum AppAction {
     CancelSelection,
     CreatePlaylist(PlaylistDescription),
     UpdatePlaylistName(PlaylistSummary),
+    DeletePlaylist(PlaylistSummary),
+    RecoverPlaylist(PlaylistDescription),
+    // Applies a destructive change in the app only, until it is committed (or undone)
+    DeferChange(PendingChange),
+    Undo(usize),
+    CommitPending(usize),
+    // Whether to look for new releases of an artist
+    SetReleaseAlert(String, bool),
+    // The releases of an artist we look out for, as we just fetched them
+    RecordReleases(String, Vec<AlbumDescription>),
+    // When we last looked for new releases, in seconds since epoch
+    SetReleasesChecked(u64),
+    MarkReleasesSeen(String),
+    // The shows (podcasts) the user follows, as we just fetched them
+    SetSavedShows(Vec<ShowSummary>),
+    // The latest episodes of a show, as we just fetched them
+    RecordEpisodes(String, Vec<String>),
+    // When we last looked for new episodes, in seconds since epoch
+    SetEpisodesChecked(u64),
+    MarkEpisodePlayed(String),
+    MarkShowPlayed(String),
+    // Whether we can reach the network, as last reported by the system
+    SetOnline(bool),
+    ScheduleAlarm(Alarm),
+    CancelAlarm(u32),
+    // Several actions to process as one (see ActionDispatcher::dispatch_many), never reaches the state
+    Batch(Vec<AppAction>),
 }
 
 // Not actual actions, just neat wrappers
 impl AppAction {
-    // An action to open a Spotify URI
+    // An action to open a Spotify URI or link
     #[allow(non_snake_case)]
     pub fn OpenURI(uri: String) -> Option<Self> {
         debug!("parsing {}", &uri);
-        let mut parts = uri.split(':');
-        if parts.next()? != "spotify" {
-            return None;
-        }
-
-        // Might start with /// because of https://gitlab.gnome.org/GNOME/glib/-/issues/1886/
-        let action = parts
-            .next()?
-            .strip_prefix("///")
-            .filter(|p| !p.is_empty())?;
-        let data = parts.next()?;
-
-        match action {
-            "album" => Some(Self::ViewAlbum(data.to_string())),
-            "artist" => Some(Self::ViewArtist(data.to_string())),
-            "playlist" => Some(Self::ViewPlaylist(data.to_string())),
-            "user" => Some(Self::ViewUser(data.to_string())),
-            _ => None,
-        }
+        SpotifyUri::parse(&uri).map(Self::from)
     }
 
     #[allow(non_snake_case)]
@@ -92,6 +114,18 @@ impl AppAction {
     }
 }
 
+impl From<SpotifyUri> for AppAction {
+    fn from(uri: SpotifyUri) -> Self {
+        match uri {
+            SpotifyUri::Track(id) => Self::PlayTrack(id),
+            SpotifyUri::Album(id) => Self::ViewAlbum(id),
+            SpotifyUri::Artist(id) => Self::ViewArtist(id),
+            SpotifyUri::Playlist(id) => Self::ViewPlaylist(id),
+            SpotifyUri::User(id) => Self::ViewUser(id),
+        }
+    }
+}
+
 // Actions mutate stuff, and we know what changed thanks to these events
 #[derive(Clone, Debug)]
 pub enum AppEvent {
@@ -104,29 +138,107 @@ pub enum AppEvent {
     Raised,
     NotificationShown(String),
     PlaylistCreatedNotificationShown(String),
+    TracksSavingProgressShown(usize, usize),
+    TracksSavedNotificationShown(Vec<String>),
+    PlaylistDeleted(PlaylistSummary),
+    PlaylistRecovered(String),
+    // A change that can be undone for a little while, with its id
+    ChangeDeferred(usize, PendingChange),
+    ChangeUndone(PendingChange),
+    // The change can no longer be undone: time to tell Spotify
+    ChangeCommitted(PendingChange),
     NowPlayingShown,
+    TrackPlayRequested(String),
+    ReleaseAlertChanged(String),
+    NewReleasesFound(String, Vec<AlbumDescription>),
+    // Anything else about release alerts changed
+    ReleaseAlertsUpdated,
+    // The followed shows, or their unplayed episodes, changed
+    SavedShowsUpdated,
+    // We went offline (false) or came back online (true)
+    ConnectivityChanged(bool),
+    AlarmScheduled(Alarm),
+    AlarmCancelled(u32),
     SettingsEvent(SettingsEvent),
 }
 
+impl AppEvent {
+    // Whether this event only says that something changed (listeners then read the state again), and an
+    // event coming after it says the same
+    fn is_repeated_by(&self, later: &Self) -> bool {
+        match (self, later) {
+            (Self::PlaybackEvent(a), Self::PlaybackEvent(b)) => {
+                use PlaybackEvent::*;
+                matches!(
+                    (a, b),
+                    (PlaylistChanged, PlaylistChanged)
+                        | (SourceChanged, SourceChanged)
+                        | (ShuffleOrderChanged, ShuffleOrderChanged)
+                        | (AvailableDevicesChanged, AvailableDevicesChanged)
+                )
+            }
+            (Self::BrowserEvent(a), Self::BrowserEvent(b)) => a.is_refresh() && a == b,
+            (
+                Self::SelectionEvent(SelectionEvent::SelectionChanged),
+                Self::SelectionEvent(SelectionEvent::SelectionChanged),
+            ) => true,
+            (Self::ReleaseAlertsUpdated, Self::ReleaseAlertsUpdated) => true,
+            (Self::SavedShowsUpdated, Self::SavedShowsUpdated) => true,
+            _ => false,
+        }
+    }
+
+    // What a batch of actions amounts to: a change notified several times is only notified once, in
+    // the last place it came up so that it comes after anything it might depend on
+    pub fn consolidate(events: Vec<Self>) -> Vec<Self> {
+        let repeated: Vec<bool> = (0..events.len())
+            .map(|i| {
+                events[i + 1..]
+                    .iter()
+                    .any(|later| events[i].is_repeated_by(later))
+            })
+            .collect();
+        events
+            .into_iter()
+            .zip(repeated)
+            .filter(|(_, repeated)| !repeated)
+            .map(|(event, _)| event)
+            .collect()
+    }
+}
+
 // The actual state, split five-ways
 pub struct AppState {
     started: bool,
+    pub online: bool,
     pub playback: PlaybackState,
     pub browser: BrowserState,
     pub selection: SelectionState,
     pub logged_user: LoginState,
     pub settings: SettingsState,
+    pub pending_changes: PendingChanges,
+    pub release_alerts: ReleaseAlerts,
+    pub saved_shows: SavedShows,
 }
 
 impl AppState {
-    pub fn new() -> Self {
+    pub fn new(settings: SpotSettings) -> Self {
+        let mut playback = PlaybackState::with_volume(settings.volume.level);
+        playback.set_shuffle_mode(settings.shuffle_mode);
+        playback.set_shuffle_mode_by_source(settings.shuffle_mode_by_source.clone());
+        playback.set_previous_restart_threshold(settings.previous_restart_threshold);
+        playback.set_pause_during_calls(settings.pause_during_calls);
         Self {
             started: false,
-            playback: Default::default(),
+            online: true,
+            playback,
             browser: BrowserState::new(),
             selection: Default::default(),
             logged_user: Default::default(),
-            settings: Default::default(),
+            settings: SettingsState { settings },
+            pending_changes: Default::default(),
+            release_alerts: Default::default(),
+            saved_shows: Default::default(),
         }
     }
 
@@ -139,8 +251,15 @@ impl AppState {
             // Couple of actions that don't mutate the state (not intested in keeping track of what they change)
             // they're here just to have a consistent way of doing things (always an Action)
             AppAction::ShowNotification(c) => vec![AppEvent::NotificationShown(c)],
+            AppAction::ShowTracksSavingProgress(saved, total) => {
+                vec![AppEvent::TracksSavingProgressShown(saved, total)]
+            }
+            AppAction::ShowTracksSavedNotification(ids) => {
+                vec![AppEvent::TracksSavedNotificationShown(ids)]
+            }
             AppAction::ViewNowPlaying => vec![AppEvent::NowPlayingShown],
             AppAction::Raise => vec![AppEvent::Raised],
+            AppAction::PlayTrack(id) => vec![AppEvent::TrackPlayRequested(id)],
             // Cross-state actions: multiple "substates" are affected by these actions, that's why they're handled here
             // Might need some clean-up
             AppAction::QueueSelection => {
@@ -184,10 +303,8 @@ impl AppState {
             }
             AppAction::SaveSelection => {
                 let tracks = self.selection.take_selection();
-                let mut events: Vec<AppEvent> = forward_action(
-                    BrowserAction::SaveTracks(tracks),
-                    self.browser.home_state_mut().unwrap(),
-                );
+                let mut events: Vec<AppEvent> =
+                    forward_action(BrowserAction::SaveTracks(tracks), &mut self.browser);
                 events.push(SelectionEvent::SelectionModeChanged(false).into());
                 events
             }
@@ -198,10 +315,8 @@ impl AppState {
                     .into_iter()
                     .map(|s| s.id)
                     .collect();
-                let mut events: Vec<AppEvent> = forward_action(
-                    BrowserAction::RemoveSavedTracks(tracks),
-                    self.browser.home_state_mut().unwrap(),
-                );
+                let mut events: Vec<AppEvent> =
+                    forward_action(BrowserAction::RemoveSavedTracks(tracks), &mut self.browser);
                 events.push(SelectionEvent::SelectionModeChanged(false).into());
                 events
             }
@@ -243,12 +358,180 @@ impl AppState {
                 events.append(&mut more_events);
                 events
             }
+            AppAction::DeletePlaylist(playlist) => {
+                let mut events = forward_action(
+                    LoginAction::RemoveUserPlaylist(playlist.id.clone()),
+                    &mut self.logged_user,
+                );
+                let mut more_events = forward_action(
+                    BrowserAction::RemovePlaylist(playlist.id.clone()),
+                    &mut self.browser,
+                );
+                events.append(&mut more_events);
+                events.push(AppEvent::PlaylistDeleted(playlist));
+                events
+            }
+            AppAction::RecoverPlaylist(playlist) => {
+                let id = playlist.id.clone();
+                let owned = self.logged_user.user.as_ref() == Some(&playlist.owner.id);
+                let mut events = if owned {
+                    forward_action(
+                        LoginAction::PrependUserPlaylist(vec![playlist.clone().into()]),
+                        &mut self.logged_user,
+                    )
+                } else {
+                    vec![]
+                };
+                let mut more_events = forward_action(
+                    BrowserAction::PrependPlaylistsContent(vec![playlist]),
+                    &mut self.browser,
+                );
+                events.append(&mut more_events);
+                events.push(AppEvent::PlaylistRecovered(id));
+                events
+            }
+            AppAction::DeferChange(change) => {
+                let mut events = match &change {
+                    PendingChange::RemoveTracksFromPlaylist { playlist_id, uris } => {
+                        forward_action(
+                            BrowserAction::RemoveTracksFromPlaylist(
+                                playlist_id.clone(),
+                                uris.clone(),
+                            ),
+                            &mut self.browser,
+                        )
+                    }
+                    PendingChange::UnsaveAlbum(album) => forward_action(
+                        BrowserAction::UnsaveAlbum(album.id.clone()),
+                        &mut self.browser,
+                    ),
+                    // Same as DeletePlaylist, but it's too early to say it's deleted
+                    PendingChange::DeletePlaylist(playlist) => {
+                        let mut events = forward_action(
+                            LoginAction::RemoveUserPlaylist(playlist.id.clone()),
+                            &mut self.logged_user,
+                        );
+                        events.append(&mut forward_action(
+                            BrowserAction::RemovePlaylist(playlist.id.clone()),
+                            &mut self.browser,
+                        ));
+                        events
+                    }
+                };
+                let id = self.pending_changes.push(change.clone());
+                events.push(AppEvent::ChangeDeferred(id, change));
+                events
+            }
+            AppAction::Undo(id) => {
+                let change = match self.pending_changes.take(id) {
+                    Some(change) => change,
+                    None => return vec![],
+                };
+                // What can't be restored from here is reloaded (see PendingChangesCommitter)
+                let mut events = match &change {
+                    PendingChange::UnsaveAlbum(album) => {
+                        forward_action(BrowserAction::SaveAlbum(album.clone()), &mut self.browser)
+                    }
+                    _ => vec![],
+                };
+                events.push(AppEvent::ChangeUndone(change));
+                events
+            }
+            AppAction::CommitPending(id) => self
+                .pending_changes
+                .take(id)
+                .map(|change| vec![AppEvent::ChangeCommitted(change)])
+                .unwrap_or_default(),
+            AppAction::SetReleaseAlert(artist_id, enabled) => {
+                if enabled {
+                    self.release_alerts.subscribe(artist_id.clone());
+                } else {
+                    self.release_alerts.unsubscribe(&artist_id);
+                }
+                vec![AppEvent::ReleaseAlertChanged(artist_id)]
+            }
+            AppAction::RecordReleases(artist_id, albums) => {
+                let known_latest = self
+                    .release_alerts
+                    .known_latest(&artist_id)
+                    .unwrap_or_default()
+                    .to_string();
+                let latest = latest_release_date(&albums);
+                if self.release_alerts.record_check(&artist_id, latest) {
+                    let new = releases_after(albums, &known_latest);
+                    vec![
+                        AppEvent::NewReleasesFound(artist_id, new),
+                        AppEvent::ReleaseAlertsUpdated,
+                    ]
+                } else {
+                    vec![AppEvent::ReleaseAlertsUpdated]
+                }
+            }
+            AppAction::SetReleasesChecked(time) => {
+                self.release_alerts.last_checked = time;
+                vec![AppEvent::ReleaseAlertsUpdated]
+            }
+            AppAction::MarkReleasesSeen(artist_id) => {
+                if self.release_alerts.mark_seen(&artist_id) {
+                    vec![AppEvent::ReleaseAlertsUpdated]
+                } else {
+                    vec![]
+                }
+            }
+            AppAction::SetSavedShows(shows) => {
+                self.saved_shows.set_shows(shows);
+                vec![AppEvent::SavedShowsUpdated]
+            }
+            AppAction::RecordEpisodes(show_id, episode_ids) => {
+                self.saved_shows
+                    .progress
+                    .record_episodes(&show_id, episode_ids);
+                vec![AppEvent::SavedShowsUpdated]
+            }
+            AppAction::SetEpisodesChecked(time) => {
+                self.saved_shows.progress.last_checked = time;
+                vec![AppEvent::SavedShowsUpdated]
+            }
+            AppAction::MarkEpisodePlayed(episode_id) => {
+                if self.saved_shows.progress.mark_played(&episode_id) {
+                    vec![AppEvent::SavedShowsUpdated]
+                } else {
+                    vec![]
+                }
+            }
+            AppAction::MarkShowPlayed(show_id) => {
+                if self.saved_shows.progress.mark_show_played(&show_id) {
+                    vec![AppEvent::SavedShowsUpdated]
+                } else {
+                    vec![]
+                }
+            }
+            AppAction::SetOnline(online) => {
+                if self.online == online {
+                    return vec![];
+                }
+                self.online = online;
+                vec![AppEvent::ConnectivityChanged(online)]
+            }
+            AppAction::ScheduleAlarm(alarm) => vec![AppEvent::AlarmScheduled(alarm)],
+            AppAction::CancelAlarm(id) => vec![AppEvent::AlarmCancelled(id)],
             // As for all other actions, we forward them to the substates :)
             AppAction::PlaybackAction(a) => forward_action(a, &mut self.playback),
             AppAction::BrowserAction(a) => forward_action(a, &mut self.browser),
             AppAction::SelectionAction(a) => forward_action(a, &mut self.selection),
             AppAction::LoginAction(a) => forward_action(a, &mut self.logged_user),
-            AppAction::SettingsAction(a) => forward_action(a, &mut self.settings),
+            AppAction::SettingsAction(a) => {
+                let events = forward_action(a, &mut self.settings);
+                let settings = &self.settings.settings;
+                self.playback.set_shuffle_mode(settings.shuffle_mode);
+                self.playback
+                    .set_shuffle_mode_by_source(settings.shuffle_mode_by_source.clone());
+                self.playback
+                    .set_previous_restart_threshold(settings.previous_restart_threshold);
+                self.playback
+                    .set_pause_during_calls(settings.pause_during_calls);
+                events
+            }
             _ => vec![],
         }
     }
@@ -1,9 +1,11 @@
 use super::{
-    AppAction, AppEvent, ArtistState, DetailsState, HomeState, PlaylistDetailsState, ScreenName,
-    SearchState, UpdatableState, UserState,
+    AppAction, AppEvent, ArtistState, DetailsState, HomeState, LibraryIndex, LibraryItem,
+    LibraryItemKind, LibrarySort, PlaylistDetailsState, ScreenName, SearchState, UpdatableState,
+    UserState,
 };
 use crate::app::models::*;
 use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
 use std::iter::Iterator;
 
 // Actions that affect any "screen" that we push over time
@@ -14,16 +16,27 @@ pub enum BrowserAction {
     SetLibraryContent(Vec<AlbumDescription>),
     PrependPlaylistsContent(Vec<PlaylistDescription>),
     AppendLibraryContent(Vec<AlbumDescription>),
+    // How the saved albums are shown, applied to what's already loaded (see LibraryOptions)
+    SetLibrarySort(LibrarySort),
+    SetLibraryFilter(String),
+    SetLibraryCompact(bool),
     SetPlaylistsContent(Vec<PlaylistDescription>),
     AppendPlaylistsContent(Vec<PlaylistDescription>),
+    RemovePlaylist(String),
+    SetRootlist(Vec<RootlistItem>),
     RemoveTracksFromPlaylist(String, Vec<String>),
     SetAlbumDetails(Box<AlbumFullDescription>),
     AppendAlbumTracks(String, Box<SongBatch>),
     SetPlaylistDetails(Box<PlaylistDescription>),
+    // Sent when a playlist was modified elsewhere (i.e. its snapshot changed)
+    RefreshPlaylist(Box<PlaylistDescription>),
     UpdatePlaylistName(PlaylistSummary),
     AppendPlaylistTracks(String, Box<SongBatch>),
     Search(String),
     SetSearchResults(Box<SearchResults>),
+    // Whether to search lyrics rather than albums and artists
+    SetLyricsSearch(bool),
+    SetLyricsResults(Vec<LyricsMatch>),
     SetArtistDetails(Box<ArtistDescription>),
     AppendArtistReleases(String, Vec<AlbumDescription>),
     NavigationPush(ScreenName),
@@ -37,6 +50,11 @@ pub enum BrowserAction {
     AppendSavedTracks(Box<SongBatch>),
     SaveTracks(Vec<SongDescription>),
     RemoveSavedTracks(Vec<String>),
+    // Whether each track is in the user's Liked Songs, as checked against the API
+    SetTracksSavedState(Vec<(String, bool)>),
+    // The release feed, with the ids of the releases the user hasn't seen yet
+    SetNewReleases(Vec<AlbumDescription>, HashSet<String>),
+    MarkNewReleasesRead,
 }
 
 impl From<BrowserAction> for AppAction {
@@ -50,6 +68,7 @@ pub enum BrowserEvent {
     NavigationHidden(bool),
     HomeVisiblePageChanged(&'static str),
     LibraryUpdated,
+    LibraryOptionsChanged,
     SavedPlaylistsUpdated,
     AlbumDetailsLoaded(String),
     AlbumTracksAppended(String),
@@ -58,6 +77,8 @@ pub enum BrowserEvent {
     PlaylistTracksRemoved(String),
     SearchUpdated,
     SearchResultsUpdated,
+    LyricsResultsUpdated,
+    LibrarySearchResultsUpdated,
     ArtistDetailsUpdated(String),
     NavigationPushed(ScreenName),
     NavigationPopped,
@@ -66,6 +87,30 @@ pub enum BrowserEvent {
     AlbumUnsaved(String),
     UserDetailsUpdated(String),
     SavedTracksUpdated,
+    TrackSavedStateChanged(Vec<String>),
+    NewReleasesUpdated,
+}
+
+impl BrowserEvent {
+    // Some part of the browser was updated, to be read again from the state
+    pub fn is_refresh(&self) -> bool {
+        matches!(
+            self,
+            Self::LibraryUpdated
+                | Self::LibraryOptionsChanged
+                | Self::SavedPlaylistsUpdated
+                | Self::AlbumDetailsLoaded(_)
+                | Self::PlaylistDetailsLoaded(_)
+                | Self::SearchUpdated
+                | Self::SearchResultsUpdated
+                | Self::LyricsResultsUpdated
+                | Self::LibrarySearchResultsUpdated
+                | Self::ArtistDetailsUpdated(_)
+                | Self::UserDetailsUpdated(_)
+                | Self::SavedTracksUpdated
+                | Self::NewReleasesUpdated
+        )
+    }
 }
 
 impl From<BrowserEvent> for AppEvent {
@@ -113,6 +158,19 @@ impl BrowserScreen {
             Self::User(state) => &mut **state,
         }
     }
+
+    // What the screen is about, once it's loaded (home and search have no such thing)
+    fn title(&self) -> Option<String> {
+        match self {
+            Self::Home(_) | Self::Search(_) => None,
+            Self::AlbumDetails(state) => {
+                state.content.as_ref().map(|c| c.description.title.clone())
+            }
+            Self::Artist(state) => state.artist.clone(),
+            Self::PlaylistDetails(state) => state.playlist.as_ref().map(|p| p.title.clone()),
+            Self::User(state) => state.user.clone(),
+        }
+    }
 }
 
 impl NamedScreen for BrowserScreen {
@@ -158,6 +216,10 @@ where
         self.0.iter_mut().rev()
     }
 
+    fn iter(&self) -> impl Iterator<Item = &Screen> {
+        self.0.iter()
+    }
+
     fn iter_rev(&self) -> impl Iterator<Item = &Screen> {
         self.0.iter().rev()
     }
@@ -218,8 +280,17 @@ where
 pub struct BrowserState {
     navigation_hidden: bool,
     navigation: NavStack<BrowserScreen>,
+    // Last known snapshot of each playlist we've loaded, by playlist id
+    playlist_snapshots: HashMap<String, String>,
+    // Everything we know is in the user's library, to search it locally
+    library_index: LibraryIndex,
+    // Whether a track is in Liked Songs, for the tracks we've seen (absent means we don't know yet)
+    saved_tracks: HashMap<String, bool>,
 }
 
+// How many items from the user's library we show when searching
+const LIBRARY_SEARCH_LIMIT: usize = 10;
+
 macro_rules! extract_state {
     ($e:expr, $p:pat if $guard:expr => $i:ident) => {
         extract_state_full!($e, $p if $guard => $i)
@@ -243,6 +314,9 @@ impl BrowserState {
         Self {
             navigation_hidden: false,
             navigation: NavStack::new(BrowserScreen::Home(Default::default())),
+            playlist_snapshots: HashMap::new(),
+            library_index: Default::default(),
+            saved_tracks: HashMap::new(),
         }
     }
 
@@ -258,6 +332,14 @@ impl BrowserState {
         self.navigation.count()
     }
 
+    // Every screen in the stack, from the bottom (home) to the current one, with its title if known
+    pub fn navigation_trail(&self) -> Vec<(ScreenName, Option<String>)> {
+        self.navigation
+            .iter()
+            .map(|screen| (screen.name().clone(), screen.title()))
+            .collect()
+    }
+
     pub fn home_state(&self) -> Option<&HomeState> {
         extract_state!(self, BrowserScreen::Home(s) => s)
     }
@@ -289,6 +371,101 @@ impl BrowserState {
         extract_state!(self, BrowserScreen::User(state) if state.id == id => state)
     }
 
+    pub fn playlist_snapshot(&self, id: &str) -> Option<&str> {
+        self.playlist_snapshots.get(id).map(|s| s.as_str())
+    }
+
+    // The playlists currently opened in the navigation stack, ie the ones worth keeping up to date
+    pub fn opened_playlists(&self) -> Vec<String> {
+        self.navigation
+            .iter_rev()
+            .filter_map(|screen| match screen {
+                BrowserScreen::PlaylistDetails(state) => Some(state.id.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    pub fn is_track_saved(&self, id: &str) -> Option<bool> {
+        self.saved_tracks.get(id).copied()
+    }
+
+    fn record_playlist_snapshots<'a>(
+        &mut self,
+        playlists: impl IntoIterator<Item = &'a PlaylistDescription>,
+    ) {
+        for playlist in playlists {
+            // The snapshot might be missing if it wasn't part of the response
+            if !playlist.snapshot_id.is_empty() {
+                self.playlist_snapshots
+                    .insert(playlist.id.clone(), playlist.snapshot_id.clone());
+            }
+        }
+    }
+
+    fn update_library_index(&mut self, action: &BrowserAction) {
+        let index = &mut self.library_index;
+        match action {
+            BrowserAction::SetLibraryContent(albums) => {
+                index.replace(LibraryItemKind::Album, albums.iter().map(LibraryItem::from))
+            }
+            BrowserAction::AppendLibraryContent(albums) => {
+                index.extend(albums.iter().map(LibraryItem::from))
+            }
+            BrowserAction::SaveAlbum(album) => index.insert((&**album).into()),
+            BrowserAction::UnsaveAlbum(id) => index.remove(LibraryItemKind::Album, id),
+            BrowserAction::SetPlaylistsContent(playlists) => index.replace(
+                LibraryItemKind::Playlist,
+                playlists.iter().map(LibraryItem::from),
+            ),
+            BrowserAction::AppendPlaylistsContent(playlists)
+            | BrowserAction::PrependPlaylistsContent(playlists) => {
+                index.extend(playlists.iter().map(LibraryItem::from))
+            }
+            BrowserAction::RemovePlaylist(id) => index.remove(LibraryItemKind::Playlist, id),
+            BrowserAction::UpdatePlaylistName(PlaylistSummary { id, title }) => {
+                index.rename(LibraryItemKind::Playlist, id, title)
+            }
+            BrowserAction::RefreshPlaylist(playlist) => {
+                index.rename(LibraryItemKind::Playlist, &playlist.id, &playlist.title)
+            }
+            BrowserAction::SetSavedTracks(batch) => index.replace(
+                LibraryItemKind::Track,
+                batch.songs.iter().map(LibraryItem::from),
+            ),
+            BrowserAction::AppendSavedTracks(batch) => {
+                index.extend(batch.songs.iter().map(LibraryItem::from))
+            }
+            BrowserAction::SaveTracks(songs) => index.extend(songs.iter().map(LibraryItem::from)),
+            BrowserAction::RemoveSavedTracks(ids) => {
+                for id in ids {
+                    index.remove(LibraryItemKind::Track, id);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // Returns the ids of the tracks whose saved state actually changed
+    fn update_saved_tracks(&mut self, action: &BrowserAction) -> Vec<String> {
+        let states: Vec<(&String, bool)> = match action {
+            BrowserAction::SetSavedTracks(batch) | BrowserAction::AppendSavedTracks(batch) => {
+                batch.songs.iter().map(|s| (&s.id, true)).collect()
+            }
+            BrowserAction::SaveTracks(songs) => songs.iter().map(|s| (&s.id, true)).collect(),
+            BrowserAction::RemoveSavedTracks(ids) => ids.iter().map(|id| (id, false)).collect(),
+            BrowserAction::SetTracksSavedState(states) => {
+                states.iter().map(|(id, saved)| (id, *saved)).collect()
+            }
+            _ => return vec![],
+        };
+        states
+            .into_iter()
+            .filter(|(id, saved)| self.saved_tracks.insert(id.to_string(), *saved) != Some(*saved))
+            .map(|(id, _)| id.clone())
+            .collect()
+    }
+
     // If a screen we want to push is already in the stack
     // we just pop all the way back to it
     fn push_if_needed(&mut self, name: &ScreenName) -> Vec<BrowserEvent> {
@@ -318,17 +495,39 @@ impl UpdatableState for BrowserState {
         let action_ref = action.as_ref();
 
         match action_ref {
+            BrowserAction::SetPlaylistDetails(playlist)
+            | BrowserAction::RefreshPlaylist(playlist) => {
+                self.record_playlist_snapshots(std::iter::once(&**playlist))
+            }
+            BrowserAction::SetPlaylistsContent(playlists)
+            | BrowserAction::AppendPlaylistsContent(playlists)
+            | BrowserAction::PrependPlaylistsContent(playlists) => {
+                self.record_playlist_snapshots(playlists)
+            }
+            _ => {}
+        }
+        self.update_library_index(action_ref);
+        let changed_tracks = self.update_saved_tracks(action_ref);
+
+        let mut events = match action_ref {
             BrowserAction::SetNavigationHidden(navigation_hidden) => {
                 self.navigation_hidden = *navigation_hidden;
                 vec![BrowserEvent::NavigationHidden(*navigation_hidden)]
             }
             // The search action will be handled here first before being passed down
             // to push the search screen if it's not there already
-            BrowserAction::Search(_) => {
+            // Results from the user's library are available right away
+            BrowserAction::Search(query) => {
+                let library_results = self.library_index.search(query, LIBRARY_SEARCH_LIMIT);
                 let mut events = self.push_if_needed(&ScreenName::Search);
 
                 let mut update_events = self.navigation.current_mut().state().update_with(action);
                 events.append(&mut update_events);
+
+                if let BrowserScreen::Search(state) = self.navigation.current_mut() {
+                    state.library_results = library_results;
+                    events.push(BrowserEvent::LibrarySearchResultsUpdated);
+                }
                 events
             }
             BrowserAction::NavigationPush(name) => self.push_if_needed(name),
@@ -350,7 +549,12 @@ impl UpdatableState for BrowserState {
                 .iter_mut()
                 .flat_map(|s| s.state().update_with(Cow::Borrowed(action_ref)))
                 .collect(),
+        };
+
+        if !changed_tracks.is_empty() {
+            events.push(BrowserEvent::TrackSavedStateChanged(changed_tracks));
         }
+        events
     }
 }
 
@@ -437,4 +641,124 @@ pub mod tests {
         assert_eq!(state.count(), 2);
         assert_eq!(events, vec![BrowserEvent::NavigationPoppedTo(new_screen)]);
     }
+
+    #[test]
+    fn test_navigation_trail() {
+        let mut state = BrowserState::new();
+        let artist = ScreenName::Artist("some_id".to_string());
+        state.update_with(Cow::Owned(BrowserAction::NavigationPush(artist.clone())));
+        state.update_with(Cow::Owned(BrowserAction::SetArtistDetails(Box::new(
+            ArtistDescription {
+                id: "some_id".to_string(),
+                name: "Foo".to_string(),
+                followers: None,
+                albums: vec![],
+                top_tracks: vec![],
+            },
+        ))));
+        state.update_with(Cow::Owned(BrowserAction::NavigationPush(
+            ScreenName::Search,
+        )));
+
+        assert_eq!(
+            state.navigation_trail(),
+            vec![
+                (ScreenName::Home, None),
+                (artist, Some("Foo".to_string())),
+                (ScreenName::Search, None)
+            ]
+        );
+    }
+
+    fn make_playlist(id: &str, snapshot_id: &str) -> PlaylistDescription {
+        PlaylistDescription {
+            id: id.to_string(),
+            title: "Playlist".to_string(),
+            art: None,
+            songs: SongBatch::empty(),
+            owner: UserRef {
+                id: "owner".to_string(),
+                display_name: "Owner".to_string(),
+            },
+            snapshot_id: snapshot_id.to_string(),
+            mosaic: vec![],
+        }
+    }
+
+    #[test]
+    fn test_refresh_playlist_updates_snapshot() {
+        let mut state = BrowserState::new();
+        state.update_with(Cow::Owned(BrowserAction::NavigationPush(
+            ScreenName::PlaylistDetails("some_id".to_string()),
+        )));
+        state.update_with(Cow::Owned(BrowserAction::SetPlaylistDetails(Box::new(
+            make_playlist("some_id", "first"),
+        ))));
+
+        assert_eq!(state.playlist_snapshot("some_id"), Some("first"));
+        assert_eq!(state.opened_playlists(), vec!["some_id".to_string()]);
+
+        let events = state.update_with(Cow::Owned(BrowserAction::RefreshPlaylist(Box::new(
+            make_playlist("some_id", "second"),
+        ))));
+
+        assert_eq!(state.playlist_snapshot("some_id"), Some("second"));
+        assert_eq!(
+            events,
+            vec![BrowserEvent::PlaylistDetailsLoaded("some_id".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_search_library() {
+        let mut state = BrowserState::new();
+        state.update_with(Cow::Owned(BrowserAction::PrependPlaylistsContent(vec![
+            make_playlist("some_id", "first"),
+        ])));
+
+        let events = state.update_with(Cow::Owned(BrowserAction::Search("play".to_string())));
+        assert!(events.contains(&BrowserEvent::LibrarySearchResultsUpdated));
+
+        let results = &state.search_state().unwrap().library_results;
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].id, "some_id");
+    }
+
+    #[test]
+    fn test_search_lyrics() {
+        let mut state = BrowserState::new();
+        state.update_with(Cow::Owned(BrowserAction::Search("some line".to_string())));
+
+        let events = state.update_with(Cow::Owned(BrowserAction::SetLyricsSearch(true)));
+        assert_eq!(events, vec![BrowserEvent::SearchUpdated]);
+        assert!(state.search_state().unwrap().lyrics_mode);
+
+        // Nothing changed
+        let events = state.update_with(Cow::Owned(BrowserAction::SetLyricsSearch(true)));
+        assert!(events.is_empty());
+    }
+
+    #[test]
+    fn test_track_saved_state() {
+        let mut state = BrowserState::new();
+        assert_eq!(state.is_track_saved("a"), None);
+
+        let events = state.update_with(Cow::Owned(BrowserAction::SetTracksSavedState(vec![
+            ("a".to_string(), true),
+            ("b".to_string(), false),
+        ])));
+        assert_eq!(state.is_track_saved("a"), Some(true));
+        assert_eq!(state.is_track_saved("b"), Some(false));
+        assert!(events.contains(&BrowserEvent::TrackSavedStateChanged(vec![
+            "a".to_string(),
+            "b".to_string()
+        ])));
+
+        let events = state.update_with(Cow::Owned(BrowserAction::RemoveSavedTracks(vec![
+            "a".to_string(),
+            "b".to_string(),
+        ])));
+        assert_eq!(state.is_track_saved("a"), Some(false));
+        assert!(events.contains(&BrowserEvent::TrackSavedStateChanged(vec!["a".to_string()])));
+    }
 }
@@ -0,0 +1,239 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use crate::app::models::*;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum LibraryItemKind {
+    Album,
+    Playlist,
+    Track,
+}
+
+// A saved album, playlist or track, as found in the library index
+#[derive(Clone, Debug)]
+pub struct LibraryItem {
+    pub kind: LibraryItemKind,
+    pub id: String,
+    pub title: String,
+    // Artists (albums and tracks) or owner (playlists)
+    pub subtitle: String,
+    // Only for tracks
+    pub album: Option<AlbumRef>,
+}
+
+impl LibraryItem {
+    fn key(&self) -> (LibraryItemKind, String) {
+        (self.kind, self.id.clone())
+    }
+
+    fn terms(&self) -> HashSet<String> {
+        let album_name = self.album.as_ref().map(|a| a.name.as_str()).unwrap_or("");
+        [self.title.as_str(), self.subtitle.as_str(), album_name]
+            .iter()
+            .flat_map(|text| tokenize(text))
+            .collect()
+    }
+}
+
+impl From<&AlbumDescription> for LibraryItem {
+    fn from(album: &AlbumDescription) -> Self {
+        Self {
+            kind: LibraryItemKind::Album,
+            id: album.id.clone(),
+            title: album.title.clone(),
+            subtitle: album.artists_name(),
+            album: None,
+        }
+    }
+}
+
+impl From<&PlaylistDescription> for LibraryItem {
+    fn from(playlist: &PlaylistDescription) -> Self {
+        Self {
+            kind: LibraryItemKind::Playlist,
+            id: playlist.id.clone(),
+            title: playlist.title.clone(),
+            subtitle: playlist.owner.display_name.clone(),
+            album: None,
+        }
+    }
+}
+
+impl From<&SongDescription> for LibraryItem {
+    fn from(song: &SongDescription) -> Self {
+        Self {
+            kind: LibraryItemKind::Track,
+            id: song.id.clone(),
+            title: song.title.clone(),
+            subtitle: song.artists_name(),
+            album: Some(song.album.clone()),
+        }
+    }
+}
+
+fn tokenize(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| word.to_lowercase())
+}
+
+// A full-text index of the user's library (saved albums, playlists and tracks), so that we can search it
+// without hitting the network. It's fed with whatever we load from the API, which is itself cached on disk,
+// so it also works offline.
+#[derive(Default)]
+pub struct LibraryIndex {
+    items: HashMap<(LibraryItemKind, String), LibraryItem>,
+    // Sorted, to find all terms starting with a given prefix
+    terms: BTreeMap<String, HashSet<(LibraryItemKind, String)>>,
+}
+
+impl LibraryIndex {
+    pub fn insert(&mut self, item: LibraryItem) {
+        self.remove(item.kind, &item.id);
+        let key = item.key();
+        for term in item.terms() {
+            self.terms.entry(term).or_default().insert(key.clone());
+        }
+        self.items.insert(key, item);
+    }
+
+    pub fn extend<I>(&mut self, items: I)
+    where
+        I: IntoIterator<Item = LibraryItem>,
+    {
+        for item in items {
+            self.insert(item);
+        }
+    }
+
+    // For when a whole list was loaded again: items of that kind that aren't in it anymore are dropped
+    pub fn replace<I>(&mut self, kind: LibraryItemKind, items: I)
+    where
+        I: IntoIterator<Item = LibraryItem>,
+    {
+        let stale: Vec<String> = self
+            .items
+            .keys()
+            .filter(|(k, _)| *k == kind)
+            .map(|(_, id)| id.clone())
+            .collect();
+        for id in stale {
+            self.remove(kind, &id);
+        }
+        self.extend(items);
+    }
+
+    pub fn remove(&mut self, kind: LibraryItemKind, id: &str) {
+        let key = (kind, id.to_string());
+        if let Some(item) = self.items.remove(&key) {
+            for term in item.terms() {
+                if let Some(keys) = self.terms.get_mut(&term) {
+                    keys.remove(&key);
+                    if keys.is_empty() {
+                        self.terms.remove(&term);
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn rename(&mut self, kind: LibraryItemKind, id: &str, title: &str) {
+        if let Some(item) = self.items.get(&(kind, id.to_string())) {
+            let item = LibraryItem {
+                title: title.to_string(),
+                ..item.clone()
+            };
+            self.insert(item);
+        }
+    }
+
+    // Every word of the query must match the beginning of a word of the item (title, artists...)
+    pub fn search(&self, query: &str, limit: usize) -> Vec<LibraryItem> {
+        let mut matches: Option<HashSet<&(LibraryItemKind, String)>> = None;
+        for token in tokenize(query) {
+            let token_matches: HashSet<&(LibraryItemKind, String)> = self
+                .terms
+                .range(token.clone()..)
+                .take_while(|(term, _)| term.starts_with(&token))
+                .flat_map(|(_, keys)| keys.iter())
+                .collect();
+            matches = Some(match matches {
+                Some(matches) => matches.intersection(&token_matches).copied().collect(),
+                None => token_matches,
+            });
+        }
+
+        let mut results: Vec<&LibraryItem> = matches
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|key| self.items.get(key))
+            .collect();
+        results.sort_by(|a, b| (a.kind, &a.title).cmp(&(b.kind, &b.title)));
+        results.into_iter().take(limit).cloned().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn item(kind: LibraryItemKind, id: &str, title: &str, subtitle: &str) -> LibraryItem {
+        LibraryItem {
+            kind,
+            id: id.to_string(),
+            title: title.to_string(),
+            subtitle: subtitle.to_string(),
+            album: None,
+        }
+    }
+
+    fn ids(results: Vec<LibraryItem>) -> Vec<String> {
+        results.into_iter().map(|i| i.id).collect()
+    }
+
+    #[test]
+    fn test_search_prefixes() {
+        let mut index = LibraryIndex::default();
+        index.insert(item(LibraryItemKind::Album, "1", "Kid A", "Radiohead"));
+        index.insert(item(LibraryItemKind::Track, "2", "Idioteque", "Radiohead"));
+        index.insert(item(LibraryItemKind::Playlist, "3", "Road trip", "Someone"));
+
+        assert_eq!(ids(index.search("radio", 10)), vec!["1", "2"]);
+        assert_eq!(ids(index.search("RADIOHEAD kid", 10)), vec!["1"]);
+        assert_eq!(ids(index.search("ro", 10)), vec!["3"]);
+        assert_eq!(ids(index.search("radio", 1)), vec!["1"]);
+        assert!(index.search("jazz", 10).is_empty());
+        assert!(index.search("", 10).is_empty());
+    }
+
+    #[test]
+    fn test_remove_and_rename() {
+        let mut index = LibraryIndex::default();
+        index.insert(item(LibraryItemKind::Playlist, "1", "Chill", "Someone"));
+        index.insert(item(LibraryItemKind::Album, "1", "Chill", "Someone else"));
+        assert_eq!(index.search("chill", 10).len(), 2);
+
+        index.remove(LibraryItemKind::Album, "1");
+        assert_eq!(index.search("chill", 10).len(), 1);
+
+        index.rename(LibraryItemKind::Playlist, "1", "Focus");
+        assert!(index.search("chill", 10).is_empty());
+        assert_eq!(ids(index.search("focus", 10)), vec!["1"]);
+        assert_eq!(ids(index.search("someone", 10)), vec!["1"]);
+    }
+
+    #[test]
+    fn test_replace() {
+        let mut index = LibraryIndex::default();
+        index.insert(item(LibraryItemKind::Album, "1", "Kid A", "Radiohead"));
+        index.insert(item(LibraryItemKind::Album, "2", "Amnesiac", "Radiohead"));
+        index.insert(item(LibraryItemKind::Track, "3", "Idioteque", "Radiohead"));
+
+        index.replace(
+            LibraryItemKind::Album,
+            vec![item(LibraryItemKind::Album, "2", "Amnesiac", "Radiohead")],
+        );
+        assert_eq!(ids(index.search("radiohead", 10)), vec!["2", "3"]);
+    }
+}
@@ -0,0 +1,99 @@
+use std::cmp::Ordering;
+
+use crate::app::models::AlbumModel;
+
+// How saved albums are ordered in the library
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LibrarySort {
+    // As Spotify returns them, most recently saved first
+    RecentlyAdded,
+    Artist,
+    Title,
+    // Newest first
+    ReleaseYear,
+}
+
+impl Default for LibrarySort {
+    fn default() -> Self {
+        Self::RecentlyAdded
+    }
+}
+
+impl LibrarySort {
+    pub fn compare(&self, a: &AlbumModel, b: &AlbumModel) -> Ordering {
+        let by_title = || a.album().to_lowercase().cmp(&b.album().to_lowercase());
+        match self {
+            Self::RecentlyAdded => Ordering::Equal,
+            Self::Artist => a
+                .artist()
+                .to_lowercase()
+                .cmp(&b.artist().to_lowercase())
+                .then_with(|| a.year().cmp(&b.year())),
+            Self::Title => by_title(),
+            Self::ReleaseYear => b.year().cmp(&a.year()).then_with(by_title),
+        }
+    }
+}
+
+// What the user chose to see of their saved albums. Only what's been loaded so far is sorted and
+// filtered, the rest comes in as the user scrolls.
+#[derive(Clone, Debug, Default)]
+pub struct LibraryOptions {
+    pub sort: LibrarySort,
+    // Not saved, it's only there while the user looks for something
+    pub filter: String,
+    // A list of small rows instead of the grid of covers
+    pub compact: bool,
+}
+
+impl LibraryOptions {
+    pub fn matches(&self, album: &AlbumModel) -> bool {
+        let filter = self.filter.trim().to_lowercase();
+        filter.is_empty()
+            || album.album().to_lowercase().contains(&filter)
+            || album.artist().to_lowercase().contains(&filter)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn album(artist: &str, title: &str, year: u32) -> AlbumModel {
+        AlbumModel::new(
+            &artist.to_string(),
+            &title.to_string(),
+            Some(year),
+            None,
+            &format!("{artist}-{title}"),
+        )
+    }
+
+    #[test]
+    fn test_sort_albums() {
+        let a = album("Pink Floyd", "Animals", 1977);
+        let b = album("pink floyd", "Meddle", 1971);
+        let c = album("Air", "Moon Safari", 1998);
+
+        assert_eq!(LibrarySort::RecentlyAdded.compare(&a, &c), Ordering::Equal);
+        assert_eq!(LibrarySort::Artist.compare(&c, &a), Ordering::Less);
+        assert_eq!(LibrarySort::Artist.compare(&b, &a), Ordering::Less);
+        assert_eq!(LibrarySort::Title.compare(&a, &b), Ordering::Less);
+        assert_eq!(LibrarySort::ReleaseYear.compare(&c, &a), Ordering::Less);
+    }
+
+    #[test]
+    fn test_filter_albums() {
+        let a = album("Pink Floyd", "Animals", 1977);
+        let mut options = LibraryOptions::default();
+        assert!(options.matches(&a));
+
+        options.filter = " floyd".to_string();
+        assert!(options.matches(&a));
+        options.filter = "ANIM".to_string();
+        assert!(options.matches(&a));
+        options.filter = "meddle".to_string();
+        assert!(!options.matches(&a));
+    }
+}
@@ -10,6 +10,7 @@ use crate::app::state::{AppAction, AppEvent, UpdatableState};
 pub enum TryLoginAction {
     Password { username: String, password: String },
     Token { username: String, token: String },
+    OAuth { refresh_token: Option<String> },
 }
 
 #[derive(Clone, Debug)]
@@ -23,9 +24,12 @@ pub enum LoginAction {
     ShowLogin,
     TryLogin(TryLoginAction),
     SetLoginSuccess(SetLoginSuccessAction),
+    // The user needs to log in from their browser, at that url
+    OpenAuthorizationPage(String),
     SetUserPlaylists(Vec<PlaylistSummary>),
     UpdateUserPlaylist(PlaylistSummary),
     PrependUserPlaylist(Vec<PlaylistSummary>),
+    RemoveUserPlaylist(String),
     SetLoginFailure,
     RefreshToken,
     SetRefreshedToken {
@@ -45,6 +49,7 @@ impl From<LoginAction> for AppAction {
 pub enum LoginStartedEvent {
     Password { username: String, password: String },
     Token { username: String, token: String },
+    OAuth { refresh_token: Option<String> },
 }
 
 #[derive(Clone, Debug)]
@@ -57,6 +62,7 @@ pub enum LoginCompletedEvent {
 pub enum LoginEvent {
     LoginShown,
     LoginStarted(LoginStartedEvent),
+    AuthorizationPageOpened(String),
     LoginCompleted(LoginCompletedEvent),
     UserPlaylistsLoaded,
     LoginFailed,
@@ -99,6 +105,12 @@ impl UpdatableState for LoginState {
             LoginAction::TryLogin(TryLoginAction::Token { username, token }) => {
                 vec![LoginEvent::LoginStarted(LoginStartedEvent::Token { username, token }).into()]
             }
+            LoginAction::TryLogin(TryLoginAction::OAuth { refresh_token }) => {
+                vec![LoginEvent::LoginStarted(LoginStartedEvent::OAuth { refresh_token }).into()]
+            }
+            LoginAction::OpenAuthorizationPage(url) => {
+                vec![LoginEvent::AuthorizationPageOpened(url).into()]
+            }
             LoginAction::SetLoginSuccess(SetLoginSuccessAction::Password(creds)) => {
                 self.user = Some(creds.username.clone());
                 vec![LoginEvent::LoginCompleted(LoginCompletedEvent::Password(creds)).into()]
@@ -142,6 +154,10 @@ impl UpdatableState for LoginState {
                 self.playlists = summaries;
                 vec![LoginEvent::UserPlaylistsLoaded.into()]
             }
+            LoginAction::RemoveUserPlaylist(id) => {
+                self.playlists.retain(|p| p.id != id);
+                vec![LoginEvent::UserPlaylistsLoaded.into()]
+            }
         }
     }
 }
@@ -1,9 +1,14 @@
 mod app_model;
 mod app_state;
 mod browser_state;
+mod library_index;
+mod library_options;
 mod login_state;
 mod pagination;
+mod pending_changes;
 mod playback_state;
+mod release_alerts;
+mod saved_shows;
 mod screen_states;
 mod selection_state;
 mod settings_state;
@@ -11,9 +16,14 @@ mod settings_state;
 pub use app_model::AppModel;
 pub use app_state::*;
 pub use browser_state::*;
+pub use library_index::*;
+pub use library_options::*;
 pub use login_state::*;
 pub use pagination::*;
+pub use pending_changes::*;
 pub use playback_state::*;
+pub use release_alerts::*;
+pub use saved_shows::*;
 pub use screen_states::*;
 pub use selection_state::*;
 pub use settings_state::*;
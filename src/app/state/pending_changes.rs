@@ -0,0 +1,68 @@
+use crate::app::models::{AlbumDescription, PlaylistSummary};
+
+// A destructive change: it shows in the app right away, but it is only sent to Spotify
+// once it can no longer be undone.
+#[derive(Clone, Debug)]
+pub enum PendingChange {
+    RemoveTracksFromPlaylist {
+        playlist_id: String,
+        uris: Vec<String>,
+    },
+    UnsaveAlbum(Box<AlbumDescription>),
+    DeletePlaylist(PlaylistSummary),
+}
+
+// The changes that can still be undone, each with an id to undo (or commit) it by
+#[derive(Default)]
+pub struct PendingChanges {
+    next_id: usize,
+    changes: Vec<(usize, PendingChange)>,
+}
+
+impl PendingChanges {
+    pub fn push(&mut self, change: PendingChange) -> usize {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.changes.push((id, change));
+        id
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &PendingChange> {
+        self.changes.iter().map(|(_, change)| change)
+    }
+
+    // Once taken (to be undone or committed), a change is gone for good
+    pub fn take(&mut self, id: usize) -> Option<PendingChange> {
+        let index = self.changes.iter().position(|(i, _)| *i == id)?;
+        Some(self.changes.remove(index).1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn delete(id: &str) -> PendingChange {
+        PendingChange::DeletePlaylist(PlaylistSummary {
+            id: id.to_string(),
+            title: "Playlist".to_string(),
+        })
+    }
+
+    #[test]
+    fn test_take_once() {
+        let mut changes = PendingChanges::default();
+        let first = changes.push(delete("1"));
+        let second = changes.push(delete("2"));
+        assert_ne!(first, second);
+
+        assert!(matches!(
+            changes.take(second),
+            Some(PendingChange::DeletePlaylist(p)) if p.id == "2"
+        ));
+        assert!(changes.take(second).is_none());
+        assert!(changes.take(first).is_some());
+        assert!(changes.changes.is_empty());
+    }
+}
@@ -1,9 +1,10 @@
 use std::borrow::Cow;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::time::Instant;
 
 use crate::app::models::*;
 use crate::app::state::{AppAction, AppEvent, UpdatableState};
-use crate::app::{BatchQuery, LazyRandomIndex, SongsSource};
+use crate::app::{BatchQuery, LazyRandomIndex, ShuffleMode, SongsSource};
 
 #[derive(Debug)]
 pub struct PlaybackState {
@@ -19,8 +20,38 @@ pub struct PlaybackState {
     repeat: RepeatMode,
     is_playing: bool,
     is_shuffled: bool,
+    // Songs that were added to the queue by autoplay (and not by the user)
+    autoplay_ids: HashSet<String>,
+    // Between 0 and 1
+    volume: f64,
+    // Whether the player is still loading the current track
+    is_buffering: bool,
+    // In kbps, the quality actually streamed by Spot's own player for the current track
+    bitrate: Option<u32>,
+    shuffle_mode: ShuffleMode,
+    // Overrides shuffle_mode depending on where the tracks come from (see SongsSource::kind)
+    shuffle_mode_by_source: HashMap<String, ShuffleMode>,
+    // Ids of the last tracks played, the most recent first
+    recently_played: VecDeque<String>,
+    // In milliseconds, how far into a track Previous restarts it (0 to always go back)
+    previous_restart_threshold: u32,
+    pause_during_calls: bool,
+    // Whether we paused because a call started (rather than the user pausing), to resume once it ends
+    paused_for_call: bool,
 }
 
+// How many recently played tracks we keep track of, to shuffle them last (see ShuffleMode::LeastRecent)
+const RECENTLY_PLAYED_COUNT: usize = 100;
+
+// How many of the next tracks are picked (and shown) in advance when shuffling
+const UPCOMING_COUNT: usize = 10;
+
+// Same as official Spotify clients
+const DEFAULT_PREVIOUS_RESTART_THRESHOLD: u32 = 2000;
+
+// How much the volume changes with each step (keyboard shortcuts)
+const VOLUME_STEP: f64 = 0.05;
+
 // Most mutatings methods shouldn't be pub
 // If they are, they probably are only used by the app state
 impl PlaybackState {
@@ -87,7 +118,45 @@ impl PlaybackState {
             .and_then(|i| Some(self.songs().index(i)?.description().id.clone()))
     }
 
+    pub fn is_autoplay(&self, id: &str) -> bool {
+        self.autoplay_ids.contains(id)
+    }
+
+    pub fn has_autoplay_songs(&self) -> bool {
+        !self.autoplay_ids.is_empty()
+    }
+
+    // The ids of (at most) the last `count` songs played, the current one included
+    pub fn recent_song_ids(&self, count: usize) -> Vec<String> {
+        match self.list_position {
+            Some(position) => {
+                let first = (position + 1).saturating_sub(count);
+                (first..=position)
+                    .filter_map(|i| Some(self.index(i)?.id))
+                    .collect()
+            }
+            None => vec![],
+        }
+    }
+
+    fn queue_autoplay(&mut self, tracks: Vec<SongDescription>) {
+        // Don't suggest songs that are already queued
+        let tracks: Vec<SongDescription> = tracks
+            .into_iter()
+            .filter(|s| self.songs.get(&s.id).is_none())
+            .collect();
+        self.autoplay_ids
+            .extend(tracks.iter().map(|s| s.id.clone()));
+        self.queue(tracks);
+    }
+
+    fn clear_autoplay(&mut self) {
+        let ids: Vec<String> = self.autoplay_ids.drain().collect();
+        self.dequeue(&ids);
+    }
+
     fn clear(&mut self, source: Option<SongsSource>) -> SongListModelPending {
+        self.autoplay_ids.clear();
         self.source = source;
         self.index = Default::default();
         self.list_position = None;
@@ -104,6 +173,7 @@ impl PlaybackState {
     fn add_batch(&mut self, song_batch: SongBatch) -> bool {
         let ok = self.songs.add(song_batch).commit();
         self.index.resize(self.songs.len());
+        self.pick_upcoming();
         ok
     }
 
@@ -117,9 +187,13 @@ impl PlaybackState {
         self.source = None;
         self.songs.append(tracks).commit();
         self.index.grow(self.songs.len());
+        self.pick_upcoming();
     }
 
     pub fn dequeue(&mut self, ids: &[String]) {
+        for id in ids {
+            self.autoplay_ids.remove(id);
+        }
         let current_id = self.current_song_id();
         self.songs.remove(ids).commit();
         self.list_position = current_id.and_then(|id| self.songs.find_index(&id));
@@ -184,8 +258,82 @@ impl PlaybackState {
         self.is_playing = true;
         self.list_position.replace(index);
         self.seek_position.set(0, true);
-        self.index.next_until(index + 1);
-        self.current_song_id()
+        self.pick_upcoming();
+        let id = self.current_song_id();
+        if let Some(id) = id.as_ref() {
+            self.add_recently_played(std::iter::once(id.clone()));
+        }
+        id
+    }
+
+    // Picks the shuffled order far enough ahead that we can show what's coming up
+    fn pick_upcoming(&mut self) {
+        let last = self.songs.len().checked_sub(1);
+        let until = self
+            .list_position
+            .zip(last)
+            .map(|(position, last)| usize::min(position + UPCOMING_COUNT, last));
+        if let Some(until) = until {
+            self.pick_until(until);
+        }
+    }
+
+    fn pick_until(&mut self, i: usize) {
+        let songs = &self.songs;
+        match self.current_shuffle_mode() {
+            ShuffleMode::Random => self.index.next_until(i),
+            ShuffleMode::ArtistSpread => self.index.next_until_avoiding(i, |a, b| {
+                a.map(|a| Self::same_artist(songs, a, b)).unwrap_or(false)
+            }),
+            ShuffleMode::LeastRecent => {
+                let recently_played = &self.recently_played;
+                self.index.next_until_avoiding(i, |_, b| {
+                    songs
+                        .index(b)
+                        .map(|song| recently_played.contains(&song.get_id()))
+                        .unwrap_or(false)
+                })
+            }
+        };
+    }
+
+    // The next tracks in the order they'll actually play in when shuffling (none otherwise)
+    pub fn upcoming_shuffled_songs(&self) -> Vec<SongDescription> {
+        match self.list_position {
+            Some(position) if self.is_shuffled => (position + 1..=position + UPCOMING_COUNT)
+                .map_while(|i| self.index(i))
+                .collect(),
+            _ => vec![],
+        }
+    }
+
+    // Picks another order for the tracks that haven't played yet, the current one keeps playing
+    fn reshuffle(&mut self) -> bool {
+        match self.list_position {
+            Some(position) if self.is_shuffled => {
+                self.index.forget_after(position);
+                self.pick_upcoming();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn current_shuffle_mode(&self) -> ShuffleMode {
+        self.source
+            .as_ref()
+            .and_then(|source| self.shuffle_mode_by_source.get(source.kind()))
+            .copied()
+            .unwrap_or(self.shuffle_mode)
+    }
+
+    // Most recent first, ids that are already known move to the front
+    fn add_recently_played(&mut self, ids: impl DoubleEndedIterator<Item = String>) {
+        for id in ids.rev() {
+            self.recently_played.retain(|recent| *recent != id);
+            self.recently_played.push_front(id);
+        }
+        self.recently_played.truncate(RECENTLY_PLAYED_COUNT);
     }
 
     fn play_next(&mut self) -> Option<String> {
@@ -207,10 +355,10 @@ impl PlaybackState {
 
     fn play_prev(&mut self) -> Option<String> {
         self.prev_index().and_then(|i| {
-            // Only jump to the previous track if we aren't more than 2 seconds (2,000 ms) into the current track.
-            // Otherwise, seek to the start of the current track.
-            // (This replicates the behavior of official Spotify clients.)
-            if self.seek_position.current() <= 2000 {
+            // Only jump to the previous track if we aren't too far into the current track
+            // (see previous_restart_threshold). Otherwise, seek to the start of the current track.
+            let threshold = u64::from(self.previous_restart_threshold);
+            if threshold == 0 || self.seek_position.current() <= threshold {
                 self.seek_position.set(0, true);
                 self.play_index(i)
             } else {
@@ -233,6 +381,8 @@ impl PlaybackState {
     fn toggle_play(&mut self) -> Option<bool> {
         if self.list_position.is_some() {
             self.is_playing = !self.is_playing;
+            // Once playback is paused or resumed during a call, it's up to the user
+            self.paused_for_call = false;
 
             match self.is_playing {
                 false => self.seek_position.pause(),
@@ -249,6 +399,7 @@ impl PlaybackState {
         self.is_shuffled = shuffled;
         let old = self.list_position.replace(0).unwrap_or(0);
         self.index.reset_picking_first(old);
+        self.pick_upcoming();
     }
 
     pub fn available_devices(&self) -> &Vec<ConnectDevice> {
@@ -258,6 +409,65 @@ impl PlaybackState {
     pub fn current_device(&self) -> &Device {
         &self.current_device
     }
+
+    pub fn volume(&self) -> f64 {
+        self.volume
+    }
+
+    // In milliseconds
+    pub fn position(&self) -> u32 {
+        self.seek_position.current() as u32
+    }
+
+    pub fn is_buffering(&self) -> bool {
+        self.is_buffering
+    }
+
+    pub fn bitrate(&self) -> Option<u32> {
+        self.bitrate
+    }
+
+    pub fn with_volume(volume: f64) -> Self {
+        Self {
+            volume: volume.clamp(0.0, 1.0),
+            ..Default::default()
+        }
+    }
+
+    // Only affects tracks that haven't come up yet in the shuffled order
+    pub fn set_shuffle_mode(&mut self, shuffle_mode: ShuffleMode) {
+        self.shuffle_mode = shuffle_mode;
+    }
+
+    pub fn set_previous_restart_threshold(&mut self, threshold: u32) {
+        self.previous_restart_threshold = threshold;
+    }
+
+    pub fn set_pause_during_calls(&mut self, pause_during_calls: bool) {
+        self.pause_during_calls = pause_during_calls;
+    }
+
+    pub fn set_shuffle_mode_by_source(
+        &mut self,
+        shuffle_mode_by_source: HashMap<String, ShuffleMode>,
+    ) {
+        self.shuffle_mode_by_source = shuffle_mode_by_source;
+    }
+
+    // Tracks that aren't loaded yet are never considered to be from the same artist
+    fn same_artist(songs: &SongListModel, a: usize, b: usize) -> bool {
+        let artist = |i: usize| {
+            songs
+                .index(i)
+                .and_then(|song| song.description().artists.first().map(|a| a.id.clone()))
+        };
+        matches!((artist(a), artist(b)), (Some(a), Some(b)) if a == b)
+    }
+
+    fn set_volume(&mut self, volume: f64) -> f64 {
+        self.volume = volume.clamp(0.0, 1.0);
+        self.volume
+    }
 }
 
 impl Default for PlaybackState {
@@ -273,6 +483,16 @@ impl Default for PlaybackState {
             repeat: RepeatMode::None,
             is_playing: false,
             is_shuffled: false,
+            autoplay_ids: HashSet::new(),
+            volume: 1.0,
+            is_buffering: false,
+            bitrate: None,
+            shuffle_mode: ShuffleMode::default(),
+            shuffle_mode_by_source: HashMap::new(),
+            previous_restart_threshold: DEFAULT_PREVIOUS_RESTART_THRESHOLD,
+            pause_during_calls: false,
+            paused_for_call: false,
+            recently_played: VecDeque::new(),
         }
     }
 }
@@ -287,17 +507,32 @@ pub enum PlaybackAction {
     SetShuffled(bool),
     ToggleRepeat,
     ToggleShuffle,
+    // Another order for what's left to play, when shuffling
+    Reshuffle,
+    // Positions are in milliseconds: Seek is requested from Spot, whereas SyncSeek and SyncDuration
+    // are reported by the player
     Seek(u32),
-    // I can't remember the diff betweek Seek and SyncSeek right now. Probably the source of the action
     SyncSeek(u32),
+    SyncDuration(u32),
+    SetBuffering(bool),
+    SetBitrate(Option<u32>),
+    // Whether a call (from another app) is going on, see the pause-during-calls setting
+    SetCallActive(bool),
+    // Tracks played before this session, the most recent first
+    SetRecentlyPlayed(Vec<String>),
     Load(String),
     LoadSongs(Vec<SongDescription>),
     LoadPagedSongs(SongsSource, SongBatch),
     SetVolume(f64),
+    IncreaseVolume,
+    DecreaseVolume,
     Next,
     Previous,
     Preload,
     Queue(Vec<SongDescription>),
+    // Suggestions queued automatically when the queue runs out
+    QueueAutoplay(Vec<SongDescription>),
+    ClearAutoplay,
     Dequeue(String),
     SwitchDevice(Device),
     SetAvailableDevices(Vec<ConnectDevice>),
@@ -322,11 +557,15 @@ pub enum PlaybackEvent {
     RepeatModeChanged(RepeatMode),
     TrackSeeked(u32),
     SeekSynced(u32),
+    DurationSynced(u32),
+    BufferingChanged(bool),
+    BitrateChanged(Option<u32>),
     VolumeSet(f64),
     TrackChanged(String),
     SourceChanged,
     Preload(String),
     ShuffleChanged(bool),
+    ShuffleOrderChanged,
     PlaylistChanged,
     PlaybackStopped,
     SwitchedDevice(Device),
@@ -391,6 +630,9 @@ impl UpdatableState for PlaybackState {
                 self.set_shuffled(!self.is_shuffled);
                 vec![PlaybackEvent::ShuffleChanged(self.is_shuffled)]
             }
+            PlaybackAction::Reshuffle if self.reshuffle() => {
+                vec![PlaybackEvent::ShuffleOrderChanged]
+            }
             PlaybackAction::Next => {
                 if let Some(id) = self.play_next() {
                     vec![
@@ -457,19 +699,70 @@ impl UpdatableState for PlaybackState {
                 self.queue(tracks);
                 vec![PlaybackEvent::PlaylistChanged]
             }
+            PlaybackAction::QueueAutoplay(tracks) => {
+                self.queue_autoplay(tracks);
+                vec![PlaybackEvent::PlaylistChanged]
+            }
+            PlaybackAction::ClearAutoplay if self.has_autoplay_songs() => {
+                self.clear_autoplay();
+                vec![PlaybackEvent::PlaylistChanged]
+            }
             PlaybackAction::Dequeue(id) => {
                 self.dequeue(&[id]);
                 vec![PlaybackEvent::PlaylistChanged]
             }
+            PlaybackAction::SetCallActive(true) if self.pause_during_calls && self.is_playing() => {
+                self.toggle_play();
+                self.paused_for_call = true;
+                vec![PlaybackEvent::PlaybackPaused]
+            }
+            PlaybackAction::SetCallActive(false) if self.paused_for_call => {
+                self.paused_for_call = false;
+                if !self.is_playing() && self.toggle_play() == Some(true) {
+                    vec![PlaybackEvent::PlaybackResumed]
+                } else {
+                    vec![]
+                }
+            }
             PlaybackAction::Seek(pos) => {
-                self.seek_position.set(pos as u64 * 1000, true);
+                self.seek_position.set(pos as u64, self.is_playing);
                 vec![PlaybackEvent::TrackSeeked(pos)]
             }
             PlaybackAction::SyncSeek(pos) => {
-                self.seek_position.set(pos as u64 * 1000, true);
+                self.seek_position.set(pos as u64, self.is_playing);
                 vec![PlaybackEvent::SeekSynced(pos)]
             }
-            PlaybackAction::SetVolume(volume) => vec![PlaybackEvent::VolumeSet(volume)],
+            PlaybackAction::SyncDuration(duration) => {
+                vec![PlaybackEvent::DurationSynced(duration)]
+            }
+            PlaybackAction::SetBuffering(buffering) if buffering != self.is_buffering => {
+                self.is_buffering = buffering;
+                vec![PlaybackEvent::BufferingChanged(buffering)]
+            }
+            PlaybackAction::SetRecentlyPlayed(ids) => {
+                // Whatever was played during this session is more recent
+                let played_now: Vec<String> = self.recently_played.drain(..).collect();
+                self.add_recently_played(ids.into_iter());
+                self.add_recently_played(played_now.into_iter());
+                vec![]
+            }
+            PlaybackAction::SetBitrate(bitrate) if bitrate != self.bitrate => {
+                self.bitrate = bitrate;
+                vec![PlaybackEvent::BitrateChanged(bitrate)]
+            }
+            PlaybackAction::SetVolume(volume) => {
+                vec![PlaybackEvent::VolumeSet(self.set_volume(volume))]
+            }
+            PlaybackAction::IncreaseVolume => {
+                vec![PlaybackEvent::VolumeSet(
+                    self.set_volume(self.volume + VOLUME_STEP),
+                )]
+            }
+            PlaybackAction::DecreaseVolume => {
+                vec![PlaybackEvent::VolumeSet(
+                    self.set_volume(self.volume - VOLUME_STEP),
+                )]
+            }
             PlaybackAction::SetAvailableDevices(list) => {
                 self.available_devices = list;
                 vec![PlaybackEvent::AvailableDevicesChanged]
@@ -547,6 +840,7 @@ mod tests {
             },
             duration: 1000,
             art: None,
+            preview_url: None,
             track_number: None,
         }
     }
@@ -652,6 +946,67 @@ mod tests {
         assert_eq!(state.current_song_id(), Some("1".to_string()));
     }
 
+    #[test]
+    fn test_previous_restart_threshold() {
+        let mut state = PlaybackState::default();
+        state.queue(vec![song("1"), song("2")]);
+        state.play("2");
+        state.update_with(Cow::Owned(PlaybackAction::Pause));
+
+        // Too far into the track: it restarts
+        state.update_with(Cow::Owned(PlaybackAction::Seek(4200)));
+        let events = state.update_with(Cow::Owned(PlaybackAction::Previous));
+        assert!(matches!(events[..], [PlaybackEvent::TrackSeeked(0)]));
+        assert_eq!(state.current_song_id(), Some("2".to_string()));
+
+        state.set_previous_restart_threshold(5000);
+        state.update_with(Cow::Owned(PlaybackAction::Pause));
+        state.update_with(Cow::Owned(PlaybackAction::Seek(4200)));
+        state.update_with(Cow::Owned(PlaybackAction::Previous));
+        assert_eq!(state.current_song_id(), Some("1".to_string()));
+
+        // 0 always goes back
+        state.play("2");
+        state.set_previous_restart_threshold(0);
+        state.update_with(Cow::Owned(PlaybackAction::Pause));
+        state.update_with(Cow::Owned(PlaybackAction::Seek(60_000)));
+        state.update_with(Cow::Owned(PlaybackAction::Previous));
+        assert_eq!(state.current_song_id(), Some("1".to_string()));
+    }
+
+    #[test]
+    fn test_pause_during_calls() {
+        let mut state = PlaybackState::default();
+        state.queue(vec![song("1")]);
+        state.play("1");
+
+        // Only if asked to
+        state.update_with(Cow::Owned(PlaybackAction::SetCallActive(true)));
+        assert!(state.is_playing());
+        state.update_with(Cow::Owned(PlaybackAction::SetCallActive(false)));
+
+        state.set_pause_during_calls(true);
+        let events = state.update_with(Cow::Owned(PlaybackAction::SetCallActive(true)));
+        assert!(matches!(events[..], [PlaybackEvent::PlaybackPaused]));
+        let events = state.update_with(Cow::Owned(PlaybackAction::SetCallActive(false)));
+        assert!(matches!(events[..], [PlaybackEvent::PlaybackResumed]));
+
+        // Paused by the user, it stays paused after the call
+        state.update_with(Cow::Owned(PlaybackAction::Pause));
+        state.update_with(Cow::Owned(PlaybackAction::SetCallActive(true)));
+        let events = state.update_with(Cow::Owned(PlaybackAction::SetCallActive(false)));
+        assert!(events.is_empty());
+        assert!(!state.is_playing());
+
+        // Resumed by the user during the call, same thing
+        state.update_with(Cow::Owned(PlaybackAction::Play));
+        state.update_with(Cow::Owned(PlaybackAction::SetCallActive(true)));
+        state.update_with(Cow::Owned(PlaybackAction::Play));
+        state.update_with(Cow::Owned(PlaybackAction::Pause));
+        let events = state.update_with(Cow::Owned(PlaybackAction::SetCallActive(false)));
+        assert!(events.is_empty());
+    }
+
     #[test]
     fn test_shuffle() {
         let mut state = PlaybackState::default();
@@ -684,6 +1039,71 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_shuffle_least_recent() {
+        let mut state = PlaybackState::default();
+        state.set_shuffle_mode(ShuffleMode::LeastRecent);
+        state.queue(vec![song("1"), song("2"), song("3"), song("4"), song("5")]);
+        state.update_with(Cow::Owned(PlaybackAction::SetRecentlyPlayed(vec![
+            "3".to_string(),
+            "4".to_string(),
+            "5".to_string(),
+        ])));
+
+        state.play("1");
+        state.set_shuffled(true);
+        assert_eq!(state.current_song_id(), Some("1".to_string()));
+
+        // The only track that wasn't played recently
+        state.play_next();
+        assert_eq!(state.current_song_id(), Some("2".to_string()));
+    }
+
+    #[test]
+    fn test_upcoming_shuffled_songs() {
+        let mut state = PlaybackState::default();
+        state.queue(vec![song("1"), song("2"), song("3"), song("4")]);
+        state.play("2");
+        assert!(state.upcoming_shuffled_songs().is_empty());
+
+        state.set_shuffled(true);
+        let upcoming = state
+            .upcoming_shuffled_songs()
+            .into_iter()
+            .map(|s| s.id)
+            .collect::<Vec<_>>();
+        assert_eq!(upcoming.len(), 3);
+        assert!(!upcoming.contains(&"2".to_string()));
+
+        // What was shown is what actually plays
+        state.play_next();
+        assert_eq!(state.current_song_id().as_ref(), upcoming.first());
+    }
+
+    #[test]
+    fn test_reshuffle() {
+        let mut state = PlaybackState::default();
+        state.queue((1..=20).map(|i| song(&i.to_string())).collect());
+        state.update_with(Cow::Owned(PlaybackAction::Reshuffle));
+        assert!(!state.is_shuffled());
+
+        state.play("5");
+        state.set_shuffled(true);
+        state.play_next();
+        let current = state.current_song_id();
+
+        let events = state.update_with(Cow::Owned(PlaybackAction::Reshuffle));
+        assert!(matches!(
+            events.as_slice(),
+            [PlaybackEvent::ShuffleOrderChanged]
+        ));
+        // Nothing restarts
+        assert_eq!(state.current_position(), Some(1));
+        assert_eq!(state.current_song_id(), current);
+        assert_eq!(state.index(0).map(|s| s.id), Some("5".to_string()));
+        assert_eq!(state.upcoming_shuffled_songs().len(), UPCOMING_COUNT);
+    }
+
     #[test]
     fn test_shuffle_queue() {
         let mut state = PlaybackState::default();
@@ -782,4 +1202,71 @@ mod tests {
         state.dequeue(&["3".to_string()]);
         assert_eq!(state.current_song_id(), None);
     }
+
+    #[test]
+    fn test_autoplay() {
+        let mut state = PlaybackState::default();
+        state.queue(vec![song("1"), song("2"), song("3")]);
+        state.play("3");
+        assert_eq!(
+            state.recent_song_ids(2),
+            vec!["2".to_string(), "3".to_string()]
+        );
+
+        state.update_with(Cow::Owned(PlaybackAction::QueueAutoplay(vec![
+            song("2"),
+            song("4"),
+            song("5"),
+        ])));
+        assert_eq!(state.songs().len(), 5);
+        assert!(state.is_autoplay("4"));
+        assert!(!state.is_autoplay("3"));
+
+        state.dequeue(&["4".to_string()]);
+        assert!(!state.is_autoplay("4"));
+
+        state.update_with(Cow::Owned(PlaybackAction::ClearAutoplay));
+        assert_eq!(state.songs().len(), 3);
+        assert!(!state.has_autoplay_songs());
+        assert_eq!(state.current_song_id(), Some("3".to_string()));
+    }
+
+    #[test]
+    fn test_volume() {
+        let mut state = PlaybackState::with_volume(0.98);
+
+        state.update_with(Cow::Owned(PlaybackAction::IncreaseVolume));
+        assert_eq!(state.volume(), 1.0);
+
+        state.update_with(Cow::Owned(PlaybackAction::SetVolume(0.5)));
+        state.update_with(Cow::Owned(PlaybackAction::DecreaseVolume));
+        assert!((state.volume() - 0.45).abs() < f64::EPSILON);
+
+        state.update_with(Cow::Owned(PlaybackAction::SetVolume(-1.0)));
+        assert_eq!(state.volume(), 0.0);
+    }
+
+    #[test]
+    fn test_seek_and_buffering() {
+        let mut state = PlaybackState::default();
+        state.queue(vec![song("1"), song("2")]);
+        state.play("1");
+        state.update_with(Cow::Owned(PlaybackAction::Pause));
+
+        state.update_with(Cow::Owned(PlaybackAction::Seek(4200)));
+        assert_eq!(state.position(), 4200);
+
+        let events = state.update_with(Cow::Owned(PlaybackAction::SetBuffering(true)));
+        assert!(matches!(
+            events[..],
+            [PlaybackEvent::BufferingChanged(true)]
+        ));
+        assert!(state.is_buffering());
+
+        let events = state.update_with(Cow::Owned(PlaybackAction::SetBuffering(true)));
+        assert!(events.is_empty());
+
+        state.update_with(Cow::Owned(PlaybackAction::SetBuffering(false)));
+        assert!(!state.is_buffering());
+    }
 }
@@ -0,0 +1,157 @@
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+
+use crate::app::models::AlbumDescription;
+
+// How often we look for new releases
+pub const RELEASE_CHECK_INTERVAL: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+// The most recent release date of these albums. Dates can be just a year ("2019") or a month ("2019-04"),
+// which still compare fine as strings.
+pub fn latest_release_date(albums: &[AlbumDescription]) -> Option<String> {
+    albums.iter().filter_map(|a| a.release_date.clone()).max()
+}
+
+// The albums released after the latest release we knew of
+pub fn releases_after(albums: Vec<AlbumDescription>, known_latest: &str) -> Vec<AlbumDescription> {
+    albums
+        .into_iter()
+        .filter(|a| {
+            a.release_date
+                .as_deref()
+                .map_or(false, |date| date > known_latest)
+        })
+        .collect()
+}
+
+// Artists the user wants to hear about when they release something
+#[derive(Clone, Debug, Default)]
+pub struct ReleaseAlerts {
+    // Artist id -> date of their latest release we know of, empty until we've checked
+    pub latest: HashMap<String, String>,
+    // Artists with new releases the user hasn't looked at yet
+    pub unseen: HashSet<String>,
+    // When we last checked, in seconds since epoch
+    pub last_checked: u64,
+}
+
+impl ReleaseAlerts {
+    pub fn is_subscribed(&self, artist_id: &str) -> bool {
+        self.latest.contains_key(artist_id)
+    }
+
+    pub fn has_unseen(&self, artist_id: &str) -> bool {
+        self.unseen.contains(artist_id)
+    }
+
+    pub fn artists(&self) -> Vec<String> {
+        self.latest.keys().cloned().collect()
+    }
+
+    // What the releases found should be compared to, None if we don't know anything yet
+    pub fn known_latest(&self, artist_id: &str) -> Option<&str> {
+        self.latest
+            .get(artist_id)
+            .map(|date| date.as_str())
+            .filter(|date| !date.is_empty())
+    }
+
+    // We'll know where the artist is at after the next check
+    pub fn subscribe(&mut self, artist_id: String) {
+        self.latest.entry(artist_id).or_default();
+    }
+
+    pub fn unsubscribe(&mut self, artist_id: &str) {
+        self.latest.remove(artist_id);
+        self.unseen.remove(artist_id);
+    }
+
+    pub fn is_check_due(&self, now: u64) -> bool {
+        now.saturating_sub(self.last_checked) >= RELEASE_CHECK_INTERVAL.as_secs()
+    }
+
+    // Records what a check found; artists are only flagged if they had releases we knew of before,
+    // so that subscribing doesn't flag their whole discography
+    pub fn record_check(&mut self, artist_id: &str, latest: Option<String>) -> bool {
+        let (latest, known) = match (latest, self.latest.get_mut(artist_id)) {
+            (Some(latest), Some(known)) => (latest, known),
+            _ => return false,
+        };
+        let is_new = !known.is_empty() && latest.as_str() > known.as_str();
+        if known.is_empty() || is_new {
+            *known = latest;
+        }
+        if is_new {
+            self.unseen.insert(artist_id.to_string());
+        }
+        is_new
+    }
+
+    pub fn mark_seen(&mut self, artist_id: &str) -> bool {
+        self.unseen.remove(artist_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::app::models::SongBatch;
+
+    fn album(id: &str, release_date: &str) -> AlbumDescription {
+        AlbumDescription {
+            id: id.to_string(),
+            title: id.to_string(),
+            artists: vec![],
+            release_date: Some(release_date.to_string()),
+            art: None,
+            songs: SongBatch::empty(),
+            is_liked: false,
+        }
+    }
+
+    #[test]
+    fn test_releases_after() {
+        let albums = vec![
+            album("a", "2021-05-01"),
+            album("b", "2022"),
+            album("c", "2023-01-10"),
+        ];
+        assert_eq!(latest_release_date(&albums).as_deref(), Some("2023-01-10"));
+
+        let new: Vec<String> = releases_after(albums, "2022-06-01")
+            .into_iter()
+            .map(|a| a.id)
+            .collect();
+        assert_eq!(new, vec!["c"]);
+    }
+
+    #[test]
+    fn test_record_check() {
+        let mut alerts = ReleaseAlerts::default();
+        alerts.subscribe("artist".to_string());
+
+        // The first check only tells us where we're at
+        assert!(!alerts.record_check("artist", Some("2022-01-01".to_string())));
+        assert_eq!(alerts.known_latest("artist"), Some("2022-01-01"));
+        assert!(!alerts.has_unseen("artist"));
+
+        assert!(alerts.record_check("artist", Some("2023-01-01".to_string())));
+        assert!(alerts.has_unseen("artist"));
+        assert!(alerts.mark_seen("artist"));
+        assert!(!alerts.has_unseen("artist"));
+
+        // Not subscribed
+        assert!(!alerts.record_check("other", Some("2023-01-01".to_string())));
+    }
+
+    #[test]
+    fn test_is_check_due() {
+        let alerts = ReleaseAlerts {
+            last_checked: 1000,
+            ..Default::default()
+        };
+        assert!(!alerts.is_check_due(1000 + 60));
+        assert!(alerts.is_check_due(1000 + RELEASE_CHECK_INTERVAL.as_secs()));
+    }
+}
@@ -0,0 +1,208 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::app::models::ShowSummary;
+
+// How often we look for new episodes
+pub const EPISODE_CHECK_INTERVAL: Duration = Duration::from_secs(6 * 60 * 60);
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+struct ShowProgress {
+    // The latest episodes, as of the last check
+    known: HashSet<String>,
+    unplayed: HashSet<String>,
+}
+
+// Which episodes of the followed shows were played. Kept on disk, and never leaves the computer.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct EpisodeProgress {
+    #[serde(skip)]
+    path: Option<PathBuf>,
+    // Show id -> its episodes
+    shows: HashMap<String, ShowProgress>,
+    // When we last checked, in seconds since epoch
+    pub last_checked: u64,
+}
+
+impl EpisodeProgress {
+    fn default_path() -> PathBuf {
+        glib::user_data_dir().join("spot").join("episodes.json")
+    }
+
+    pub fn load() -> Self {
+        let path = Self::default_path();
+        let progress: Self = fs::read(&path)
+            .ok()
+            .and_then(|content| serde_json::from_slice(&content).ok())
+            .unwrap_or_default();
+        Self {
+            path: Some(path),
+            ..progress
+        }
+    }
+
+    pub fn save(&self) {
+        let path = match self.path.as_ref() {
+            Some(path) => path,
+            None => return,
+        };
+        let result = path
+            .parent()
+            .map(fs::create_dir_all)
+            .unwrap_or(Ok(()))
+            .and_then(|_| {
+                let content = serde_json::to_vec(self).unwrap_or_default();
+                fs::write(path, content)
+            });
+        if let Err(e) = result {
+            warn!("Could not save episode progress: {}", e);
+        }
+    }
+
+    pub fn unplayed_count(&self, show_id: &str) -> usize {
+        self.shows
+            .get(show_id)
+            .map(|show| show.unplayed.len())
+            .unwrap_or(0)
+    }
+
+    pub fn is_unplayed(&self, episode_id: &str) -> bool {
+        self.shows
+            .values()
+            .any(|show| show.unplayed.contains(episode_id))
+    }
+
+    pub fn is_check_due(&self, now: u64) -> bool {
+        now.saturating_sub(self.last_checked) >= EPISODE_CHECK_INTERVAL.as_secs()
+    }
+
+    // Records the latest episodes of a show, returns how many are new. The first check of a show only
+    // tells us where it's at, so that following a show doesn't flag all of its episodes.
+    pub fn record_episodes(&mut self, show_id: &str, episode_ids: Vec<String>) -> usize {
+        let latest: HashSet<String> = episode_ids.into_iter().collect();
+        let show = match self.shows.get_mut(show_id) {
+            Some(show) => show,
+            None => {
+                self.shows.insert(
+                    show_id.to_string(),
+                    ShowProgress {
+                        known: latest,
+                        unplayed: HashSet::new(),
+                    },
+                );
+                return 0;
+            }
+        };
+        let new: Vec<String> = latest.difference(&show.known).cloned().collect();
+        show.unplayed.extend(new.iter().cloned());
+        // Episodes too old to be among the latest are forgotten
+        show.unplayed.retain(|id| latest.contains(id));
+        show.known = latest;
+        new.len()
+    }
+
+    pub fn mark_played(&mut self, episode_id: &str) -> bool {
+        self.shows.values_mut().fold(false, |found, show| {
+            show.unplayed.remove(episode_id) || found
+        })
+    }
+
+    pub fn mark_show_played(&mut self, show_id: &str) -> bool {
+        match self.shows.get_mut(show_id) {
+            Some(show) if !show.unplayed.is_empty() => {
+                show.unplayed.clear();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    // Forgets about shows that were unfollowed
+    fn retain_shows(&mut self, shows: &[ShowSummary]) {
+        let ids: HashSet<&str> = shows.iter().map(|show| show.id.as_str()).collect();
+        self.shows.retain(|id, _| ids.contains(id.as_str()));
+    }
+}
+
+// The shows (podcasts) the user follows, and how far they are in them
+#[derive(Clone, Debug, Default)]
+pub struct SavedShows {
+    pub shows: Vec<ShowSummary>,
+    pub progress: EpisodeProgress,
+}
+
+impl SavedShows {
+    pub fn set_shows(&mut self, shows: Vec<ShowSummary>) {
+        self.progress.retain_shows(&shows);
+        self.shows = shows;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn ids(ids: &[&str]) -> Vec<String> {
+        ids.iter().map(|id| id.to_string()).collect()
+    }
+
+    #[test]
+    fn test_record_episodes() {
+        let mut progress = EpisodeProgress::default();
+
+        // The first check only tells us where we're at
+        assert_eq!(progress.record_episodes("show", ids(&["b", "a"])), 0);
+        assert_eq!(progress.unplayed_count("show"), 0);
+
+        assert_eq!(progress.record_episodes("show", ids(&["d", "c", "b"])), 2);
+        assert_eq!(progress.unplayed_count("show"), 2);
+        assert!(progress.is_unplayed("c"));
+
+        assert!(progress.mark_played("c"));
+        assert!(!progress.mark_played("c"));
+        assert_eq!(progress.unplayed_count("show"), 1);
+
+        // Too old to be among the latest episodes
+        assert_eq!(progress.record_episodes("show", ids(&["e", "b"])), 1);
+        assert!(!progress.is_unplayed("d"));
+
+        assert!(progress.mark_show_played("show"));
+        assert_eq!(progress.unplayed_count("show"), 0);
+    }
+
+    #[test]
+    fn test_set_shows() {
+        let show = |id: &str| ShowSummary {
+            id: id.to_string(),
+            title: id.to_string(),
+            publisher: String::new(),
+            art: None,
+        };
+        let mut saved_shows = SavedShows::default();
+        saved_shows.progress.record_episodes("kept", ids(&["a"]));
+        saved_shows
+            .progress
+            .record_episodes("unfollowed", ids(&["b"]));
+        saved_shows
+            .progress
+            .record_episodes("unfollowed", ids(&["c"]));
+
+        saved_shows.set_shows(vec![show("kept")]);
+        assert!(!saved_shows.progress.is_unplayed("c"));
+        assert_eq!(saved_shows.shows.len(), 1);
+    }
+
+    #[test]
+    fn test_is_check_due() {
+        let progress = EpisodeProgress {
+            last_checked: 1000,
+            ..Default::default()
+        };
+        assert!(!progress.is_check_due(1000 + 60));
+        assert!(progress.is_check_due(1000 + EPISODE_CHECK_INTERVAL.as_secs()));
+    }
+}
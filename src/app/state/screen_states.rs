@@ -1,11 +1,16 @@
 use std::borrow::Cow;
 use std::cmp::PartialEq;
+use std::collections::HashSet;
 
-use super::{pagination::Pagination, BrowserAction, BrowserEvent, UpdatableState};
+use super::{
+    pagination::Pagination, BrowserAction, BrowserEvent, LibraryItem, LibraryOptions,
+    UpdatableState,
+};
 use crate::app::models::*;
 use crate::app::ListStore;
+use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum ScreenName {
     Home,
     AlbumDetails(String),
@@ -125,6 +130,12 @@ impl UpdatableState for PlaylistDetailsState {
                 self.playlist = Some(*playlist.clone());
                 vec![BrowserEvent::PlaylistDetailsLoaded(id)]
             }
+            BrowserAction::RefreshPlaylist(playlist) if playlist.id == self.id => {
+                let PlaylistDescription { id, songs, .. } = *playlist.clone();
+                self.songs.replace(songs).commit();
+                self.playlist = Some(*playlist.clone());
+                vec![BrowserEvent::PlaylistDetailsLoaded(id)]
+            }
             BrowserAction::UpdatePlaylistName(PlaylistSummary { id, title }) if id == &self.id => {
                 if let Some(p) = self.playlist.as_mut() {
                     p.title = title.clone();
@@ -136,7 +147,14 @@ impl UpdatableState for PlaylistDetailsState {
                 vec![BrowserEvent::PlaylistTracksAppended(id.clone())]
             }
             BrowserAction::RemoveTracksFromPlaylist(id, uris) if id == &self.id => {
-                self.songs.remove(&uris[..]).commit();
+                let ids: Vec<String> = self
+                    .songs
+                    .collect()
+                    .into_iter()
+                    .filter(|s| uris.contains(&s.uri))
+                    .map(|s| s.id)
+                    .collect();
+                self.songs.remove(&ids[..]).commit();
                 vec![BrowserEvent::PlaylistTracksRemoved(self.id.clone())]
             }
             _ => vec![],
@@ -148,6 +166,7 @@ pub struct ArtistState {
     pub id: String,
     pub name: ScreenName,
     pub artist: Option<String>,
+    pub followers: Option<usize>,
     pub next_page: Pagination<String>,
     pub albums: ListStore<AlbumModel>,
     pub top_tracks: SongListModel,
@@ -159,6 +178,7 @@ impl ArtistState {
             id: id.clone(),
             name: ScreenName::Artist(id.clone()),
             artist: None,
+            followers: None,
             next_page: Pagination::new(id, 20),
             albums: ListStore::new(),
             top_tracks: SongListModel::new(10),
@@ -176,10 +196,12 @@ impl UpdatableState for ArtistState {
                 let ArtistDescription {
                     id,
                     name,
+                    followers,
                     albums,
                     mut top_tracks,
                 } = *details.clone();
                 self.artist = Some(name);
+                self.followers = followers;
                 self.albums
                     .replace_all(albums.into_iter().map(|a| a.into()));
                 self.next_page.reset_count(self.albums.len());
@@ -205,9 +227,16 @@ pub struct HomeState {
     pub visible_page: &'static str,
     pub next_albums_page: Pagination<()>,
     pub albums: ListStore<AlbumModel>,
+    pub library_options: LibraryOptions,
     pub next_playlists_page: Pagination<()>,
     pub playlists: ListStore<AlbumModel>,
+    // Empty until we get the folder structure of the user's playlists
+    pub rootlist: Vec<RootlistItem>,
     pub saved_tracks: SongListModel,
+    // Recent releases of the artists the user follows, newest first (see NewReleases)
+    pub new_releases: ListStore<AlbumModel>,
+    // Those released since the user last looked at them
+    pub unread_releases: HashSet<String>,
 }
 
 impl Default for HomeState {
@@ -217,9 +246,13 @@ impl Default for HomeState {
             visible_page: "library",
             next_albums_page: Pagination::new((), 30),
             albums: ListStore::new(),
+            library_options: Default::default(),
             next_playlists_page: Pagination::new((), 30),
             playlists: ListStore::new(),
+            rootlist: vec![],
             saved_tracks: SongListModel::new(50),
+            new_releases: ListStore::new(),
+            unread_releases: HashSet::new(),
         }
     }
 }
@@ -243,6 +276,20 @@ impl UpdatableState for HomeState {
                     vec![]
                 }
             }
+            BrowserAction::SetLibrarySort(sort) if self.library_options.sort != *sort => {
+                self.library_options.sort = *sort;
+                vec![BrowserEvent::LibraryOptionsChanged]
+            }
+            BrowserAction::SetLibraryFilter(filter) if self.library_options.filter != *filter => {
+                self.library_options.filter = filter.clone();
+                vec![BrowserEvent::LibraryOptionsChanged]
+            }
+            BrowserAction::SetLibraryCompact(compact)
+                if self.library_options.compact != *compact =>
+            {
+                self.library_options.compact = *compact;
+                vec![BrowserEvent::LibraryOptionsChanged]
+            }
             BrowserAction::PrependPlaylistsContent(content) => {
                 self.playlists.prepend(content.iter().map(|a| a.into()));
                 vec![BrowserEvent::SavedPlaylistsUpdated]
@@ -287,12 +334,34 @@ impl UpdatableState for HomeState {
                 self.playlists.extend(content.iter().map(|p| p.into()));
                 vec![BrowserEvent::SavedPlaylistsUpdated]
             }
+            BrowserAction::RemovePlaylist(id) => {
+                let position = self.playlists.iter().position(|p| p.uri() == *id);
+                if let Some(position) = position {
+                    self.playlists.remove(position as u32);
+                    self.next_playlists_page.decrement();
+                    vec![BrowserEvent::SavedPlaylistsUpdated]
+                } else {
+                    vec![]
+                }
+            }
+            BrowserAction::SetRootlist(rootlist) => {
+                self.rootlist = rootlist.clone();
+                vec![BrowserEvent::SavedPlaylistsUpdated]
+            }
             BrowserAction::UpdatePlaylistName(PlaylistSummary { id, title }) => {
                 if let Some(p) = self.playlists.iter().find(|p| &p.uri() == id) {
                     p.set_album(title.to_owned());
                 }
                 vec![BrowserEvent::SavedPlaylistsUpdated]
             }
+            BrowserAction::RefreshPlaylist(playlist) => {
+                if let Some(p) = self.playlists.iter().find(|p| p.uri() == playlist.id) {
+                    p.set_album(playlist.title.clone());
+                    vec![BrowserEvent::SavedPlaylistsUpdated]
+                } else {
+                    vec![]
+                }
+            }
             BrowserAction::AppendSavedTracks(song_batch) => {
                 if self.saved_tracks.add(*song_batch.clone()).commit() {
                     vec![BrowserEvent::SavedTracksUpdated]
@@ -321,6 +390,16 @@ impl UpdatableState for HomeState {
                 self.saved_tracks.remove(&tracks[..]).commit();
                 vec![BrowserEvent::SavedTracksUpdated]
             }
+            BrowserAction::SetNewReleases(releases, unread) => {
+                self.unread_releases = unread.clone();
+                self.new_releases
+                    .replace_all(releases.iter().map(|a| a.into()));
+                vec![BrowserEvent::NewReleasesUpdated]
+            }
+            BrowserAction::MarkNewReleasesRead if !self.unread_releases.is_empty() => {
+                self.unread_releases.clear();
+                vec![BrowserEvent::NewReleasesUpdated]
+            }
             _ => vec![],
         }
     }
@@ -331,6 +410,10 @@ pub struct SearchState {
     pub query: String,
     pub album_results: Vec<AlbumDescription>,
     pub artist_results: Vec<ArtistSummary>,
+    // Found locally, see BrowserState
+    pub library_results: Vec<LibraryItem>,
+    pub lyrics_mode: bool,
+    pub lyrics_results: Vec<LyricsMatch>,
 }
 
 impl Default for SearchState {
@@ -340,6 +423,9 @@ impl Default for SearchState {
             query: "".to_owned(),
             album_results: vec![],
             artist_results: vec![],
+            library_results: vec![],
+            lyrics_mode: false,
+            lyrics_results: vec![],
         }
     }
 }
@@ -359,6 +445,16 @@ impl UpdatableState for SearchState {
                 self.artist_results = results.artists.clone();
                 vec![BrowserEvent::SearchResultsUpdated]
             }
+            // Same query, different results
+            BrowserAction::SetLyricsSearch(enabled) if *enabled != self.lyrics_mode => {
+                self.lyrics_mode = *enabled;
+                self.lyrics_results.clear();
+                vec![BrowserEvent::SearchUpdated]
+            }
+            BrowserAction::SetLyricsResults(results) => {
+                self.lyrics_results = results.clone();
+                vec![BrowserEvent::LyricsResultsUpdated]
+            }
             _ => vec![],
         }
     }
@@ -418,6 +514,7 @@ impl UpdatableState for UserState {
 mod tests {
 
     use super::*;
+    use crate::app::state::LibrarySort;
 
     #[test]
     fn test_next_page_no_next() {
@@ -426,6 +523,7 @@ mod tests {
             ArtistDescription {
                 id: "id".to_owned(),
                 name: "Foo".to_owned(),
+                followers: None,
                 albums: vec![],
                 top_tracks: vec![],
             },
@@ -452,6 +550,7 @@ mod tests {
             ArtistDescription {
                 id: id.clone(),
                 name: "Foo".to_owned(),
+                followers: None,
                 albums: (0..20).map(|_| fake_album.clone()).collect(),
                 top_tracks: vec![],
             },
@@ -468,4 +567,26 @@ mod tests {
         let next = &artist_state.next_page;
         assert_eq!(None, next.next_offset);
     }
+
+    #[test]
+    fn test_library_options() {
+        let mut home_state = HomeState::default();
+        let events = home_state.update_with(Cow::Owned(BrowserAction::SetLibrarySort(
+            LibrarySort::Title,
+        )));
+        assert_eq!(events, vec![BrowserEvent::LibraryOptionsChanged]);
+
+        let events = home_state.update_with(Cow::Owned(BrowserAction::SetLibrarySort(
+            LibrarySort::Title,
+        )));
+        assert!(events.is_empty());
+
+        home_state.update_with(Cow::Owned(BrowserAction::SetLibraryFilter(
+            "floyd".to_string(),
+        )));
+        home_state.update_with(Cow::Owned(BrowserAction::SetLibraryCompact(true)));
+        assert_eq!(home_state.library_options.sort, LibrarySort::Title);
+        assert_eq!(home_state.library_options.filter, "floyd");
+        assert!(home_state.library_options.compact);
+    }
 }
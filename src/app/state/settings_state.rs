@@ -6,6 +6,10 @@ use crate::{
 #[derive(Clone, Debug)]
 pub enum SettingsAction {
     ChangeSettings,
+    // Only reloads the appearance settings, which apply as soon as they're changed
+    ChangeAppearance,
+    // Same with night mode, so that it can be heard as it's adjusted
+    ChangeNightMode,
 }
 
 impl From<SettingsAction> for AppAction {
@@ -17,6 +21,15 @@ impl From<SettingsAction> for AppAction {
 #[derive(Clone, Debug)]
 pub enum SettingsEvent {
     PlayerSettingsChanged,
+    PlaylistRefreshIntervalChanged(u32),
+    // The theme, the use of artwork colors or high contrast indicators
+    AppearanceChanged,
+    PauseDuringCallsChanged(bool),
+    PlaybackMetricsChanged(bool),
+    // The strength of night mode, in percent
+    NightModeChanged(u32),
+    // Turned on or off, or an album opted out
+    SilenceTrimmingChanged,
 }
 
 impl From<SettingsEvent> for AppEvent {
@@ -42,12 +55,67 @@ impl UpdatableState for SettingsState {
                 let new_settings = SpotSettings::new_from_gsettings().unwrap_or_default();
                 let player_settings_changed =
                     new_settings.player_settings != old_settings.player_settings;
+                let refresh_interval = new_settings.playlist_refresh_interval;
+                let refresh_interval_changed =
+                    refresh_interval != old_settings.playlist_refresh_interval;
+                let pause_during_calls = new_settings.pause_during_calls;
+                let pause_during_calls_changed =
+                    pause_during_calls != old_settings.pause_during_calls;
+                let playback_metrics = new_settings.playback_metrics;
+                let playback_metrics_changed = playback_metrics != old_settings.playback_metrics;
+                let night_mode_strength = new_settings.night_mode.strength;
+                let night_mode_changed = new_settings.night_mode != old_settings.night_mode;
+                let silence_trimming_changed =
+                    new_settings.silence_trimming != old_settings.silence_trimming;
                 self.settings = new_settings;
+
+                let mut events = vec![];
                 if player_settings_changed {
-                    vec![SettingsEvent::PlayerSettingsChanged.into()]
-                } else {
-                    vec![]
+                    events.push(SettingsEvent::PlayerSettingsChanged.into());
+                }
+                if refresh_interval_changed {
+                    events.push(
+                        SettingsEvent::PlaylistRefreshIntervalChanged(refresh_interval).into(),
+                    );
+                }
+                if pause_during_calls_changed {
+                    events.push(SettingsEvent::PauseDuringCallsChanged(pause_during_calls).into());
+                }
+                if playback_metrics_changed {
+                    events.push(SettingsEvent::PlaybackMetricsChanged(playback_metrics).into());
+                }
+                if night_mode_changed {
+                    events.push(SettingsEvent::NightModeChanged(night_mode_strength).into());
+                }
+                if silence_trimming_changed {
+                    events.push(SettingsEvent::SilenceTrimmingChanged.into());
+                }
+                events
+            }
+            SettingsAction::ChangeAppearance => {
+                let new_settings = SpotSettings::new_from_gsettings().unwrap_or_default();
+                let settings = &mut self.settings;
+                if settings.theme_preference == new_settings.theme_preference
+                    && settings.use_artwork_colors == new_settings.use_artwork_colors
+                    && settings.high_contrast_indicators == new_settings.high_contrast_indicators
+                {
+                    return vec![];
+                }
+                settings.theme_preference = new_settings.theme_preference;
+                settings.use_artwork_colors = new_settings.use_artwork_colors;
+                settings.high_contrast_indicators = new_settings.high_contrast_indicators;
+                vec![SettingsEvent::AppearanceChanged.into()]
+            }
+            SettingsAction::ChangeNightMode => {
+                let night_mode = SpotSettings::new_from_gsettings()
+                    .unwrap_or_default()
+                    .night_mode;
+                if self.settings.night_mode == night_mode {
+                    return vec![];
                 }
+                let strength = night_mode.strength;
+                self.settings.night_mode = night_mode;
+                vec![SettingsEvent::NightModeChanged(strength).into()]
             }
         }
     }
@@ -0,0 +1,138 @@
+// What a Spotify URI (spotify:album:<id>) or link (https://open.spotify.com/album/<id>) points to
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SpotifyUri {
+    Track(String),
+    Album(String),
+    Artist(String),
+    Playlist(String),
+    User(String),
+}
+
+// Ids of tracks, albums, artists and playlists are 22 characters long, in base 62
+fn valid_id(id: &str) -> Option<String> {
+    Some(id)
+        .filter(|id| id.len() == 22 && id.chars().all(|c| c.is_ascii_alphanumeric()))
+        .map(|id| id.to_string())
+}
+
+impl SpotifyUri {
+    pub fn parse(uri: &str) -> Option<Self> {
+        let uri = uri.trim();
+        let parts: Vec<&str> = if let Some(rest) = uri.strip_prefix("spotify:") {
+            // Might start with /// because of https://gitlab.gnome.org/GNOME/glib/-/issues/1886/
+            rest.trim_start_matches('/').split(':').collect()
+        } else {
+            let path = ["https://", "http://", ""]
+                .iter()
+                .find_map(|scheme| uri.strip_prefix(scheme)?.strip_prefix("open.spotify.com/"))?;
+            // Links usually come with a query (?si=...) that we don't need
+            let path = path.split(&['?', '#'][..]).next()?;
+            path.split('/')
+                .filter(|part| !part.is_empty())
+                // Localized links: https://open.spotify.com/intl-fr/album/<id>
+                .skip_while(|part| part.starts_with("intl-"))
+                .collect()
+        };
+        Self::from_parts(&parts)
+    }
+
+    fn from_parts(parts: &[&str]) -> Option<Self> {
+        match parts {
+            // Older playlist URIs are prefixed with their owner
+            ["user", _, "playlist", id, ..] => valid_id(id).map(Self::Playlist),
+            ["user", id, ..] if !id.is_empty() => Some(Self::User(id.to_string())),
+            ["track", id, ..] => valid_id(id).map(Self::Track),
+            ["album", id, ..] => valid_id(id).map(Self::Album),
+            ["artist", id, ..] => valid_id(id).map(Self::Artist),
+            ["playlist", id, ..] => valid_id(id).map(Self::Playlist),
+            _ => None,
+        }
+    }
+
+    fn kind_and_id(&self) -> (&'static str, &str) {
+        match self {
+            Self::Track(id) => ("track", id),
+            Self::Album(id) => ("album", id),
+            Self::Artist(id) => ("artist", id),
+            Self::Playlist(id) => ("playlist", id),
+            Self::User(id) => ("user", id),
+        }
+    }
+
+    pub fn to_uri(&self) -> String {
+        let (kind, id) = self.kind_and_id();
+        format!("spotify:{kind}:{id}")
+    }
+
+    pub fn web_url(&self) -> String {
+        let (kind, id) = self.kind_and_id();
+        format!("https://open.spotify.com/{kind}/{id}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    const ID: &str = "6rqhFgbbKwnb9MLmUQDhG6";
+
+    #[test]
+    fn test_parse_uri() {
+        assert_eq!(
+            SpotifyUri::parse(&format!("spotify:track:{ID}")),
+            Some(SpotifyUri::Track(ID.to_string()))
+        );
+        assert_eq!(
+            SpotifyUri::parse(&format!("spotify:///album:{ID}")),
+            Some(SpotifyUri::Album(ID.to_string()))
+        );
+        assert_eq!(
+            SpotifyUri::parse(&format!("spotify:user:someone:playlist:{ID}")),
+            Some(SpotifyUri::Playlist(ID.to_string()))
+        );
+        assert_eq!(
+            SpotifyUri::parse("spotify:user:someone"),
+            Some(SpotifyUri::User("someone".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_parse_link() {
+        assert_eq!(
+            SpotifyUri::parse(&format!("https://open.spotify.com/artist/{ID}?si=abc")),
+            Some(SpotifyUri::Artist(ID.to_string()))
+        );
+        assert_eq!(
+            SpotifyUri::parse(&format!("https://open.spotify.com/intl-fr/track/{ID}")),
+            Some(SpotifyUri::Track(ID.to_string()))
+        );
+        assert_eq!(
+            SpotifyUri::parse(&format!("open.spotify.com/playlist/{ID}")),
+            Some(SpotifyUri::Playlist(ID.to_string()))
+        );
+    }
+
+    #[test]
+    fn test_to_uri() {
+        let uri = SpotifyUri::Album(ID.to_string());
+        assert_eq!(uri.to_uri(), format!("spotify:album:{ID}"));
+        assert_eq!(
+            uri.web_url(),
+            format!("https://open.spotify.com/album/{ID}")
+        );
+        assert_eq!(SpotifyUri::parse(&uri.to_uri()), Some(uri.clone()));
+        assert_eq!(SpotifyUri::parse(&uri.web_url()), Some(uri));
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        assert_eq!(SpotifyUri::parse("spotify:track:abc"), None);
+        assert_eq!(SpotifyUri::parse(&format!("spotify:show:{ID}")), None);
+        assert_eq!(
+            SpotifyUri::parse(&format!("https://example.com/track/{ID}")),
+            None
+        );
+        assert_eq!(SpotifyUri::parse("some search"), None);
+    }
+}
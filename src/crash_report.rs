@@ -0,0 +1,271 @@
+use serde::{Deserialize, Serialize};
+use std::backtrace::Backtrace;
+use std::collections::VecDeque;
+use std::fmt::{self, Write};
+use std::fs;
+use std::panic::{self, PanicInfo};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Instant;
+
+use crate::app::models::SongDescription;
+use crate::app::state::ScreenName;
+use crate::config;
+
+const MAX_RECORDED_ACTIONS: usize = 50;
+// GitHub (and browsers) won't take much more than that in a URL
+const MAX_REPORT_LENGTH: usize = 6000;
+const NEW_ISSUE_URL: &str = "https://github.com/xou816/spot/issues/new";
+
+lazy_static! {
+    // The last few actions, oldest first, to give an idea of what led to a crash
+    static ref RECENT_ACTIONS: Mutex<VecDeque<String>> =
+        Mutex::new(VecDeque::with_capacity(MAX_RECORDED_ACTIONS));
+
+    // Kept up to date as the user goes, to be saved if Spot crashes (see SessionKeeper)
+    static ref SESSION: Mutex<Option<SessionSnapshot>> = Mutex::new(None);
+}
+
+// Where the user was when Spot crashed: what was queued and playing, and the page they were on
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct SessionSnapshot {
+    pub songs: Vec<SongDescription>,
+    pub current_song_id: Option<String>,
+    // In milliseconds, as of playing_since
+    pub position: u32,
+    #[serde(skip)]
+    pub playing_since: Option<Instant>,
+    pub screen: Option<ScreenName>,
+}
+
+impl SessionSnapshot {
+    // The position right now, if it kept playing since the snapshot was taken
+    fn settle(mut self) -> Self {
+        if let Some(since) = self.playing_since.take() {
+            self.position += since.elapsed().as_millis() as u32;
+        }
+        self
+    }
+}
+
+fn report_path() -> PathBuf {
+    glib::user_data_dir().join("spot").join("crash-report.txt")
+}
+
+fn session_path() -> PathBuf {
+    glib::user_data_dir()
+        .join("spot")
+        .join("crash-session.json")
+}
+
+// Only the variants are kept: whatever they carry (ids, names, credentials...) starts with a brace,
+// bracket or quote in their debug output, and is cut off from there (formatting stops right away)
+struct Summary(String);
+
+impl fmt::Write for Summary {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        match s.find(['{', '[', '"']) {
+            Some(index) => {
+                self.0.push_str(&s[..index]);
+                Err(fmt::Error)
+            }
+            None => {
+                self.0.push_str(s);
+                Ok(())
+            }
+        }
+    }
+}
+
+fn summarize(action: &impl fmt::Debug) -> String {
+    let mut summary = Summary(String::new());
+    match write!(summary, "{:?}", action) {
+        Ok(_) => summary.0,
+        Err(_) => format!("{}…", summary.0.trim_end()),
+    }
+}
+
+pub fn record_action(action: &impl fmt::Debug) {
+    if let Ok(mut actions) = RECENT_ACTIONS.lock() {
+        if actions.len() == MAX_RECORDED_ACTIONS {
+            actions.pop_front();
+        }
+        actions.push_back(summarize(action));
+    }
+}
+
+// Paths tend to give away the user's name
+fn redact(text: &str, home: &str, user: &str) -> String {
+    let text = if home.len() > 1 {
+        text.replace(home, "~")
+    } else {
+        text.to_string()
+    };
+    if user.len() > 2 {
+        text.replace(user, "<user>")
+    } else {
+        text
+    }
+}
+
+fn write_report(info: &PanicInfo) -> std::io::Result<()> {
+    let mut report = String::new();
+    let _ = writeln!(report, "Spot {}", config::VERSION);
+    let _ = writeln!(report, "\n{}", info);
+    let _ = writeln!(report, "\nBacktrace:\n{}", Backtrace::force_capture());
+    let _ = writeln!(report, "Recent actions:");
+    // The panic might have happened while holding the lock
+    if let Ok(actions) = RECENT_ACTIONS.try_lock() {
+        for action in actions.iter() {
+            let _ = writeln!(report, "{}", action);
+        }
+    }
+
+    let report = redact(
+        &report,
+        &glib::home_dir().to_string_lossy(),
+        &glib::user_name().to_string_lossy(),
+    );
+    let path = report_path();
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, report)
+}
+
+fn write_session() -> std::io::Result<()> {
+    let session = SESSION.try_lock().ok().and_then(|session| session.clone());
+    match session {
+        Some(session) => {
+            let content = serde_json::to_vec(&session.settle()).unwrap_or_default();
+            fs::write(session_path(), content)
+        }
+        None => Ok(()),
+    }
+}
+
+// Panics are still printed as usual, a report is saved on top of that to be submitted next time
+pub fn install() {
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        if let Err(err) = write_report(info).and_then(|_| write_session()) {
+            eprintln!("Could not save crash report: {}", err);
+        }
+        default_hook(info);
+    }));
+}
+
+// The report left by the last crash, if any: it's only offered once
+pub fn take_report() -> Option<String> {
+    let path = report_path();
+    let report = fs::read_to_string(&path).ok()?;
+    let _ = fs::remove_file(&path);
+    Some(report)
+}
+
+// Changes only part of the session, the rest of it is left as it was
+pub fn update_session(update: impl FnOnce(&mut SessionSnapshot)) {
+    if let Ok(mut current) = SESSION.lock() {
+        update(current.get_or_insert_with(Default::default));
+    }
+}
+
+// The session Spot crashed in, if any: like the report, it's only picked up once
+pub fn take_session() -> Option<SessionSnapshot> {
+    let path = session_path();
+    let content = fs::read(&path).ok()?;
+    let _ = fs::remove_file(&path);
+    serde_json::from_slice(&content).ok()
+}
+
+fn truncate(report: &str) -> &str {
+    if report.len() <= MAX_REPORT_LENGTH {
+        return report;
+    }
+    let mut end = MAX_REPORT_LENGTH;
+    while !report.is_char_boundary(end) {
+        end -= 1;
+    }
+    &report[..end]
+}
+
+// Opens a prefilled issue, that the user can still review and edit before submitting it
+pub fn issue_url(report: &str) -> String {
+    let title = report
+        .lines()
+        .find(|line| line.starts_with("panicked at"))
+        .unwrap_or("Crash report");
+    let body = format!(
+        "<!-- Please describe what you were doing when Spot crashed -->\n\n\n<details>\n<summary>Crash report</summary>\n\n```\n{}\n```\n</details>\n",
+        truncate(report)
+    );
+    let query = form_urlencoded::Serializer::new(String::new())
+        .append_pair("title", title)
+        .append_pair("body", &body)
+        .finish();
+    format!("{}?{}", NEW_ISSUE_URL, query)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    // Only ever printed, like the actions it stands for
+    #[allow(dead_code)]
+    #[derive(Debug)]
+    enum Action {
+        Next,
+        Open(String),
+        Nested(Box<Action>),
+        Login { token: String },
+    }
+
+    #[test]
+    fn test_summarize_actions() {
+        assert_eq!(summarize(&Action::Next), "Next");
+        assert_eq!(
+            summarize(&Action::Nested(Box::new(Action::Open("id".to_string())))),
+            "Nested(Open(…"
+        );
+        assert_eq!(
+            summarize(&Action::Login {
+                token: "secret".to_string()
+            }),
+            "Login…"
+        );
+    }
+
+    #[test]
+    fn test_settle_session() {
+        let paused = SessionSnapshot {
+            position: 1000,
+            ..Default::default()
+        };
+        assert_eq!(paused.settle().position, 1000);
+
+        let playing = SessionSnapshot {
+            position: 1000,
+            playing_since: Instant::now().checked_sub(std::time::Duration::from_secs(2)),
+            ..Default::default()
+        };
+        assert!(playing.settle().position >= 3000);
+    }
+
+    #[test]
+    fn test_redact_report() {
+        let report = "at /home/alice/src/spot/main.rs, alice's cache";
+        assert_eq!(
+            redact(report, "/home/alice", "alice"),
+            "at ~/src/spot/main.rs, <user>'s cache"
+        );
+    }
+
+    #[test]
+    fn test_issue_url() {
+        let url = issue_url("Spot 0.4.0\n\npanicked at 'oops', src/main.rs:1:1\n");
+        assert!(url.starts_with(NEW_ISSUE_URL));
+        assert!(url.contains("title=panicked+at+%27oops%27"));
+        assert!(issue_url(&"a".repeat(10 * MAX_REPORT_LENGTH)).len() < 3 * MAX_REPORT_LENGTH);
+    }
+}
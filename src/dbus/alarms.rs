@@ -0,0 +1,73 @@
+#![allow(non_snake_case)]
+
+use futures::channel::mpsc::UnboundedSender;
+use zbus::dbus_interface;
+use zbus::fdo::{Error, Result};
+
+use crate::app::models::Alarm;
+use crate::app::{AppAction, SpotifyUri};
+
+// Alarms can be programmed a little late, but not for a time long gone
+const PAST_TOLERANCE_SECS: i64 = 60;
+
+// ISO 8601, in local time unless an offset is given. Returns seconds since epoch.
+fn parse_alarm_time(time: &str) -> Option<i64> {
+    glib::DateTime::from_iso8601(time, Some(&glib::TimeZone::local()))
+        .ok()
+        .map(|time| time.to_unix())
+}
+
+// Lets external schedulers (systemd timers, Home Assistant...) use Spot as an alarm clock
+pub struct SpotAlarms {
+    sender: UnboundedSender<AppAction>,
+    next_id: u32,
+}
+
+impl SpotAlarms {
+    pub fn new(sender: UnboundedSender<AppAction>) -> Self {
+        Self { sender, next_id: 1 }
+    }
+}
+
+#[dbus_interface(interface = "dev.alextren.Spot.Alarms")]
+impl SpotAlarms {
+    // Plays an album, a playlist or a track (URI or link) at the given time, with the volume rising
+    // over RampSeconds. Returns the id of the alarm, to cancel it.
+    fn play_context_at(&mut self, Uri: &str, Time: &str, RampSeconds: u32) -> Result<u32> {
+        let uri = SpotifyUri::parse(Uri)
+            .filter(|uri| {
+                matches!(
+                    uri,
+                    SpotifyUri::Album(_) | SpotifyUri::Playlist(_) | SpotifyUri::Track(_)
+                )
+            })
+            .ok_or_else(|| Error::InvalidArgs("Not an album, playlist or track".to_string()))?;
+        let at = parse_alarm_time(Time)
+            .ok_or_else(|| Error::InvalidArgs("Not an ISO 8601 time".to_string()))?;
+        let now = glib::DateTime::now_utc()
+            .map(|now| now.to_unix())
+            .map_err(|_| Error::Failed("Could not tell the time".to_string()))?;
+        if at < now - PAST_TOLERANCE_SECS {
+            return Err(Error::InvalidArgs("This time is past".to_string()));
+        }
+
+        let id = self.next_id;
+        self.next_id += 1;
+        let alarm = Alarm {
+            id,
+            uri,
+            at,
+            ramp_secs: RampSeconds,
+        };
+        self.sender
+            .unbounded_send(AppAction::ScheduleAlarm(alarm))
+            .map_err(|_| Error::Failed("Could not send action".to_string()))?;
+        Ok(id)
+    }
+
+    fn cancel_alarm(&self, Id: u32) -> Result<()> {
+        self.sender
+            .unbounded_send(AppAction::CancelAlarm(Id))
+            .map_err(|_| Error::Failed("Could not send action".to_string()))
+    }
+}
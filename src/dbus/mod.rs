@@ -6,6 +6,9 @@ use zbus::Connection;
 
 use crate::app::{AppAction, AppModel};
 
+mod alarms;
+use alarms::SpotAlarms;
+
 mod mpris;
 pub use mpris::*;
 
@@ -18,6 +21,7 @@ use listener::*;
 async fn dbus_server(
     mpris: SpotMpris,
     player: SpotMprisPlayer,
+    alarms: SpotAlarms,
     receiver: UnboundedReceiver<MprisStateUpdate>,
 ) -> zbus::Result<()> {
     let connection = Connection::session().await?;
@@ -32,6 +36,11 @@ async fn dbus_server(
     connection
         .request_name("org.mpris.MediaPlayer2.Spot")
         .await?;
+    connection
+        .object_server()
+        .at("/dev/alextren/Spot/Alarms", alarms)
+        .await?;
+    connection.request_name("dev.alextren.Spot.Alarms").await?;
 
     receiver
         .for_each(|update| async {
@@ -93,11 +102,12 @@ pub fn start_dbus_server(
     sender: UnboundedSender<AppAction>,
 ) -> AppPlaybackStateListener {
     let mpris = SpotMpris::new(sender.clone());
-    let player = SpotMprisPlayer::new(sender);
+    let player = SpotMprisPlayer::new(sender.clone());
+    let alarms = SpotAlarms::new(sender);
 
     let (sender, receiver) = unbounded();
 
-    thread::spawn(move || dbus_server(mpris, player, receiver));
+    thread::spawn(move || dbus_server(mpris, player, alarms, receiver));
 
     AppPlaybackStateListener::new(app_model, sender)
 }
@@ -12,11 +12,13 @@ use gio::prelude::*;
 use gio::ApplicationFlags;
 use gio::SimpleAction;
 use gtk::prelude::*;
+use std::cell::Cell;
 
 mod api;
 mod app;
 mod config;
 mod connect;
+mod crash_report;
 mod dbus;
 mod player;
 mod settings;
@@ -26,6 +28,7 @@ use crate::app::dispatch::{spawn_task_handler, DispatchLoop};
 use crate::app::{state::PlaybackAction, App, AppAction, BrowserAction};
 
 fn main() {
+    crash_report::install();
     let settings = settings::SpotSettings::new_from_gsettings().unwrap_or_default();
     setup_gtk(&settings);
 
@@ -33,14 +36,43 @@ fn main() {
     // so here goes!
     expose_custom_widgets();
 
-    let gtk_app = gtk::Application::new(Some(config::APPID), ApplicationFlags::HANDLES_OPEN);
-    let builder = gtk::Builder::from_resource("/dev/alextren/Spot/window.ui");
-    let window: libadwaita::ApplicationWindow = builder.object("window").unwrap();
-
+    // Only one instance runs at a time: launching Spot again hands its command line over to the running
+    // one (see handle_command_line), then exits
+    let gtk_app =
+        gtk::Application::new(Some(config::APPID), ApplicationFlags::HANDLES_COMMAND_LINE);
+    // The profile is read along with the settings (see PlayerProfile), GTK just needs to accept the option.
+    // It only matters to the first instance.
+    gtk_app.add_main_option(
+        "profile",
+        glib::Char::from(b'p'),
+        glib::OptionFlags::NONE,
+        glib::OptionArg::String,
+        &gettext("Name of the player profile to use"),
+        Some("NAME"),
+    );
+    // Same for demo mode, read before GTK gets the command line
+    gtk_app.add_main_option(
+        "demo",
+        glib::Char::from(0u8),
+        glib::OptionFlags::NONE,
+        glib::OptionArg::None,
+        // translators: Description of the --demo command line option, for people to try Spot (or test a translation, a theme...) without a Spotify account
+        &gettext("Browse and play a made up library, without a Spotify account"),
+        None,
+    );
+    for (name, _, description) in remote_actions() {
+        gtk_app.add_main_option(
+            name,
+            glib::Char::from(0u8),
+            glib::OptionFlags::NONE,
+            glib::OptionArg::None,
+            &description,
+            None,
+        );
+    }
     // In debug mode, the app id is different (see meson config) so we fix the resource path (and add a distinctive style)
     // Having a different app id allows running both the stable and development version at the same time
     if cfg!(debug_assertions) {
-        window.add_css_class("devel");
         gtk_app.set_resource_base_path(Some("/dev/alextren/Spot"));
     }
 
@@ -50,39 +82,50 @@ fn main() {
 
     // Couple of actions used with shortcuts
     register_actions(&gtk_app, sender.clone());
-    setup_credits(builder.object::<libadwaita::AboutWindow>("about").unwrap());
 
-    // Main app logic is hooked up here
-    let app = App::new(
-        settings,
-        builder,
-        sender.clone(),
-        spawn_task_handler(&context),
-    );
-    context.spawn_local(app.attach(dispatch_loop));
-
-    let sender_clone = sender.clone();
-    gtk_app.connect_activate(move |gtk_app| {
-        debug!("activate");
-        if let Some(existing_window) = gtk_app.active_window() {
-            existing_window.present();
-        } else {
-            // Only send the Start action if we've just created the window
-            window.set_application(Some(gtk_app));
-            gtk_app.add_window(&window);
-            sender_clone.unbounded_send(AppAction::Start).unwrap();
+    // Startup only happens in the first instance, so that's where the window, the player and everything
+    // else is set up: other instances just pass their command line along
+    let demo = std::env::args().skip(1).any(|arg| arg == "--demo");
+    let startup = Cell::new(Some((settings, dispatch_loop)));
+    gtk_app.connect_startup(clone!(@strong sender, @strong context => move |gtk_app| {
+        let (settings, dispatch_loop) = match startup.take() {
+            Some(startup) => startup,
+            None => return,
+        };
+
+        let builder = gtk::Builder::from_resource("/dev/alextren/Spot/window.ui");
+        let window: libadwaita::ApplicationWindow = builder.object("window").unwrap();
+        if cfg!(debug_assertions) {
+            window.add_css_class("devel");
         }
-    });
+        setup_credits(builder.object::<libadwaita::AboutWindow>("about").unwrap());
 
-    gtk_app.connect_open(move |gtk_app, targets, _| {
-        gtk_app.activate();
+        // Main app logic is hooked up here
+        let app = App::new(
+            settings,
+            builder,
+            sender.clone(),
+            spawn_task_handler(&context),
+            demo,
+        );
+        context.spawn_local(app.attach(dispatch_loop));
 
-        // There should only be one target because %u is used in desktop file
-        let target = &targets[0];
-        let uri = target.uri().to_string();
-        let action = AppAction::OpenURI(uri)
-            .unwrap_or_else(|| AppAction::ShowNotification(gettext("Failed to open link!")));
-        sender.unbounded_send(action).unwrap();
+        let sender = sender.clone();
+        gtk_app.connect_activate(move |gtk_app| {
+            debug!("activate");
+            if let Some(existing_window) = gtk_app.active_window() {
+                existing_window.present();
+            } else {
+                // Only send the Start action if we've just created the window
+                window.set_application(Some(gtk_app));
+                gtk_app.add_window(&window);
+                sender.unbounded_send(AppAction::Start).unwrap();
+            }
+        });
+    }));
+
+    gtk_app.connect_command_line(move |gtk_app, command_line| {
+        handle_command_line(gtk_app, command_line, &sender)
     });
 
     context.invoke_local(move || {
@@ -140,16 +183,82 @@ fn setup_credits(about: libadwaita::AboutWindow) {
     about.set_artists(&artists);
 }
 
+// Options to control playback from the command line, usually while Spot is already running
+fn remote_actions() -> Vec<(&'static str, AppAction, String)> {
+    vec![
+        (
+            "play-pause",
+            PlaybackAction::TogglePlay.into(),
+            gettext("Toggle playback"),
+        ),
+        (
+            "next",
+            PlaybackAction::Next.into(),
+            gettext("Play the next track"),
+        ),
+        (
+            "previous",
+            PlaybackAction::Previous.into(),
+            gettext("Play the previous track"),
+        ),
+    ]
+}
+
+// Runs in the first instance, for its own command line or that of any instance started after it:
+// options are turned into actions, and other arguments are Spotify URIs or links to open
+fn handle_command_line(
+    app: &gtk::Application,
+    command_line: &gio::ApplicationCommandLine,
+    sender: &UnboundedSender<AppAction>,
+) -> i32 {
+    let options = command_line.options_dict();
+    let playback_actions: Vec<AppAction> = remote_actions()
+        .into_iter()
+        .filter(|(name, _, _)| options.contains(name))
+        .map(|(_, action, _)| action)
+        .collect();
+
+    // Options were parsed out already, the first argument is the program itself
+    let open_actions: Vec<AppAction> = command_line
+        .arguments()
+        .iter()
+        .skip(1)
+        .map(|arg| {
+            AppAction::OpenURI(arg.to_string_lossy().into_owned())
+                .unwrap_or_else(|| AppAction::ShowNotification(gettext("Failed to open link!")))
+        })
+        .collect();
+
+    // Controlling playback doesn't bring the window up, unless there's none yet
+    if playback_actions.is_empty() || !open_actions.is_empty() || app.active_window().is_none() {
+        app.activate();
+    }
+    let actions = playback_actions.into_iter().chain(open_actions);
+    for action in actions {
+        sender.unbounded_send(action).unwrap();
+    }
+    0
+}
+
 fn register_actions(app: &gtk::Application, sender: UnboundedSender<AppAction>) {
     let quit = SimpleAction::new("quit", None);
     quit.connect_activate(clone!(@weak app => move |_, _| {
+        // Even if the window would only be hidden when closed (see CloseBehavior)
         if let Some(existing_window) = app.active_window() {
-            existing_window.close();
+            existing_window.destroy();
         }
         app.quit();
     }));
     app.add_action(&quit);
 
+    let toggle_fullscreen = SimpleAction::new("toggle_fullscreen", None);
+    toggle_fullscreen.connect_activate(clone!(@weak app => move |_, _| {
+        if let Some(window) = app.active_window() {
+            window.set_fullscreened(!window.is_fullscreen());
+        }
+    }));
+    app.add_action(&toggle_fullscreen);
+
     app.add_action(&make_action(
         "toggle_playback",
         PlaybackAction::TogglePlay.into(),
@@ -168,6 +277,18 @@ fn register_actions(app: &gtk::Application, sender: UnboundedSender<AppAction>)
         sender.clone(),
     ));
 
+    app.add_action(&make_action(
+        "volume_up",
+        PlaybackAction::IncreaseVolume.into(),
+        sender.clone(),
+    ));
+
+    app.add_action(&make_action(
+        "volume_down",
+        PlaybackAction::DecreaseVolume.into(),
+        sender.clone(),
+    ));
+
     app.add_action(&make_action(
         "nav_pop",
         AppAction::BrowserAction(BrowserAction::NavigationPop),
@@ -180,18 +301,38 @@ fn register_actions(app: &gtk::Application, sender: UnboundedSender<AppAction>)
         sender.clone(),
     ));
 
-    app.add_action(&{
-        let action = SimpleAction::new("open_playlist", Some(glib::VariantTy::STRING));
-        action.set_enabled(true);
-        action.connect_activate(move |_, playlist_id| {
-            if let Some(id) = playlist_id.and_then(|s| s.str()) {
-                sender
-                    .unbounded_send(AppAction::ViewPlaylist(id.to_owned()))
-                    .unwrap();
-            }
-        });
-        action
+    app.add_action(&make_open_action(
+        "open_album",
+        AppAction::ViewAlbum,
+        sender.clone(),
+    ));
+
+    app.add_action(&make_open_action(
+        "open_artist",
+        AppAction::ViewArtist,
+        sender.clone(),
+    ));
+
+    app.add_action(&make_open_action(
+        "open_playlist",
+        AppAction::ViewPlaylist,
+        sender,
+    ));
+}
+
+// An action that takes the id of what to open, like app.open_playlist("some_id")
+fn make_open_action(
+    name: &str,
+    app_action: fn(String) -> AppAction,
+    sender: UnboundedSender<AppAction>,
+) -> SimpleAction {
+    let action = SimpleAction::new(name, Some(glib::VariantTy::STRING));
+    action.connect_activate(move |_, id| {
+        if let Some(id) = id.and_then(|s| s.str()) {
+            sender.unbounded_send(app_action(id.to_owned())).unwrap();
+        }
     });
+    action
 }
 
 fn make_action(
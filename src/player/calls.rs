@@ -0,0 +1,102 @@
+use futures::channel::mpsc::UnboundedSender;
+use libpulse_binding::callbacks::ListResult;
+use libpulse_binding::context::subscribe::InterestMaskSet;
+use libpulse_binding::context::{Context, FlagSet, State};
+use libpulse_binding::mainloop::standard::{IterateResult, Mainloop};
+use libpulse_binding::proplist::properties;
+use std::cell::Cell;
+use std::rc::Rc;
+
+use crate::app::state::PlaybackAction;
+use crate::app::AppAction;
+
+// Roles set by VoIP and video call apps on their streams (PipeWire's pulse server reports them too)
+const CALL_ROLES: [&str; 2] = ["phone", "communication"];
+
+// A stream that's part of an ongoing call, as opposed to, say, a call app idling in the background
+fn is_call_stream(role: Option<&str>, corked: bool) -> bool {
+    !corked && role.map(|r| CALL_ROLES.contains(&r)).unwrap_or(false)
+}
+
+fn wait_until_ready(mainloop: &mut Mainloop, context: &Context) -> Option<()> {
+    loop {
+        if let IterateResult::Quit(_) | IterateResult::Err(_) = mainloop.iterate(true) {
+            return None;
+        }
+        match context.get_state() {
+            State::Ready => return Some(()),
+            State::Failed | State::Terminated => return None,
+            _ => {}
+        }
+    }
+}
+
+fn watch_calls(sender: UnboundedSender<AppAction>) -> Option<()> {
+    let mut mainloop = Mainloop::new()?;
+    let mut context = Context::new(&mainloop, "Spot")?;
+    context.connect(None, FlagSet::NOFLAGS, None).ok()?;
+    wait_until_ready(&mut mainloop, &context)?;
+
+    // Streams come and go: whenever they do, we go through all of them again
+    let changed = Rc::new(Cell::new(true));
+    let on_change = Rc::clone(&changed);
+    context.set_subscribe_callback(Some(Box::new(move |_, _, _| on_change.set(true))));
+    context.subscribe(InterestMaskSet::SINK_INPUT, |_| {});
+
+    let in_call = Rc::new(Cell::new(false));
+    let found_call = Rc::new(Cell::new(false));
+    loop {
+        if changed.replace(false) {
+            let in_call = Rc::clone(&in_call);
+            let found_call = Rc::clone(&found_call);
+            let sender = sender.clone();
+            context
+                .introspect()
+                .get_sink_input_info_list(move |result| match result {
+                    ListResult::Item(info) => {
+                        let role = info.proplist.get_str(properties::MEDIA_ROLE);
+                        if is_call_stream(role.as_deref(), info.corked) {
+                            found_call.set(true);
+                        }
+                    }
+                    ListResult::End => {
+                        let found = found_call.replace(false);
+                        if in_call.replace(found) != found {
+                            debug!("Call active: {}", found);
+                            let _ =
+                                sender.unbounded_send(PlaybackAction::SetCallActive(found).into());
+                        }
+                    }
+                    ListResult::Error => found_call.set(false),
+                });
+        }
+        if let IterateResult::Quit(_) | IterateResult::Err(_) = mainloop.iterate(true) {
+            return None;
+        }
+    }
+}
+
+// Lets the app know when a call starts or ends, so that playback can pause in the meantime
+pub fn start_call_watcher(sender: UnboundedSender<AppAction>) {
+    std::thread::spawn(move || {
+        if watch_calls(sender).is_none() {
+            warn!("Couldn't watch for calls, is PulseAudio or PipeWire running?");
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_is_call_stream() {
+        assert!(is_call_stream(Some("phone"), false));
+        assert!(is_call_stream(Some("communication"), false));
+        // Muted or on hold
+        assert!(!is_call_stream(Some("phone"), true));
+        assert!(!is_call_stream(Some("music"), false));
+        assert!(!is_call_stream(None, false));
+    }
+}
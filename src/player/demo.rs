@@ -0,0 +1,189 @@
+use futures::channel::mpsc::{unbounded, UnboundedReceiver, UnboundedSender};
+use futures::StreamExt;
+use librespot::core::spotify_id::SpotifyId;
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+use tokio::task;
+
+use super::{AppPlayerDelegate, Command, SpotifyPlayerDelegate};
+use crate::api::{demo_song_durations, DEMO_USER};
+use crate::app::AppAction;
+
+// Where the song is at, since nothing is actually played
+struct Progress {
+    duration_ms: u32,
+    position_ms: u32,
+    // None when paused
+    resumed_at: Option<Instant>,
+}
+
+impl Progress {
+    fn position(&self) -> u32 {
+        let elapsed = self
+            .resumed_at
+            .map(|resumed_at| resumed_at.elapsed().as_millis() as u32)
+            .unwrap_or(0);
+        (self.position_ms + elapsed).min(self.duration_ms)
+    }
+
+    fn pause(&mut self) {
+        self.position_ms = self.position();
+        self.resumed_at = None;
+    }
+
+    fn resume(&mut self) {
+        if self.resumed_at.is_none() {
+            self.resumed_at = Some(Instant::now());
+        }
+    }
+
+    fn seek(&mut self, position_ms: u32) {
+        self.position_ms = position_ms.min(self.duration_ms);
+        if self.resumed_at.is_some() {
+            self.resumed_at = Some(Instant::now());
+        }
+    }
+}
+
+// Stands in for the librespot player in demo mode: logging in always works, and songs of the demo
+// library "play" silently for as long as they last, so that the playback UI behaves as usual.
+struct DemoPlayer {
+    durations: HashMap<String, u32>,
+    progress: Option<Progress>,
+    // Bumped whenever the song is loaded, paused or seeked, so that outdated timers are ignored
+    generation: Rc<Cell<u32>>,
+    delegate: Rc<dyn SpotifyPlayerDelegate>,
+}
+
+impl DemoPlayer {
+    fn new(delegate: Rc<dyn SpotifyPlayerDelegate>) -> Self {
+        Self {
+            durations: demo_song_durations(),
+            progress: None,
+            generation: Default::default(),
+            delegate,
+        }
+    }
+
+    fn load(&mut self, track: SpotifyId, resume: bool) {
+        let duration_ms = track
+            .to_base62()
+            .ok()
+            .and_then(|id| self.durations.get(&id).copied());
+        self.progress = duration_ms.map(|duration_ms| Progress {
+            duration_ms,
+            position_ms: 0,
+            resumed_at: if resume { Some(Instant::now()) } else { None },
+        });
+        self.delegate.notify_buffering(false);
+        self.schedule_end_of_track();
+    }
+
+    fn update(&mut self, f: impl FnOnce(&mut Progress)) {
+        if let Some(progress) = self.progress.as_mut() {
+            f(progress);
+        }
+        self.schedule_end_of_track();
+    }
+
+    fn schedule_end_of_track(&self) {
+        let generation = self.generation.get().wrapping_add(1);
+        self.generation.set(generation);
+        let remaining = match self.progress.as_ref() {
+            Some(progress) if progress.resumed_at.is_some() => {
+                progress.duration_ms - progress.position()
+            }
+            _ => return,
+        };
+        let current_generation = Rc::clone(&self.generation);
+        let delegate = Rc::clone(&self.delegate);
+        task::spawn_local(async move {
+            tokio::time::sleep(Duration::from_millis(remaining as u64)).await;
+            if current_generation.get() == generation {
+                delegate.end_of_track_reached();
+            }
+        });
+    }
+
+    fn handle(&mut self, action: Command) {
+        match action {
+            Command::PasswordLogin { .. }
+            | Command::TokenLogin { .. }
+            | Command::OAuthLogin { .. } => {
+                self.delegate
+                    .token_login_successful(DEMO_USER.to_string(), String::new());
+            }
+            Command::Logout | Command::PlayerStop => {
+                self.progress = None;
+                self.schedule_end_of_track();
+            }
+            Command::PlayerLoad { track, resume } => self.load(track, resume),
+            Command::PlayerResume => self.update(Progress::resume),
+            Command::PlayerPause => self.update(Progress::pause),
+            Command::PlayerSeek(position) => self.update(|progress| progress.seek(position)),
+            // The demo client doesn't need a token, and the real one mustn't be touched
+            Command::RefreshToken => {}
+            Command::PlayerSetVolume(_)
+            | Command::PlayerSetNightMode(_)
+            | Command::PlayerSetSilenceTrimming(_)
+            | Command::PlayerSetMetricsEnabled(_)
+            | Command::PlayerPreload(_)
+            | Command::OAuthLoginCompleted(_)
+            | Command::ReloadSettings
+            | Command::FetchRootlist => {}
+        }
+    }
+
+    async fn start(self, receiver: UnboundedReceiver<Command>) {
+        let _self = RefCell::new(self);
+        receiver
+            .for_each(|action| async {
+                _self.borrow_mut().handle(action);
+            })
+            .await;
+    }
+}
+
+#[tokio::main]
+async fn demo_player_main(
+    appaction_sender: UnboundedSender<AppAction>,
+    receiver: UnboundedReceiver<Command>,
+) {
+    task::LocalSet::new()
+        .run_until(async move {
+            let delegate = Rc::new(AppPlayerDelegate::new(appaction_sender));
+            DemoPlayer::new(delegate).start(receiver).await;
+        })
+        .await;
+}
+
+pub fn start_demo_player(appaction_sender: UnboundedSender<AppAction>) -> UnboundedSender<Command> {
+    let (sender, receiver) = unbounded::<Command>();
+    std::thread::spawn(move || demo_player_main(appaction_sender, receiver));
+    sender
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_progress() {
+        let mut progress = Progress {
+            duration_ms: 1000,
+            position_ms: 0,
+            resumed_at: None,
+        };
+        progress.seek(400);
+        assert_eq!(progress.position(), 400);
+        progress.seek(2000);
+        assert_eq!(progress.position(), 1000);
+        progress.resume();
+        progress.pause();
+        assert!(progress.resumed_at.is_none());
+        assert_eq!(progress.position(), 1000);
+    }
+}
@@ -0,0 +1,270 @@
+use librespot::core::session::Session;
+use librespot::core::spotify_id::FileId;
+use librespot::playback::audio_backend::{Sink, SinkResult};
+use librespot::playback::convert::Converter;
+use librespot::playback::decoder::AudioPacket;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+// Packets are written every few tens of milliseconds while playing: waiting longer than that for the
+// next one means the audio wasn't there yet
+const STALL_THRESHOLD: Duration = Duration::from_secs(1);
+
+// What the cache and network are doing for playback, to tune the cache and audio quality settings.
+// Only collected if the user opted in, and never leaves the computer.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlaybackMetrics {
+    // Tracks loaded, and how many of them were already in the cache
+    pub loads: u32,
+    pub cache_hits: u32,
+    // Tracks started, and the total time it took (from the load to the first sound)
+    pub starts: u32,
+    pub start_latency_ms: u64,
+    // Times playback stopped to wait for audio
+    pub stalls: u32,
+    pub played_ms: u64,
+}
+
+impl PlaybackMetrics {
+    fn default_path() -> PathBuf {
+        glib::user_data_dir()
+            .join("spot")
+            .join("playback-metrics.json")
+    }
+
+    pub fn load() -> Self {
+        fs::read(Self::default_path())
+            .ok()
+            .and_then(|content| serde_json::from_slice(&content).ok())
+            .unwrap_or_default()
+    }
+
+    fn save(&self) {
+        let path = Self::default_path();
+        let result = path
+            .parent()
+            .map(fs::create_dir_all)
+            .unwrap_or(Ok(()))
+            .and_then(|_| {
+                let content = serde_json::to_vec(self).unwrap_or_default();
+                fs::write(&path, content)
+            });
+        if let Err(e) = result {
+            warn!("Could not save playback metrics: {}", e);
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.loads == 0 && self.starts == 0 && self.played_ms == 0
+    }
+
+    // Between 0 and 1
+    pub fn cache_hit_rate(&self) -> Option<f64> {
+        if self.loads == 0 {
+            return None;
+        }
+        Some(self.cache_hits as f64 / self.loads as f64)
+    }
+
+    pub fn stalls_per_hour(&self) -> Option<f64> {
+        if self.played_ms == 0 {
+            return None;
+        }
+        let hours = self.played_ms as f64 / Duration::from_secs(3600).as_millis() as f64;
+        Some(self.stalls as f64 / hours)
+    }
+
+    pub fn average_start_latency(&self) -> Option<Duration> {
+        if self.starts == 0 {
+            return None;
+        }
+        Some(Duration::from_millis(
+            self.start_latency_ms / self.starts as u64,
+        ))
+    }
+}
+
+#[derive(Default)]
+struct Collector {
+    // None unless enabled
+    metrics: Option<PlaybackMetrics>,
+    loading_since: Option<Instant>,
+}
+
+// Shared by the player and its sink, which runs on a thread of its own
+#[derive(Clone, Default)]
+pub struct MetricsCollector(Arc<Mutex<Collector>>);
+
+impl MetricsCollector {
+    // Metrics collected so far are kept when disabled, until cleared from the diagnostics
+    pub fn set_enabled(&self, enabled: bool) {
+        if let Ok(mut collector) = self.0.lock() {
+            match (enabled, collector.metrics.is_some()) {
+                (true, false) => collector.metrics = Some(PlaybackMetrics::load()),
+                (false, true) => collector.metrics = None,
+                _ => {}
+            }
+        }
+    }
+
+    fn update(&self, f: impl FnOnce(&mut Collector)) {
+        if let Ok(mut collector) = self.0.lock() {
+            if collector.metrics.is_some() {
+                f(&mut collector);
+            }
+        }
+    }
+
+    fn update_metrics(&self, f: impl FnOnce(&mut PlaybackMetrics)) {
+        self.update(|collector| {
+            if let Some(metrics) = collector.metrics.as_mut() {
+                f(metrics);
+            }
+        });
+    }
+
+    // Only loads meant to play right away are timed
+    pub fn track_loading(&self, resume: bool) {
+        self.update(|collector| {
+            collector.loading_since = if resume { Some(Instant::now()) } else { None };
+        });
+    }
+
+    pub fn track_started(&self) {
+        self.update(|collector| {
+            if let (Some(since), Some(metrics)) =
+                (collector.loading_since.take(), collector.metrics.as_mut())
+            {
+                metrics.starts += 1;
+                metrics.start_latency_ms += since.elapsed().as_millis() as u64;
+            }
+        });
+    }
+
+    // librespot only saves a file to the cache once it's fully downloaded, so a track that's still
+    // being fetched doesn't count as a hit
+    pub fn check_cache(&self, session: &Session, file: FileId) {
+        let cached = session
+            .cache()
+            .map(|cache| cache.file(file).is_some())
+            .unwrap_or(false);
+        self.update_metrics(|metrics| {
+            metrics.loads += 1;
+            if cached {
+                metrics.cache_hits += 1;
+            }
+        });
+    }
+
+    fn stalled(&self) {
+        self.update_metrics(|metrics| metrics.stalls += 1);
+    }
+
+    fn played(&self, duration: Duration) {
+        self.update_metrics(|metrics| metrics.played_ms += duration.as_millis() as u64);
+    }
+
+    // Not done by the sink, so that writing to disk never holds up the audio
+    pub fn save(&self) {
+        let metrics = self.0.lock().ok().and_then(|c| c.metrics.clone());
+        if let Some(metrics) = metrics {
+            metrics.save();
+        }
+    }
+}
+
+// Times how long audio is played for, and notices when it has to wait for more
+pub struct MetricsSink {
+    sink: Box<dyn Sink>,
+    collector: MetricsCollector,
+    started_at: Option<Instant>,
+    last_write: Option<Instant>,
+}
+
+impl MetricsSink {
+    pub fn new(sink: Box<dyn Sink>, collector: MetricsCollector) -> Self {
+        Self {
+            sink,
+            collector,
+            started_at: None,
+            last_write: None,
+        }
+    }
+}
+
+impl Sink for MetricsSink {
+    fn start(&mut self) -> SinkResult<()> {
+        self.started_at = Some(Instant::now());
+        self.last_write = None;
+        self.sink.start()
+    }
+
+    fn stop(&mut self) -> SinkResult<()> {
+        if let Some(started_at) = self.started_at.take() {
+            self.collector.played(started_at.elapsed());
+        }
+        self.sink.stop()
+    }
+
+    fn write(&mut self, packet: AudioPacket, converter: &mut Converter) -> SinkResult<()> {
+        let now = Instant::now();
+        if let Some(last_write) = self.last_write.replace(now) {
+            if now.duration_since(last_write) > STALL_THRESHOLD {
+                self.collector.stalled();
+            }
+        }
+        self.sink.write(packet, converter)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_metrics_rates() {
+        let metrics = PlaybackMetrics::default();
+        assert!(metrics.is_empty());
+        assert_eq!(metrics.cache_hit_rate(), None);
+        assert_eq!(metrics.stalls_per_hour(), None);
+        assert_eq!(metrics.average_start_latency(), None);
+
+        let metrics = PlaybackMetrics {
+            loads: 4,
+            cache_hits: 1,
+            starts: 2,
+            start_latency_ms: 900,
+            stalls: 3,
+            played_ms: 30 * 60 * 1000,
+        };
+        assert_eq!(metrics.cache_hit_rate(), Some(0.25));
+        assert_eq!(metrics.stalls_per_hour(), Some(6.0));
+        assert_eq!(
+            metrics.average_start_latency(),
+            Some(Duration::from_millis(450))
+        );
+    }
+
+    #[test]
+    fn test_collect_only_when_enabled() {
+        let collector = MetricsCollector::default();
+        collector.track_loading(true);
+        collector.track_started();
+        collector.played(Duration::from_secs(1));
+        assert!(collector.0.lock().unwrap().metrics.is_none());
+
+        collector.0.lock().unwrap().metrics = Some(Default::default());
+        collector.track_loading(false);
+        collector.track_started();
+        collector.track_loading(true);
+        collector.track_started();
+        collector.stalled();
+        let metrics = collector.0.lock().unwrap().metrics.clone().unwrap();
+        assert_eq!(metrics.starts, 1);
+        assert_eq!(metrics.stalls, 1);
+    }
+}
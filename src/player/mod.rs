@@ -6,16 +6,31 @@ use std::time::SystemTime;
 use tokio::task;
 
 use crate::app::credentials::Credentials;
+use crate::app::models::RootlistItem;
 use crate::app::state::{LoginAction, PlaybackAction, SetLoginSuccessAction};
-use crate::app::AppAction;
+use crate::app::{AppAction, BrowserAction};
 
+mod calls;
+mod demo;
+mod metrics;
+mod night_mode;
+mod oauth;
+mod pipewire;
 mod player;
+mod silence;
+pub use calls::start_call_watcher;
+pub use demo::start_demo_player;
+pub use metrics::PlaybackMetrics;
 pub use player::*;
 
 #[derive(Debug, Clone)]
 pub enum Command {
     PasswordLogin { username: String, password: String },
     TokenLogin { username: String, token: String },
+    // Without a refresh token, the user logs in from their browser
+    OAuthLogin { refresh_token: Option<String> },
+    // Sent by the player itself once the user logged in from their browser
+    OAuthLoginCompleted(oauth::OAuthToken),
     Logout,
     PlayerLoad { track: SpotifyId, resume: bool },
     PlayerResume,
@@ -23,9 +38,16 @@ pub enum Command {
     PlayerStop,
     PlayerSeek(u32),
     PlayerSetVolume(f64),
+    // In percent, 0 to disable it
+    PlayerSetNightMode(u32),
+    // Whether long silences are skipped, for the track about to be loaded
+    PlayerSetSilenceTrimming(bool),
+    // Whether playback metrics are collected (see PlaybackMetrics)
+    PlayerSetMetricsEnabled(bool),
     PlayerPreload(SpotifyId),
     RefreshToken,
     ReloadSettings,
+    FetchRootlist,
 }
 
 struct AppPlayerDelegate {
@@ -66,6 +88,13 @@ impl SpotifyPlayerDelegate for AppPlayerDelegate {
             .unwrap();
     }
 
+    fn authorization_page_ready(&self, url: String) {
+        self.sender
+            .borrow_mut()
+            .unbounded_send(LoginAction::OpenAuthorizationPage(url).into())
+            .unwrap();
+    }
+
     fn refresh_successful(&self, token: String, token_expiry_time: SystemTime) {
         self.sender
             .borrow_mut()
@@ -89,10 +118,27 @@ impl SpotifyPlayerDelegate for AppPlayerDelegate {
             .unwrap();
     }
 
-    fn notify_playback_state(&self, position: u32) {
+    fn notify_playback_state(&self, position: u32, duration: u32) {
+        let sender = self.sender.borrow_mut();
+        sender
+            .unbounded_send(PlaybackAction::SyncSeek(position).into())
+            .unwrap();
+        sender
+            .unbounded_send(PlaybackAction::SyncDuration(duration).into())
+            .unwrap();
+    }
+
+    fn notify_buffering(&self, buffering: bool) {
         self.sender
             .borrow_mut()
-            .unbounded_send(PlaybackAction::SyncSeek(position).into())
+            .unbounded_send(PlaybackAction::SetBuffering(buffering).into())
+            .unwrap();
+    }
+
+    fn notify_bitrate(&self, bitrate: Option<u32>) {
+        self.sender
+            .borrow_mut()
+            .unbounded_send(PlaybackAction::SetBitrate(bitrate).into())
             .unwrap();
     }
 
@@ -102,19 +148,27 @@ impl SpotifyPlayerDelegate for AppPlayerDelegate {
             .unbounded_send(PlaybackAction::Preload.into())
             .unwrap();
     }
+
+    fn rootlist_fetched(&self, rootlist: Vec<RootlistItem>) {
+        self.sender
+            .borrow_mut()
+            .unbounded_send(BrowserAction::SetRootlist(rootlist).into())
+            .unwrap();
+    }
 }
 
 #[tokio::main]
 async fn player_main(
     player_settings: SpotifyPlayerSettings,
     appaction_sender: UnboundedSender<AppAction>,
+    sender: UnboundedSender<Command>,
     receiver: UnboundedReceiver<Command>,
 ) {
     task::LocalSet::new()
         .run_until(async move {
             task::spawn_local(async move {
                 let delegate = Rc::new(AppPlayerDelegate::new(appaction_sender.clone()));
-                let player = SpotifyPlayer::new(player_settings, delegate);
+                let player = SpotifyPlayer::new(player_settings, delegate, sender);
                 player.start(receiver).await.unwrap();
             })
             .await
@@ -128,6 +182,9 @@ pub fn start_player_service(
     appaction_sender: UnboundedSender<AppAction>,
 ) -> UnboundedSender<Command> {
     let (sender, receiver) = unbounded::<Command>();
-    std::thread::spawn(move || player_main(player_settings, appaction_sender, receiver));
+    let player_sender = sender.clone();
+    std::thread::spawn(move || {
+        player_main(player_settings, appaction_sender, player_sender, receiver)
+    });
     sender
 }
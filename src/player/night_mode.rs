@@ -0,0 +1,180 @@
+use librespot::playback::audio_backend::{Sink, SinkResult};
+use librespot::playback::convert::Converter;
+use librespot::playback::decoder::AudioPacket;
+use librespot::playback::mixer::VolumeGetter;
+use librespot::playback::{NUM_CHANNELS, SAMPLE_RATE};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+
+// Quick enough to catch drum hits, slow enough not to distort them
+const ATTACK_SECS: f64 = 0.005;
+// Lets the level come back up smoothly after a loud passage
+const RELEASE_SECS: f64 = 0.2;
+// Keeps the log of silence finite
+const MIN_LEVEL: f64 = 1e-9;
+
+// How much night mode compresses (in percent, 0 if disabled), shared with the sink so that
+// changes apply right away
+#[derive(Clone, Default)]
+pub struct NightMode(Arc<AtomicU32>);
+
+impl NightMode {
+    pub fn set_strength(&self, strength: u32) {
+        self.0.store(strength.min(100), Ordering::Relaxed);
+    }
+
+    fn strength(&self) -> u32 {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+fn time_coefficient(secs: f64) -> f64 {
+    (-1.0 / (secs * SAMPLE_RATE as f64)).exp()
+}
+
+// A feed-forward compressor, with both channels linked so that the stereo image doesn't wander.
+// Levels are looked at before the volume is applied, so that it behaves the same at any volume.
+struct Compressor {
+    strength: u32,
+    threshold_db: f64,
+    slope: f64,
+    makeup_db: f64,
+    attack: f64,
+    release: f64,
+    // Current gain reduction, in dB
+    reduction_db: f64,
+}
+
+impl Compressor {
+    fn new() -> Self {
+        Self {
+            strength: 0,
+            threshold_db: 0.0,
+            slope: 0.0,
+            makeup_db: 0.0,
+            attack: time_coefficient(ATTACK_SECS),
+            release: time_coefficient(RELEASE_SECS),
+            reduction_db: 0.0,
+        }
+    }
+
+    // At full strength, anything above -30 dBFS is compressed 6:1
+    fn set_strength(&mut self, strength: u32) {
+        if strength == self.strength {
+            return;
+        }
+        let s = strength as f64 / 100.0;
+        let ratio = 1.0 + 5.0 * s;
+        self.strength = strength;
+        self.threshold_db = -10.0 - 20.0 * s;
+        self.slope = 1.0 - 1.0 / ratio;
+        // Quiet passages are brought up by half of what's taken off the loudest peaks
+        self.makeup_db = -self.threshold_db * self.slope / 2.0;
+        self.reduction_db = 0.0;
+    }
+
+    fn target_reduction_db(&self, level: f64) -> f64 {
+        let level_db = 20.0 * level.max(MIN_LEVEL).log10();
+        (level_db - self.threshold_db).max(0.0) * self.slope
+    }
+
+    fn process(&mut self, samples: &mut [f64], attenuation: f64) {
+        if self.strength == 0 {
+            return;
+        }
+        let attenuation = attenuation.max(MIN_LEVEL);
+        for frame in samples.chunks_mut(NUM_CHANNELS as usize) {
+            let peak = frame.iter().fold(0.0f64, |peak, s| peak.max(s.abs()));
+            let target = self.target_reduction_db(peak / attenuation);
+            let coefficient = if target > self.reduction_db {
+                self.attack
+            } else {
+                self.release
+            };
+            self.reduction_db = target + coefficient * (self.reduction_db - target);
+
+            let gain = 10f64.powf((self.makeup_db - self.reduction_db) / 20.0);
+            for sample in frame.iter_mut() {
+                *sample = (*sample * gain).clamp(-1.0, 1.0);
+            }
+        }
+    }
+}
+
+// Runs what's played through the compressor, then on to the actual sink
+pub struct NightModeSink {
+    sink: Box<dyn Sink>,
+    night_mode: NightMode,
+    volume: Box<dyn VolumeGetter + Send>,
+    compressor: Compressor,
+}
+
+impl NightModeSink {
+    pub fn new(
+        sink: Box<dyn Sink>,
+        night_mode: NightMode,
+        volume: Box<dyn VolumeGetter + Send>,
+    ) -> Self {
+        Self {
+            sink,
+            night_mode,
+            volume,
+            compressor: Compressor::new(),
+        }
+    }
+}
+
+impl Sink for NightModeSink {
+    fn start(&mut self) -> SinkResult<()> {
+        self.sink.start()
+    }
+
+    fn stop(&mut self) -> SinkResult<()> {
+        self.sink.stop()
+    }
+
+    fn write(&mut self, packet: AudioPacket, converter: &mut Converter) -> SinkResult<()> {
+        self.compressor.set_strength(self.night_mode.strength());
+        match packet {
+            AudioPacket::Samples(mut samples) => {
+                self.compressor
+                    .process(&mut samples, self.volume.attenuation_factor());
+                self.sink.write(AudioPacket::Samples(samples), converter)
+            }
+            packet => self.sink.write(packet, converter),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn settled_gain(compressor: &mut Compressor, level: f64, attenuation: f64) -> f64 {
+        let mut samples = vec![level * attenuation; SAMPLE_RATE as usize * NUM_CHANNELS as usize];
+        compressor.process(&mut samples, attenuation);
+        samples.last().unwrap() / (level * attenuation)
+    }
+
+    #[test]
+    fn test_compressor_evens_out_levels() {
+        let mut compressor = Compressor::new();
+        assert_eq!(settled_gain(&mut compressor, 0.9, 1.0), 1.0);
+
+        compressor.set_strength(100);
+        let loud = settled_gain(&mut compressor, 0.9, 1.0);
+        let quiet = settled_gain(&mut compressor, 0.01, 1.0);
+        assert!(loud < 1.0);
+        assert!(quiet > 1.0);
+    }
+
+    #[test]
+    fn test_compressor_ignores_volume() {
+        let mut compressor = Compressor::new();
+        compressor.set_strength(50);
+        let full_volume = settled_gain(&mut compressor, 0.9, 1.0);
+        let low_volume = settled_gain(&mut compressor, 0.9, 0.05);
+        assert!((full_volume - low_volume).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,260 @@
+// Logging in through Spotify's website (OAuth authorization code flow, with PKCE), which works for
+// accounts that have no password, like those linked to Facebook.
+// The browser is sent back to a small HTTP listener of ours with the code to exchange for tokens.
+
+use async_std::io::prelude::*;
+use async_std::io::BufReader;
+use async_std::net::{TcpListener, TcpStream};
+use form_urlencoded::Serializer;
+use isahc::{AsyncReadResponseExt, Request};
+use rand::distributions::Alphanumeric;
+use rand::Rng;
+use serde::Deserialize;
+use std::time::{Duration, SystemTime};
+
+use super::SpotifyError;
+
+// The same client as Spotify's own desktop app, which allows this redirect uri
+const CLIENT_ID: &str = "65b708073fc0480ea92a077233ca87bd";
+const REDIRECT_PORT: u16 = 8898;
+const REDIRECT_URI: &str = "http://127.0.0.1:8898/login";
+
+const AUTHORIZE_URL: &str = "https://accounts.spotify.com/authorize";
+const TOKEN_URL: &str = "https://accounts.spotify.com/api/token";
+const ME_URL: &str = "https://api.spotify.com/v1/me";
+
+const SCOPES: &str = "user-read-private \
+playlist-read-private \
+playlist-read-collaborative \
+user-library-read \
+user-library-modify \
+user-follow-read \
+user-top-read \
+user-read-recently-played \
+user-read-playback-state \
+playlist-modify-public \
+playlist-modify-private \
+user-modify-playback-state \
+streaming";
+
+// How long we wait for the user to log in from their browser
+const LOGIN_TIMEOUT: Duration = Duration::from_secs(300);
+
+const LOGIN_DONE_PAGE: &str = "<!DOCTYPE html><html><body>\
+<p>You can now close this page and go back to Spot.</p>\
+</body></html>";
+
+#[derive(Debug, Clone)]
+pub struct OAuthToken {
+    pub access_token: String,
+    pub refresh_token: String,
+    pub expiry_time: SystemTime,
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    expires_in: u64,
+    // Not always sent back when refreshing, in which case the one we had is still valid
+    refresh_token: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Me {
+    id: String,
+}
+
+fn random_string(len: usize) -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(len)
+        .map(char::from)
+        .collect()
+}
+
+// base64url(sha256(verifier)), see https://www.rfc-editor.org/rfc/rfc7636#section-4.2
+fn code_challenge(verifier: &str) -> String {
+    let mut checksum =
+        glib::Checksum::new(glib::ChecksumType::Sha256).expect("sha256 should be supported");
+    checksum.update(verifier.as_bytes());
+    glib::base64_encode(&checksum.digest())
+        .trim_end_matches('=')
+        .replace('+', "-")
+        .replace('/', "_")
+}
+
+// A login in progress: the user has to go to authorize_url() and log in there
+pub struct OAuthLogin {
+    listener: TcpListener,
+    verifier: String,
+    state: String,
+}
+
+impl OAuthLogin {
+    // Fails if we can't listen for the redirect, there's no point in opening the browser then
+    pub async fn start() -> Result<Self, SpotifyError> {
+        let listener = TcpListener::bind(("127.0.0.1", REDIRECT_PORT))
+            .await
+            .map_err(|e| {
+                warn!("Could not listen for the login redirect: {}", e);
+                SpotifyError::TechnicalError
+            })?;
+        Ok(Self {
+            listener,
+            verifier: random_string(64),
+            state: random_string(16),
+        })
+    }
+
+    pub fn authorize_url(&self) -> String {
+        let query = Serializer::new(String::new())
+            .append_pair("client_id", CLIENT_ID)
+            .append_pair("response_type", "code")
+            .append_pair("redirect_uri", REDIRECT_URI)
+            .append_pair("scope", SCOPES)
+            .append_pair("state", &self.state)
+            .append_pair("code_challenge_method", "S256")
+            .append_pair("code_challenge", &code_challenge(&self.verifier))
+            .finish();
+        format!("{AUTHORIZE_URL}?{query}")
+    }
+
+    // Waits for the user to log in, then exchanges the code we're given for tokens
+    pub async fn complete(self) -> Result<OAuthToken, SpotifyError> {
+        let code = async_std::future::timeout(LOGIN_TIMEOUT, self.wait_for_code())
+            .await
+            .map_err(|_| {
+                warn!("Timed out waiting for the login redirect");
+                SpotifyError::LoginFailed
+            })??;
+
+        request_token(&[
+            ("grant_type", "authorization_code"),
+            ("code", &code),
+            ("redirect_uri", REDIRECT_URI),
+            ("client_id", CLIENT_ID),
+            ("code_verifier", &self.verifier),
+        ])
+        .await
+    }
+
+    async fn wait_for_code(&self) -> Result<String, SpotifyError> {
+        loop {
+            let (stream, _) = self
+                .listener
+                .accept()
+                .await
+                .map_err(|_| SpotifyError::TechnicalError)?;
+            // Browsers might ask for other things (a favicon...), we only care about the redirect
+            if let Some(result) = self.handle_redirect(stream).await {
+                return result;
+            }
+        }
+    }
+
+    async fn handle_redirect(&self, mut stream: TcpStream) -> Option<Result<String, SpotifyError>> {
+        let mut request_line = String::new();
+        BufReader::new(&stream)
+            .read_line(&mut request_line)
+            .await
+            .ok()?;
+
+        // GET /login?code=...&state=... HTTP/1.1
+        let path = request_line.split_whitespace().nth(1)?;
+        let query = path.strip_prefix("/login?")?;
+
+        let mut code = None;
+        let mut state = None;
+        for (key, value) in form_urlencoded::parse(query.as_bytes()) {
+            match key.as_ref() {
+                "code" => code = Some(value.into_owned()),
+                "state" => state = Some(value.into_owned()),
+                "error" => warn!("Login refused: {}", value),
+                _ => {}
+            }
+        }
+
+        let response = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            LOGIN_DONE_PAGE.len(),
+            LOGIN_DONE_PAGE
+        );
+        let _ = stream.write_all(response.as_bytes()).await;
+
+        if state.as_deref() != Some(self.state.as_str()) {
+            warn!("Login redirect with an unexpected state, ignoring");
+            return None;
+        }
+        Some(code.ok_or(SpotifyError::LoginFailed))
+    }
+}
+
+// Gets a new access token without bothering the user
+pub async fn refresh_token(refresh_token: &str) -> Result<OAuthToken, SpotifyError> {
+    let mut token = request_token(&[
+        ("grant_type", "refresh_token"),
+        ("refresh_token", refresh_token),
+        ("client_id", CLIENT_ID),
+    ])
+    .await?;
+    if token.refresh_token.is_empty() {
+        token.refresh_token = refresh_token.to_string();
+    }
+    Ok(token)
+}
+
+async fn request_token(params: &[(&str, &str)]) -> Result<OAuthToken, SpotifyError> {
+    let body = Serializer::new(String::new()).extend_pairs(params).finish();
+    let request = Request::post(TOKEN_URL)
+        .header("Content-Type", "application/x-www-form-urlencoded")
+        .body(body)
+        .map_err(|_| SpotifyError::TechnicalError)?;
+    let mut response = isahc::send_async(request)
+        .await
+        .map_err(|_| SpotifyError::TechnicalError)?;
+    if !response.status().is_success() {
+        warn!("Token request failed: {}", response.status());
+        return Err(SpotifyError::LoginFailed);
+    }
+
+    let token: TokenResponse = response
+        .json()
+        .await
+        .map_err(|_| SpotifyError::TechnicalError)?;
+    Ok(OAuthToken {
+        access_token: token.access_token,
+        refresh_token: token.refresh_token.unwrap_or_default(),
+        expiry_time: SystemTime::now() + Duration::from_secs(token.expires_in),
+    })
+}
+
+// The player session needs the username along with the token
+pub async fn get_username(access_token: &str) -> Result<String, SpotifyError> {
+    let request = Request::get(ME_URL)
+        .header("Authorization", format!("Bearer {access_token}"))
+        .body(())
+        .map_err(|_| SpotifyError::TechnicalError)?;
+    let mut response = isahc::send_async(request)
+        .await
+        .map_err(|_| SpotifyError::TechnicalError)?;
+    let me: Me = response
+        .json()
+        .await
+        .map_err(|_| SpotifyError::TechnicalError)?;
+    Ok(me.id)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_code_challenge() {
+        // Example from https://www.rfc-editor.org/rfc/rfc7636#appendix-B
+        assert_eq!(
+            code_challenge("dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk"),
+            "E9Melhoa2OwvFrEMTJguCjoKaI6EXtMbAQ8m6DqhCTw"
+        );
+    }
+}
@@ -0,0 +1,290 @@
+use librespot::playback::audio_backend::{Sink, SinkError, SinkResult};
+use librespot::playback::convert::Converter;
+use librespot::playback::decoder::AudioPacket;
+use librespot::playback::{NUM_CHANNELS, SAMPLE_RATE};
+use pipewire as pw;
+use pw::spa;
+use std::collections::VecDeque;
+use std::io::Cursor;
+use std::rc::Rc;
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use crate::config;
+
+// Interleaved 32-bit floats
+const SAMPLE_SIZE: usize = std::mem::size_of::<f32>();
+const FRAME_SIZE: usize = SAMPLE_SIZE * NUM_CHANNELS as usize;
+// A fifth of a second: enough to ride out hiccups, short enough for seeking to feel immediate
+const BUFFERED_SAMPLES: usize = SAMPLE_RATE as usize * NUM_CHANNELS as usize / 5;
+const STALLED_TIMEOUT: Duration = Duration::from_secs(1);
+
+enum Message {
+    SetTitle(String),
+    Terminate,
+}
+
+struct StreamDescriptionInner {
+    title: String,
+    sender: Option<pw::channel::Sender<Message>>,
+}
+
+// What's playing, as shown by the likes of pavucontrol or Helvum next to the stream.
+// The player sets it as tracks are loaded, and the stream (if there's one) picks it up.
+#[derive(Clone)]
+pub struct StreamDescription(Arc<Mutex<StreamDescriptionInner>>);
+
+impl Default for StreamDescription {
+    fn default() -> Self {
+        Self(Arc::new(Mutex::new(StreamDescriptionInner {
+            title: String::new(),
+            sender: None,
+        })))
+    }
+}
+
+impl StreamDescription {
+    pub fn set_title(&self, title: String) {
+        let mut inner = self.0.lock().unwrap();
+        if let Some(sender) = inner.sender.as_ref() {
+            let _ = sender.send(Message::SetTitle(title.clone()));
+        }
+        inner.title = title;
+    }
+
+    fn attach(&self, sender: pw::channel::Sender<Message>) -> String {
+        let mut inner = self.0.lock().unwrap();
+        inner.sender = Some(sender);
+        inner.title.clone()
+    }
+
+    fn detach(&self) {
+        self.0.lock().unwrap().sender = None;
+    }
+}
+
+// Samples decoded by librespot, waiting to be played by PipeWire
+#[derive(Default)]
+struct SampleQueue {
+    samples: Mutex<VecDeque<f32>>,
+    drained: Condvar,
+}
+
+impl SampleQueue {
+    // Blocks while the queue is full, which is what paces the decoder. Samples are dropped if
+    // nothing gets played for a while, so that the player doesn't hang if the stream died.
+    fn push(&self, samples: Vec<f32>) -> bool {
+        let mut queue = self.samples.lock().unwrap();
+        while queue.len() >= BUFFERED_SAMPLES {
+            let (guard, timeout) = self.drained.wait_timeout(queue, STALLED_TIMEOUT).unwrap();
+            queue = guard;
+            if timeout.timed_out() {
+                return false;
+            }
+        }
+        queue.extend(samples);
+        true
+    }
+
+    // Fills the buffer with as many frames as available, then silence
+    fn pop_into(&self, buffer: &mut [u8]) {
+        let mut queue = self.samples.lock().unwrap();
+        for chunk in buffer.chunks_exact_mut(SAMPLE_SIZE) {
+            let sample = queue.pop_front().unwrap_or(0.0);
+            chunk.copy_from_slice(&sample.to_le_bytes());
+        }
+        self.drained.notify_all();
+    }
+}
+
+fn stream_properties(app_name: &str, title: &str) -> pw::Properties {
+    pw::properties! {
+        *pw::keys::MEDIA_TYPE => "Audio",
+        *pw::keys::MEDIA_CATEGORY => "Playback",
+        *pw::keys::MEDIA_ROLE => "Music",
+        *pw::keys::APP_NAME => app_name,
+        *pw::keys::APP_ID => config::APPID,
+        *pw::keys::APP_ICON_NAME => config::APPID,
+        *pw::keys::NODE_NAME => app_name,
+        *pw::keys::MEDIA_NAME => title,
+    }
+}
+
+fn audio_format() -> Result<Vec<u8>, SinkError> {
+    let mut info = spa::param::audio::AudioInfoRaw::new();
+    info.set_format(spa::param::audio::AudioFormat::F32LE);
+    info.set_rate(SAMPLE_RATE);
+    info.set_channels(NUM_CHANNELS as u32);
+    let object = spa::pod::Value::Object(spa::pod::Object {
+        type_: spa::utils::SpaTypes::ObjectParamFormat.as_raw(),
+        id: spa::param::ParamType::EnumFormat.as_raw(),
+        properties: info.into(),
+    });
+    spa::pod::serialize::PodSerializer::serialize(Cursor::new(Vec::new()), &object)
+        .map(|(cursor, _)| cursor.into_inner())
+        .map_err(|e| SinkError::InvalidParams(format!("{e:?}")))
+}
+
+// Renames a running stream, which the bindings can't do yet
+fn set_stream_title(stream: &pw::stream::Stream, title: String) {
+    let properties = pw::properties! {
+        *pw::keys::MEDIA_NAME => title,
+    };
+    // SAFETY: both pointers come from wrappers that outlive the call, and PipeWire copies the
+    // given properties into the stream's own rather than keeping a reference to them
+    unsafe {
+        pw::sys::pw_stream_update_properties(stream.as_raw_ptr(), &(*properties.as_ptr()).dict);
+    }
+}
+
+// Runs the PipeWire loop, until told to terminate
+fn run_stream(
+    app_name: String,
+    title: String,
+    queue: Arc<SampleQueue>,
+    receiver: pw::channel::Receiver<Message>,
+) -> Result<(), pw::Error> {
+    pw::init();
+    let mainloop = pw::MainLoop::new()?;
+    let context = pw::Context::new(&mainloop)?;
+    let core = context.connect(None)?;
+
+    let stream = Rc::new(pw::stream::Stream::new(
+        &core,
+        &app_name,
+        stream_properties(&app_name, &title),
+    )?);
+
+    let _listener = stream
+        .add_local_listener_with_user_data(queue)
+        .process(|stream, queue| {
+            if let Some(mut buffer) = stream.dequeue_buffer() {
+                let data = &mut buffer.datas_mut()[0];
+                let size = match data.data() {
+                    Some(bytes) => {
+                        let size = bytes.len() - bytes.len() % FRAME_SIZE;
+                        queue.pop_into(&mut bytes[..size]);
+                        size
+                    }
+                    None => 0,
+                };
+                let chunk = data.chunk_mut();
+                *chunk.offset_mut() = 0;
+                *chunk.stride_mut() = FRAME_SIZE as _;
+                *chunk.size_mut() = size as _;
+            }
+        })
+        .register()?;
+
+    let format = audio_format().map_err(|_| pw::Error::CreationFailed)?;
+    let mut params = [spa::pod::Pod::from_bytes(&format).ok_or(pw::Error::CreationFailed)?];
+    stream.connect(
+        spa::Direction::Output,
+        None,
+        pw::stream::StreamFlags::AUTOCONNECT | pw::stream::StreamFlags::MAP_BUFFERS,
+        &mut params,
+    )?;
+
+    let _receiver = receiver.attach(&mainloop, {
+        let mainloop = mainloop.clone();
+        let stream = Rc::clone(&stream);
+        move |message| match message {
+            Message::SetTitle(title) => set_stream_title(&stream, title),
+            Message::Terminate => mainloop.quit(),
+        }
+    });
+
+    mainloop.run();
+    Ok(())
+}
+
+// Plays through a PipeWire stream of its own, rather than through the PulseAudio compatibility layer,
+// so that it's labelled properly: as music, with Spot's icon, and the title of what's playing.
+pub struct PipeWireSink {
+    app_name: String,
+    description: StreamDescription,
+    queue: Arc<SampleQueue>,
+    running: Option<(JoinHandle<()>, pw::channel::Sender<Message>)>,
+}
+
+impl PipeWireSink {
+    pub fn new(app_name: String, description: StreamDescription) -> Self {
+        Self {
+            app_name,
+            description,
+            queue: Default::default(),
+            running: None,
+        }
+    }
+}
+
+impl Sink for PipeWireSink {
+    fn start(&mut self) -> SinkResult<()> {
+        if self.running.is_some() {
+            return Ok(());
+        }
+        let (sender, receiver) = pw::channel::channel();
+        let title = self.description.attach(sender.clone());
+        let app_name = self.app_name.clone();
+        let queue = Arc::clone(&self.queue);
+        let thread = std::thread::Builder::new()
+            .name("pipewire".to_string())
+            .spawn(move || {
+                if let Err(e) = run_stream(app_name, title, queue, receiver) {
+                    error!("PipeWire stream failed: {}", e);
+                }
+            })
+            .map_err(|e| SinkError::ConnectionRefused(e.to_string()))?;
+        self.running = Some((thread, sender));
+        Ok(())
+    }
+
+    fn stop(&mut self) -> SinkResult<()> {
+        self.description.detach();
+        if let Some((thread, sender)) = self.running.take() {
+            // What's left in the queue is played on start, as stopping is how librespot pauses
+            let _ = sender.send(Message::Terminate);
+            thread
+                .join()
+                .map_err(|_| SinkError::StateChange("PipeWire thread panicked".to_string()))?;
+        }
+        Ok(())
+    }
+
+    fn write(&mut self, packet: AudioPacket, converter: &mut Converter) -> SinkResult<()> {
+        let samples = packet
+            .samples()
+            .map_err(|e| SinkError::OnWrite(e.to_string()))?;
+        if !self.queue.push(converter.f64_to_f32(samples)) {
+            debug!("PipeWire stream stalled, dropping samples");
+        }
+        Ok(())
+    }
+}
+
+impl Drop for PipeWireSink {
+    fn drop(&mut self) {
+        let _ = self.stop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_sample_queue_pads_with_silence() {
+        let queue = SampleQueue::default();
+        assert!(queue.push(vec![0.5, -0.5]));
+
+        let mut buffer = [0xffu8; 4 * SAMPLE_SIZE];
+        queue.pop_into(&mut buffer);
+        let played: Vec<f32> = buffer
+            .chunks_exact(SAMPLE_SIZE)
+            .map(|b| f32::from_le_bytes(b.try_into().unwrap()))
+            .collect();
+        assert_eq!(played, vec![0.5, -0.5, 0.0, 0.0]);
+    }
+}
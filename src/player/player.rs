@@ -1,4 +1,4 @@
-use futures::channel::mpsc::UnboundedReceiver;
+use futures::channel::mpsc::{UnboundedReceiver, UnboundedSender};
 use futures::stream::StreamExt;
 
 use librespot::core::authentication::Credentials;
@@ -6,25 +6,39 @@ use librespot::core::cache::Cache;
 use librespot::core::config::SessionConfig;
 use librespot::core::keymaster;
 use librespot::core::session::{Session, SessionError};
+use librespot::core::spotify_id::{FileId, SpotifyId};
+use librespot::metadata::{FileFormat, Metadata, Track};
 
+use librespot::playback::mixer::alsamixer::AlsaMixer;
 use librespot::playback::mixer::softmixer::SoftMixer;
 use librespot::playback::mixer::{Mixer, MixerConfig};
 use librespot::protocol::authentication::AuthenticationType;
+use librespot::protocol::playlist4changes::SelectedListContent;
+use protobuf::Message;
 
-use librespot::playback::audio_backend;
+use librespot::playback::audio_backend::{self, Sink};
 use librespot::playback::config::{AudioFormat, Bitrate, PlayerConfig, VolumeCtrl};
 use librespot::playback::player::{Player, PlayerEvent, PlayerEventChannel};
 
 use std::cell::RefCell;
+use std::collections::hash_map::DefaultHasher;
 use std::env;
 use std::error::Error;
 use std::fmt;
+use std::hash::Hasher;
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
 use std::time::{Duration, SystemTime};
 
+use super::metrics::{MetricsCollector, MetricsSink};
+use super::night_mode::{NightMode, NightModeSink};
+use super::oauth::{self, OAuthLogin, OAuthToken};
+use super::pipewire::{PipeWireSink, StreamDescription};
+use super::silence::{SilenceTrimSink, SilenceTrimming};
 use super::Command;
 use crate::app::credentials;
-use crate::settings::SpotSettings;
+use crate::app::models::RootlistItem;
+use crate::settings::{NightModeSettings, PlaybackVolume, SilenceTrimSettings, SpotSettings};
 
 #[derive(Debug)]
 pub enum SpotifyError {
@@ -53,10 +67,14 @@ pub trait SpotifyPlayerDelegate {
     fn end_of_track_reached(&self);
     fn password_login_successful(&self, credentials: credentials::Credentials);
     fn token_login_successful(&self, username: String, token: String);
+    fn authorization_page_ready(&self, url: String);
     fn refresh_successful(&self, token: String, token_expiry_time: SystemTime);
     fn report_error(&self, error: SpotifyError);
-    fn notify_playback_state(&self, position: u32);
+    fn notify_playback_state(&self, position: u32, duration: u32);
+    fn notify_buffering(&self, buffering: bool);
+    fn notify_bitrate(&self, bitrate: Option<u32>);
     fn preload_next_track(&self);
+    fn rootlist_fetched(&self, rootlist: Vec<RootlistItem>);
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -64,14 +82,39 @@ pub enum AudioBackend {
     GStreamer(String),
     PulseAudio,
     Alsa(String),
+    PipeWire,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AudioMixer {
+    Software,
+    // Only with the ALSA backend, falls back to software otherwise
+    Hardware,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct SpotifyPlayerSettings {
     pub bitrate: Bitrate,
     pub backend: AudioBackend,
+    pub mixer: AudioMixer,
     pub gapless: bool,
     pub ap_port: Option<u16>,
+    // Both only set by player profiles, in which case they override the defaults
+    pub device_name: Option<String>,
+    pub cache_dir: Option<PathBuf>,
+}
+
+impl SpotifyPlayerSettings {
+    // Whether these settings only differ by the quality of the audio, which a new player
+    // can pick up for the next track without interrupting the current one
+    fn differs_only_in_quality(&self, other: &Self) -> bool {
+        let with_other_quality = Self {
+            bitrate: other.bitrate,
+            gapless: other.gapless,
+            ..self.clone()
+        };
+        with_other_quality == *other
+    }
 }
 
 impl Default for SpotifyPlayerSettings {
@@ -80,27 +123,63 @@ impl Default for SpotifyPlayerSettings {
             bitrate: Bitrate::Bitrate160,
             gapless: true,
             backend: AudioBackend::PulseAudio,
+            mixer: AudioMixer::Software,
             ap_port: None,
+            device_name: None,
+            cache_dir: None,
         }
     }
 }
 
 pub struct SpotifyPlayer {
     settings: SpotifyPlayerSettings,
+    // Settings to apply when the next track is loaded
+    pending_settings: Option<SpotifyPlayerSettings>,
     player: Option<Player>,
     mixer: Option<Box<dyn Mixer>>,
     session: Option<Session>,
+    // Only used by the PipeWire backend
+    stream_description: StreamDescription,
+    night_mode: NightMode,
+    silence_trimming: SilenceTrimming,
+    metrics: MetricsCollector,
     delegate: Rc<dyn SpotifyPlayerDelegate>,
+    // To send commands to ourselves, once something we're waiting on in the background is done
+    commands: UnboundedSender<Command>,
+    // Waiting for the user to log in from their browser
+    oauth_login: Option<tokio::task::JoinHandle<()>>,
 }
 
 impl SpotifyPlayer {
-    pub fn new(settings: SpotifyPlayerSettings, delegate: Rc<dyn SpotifyPlayerDelegate>) -> Self {
+    pub fn new(
+        settings: SpotifyPlayerSettings,
+        delegate: Rc<dyn SpotifyPlayerDelegate>,
+        commands: UnboundedSender<Command>,
+    ) -> Self {
+        let night_mode = NightMode::default();
+        night_mode.set_strength(NightModeSettings::new_from_gsettings().strength);
+        // Until the first track is loaded, and the app tells whether its album opted out
+        let silence_trimming = SilenceTrimming::default();
+        silence_trimming.set_enabled(SilenceTrimSettings::new_from_gsettings().enabled);
+        let metrics = MetricsCollector::default();
+        metrics.set_enabled(
+            SpotSettings::new_from_gsettings()
+                .map(|settings| settings.playback_metrics)
+                .unwrap_or_default(),
+        );
         Self {
             settings,
+            pending_settings: None,
             mixer: None,
             player: None,
             session: None,
+            stream_description: Default::default(),
+            night_mode,
+            silence_trimming,
+            metrics,
             delegate,
+            commands,
+            oauth_login: None,
         }
     }
 
@@ -108,10 +187,22 @@ impl SpotifyPlayer {
         match action {
             Command::PlayerSetVolume(volume) => {
                 if let Some(mixer) = self.mixer.as_mut() {
-                    mixer.set_volume((VolumeCtrl::MAX_VOLUME as f64 * volume) as u16);
+                    mixer.set_volume(mixer_volume(volume));
                 }
                 Ok(())
             }
+            Command::PlayerSetNightMode(strength) => {
+                self.night_mode.set_strength(strength);
+                Ok(())
+            }
+            Command::PlayerSetSilenceTrimming(enabled) => {
+                self.silence_trimming.set_enabled(enabled);
+                Ok(())
+            }
+            Command::PlayerSetMetricsEnabled(enabled) => {
+                self.metrics.set_enabled(enabled);
+                Ok(())
+            }
             Command::PlayerResume => {
                 self.player
                     .as_ref()
@@ -141,10 +232,14 @@ impl SpotifyPlayer {
                 Ok(())
             }
             Command::PlayerLoad { track, resume } => {
-                self.player
-                    .as_mut()
-                    .ok_or(SpotifyError::PlayerNotReady)?
-                    .load(track, resume, 0);
+                if let Some(settings) = self.pending_settings.take() {
+                    self.apply_settings(settings)?;
+                }
+                let player = self.player.as_mut().ok_or(SpotifyError::PlayerNotReady)?;
+                self.metrics.track_loading(resume);
+                player.load(track, resume, 0);
+                self.report_bitrate(track);
+                self.describe_stream(track);
                 Ok(())
             }
             Command::PlayerPreload(track) => {
@@ -170,7 +265,7 @@ impl SpotifyPlayer {
             }
             Command::PasswordLogin { username, password } => {
                 let credentials = Credentials::with_password(username, password.clone());
-                let new_session = create_session(&credentials, self.settings.ap_port).await?;
+                let new_session = create_session(&credentials, &self.settings).await?;
                 let (token, token_expiry_time) =
                     get_access_token_and_expiry_time(&new_session).await?;
                 let credentials = credentials::Credentials {
@@ -179,14 +274,10 @@ impl SpotifyPlayer {
                     token,
                     token_expiry_time: Some(token_expiry_time),
                     country: new_session.country(),
+                    refresh_token: None,
                 };
                 self.delegate.password_login_successful(credentials);
-
-                let (new_player, channel) = self.create_player(new_session.clone());
-                tokio::task::spawn_local(player_setup_delegate(channel, Rc::clone(&self.delegate)));
-                self.player.replace(new_player);
-                self.session.replace(new_session);
-
+                self.start_session(new_session);
                 Ok(())
             }
             Command::TokenLogin { username, token } => {
@@ -195,33 +286,166 @@ impl SpotifyPlayer {
                     auth_type: AuthenticationType::AUTHENTICATION_SPOTIFY_TOKEN,
                     auth_data: token.clone().into_bytes(),
                 };
-                let new_session = create_session(&credentials, self.settings.ap_port).await?;
+                let new_session = create_session(&credentials, &self.settings).await?;
                 self.delegate
                     .token_login_successful(new_session.username(), token);
-
-                let (new_player, channel) = self.create_player(new_session.clone());
-                tokio::task::spawn_local(player_setup_delegate(channel, Rc::clone(&self.delegate)));
-                self.player.replace(new_player);
-                self.session.replace(new_session);
-
+                self.start_session(new_session);
                 Ok(())
             }
+            Command::OAuthLogin {
+                refresh_token: Some(refresh_token),
+            } => {
+                let token = oauth::refresh_token(&refresh_token).await?;
+                self.oauth_login(token).await
+            }
+            Command::OAuthLogin {
+                refresh_token: None,
+            } => {
+                // Only one login at a time, and it's listening on the port we need
+                if let Some(previous) = self.oauth_login.take() {
+                    previous.abort();
+                    // Cancelled, once it let go of the port
+                    let _ = previous.await;
+                }
+                let login = OAuthLogin::start().await?;
+                self.delegate
+                    .authorization_page_ready(login.authorize_url());
+                // The user may take a while, other commands shouldn't wait on them
+                let commands = self.commands.clone();
+                let delegate = Rc::clone(&self.delegate);
+                self.oauth_login = Some(tokio::task::spawn_local(async move {
+                    match login.complete().await {
+                        Ok(token) => {
+                            let _ = commands.unbounded_send(Command::OAuthLoginCompleted(token));
+                        }
+                        Err(err) => delegate.report_error(err),
+                    }
+                }));
+                Ok(())
+            }
+            Command::OAuthLoginCompleted(token) => {
+                self.oauth_login = None;
+                self.oauth_login(token).await
+            }
             Command::ReloadSettings => {
                 let settings = SpotSettings::new_from_gsettings().unwrap_or_default();
-                self.settings = settings.player_settings;
-
-                let session = self.session.take().ok_or(SpotifyError::PlayerNotReady)?;
-                let (new_player, channel) = self.create_player(session);
-                tokio::task::spawn_local(player_setup_delegate(channel, Rc::clone(&self.delegate)));
-                self.player.replace(new_player);
-
+                let player_settings = settings.player_settings;
+                // No need to interrupt the current track to change the quality, the next one will do
+                if self.player.is_some() && self.settings.differs_only_in_quality(&player_settings)
+                {
+                    self.pending_settings = Some(player_settings);
+                    Ok(())
+                } else {
+                    self.pending_settings = None;
+                    self.apply_settings(player_settings)
+                }
+            }
+            Command::FetchRootlist => {
+                let session = self.session.as_ref().ok_or(SpotifyError::PlayerNotReady)?;
+                // Folders are a nice to have, no need to bother the user if we can't get them
+                match get_rootlist(session).await {
+                    Ok(rootlist) => self.delegate.rootlist_fetched(rootlist),
+                    Err(err) => warn!("Could not fetch rootlist: {}", err),
+                }
                 Ok(())
             }
         }
     }
 
+    fn apply_settings(
+        &mut self,
+        player_settings: SpotifyPlayerSettings,
+    ) -> Result<(), SpotifyError> {
+        // The mixer depends on these, so it needs to be recreated with the player
+        if player_settings.mixer != self.settings.mixer
+            || player_settings.backend != self.settings.backend
+        {
+            self.mixer = None;
+        }
+        self.settings = player_settings;
+
+        let session = self.session.clone().ok_or(SpotifyError::PlayerNotReady)?;
+        let (new_player, channel) = self.create_player(session);
+        tokio::task::spawn_local(player_setup_delegate(
+            channel,
+            Rc::clone(&self.delegate),
+            self.metrics.clone(),
+            self.silence_trimming.clone(),
+        ));
+        self.player.replace(new_player);
+
+        Ok(())
+    }
+
+    async fn oauth_login(&mut self, token: OAuthToken) -> Result<(), SpotifyError> {
+        let credentials = Credentials {
+            username: oauth::get_username(&token.access_token).await?,
+            auth_type: AuthenticationType::AUTHENTICATION_SPOTIFY_TOKEN,
+            auth_data: token.access_token.clone().into_bytes(),
+        };
+        let new_session = create_session(&credentials, &self.settings).await?;
+        // Same as a password login from there, so that the credentials are saved for autologin
+        let credentials = credentials::Credentials {
+            username: new_session.username(),
+            password: String::new(),
+            token: token.access_token,
+            token_expiry_time: Some(token.expiry_time),
+            country: new_session.country(),
+            refresh_token: Some(token.refresh_token),
+        };
+        self.delegate.password_login_successful(credentials);
+        self.start_session(new_session);
+        Ok(())
+    }
+
+    fn start_session(&mut self, session: Session) {
+        let (new_player, channel) = self.create_player(session.clone());
+        tokio::task::spawn_local(player_setup_delegate(
+            channel,
+            Rc::clone(&self.delegate),
+            self.metrics.clone(),
+            self.silence_trimming.clone(),
+        ));
+        self.player.replace(new_player);
+        self.session.replace(session);
+    }
+
+    fn report_bitrate(&self, track: SpotifyId) {
+        if let Some(session) = self.session.clone() {
+            let bitrate = self.settings.bitrate;
+            let delegate = Rc::clone(&self.delegate);
+            let metrics = self.metrics.clone();
+            tokio::task::spawn_local(async move {
+                let file = get_effective_file(&session, track, bitrate).await;
+                if let Some((file, _)) = file {
+                    metrics.check_cache(&session, file);
+                }
+                delegate.notify_bitrate(file.map(|(_, kbps)| kbps));
+            });
+        }
+    }
+
+    fn describe_stream(&self, track: SpotifyId) {
+        if self.settings.backend != AudioBackend::PipeWire {
+            return;
+        }
+        if let Some(session) = self.session.clone() {
+            let description = self.stream_description.clone();
+            tokio::task::spawn_local(async move {
+                if let Ok(track) = Track::get(&session, track).await {
+                    description.set_title(track.name);
+                }
+            });
+        }
+    }
+
     fn create_player(&mut self, session: Session) -> (Player, PlayerEventChannel) {
         let backend = self.settings.backend.clone();
+        let device_name = self
+            .settings
+            .device_name
+            .clone()
+            .unwrap_or_else(|| "Spot".to_string());
 
         let player_config = PlayerConfig {
             gapless: self.settings.gapless,
@@ -230,36 +454,21 @@ impl SpotifyPlayer {
         };
         info!("bitrate: {:?}", &player_config.bitrate);
 
-        let soft_volume = self
-            .mixer
-            .get_or_insert_with(|| {
-                let mix = Box::new(SoftMixer::open(MixerConfig {
-                    // This value feels reasonable to me. Feel free to change it
-                    volume_ctrl: VolumeCtrl::Log(VolumeCtrl::DEFAULT_DB_RANGE / 2.0),
-                    ..Default::default()
-                }));
-                // TODO: Should read volume from somewhere instead of hard coding.
-                // Sets volume to 100%
-                mix.set_volume(VolumeCtrl::MAX_VOLUME);
-                mix
-            })
-            .get_soft_volume();
-        Player::new(player_config, session, soft_volume, move || match backend {
-            AudioBackend::GStreamer(pipeline) => {
-                let backend = audio_backend::find(Some("gstreamer".to_string())).unwrap();
-                backend(Some(pipeline), AudioFormat::default())
-            }
-            AudioBackend::PulseAudio => {
-                info!("using pulseaudio");
-                env::set_var("PULSE_PROP_application.name", "Spot");
-                let backend = audio_backend::find(Some("pulseaudio".to_string())).unwrap();
-                backend(None, AudioFormat::default())
-            }
-            AudioBackend::Alsa(device) => {
-                info!("using alsa ({})", &device);
-                let backend = audio_backend::find(Some("alsa".to_string())).unwrap();
-                backend(Some(device), AudioFormat::default())
-            }
+        let stream_description = self.stream_description.clone();
+        let night_mode = self.night_mode.clone();
+        let silence_trimming = self.silence_trimming.clone();
+        let metrics = self.metrics.clone();
+        let settings = &self.settings;
+        let mixer = self.mixer.get_or_insert_with(|| create_mixer(settings));
+        let soft_volume = mixer.get_soft_volume();
+        // For night mode to tell how loud the track is, regardless of the volume
+        let night_mode_volume = mixer.get_soft_volume();
+        Player::new(player_config, session, soft_volume, move || {
+            let sink = create_sink(backend, device_name, stream_description);
+            let sink = Box::new(NightModeSink::new(sink, night_mode, night_mode_volume));
+            // Skipped silence isn't worth compressing
+            let sink = Box::new(SilenceTrimSink::new(sink, silence_trimming));
+            Box::new(MetricsSink::new(sink, metrics))
         })
     }
 
@@ -278,6 +487,82 @@ impl SpotifyPlayer {
     }
 }
 
+// Where librespot keeps credentials, the volume and the audio it downloads
+pub fn cache_dir(settings: &SpotifyPlayerSettings) -> PathBuf {
+    settings
+        .cache_dir
+        .clone()
+        .unwrap_or_else(|| glib::user_cache_dir().join("spot").join("librespot"))
+}
+
+// Whether the sound server or the sound card the backend plays to is there, without connecting to it
+pub fn audio_backend_available(backend: &AudioBackend) -> bool {
+    let runtime_dir = glib::user_runtime_dir();
+    match backend {
+        AudioBackend::GStreamer(_) => audio_backend::find(Some("gstreamer".to_string())).is_some(),
+        AudioBackend::PulseAudio => {
+            env::var_os("PULSE_SERVER").is_some() || runtime_dir.join("pulse/native").exists()
+        }
+        AudioBackend::Alsa(_) => Path::new("/dev/snd").exists(),
+        AudioBackend::PipeWire => {
+            env::var_os("PIPEWIRE_REMOTE").is_some() || runtime_dir.join("pipewire-0").exists()
+        }
+    }
+}
+
+fn create_sink(
+    backend: AudioBackend,
+    device_name: String,
+    stream_description: StreamDescription,
+) -> Box<dyn Sink> {
+    match backend {
+        AudioBackend::GStreamer(pipeline) => {
+            let backend = audio_backend::find(Some("gstreamer".to_string())).unwrap();
+            backend(Some(pipeline), AudioFormat::default())
+        }
+        AudioBackend::PulseAudio => {
+            info!("using pulseaudio");
+            env::set_var("PULSE_PROP_application.name", &device_name);
+            let backend = audio_backend::find(Some("pulseaudio".to_string())).unwrap();
+            backend(None, AudioFormat::default())
+        }
+        AudioBackend::Alsa(device) => {
+            info!("using alsa ({})", &device);
+            let backend = audio_backend::find(Some("alsa".to_string())).unwrap();
+            backend(Some(device), AudioFormat::default())
+        }
+        AudioBackend::PipeWire => {
+            info!("using pipewire");
+            Box::new(PipeWireSink::new(device_name, stream_description))
+        }
+    }
+}
+
+fn create_mixer(settings: &SpotifyPlayerSettings) -> Box<dyn Mixer> {
+    let mixer: Box<dyn Mixer> = match (settings.mixer, &settings.backend) {
+        (AudioMixer::Hardware, AudioBackend::Alsa(device)) => {
+            info!("using alsa mixer ({})", device);
+            Box::new(AlsaMixer::open(MixerConfig {
+                device: device.clone(),
+                ..Default::default()
+            }))
+        }
+        _ => Box::new(SoftMixer::open(MixerConfig {
+            // This value feels reasonable to me. Feel free to change it
+            volume_ctrl: VolumeCtrl::Log(VolumeCtrl::DEFAULT_DB_RANGE / 2.0),
+            ..Default::default()
+        })),
+    };
+    // Restore the volume from the last session
+    mixer.set_volume(mixer_volume(PlaybackVolume::new_from_gsettings().level));
+    mixer
+}
+
+// From a volume between 0 and 1
+fn mixer_volume(volume: f64) -> u16 {
+    (VolumeCtrl::MAX_VOLUME as f64 * volume.clamp(0.0, 1.0)) as u16
+}
+
 const CLIENT_ID: &str = "782ae96ea60f4cdf986a766049607005";
 
 const SCOPES: &str = "user-read-private,\
@@ -285,6 +570,7 @@ playlist-read-private,\
 playlist-read-collaborative,\
 user-library-read,\
 user-library-modify,\
+user-follow-read,\
 user-top-read,\
 user-read-recently-played,\
 user-read-playback-state,\
@@ -306,15 +592,74 @@ async fn get_access_token_and_expiry_time(
     Ok((token.access_token, expiry_time))
 }
 
+// Not every track is available in every quality: like librespot's player, we pick the first format
+// available from the ones matching the requested bitrate
+async fn get_effective_file(
+    session: &Session,
+    track: SpotifyId,
+    bitrate: Bitrate,
+) -> Option<(FileId, u32)> {
+    let track = Track::get(session, track).await.ok()?;
+    let formats = match bitrate {
+        Bitrate::Bitrate96 => [
+            (FileFormat::OGG_VORBIS_96, 96),
+            (FileFormat::OGG_VORBIS_160, 160),
+            (FileFormat::OGG_VORBIS_320, 320),
+        ],
+        Bitrate::Bitrate160 => [
+            (FileFormat::OGG_VORBIS_160, 160),
+            (FileFormat::OGG_VORBIS_96, 96),
+            (FileFormat::OGG_VORBIS_320, 320),
+        ],
+        Bitrate::Bitrate320 => [
+            (FileFormat::OGG_VORBIS_320, 320),
+            (FileFormat::OGG_VORBIS_160, 160),
+            (FileFormat::OGG_VORBIS_96, 96),
+        ],
+    };
+    formats
+        .iter()
+        .find_map(|(format, kbps)| track.files.get(format).map(|file| (*file, *kbps)))
+}
+
+// The rootlist isn't exposed by the web API, but we can get it (with folders) from the player session
+async fn get_rootlist(session: &Session) -> Result<Vec<RootlistItem>, SpotifyError> {
+    let uri = format!("hm://playlist/user/{}/rootlist", session.username());
+    let response = session
+        .mercury()
+        .get(uri)
+        .await
+        .map_err(|_| SpotifyError::TechnicalError)?;
+    let payload = response
+        .payload
+        .first()
+        .ok_or(SpotifyError::TechnicalError)?;
+    let content =
+        SelectedListContent::parse_from_bytes(payload).map_err(|_| SpotifyError::TechnicalError)?;
+    let uris = content
+        .get_contents()
+        .get_items()
+        .iter()
+        .map(|item| item.get_uri());
+    Ok(RootlistItem::parse_uris(uris))
+}
+
 async fn create_session_with_port(
     credentials: &Credentials,
+    settings: &SpotifyPlayerSettings,
     ap_port: Option<u16>,
 ) -> Result<Session, SpotifyError> {
-    let session_config = SessionConfig {
+    let mut session_config = SessionConfig {
         ap_port,
         ..Default::default()
     };
-    let root = glib::user_cache_dir().join("spot").join("librespot");
+    // A stable id per device name, so that each profile shows up as its own device
+    if let Some(device_name) = settings.device_name.as_ref() {
+        let mut hasher = DefaultHasher::new();
+        hasher.write(device_name.as_bytes());
+        session_config.device_id = format!("{:016x}", hasher.finish());
+    }
+    let root = cache_dir(settings);
     let cache = Cache::new(
         Some(root.join("credentials")),
         Some(root.join("volume")),
@@ -335,15 +680,15 @@ async fn create_session_with_port(
 
 async fn create_session(
     credentials: &Credentials,
-    ap_port: Option<u16>,
+    settings: &SpotifyPlayerSettings,
 ) -> Result<Session, SpotifyError> {
-    match ap_port {
-        Some(_) => create_session_with_port(credentials, ap_port).await,
+    match settings.ap_port {
+        Some(_) => create_session_with_port(credentials, settings, settings.ap_port).await,
         None => {
             let mut ports_to_try = KNOWN_AP_PORTS.iter();
             loop {
                 if let Some(next_port) = ports_to_try.next() {
-                    let res = create_session_with_port(credentials, *next_port).await;
+                    let res = create_session_with_port(credentials, settings, *next_port).await;
                     match res {
                         Err(SpotifyError::TechnicalError) => continue,
                         _ => break res,
@@ -359,14 +704,33 @@ async fn create_session(
 async fn player_setup_delegate(
     mut channel: PlayerEventChannel,
     delegate: Rc<dyn SpotifyPlayerDelegate>,
+    metrics: MetricsCollector,
+    silence_trimming: SilenceTrimming,
 ) {
     while let Some(event) = channel.recv().await {
         match event {
             PlayerEvent::EndOfTrack { .. } => {
+                metrics.save();
                 delegate.end_of_track_reached();
             }
-            PlayerEvent::Playing { position_ms, .. } => {
-                delegate.notify_playback_state(position_ms);
+            PlayerEvent::Loading { .. } => {
+                silence_trimming.reset_position();
+                delegate.notify_buffering(true);
+            }
+            PlayerEvent::Playing {
+                position_ms,
+                duration_ms,
+                ..
+            } => {
+                metrics.track_started();
+                metrics.save();
+                silence_trimming.set_position(position_ms, duration_ms);
+                delegate.notify_buffering(false);
+                delegate.notify_playback_state(position_ms, duration_ms);
+            }
+            PlayerEvent::Paused { .. } | PlayerEvent::Stopped { .. } => {
+                metrics.save();
+                delegate.notify_buffering(false);
             }
             PlayerEvent::TimeToPreloadNextTrack { .. } => {
                 debug!("Requestiong next track to be preloaded...");
@@ -0,0 +1,201 @@
+use librespot::playback::audio_backend::{Sink, SinkResult};
+use librespot::playback::convert::Converter;
+use librespot::playback::decoder::AudioPacket;
+use librespot::playback::{NUM_CHANNELS, SAMPLE_RATE};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+
+// About -60 dBFS: the tail of a fade out, or the noise floor of a quiet recording
+const SILENCE_LEVEL: f64 = 0.001;
+// Pauses shorter than that are part of the music, and so is the start of longer ones
+const KEPT_SILENCE_SECS: u64 = 4;
+// Only silences this close to the end of a track are skipped, pauses before that are part of it
+const TRAILING_SECS: u64 = 60;
+
+fn ms_to_frames(ms: u32) -> u64 {
+    u64::from(ms) * u64::from(SAMPLE_RATE) / 1000
+}
+
+#[derive(Default)]
+struct SilenceTrimmingState {
+    enabled: AtomicBool,
+    // Where we are in the current track, and where it ends (0 if we don't know), in frames
+    position: AtomicU64,
+    duration: AtomicU64,
+}
+
+// Whether long silences are skipped for the current track (the setting is on, and its album didn't
+// opt out), shared with the sink so that changes apply right away.
+// Also follows how far along the track the sink is, since only its end is trimmed.
+#[derive(Clone, Default)]
+pub struct SilenceTrimming(Arc<SilenceTrimmingState>);
+
+impl SilenceTrimming {
+    pub fn set_enabled(&self, enabled: bool) {
+        self.0.enabled.store(enabled, Ordering::Relaxed);
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.0.enabled.load(Ordering::Relaxed)
+    }
+
+    // As reported by the player, whenever a track starts playing or is seeked
+    pub fn set_position(&self, position_ms: u32, duration_ms: u32) {
+        self.0
+            .position
+            .store(ms_to_frames(position_ms), Ordering::Relaxed);
+        self.0
+            .duration
+            .store(ms_to_frames(duration_ms), Ordering::Relaxed);
+    }
+
+    // Until the next track starts playing, we don't know where it ends
+    pub fn reset_position(&self) {
+        self.set_position(0, 0);
+    }
+
+    // Moves past the frames just decoded, returning whether they're close enough to the end
+    fn advance(&self, frames: u64) -> bool {
+        let position = self.0.position.fetch_add(frames, Ordering::Relaxed);
+        let duration = self.0.duration.load(Ordering::Relaxed);
+        duration > 0 && position + TRAILING_SECS * u64::from(SAMPLE_RATE) >= duration
+    }
+}
+
+// Keeps track of how long it's been silent
+#[derive(Default)]
+struct SilenceDetector {
+    silent_frames: u64,
+}
+
+impl SilenceDetector {
+    // Whether the packet is silent, and comes after enough silence to be skipped
+    fn is_skippable(&mut self, samples: &[f64]) -> bool {
+        if samples.iter().any(|sample| sample.abs() >= SILENCE_LEVEL) {
+            self.silent_frames = 0;
+            return false;
+        }
+        let skippable = self.silent_frames >= KEPT_SILENCE_SECS * SAMPLE_RATE as u64;
+        self.silent_frames += (samples.len() / NUM_CHANNELS as usize) as u64;
+        skippable
+    }
+
+    fn reset(&mut self) {
+        self.silent_frames = 0;
+    }
+}
+
+// Drops what's past the first few seconds of a long silence at the end of a track instead of passing
+// it on to the actual sink. With nothing to wait for, the decoder rushes through the rest of the
+// silence, until the track ends (or the music starts again, should there be a hidden track).
+pub struct SilenceTrimSink {
+    sink: Box<dyn Sink>,
+    trimming: SilenceTrimming,
+    detector: SilenceDetector,
+}
+
+impl SilenceTrimSink {
+    pub fn new(sink: Box<dyn Sink>, trimming: SilenceTrimming) -> Self {
+        Self {
+            sink,
+            trimming,
+            detector: Default::default(),
+        }
+    }
+
+    // Whether the packet is part of a long silence, close to the end of the track
+    fn is_skippable(&mut self, samples: &[f64]) -> bool {
+        let near_end = self
+            .trimming
+            .advance((samples.len() / NUM_CHANNELS as usize) as u64);
+        // Silences are followed before that too, to know how long they've lasted by then
+        self.detector.is_skippable(samples) && near_end
+    }
+}
+
+impl Sink for SilenceTrimSink {
+    fn start(&mut self) -> SinkResult<()> {
+        self.sink.start()
+    }
+
+    fn stop(&mut self) -> SinkResult<()> {
+        self.detector.reset();
+        self.sink.stop()
+    }
+
+    fn write(&mut self, packet: AudioPacket, converter: &mut Converter) -> SinkResult<()> {
+        if !self.trimming.is_enabled() {
+            self.detector.reset();
+            return self.sink.write(packet, converter);
+        }
+        match packet {
+            AudioPacket::Samples(samples) if self.is_skippable(&samples) => Ok(()),
+            packet => self.sink.write(packet, converter),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    // A tenth of a second
+    fn packet(level: f64) -> Vec<f64> {
+        vec![level; SAMPLE_RATE as usize / 10 * NUM_CHANNELS as usize]
+    }
+
+    fn skipped_secs(detector: &mut SilenceDetector, level: f64, secs: u64) -> f64 {
+        let skipped = (0..secs * 10)
+            .filter(|_| detector.is_skippable(&packet(level)))
+            .count();
+        skipped as f64 / 10.0
+    }
+
+    #[test]
+    fn test_only_long_silences_are_skipped() {
+        let mut detector = SilenceDetector::default();
+        assert_eq!(skipped_secs(&mut detector, 0.5, 10), 0.0);
+        assert_eq!(skipped_secs(&mut detector, 0.0, 3), 0.0);
+        assert_eq!(skipped_secs(&mut detector, 0.0001, 7), 6.0);
+
+        // The music starts again
+        assert_eq!(skipped_secs(&mut detector, 0.5, 1), 0.0);
+        assert_eq!(skipped_secs(&mut detector, 0.0, 4), 0.0);
+    }
+
+    struct NullSink;
+
+    impl Sink for NullSink {
+        fn write(&mut self, _packet: AudioPacket, _converter: &mut Converter) -> SinkResult<()> {
+            Ok(())
+        }
+    }
+
+    fn trimmed_secs(sink: &mut SilenceTrimSink, level: f64, secs: u64) -> f64 {
+        let skipped = (0..secs * 10)
+            .filter(|_| sink.is_skippable(&packet(level)))
+            .count();
+        skipped as f64 / 10.0
+    }
+
+    #[test]
+    fn test_only_silences_at_the_end_are_skipped() {
+        let trimming = SilenceTrimming::default();
+        trimming.set_enabled(true);
+        trimming.set_position(0, 180_000);
+        let mut sink = SilenceTrimSink::new(Box::new(NullSink), trimming.clone());
+
+        // A pause in the middle of the track
+        assert_eq!(trimmed_secs(&mut sink, 0.5, 60), 0.0);
+        assert_eq!(trimmed_secs(&mut sink, 0.0, 20), 0.0);
+
+        // The silence it ends with
+        assert_eq!(trimmed_secs(&mut sink, 0.5, 70), 0.0);
+        assert_eq!(trimmed_secs(&mut sink, 0.0, 30), 26.0);
+
+        // Nothing is skipped until we know where the next track ends
+        trimming.reset_position();
+        assert_eq!(trimmed_secs(&mut sink, 0.0, 30), 0.0);
+    }
+}
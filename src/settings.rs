@@ -1,7 +1,14 @@
-use crate::player::{AudioBackend, SpotifyPlayerSettings};
+use crate::app::models::{DeletedPlaylist, PlaylistLabel};
+use crate::app::state::{LibraryOptions, LibrarySort, ReleaseAlerts};
+use crate::app::ShuffleMode;
+use crate::player::{AudioBackend, AudioMixer, SpotifyPlayerSettings};
 use gio::prelude::SettingsExt;
+use glib::ToVariant;
 use libadwaita::ColorScheme;
 use librespot::playback::config::Bitrate;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 const SETTINGS: &str = "dev.alextren.Spot";
 
@@ -35,6 +42,407 @@ impl WindowGeometry {
     }
 }
 
+// Last volume level, restored on startup
+#[derive(Clone, Debug)]
+pub struct PlaybackVolume {
+    // Between 0 and 1
+    pub level: f64,
+}
+
+impl Default for PlaybackVolume {
+    fn default() -> Self {
+        Self { level: 1.0 }
+    }
+}
+
+impl PlaybackVolume {
+    pub fn new_from_gsettings() -> Self {
+        let settings = gio::Settings::new(SETTINGS);
+        Self {
+            level: settings.double("volume").clamp(0.0, 1.0),
+        }
+    }
+
+    pub fn save(&self) -> Option<()> {
+        let settings = gio::Settings::new(SETTINGS);
+        settings.set_double("volume", self.level).ok()
+    }
+}
+
+// Night mode compression (see NightModeSink)
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct NightModeSettings {
+    // In percent, 0 if disabled
+    pub strength: u32,
+}
+
+impl NightModeSettings {
+    pub fn new_from_gsettings() -> Self {
+        let settings = gio::Settings::new(SETTINGS);
+        Self {
+            strength: settings.uint("night-mode-strength").min(100),
+        }
+    }
+}
+
+// Whether long silences at the end of tracks are skipped (see SilenceTrimSink)
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SilenceTrimSettings {
+    pub enabled: bool,
+    // Albums whose silences are part of the album, like a gap before a hidden track
+    pub kept_albums: HashSet<String>,
+}
+
+impl SilenceTrimSettings {
+    pub fn new_from_gsettings() -> Self {
+        let settings = gio::Settings::new(SETTINGS);
+        Self {
+            enabled: settings.boolean("trim-silence"),
+            kept_albums: settings
+                .value("trim-silence-kept-albums")
+                .get::<Vec<String>>()
+                .unwrap_or_default()
+                .into_iter()
+                .collect(),
+        }
+    }
+
+    pub fn save_kept_albums(&self) -> Option<()> {
+        let settings = gio::Settings::new(SETTINGS);
+        let kept: Vec<String> = self.kept_albums.iter().cloned().collect();
+        settings
+            .set_value("trim-silence-kept-albums", &kept.to_variant())
+            .ok()
+    }
+
+    pub fn applies_to(&self, album_id: &str) -> bool {
+        self.enabled && !self.kept_albums.contains(album_id)
+    }
+}
+
+// When listens are submitted (see Scrobbler)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ScrobbleSettings {
+    // How much of a track has to be played, in percent
+    pub threshold: u32,
+    // Or how many minutes, for long tracks (0 if there's no such limit)
+    pub max_minutes: u32,
+    // Playlists never scrobbled from
+    pub ignored_playlists: HashSet<String>,
+    // Whether podcast episodes are never scrobbled
+    pub ignore_podcasts: bool,
+}
+
+impl Default for ScrobbleSettings {
+    fn default() -> Self {
+        // Same as Last.fm
+        Self {
+            threshold: 50,
+            max_minutes: 4,
+            ignored_playlists: HashSet::new(),
+            ignore_podcasts: true,
+        }
+    }
+}
+
+impl ScrobbleSettings {
+    pub fn new_from_gsettings() -> Self {
+        let settings = gio::Settings::new(SETTINGS);
+        Self {
+            threshold: settings.uint("scrobble-threshold").clamp(1, 100),
+            max_minutes: settings.uint("scrobble-max-minutes"),
+            ignored_playlists: settings
+                .value("scrobble-ignored-playlists")
+                .get::<Vec<String>>()
+                .unwrap_or_default()
+                .into_iter()
+                .collect(),
+            ignore_podcasts: settings.boolean("scrobble-ignore-podcasts"),
+        }
+    }
+
+    pub fn save_ignored_playlists(&self) -> Option<()> {
+        let settings = gio::Settings::new(SETTINGS);
+        let ignored: Vec<String> = self.ignored_playlists.iter().cloned().collect();
+        settings
+            .set_value("scrobble-ignored-playlists", &ignored.to_variant())
+            .ok()
+    }
+}
+
+// Labels assigned to playlists, only used in the sidebar
+#[derive(Clone, Debug, Default)]
+pub struct PlaylistLabels {
+    pub labels: HashMap<String, PlaylistLabel>,
+    pub sort_by_label: bool,
+}
+
+impl PlaylistLabels {
+    pub fn new_from_gsettings() -> Self {
+        let settings = gio::Settings::new(SETTINGS);
+        let labels = settings
+            .value("playlist-labels")
+            .get::<HashMap<String, String>>()
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|(id, label)| Some((id, PlaylistLabel::parse(&label)?)))
+            .collect();
+        Self {
+            labels,
+            sort_by_label: settings.boolean("sort-playlists-by-label"),
+        }
+    }
+
+    pub fn save(&self) -> Option<()> {
+        let settings = gio::Settings::new(SETTINGS);
+        let labels: HashMap<String, String> = self
+            .labels
+            .iter()
+            .map(|(id, label)| (id.clone(), label.serialize()))
+            .collect();
+        settings.delay();
+        settings
+            .set_value("playlist-labels", &labels.to_variant())
+            .ok()?;
+        settings
+            .set_boolean("sort-playlists-by-label", self.sort_by_label)
+            .ok()?;
+        settings.apply();
+        Some(())
+    }
+}
+
+impl LibraryOptions {
+    pub fn new_from_gsettings() -> Self {
+        let settings = gio::Settings::new(SETTINGS);
+        let sort = match settings.enum_("library-sort") {
+            1 => LibrarySort::Artist,
+            2 => LibrarySort::Title,
+            3 => LibrarySort::ReleaseYear,
+            _ => LibrarySort::RecentlyAdded,
+        };
+        Self {
+            sort,
+            filter: String::new(),
+            compact: settings.boolean("library-compact"),
+        }
+    }
+
+    pub fn save(&self) -> Option<()> {
+        let settings = gio::Settings::new(SETTINGS);
+        let sort = match self.sort {
+            LibrarySort::RecentlyAdded => 0,
+            LibrarySort::Artist => 1,
+            LibrarySort::Title => 2,
+            LibrarySort::ReleaseYear => 3,
+        };
+        settings.delay();
+        settings.set_enum("library-sort", sort).ok()?;
+        settings.set_boolean("library-compact", self.compact).ok()?;
+        settings.apply();
+        Some(())
+    }
+}
+
+// Playlists deleted from Spot that can still be recovered, most recent first
+#[derive(Clone, Debug, Default)]
+pub struct DeletedPlaylists {
+    pub playlists: Vec<DeletedPlaylist>,
+}
+
+impl DeletedPlaylists {
+    pub fn new_from_gsettings() -> Self {
+        let settings = gio::Settings::new(SETTINGS);
+        let now = SystemTime::now();
+        let playlists = settings
+            .value("deleted-playlists")
+            .get::<Vec<(String, String, u64)>>()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(id, title, deleted_at)| DeletedPlaylist {
+                id,
+                title,
+                deleted_at: UNIX_EPOCH + Duration::from_secs(deleted_at),
+            })
+            .filter(|playlist| playlist.is_recoverable(now))
+            .collect();
+        Self { playlists }
+    }
+
+    pub fn save(&self) -> Option<()> {
+        let settings = gio::Settings::new(SETTINGS);
+        let playlists: Vec<(String, String, u64)> = self
+            .playlists
+            .iter()
+            .map(|playlist| {
+                let deleted_at = playlist
+                    .deleted_at
+                    .duration_since(UNIX_EPOCH)
+                    .map(|d| d.as_secs())
+                    .unwrap_or_default();
+                (playlist.id.clone(), playlist.title.clone(), deleted_at)
+            })
+            .collect();
+        settings
+            .set_value("deleted-playlists", &playlists.to_variant())
+            .ok()
+    }
+
+    pub fn add(&mut self, playlist: DeletedPlaylist) {
+        self.remove(&playlist.id);
+        self.playlists.insert(0, playlist);
+    }
+
+    pub fn remove(&mut self, id: &str) {
+        self.playlists.retain(|playlist| playlist.id != id);
+    }
+}
+
+// Release alerts (see ReleaseWatcher)
+impl ReleaseAlerts {
+    pub fn new_from_gsettings() -> Self {
+        let settings = gio::Settings::new(SETTINGS);
+        Self {
+            latest: settings
+                .value("release-alerts")
+                .get::<HashMap<String, String>>()
+                .unwrap_or_default(),
+            unseen: settings
+                .value("release-alerts-unseen")
+                .get::<Vec<String>>()
+                .unwrap_or_default()
+                .into_iter()
+                .collect(),
+            last_checked: settings.uint64("release-alerts-checked"),
+        }
+    }
+
+    pub fn save(&self) -> Option<()> {
+        let settings = gio::Settings::new(SETTINGS);
+        let unseen: Vec<String> = self.unseen.iter().cloned().collect();
+        settings.delay();
+        settings
+            .set_value("release-alerts", &self.latest.to_variant())
+            .ok()?;
+        settings
+            .set_value("release-alerts-unseen", &unseen.to_variant())
+            .ok()?;
+        settings
+            .set_uint64("release-alerts-checked", self.last_checked)
+            .ok()?;
+        settings.apply();
+        Some(())
+    }
+}
+
+// Where the user was at in the release feed when they last looked at it (see NewReleases)
+#[derive(Clone, Debug, Default)]
+pub struct NewReleasesLastSeen {
+    // Release date of the latest release they saw, empty if they never did
+    pub date: String,
+}
+
+impl NewReleasesLastSeen {
+    pub fn new_from_gsettings() -> Self {
+        let settings = gio::Settings::new(SETTINGS);
+        Self {
+            date: settings.string("new-releases-last-seen").to_string(),
+        }
+    }
+
+    pub fn save(&self) -> Option<()> {
+        let settings = gio::Settings::new(SETTINGS);
+        settings
+            .set_string("new-releases-last-seen", &self.date)
+            .ok()
+    }
+}
+
+// A named set of overrides for the player settings, so that several machines sharing the same
+// home directory (say, a laptop and an HTPC) can each use their own setup
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PlayerProfile {
+    pub device_name: Option<String>,
+    pub bitrate: Option<Bitrate>,
+    pub cache_dir: Option<PathBuf>,
+    pub backend: Option<AudioBackend>,
+}
+
+impl PlayerProfile {
+    fn parse(values: &HashMap<String, String>) -> Self {
+        let get = |key: &str| values.get(key).filter(|value| !value.is_empty());
+        let bitrate = get("bitrate").and_then(|bitrate| match bitrate.as_str() {
+            "96" => Some(Bitrate::Bitrate96),
+            "160" => Some(Bitrate::Bitrate160),
+            "320" => Some(Bitrate::Bitrate320),
+            _ => None,
+        });
+        let backend = get("audio-backend").and_then(|backend| match backend.as_str() {
+            "pulseaudio" => Some(AudioBackend::PulseAudio),
+            "alsa" => Some(AudioBackend::Alsa(
+                get("alsa-device")
+                    .cloned()
+                    .unwrap_or_else(|| "default".to_string()),
+            )),
+            "gstreamer" => Some(AudioBackend::GStreamer(
+                "audioconvert dithering=none ! audioresample ! pipewiresink".to_string(),
+            )),
+            "pipewire" => Some(AudioBackend::PipeWire),
+            _ => None,
+        });
+        Self {
+            device_name: get("device-name").cloned(),
+            bitrate,
+            cache_dir: get("cache-dir").map(PathBuf::from),
+            backend,
+        }
+    }
+
+    // The name passed with --profile (or -p) on the command line, if any
+    fn name_from_args(args: impl Iterator<Item = String>) -> Option<String> {
+        let mut args = args.skip(1);
+        while let Some(arg) = args.next() {
+            if let Some(name) = arg.strip_prefix("--profile=") {
+                return Some(name.to_string());
+            }
+            if arg == "--profile" || arg == "-p" {
+                return args.next();
+            }
+        }
+        None
+    }
+
+    pub fn new_from_gsettings() -> Option<Self> {
+        let settings = gio::Settings::new(SETTINGS);
+        let name = Self::name_from_args(std::env::args())
+            .unwrap_or_else(|| settings.string("player-profile").as_str().to_string());
+        if name.is_empty() {
+            return None;
+        }
+        let profile = settings
+            .value("player-profiles")
+            .get::<HashMap<String, HashMap<String, String>>>()
+            .unwrap_or_default()
+            .remove(&name)
+            .map(|values| Self::parse(&values));
+        if profile.is_none() {
+            warn!("Unknown player profile: {}", name);
+        }
+        profile
+    }
+
+    fn apply(self, player_settings: &mut SpotifyPlayerSettings) {
+        if let Some(bitrate) = self.bitrate {
+            player_settings.bitrate = bitrate;
+        }
+        if let Some(backend) = self.backend {
+            player_settings.backend = backend;
+        }
+        player_settings.device_name = self.device_name;
+        player_settings.cache_dir = self.cache_dir;
+    }
+}
+
 // Player (librespot) settings
 impl SpotifyPlayerSettings {
     pub fn new_from_gsettings() -> Option<Self> {
@@ -53,6 +461,12 @@ impl SpotifyPlayerSettings {
             2 => Some(AudioBackend::GStreamer(
                 "audioconvert dithering=none ! audioresample ! pipewiresink".to_string(), // This should be configurable eventually
             )),
+            3 => Some(AudioBackend::PipeWire),
+            _ => None,
+        }?;
+        let mixer = match settings.enum_("audio-mixer") {
+            0 => Some(AudioMixer::Software),
+            1 => Some(AudioMixer::Hardware),
             _ => None,
         }?;
         let gapless = settings.boolean("gapless-playback");
@@ -70,20 +484,68 @@ impl SpotifyPlayerSettings {
             x => Some(x as u16),
         };
 
-        Some(Self {
+        let mut player_settings = Self {
             bitrate,
             backend,
+            mixer,
             gapless,
             ap_port,
-        })
+            device_name: None,
+            cache_dir: None,
+        };
+        if let Some(profile) = PlayerProfile::new_from_gsettings() {
+            profile.apply(&mut player_settings);
+        }
+        Some(player_settings)
+    }
+}
+
+// What closing the main window does while music is playing
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CloseBehavior {
+    Ask = 0,
+    Stop = 1,
+    Background = 2,
+}
+
+impl CloseBehavior {
+    // Used when the user makes their choice as they close the window
+    pub fn save(self) -> Option<()> {
+        let settings = gio::Settings::new(SETTINGS);
+        settings.set_enum("close-behavior", self as i32).ok()
     }
 }
 
 #[derive(Debug, Clone)]
 pub struct SpotSettings {
     pub theme_preference: ColorScheme,
+    pub use_artwork_colors: bool,
+    pub high_contrast_indicators: bool,
     pub player_settings: SpotifyPlayerSettings,
     pub window: WindowGeometry,
+    pub volume: PlaybackVolume,
+    pub night_mode: NightModeSettings,
+    pub silence_trimming: SilenceTrimSettings,
+    pub track_list_template: String,
+    // In seconds, 0 if disabled
+    pub playlist_refresh_interval: u32,
+    // In milliseconds, 0 if disabled
+    pub track_change_delay: u32,
+    // In milliseconds, 0 if Previous should always go back to the previous track
+    pub previous_restart_threshold: u32,
+    pub autoplay: bool,
+    pub pause_during_calls: bool,
+    pub playback_metrics: bool,
+    pub shuffle_mode: ShuffleMode,
+    // By kind of source (see SongsSource::kind)
+    pub shuffle_mode_by_source: HashMap<String, ShuffleMode>,
+    pub preview_on_hover: bool,
+    pub notify_track_changes: bool,
+    pub close_behavior: CloseBehavior,
+    pub start_minimized: bool,
+    // Empty if listens shouldn't be submitted
+    pub listenbrainz_token: String,
+    pub scrobble: ScrobbleSettings,
 }
 
 // Application settings
@@ -96,10 +558,49 @@ impl SpotSettings {
             2 => Some(ColorScheme::Default),
             _ => None,
         }?;
+        let shuffle_mode = match settings.enum_("shuffle-mode") {
+            0 => Some(ShuffleMode::Random),
+            1 => Some(ShuffleMode::ArtistSpread),
+            2 => Some(ShuffleMode::LeastRecent),
+            _ => None,
+        }?;
+        let shuffle_mode_by_source = settings
+            .value("shuffle-mode-by-source")
+            .get::<HashMap<String, String>>()
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|(source, mode)| Some((source, ShuffleMode::from_nick(&mode)?)))
+            .collect();
+        let close_behavior = match settings.enum_("close-behavior") {
+            0 => Some(CloseBehavior::Ask),
+            1 => Some(CloseBehavior::Stop),
+            2 => Some(CloseBehavior::Background),
+            _ => None,
+        }?;
         Some(Self {
             theme_preference,
+            use_artwork_colors: settings.boolean("use-artwork-colors"),
+            high_contrast_indicators: settings.boolean("high-contrast-indicators"),
             player_settings: SpotifyPlayerSettings::new_from_gsettings()?,
             window: WindowGeometry::new_from_gsettings(),
+            volume: PlaybackVolume::new_from_gsettings(),
+            night_mode: NightModeSettings::new_from_gsettings(),
+            silence_trimming: SilenceTrimSettings::new_from_gsettings(),
+            track_list_template: settings.string("track-list-template").as_str().to_string(),
+            playlist_refresh_interval: settings.uint("playlist-refresh-interval"),
+            track_change_delay: settings.uint("track-change-delay"),
+            previous_restart_threshold: settings.uint("previous-restart-threshold"),
+            autoplay: settings.boolean("autoplay"),
+            pause_during_calls: settings.boolean("pause-during-calls"),
+            playback_metrics: settings.boolean("playback-metrics"),
+            shuffle_mode,
+            shuffle_mode_by_source,
+            preview_on_hover: settings.boolean("preview-on-hover"),
+            notify_track_changes: settings.boolean("notify-track-changes"),
+            close_behavior,
+            start_minimized: settings.boolean("start-minimized"),
+            listenbrainz_token: settings.string("listenbrainz-token").as_str().to_string(),
+            scrobble: ScrobbleSettings::new_from_gsettings(),
         })
     }
 }
@@ -108,8 +609,72 @@ impl Default for SpotSettings {
     fn default() -> Self {
         Self {
             theme_preference: ColorScheme::PreferDark,
+            use_artwork_colors: true,
+            high_contrast_indicators: false,
             player_settings: Default::default(),
             window: Default::default(),
+            volume: Default::default(),
+            night_mode: Default::default(),
+            silence_trimming: Default::default(),
+            track_list_template: "{index}. {artist} – {title} ({duration})".to_string(),
+            playlist_refresh_interval: 60,
+            track_change_delay: 500,
+            previous_restart_threshold: 2000,
+            autoplay: false,
+            pause_during_calls: false,
+            playback_metrics: false,
+            shuffle_mode: ShuffleMode::Random,
+            shuffle_mode_by_source: HashMap::new(),
+            preview_on_hover: false,
+            notify_track_changes: true,
+            close_behavior: CloseBehavior::Ask,
+            start_minimized: false,
+            listenbrainz_token: String::new(),
+            scrobble: Default::default(),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_profile() {
+        let values: HashMap<String, String> = [
+            ("device-name", "HTPC"),
+            ("bitrate", "320"),
+            ("audio-backend", "alsa"),
+            ("alsa-device", "hw:1"),
+            ("cache-dir", ""),
+        ]
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+
+        let profile = PlayerProfile::parse(&values);
+        assert_eq!(
+            profile,
+            PlayerProfile {
+                device_name: Some("HTPC".to_string()),
+                bitrate: Some(Bitrate::Bitrate320),
+                cache_dir: None,
+                backend: Some(AudioBackend::Alsa("hw:1".to_string())),
+            }
+        );
+    }
+
+    #[test]
+    fn test_profile_name_from_args() {
+        let args =
+            |args: &[&str]| PlayerProfile::name_from_args(args.iter().map(|arg| arg.to_string()));
+        assert_eq!(args(&["spot"]), None);
+        assert_eq!(
+            args(&["spot", "--profile", "htpc"]),
+            Some("htpc".to_string())
+        );
+        assert_eq!(args(&["spot", "--profile=htpc"]), Some("htpc".to_string()));
+        assert_eq!(args(&["spot", "-p", "laptop"]), Some("laptop".to_string()));
+        assert_eq!(args(&["spot", "-p"]), None);
+    }
+}